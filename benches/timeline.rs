@@ -0,0 +1,110 @@
+//! Benchmarks of the note store as it grows: inserting notes into a tab, attaching reactions to
+//! them and drawing the timeline.
+//!
+//! ```sh
+//! cargo bench --bench timeline
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use nostr_sdk::prelude::*;
+use nostui::{
+    action::Action,
+    components::{Component, Home},
+    config::Config,
+    timeline::{TimelineTab, TimelineTabType},
+};
+use ratatui::{backend::TestBackend, Terminal};
+
+const NOTES: usize = 10_000;
+
+/// Text notes by a few authors, one second apart.
+fn notes(len: usize) -> Vec<Event> {
+    let authors: Vec<Keys> = (0..10).map(|_| Keys::generate()).collect();
+    (0..len)
+        .map(|i| {
+            EventBuilder::text_note(format!("gm #{i}"), [])
+                .custom_created_at(Timestamp::from(1_700_000_000 + i as u64))
+                .to_event(&authors[i % authors.len()])
+                .unwrap()
+        })
+        .collect()
+}
+
+/// A like to each note.
+fn reactions(notes: &[Event]) -> Vec<Event> {
+    let keys = Keys::generate();
+    notes
+        .iter()
+        .map(|note| EventBuilder::reaction(note, "+").to_event(&keys).unwrap())
+        .collect()
+}
+
+/// The home timeline showing the notes.
+fn home(notes: &[Event]) -> Home<'static> {
+    let mut config = Config::default();
+    config.config._data_dir = std::env::temp_dir().join("nostui-bench");
+    config.startup_tabs = vec![TimelineTabType::Home];
+
+    let mut home = Home::new();
+    home.register_config_handler(config).unwrap();
+    for note in notes {
+        home.update(Action::ReceiveTabEvent(TimelineTabType::Home, note.clone()))
+            .unwrap();
+    }
+    home
+}
+
+fn insert(c: &mut Criterion) {
+    let notes = notes(NOTES);
+    c.bench_function("insert 10k notes", |b| {
+        b.iter_batched(
+            || notes.clone(),
+            |notes| {
+                let mut tab = TimelineTab::new(TimelineTabType::Home);
+                tab.extend(notes);
+                black_box(tab)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn engagement(c: &mut Criterion) {
+    let notes = notes(NOTES);
+    let reactions = reactions(&notes);
+    c.bench_function("attach 10k reactions", |b| {
+        b.iter_batched(
+            || (home(&notes), reactions.clone()),
+            |(mut home, reactions)| {
+                for reaction in reactions {
+                    home.update(Action::ReceiveEvent(reaction)).unwrap();
+                }
+                black_box(home.engagement(&notes[0].id))
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn render(c: &mut Criterion) {
+    let notes = notes(NOTES);
+    let mut home = home(&notes);
+    for reaction in reactions(&notes) {
+        home.update(Action::ReceiveEvent(reaction)).unwrap();
+    }
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    c.bench_function("render a timeline of 10k notes", |b| {
+        b.iter(|| {
+            terminal.draw(|f| home.draw(f, f.size()).unwrap()).unwrap();
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = insert, engagement, render
+}
+criterion_main!(benches);
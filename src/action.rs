@@ -3,6 +3,23 @@ use nostr_sdk::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
+use crate::{
+    activity::Activity,
+    components::{MenuItem, PromptKind, UiLayout},
+    discover::Suggestion,
+    followers::FollowerList,
+    interaction_limit::CollapsedAuthor,
+    link_preview::LinkPreview,
+    mode::Mode,
+    nostr::{
+        nip11::RelayInfo, nip57::Zap, subscriptions::SubscriptionInfo, trace::TraceEntry, Profile,
+    },
+    stats::{NoteStoreStats, QueueDepth, RelayKinds, TrafficStats},
+    timeline::TimelineTabType,
+    translate::Translation,
+    viewport::Placement,
+};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
     Tick,
@@ -15,20 +32,180 @@ pub enum Action {
     Error(String),
     Help,
     ReceiveEvent(Event),
+    ReceiveTabEvent(TimelineTabType, Event),
+    ReceiveFetchedEvent(Event),
+    /// Profile parsed off the update loop, delivered before its metadata event
+    ReceiveProfile(Profile),
+    /// Zap decoded from the receipt with the id, delivered before the receipt
+    ReceiveZap(EventId, Zap),
+    FetchEvent(EventId),
+    /// Fetches the zap goal with the zaps to it
+    FetchZapGoal(EventId),
+    FetchProfile(PublicKey),
+    FetchContactLists(Vec<PublicKey>),
     ScrollUp,
     ScrollDown,
     ScrollToTop,
     ScrollToBottom,
     React,
     SendReaction(Event),
+    Unreact,
+    DeleteReaction(Event),
+    DeleteNote(Event),
+    /// Publishes the deletion of my repost
+    DeleteRepost(Event),
     Repost,
     SendRepost(Event),
     Unselect,
     NewTextNote,
     ReplyTextNote,
     SubmitTextNote,
+    SubmitLongTextNote,
+    EditRecipients,
     SendTextNote(String, Vec<Tag>),
+    SendChannelMessage(String, Vec<Tag>),
+    ListChannels,
+    FetchChannels(Vec<EventId>),
     Key(KeyEvent),
     MetadataUpdated(Box<Metadata>),
     SystemMessage(String),
+    SystemWarning(String),
+    DismissMessage,
+    ToggleMessageHistory,
+    Confirm(String, Vec<Action>),
+    /// Saves the config files which were upgraded from older versions on load
+    SaveMigratedConfig,
+    EnterMode(Mode),
+    LeaveMode,
+    ReviewCollapsedAuthors,
+    OpenCollapsedAuthors(Vec<CollapsedAuthor>),
+    WhitelistAuthor(PublicKey),
+    MuteAuthor(PublicKey),
+    /// Offers to mute the author of the selected note for an hour, the session or permanently
+    MuteSelectedAuthor,
+    /// Mutes the author for the seconds, or until the app quits, without saving or publishing it
+    MuteAuthorFor(PublicKey, Option<u64>),
+    OpenAuthorTimeline,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    MoveTabLeft,
+    MoveTabRight,
+    SelectTab(usize),
+    /// Lists the open tabs to search and switch to one of them
+    ListTabs,
+    /// Opens the list of the tabs with their titles and the index of the active one
+    OpenTabList(Vec<String>, usize),
+    CheckTheme,
+    RunDiagnostics,
+    ShowReport(String, Vec<String>),
+    OpenPrompt(PromptKind, String, String),
+    SubmitPrompt(PromptKind, String),
+    ExportTimeline,
+    ExportThread,
+    OpenTab(TimelineTabType),
+    OpenFromClipboard,
+    CopyToClipboard(String),
+    ShowActionMenu,
+    OpenActionMenu(Vec<MenuItem>),
+    SubscribeTimeline(TimelineTabType),
+    UnsubscribeTimeline(TimelineTabType),
+    LoadOlderNotes(TimelineTabType, Timestamp, usize),
+    LoadNotesBetween(TimelineTabType, Timestamp, Timestamp, usize),
+    JumpToDate,
+    /// Goes to the marker of the notes received since the app quit last time
+    JumpToLastVisit,
+    FillGap,
+    AttachMedia,
+    SetExpiration,
+    EditInEditor,
+    LinkBareIds,
+    Paste(String),
+    RunEditor(String),
+    LoadDraft(String),
+    UploadMedia(String),
+    MediaUploaded(String, Tag),
+    ToggleTrace,
+    ReceiveTrace(TraceEntry),
+    ViewOutbox,
+    /// Lists my recent reactions, reposts and replies to undo them
+    ViewMyActivity,
+    OpenMyActivity(Vec<Activity>),
+    /// Updates the listed activity once older activity is fetched
+    RefreshMyActivity(Vec<Activity>),
+    /// Fetches my older activity from relays
+    FetchMyActivity,
+    /// Counts and lists the followers of the author of the selected note
+    ShowFollowers,
+    OpenFollowers(FollowerList),
+    /// Updates the listed followers as their contact lists and profiles arrive
+    RefreshFollowers(FollowerList),
+    /// Fetches the contact lists which follow the account
+    FetchFollowers(PublicKey),
+    ViewRelays,
+    ReceiveRelayInfo(String, RelayInfo),
+    ViewSubscriptions,
+    ReceiveSubscriptions(Vec<SubscriptionInfo>),
+    CloseSubscription(SubscriptionId),
+    ViewKindStats,
+    ReceiveKindStats(Vec<RelayKinds>),
+    EventPublished(Event, Vec<String>),
+    ReceiveReceipt(EventId, String, bool, String),
+    RetryPublish(Event, Vec<String>, Option<u8>),
+    PublishTo(Event, Vec<String>),
+    /// Asks to send the selected note as it is to my write relays, e.g. ones which missed it
+    Rebroadcast,
+    BroadcastEvent(Event),
+    DeliverTo(Event, Vec<String>),
+    AnnotateEvent(EventId, Vec<String>),
+    RunPluginCommand(String),
+    InvokePluginCommand(String, Option<Event>),
+    ReloadPlugins,
+    ToggleDoNotDisturb,
+    RevealContent,
+    ToggleFilteredNotes,
+    ShowEngagement,
+    Translate,
+    TranslateNote(EventId, String),
+    ReceiveTranslation(EventId, Result<Translation, String>),
+    /// Shows or hides the cards of the links in the selected note
+    PreviewLinks,
+    /// Opens the URL in the browser or the default application
+    OpenUrl(String),
+    FetchLinkPreviews(Vec<String>),
+    ReceiveLinkPreview(String, Result<LinkPreview, String>),
+    ToggleTimestamps,
+    ToggleFollowMode,
+    ToggleExpand,
+    ToggleSplitPane,
+    ToggleStatusBar,
+    ToggleTabBar,
+    ToggleZenMode,
+    GrowComposer,
+    ShrinkComposer,
+    LayoutChanged(UiLayout),
+    ScrollHalfPageDown,
+    ScrollHalfPageUp,
+    ScrollViewDown,
+    ScrollViewUp,
+    PlaceSelection(Placement),
+    EditProfile,
+    SendMetadata(Box<Metadata>),
+    Discover,
+    OpenSuggestions(Vec<Suggestion>),
+    Follow(PublicKey),
+    Unfollow(PublicKey),
+    SendContactList(Vec<Tag>, String),
+    ToggleStats,
+    ToggleLogs,
+    ReceiveStats(TrafficStats, QueueDepth),
+    UnreadChanged(usize),
+    FocusGained,
+    FocusLost,
+    /// Notes which arrived in the Home tab while the terminal was unfocused
+    AwayUnreadChanged(usize),
+    /// Ticks resume after a pause in which the subscriptions were kept open, so no notes are missing
+    ResumeTicks,
+    MeasureNoteStore,
+    NoteStoreMeasured(NoteStoreStats),
 }
@@ -0,0 +1,97 @@
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::nostr::nip10::ThreadTags;
+
+/// Number of my latest reactions, reposts and replies listed, and fetched from relays.
+pub const LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivityKind {
+    Reaction,
+    Repost,
+    Reply,
+}
+
+impl ActivityKind {
+    /// Classifies the event, which is not an activity if it is neither of the kinds.
+    pub fn of(event: &Event) -> Option<Self> {
+        match event.kind {
+            Kind::Reaction => Some(Self::Reaction),
+            Kind::Repost => Some(Self::Repost),
+            Kind::TextNote if ThreadTags::parse(event).parent().is_some() => Some(Self::Reply),
+            _ => None,
+        }
+    }
+}
+
+/// My reaction, repost or reply, as listed on the activity view to be undone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Activity {
+    pub event: Event,
+    pub kind: ActivityKind,
+    /// The note reacted to, reposted or replied to, e.g. `alice: gm`
+    pub target: String,
+}
+
+/// Returns the latest activities of the author among the events, the newest first. Events
+/// given twice, e.g. from several stores, are listed once.
+pub fn recent<'a>(
+    author: &PublicKey,
+    events: impl IntoIterator<Item = &'a Event>,
+    limit: usize,
+) -> Vec<(&'a Event, ActivityKind)> {
+    let mut activities: Vec<(&Event, ActivityKind)> = events
+        .into_iter()
+        .filter(|event| event.pubkey == *author)
+        .filter_map(|event| Some((event, ActivityKind::of(event)?)))
+        .collect();
+    activities.sort_by(|(a, _), (b, _)| b.created_at.cmp(&a.created_at).then(a.id.cmp(&b.id)));
+    activities.dedup_by_key(|(event, _)| event.id);
+    activities.truncate(limit);
+    activities
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn event(keys: &Keys, builder: EventBuilder, created_at: u64) -> Event {
+        builder
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_recent() {
+        let (me, alice) = (Keys::generate(), Keys::generate());
+        let note = event(&alice, EventBuilder::text_note("gm", []), 1);
+        let reaction = event(&me, EventBuilder::reaction(&note, "+"), 2);
+        let repost = event(&me, EventBuilder::repost(&note, None), 3);
+        let reply = event(
+            &me,
+            EventBuilder::text_note("gm!", [Tag::event(note.id)]),
+            4,
+        );
+        let mine = event(&me, EventBuilder::text_note("gn", []), 5);
+        let others = event(&alice, EventBuilder::reaction(&reply, "+"), 6);
+        let events = [&note, &reaction, &repost, &reply, &mine, &others, &reaction];
+
+        assert_eq!(
+            recent(&me.public_key(), events, LIMIT),
+            vec![
+                (&reply, ActivityKind::Reply),
+                (&repost, ActivityKind::Repost),
+                (&reaction, ActivityKind::Reaction),
+            ]
+        );
+        assert_eq!(
+            recent(&me.public_key(), events, 1),
+            vec![(&reply, ActivityKind::Reply)]
+        );
+    }
+}
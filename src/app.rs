@@ -1,3 +1,8 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
 use color_eyre::eyre::Result;
 use crossterm::event::KeyEvent;
 use nostr_sdk::prelude::*;
@@ -6,54 +11,232 @@ use tokio::sync::mpsc;
 
 use crate::{
     action::Action,
-    components::{Component, FpsCounter, Home, StatusBar},
-    config::Config,
+    activity, browser,
+    cli::Cli,
+    clipboard,
+    components::{
+        ActionMenu, ActivityView, AuthorReview, Component, ConfirmDialog, DiscoverView,
+        FollowersView, FpsCounter, Home, KindStatsView, LogViewer, OutboxView, ProfileEditor,
+        Prompt, RelayView, ReportPopup, StatsOverlay, StatusBar, SubscriptionView, TabList,
+        TraceOverlay, UiLayout,
+    },
+    config::{
+        contrast::{self, ContrastIssue},
+        palette, Config,
+    },
+    discover::CONTACT_LIST_BATCH_SIZE,
+    doctor, editor, followers, i18n,
+    keys::TerminalPrompt,
+    link_preview, media,
     mode::Mode,
-    nostr::Connection,
-    nostr::ConnectionProcess,
-    tui,
+    nostr::{
+        ingestion::{self, Ingested},
+        nip11,
+        nip21::NostrTarget,
+        nip65::Inboxes,
+        proxy,
+        trace::Tracer,
+        Connection, ConnectionProcess, NostrCommand, Notification,
+    },
+    notify::Notifier,
+    plugin::Plugins,
+    power::PowerState,
+    stats::QueueDepth,
+    throttle::Throttle,
+    timeline::TimelineTabType,
+    translate, tui,
 };
 
+#[cfg(feature = "simulation")]
+use crate::nostr::simulation::{Script, Simulation};
+
+/// Receiver of notifications, sender of commands and sender of the termination of the relay
+/// connection or the simulation replacing it.
+type ConnectionChannels = (
+    mpsc::UnboundedReceiver<Notification>,
+    mpsc::UnboundedSender<NostrCommand>,
+    mpsc::UnboundedSender<()>,
+);
+
+/// Time to type the next key of a multi-key binding, e.g. `zz`.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time for the connection to close the subscriptions of tabs before the app is suspended.
+const SUSPEND_GRACE: Duration = Duration::from_millis(200);
+
 pub struct App {
     pub config: Config,
     pub tick_rate: f64,
     pub frame_rate: f64,
+    pub trace: bool,
     pub components: Vec<Box<dyn Component>>,
     pub should_quit: bool,
     pub should_suspend: bool,
     pub mode: Mode,
-    pub last_tick_key_events: Vec<KeyEvent>,
+    pub previous_modes: Vec<Mode>,
+    /// Keys typed so far for a multi-key binding
+    pub pending_key_events: Vec<KeyEvent>,
+    pub last_key_at: Instant,
+    /// Regions of the screen which components are drawn in
+    pub layout: UiLayout,
+    /// Pairs of colors drawn so far which failed the contrast check
+    pub contrast_issues: Vec<ContrastIssue>,
+    /// Script replayed instead of connecting to relays
+    #[cfg(feature = "simulation")]
+    pub simulation: Option<Simulation>,
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
+    pub fn new(args: &Cli) -> Result<Self> {
+        let mut config = Config::new()?;
+        config.override_relays(&args.relays);
+        config.startup_tabs.clone_from(&args.tabs);
+        config.startup_uri.clone_from(&args.uri);
+        config.key_stdin = args.key_stdin;
+        config.workspace.clone_from(&args.workspace);
+        if let Some(locale) = config.locale {
+            i18n::set_locale(locale);
+        }
+        // Asked before the TUI starts
+        config.unlock(&mut TerminalPrompt)?;
+        Self::with_config(config, args)
+    }
+
+    /// Builds the app around a config which is ready to use, i.e. with the overrides of the
+    /// arguments applied and the key unlocked.
+    pub fn with_config(config: Config, args: &Cli) -> Result<Self> {
         let home = Home::new();
         let fps = FpsCounter::default();
-        let config = Config::new()?;
-        let pubkey = Keys::parse(config.privatekey.as_str())?.public_key();
+        let stats_overlay = StatsOverlay::new();
+        let pubkey = config.keys().map(|keys| keys.public_key());
         let status_bar = StatusBar::new(pubkey, None, None, true);
+        let author_review = AuthorReview::new();
+        let activity_view = ActivityView::new();
+        let followers_view = FollowersView::new();
+        let report_popup = ReportPopup::new();
+        let prompt = Prompt::new();
+        let trace_overlay = TraceOverlay::new(args.trace);
+        let log_viewer = LogViewer::new();
+        let outbox_view = OutboxView::new();
+        let relay_view = RelayView::new();
+        let subscription_view = SubscriptionView::new();
+        let kind_stats_view = KindStatsView::new();
+        let profile_editor = ProfileEditor::new(pubkey);
+        let discover_view = DiscoverView::new();
+        let confirm_dialog = ConfirmDialog::new();
+        let action_menu = ActionMenu::new();
+        let tab_list = TabList::new();
         let mode = Mode::Home;
+        let layout = UiLayout::new(&config.layout);
         Ok(Self {
-            tick_rate,
-            frame_rate,
-            components: vec![Box::new(home), Box::new(fps), Box::new(status_bar)],
+            tick_rate: args.tick_rate,
+            frame_rate: args.frame_rate,
+            trace: args.trace,
+            components: vec![
+                Box::new(home),
+                Box::new(fps),
+                Box::new(stats_overlay),
+                Box::new(status_bar),
+                Box::new(author_review),
+                Box::new(activity_view),
+                Box::new(followers_view),
+                Box::new(report_popup),
+                Box::new(prompt),
+                Box::new(trace_overlay),
+                Box::new(log_viewer),
+                Box::new(outbox_view),
+                Box::new(relay_view),
+                Box::new(subscription_view),
+                Box::new(kind_stats_view),
+                Box::new(profile_editor),
+                Box::new(discover_view),
+                Box::new(action_menu),
+                Box::new(tab_list),
+                Box::new(confirm_dialog),
+            ],
             should_quit: false,
             should_suspend: false,
             config,
             mode,
-            last_tick_key_events: Vec::new(),
+            previous_modes: Vec::new(),
+            pending_key_events: Vec::new(),
+            last_key_at: Instant::now(),
+            layout,
+            contrast_issues: Vec::new(),
+            #[cfg(feature = "simulation")]
+            simulation: args
+                .simulate
+                .as_deref()
+                .map(Script::load)
+                .transpose()?
+                .map(Simulation::new),
         })
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        let (action_tx, mut action_rx) = mpsc::unbounded_channel();
+    /// Draws the components into the areas of their regions, skipping the hidden ones.
+    fn render(
+        &mut self,
+        tui: &mut tui::Tui,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<()> {
+        tui.draw(|f| self.draw(f, action_tx))?;
 
-        let mut tui = tui::Tui::new()?
-            .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
-        // tui.mouse(true);
-        tui.enter()?;
+        Ok(())
+    }
+
+    pub(crate) fn draw(
+        &mut self,
+        f: &mut tui::Frame<'_>,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) {
+        for component in self.components.iter_mut() {
+            let Some(area) = self.layout.area(component.region(), f.size()) else {
+                continue;
+            };
+            if let Err(e) = component.draw(f, area) {
+                action_tx
+                    .send(Action::Error(i18n::tr_args(
+                        "Failed to draw: {error}",
+                        &[("error", &format!("{e:?}"))],
+                    )))
+                    .unwrap();
+            }
+        }
+        palette::apply(f.buffer_mut(), self.config.theme.color_mode);
+
+        let new_issues: Vec<ContrastIssue> = contrast::enforce(
+            f.buffer_mut(),
+            self.config.theme.min_contrast_ratio,
+            self.config.theme.contrast_policy,
+        )
+        .into_iter()
+        .filter(|issue| {
+            !self
+                .contrast_issues
+                .iter()
+                .any(|known| (known.fg, known.bg) == (issue.fg, issue.bg))
+        })
+        .collect();
+        if !new_issues.is_empty() {
+            for issue in &new_issues {
+                tracing::warn!(colors = %issue.describe(), "Low contrast colors");
+            }
+            action_tx
+                .send(Action::SystemWarning(i18n::plural(
+                    "{count, plural, one {# color pair is} other {# color pairs are}} below the minimum contrast ratio: see the theme check",
+                    new_issues.len() as u64,
+                )))
+                .unwrap();
+            self.contrast_issues.extend(new_issues);
+        }
+    }
 
+    /// Hands the channel and the config to the components before the first frame.
+    pub(crate) fn init_components(
+        &mut self,
+        action_tx: &mpsc::UnboundedSender<Action>,
+        size: Rect,
+    ) -> Result<()> {
         for component in self.components.iter_mut() {
             component.register_action_handler(action_tx.clone())?;
         }
@@ -63,128 +246,831 @@ impl App {
         }
 
         for component in self.components.iter_mut() {
-            component.init(tui.size()?)?;
+            component.init(size)?;
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the keybindings for a key, which the components then handle by themselves.
+    pub(crate) fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<()> {
+        // Popups handle key events by themselves
+        if !self.mode.is_modal() {
+            action_tx.send(Action::Key(key))?;
+        }
+
+        self.last_key_at = Instant::now();
+        if let Some(action) =
+            self.config
+                .keybindings
+                .lookup(self.mode, &mut self.pending_key_events, key)
+        {
+            tracing::info!(?action, "Got action");
+            action_tx.send(action)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn handle_event(
+        &mut self,
+        e: tui::Event,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<()> {
+        for component in self.components.iter_mut() {
+            if let Some(action) = component.handle_events(Some(e.clone()))? {
+                action_tx.send(action)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates the state of the app itself, i.e. the mode and the layout, which needs neither
+    /// the terminal nor the relays.
+    pub(crate) fn update_state(
+        &mut self,
+        action: &Action,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<()> {
+        match action {
+            Action::Tick if self.last_key_at.elapsed() >= KEY_SEQUENCE_TIMEOUT => {
+                self.pending_key_events.clear();
+            }
+            // The composer decides whether to quit because it knows about unsent notes
+            Action::Quit if self.mode != Mode::Compose => self.should_quit = true,
+            Action::Suspend => self.should_suspend = true,
+            Action::Resume => self.should_suspend = false,
+            Action::EnterMode(mode) => {
+                self.previous_modes.push(self.mode);
+                self.mode = *mode;
+            }
+            Action::LeaveMode => {
+                self.mode = self.previous_modes.pop().unwrap_or_default();
+            }
+            Action::ToggleStatusBar => self.change_layout(action_tx, |layout| {
+                layout.status_bar = !layout.status_bar;
+            })?,
+            Action::ToggleTabBar => self.change_layout(action_tx, |layout| {
+                layout.tab_bar = !layout.tab_bar;
+            })?,
+            Action::ToggleZenMode => {
+                self.change_layout(action_tx, |layout| layout.zen = !layout.zen)?
+            }
+            Action::GrowComposer => self.change_layout(action_tx, UiLayout::grow_composer)?,
+            Action::ShrinkComposer => self.change_layout(action_tx, UiLayout::shrink_composer)?,
+            Action::CheckTheme => action_tx.send(Action::ShowReport(
+                i18n::tr("Theme check"),
+                self.config.theme_report(&self.contrast_issues),
+            ))?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Passes an action to every component, queueing the actions they return.
+    pub(crate) fn dispatch(
+        &mut self,
+        action: &Action,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<()> {
+        for component in self.components.iter_mut() {
+            if let Some(action) = component.update(action.clone())? {
+                action_tx.send(action)?
+            };
         }
 
-        let keys = Keys::parse(self.config.privatekey.clone())?;
-        let conn = Connection::new(keys.clone(), self.config.relays.clone()).await?;
-        let (mut req_rx, event_tx, terminate_tx, conn_wrapper) = ConnectionProcess::new(conn)?;
+        Ok(())
+    }
+
+    fn change_layout(
+        &mut self,
+        action_tx: &mpsc::UnboundedSender<Action>,
+        change: impl FnOnce(&mut UiLayout),
+    ) -> Result<()> {
+        change(&mut self.layout);
+        action_tx.send(Action::LayoutChanged(self.layout))?;
+        Ok(())
+    }
+
+    /// Connects to the reachable relays, returning the channels of the connection process.
+    async fn connect(
+        &self,
+        keys: Option<Keys>,
+        proxy: Option<SocketAddr>,
+        action_tx: &mpsc::UnboundedSender<Action>,
+    ) -> Result<ConnectionChannels> {
+        let (relays, onion_relays) = proxy::reachable_relays(&self.config.relays, proxy.is_some());
+        if !onion_relays.is_empty() {
+            action_tx.send(Action::SystemWarning(i18n::tr_args(
+                "Skipped onion relays, which need a proxy: {relays}",
+                &[(
+                    "relays",
+                    &onion_relays
+                        .iter()
+                        .map(|relay| relay.url.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )],
+            )))?;
+        }
+        let mut conn = Connection::new(keys, relays, proxy)
+            .await?
+            .timeline_limit(self.config.pagination.limit);
+        if self.trace {
+            conn = conn.trace(Tracer::new(&self.config.config._data_dir)?);
+        }
+        let (req_rx, command_tx, terminate_tx, conn_wrapper) = ConnectionProcess::new(conn)?;
         conn_wrapper.run();
+        Ok((req_rx, command_tx, terminate_tx))
+    }
+
+    /// Loads plugins, warning about the ones which failed to load.
+    fn load_plugins(&self, action_tx: &mpsc::UnboundedSender<Action>) -> Result<Plugins> {
+        let mut plugins = Plugins::new()?;
+        if !self.config.plugins.enabled {
+            return Ok(plugins);
+        }
+
+        let errors = plugins.load_dir(&self.config.plugins_dir());
+        for e in &errors {
+            tracing::error!(error = %e, "Failed to load a plugin");
+        }
+        if !errors.is_empty() {
+            action_tx.send(Action::SystemWarning(i18n::plural(
+                "{count, plural, one {# plugin} other {# plugins}} failed to load: see the log",
+                errors.len() as u64,
+            )))?;
+        }
+        tracing::info!(plugins = ?plugins.names(), "Loaded plugins");
+
+        Ok(plugins)
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let (action_tx, mut action_rx) = mpsc::unbounded_channel();
+
+        let mut tui = tui::Tui::new()?
+            .tick_rate(self.tick_rate)
+            .frame_rate(self.frame_rate)
+            .paste(true)
+            .focus(true);
+        // tui.mouse(true);
+        tui.enter()?;
+        self.init_components(&action_tx, tui.size()?)?;
+
+        if !self.config.migrations.is_empty() {
+            action_tx.send(Action::Confirm(
+                i18n::tr("The config file is from an older version of nostui. Save the upgraded file? Comments are not kept"),
+                vec![Action::SaveMigratedConfig],
+            ))?;
+        }
+
+        let mut plugins = self.load_plugins(&action_tx)?;
+
+        let keys = self.config.keys();
+        let mut notifier = keys
+            .as_ref()
+            .map(|keys| Notifier::new(keys.public_key(), self.config.notifications.clone()));
+        let mut focused = true;
+        let mut power = PowerState::default();
+        let proxy = self.config.proxy_addr()?;
+        #[cfg(feature = "simulation")]
+        let simulated = self.simulation.take().map(Simulation::spawn);
+        #[cfg(not(feature = "simulation"))]
+        let simulated = None;
+        let (req_rx, command_tx, terminate_tx) = match simulated {
+            Some(channels) => channels,
+            None => self.connect(keys.clone(), proxy, &action_tx).await?,
+        };
+        let mut req_rx = ingestion::spawn(req_rx);
+        let relay_urls = self.config.write_relays();
+        let mut inboxes = Inboxes::new();
+        let mut relay_info = nip11::RelayInfoCache::new();
+        let mut global_throttle = Throttle::new(
+            self.config.global.max_rate,
+            self.config.pagination.limit,
+            Instant::now(),
+        );
 
         loop {
             if let Some(e) = tui.next().await {
                 match e {
                     tui::Event::Quit => action_tx.send(Action::Quit)?,
-                    tui::Event::Tick => action_tx.send(Action::Tick)?,
+                    tui::Event::Tick | tui::Event::Render if power.is_paused() => {}
+                    tui::Event::Tick => {
+                        if power.tick(&self.config.power, Instant::now()) {
+                            tracing::info!("Pause while the terminal is unfocused");
+                            if self.config.power.pause_subscriptions {
+                                command_tx.send(NostrCommand::PauseTimelines)?;
+                            }
+                        } else {
+                            action_tx.send(Action::Tick)?;
+                        }
+                    }
                     tui::Event::Render => action_tx.send(Action::Render)?,
                     tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                    tui::Event::Key(key) => {
-                        action_tx.send(Action::Key(key))?;
-
-                        if let Some(keymap) = self.config.keybindings.get(&self.mode) {
-                            if let Some(action) = keymap.get(&vec![key]) {
-                                log::info!("Got action: {action:?}");
-                                action_tx.send(action.clone())?;
+                    tui::Event::FocusGained => {
+                        focused = true;
+                        action_tx.send(Action::FocusGained)?;
+                        if power.focus_gained() {
+                            tracing::info!("Resume as the terminal is focused");
+                            if self.config.power.pause_subscriptions {
+                                command_tx.send(NostrCommand::ResumeTimelines)?;
                             } else {
-                                // If the key was not handled as a single key action,
-                                // then consider it for multi-key combinations.
-                                self.last_tick_key_events.push(key);
-
-                                // Check for multi-key combinations
-                                if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                                    log::info!("Got action: {action:?}");
-                                    action_tx.send(action.clone())?;
-                                }
+                                action_tx.send(Action::ResumeTicks)?;
                             }
-                        };
+                            action_tx.send(Action::Render)?;
+                        }
+                    }
+                    tui::Event::FocusLost => {
+                        focused = false;
+                        action_tx.send(Action::FocusLost)?;
+                        power.focus_lost(Instant::now());
                     }
+                    // Popups handle pasted text by themselves, and keybindings are not looked up
+                    tui::Event::Paste(ref text) if !self.mode.is_modal() => {
+                        action_tx.send(Action::Paste(text.clone()))?
+                    }
+                    tui::Event::Key(key) => self.handle_key(key, &action_tx)?,
                     _ => {}
                 }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
-                        action_tx.send(action)?;
+                self.handle_event(e, &action_tx)?;
+            }
+
+            while let Ok(ingested) = req_rx.try_recv() {
+                let notification = match ingested {
+                    Ingested::Notification(notification) => notification,
+                    Ingested::Profile(profile) => {
+                        action_tx.send(Action::ReceiveProfile(profile))?;
+                        continue;
+                    }
+                    Ingested::Zap(id, zap) => {
+                        action_tx.send(Action::ReceiveZap(id, zap))?;
+                        continue;
+                    }
+                };
+                if let Notification::Timeline(TimelineTabType::Global, ref event) = notification {
+                    if !global_throttle.allow(Instant::now()) {
+                        let dropped = global_throttle.dropped();
+                        tracing::debug!(id = %event.id, dropped, "Drop a global note");
+                        continue;
+                    }
+                }
+                if let Notification::Timeline(_, ref event) = notification {
+                    let verdict = plugins.check_event(event);
+                    if verdict.hidden {
+                        continue;
+                    }
+                    if !verdict.annotations.is_empty() {
+                        action_tx.send(Action::AnnotateEvent(event.id, verdict.annotations))?;
+                    }
+                }
+                if let Notification::Timeline(TimelineTabType::Home, ref event) = notification {
+                    if let Some(notifier) = notifier.as_mut() {
+                        if let Some(kind) = notifier.check(event) {
+                            notifier.alert(kind, event, focused);
+                        }
                     }
                 }
-            }
 
-            while let Ok(event) = req_rx.try_recv() {
-                action_tx.send(Action::ReceiveEvent(event))?;
+                match notification {
+                    Notification::Timeline(TimelineTabType::Home, event) => {
+                        action_tx.send(Action::ReceiveEvent(event))?
+                    }
+                    Notification::Timeline(tab, event) => {
+                        action_tx.send(Action::ReceiveTabEvent(tab, event))?
+                    }
+                    Notification::Fetched(event) => {
+                        action_tx.send(Action::ReceiveFetchedEvent(event))?
+                    }
+                    Notification::Receipt(event_id, relay_url, status, message) => action_tx.send(
+                        Action::ReceiveReceipt(event_id, relay_url.to_string(), status, message),
+                    )?,
+                    Notification::Trace(entry) => action_tx.send(Action::ReceiveTrace(entry))?,
+                    Notification::Subscriptions(subscriptions) => {
+                        action_tx.send(Action::ReceiveSubscriptions(subscriptions))?
+                    }
+                    Notification::Kinds(kinds) => {
+                        action_tx.send(Action::ReceiveKindStats(kinds))?
+                    }
+                    Notification::Stats(stats) => {
+                        let depth = QueueDepth {
+                            actions: action_rx.len(),
+                            notifications: req_rx.len(),
+                        };
+                        action_tx.send(Action::ReceiveStats(stats, depth))?
+                    }
+                }
             }
 
             while let Ok(action) = action_rx.try_recv() {
+                let _span = tracing::debug_span!("update", action = %action).entered();
                 if action != Action::Tick && action != Action::Render {
-                    log::debug!("{action:?}");
+                    tracing::debug!(?action, "Update");
                 }
+                self.update_state(&action, &action_tx)?;
                 match action {
-                    Action::Tick => {
-                        self.last_tick_key_events.drain(..);
-                    }
-                    Action::Quit => self.should_quit = true,
-                    Action::Suspend => self.should_suspend = true,
-                    Action::Resume => self.should_suspend = false,
                     Action::Resize(w, h) => {
                         tui.resize(Rect::new(0, 0, w, h))?;
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
-                                }
+                        self.render(&mut tui, &action_tx)?;
+                    }
+                    Action::Render => self.render(&mut tui, &action_tx)?,
+                    Action::AwayUnreadChanged(unread)
+                        if self.config.notifications.unread_in_title =>
+                    {
+                        let title = match unread {
+                            0 => String::from("nostui"),
+                            unread => format!("({unread}) nostui"),
+                        };
+                        tui.set_title(&title)?;
+                    }
+                    Action::ReceiveEvent(ref event) => {
+                        tracing::info!(id = %event.id, kind = %event.kind, "Got nostr event");
+                    }
+                    Action::FetchEvent(id) => {
+                        command_tx.send(NostrCommand::Fetch(vec![Filter::new().id(id)]))?;
+                    }
+                    Action::FetchMyActivity => {
+                        if let Some(keys) = &keys {
+                            let filter = Filter::new()
+                                .author(keys.public_key())
+                                .kinds([Kind::TextNote, Kind::Repost, Kind::Reaction])
+                                .limit(activity::LIMIT);
+                            command_tx.send(NostrCommand::Fetch(vec![filter]))?;
+                        }
+                    }
+                    Action::FetchFollowers(pubkey) => {
+                        let filter = Filter::new()
+                            .pubkey(pubkey)
+                            .kind(Kind::ContactList)
+                            .limit(followers::LIMIT);
+                        command_tx.send(NostrCommand::Fetch(vec![filter]))?;
+                    }
+                    Action::FetchZapGoal(id) => {
+                        let filters = vec![
+                            Filter::new().id(id),
+                            Filter::new().event(id).kind(Kind::ZapReceipt),
+                        ];
+                        command_tx.send(NostrCommand::Fetch(filters))?;
+                    }
+                    Action::FetchProfile(pubkey) => {
+                        // Without a limit, so that requests of profiles are merged into one
+                        let filter = Filter::new().author(pubkey).kind(Kind::Metadata);
+                        command_tx.send(NostrCommand::Fetch(vec![filter]))?;
+                    }
+                    Action::ReceiveFetchedEvent(ref event) if event.kind == Kind::RelayList => {
+                        for (event, relays) in inboxes.add(event, &relay_urls) {
+                            action_tx.send(Action::DeliverTo(event, relays))?;
+                        }
+                    }
+                    Action::FetchChannels(ref ids) => {
+                        let filters = vec![
+                            Filter::new().ids(ids.clone()).kind(Kind::ChannelCreation),
+                            Filter::new()
+                                .events(ids.clone())
+                                .kind(Kind::ChannelMetadata),
+                        ];
+                        command_tx.send(NostrCommand::Fetch(filters))?;
+                    }
+                    Action::FetchContactLists(ref pubkeys) => {
+                        for batch in pubkeys.chunks(CONTACT_LIST_BATCH_SIZE) {
+                            let filter = Filter::new()
+                                .authors(batch.to_vec())
+                                .kind(Kind::ContactList);
+                            command_tx.send(NostrCommand::Fetch(vec![filter]))?;
+                        }
+                    }
+                    Action::RunDiagnostics => {
+                        action_tx.send(Action::SystemMessage(i18n::tr(
+                            "[Diagnostics] Checking the relays...",
+                        )))?;
+                        let (tx, config) = (action_tx.clone(), self.config.clone());
+                        tokio::spawn(async move {
+                            let checks = doctor::diagnose(&config).await;
+                            let lines = checks.iter().map(|check| check.line()).collect();
+                            tx.send(Action::ShowReport(i18n::tr("Diagnostics"), lines))
+                        });
+                    }
+                    Action::ViewRelays => {
+                        for relay in &self.config.relays {
+                            if let Some(info) = relay_info.get(&relay.url, Instant::now()) {
+                                let action =
+                                    Action::ReceiveRelayInfo(relay.url.clone(), info.clone());
+                                action_tx.send(action)?;
+                                continue;
                             }
-                        })?;
-                    }
-                    Action::Render => {
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
-                                }
+                            let (tx, url) = (action_tx.clone(), relay.url.clone());
+                            tokio::spawn(async move {
+                                let info = match Url::parse(&url) {
+                                    Ok(parsed) => nip11::fetch(parsed, proxy)
+                                        .await
+                                        .map(Box::new)
+                                        .map_err(|e| e.to_string()),
+                                    Err(e) => Err(e.to_string()),
+                                };
+                                tx.send(Action::ReceiveRelayInfo(url, info))
+                            });
+                        }
+                    }
+                    Action::ReceiveRelayInfo(ref url, ref info) => {
+                        relay_info.insert(url.clone(), info.clone(), Instant::now());
+                    }
+                    Action::OpenFromClipboard => {
+                        let target = clipboard::read().and_then(|text| NostrTarget::parse(&text));
+                        match target {
+                            Ok(target) => action_tx.send(Action::OpenTab(target.into()))?,
+                            Err(e) => action_tx.send(Action::SystemWarning(i18n::tr_args(
+                                "Cannot open the clipboard: {error}",
+                                &[("error", &e)],
+                            )))?,
+                        }
+                    }
+                    Action::CopyToClipboard(ref text) => match clipboard::write(text) {
+                        Ok(()) => action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Copied] {text}",
+                            &[("text", text)],
+                        )))?,
+                        Err(e) => action_tx.send(Action::SystemWarning(i18n::tr_args(
+                            "Cannot copy to the clipboard: {error}",
+                            &[("error", &e)],
+                        )))?,
+                    },
+                    Action::OpenUrl(ref url) => {
+                        if let Err(e) = browser::open(url) {
+                            action_tx.send(Action::SystemWarning(i18n::tr_args(
+                                "Cannot open {url}: {error}",
+                                &[("url", url), ("error", &e)],
+                            )))?;
+                        }
+                    }
+                    Action::RunEditor(ref draft) => match tui.exec(|| editor::edit(draft))? {
+                        Ok(text) => action_tx.send(Action::LoadDraft(text))?,
+                        Err(e) => action_tx.send(Action::SystemWarning(i18n::tr_args(
+                            "Cannot edit the note: {error}",
+                            &[("error", &e)],
+                        )))?,
+                    },
+                    Action::SaveMigratedConfig => {
+                        for migration in std::mem::take(&mut self.config.migrations) {
+                            match migration.save() {
+                                Ok(backup) => action_tx
+                                    .send(Action::SystemMessage(i18n::tr_args(
+                                    "[Config] Upgraded {path}, the original is kept as {backup}",
+                                    &[
+                                        ("path", &migration.path.display()),
+                                        ("backup", &backup.display()),
+                                    ],
+                                )))?,
+                                Err(e) => action_tx.send(Action::SystemWarning(i18n::tr_args(
+                                    "Cannot save the config file: {error}",
+                                    &[("error", &e)],
+                                )))?,
                             }
-                        })?;
+                        }
                     }
-                    Action::ReceiveEvent(ref event) => {
-                        log::info!("Got nostr event: {event:?}");
+                    Action::ReloadPlugins => {
+                        plugins = self.load_plugins(&action_tx)?;
+                        action_tx.send(Action::SystemMessage(i18n::plural(
+                            "[Plugins] {count, plural, one {# plugin} other {# plugins}} loaded",
+                            plugins.names().len() as u64,
+                        )))?;
+                    }
+                    Action::InvokePluginCommand(ref name, ref note) => {
+                        match plugins.run_command(name, note.as_ref()) {
+                            Ok(Some(message)) => action_tx
+                                .send(Action::SystemMessage(format!("[{name}] {message}")))?,
+                            Ok(None) => {}
+                            Err(e) => action_tx.send(Action::SystemWarning(format!("{e}")))?,
+                        }
+                    }
+                    Action::ToggleDoNotDisturb => {
+                        let message = match notifier.as_mut().map(|n| n.toggle_do_not_disturb()) {
+                            Some(true) => "[Do not disturb] Notifications and sounds are off",
+                            Some(false) => "[Do not disturb] Notifications and sounds are on",
+                            None => "Notifications need privatekey in the config",
+                        };
+                        action_tx.send(Action::SystemMessage(i18n::tr(message)))?;
+                    }
+                    Action::SubscribeTimeline(ref tab) => {
+                        command_tx.send(NostrCommand::SubscribeTimeline(tab.clone()))?;
+                    }
+                    Action::UnsubscribeTimeline(ref tab) => {
+                        command_tx.send(NostrCommand::Unsubscribe(tab.clone()))?;
+                    }
+                    Action::ViewSubscriptions => {
+                        command_tx.send(NostrCommand::InspectSubscriptions)?;
+                    }
+                    Action::CloseSubscription(ref id) => {
+                        command_tx.send(NostrCommand::CloseSubscription(id.clone()))?;
+                        command_tx.send(NostrCommand::InspectSubscriptions)?;
+                    }
+                    Action::ViewKindStats => command_tx.send(NostrCommand::InspectKinds)?,
+                    Action::LoadOlderNotes(ref tab, until, limit) => {
+                        let command = NostrCommand::LoadPage(tab.clone(), None, until, limit);
+                        command_tx.send(command)?;
+                    }
+                    Action::LoadNotesBetween(ref tab, since, until, limit) => {
+                        let command =
+                            NostrCommand::LoadPage(tab.clone(), Some(since), until, limit);
+                        command_tx.send(command)?;
+                    }
+                    Action::SendReaction(_)
+                    | Action::DeleteReaction(_)
+                    | Action::DeleteRepost(_)
+                    | Action::DeleteNote(_)
+                    | Action::SendRepost(_)
+                    | Action::SendTextNote(..)
+                    | Action::SendChannelMessage(..)
+                    | Action::RetryPublish(..)
+                    | Action::SendMetadata(_)
+                    | Action::SendContactList(..)
+                    | Action::UploadMedia(_)
+                        if keys.is_none() =>
+                    {
+                        action_tx.send(Action::SystemWarning(i18n::tr(
+                            "Browse mode is read-only: set privatekey in the config to publish",
+                        )))?;
                     }
                     Action::SendReaction(ref target_event) => {
-                        let event = EventBuilder::reaction(target_event, "+").to_event(&keys)?;
-                        log::info!("Send reaction: {event:?}");
-                        event_tx.send(event)?;
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let builder =
+                            EventBuilder::reaction(target_event, &self.config.reaction.content);
+                        let event = sign(&plugins, builder, keys, &action_tx)?;
+                        tracing::info!(id = %event.id, "Send reaction");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
                         let note1 = target_event.id.to_bech32()?;
-                        action_tx.send(Action::SystemMessage(format!("[Liked] {note1}")))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Liked] {note}",
+                            &[("note", &note1)],
+                        )))?;
+                    }
+                    Action::DeleteReaction(ref reaction) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let event = sign(
+                            &plugins,
+                            EventBuilder::delete([reaction.id]),
+                            keys,
+                            &action_tx,
+                        )?;
+                        tracing::info!(id = %event.id, reaction = %reaction.id, "Delete reaction");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr("[Reaction removed]")))?;
+                    }
+                    Action::DeleteRepost(ref repost) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let event = sign(
+                            &plugins,
+                            EventBuilder::delete([repost.id]),
+                            keys,
+                            &action_tx,
+                        )?;
+                        tracing::info!(id = %event.id, repost = %repost.id, "Delete repost");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr("[Repost removed]")))?;
+                    }
+                    Action::DeleteNote(ref note) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let event =
+                            sign(&plugins, EventBuilder::delete([note.id]), keys, &action_tx)?;
+                        tracing::info!(id = %event.id, note = %note.id, "Delete note");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr("[Note deleted]")))?;
                     }
                     Action::SendRepost(ref target_event) => {
-                        let event = EventBuilder::repost(target_event, None).to_event(&keys)?;
-                        log::info!("Send repost: {event:?}");
-                        event_tx.send(event)?;
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let event = sign(
+                            &plugins,
+                            EventBuilder::repost(target_event, None),
+                            keys,
+                            &action_tx,
+                        )?;
+                        tracing::info!(id = %event.id, "Send repost");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
                         let note1 = target_event.id.to_bech32()?;
-                        action_tx.send(Action::SystemMessage(format!("[Reposted] {note1}")))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Reposted] {note}",
+                            &[("note", &note1)],
+                        )))?;
                     }
                     Action::SendTextNote(ref content, ref tags) => {
-                        let event = EventBuilder::text_note(content, tags.iter().cloned())
-                            .to_event(&keys)?;
-                        log::info!("Send text note: {event:?}");
-                        event_tx.send(event)?;
-                        action_tx.send(Action::SystemMessage(format!("[Posted] {content}")))?;
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let builder = EventBuilder::text_note(content, tags.iter().cloned());
+                        let event = sign(&plugins, builder, keys, &action_tx)?;
+                        tracing::info!(id = %event.id, "Send text note");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event.clone())))?;
+                        // Mentioned users may not read any of my relays
+                        let (relays, missing) = inboxes.route(&event, &relay_urls);
+                        if !relays.is_empty() {
+                            action_tx.send(Action::DeliverTo(event, relays))?;
+                        }
+                        if !missing.is_empty() {
+                            let filter = Filter::new().authors(missing).kind(Kind::RelayList);
+                            command_tx.send(NostrCommand::Fetch(vec![filter]))?;
+                        }
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Posted] {content}",
+                            &[("content", content)],
+                        )))?;
+                    }
+                    Action::SendChannelMessage(ref content, ref tags) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let builder =
+                            EventBuilder::new(Kind::ChannelMessage, content, tags.iter().cloned());
+                        let event = sign(&plugins, builder, keys, &action_tx)?;
+                        tracing::info!(id = %event.id, "Send channel message");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Sent to the channel] {content}",
+                            &[("content", content)],
+                        )))?;
+                    }
+                    Action::SendMetadata(ref metadata) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let event =
+                            sign(&plugins, EventBuilder::metadata(metadata), keys, &action_tx)?;
+                        tracing::info!(id = %event.id, "Send metadata");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                        action_tx.send(Action::SystemMessage(i18n::tr("[Profile updated]")))?;
+                    }
+                    Action::SendContactList(ref tags, ref content) => {
+                        let keys = keys.as_ref().expect("keys should exist");
+                        let builder = EventBuilder::new(Kind::ContactList, content, tags.clone());
+                        let event = sign(&plugins, builder, keys, &action_tx)?;
+                        tracing::info!(id = %event.id, "Send contact list");
+                        action_tx
+                            .send(Action::EventPublished(event.clone(), relay_urls.clone()))?;
+                        command_tx.send(NostrCommand::SendEvent(Box::new(event)))?;
+                    }
+                    Action::RetryPublish(ref event, ref relays, Some(difficulty)) => {
+                        let keys = keys.clone().expect("keys should exist");
+                        let builder = EventBuilder::new(
+                            event.kind,
+                            event.content.clone(),
+                            event.tags.clone(),
+                        );
+                        let (tx, relays) = (action_tx.clone(), relays.clone());
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Mining] PoW difficulty {difficulty}",
+                            &[("difficulty", &difficulty)],
+                        )))?;
+                        // The event has to be signed again with a nonce, which may take a while
+                        tokio::spawn(async move {
+                            let mined = tokio::task::spawn_blocking(move || {
+                                builder.to_pow_event(&keys, difficulty)
+                            })
+                            .await;
+                            let error = |e: &dyn std::fmt::Display| {
+                                Action::Error(i18n::tr_args(
+                                    "Failed to mine PoW: {error}",
+                                    &[("error", e)],
+                                ))
+                            };
+                            let action = match mined {
+                                Ok(Ok(event)) => Action::PublishTo(event, relays),
+                                Ok(Err(e)) => error(&e),
+                                Err(e) => error(&e),
+                            };
+                            tx.send(action)
+                        });
+                    }
+                    Action::UploadMedia(_) if self.config.media.server.is_none() => {
+                        action_tx.send(Action::SystemWarning(i18n::tr(
+                            "Set media.server in the config to attach files",
+                        )))?;
+                    }
+                    Action::UploadMedia(ref path) => {
+                        let server = self.config.media.server.clone().unwrap_or_default();
+                        let keys = keys.clone().expect("keys should exist");
+                        let protocol = self.config.media.protocol;
+                        let path = std::path::PathBuf::from(path);
+                        let tx = action_tx.clone();
+                        action_tx.send(Action::SystemMessage(i18n::tr_args(
+                            "[Uploading] {file} to {server}",
+                            &[("file", &path.display()), ("server", &server)],
+                        )))?;
+                        tokio::spawn(async move {
+                            let uploaded = media::upload(&keys, &server, protocol, proxy, &path)
+                                .await
+                                .and_then(|upload| Ok((upload.imeta_tag()?, upload.url)));
+                            let action = match uploaded {
+                                Ok((tag, url)) => Action::MediaUploaded(url, tag),
+                                Err(e) => Action::Error(i18n::tr_args(
+                                    "Failed to upload {file}: {error}",
+                                    &[("file", &path.display()), ("error", &e)],
+                                )),
+                            };
+                            tx.send(action)
+                        });
+                    }
+                    Action::TranslateNote(id, _) if self.config.translation.url.is_none() => {
+                        action_tx.send(Action::ReceiveTranslation(
+                            id,
+                            Err(i18n::tr("set translation.url in the config")),
+                        ))?;
+                    }
+                    Action::TranslateNote(id, ref content) => {
+                        let config = self.config.translation.clone();
+                        let target = translate::target_language(&config, i18n::locale());
+                        let (tx, content) = (action_tx.clone(), content.clone());
+                        tokio::spawn(async move {
+                            let translation =
+                                translate::translate(&config, proxy, &content, &target)
+                                    .await
+                                    .map_err(|e| e.to_string());
+                            tx.send(Action::ReceiveTranslation(id, translation))
+                        });
+                    }
+                    Action::FetchLinkPreviews(ref urls) => {
+                        for url in urls.clone() {
+                            let (tx, config) =
+                                (action_tx.clone(), self.config.link_preview.clone());
+                            tokio::spawn(async move {
+                                let preview = link_preview::resolve(&config, proxy, &url)
+                                    .await
+                                    .map_err(|e| e.to_string());
+                                tx.send(Action::ReceiveLinkPreview(url, preview))
+                            });
+                        }
+                    }
+                    Action::RetryPublish(ref event, ref relays, None) => {
+                        action_tx.send(Action::PublishTo(event.clone(), relays.clone()))?;
+                    }
+                    Action::PublishTo(ref event, ref relays) => {
+                        tracing::info!(id = %event.id, ?relays, "Send event to relays");
+                        action_tx.send(Action::EventPublished(event.clone(), relays.clone()))?;
+                        command_tx.send(NostrCommand::SendEventTo(
+                            relays.clone(),
+                            Box::new(event.clone()),
+                        ))?;
+                        action_tx.send(Action::SystemMessage(i18n::plural(
+                            "[Retried] {count, plural, one {# relay} other {# relays}}",
+                            relays.len() as u64,
+                        )))?;
+                    }
+                    // Events are signed by their authors, so they can be broadcast in browse mode
+                    Action::BroadcastEvent(ref event) => {
+                        let relays = self.config.write_relays();
+                        tracing::info!(id = %event.id, ?relays, "Broadcast event");
+                        action_tx.send(Action::EventPublished(event.clone(), relays.clone()))?;
+                        command_tx.send(NostrCommand::SendEventTo(
+                            relays.clone(),
+                            Box::new(event.clone()),
+                        ))?;
+                        action_tx.send(Action::SystemMessage(i18n::plural(
+                            "[Broadcasting] to {count, plural, one {# relay} other {# relays}}: press Shift-o for the results",
+                            relays.len() as u64,
+                        )))?;
+                    }
+                    Action::DeliverTo(ref event, ref relays) => {
+                        tracing::info!(id = %event.id, ?relays, "Deliver event to inboxes");
+                        action_tx.send(Action::EventPublished(event.clone(), relays.clone()))?;
+                        command_tx.send(NostrCommand::SendEventTo(
+                            relays.clone(),
+                            Box::new(event.clone()),
+                        ))?;
                     }
                     _ => {}
                 }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.update(action.clone())? {
-                        action_tx.send(action)?
-                    };
-                }
+                self.dispatch(&action, &action_tx)?;
             }
             if self.should_suspend {
+                if self.config.power.pause_subscriptions {
+                    command_tx.send(NostrCommand::PauseTimelines)?;
+                    // The connection closes the subscriptions before the process is stopped
+                    tokio::time::sleep(SUSPEND_GRACE).await;
+                }
                 tui.suspend()?;
+                if self.config.power.pause_subscriptions && !power.is_paused() {
+                    command_tx.send(NostrCommand::ResumeTimelines)?;
+                }
                 action_tx.send(Action::Resume)?;
                 tui = tui::Tui::new()?
                     .tick_rate(self.tick_rate)
-                    .frame_rate(self.frame_rate);
+                    .frame_rate(self.frame_rate)
+                    .paste(true)
+                    .focus(true);
                 // tui.mouse(true);
                 tui.enter()?;
             } else if self.should_quit {
@@ -197,3 +1083,20 @@ impl App {
         Ok(())
     }
 }
+
+/// Signs the event after the publish hooks of plugins, warning about the hooks which failed.
+fn sign(
+    plugins: &Plugins,
+    builder: EventBuilder,
+    keys: &Keys,
+    action_tx: &mpsc::UnboundedSender<Action>,
+) -> Result<Event> {
+    let (event, failures) = plugins.sign(builder, keys)?;
+    for error in failures {
+        action_tx.send(Action::SystemWarning(i18n::tr_args(
+            "Publish hook failed, so its tags are not used: {error}",
+            &[("error", &error)],
+        )))?;
+    }
+    Ok(event)
+}
@@ -0,0 +1,70 @@
+use nostr_sdk::PublicKey;
+use ratatui::style::Color;
+
+/// Colors which authors are told apart by, readable on both dark and light backgrounds.
+const PALETTE: [Color; 12] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+];
+
+/// The color of an author, which is the same on every run (FNV-1a of the public key).
+pub fn color(pubkey: &PublicKey) -> Color {
+    let hash = pubkey
+        .to_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        });
+    PALETTE[(hash % PALETTE.len() as u64) as usize]
+}
+
+/// The first letter or digit of a name in upper case, e.g. `A` for `@alice`.
+pub fn initial(name: &str) -> Option<String> {
+    name.chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::Keys;
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_color() {
+        let pubkey =
+            PublicKey::from_hex("4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25")
+                .unwrap();
+        assert_eq!(color(&pubkey), color(&pubkey));
+
+        let colors: Vec<Color> = (0..500)
+            .map(|_| color(&Keys::generate().public_key()))
+            .collect();
+        assert!(PALETTE.iter().all(|c| colors.contains(c)));
+    }
+
+    #[rstest]
+    #[case("alice", Some("A"))]
+    #[case("@bob", Some("B"))]
+    #[case("  7days", Some("7"))]
+    #[case("ñandu", Some("Ñ"))]
+    #[case("のすとら", Some("の"))]
+    #[case("🤙", None)]
+    #[case("", None)]
+    fn test_initial(#[case] name: &str, #[case] expected: Option<&str>) {
+        assert_eq!(initial(name), expected.map(String::from));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+
+use nostr_sdk::prelude::*;
+
+/// Notes which arrived in the Home tab while the terminal was unfocused, counted in the terminal
+/// title and marked in the tab once it is focused again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Away {
+    since: Option<Timestamp>,
+    unread: HashSet<EventId>,
+}
+
+impl Away {
+    pub fn is_away(&self) -> bool {
+        self.since.is_some()
+    }
+
+    pub fn focus_lost(&mut self, now: Timestamp) {
+        if self.since.is_none() {
+            self.since = Some(now);
+            self.unread.clear();
+        }
+    }
+
+    /// Counts a note which arrived while away, returning `true` if it is a new one. Notes from
+    /// before, e.g. in pages of older notes, are not counted.
+    pub fn add(&mut self, event: &Event) -> bool {
+        match self.since {
+            Some(since) if event.created_at >= since => self.unread.insert(event.id),
+            _ => false,
+        }
+    }
+
+    pub fn unread(&self) -> usize {
+        self.unread.len()
+    }
+
+    /// Ends the time away, returning when it started if any notes arrived meanwhile.
+    pub fn focus_gained(&mut self) -> Option<Timestamp> {
+        let since = self.since.take()?;
+        let arrived = !self.unread.is_empty();
+        self.unread.clear();
+        arrived.then_some(since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn note(created_at: u64) -> Event {
+        EventBuilder::text_note("gm", [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_away() {
+        let mut away = Away::default();
+        assert_eq!(away.add(&note(20)), false);
+        assert_eq!(away.focus_gained(), None);
+
+        away.focus_lost(Timestamp::from(10));
+        // Repeated focus events don't restart the time away
+        away.focus_lost(Timestamp::from(15));
+        assert_eq!(away.focus_gained(), None);
+
+        away.focus_lost(Timestamp::from(10));
+        let new = note(20);
+        assert_eq!(away.add(&new), true);
+        // Received again from another relay
+        assert_eq!(away.add(&new), false);
+        assert_eq!(away.add(&note(5)), false);
+        assert_eq!(away.unread(), 1);
+        assert_eq!(away.focus_gained(), Some(Timestamp::from(10)));
+        assert_eq!(away.unread(), 0);
+        assert_eq!(away.is_away(), false);
+    }
+}
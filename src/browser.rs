@@ -0,0 +1,36 @@
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre::{eyre, Result};
+
+/// Variable of the command which opens URLs, e.g. `firefox`, which overrides the platform's one.
+const BROWSER_ENV: &str = "BROWSER";
+
+/// Command to open URLs with the default application of the platform.
+#[cfg(target_os = "macos")]
+const OPEN_COMMAND: &[&str] = &["open"];
+#[cfg(target_os = "windows")]
+const OPEN_COMMAND: &[&str] = &["cmd", "/C", "start", ""];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const OPEN_COMMAND: &[&str] = &["xdg-open"];
+
+/// Opens the URL in `$BROWSER` or the default application, without waiting for it to exit.
+pub fn open(url: &str) -> Result<()> {
+    let browser = std::env::var(BROWSER_ENV).ok().filter(|b| !b.is_empty());
+    let command: Vec<&str> = match browser {
+        Some(ref browser) => browser.split_whitespace().collect(),
+        None => OPEN_COMMAND.to_vec(),
+    };
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| eyre!("${BROWSER_ENV} is empty"))?;
+
+    Command::new(program)
+        .args(args)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| eyre!("Failed to run {program}: {e}"))?;
+    Ok(())
+}
@@ -1,6 +1,8 @@
-use clap::Parser;
+use std::path::PathBuf;
 
-use crate::utils::version;
+use clap::{Parser, Subcommand};
+
+use crate::{nostr::nip21::NostrTarget, session, timeline::TimelineTabType, utils::version};
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
@@ -22,4 +24,126 @@ pub struct Cli {
         default_value_t = 16.0
     )]
     pub frame_rate: f64,
+
+    #[arg(
+        short,
+        long = "relay",
+        value_name = "URL",
+        help = "Relay to connect to instead of the configured ones (can be repeated)"
+    )]
+    pub relays: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Record raw relay messages to trace.log in the data directory"
+    )]
+    pub trace: bool,
+
+    #[arg(
+        long,
+        help = "Read the private key from the first line of stdin instead of the config"
+    )]
+    pub key_stdin: bool,
+
+    #[cfg(feature = "simulation")]
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Replay relay notifications from a script instead of connecting to relays"
+    )]
+    pub simulate: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Check the config, the key and the relays, print a report and exit"
+    )]
+    pub doctor: bool,
+
+    #[arg(
+        long = "tab",
+        value_name = "SPEC",
+        value_parser = parse_tab,
+        help = "Tab to open instead of the saved ones: home, global, user:<npub>, thread:<note>, hashtag:<tag> or channel:<note> (can be repeated)"
+    )]
+    pub tabs: Vec<TimelineTabType>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        value_parser = parse_workspace,
+        help = "Workspace to save and restore open tabs in, apart from the default one"
+    )]
+    pub workspace: Option<String>,
+
+    #[arg(
+        value_name = "URI",
+        value_parser = parse_uri,
+        help = "Note or user to open at startup, e.g. nostr:nevent1... or nostr:npub1..."
+    )]
+    pub uri: Option<TimelineTabType>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands which run without the TUI, e.g. for scripting.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Publish a text note
+    Post {
+        /// Content of the note
+        text: String,
+    },
+    /// Print the public key of the configured private key
+    Whoami,
+    /// Manage the relays in the config file
+    Relays {
+        #[command(subcommand)]
+        command: RelaysCommand,
+    },
+    /// Manage the private key
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RelaysCommand {
+    /// Print the relays to connect to
+    List,
+    /// Add a relay
+    Add { url: String },
+    /// Remove a relay
+    Remove { url: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum KeyCommand {
+    /// Encrypt the private key with a passphrase into a file (NIP-49)
+    Encrypt {
+        /// File to write to [default: key.ncryptsec in the config directory]
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+fn parse_tab(spec: &str) -> Result<TimelineTabType, String> {
+    spec.parse().map_err(|e| format!("{e}"))
+}
+
+fn parse_uri(uri: &str) -> Result<TimelineTabType, String> {
+    NostrTarget::parse(uri)
+        .map(TimelineTabType::from)
+        .map_err(|e| format!("{e}"))
+}
+
+fn parse_workspace(name: &str) -> Result<String, String> {
+    if !session::is_valid_workspace(name) {
+        return Err(String::from(
+            "Only letters, digits, '-' and '_' are allowed",
+        ));
+    }
+    Ok(name.to_string())
 }
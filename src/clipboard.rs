@@ -0,0 +1,83 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use color_eyre::eyre::{eyre, Result};
+
+/// Commands to read the system clipboard, tried in order.
+#[cfg(target_os = "macos")]
+const PASTE_COMMANDS: &[&[&str]] = &[&["pbpaste"]];
+#[cfg(target_os = "windows")]
+const PASTE_COMMANDS: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+/// Commands to write the system clipboard, tried in order.
+#[cfg(target_os = "macos")]
+const COPY_COMMANDS: &[&[&str]] = &[&["pbcopy"]];
+#[cfg(target_os = "windows")]
+const COPY_COMMANDS: &[&[&str]] = &[&["clip"]];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const COPY_COMMANDS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Reads text from the system clipboard using the platform's paste command.
+pub fn read() -> Result<String> {
+    for command in PASTE_COMMANDS {
+        let output = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8(output.stdout)?);
+            }
+            Ok(output) => {
+                tracing::debug!(program = command[0], status = %output.status, "Clipboard command failed")
+            }
+            Err(e) => {
+                tracing::debug!(program = command[0], error = %e, "Failed to run a clipboard command")
+            }
+        }
+    }
+
+    Err(eyre!("Failed to read the clipboard"))
+}
+
+/// Writes text to the system clipboard using the platform's copy command.
+pub fn write(text: &str) -> Result<()> {
+    for command in COPY_COMMANDS {
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::debug!(program = command[0], error = %e, "Failed to run a clipboard command");
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(());
+        }
+        tracing::debug!(program = command[0], %status, "Clipboard command failed");
+    }
+
+    Err(eyre!("Failed to write the clipboard"))
+}
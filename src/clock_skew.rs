@@ -0,0 +1,41 @@
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+/// What to do with notes dated further in the future than the tolerance, e.g. by a wrong clock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FuturePolicy {
+    /// Sort them by the time they were received
+    Clamp,
+    /// Keep them out of timelines
+    Hide,
+    /// Sort them by the time they were received and mark them
+    #[default]
+    Annotate,
+}
+
+/// Returns whether the note is dated more than `tolerance` seconds after `now`.
+pub fn is_future(event: &Event, now: Timestamp, tolerance: u64) -> bool {
+    event.created_at.as_u64() > now.as_u64().saturating_add(tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(1000, false)]
+    #[case(1300, false)]
+    #[case(1301, true)]
+    #[case(0, false)]
+    fn test_is_future(#[case] created_at: u64, #[case] expected: bool) {
+        let event = EventBuilder::text_note("gm", [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(is_future(&event, Timestamp::from(1000), 300), expected);
+    }
+}
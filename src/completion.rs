@@ -0,0 +1,791 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use nostr_sdk::prelude::*;
+use regex::Regex;
+
+use crate::{
+    emoji::EMOJIS,
+    i18n,
+    nostr::{nip21::NostrTarget, Profile},
+    text::shorten_hex,
+};
+
+/// Number of candidates shown in the completion popup.
+pub const MAX_CANDIDATES: usize = 8;
+
+/// Number of hashtags remembered for completion.
+pub const MAX_RECENT_HASHTAGS: usize = 200;
+
+/// A word which can be completed, e.g. `@ali` before the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigger {
+    pub prefix: char,
+    /// Column of the prefix in the line, in chars
+    pub start: usize,
+    /// The word typed after the prefix
+    pub query: String,
+}
+
+impl Trigger {
+    /// Finds the word before the cursor, which starts with `prefix` at the beginning of the line
+    /// or after a whitespace.
+    pub fn find(line: &str, cursor: usize, prefix: char) -> Option<Self> {
+        let before: Vec<char> = line.chars().take(cursor).collect();
+        let start = before
+            .iter()
+            .rposition(|c| c.is_whitespace() || *c == prefix)?;
+        if before[start] != prefix || (start > 0 && !before[start - 1].is_whitespace()) {
+            return None;
+        }
+
+        Some(Self {
+            prefix,
+            start,
+            query: before[start + 1..].iter().collect(),
+        })
+    }
+}
+
+/// An entry of the completion popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub label: String,
+    pub detail: String,
+    /// Text which replaces the trigger word
+    pub replacement: String,
+}
+
+/// Suggests candidates for the words starting with its prefix.
+pub trait CompletionProvider {
+    fn prefix(&self) -> char;
+    fn complete(&self, query: &str) -> Vec<Candidate>;
+}
+
+/// Scores how well `text` matches `query` as a subsequence, ignoring case.
+/// Lower is better: 0 for a prefix match, and `None` if it does not match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    for q in query.to_lowercase().chars() {
+        let found = text[position..].iter().position(|c| *c == q)?;
+        score += found;
+        position += found + 1;
+    }
+    Some(score)
+}
+
+/// Words and trigrams of the names and NIP-05 identifiers of profiles, updated as profiles
+/// arrive, so that mentions are looked up without scanning every profile on each keystroke.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileIndex {
+    /// Indexed words and trigrams of each account, to remove them when the profile is updated
+    terms: HashMap<PublicKey, (Vec<String>, Vec<String>)>,
+    /// Accounts by the lowercased words of their names and their npubs, looked up by prefix
+    words: BTreeMap<String, HashSet<PublicKey>>,
+    /// Accounts by the trigrams of their names, for queries in the middle of a word
+    trigrams: HashMap<String, HashSet<PublicKey>>,
+}
+
+impl ProfileIndex {
+    pub fn insert(&mut self, profile: &Profile) {
+        self.remove(&profile.pubkey);
+
+        let metadata = &profile.metadata;
+        let names: Vec<String> = [&metadata.display_name, &metadata.name, &metadata.nip05]
+            .into_iter()
+            .flatten()
+            .map(|name| name.to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let mut words: HashSet<String> = names
+            .iter()
+            .flat_map(|name| {
+                name.split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .map(String::from)
+                    .chain([name.clone()])
+            })
+            .collect();
+        // npubs are random, so only their prefixes are searched
+        words.extend(profile.pubkey.to_bech32().ok());
+        let trigrams: HashSet<String> = names.iter().flat_map(|name| trigrams(name)).collect();
+
+        for word in &words {
+            self.words
+                .entry(word.clone())
+                .or_default()
+                .insert(profile.pubkey);
+        }
+        for trigram in &trigrams {
+            self.trigrams
+                .entry(trigram.clone())
+                .or_default()
+                .insert(profile.pubkey);
+        }
+        self.terms.insert(
+            profile.pubkey,
+            (words.into_iter().collect(), trigrams.into_iter().collect()),
+        );
+    }
+
+    pub fn remove(&mut self, pubkey: &PublicKey) {
+        let Some((words, trigrams)) = self.terms.remove(pubkey) else {
+            return;
+        };
+        for word in words {
+            if let Some(pubkeys) = self.words.get_mut(&word) {
+                pubkeys.remove(pubkey);
+                if pubkeys.is_empty() {
+                    self.words.remove(&word);
+                }
+            }
+        }
+        for trigram in trigrams {
+            if let Some(pubkeys) = self.trigrams.get_mut(&trigram) {
+                pubkeys.remove(pubkey);
+                if pubkeys.is_empty() {
+                    self.trigrams.remove(&trigram);
+                }
+            }
+        }
+    }
+
+    /// Returns the accounts with a word starting with the query or a name containing all of its
+    /// trigrams. An empty query returns some accounts in the order of their names.
+    pub fn search(&self, query: &str) -> HashSet<PublicKey> {
+        let query = query.to_lowercase();
+        let prefixed = self
+            .words
+            .range(query.clone()..)
+            .take_while(|(word, _)| word.starts_with(&query))
+            .flat_map(|(_, pubkeys)| pubkeys);
+        if query.is_empty() {
+            let mut found = HashSet::new();
+            for pubkey in prefixed {
+                if found.len() >= MAX_CANDIDATES {
+                    break;
+                }
+                found.insert(*pubkey);
+            }
+            return found;
+        }
+
+        let mut found: HashSet<PublicKey> = prefixed.copied().collect();
+        let mut sets = trigrams(&query)
+            .into_iter()
+            .map(|trigram| self.trigrams.get(&trigram));
+        if let Some(Some(first)) = sets.next() {
+            let mut infixed = first.clone();
+            for set in sets {
+                match set {
+                    Some(set) => infixed.retain(|pubkey| set.contains(pubkey)),
+                    None => infixed.clear(),
+                }
+            }
+            found.extend(infixed);
+        }
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// Returns the sequences of three characters of the text.
+fn trigrams(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+/// Completes `@` with known profiles, inserting a NIP-27 reference to the account.
+pub struct MentionProvider<'a> {
+    pub profiles: &'a HashMap<PublicKey, Profile>,
+    pub index: &'a ProfileIndex,
+    pub followings: &'a HashSet<PublicKey>,
+}
+
+impl MentionProvider<'_> {
+    /// Scores the names and the NIP-05 identifier of the account, or its npub by prefix as it is
+    /// random.
+    fn score(&self, query: &str, pubkey: &PublicKey) -> Option<usize> {
+        let names = self.profiles.get(pubkey).into_iter().flat_map(|profile| {
+            [
+                &profile.metadata.display_name,
+                &profile.metadata.name,
+                &profile.metadata.nip05,
+            ]
+            .into_iter()
+            .flatten()
+        });
+        let npub = pubkey.to_bech32().ok();
+        names
+            .filter_map(|name| fuzzy_score(query, name))
+            .chain(npub.filter(|npub| npub.starts_with(query)).map(|_| 0))
+            .min()
+    }
+}
+
+impl CompletionProvider for MentionProvider<'_> {
+    fn prefix(&self) -> char {
+        '@'
+    }
+
+    fn complete(&self, query: &str) -> Vec<Candidate> {
+        // Accounts I follow are few, so that they are matched fuzzily even without a profile
+        let mut pubkeys = self.index.search(query);
+        pubkeys.extend(self.followings);
+        let mut matches: Vec<(usize, bool, String, PublicKey)> = pubkeys
+            .into_iter()
+            .filter_map(|pubkey| {
+                let score = self.score(query, &pubkey)?;
+                let label = self
+                    .profiles
+                    .get(&pubkey)
+                    .map(Profile::name)
+                    .unwrap_or_else(|| shorten_hex(&pubkey.to_string()));
+                // Accounts I follow come first among equally good matches
+                Some((score, !self.followings.contains(&pubkey), label, pubkey))
+            })
+            .collect();
+        matches.sort();
+
+        matches
+            .into_iter()
+            .take(MAX_CANDIDATES)
+            .filter_map(|(_, _, label, pubkey)| {
+                let npub = pubkey.to_bech32().ok()?;
+                Some(Candidate {
+                    label,
+                    detail: shorten_hex(&pubkey.to_string()),
+                    replacement: format!("nostr:{npub}"),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Sorts matches by their score, keeping the given order among equal scores.
+fn best_matches<T>(query: &str, items: impl Iterator<Item = (String, T)>) -> Vec<T> {
+    let mut matches: Vec<(usize, usize, T)> = items
+        .enumerate()
+        .filter_map(|(i, (text, item))| Some((fuzzy_score(query, &text)?, i, item)))
+        .collect();
+    matches.sort_by_key(|(score, i, _)| (*score, *i));
+    matches
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|(_, _, item)| item)
+        .collect()
+}
+
+/// Hashtags seen in notes, the most recent first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentHashtags {
+    capacity: usize,
+    hashtags: VecDeque<String>,
+}
+
+impl Default for RecentHashtags {
+    fn default() -> Self {
+        Self::new(MAX_RECENT_HASHTAGS)
+    }
+}
+
+impl RecentHashtags {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            hashtags: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Remembers the `t` tags of the event, which are lowercase by NIP-24.
+    pub fn add(&mut self, event: &Event) {
+        for tag in &event.tags {
+            let Tag::Hashtag(hashtag) = tag else {
+                continue;
+            };
+            let hashtag = hashtag.to_lowercase();
+            if let Some(i) = self.hashtags.iter().position(|h| *h == hashtag) {
+                self.hashtags.remove(i);
+            } else if self.hashtags.len() == self.capacity {
+                self.hashtags.pop_back();
+            }
+            self.hashtags.push_front(hashtag);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.hashtags.iter()
+    }
+}
+
+/// Completes `#` with recently seen hashtags.
+pub struct HashtagProvider<'a> {
+    pub recent: &'a RecentHashtags,
+}
+
+impl CompletionProvider for HashtagProvider<'_> {
+    fn prefix(&self) -> char {
+        '#'
+    }
+
+    fn complete(&self, query: &str) -> Vec<Candidate> {
+        let hashtags = self.recent.iter().map(|h| (h.clone(), h));
+        best_matches(query, hashtags)
+            .into_iter()
+            .map(|hashtag| Candidate {
+                label: format!("#{hashtag}"),
+                detail: String::new(),
+                replacement: format!("#{hashtag}"),
+            })
+            .collect()
+    }
+}
+
+/// Custom emojis (NIP-30) seen in events, keyed by shortcode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomEmojis {
+    emojis: BTreeMap<String, UncheckedUrl>,
+}
+
+impl CustomEmojis {
+    pub fn add(&mut self, event: &Event) {
+        for tag in &event.tags {
+            if let Tag::Emoji { shortcode, url } = tag {
+                self.emojis.insert(shortcode.clone(), url.clone());
+            }
+        }
+    }
+
+    /// Emoji tags for the custom emojis used in the content, except the ones already tagged.
+    pub fn tags(&self, content: &str, tags: &[Tag]) -> Vec<Tag> {
+        let pattern = Regex::new(r":(\w+):").unwrap();
+        let mut tagged: HashSet<&str> = tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Emoji { shortcode, .. } => Some(shortcode.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        pattern
+            .captures_iter(content)
+            .filter_map(|capture| self.emojis.get_key_value(capture.get(1)?.as_str()))
+            .filter(|(shortcode, _)| tagged.insert(shortcode.as_str()))
+            .map(|(shortcode, url)| Tag::Emoji {
+                shortcode: shortcode.clone(),
+                url: url.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Completes `:` with the shortcodes of common emojis and custom emojis.
+pub struct EmojiProvider<'a> {
+    pub custom: &'a CustomEmojis,
+}
+
+impl CompletionProvider for EmojiProvider<'_> {
+    fn prefix(&self) -> char {
+        ':'
+    }
+
+    fn complete(&self, query: &str) -> Vec<Candidate> {
+        // Avoids popping up at every colon, e.g. in `:)`
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let builtin = EMOJIS.iter().map(|(shortcode, emoji)| {
+            let candidate = Candidate {
+                label: format!(":{shortcode}:"),
+                detail: String::from(*emoji),
+                replacement: String::from(*emoji),
+            };
+            (String::from(*shortcode), candidate)
+        });
+        let custom = self.custom.emojis.keys().map(|shortcode| {
+            let candidate = Candidate {
+                label: format!(":{shortcode}:"),
+                detail: i18n::tr("custom"),
+                replacement: format!(":{shortcode}:"),
+            };
+            (shortcode.clone(), candidate)
+        });
+        best_matches(query, builtin.chain(custom))
+    }
+}
+
+/// Candidates for the trigger word under the cursor and the selected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub trigger: Trigger,
+    pub candidates: Vec<Candidate>,
+    pub selected: usize,
+}
+
+impl Completion {
+    /// Completes the word before the cursor with the first provider whose prefix it starts with.
+    pub fn new(line: &str, cursor: usize, providers: &[&dyn CompletionProvider]) -> Option<Self> {
+        providers.iter().find_map(|provider| {
+            let trigger = Trigger::find(line, cursor, provider.prefix())?;
+            let candidates = provider.complete(&trigger.query);
+            (!candidates.is_empty()).then_some(Self {
+                trigger,
+                candidates,
+                selected: 0,
+            })
+        })
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.candidates.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.candidates.len() - 1) % self.candidates.len();
+    }
+
+    pub fn selected(&self) -> &Candidate {
+        &self.candidates[self.selected]
+    }
+}
+
+/// `t` tags for the hashtags in the content, except the ones already tagged.
+pub fn hashtag_tags(content: &str, tags: &[Tag]) -> Vec<Tag> {
+    let pattern = Regex::new(r"(?:^|\s)#(\w+)").unwrap();
+    let mut tagged: HashSet<String> = tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Hashtag(hashtag) => Some(hashtag.to_lowercase()),
+            _ => None,
+        })
+        .collect();
+
+    pattern
+        .captures_iter(content)
+        .map(|capture| capture[1].to_lowercase())
+        .filter(|hashtag| tagged.insert(hashtag.clone()))
+        .map(Tag::Hashtag)
+        .collect()
+}
+
+/// P-tags for the accounts mentioned in the content, except the ones already tagged.
+pub fn mention_tags(content: &str, tags: &[Tag]) -> Vec<Tag> {
+    let pattern = Regex::new(r"nostr:((?:npub|nprofile)1[a-z0-9]+)").unwrap();
+    let mut tagged: HashSet<PublicKey> = tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::PublicKey { public_key, .. } => Some(*public_key),
+            _ => None,
+        })
+        .collect();
+
+    pattern
+        .captures_iter(content)
+        .filter_map(|capture| match NostrTarget::parse(&capture[1]) {
+            Ok(NostrTarget::Profile(pubkey)) => Some(pubkey),
+            _ => None,
+        })
+        .filter(|pubkey| tagged.insert(*pubkey))
+        .map(Tag::public_key)
+        .collect()
+}
+
+/// Q-tags for the notes quoted in the content (NIP-18), except the ones already tagged.
+pub fn quote_tags(content: &str, tags: &[Tag]) -> Vec<Tag> {
+    let pattern = Regex::new(r"nostr:((?:note|nevent)1[a-z0-9]+)").unwrap();
+    let mut tagged: HashSet<String> = tags
+        .iter()
+        .map(Tag::as_vec)
+        .filter(|values| values.len() > 1 && values[0] == "q")
+        .map(|values| values[1].clone())
+        .collect();
+
+    pattern
+        .captures_iter(content)
+        .filter_map(|capture| match NostrTarget::parse(&capture[1]) {
+            Ok(NostrTarget::Event(id)) => Some(id.to_hex()),
+            _ => None,
+        })
+        .filter(|id| tagged.insert(id.clone()))
+        .map(|id| {
+            Tag::Generic(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::Q)),
+                vec![id],
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("@ali", 4, Some((0, "ali")))]
+    #[case("hi @ali", 7, Some((3, "ali")))]
+    #[case("hi @", 4, Some((3, "")))]
+    #[case("hi @ali and", 7, Some((3, "ali")))]
+    #[case("hi @ali and", 11, None)]
+    #[case("mail@example", 12, None)]
+    #[case("hi ali", 6, None)]
+    #[case("こんにちは @あり", 9, Some((6, "あり")))]
+    fn test_trigger_find(
+        #[case] line: &str,
+        #[case] cursor: usize,
+        #[case] expected: Option<(usize, &str)>,
+    ) {
+        let trigger = Trigger::find(line, cursor, '@');
+        assert_eq!(
+            trigger.map(|t| (t.start, t.query)),
+            expected.map(|(start, query)| (start, String::from(query)))
+        );
+    }
+
+    #[rstest]
+    #[case("", "alice", Some(0))]
+    #[case("ali", "Alice", Some(0))]
+    #[case("ace", "alice", Some(2))]
+    #[case("lc", "alice", Some(2))]
+    #[case("bob", "alice", None)]
+    fn test_fuzzy_score(#[case] query: &str, #[case] text: &str, #[case] expected: Option<usize>) {
+        assert_eq!(fuzzy_score(query, text), expected);
+    }
+
+    fn profile(keys: &Keys, name: &str) -> (PublicKey, Profile) {
+        let metadata = Metadata::new().name(name);
+        (
+            keys.public_key(),
+            Profile::new(keys.public_key(), Timestamp::now(), metadata),
+        )
+    }
+
+    fn index(profiles: &HashMap<PublicKey, Profile>) -> ProfileIndex {
+        let mut index = ProfileIndex::default();
+        for profile in profiles.values() {
+            index.insert(profile);
+        }
+        index
+    }
+
+    #[rstest]
+    #[case("ali", &["alice", "alicia"])]
+    #[case("LIC", &["alice", "alicia"])]
+    #[case("cia", &["alicia"])]
+    #[case("example", &["bob"])]
+    #[case("bob@", &["bob"])]
+    #[case("bo", &["bob"])]
+    #[case("ace", &[])]
+    fn test_profile_index(#[case] query: &str, #[case] expected: &[&str]) {
+        let keys: Vec<Keys> = (0..3).map(|_| Keys::generate()).collect();
+        let (_, alice) = profile(&keys[0], "alice");
+        let (_, alicia) = profile(&keys[1], "Alicia");
+        let bob = Profile::new(
+            keys[2].public_key(),
+            Timestamp::now(),
+            Metadata::new().name("bob").nip05("bob@example.com"),
+        );
+        let names = HashMap::from([
+            (keys[0].public_key(), "alice"),
+            (keys[1].public_key(), "alicia"),
+            (keys[2].public_key(), "bob"),
+        ]);
+        let mut index = ProfileIndex::default();
+        for profile in [&alice, &alicia, &bob] {
+            index.insert(profile);
+        }
+
+        let mut found: Vec<&str> = index
+            .search(query)
+            .iter()
+            .map(|pubkey| names[pubkey])
+            .collect();
+        found.sort();
+        assert_eq!(found, expected);
+
+        // npubs are searched by prefix
+        let npub = keys[0].public_key().to_bech32().unwrap();
+        assert_eq!(
+            index.search(&npub[..12]).contains(&keys[0].public_key()),
+            true
+        );
+    }
+
+    #[rstest]
+    fn test_profile_index_update() {
+        let keys = Keys::generate();
+        let (_, alice) = profile(&keys, "alice");
+        let (_, bob) = profile(&keys, "bob");
+        let mut index = ProfileIndex::default();
+        index.insert(&alice);
+        index.insert(&bob);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.search("ali"), HashSet::new());
+        assert_eq!(index.search("bob"), HashSet::from([keys.public_key()]));
+        assert_eq!(index.search(""), HashSet::from([keys.public_key()]));
+
+        index.remove(&keys.public_key());
+        assert!(index.is_empty());
+        assert_eq!(index.search(""), HashSet::new());
+    }
+
+    #[rstest]
+    fn test_mention_provider() {
+        let (alice, alicia, bob) = (Keys::generate(), Keys::generate(), Keys::generate());
+        let profiles = HashMap::from([
+            profile(&alice, "alice"),
+            profile(&alicia, "alicia"),
+            profile(&bob, "bob"),
+        ]);
+        let followings = HashSet::from([alicia.public_key()]);
+        let index = index(&profiles);
+        let provider = MentionProvider {
+            profiles: &profiles,
+            index: &index,
+            followings: &followings,
+        };
+
+        let candidates = provider.complete("ali");
+        let labels: Vec<&str> = candidates.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["@alicia", "@alice"]);
+        assert_eq!(
+            candidates[0].replacement,
+            format!("nostr:{}", alicia.public_key().to_bech32().unwrap())
+        );
+        assert_eq!(provider.complete("xyz"), vec![]);
+    }
+
+    #[rstest]
+    fn test_completion() {
+        let alice = Keys::generate();
+        let profiles = HashMap::from([profile(&alice, "alice")]);
+        let followings = HashSet::new();
+        let index = index(&profiles);
+        let provider = MentionProvider {
+            profiles: &profiles,
+            index: &index,
+            followings: &followings,
+        };
+
+        let mut completion = Completion::new("hi @al", 6, &[&provider]).unwrap();
+        assert_eq!(completion.trigger.start, 3);
+        completion.select_next();
+        completion.select_previous();
+        assert_eq!(completion.selected().label, "@alice");
+        assert_eq!(Completion::new("hi al", 5, &[&provider]), None);
+    }
+
+    fn note_with_tags(tags: Vec<Tag>) -> Event {
+        EventBuilder::text_note("", tags)
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_recent_hashtags() {
+        let mut recent = RecentHashtags::new(3);
+        recent.add(&note_with_tags(vec![
+            Tag::Hashtag(String::from("nostr")),
+            Tag::Hashtag(String::from("rust")),
+        ]));
+        recent.add(&note_with_tags(vec![Tag::Hashtag(String::from("Nostr"))]));
+        recent.add(&note_with_tags(vec![
+            Tag::Hashtag(String::from("tui")),
+            Tag::Hashtag(String::from("zap")),
+        ]));
+        let hashtags: Vec<&String> = recent.iter().collect();
+        assert_eq!(hashtags, vec!["zap", "tui", "nostr"]);
+
+        let provider = HashtagProvider { recent: &recent };
+        let labels: Vec<String> = provider
+            .complete("t")
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["#tui", "#nostr"]);
+    }
+
+    #[rstest]
+    fn test_emoji_provider() {
+        let mut custom = CustomEmojis::default();
+        custom.add(&note_with_tags(vec![Tag::Emoji {
+            shortcode: String::from("soapbox"),
+            url: UncheckedUrl::from("https://example.com/soapbox.png"),
+        }]));
+        let provider = EmojiProvider { custom: &custom };
+
+        assert_eq!(provider.complete(""), vec![]);
+        let candidates = provider.complete("sob");
+        let replacements: Vec<&str> = candidates.iter().map(|c| c.replacement.as_str()).collect();
+        assert_eq!(replacements, vec!["😭", ":soapbox:"]);
+
+        assert_eq!(
+            custom.tags("hi :soapbox: :soapbox: :unknown:", &[]),
+            vec![Tag::Emoji {
+                shortcode: String::from("soapbox"),
+                url: UncheckedUrl::from("https://example.com/soapbox.png"),
+            }]
+        );
+    }
+
+    #[rstest]
+    fn test_hashtag_tags() {
+        let tags = vec![Tag::Hashtag(String::from("nostr"))];
+        assert_eq!(
+            hashtag_tags("#Nostr and #rust, #tui#x a#b #rust", &tags),
+            vec![
+                Tag::Hashtag(String::from("rust")),
+                Tag::Hashtag(String::from("tui"))
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_mention_tags() {
+        let (alice, bob) = (Keys::generate(), Keys::generate());
+        let content = format!(
+            "hi nostr:{} and nostr:{}, nostr:{}",
+            alice.public_key().to_bech32().unwrap(),
+            bob.public_key().to_bech32().unwrap(),
+            bob.public_key().to_bech32().unwrap(),
+        );
+        let tags = vec![Tag::public_key(alice.public_key())];
+        assert_eq!(
+            mention_tags(&content, &tags),
+            vec![Tag::public_key(bob.public_key())]
+        );
+    }
+
+    #[rstest]
+    fn test_quote_tags() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("gm", []).to_event(&keys).unwrap();
+        let nevent = Nip19Event::new(note.id, ["wss://nos.lol"]);
+        let content = format!(
+            "nostr:{} nostr:{} nostr:{}",
+            note.id.to_bech32().unwrap(),
+            nevent.to_bech32().unwrap(),
+            keys.public_key().to_bech32().unwrap(),
+        );
+        let tags = quote_tags(&content, &[]);
+        assert_eq!(
+            tags.iter().map(Tag::as_vec).collect::<Vec<_>>(),
+            vec![vec![String::from("q"), note.id.to_hex()]]
+        );
+        assert_eq!(quote_tags(&content, &tags), vec![]);
+    }
+}
@@ -9,13 +9,51 @@ use crate::{
     tui::{Event, Frame},
 };
 
+mod action_menu;
+mod activity_view;
+mod author_review;
+mod confirm_dialog;
+mod discover_view;
+mod followers_view;
 mod fps;
 mod home;
+mod kind_stats_view;
+mod layout;
+mod log_viewer;
+mod outbox_view;
+mod profile_editor;
+mod prompt;
+mod relay_view;
+mod report_popup;
+mod stats_overlay;
 mod status_bar;
+mod subscription_view;
+mod tab_list;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod trace_overlay;
 
+pub use action_menu::{ActionMenu, MenuItem};
+pub use activity_view::ActivityView;
+pub use author_review::AuthorReview;
+pub use confirm_dialog::ConfirmDialog;
+pub use discover_view::DiscoverView;
+pub use followers_view::FollowersView;
 pub use fps::FpsCounter;
 pub use home::Home;
+pub use kind_stats_view::KindStatsView;
+pub use layout::{PaneLayout, Region, UiLayout};
+pub use log_viewer::LogViewer;
+pub use outbox_view::OutboxView;
+pub use profile_editor::ProfileEditor;
+pub use prompt::{Prompt, PromptKind};
+pub use relay_view::RelayView;
+pub use report_popup::ReportPopup;
+pub use stats_overlay::StatsOverlay;
 pub use status_bar::StatusBar;
+pub use subscription_view::SubscriptionView;
+pub use tab_list::TabList;
+pub use trace_overlay::TraceOverlay;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
@@ -73,6 +111,7 @@ pub trait Component {
         let r = match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste_events(text)?,
             _ => None,
         };
         Ok(r)
@@ -103,6 +142,19 @@ pub trait Component {
     fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Handle text pasted at once (bracketed paste) and produce actions if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The pasted text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    #[allow(unused_variables)]
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
@@ -116,6 +168,14 @@ pub trait Component {
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Region of the screen which the component is drawn in.
+    ///
+    /// # Returns
+    ///
+    /// * `Region` - The region, whose area is given to `draw`.
+    fn region(&self) -> Region {
+        Region::Screen
+    }
     /// Render the component on the screen. (REQUIRED)
     ///
     /// # Arguments
@@ -0,0 +1,152 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    config::{keybindings::KeyBindings, Config},
+    i18n,
+    mode::Mode,
+};
+
+/// An entry of the action menu, which sends its action when chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MenuItem {
+    pub label: String,
+    pub action: Action,
+}
+
+impl MenuItem {
+    pub fn new(label: String, action: Action) -> Self {
+        Self { label, action }
+    }
+}
+
+/// Lists the actions applicable to the selected note with their keys,
+/// so that they can be found without knowing the keybindings.
+#[derive(Default)]
+pub struct ActionMenu {
+    command_tx: Option<UnboundedSender<Action>>,
+    keybindings: KeyBindings,
+    items: Vec<MenuItem>,
+    selected: usize,
+}
+
+impl ActionMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_open(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    fn close(&mut self, chosen: Option<usize>) -> Result<()> {
+        let items = std::mem::take(&mut self.items);
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+            if let Some(item) = chosen.and_then(|i| items.into_iter().nth(i)) {
+                tx.send(item.action)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(&self, area: Rect) -> Rect {
+        let width = area.width.min(50);
+        let height = area.height.min(self.items.len() as u16 + 2);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for ActionMenu {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.keybindings = config.keybindings;
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(None)?,
+            KeyCode::Enter => self.close(Some(self.selected))?,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.items.len() - 1);
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let i = c as usize - '1' as usize;
+                if i < self.items.len() {
+                    self.close(Some(i))?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenActionMenu(items) if !self.is_open() && !items.is_empty() => {
+                self.items = items;
+                self.selected = 0;
+                Ok(Some(Action::EnterMode(Mode::Popup)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.is_open() {
+            return Ok(());
+        }
+
+        let popup = self.popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let mut spans = vec![Span::raw(format!("{} {}", i + 1, item.label))];
+                if let Some(key) = self.keybindings.key_for(Mode::Home, &item.action) {
+                    spans.push(Span::styled(
+                        format!(" ({key})"),
+                        Style::default().fg(Color::Gray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(i18n::tr("Actions: [enter] run [esc] close")),
+            )
+            .highlight_style(Style::default().reversed());
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(list, popup, &mut state);
+
+        Ok(())
+    }
+}
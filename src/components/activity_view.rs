@@ -0,0 +1,310 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use nostr_sdk::EventId;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    activity::{Activity, ActivityKind},
+    i18n,
+    mode::Mode,
+    text::truncate_width,
+};
+
+/// Widest the content of my replies is shown, leaving room for the notes replied to.
+const MAX_CONTENT_WIDTH: usize = 30;
+
+/// Lists my recent reactions, reposts and replies, to undo them by publishing deletions.
+#[derive(Default)]
+pub struct ActivityView {
+    command_tx: Option<UnboundedSender<Action>>,
+    activities: Option<Vec<Activity>>,
+    list_state: ListState,
+    /// Whether an undo waits for a confirmation, which takes the keys until it is answered
+    confirming: bool,
+}
+
+impl ActivityView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.activities.is_some()
+    }
+
+    fn open(&mut self, activities: Vec<Activity>) {
+        self.list_state
+            .select(if activities.is_empty() { None } else { Some(0) });
+        self.activities = Some(activities);
+    }
+
+    /// Replaces the activities with the ones fetched since, keeping the selected one.
+    fn refresh(&mut self, activities: Vec<Activity>) {
+        let Some(ref current) = self.activities else {
+            return;
+        };
+
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| current.get(i))
+            .and_then(|activity| {
+                activities
+                    .iter()
+                    .position(|other| other.event.id == activity.event.id)
+            });
+        self.list_state
+            .select(selected.or((!activities.is_empty()).then_some(0)));
+        self.activities = Some(activities);
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.activities = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.activities.as_ref().map_or(0, Vec::len)
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state
+            .select(Some(selected.clamp(0, self.len() as isize - 1) as usize));
+    }
+
+    /// Asks to publish a deletion of the selected activity, as deletions can't be taken back.
+    fn undo_selected(&mut self) -> Option<Action> {
+        let activity = self.activities.as_ref()?.get(self.list_state.selected()?)?;
+        let target = &activity.target;
+        let (message, action) = match activity.kind {
+            ActivityKind::Reaction => (
+                i18n::tr_args(
+                    "Delete your reaction {reaction} to {target}?",
+                    &[("reaction", &activity.event.content), ("target", target)],
+                ),
+                Action::DeleteReaction(activity.event.clone()),
+            ),
+            ActivityKind::Repost => (
+                i18n::tr_args("Delete your repost of {target}?", &[("target", target)]),
+                Action::DeleteRepost(activity.event.clone()),
+            ),
+            ActivityKind::Reply => (
+                i18n::tr_args("Delete your reply to {target}?", &[("target", target)]),
+                Action::DeleteNote(activity.event.clone()),
+            ),
+        };
+        self.confirming = true;
+        Some(Action::Confirm(message, vec![action]))
+    }
+
+    /// Drops the activity once its deletion is confirmed.
+    fn remove(&mut self, id: EventId) {
+        let Some(activities) = &mut self.activities else {
+            return;
+        };
+        let Some(i) = activities
+            .iter()
+            .position(|activity| activity.event.id == id)
+        else {
+            return;
+        };
+
+        activities.remove(i);
+        match self.list_state.selected() {
+            _ if activities.is_empty() => self.list_state.select(None),
+            Some(selected) if selected > i || selected >= activities.len() => {
+                self.list_state.select(Some(selected - 1))
+            }
+            _ => {}
+        }
+    }
+
+    fn line(activity: &Activity) -> Line<'static> {
+        let (icon, color) = match activity.kind {
+            ActivityKind::Reaction => (activity.event.content.clone(), Color::LightRed),
+            ActivityKind::Repost => (String::from("⟳"), Color::LightGreen),
+            ActivityKind::Reply => (String::from("↳"), Color::Cyan),
+        };
+        let mut spans = vec![Span::styled(format!("{icon} "), Style::default().fg(color))];
+        if activity.kind == ActivityKind::Reply {
+            let content = activity.event.content.lines().next().unwrap_or_default();
+            spans.push(Span::raw(format!(
+                "{} → ",
+                truncate_width(content, MAX_CONTENT_WIDTH)
+            )));
+        }
+        spans.push(Span::styled(
+            activity.target.clone(),
+            Style::default().fg(Color::Gray),
+        ));
+        Line::from(spans)
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(80);
+        let height = area.height.min(20);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for ActivityView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() || self.confirming {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Char('d') => return Ok(self.undo_selected()),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenMyActivity(activities) if !self.is_open() => {
+                self.open(activities);
+                Ok(Some(Action::EnterMode(Mode::Review)))
+            }
+            Action::RefreshMyActivity(activities) => {
+                self.refresh(activities);
+                Ok(None)
+            }
+            Action::DeleteReaction(event)
+            | Action::DeleteRepost(event)
+            | Action::DeleteNote(event) => {
+                self.remove(event.id);
+                Ok(None)
+            }
+            // The confirmation is answered
+            Action::LeaveMode => {
+                self.confirming = false;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref activities) = self.activities else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("My activity: [d] undo [esc] close"))
+            .padding(Padding::horizontal(1));
+
+        if activities.is_empty() {
+            f.render_widget(
+                Paragraph::new(i18n::tr("No reactions, reposts or replies yet")).block(block),
+                popup,
+            );
+            return Ok(());
+        }
+
+        let items: Vec<ListItem> = activities
+            .iter()
+            .map(|activity| ListItem::new(Self::line(activity)))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use nostr_sdk::prelude::*;
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    fn reaction(target: &str) -> Activity {
+        Activity {
+            event: EventBuilder::new(Kind::Reaction, "+", [])
+                .to_event(&Keys::generate())
+                .unwrap(),
+            kind: ActivityKind::Reaction,
+            target: String::from(target),
+        }
+    }
+
+    #[rstest]
+    fn test_undo_selected() {
+        let activities = vec![reaction("alice: gm"), reaction("bob: gn")];
+        let event = activities[1].event.clone();
+        let mut view = ActivityView::new();
+        view.update(Action::OpenMyActivity(activities)).unwrap();
+        view.handle_key_events(key(KeyCode::Down)).unwrap();
+
+        let Some(Action::Confirm(message, actions)) =
+            view.handle_key_events(key(KeyCode::Char('d'))).unwrap()
+        else {
+            panic!("undoing is not confirmed");
+        };
+        assert_eq!(message, "Delete your reaction + to bob: gn?");
+        assert_eq!(actions, [Action::DeleteReaction(event.clone())]);
+        assert_eq!(view.len(), 2);
+
+        // The dialog takes the keys until it is answered
+        assert_eq!(view.handle_key_events(key(KeyCode::Esc)).unwrap(), None);
+        assert!(view.is_open());
+
+        view.update(Action::LeaveMode).unwrap();
+        view.update(Action::DeleteReaction(event)).unwrap();
+        assert_eq!(view.len(), 1);
+        assert_eq!(view.list_state.selected(), Some(0));
+    }
+
+    #[rstest]
+    fn test_undo_cancelled() {
+        let mut view = ActivityView::new();
+        view.update(Action::OpenMyActivity(vec![reaction("alice: gm")]))
+            .unwrap();
+        view.handle_key_events(key(KeyCode::Char('d'))).unwrap();
+        view.update(Action::LeaveMode).unwrap();
+        assert_eq!(view.len(), 1);
+
+        view.handle_key_events(key(KeyCode::Esc)).unwrap();
+        assert!(!view.is_open());
+    }
+}
@@ -0,0 +1,198 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, interaction_limit::CollapsedAuthor, mode::Mode};
+
+/// Lists authors whose replies are collapsed, to whitelist or mute them permanently.
+#[derive(Default)]
+pub struct AuthorReview {
+    command_tx: Option<UnboundedSender<Action>>,
+    authors: Option<Vec<CollapsedAuthor>>,
+    list_state: ListState,
+}
+
+impl AuthorReview {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.authors.is_some()
+    }
+
+    fn open(&mut self, authors: Vec<CollapsedAuthor>) {
+        self.list_state
+            .select(if authors.is_empty() { None } else { Some(0) });
+        self.authors = Some(authors);
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.authors = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.authors.as_ref().map_or(0, Vec::len)
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state
+            .select(Some(selected.clamp(0, self.len() as isize - 1) as usize));
+    }
+
+    fn whitelist_selected(&mut self) -> Result<()> {
+        let (Some(authors), Some(i)) = (&mut self.authors, self.list_state.selected()) else {
+            return Ok(());
+        };
+
+        // Whitelisted authors are no longer collapsed
+        let author = authors.remove(i);
+        if authors.is_empty() {
+            self.list_state.select(None);
+        } else if i >= authors.len() {
+            self.list_state.select(Some(authors.len() - 1));
+        }
+
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::WhitelistAuthor(author.pubkey))?;
+            tx.send(Action::SystemMessage(i18n::tr_args(
+                "[Whitelisted] {name}",
+                &[("name", &author.name)],
+            )))?;
+        }
+
+        Ok(())
+    }
+
+    fn mute_selected(&mut self) -> Result<()> {
+        let (Some(authors), Some(i)) = (&mut self.authors, self.list_state.selected()) else {
+            return Ok(());
+        };
+
+        let author = &mut authors[i];
+        if author.muted {
+            return Ok(());
+        }
+
+        author.muted = true;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::MuteAuthor(author.pubkey))?;
+            tx.send(Action::SystemMessage(i18n::tr_args(
+                "[Muted] {name}",
+                &[("name", &author.name)],
+            )))?;
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(70);
+        let height = area.height.min(20);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for AuthorReview {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Char('w') => self.whitelist_selected()?,
+            KeyCode::Char('m') => self.mute_selected()?,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenCollapsedAuthors(authors) if !self.is_open() => {
+                self.open(authors);
+                Ok(Some(Action::EnterMode(Mode::Review)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref authors) = self.authors else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr(
+                "Collapsed authors: [w]hitelist [m]ute [esc] close",
+            ))
+            .padding(Padding::horizontal(1));
+
+        if authors.is_empty() {
+            f.render_widget(
+                Paragraph::new(i18n::tr("No collapsed authors")).block(block),
+                popup,
+            );
+            return Ok(());
+        }
+
+        let items: Vec<ListItem> = authors
+            .iter()
+            .map(|author| {
+                let mut spans = vec![
+                    Span::styled(author.name.clone(), Style::default().bold()),
+                    Span::raw(format!(
+                        " {}",
+                        i18n::plural(
+                            "{count, plural, one {# reply} other {# replies}}",
+                            author.replies as u64
+                        )
+                    )),
+                ];
+                if author.muted {
+                    spans.push(Span::styled(
+                        i18n::tr(" (muted)"),
+                        Style::default().fg(Color::LightRed),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
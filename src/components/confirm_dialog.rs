@@ -0,0 +1,199 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode};
+
+#[derive(Default)]
+pub struct ConfirmDialog {
+    command_tx: Option<UnboundedSender<Action>>,
+    message: Option<String>,
+    actions: Vec<Action>,
+    yes_selected: bool,
+}
+
+impl ConfirmDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.message.is_some()
+    }
+
+    fn open(&mut self, message: String, actions: Vec<Action>) {
+        self.message = Some(message);
+        self.actions = actions;
+        self.yes_selected = false;
+    }
+
+    fn close(&mut self, confirmed: bool) -> Result<()> {
+        self.message = None;
+        let actions = std::mem::take(&mut self.actions);
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+            if confirmed {
+                for action in actions {
+                    tx.send(action)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(60);
+        let height = area.height.min(6);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for ConfirmDialog {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => self.close(true)?,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => self.close(false)?,
+            KeyCode::Enter => self.close(self.yes_selected)?,
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Tab
+            | KeyCode::BackTab
+            | KeyCode::Char('h')
+            | KeyCode::Char('l') => self.yes_selected = !self.yes_selected,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Confirm(message, actions) if !self.is_open() => {
+                self.open(message, actions);
+                Ok(Some(Action::EnterMode(Mode::Confirm)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref message) = self.message else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let button_style = |selected: bool| {
+            if selected {
+                Style::default().bold().reversed()
+            } else {
+                Style::default()
+            }
+        };
+        let buttons = Line::from(vec![
+            Span::styled(i18n::tr(" [Y]es "), button_style(self.yes_selected)),
+            Span::raw("  "),
+            Span::styled(i18n::tr(" [N]o "), button_style(!self.yes_selected)),
+        ])
+        .alignment(Alignment::Center);
+        let text = Text::from(vec![Line::from(message.as_str()), Line::default(), buttons]);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Confirm"))
+            .padding(Padding::horizontal(1));
+        f.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+            popup,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::components::testing::render;
+
+    fn open() -> (ConfirmDialog, mpsc::UnboundedReceiver<Action>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut dialog = ConfirmDialog::new();
+        dialog.register_action_handler(tx).unwrap();
+        let action = Action::Confirm(String::from("Repost the note?"), vec![Action::Quit]);
+        assert_eq!(
+            dialog.update(action).unwrap(),
+            Some(Action::EnterMode(Mode::Confirm))
+        );
+        (dialog, rx)
+    }
+
+    fn received(rx: &mut mpsc::UnboundedReceiver<Action>) -> Vec<Action> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    #[rstest]
+    #[case(&[KeyCode::Char('y')], vec![Action::LeaveMode, Action::Quit])]
+    #[case(&[KeyCode::Char('N')], vec![Action::LeaveMode])]
+    #[case(&[KeyCode::Esc], vec![Action::LeaveMode])]
+    // No is selected at first
+    #[case(&[KeyCode::Enter], vec![Action::LeaveMode])]
+    #[case(&[KeyCode::Left, KeyCode::Enter], vec![Action::LeaveMode, Action::Quit])]
+    #[case(&[KeyCode::Tab, KeyCode::Char('l'), KeyCode::Enter], vec![Action::LeaveMode])]
+    fn test_keys(#[case] codes: &[KeyCode], #[case] expected: Vec<Action>) {
+        let (mut dialog, mut rx) = open();
+        for code in codes {
+            let key = KeyEvent::new(*code, KeyModifiers::empty());
+            assert_eq!(dialog.handle_key_events(key).unwrap(), None);
+        }
+        assert_eq!(received(&mut rx), expected);
+        assert!(!dialog.is_open());
+    }
+
+    #[rstest]
+    fn test_ignores_keys_when_closed() {
+        let (mut dialog, mut rx) = open();
+        dialog
+            .handle_key_events(KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()))
+            .unwrap();
+        received(&mut rx);
+
+        let key = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::empty());
+        dialog.handle_key_events(key).unwrap();
+        assert_eq!(received(&mut rx), vec![]);
+    }
+
+    #[rstest]
+    fn test_one_dialog_at_a_time() {
+        let (mut dialog, _rx) = open();
+        let other = Action::Confirm(String::from("Quit?"), vec![]);
+        assert_eq!(dialog.update(other).unwrap(), None);
+
+        let lines = render(&mut dialog, 60, 10);
+        assert!(lines.iter().any(|line| line.contains("Repost the note?")));
+        assert!(lines.iter().any(|line| line.contains("[Y]es")));
+    }
+}
@@ -0,0 +1,274 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action, discover::Suggestion, i18n, mode::Mode, timeline::TimelineTabType,
+    widgets::PublicKey,
+};
+
+const PREVIEW_HEIGHT: u16 = 7;
+
+/// Suggests accounts followed by many of my followings, to follow them with a key.
+#[derive(Default)]
+pub struct DiscoverView {
+    command_tx: Option<UnboundedSender<Action>>,
+    suggestions: Option<Vec<Suggestion>>,
+    list_state: ListState,
+    /// Whether unfollowing waits for a confirmation, which takes the keys until it is answered
+    confirming: bool,
+}
+
+impl DiscoverView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.suggestions.is_some()
+    }
+
+    /// Shows the suggestions, keeping the selection if they are updated while open.
+    fn open(&mut self, suggestions: Vec<Suggestion>) {
+        let selected = match self.list_state.selected() {
+            Some(i) if self.is_open() => Some(i.min(suggestions.len().saturating_sub(1))),
+            _ => Some(0),
+        };
+        self.list_state
+            .select(selected.filter(|_| !suggestions.is_empty()));
+        self.suggestions = Some(suggestions);
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.suggestions = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.suggestions.as_ref().map_or(0, Vec::len)
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state
+            .select(Some(selected.clamp(0, self.len() as isize - 1) as usize));
+    }
+
+    fn selected(&self) -> Option<&Suggestion> {
+        self.suggestions.as_ref()?.get(self.list_state.selected()?)
+    }
+
+    fn toggle_follow(&mut self) -> Option<Action> {
+        let suggestion = self.selected()?;
+        if !suggestion.followed {
+            return Some(Action::Follow(suggestion.pubkey));
+        }
+
+        let action = Action::Confirm(
+            i18n::tr_args("Unfollow {name}?", &[("name", &suggestion.name)]),
+            vec![Action::Unfollow(suggestion.pubkey)],
+        );
+        self.confirming = true;
+        Some(action)
+    }
+
+    fn open_timeline(&mut self) -> Result<Option<Action>> {
+        let Some(pubkey) = self.selected().map(|suggestion| suggestion.pubkey) else {
+            return Ok(None);
+        };
+
+        self.close()?;
+        Ok(Some(Action::OpenTab(TimelineTabType::UserTimeline {
+            pubkey,
+        })))
+    }
+
+    fn preview(suggestion: &Suggestion) -> Text<'static> {
+        let mut text = Text::from(Line::from(vec![
+            Span::styled(suggestion.name.clone(), Style::default().bold()),
+            Span::styled(
+                format!(" {}", PublicKey::new(suggestion.pubkey).shortened()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]));
+        if let Some(ref nip05) = suggestion.nip05 {
+            text.extend(Text::styled(
+                nip05.clone(),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if let Some(ref about) = suggestion.about {
+            text.extend(Text::raw(about.clone()));
+        }
+        text
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(80);
+        let height = area.height.min(24);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for DiscoverView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() || self.confirming {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Char('f') => return Ok(self.toggle_follow()),
+            KeyCode::Enter => return self.open_timeline(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenSuggestions(suggestions) => {
+                let was_open = self.is_open();
+                self.open(suggestions);
+                if !was_open {
+                    return Ok(Some(Action::EnterMode(Mode::Review)));
+                }
+            }
+            // The confirmation is answered
+            Action::LeaveMode => self.confirming = false,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref suggestions) = self.suggestions else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr(
+                "Discover: [f]ollow or unfollow [enter] open timeline [esc] close",
+            ))
+            .padding(Padding::horizontal(1));
+
+        if suggestions.is_empty() {
+            f.render_widget(
+                Paragraph::new(i18n::tr(
+                    "No suggestions yet: contact lists of your followings are being fetched",
+                ))
+                .wrap(Wrap { trim: true })
+                .block(block),
+                popup,
+            );
+            return Ok(());
+        }
+
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+        let [list_area, preview_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(PREVIEW_HEIGHT)]).areas(inner);
+
+        let items: Vec<ListItem> = suggestions
+            .iter()
+            .map(|suggestion| {
+                let mut spans = vec![
+                    Span::styled(suggestion.name.clone(), Style::default().bold()),
+                    Span::raw(i18n::tr_args(
+                        " followed by {count} you follow",
+                        &[("count", &suggestion.followed_by)],
+                    )),
+                ];
+                if suggestion.followed {
+                    spans.push(Span::styled(
+                        i18n::tr(" (following)"),
+                        Style::default().fg(Color::LightGreen),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        if let Some(suggestion) = self.selected() {
+            f.render_widget(
+                Paragraph::new(Self::preview(suggestion))
+                    .wrap(Wrap { trim: true })
+                    .block(Block::default().borders(Borders::TOP)),
+                preview_area,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use nostr_sdk::Keys;
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    #[rstest]
+    fn test_toggle_follow() {
+        let pubkey = Keys::generate().public_key();
+        let mut view = DiscoverView::new();
+        view.update(Action::OpenSuggestions(vec![Suggestion::new(pubkey, 3)]))
+            .unwrap();
+        assert_eq!(
+            view.handle_key_events(key(KeyCode::Char('f'))).unwrap(),
+            Some(Action::Follow(pubkey))
+        );
+
+        view.suggestions.as_mut().unwrap()[0].followed = true;
+        let Some(Action::Confirm(_, actions)) =
+            view.handle_key_events(key(KeyCode::Char('f'))).unwrap()
+        else {
+            panic!("unfollowing is not confirmed");
+        };
+        assert_eq!(actions, [Action::Unfollow(pubkey)]);
+
+        // The dialog takes the keys until it is answered
+        assert_eq!(view.handle_key_events(key(KeyCode::Esc)).unwrap(), None);
+        assert!(view.is_open());
+        view.update(Action::LeaveMode).unwrap();
+        view.handle_key_events(key(KeyCode::Esc)).unwrap();
+        assert!(!view.is_open());
+    }
+}
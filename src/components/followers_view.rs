@@ -0,0 +1,210 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    followers::FollowerList,
+    i18n,
+    mode::Mode,
+    text::{compact_number, shorten_hex},
+    timeline::TimelineTabType,
+};
+
+/// Lists the followers of an account as they are fetched, to open their timelines.
+#[derive(Default)]
+pub struct FollowersView {
+    command_tx: Option<UnboundedSender<Action>>,
+    list: Option<FollowerList>,
+    list_state: ListState,
+}
+
+impl FollowersView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.list.is_some()
+    }
+
+    fn open(&mut self, list: FollowerList) {
+        self.list_state.select(if list.followers.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.list = Some(list);
+    }
+
+    /// Replaces the followers with the ones fetched since, keeping the selected one.
+    fn refresh(&mut self, list: FollowerList) {
+        let Some(ref current) = self.list else {
+            return;
+        };
+
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| current.followers.get(i))
+            .and_then(|follower| {
+                list.followers
+                    .iter()
+                    .position(|other| other.pubkey == follower.pubkey)
+            });
+        self.list_state
+            .select(selected.or((!list.followers.is_empty()).then_some(0)));
+        self.list = Some(list);
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.list = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.list.as_ref().map_or(0, |list| list.followers.len())
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state
+            .select(Some(selected.clamp(0, self.len() as isize - 1) as usize));
+    }
+
+    fn open_selected(&mut self) -> Result<()> {
+        let pubkey = self
+            .list
+            .as_ref()
+            .zip(self.list_state.selected())
+            .and_then(|(list, i)| list.followers.get(i))
+            .map(|follower| follower.pubkey);
+        let Some(pubkey) = pubkey else {
+            return Ok(());
+        };
+
+        self.close()?;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::OpenTab(TimelineTabType::UserTimeline { pubkey }))?;
+        }
+
+        Ok(())
+    }
+
+    fn title(list: &FollowerList) -> String {
+        let count = compact_number(list.followers.len() as u64);
+        if list.fetching {
+            i18n::tr_args(
+                "Followers of {name}: {count} so far, fetching…",
+                &[("name", &list.name), ("count", &count)],
+            )
+        } else {
+            i18n::tr_args(
+                "Followers of {name}: about {count} [enter] open [esc] close",
+                &[("name", &list.name), ("count", &count)],
+            )
+        }
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(80);
+        let height = area.height.min(20);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for FollowersView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Enter => self.open_selected()?,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenFollowers(list) if !self.is_open() => {
+                self.open(list);
+                Ok(Some(Action::EnterMode(Mode::Review)))
+            }
+            Action::RefreshFollowers(list) => {
+                self.refresh(list);
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref list) = self.list else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Self::title(list))
+            .padding(Padding::horizontal(1));
+
+        if list.followers.is_empty() {
+            let message = if list.fetching {
+                i18n::tr("Asking relays for the contact lists which follow the account…")
+            } else {
+                i18n::tr("No followers found on the relays")
+            };
+            f.render_widget(Paragraph::new(message).block(block), popup);
+            return Ok(());
+        }
+
+        let items: Vec<ListItem> = list
+            .followers
+            .iter()
+            .map(|follower| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(follower.name.clone()),
+                    Span::styled(
+                        format!(" {}", shorten_hex(&follower.pubkey.to_string())),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
@@ -1,146 +1,2315 @@
-use std::cmp::Reverse;
 use std::collections::HashSet;
 use std::collections::{hash_map::Entry, HashMap};
+use std::time::Instant;
 
+use chrono::Local;
 use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
 use nostr_sdk::prelude::*;
 use ratatui::{prelude::*, widgets, widgets::*};
-use sorted_vec::ReverseSortedSet;
 use tokio::sync::mpsc::UnboundedSender;
 use tui_textarea::TextArea;
-use tui_widget_list::List;
+use tui_widget_list::{List, Listable};
 
-use super::{Component, Frame};
-use crate::text::shorten_hex;
+use super::{Component, Frame, MenuItem, PaneLayout, PromptKind, Region, UiLayout};
+use crate::text::{self, compact_number, shorten_hex, InputStats};
 use crate::{
     action::Action,
+    activity::{self, Activity, ActivityKind},
+    away::Away,
+    clock_skew::{self, FuturePolicy},
+    completion::{
+        self, Completion, CompletionProvider, CustomEmojis, EmojiProvider, HashtagProvider,
+        MentionProvider, ProfileIndex, RecentHashtags,
+    },
     config::Config,
-    nostr::{nip10::ReplyTagsBuilder, Profile, SortableEvent},
+    discover::{self, Suggestion},
+    export,
+    followers::{Follower, FollowerCount, FollowerList},
+    i18n,
+    interaction_limit::{AuthorLists, CollapsedAuthor, InteractionLimiter, TemporaryMutes},
+    link_preview::{self, PreviewState},
+    mode::Mode,
+    nostr::{
+        nip09,
+        nip10::{ReplyTagsBuilder, ThreadTags},
+        nip18::{self, Reposted},
+        nip27, nip28, nip36,
+        nip57::{Zap, ZapSplit},
+        nip75::{ZapGoal, ZAP_GOAL_KIND},
+        nip94::{self, FileMetadata},
+        Engagement, Profile,
+    },
+    outbox::{Delivery, Outbox},
+    pagination::GapDetector,
+    profile_queue::{Priority, ProfileQueue, ProfileRequests},
+    session::{Session, TabSession},
+    spam_filter::{FilterAction, Score, SpamFilter},
+    stats::NoteStoreStats,
+    timeline::{TimelineTab, TimelineTabType},
+    timestamp::{self, TimestampMode},
+    translate::Translation,
+    viewport::{Placement, ViewportMove},
+    vim::Vim,
+    widgets::LayoutCache,
     widgets::ScrollableList,
+    widgets::Scrolled,
+    widgets::TabBar,
     widgets::TextNote,
 };
 
+/// Number of accounts suggested in the discover view
+const SUGGESTION_LIMIT: usize = 50;
+
+/// Profiles requested on each tick, so that authors on the screen are not kept waiting behind
+/// thousands of others
+const PROFILES_PER_TICK: usize = 20;
+
+/// Seconds an author is muted for by "Mute the author for an hour"
+const HOUR_MUTE_SECS: u64 = 60 * 60;
+
+/// Notes hidden by the spam filter kept to be shown on request, dropping the oldest beyond it
+const MAX_HIDDEN_NOTES: usize = 1000;
+
 #[derive(Default)]
 pub struct Home<'a> {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
-    list_state: tui_widget_list::ListState,
-    notes: ReverseSortedSet<SortableEvent>,
+    tabs: Vec<TimelineTab>,
+    active_tab: usize,
+    events: HashMap<EventId, Event>,
     profiles: HashMap<PublicKey, Profile>,
+    /// Names of the profiles, to look up mentions
+    profile_index: ProfileIndex,
+    requested_events: HashSet<EventId>,
+    requested_profiles: ProfileRequests,
+    profile_queue: ProfileQueue,
     reactions: HashMap<EventId, HashSet<Event>>,
     reposts: HashMap<EventId, HashSet<Event>>,
     zap_receipts: HashMap<EventId, HashSet<Event>>,
+    /// Zap goals which notes raise funds for
+    goals: HashMap<EventId, ZapGoal>,
+    /// Whether my activity is listed, which is listed again as older activity arrives
+    activity_shown: bool,
+    /// Followers being counted while they are listed
+    followers: Option<FollowerCount>,
+    /// Whether the listed followers are out of date, e.g. as their profiles arrived
+    followers_changed: bool,
+    /// Whether the listed followers were still being fetched
+    followers_fetching: bool,
+    /// Zaps decoded from the receipts, by the ids of the receipts
+    zaps: HashMap<EventId, Zap>,
     show_input: bool,
     input: TextArea<'a>,
     reply_to: Option<Event>,
+    /// Accounts notified of the reply, except me
+    recipients: Vec<PublicKey>,
+    /// Recipients removed before sending the reply
+    removed_recipients: Vec<PublicKey>,
+    /// Recipient selected while editing the recipients
+    recipient_cursor: Option<usize>,
+    /// Candidates for the word being typed in the composer
+    completion: Option<Completion>,
+    /// URLs of files attached to the note being composed, with their `imeta` tags
+    attachments: Vec<(String, Tag)>,
+    /// Bare NIP-19 ids in the draft which are sent as `nostr:` mentions
+    linked_ids: HashSet<String>,
+    /// Seconds after which the note being composed expires (NIP-40)
+    expiration: Option<u64>,
+    /// Modal editing in the composer, if enabled
+    vim: Option<Vim>,
+    /// Hashtags and custom emojis seen in notes, for completion
+    recent_hashtags: RecentHashtags,
+    custom_emojis: CustomEmojis,
+    pubkey: Option<PublicKey>,
+    /// My latest contact list, which follows and unfollows are based on
+    contact_list: Option<Event>,
+    /// My latest list of public chats (NIP-51), listing the channels I joined
+    public_chats: Option<Event>,
+    channels: nip28::Channels,
+    interaction_limiter: InteractionLimiter,
+    /// When the app quit last time or the terminal lost focus, whose newer notes are marked as
+    /// new in the Home tab
+    last_seen: Option<Timestamp>,
+    away: Away,
+    /// Authors muted for an hour or this session, whose notes are collapsed everywhere
+    temporary_mutes: TemporaryMutes,
+    /// Notes added to events by plugins
+    annotations: HashMap<EventId, Vec<String>>,
+    /// Notes whose content warnings are dismissed in this session
+    revealed: HashSet<EventId>,
+    /// Long notes shown in full in this session
+    expanded: HashSet<EventId>,
+    /// Translations of notes fetched in this session
+    translations: HashMap<EventId, Translation>,
+    /// Notes whose translations are shown, including the ones being translated
+    shown_translations: HashSet<EventId>,
+    /// Previews of links fetched in this session, by URL
+    link_previews: HashMap<String, PreviewState>,
+    /// Notes whose link previews are shown
+    shown_previews: HashSet<EventId>,
+    /// My notes published in this session, which are shown before relays echo them
+    published: Outbox,
+    /// Published notes which relays echoed
+    echoed: HashSet<EventId>,
+    spam_filter: SpamFilter,
+    /// Whether to show notes filtered as spam
+    show_filtered: bool,
+    /// Notes kept out of tabs by the spam filter
+    hidden_notes: Vec<(TimelineTabType, Event)>,
+    /// Tabs and times of reposts whose notes are being fetched
+    pending_reposts: HashMap<EventId, Vec<(TimelineTabType, Timestamp)>>,
+    /// Note whose engagement is shown, to update names as profiles arrive
+    engagement_note: Option<EventId>,
+    /// Accounts suggested in the discover view, to update them as profiles arrive
+    suggestions: Option<Vec<Suggestion>>,
+    timestamp_mode: TimestampMode,
+    gap_detector: GapDetector,
+    layout: PaneLayout,
+    ui: UiLayout,
+    /// Unread notes of the current tab last shown in the status bar
+    unread: usize,
+    /// Wrapped content of notes, kept until the terminal is resized or the theme changes
+    layout_cache: LayoutCache,
 }
 
-impl Home<'_> {
-    pub fn new() -> Self {
-        Self::default()
+impl Home<'_> {
+    pub fn new() -> Self {
+        Self {
+            tabs: vec![TimelineTab::new(TimelineTabType::Home)],
+            ..Self::default()
+        }
+    }
+
+    fn tab(&self) -> &TimelineTab {
+        &self.tabs[self.active_tab]
+    }
+
+    fn tab_mut(&mut self) -> &mut TimelineTab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    /// Checks that there is a tab, the active one exists, no tab is open twice and every tab
+    /// keeps its invariants, describing the first broken one. Any sequence of actions should keep
+    /// them.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.tabs.is_empty() {
+            return Err(String::from("No tab is open"));
+        }
+        if self.active_tab >= self.tabs.len() {
+            return Err(format!(
+                "Tab {} is active out of {}",
+                self.active_tab,
+                self.tabs.len()
+            ));
+        }
+        for (i, tab) in self.tabs.iter().enumerate() {
+            if self.tabs[..i]
+                .iter()
+                .any(|other| other.tab_type == tab.tab_type)
+            {
+                return Err(format!("{:?} is open twice", tab.tab_type));
+            }
+            tab.check_invariants()?;
+        }
+
+        Ok(())
+    }
+
+    fn tab_title(&self, tab_type: &TimelineTabType) -> String {
+        match tab_type {
+            TimelineTabType::UserTimeline { pubkey } => {
+                tab_type.title(self.profiles.get(pubkey).map(|profile| profile.name()))
+            }
+            TimelineTabType::Channel { id } => tab_type.title(self.channels.name(id)),
+            _ => tab_type.title(None),
+        }
+    }
+
+    fn open_tab(&mut self, tab_type: TimelineTabType) -> Result<()> {
+        if let Some(i) = self.tabs.iter().position(|tab| tab.tab_type == tab_type) {
+            self.active_tab = i;
+            return Ok(());
+        }
+
+        let mut tab = TimelineTab::new(tab_type.clone());
+        tab.follow = self.config.follow_new_notes;
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::SubscribeTimeline(tab_type))?;
+        }
+
+        Ok(())
+    }
+
+    fn close_tab(&mut self) -> Result<()> {
+        if !self.tab().tab_type.is_closable() {
+            return Ok(());
+        }
+
+        let tab = self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::UnsubscribeTimeline(tab.tab_type))?;
+        }
+
+        Ok(())
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| TabSession {
+                    tab_type: tab.tab_type.clone(),
+                    selected: tab.selected_note().map(|event| event.id),
+                    offset: tab
+                        .selected()
+                        .map_or(0, |i| i.saturating_sub(tab.viewport.offset())),
+                })
+                .collect(),
+            active_tab: self.active_tab,
+            last_seen: Some(Timestamp::now()),
+        }
+    }
+
+    fn restore_session(&mut self, session: Session) -> Result<()> {
+        self.tabs = session
+            .tabs
+            .into_iter()
+            .map(|tab_session| {
+                let mut tab = TimelineTab::new(tab_session.tab_type);
+                tab.pending_selection = tab_session.selected;
+                tab.pending_offset = tab_session.offset;
+                tab
+            })
+            .collect();
+        let mut active_tab = session.active_tab;
+        if !self
+            .tabs
+            .iter()
+            .any(|tab| tab.tab_type == TimelineTabType::Home)
+        {
+            self.tabs.insert(0, TimelineTab::new(TimelineTabType::Home));
+            active_tab += 1;
+        }
+        self.active_tab = active_tab.min(self.tabs.len() - 1);
+
+        if let Some(tx) = &self.command_tx {
+            for tab in self.tabs.iter().filter(|tab| tab.tab_type.is_closable()) {
+                tx.send(Action::SubscribeTimeline(tab.tab_type.clone()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_session(&self) {
+        if let Err(e) = self.session().save(
+            &self.config.config._data_dir,
+            self.config.workspace.as_deref(),
+        ) {
+            tracing::error!(error = %e, "Failed to save the session");
+        }
+    }
+
+    fn next_tab(&mut self) {
+        self.active_tab = (self.active_tab + 1) % self.tabs.len();
+    }
+
+    fn previous_tab(&mut self) {
+        self.active_tab = (self.active_tab + self.tabs.len() - 1) % self.tabs.len();
+    }
+
+    /// Selects the tab by its number shown on the tab bar, which starts from 1.
+    /// Returns the titles of the open tabs with the icons of their kinds.
+    fn tab_labels(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .map(|tab| {
+                let title = self.tab_title(&tab.tab_type);
+                match tab.tab_type.icon() {
+                    Some(icon) => format!("{icon} {title}"),
+                    None => title,
+                }
+            })
+            .collect()
+    }
+
+    fn select_tab(&mut self, number: usize) {
+        if (1..=self.tabs.len()).contains(&number) {
+            self.active_tab = number - 1;
+        }
+    }
+
+    fn move_tab_left(&mut self) {
+        if self.active_tab > 0 {
+            self.tabs.swap(self.active_tab, self.active_tab - 1);
+            self.active_tab -= 1;
+        }
+    }
+
+    fn move_tab_right(&mut self) {
+        if self.active_tab + 1 < self.tabs.len() {
+            self.tabs.swap(self.active_tab, self.active_tab + 1);
+            self.active_tab += 1;
+        }
+    }
+
+    fn receive_event(&mut self, tab_type: &TimelineTabType, event: Event) -> Result<()> {
+        self.spam_filter.observe(&event);
+        if self.published.get(&event.id).is_some() {
+            self.echoed.insert(event.id);
+        }
+        match event.kind {
+            Kind::Metadata => self.add_profile(event),
+            Kind::TextNote => self.add_note(tab_type, event)?,
+            Kind::Reaction => self.append_reaction(event),
+            Kind::Repost => self.add_repost(tab_type, event)?,
+            Kind::ZapReceipt => self.append_zap_receipt(event),
+            Kind::ContactList => self.update_followings(event)?,
+            Kind::EventDeletion => self.apply_deletion(&event),
+            Kind::ChannelCreation | Kind::ChannelMetadata => self.channels.add(&event),
+            Kind::ChannelMessage => self.add_note(tab_type, event)?,
+            kind if nip94::is_file(kind) => self.add_note(tab_type, event)?,
+            kind if kind == Kind::from(nip28::PUBLIC_CHATS_KIND) => {
+                self.update_public_chats(event)?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Shows my note in the open tabs it belongs to right away, marked as being sent until relays
+    /// accept or echo it.
+    fn echo_note(&mut self, event: Event, relays: Vec<String>) -> Result<()> {
+        self.published.add(event.clone(), relays);
+        let tab_types: Vec<TimelineTabType> = self
+            .tabs
+            .iter()
+            .map(|tab| tab.tab_type.clone())
+            .filter(|tab_type| tab_type.includes(&event))
+            .collect();
+        for tab_type in tab_types {
+            self.add_note(&tab_type, event.clone())?;
+        }
+
+        Ok(())
+    }
+
+    fn delivery(&self, id: &EventId) -> Option<Delivery> {
+        let published = self.published.get(id)?;
+        if self.echoed.contains(id) {
+            Some(Delivery::Sent)
+        } else {
+            Some(published.delivery())
+        }
+    }
+
+    /// Keeps my latest list of joined channels, fetching their names.
+    fn update_public_chats(&mut self, list: Event) -> Result<()> {
+        let created_at = self.public_chats.as_ref().map(|event| event.created_at);
+        if Some(list.pubkey) != self.pubkey || created_at >= Some(list.created_at) {
+            return Ok(());
+        }
+
+        let ids: Vec<EventId> = nip28::joined_channels(&list)
+            .into_iter()
+            .filter(|id| self.channels.metadata(id).is_none())
+            .collect();
+        self.public_chats = Some(list);
+        if let (false, Some(tx)) = (ids.is_empty(), &self.command_tx) {
+            tx.send(Action::FetchChannels(ids))?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the channels I joined in a menu, which opens the chosen one.
+    fn list_channels(&self) -> Action {
+        let ids = self
+            .public_chats
+            .as_ref()
+            .map(nip28::joined_channels)
+            .unwrap_or_default();
+        if ids.is_empty() {
+            return Action::SystemWarning(i18n::tr(
+                "No joined channels: open one with --tab channel:<note or nevent>",
+            ));
+        }
+
+        let items = ids
+            .into_iter()
+            .map(|id| {
+                let tab_type = TimelineTabType::Channel { id };
+                let mut label = self.tab_title(&tab_type);
+                let about = self
+                    .channels
+                    .metadata(&id)
+                    .and_then(|metadata| metadata.about.clone())
+                    .and_then(|about| about.lines().next().map(str::to_string))
+                    .filter(|about| !about.is_empty());
+                if let Some(about) = about {
+                    label = format!("{label}: {about}");
+                }
+                MenuItem::new(label, Action::OpenTab(tab_type))
+            })
+            .collect();
+        Action::OpenActionMenu(items)
+    }
+
+    fn find_last_event_tag(&self, ev: &Event) -> Option<Tag> {
+        ev.tags
+            .iter()
+            .rfind(|tag| matches!(tag, Tag::Event { .. }))
+            .cloned()
+    }
+
+    fn add_note(&mut self, tab_type: &TimelineTabType, event: Event) -> Result<()> {
+        if *tab_type == TimelineTabType::Global && self.is_spam(&event) {
+            // Spam would crowd out the others in the firehose, so it is not kept at all
+            return Ok(());
+        }
+        self.request_parent(&event)?;
+        self.request_goal(&event)?;
+        if self.is_reply_to_me(&event) {
+            self.interaction_limiter.record_reply(&event);
+            self.request_profile(event.pubkey)?;
+        }
+        if *tab_type != TimelineTabType::Home {
+            // Authors outside of followings
+            self.request_profile(event.pubkey)?;
+        }
+        self.recent_hashtags.add(&event);
+        self.custom_emojis.add(&event);
+        self.events.insert(event.id, event.clone());
+        if event.is_expired_at(&Timestamp::now()) {
+            return Ok(());
+        }
+        if self.config.spam_filter.action == FilterAction::Hide
+            && self.filtered_score(&event).is_some()
+        {
+            self.hide_note(tab_type.clone(), event);
+            return Ok(());
+        }
+        let future = self.is_future(&event);
+        if future && self.config.future_notes.policy == FuturePolicy::Hide {
+            return Ok(());
+        }
+        if *tab_type == TimelineTabType::Home && self.away.add(&event) {
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::AwayUnreadChanged(self.away.unread()))?;
+            }
+        }
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.tab_type == *tab_type) {
+            if future {
+                tab.add_received_note(event, Timestamp::now());
+            } else {
+                tab.add_note(event);
+            }
+        }
+        if *tab_type == TimelineTabType::Global {
+            self.truncate_global();
+        }
+
+        Ok(())
+    }
+
+    fn is_spam(&self, event: &Event) -> bool {
+        self.spam_filter.filter(event, Timestamp::now()).is_some()
+    }
+
+    fn hide_note(&mut self, tab_type: TimelineTabType, event: Event) {
+        self.hidden_notes.push((tab_type, event));
+        if self.hidden_notes.len() > MAX_HIDDEN_NOTES {
+            let (_, dropped) = self.hidden_notes.remove(0);
+            self.forget_note(&dropped.id);
+        }
+    }
+
+    /// Caps the global tab, forgetting the notes dropped from it.
+    fn truncate_global(&mut self) {
+        let max_notes = self.config.global.max_notes;
+        let Some(tab) = self
+            .tabs
+            .iter_mut()
+            .find(|tab| tab.tab_type == TimelineTabType::Global)
+        else {
+            return;
+        };
+        for id in tab.truncate(max_notes) {
+            self.forget_note(&id);
+        }
+    }
+
+    /// Forgets a note shown nowhere any more.
+    fn forget_note(&mut self, id: &EventId) {
+        let shown = self.tabs.iter().any(|tab| tab.contains(id))
+            || self.hidden_notes.iter().any(|(_, event)| event.id == *id);
+        if !shown {
+            self.events.remove(id);
+        }
+    }
+
+    /// Shows the reposted note in the tab at the time of the repost, fetching it unless embedded.
+    fn add_repost(&mut self, tab_type: &TimelineTabType, repost: Event) -> Result<()> {
+        let reposted = nip18::reposted(&repost);
+        let reposted_at = repost.created_at;
+        if *tab_type != TimelineTabType::Home {
+            self.request_profile(repost.pubkey)?;
+        }
+        self.append_repost(repost);
+
+        match reposted {
+            Some(Reposted::Note(note)) => self.add_reposted_note(tab_type, *note, reposted_at)?,
+            Some(Reposted::Id(id)) => match self.events.get(&id) {
+                Some(note) => self.add_reposted_note(tab_type, note.clone(), reposted_at)?,
+                None => {
+                    self.pending_reposts
+                        .entry(id)
+                        .or_default()
+                        .push((tab_type.clone(), reposted_at));
+                    if let Some(tx) = &self.command_tx {
+                        if self.requested_events.insert(id) {
+                            tx.send(Action::FetchEvent(id))?;
+                        }
+                    }
+                }
+            },
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn add_reposted_note(
+        &mut self,
+        tab_type: &TimelineTabType,
+        note: Event,
+        reposted_at: Timestamp,
+    ) -> Result<()> {
+        if *tab_type == TimelineTabType::Global && self.is_spam(&note) {
+            return Ok(());
+        }
+        // Authors of reposted notes are often not followed
+        self.request_profile(note.pubkey)?;
+        self.request_parent(&note)?;
+        self.events.insert(note.id, note.clone());
+        if note.is_expired_at(&Timestamp::now()) {
+            return Ok(());
+        }
+        if self.config.spam_filter.action == FilterAction::Hide
+            && self.filtered_score(&note).is_some()
+        {
+            self.hide_note(tab_type.clone(), note);
+            return Ok(());
+        }
+        let future = self.is_future(&note);
+        if future && self.config.future_notes.policy == FuturePolicy::Hide {
+            return Ok(());
+        }
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.tab_type == *tab_type) {
+            if future {
+                tab.add_received_note(note, reposted_at.min(Timestamp::now()));
+            } else {
+                tab.add_repost(note, reposted_at);
+            }
+        }
+        if *tab_type == TimelineTabType::Global {
+            self.truncate_global();
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the note is dated in the future beyond the tolerance to clock skew.
+    fn is_future(&self, event: &Event) -> bool {
+        clock_skew::is_future(event, Timestamp::now(), self.config.future_notes.tolerance)
+    }
+
+    /// Returns the names of the accounts which reposted the note, the latest first.
+    fn reposted_by(&self, id: &EventId) -> Vec<String> {
+        let mut reposts: Vec<&Event> = self.reposts.get(id).into_iter().flatten().collect();
+        reposts.sort_by_key(|repost| std::cmp::Reverse(repost.created_at));
+        let mut reposters = HashSet::new();
+        reposts
+            .into_iter()
+            .filter(|repost| reposters.insert(repost.pubkey))
+            .map(|repost| self.profile_name(&repost.pubkey))
+            .collect()
+    }
+
+    /// Returns the score of a note filtered as spam, unless filtered notes are shown.
+    fn filtered_score(&self, event: &Event) -> Option<Score> {
+        if self.show_filtered {
+            return None;
+        }
+
+        self.spam_filter.filter(event, Timestamp::now())
+    }
+
+    /// Loads older notes of the active tab when the selection gets near its bottom.
+    fn load_older_notes(&mut self) -> Option<Action> {
+        let config = self.config.pagination.clone();
+        let tab = self.tab_mut();
+        // Threads show all of their replies at once, and the global tab only the newest notes
+        if matches!(
+            tab.tab_type,
+            TimelineTabType::Thread { .. } | TimelineTabType::Global
+        ) {
+            return None;
+        }
+        let until = tab.oldest_sorted_at()?;
+        let selected = tab.list_state.selected();
+        let len = tab.notes.len();
+        // Notes requested before, e.g. around a date jumped to, are skipped
+        let (since, until) = tab.coverage.narrow(None, until)?;
+        let now = Instant::now();
+        let limit = tab
+            .paginator
+            .poll(&config, selected, len, since.is_some(), now)?;
+        let tab_type = tab.tab_type.clone();
+        Some(match since {
+            Some(since) => {
+                tab.coverage.request(since, until, limit, now);
+                Action::LoadNotesBetween(tab_type, since, until, limit)
+            }
+            None => Action::LoadOlderNotes(tab_type, until, limit),
+        })
+    }
+
+    /// Marks gaps in the tabs after the app was suspended or the machine slept.
+    fn detect_gap(&mut self) {
+        let now = Timestamp::now();
+        if !self.gap_detector.tick(now) {
+            return;
+        }
+
+        for tab in &mut self.tabs {
+            if !matches!(tab.tab_type, TimelineTabType::Thread { .. }) {
+                tab.open_gap(now);
+            }
+        }
+    }
+
+    /// Tells the status bar how many notes arrived above the view of the current tab.
+    fn notify_unread(&mut self) -> Result<()> {
+        let unread = self.tab().viewport.unread();
+        if unread != self.unread {
+            self.unread = unread;
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::UnreadChanged(unread))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the notes missing in the newest gap of the tab.
+    fn fill_gap(&mut self) -> Option<Action> {
+        let limit = self.config.pagination.max_limit;
+        let tab = self.tab_mut();
+        // Gaps stay until their notes arrived, and are narrowed to the times still missing
+        let (since, until) = tab.gaps.iter().find_map(|gap| {
+            let (since, until) = tab.coverage.narrow(Some(gap.since), gap.until)?;
+            Some((since.unwrap_or(gap.since), until))
+        })?;
+        tab.coverage.request(since, until, limit, Instant::now());
+        Some(Action::LoadNotesBetween(
+            tab.tab_type.clone(),
+            since,
+            until,
+            limit,
+        ))
+    }
+
+    /// Selects the note closest to the date, loading notes before and after it.
+    fn jump_to_date(&mut self, input: &str) -> Result<Option<Action>> {
+        let now = Timestamp::now();
+        let Some(target) = timestamp::parse(input, now) else {
+            return Ok(Some(Action::Error(i18n::tr_args(
+                "Invalid date: {input}",
+                &[("input", &input.trim())],
+            ))));
+        };
+        self.jump_to(target)
+    }
+
+    /// Selects the note closest to the time in the current tab, loading the notes around it.
+    fn jump_to(&mut self, target: Timestamp) -> Result<Option<Action>> {
+        let now = Timestamp::now();
+        let limit = self.config.pagination.limit;
+        let tab = self.tab_mut();
+        let loaded_since = tab.oldest_sorted_at().unwrap_or(now);
+        tab.jump_to(target);
+        tab.viewport.request(ViewportMove::Place(Placement::Center));
+        // Notes between the date and the loaded ones, then notes before the date, except the ones
+        // requested by earlier jumps
+        let between = (target < loaded_since)
+            .then(|| tab.coverage.narrow(Some(target), loaded_since))
+            .flatten();
+        if let Some((since, until)) = between {
+            tab.coverage
+                .request(since.unwrap_or(target), until, limit, Instant::now());
+        }
+        let older = tab.coverage.narrow(None, target);
+        let tab_type = tab.tab_type.clone();
+        if let Some(tx) = &self.command_tx {
+            if let Some((since, until)) = between {
+                tx.send(Action::LoadNotesBetween(
+                    tab_type.clone(),
+                    since.unwrap_or(target),
+                    until,
+                    limit,
+                ))?;
+            }
+            match older {
+                Some((Some(since), until)) => {
+                    tx.send(Action::LoadNotesBetween(tab_type, since, until, limit))?
+                }
+                Some((None, until)) => tx.send(Action::LoadOlderNotes(tab_type, until, limit))?,
+                None => {}
+            }
+        }
+        Ok(None)
+    }
+
+    fn toggle_follow_mode(&mut self) -> Result<()> {
+        let tab = self.tab_mut();
+        tab.follow = !tab.follow;
+        let message = if tab.follow {
+            i18n::tr("[Follow] The newest note is kept selected as new notes arrive")
+        } else {
+            i18n::tr("[Follow] Stopped following new notes")
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::SystemMessage(message))?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws a banner over the top of the timeline while new notes are kept above the view.
+    fn draw_new_notes_banner(&self, f: &mut Frame<'_>, area: Rect) {
+        let unread = self.tab().viewport.unread();
+        if !self.tab().follow || unread == 0 {
+            return;
+        }
+
+        let banner = i18n::plural(
+            "{count, plural, one {↑ # new note} other {↑ # new notes}}",
+            unread as u64,
+        );
+        let paragraph = Paragraph::new(banner)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Black).bg(Color::LightCyan));
+        f.render_widget(paragraph, Rect { height: 1, ..area });
+    }
+
+    fn toggle_filtered_notes(&mut self) -> Result<()> {
+        self.show_filtered = !self.show_filtered;
+        let message = if self.show_filtered {
+            let count = self.hidden_notes.len();
+            for (tab_type, event) in self.hidden_notes.drain(..) {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.tab_type == tab_type) {
+                    tab.add_note(event);
+                }
+            }
+            self.truncate_global();
+            let notes = i18n::plural(
+                "{count, plural, one {# hidden note} other {# hidden notes}}",
+                count as u64,
+            );
+            i18n::tr_args(
+                "[Spam filter] Showing filtered notes, including {notes}",
+                &[("notes", &notes)],
+            )
+        } else {
+            i18n::tr("[Spam filter] Filtering notes")
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::SystemMessage(message))?;
+        }
+
+        Ok(())
+    }
+
+    fn add_fetched_event(&mut self, event: Event) -> Result<()> {
+        self.spam_filter.observe(&event);
+        match event.kind {
+            Kind::Metadata => {
+                let pubkey = event.pubkey;
+                self.add_profile(event);
+                self.refresh_profile(pubkey)?;
+            }
+            Kind::ContactList => {
+                self.spam_filter.add_contact_list(&event);
+                let counted = self
+                    .followers
+                    .as_mut()
+                    .map(|count| count.add(&event, Instant::now()));
+                if counted == Some(true) {
+                    self.followers_changed = true;
+                    self.request_profile(event.pubkey)?;
+                }
+            }
+            Kind::ChannelCreation | Kind::ChannelMetadata => self.channels.add(&event),
+            // My older activity
+            Kind::Reaction if self.pubkey == Some(event.pubkey) => {
+                self.append_reaction(event);
+                self.refresh_activity()?;
+            }
+            Kind::Repost if self.pubkey == Some(event.pubkey) => {
+                self.append_repost(event);
+                self.refresh_activity()?;
+            }
+            // Zaps to goals are fetched along with them
+            Kind::ZapReceipt => self.append_zap_receipt(event),
+            kind if kind == Kind::from(ZAP_GOAL_KIND) => {
+                if let Some(goal) = ZapGoal::from_event(&event) {
+                    self.goals.insert(event.id, goal);
+                }
+            }
+            Kind::TextNote => {
+                self.request_profile(event.pubkey)?;
+                for (tab_type, reposted_at) in
+                    self.pending_reposts.remove(&event.id).unwrap_or_default()
+                {
+                    self.add_reposted_note(&tab_type, event.clone(), reposted_at)?;
+                }
+                let mine = self.pubkey == Some(event.pubkey);
+                self.events.insert(event.id, event);
+                if mine {
+                    self.refresh_activity()?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Lists my latest reactions, reposts and replies among the stored events.
+    fn my_activity(&self) -> Vec<Activity> {
+        let Some(pubkey) = self.pubkey else {
+            return vec![];
+        };
+
+        let events = self
+            .reactions
+            .values()
+            .flatten()
+            .chain(self.reposts.values().flatten())
+            .chain(self.events.values());
+        activity::recent(&pubkey, events, activity::LIMIT)
+            .into_iter()
+            .map(|(event, kind)| Activity {
+                event: event.clone(),
+                kind,
+                target: self.activity_target(event, kind),
+            })
+            .collect()
+    }
+
+    /// Describes the note reacted to, reposted or replied to, e.g. `alice: gm`.
+    fn activity_target(&self, event: &Event, kind: ActivityKind) -> String {
+        let id = match kind {
+            ActivityKind::Reply => ThreadTags::parse(event).parent(),
+            ActivityKind::Reaction | ActivityKind::Repost => {
+                match self.find_last_event_tag(event) {
+                    Some(Tag::Event { event_id, .. }) => Some(event_id),
+                    _ => None,
+                }
+            }
+        };
+        match id.and_then(|id| self.events.get(&id)) {
+            Some(note) => format!(
+                "{}: {}",
+                self.profile_name(&note.pubkey),
+                note.content.lines().next().unwrap_or_default()
+            ),
+            None => id.map_or_else(String::new, |id| shorten_hex(&id.to_hex())),
+        }
+    }
+
+    /// Lists my activity again while it is shown, as older activity arrives.
+    fn refresh_activity(&self) -> Result<()> {
+        if let (true, Some(tx)) = (self.activity_shown, &self.command_tx) {
+            tx.send(Action::RefreshMyActivity(self.my_activity()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the zap goal which the note raises funds for, with the zaps to the goal.
+    fn request_goal(&mut self, event: &Event) -> Result<()> {
+        if let (Some(goal), Some(tx)) = (ZapGoal::linked(event), &self.command_tx) {
+            if !self.goals.contains_key(&goal) && self.requested_events.insert(goal) {
+                tx.send(Action::FetchZapGoal(goal))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn request_parent(&mut self, event: &Event) -> Result<()> {
+        if let (Some(parent_id), Some(tx)) = (ThreadTags::parse(event).parent(), &self.command_tx) {
+            if !self.events.contains_key(&parent_id) && self.requested_events.insert(parent_id) {
+                tx.send(Action::FetchEvent(parent_id))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns who reacted to, reposted and zapped the note so far.
+    pub fn engagement(&self, id: &EventId) -> Engagement {
+        let empty = HashSet::new();
+        Engagement::new(
+            self.reactions.get(id).unwrap_or(&empty),
+            self.reposts.get(id).unwrap_or(&empty),
+            &self.note_zaps(id),
+        )
+    }
+
+    fn profile_name(&self, pubkey: &PublicKey) -> String {
+        match self.profiles.get(pubkey) {
+            Some(profile) => profile.name(),
+            None => shorten_hex(&pubkey.to_string()),
+        }
+    }
+
+    fn engagement_report(&self, id: &EventId, engagement: &Engagement) -> Vec<String> {
+        let mut lines = if engagement.is_empty() {
+            vec![i18n::tr("No reactions, reposts or zaps yet")]
+        } else {
+            self.engagement_lines(engagement)
+        };
+
+        // Recipients of the zaps to the note, if they are split
+        let splits = self.events.get(id).map(ZapSplit::parse).unwrap_or_default();
+        let shares = ZapSplit::shares(&splits);
+        if !shares.is_empty() {
+            lines.push(String::new());
+            lines.push(i18n::tr("Zaps are split among"));
+            lines.extend(
+                shares
+                    .iter()
+                    .map(|(pubkey, share)| format!("  {share:.0}% {}", self.profile_name(pubkey))),
+            );
+        }
+        lines
+    }
+
+    fn engagement_lines(&self, engagement: &Engagement) -> Vec<String> {
+        let mut lines = vec![i18n::plural(
+            "{count, plural, one {# Like} other {# Likes}}",
+            engagement.reactions.len() as u64,
+        )];
+        lines.extend(
+            engagement
+                .reactions
+                .iter()
+                .map(|(pubkey, emoji)| format!("  {emoji} {}", self.profile_name(pubkey))),
+        );
+        lines.push(String::new());
+        lines.push(i18n::plural(
+            "{count, plural, one {# Repost} other {# Reposts}}",
+            engagement.reposts.len() as u64,
+        ));
+        lines.extend(
+            engagement
+                .reposts
+                .iter()
+                .map(|pubkey| format!("  {}", self.profile_name(pubkey))),
+        );
+        lines.push(String::new());
+        lines.push(format!(
+            "⚡ {} sats",
+            compact_number(engagement.zapped_millisats() / 1000)
+        ));
+        lines.extend(engagement.zaps.iter().map(|(pubkey, millisats)| {
+            let sender = pubkey.map_or_else(
+                || i18n::tr("Anonymous"),
+                |pubkey| self.profile_name(&pubkey),
+            );
+            format!("  ⚡ {} {sender}", compact_number(millisats / 1000))
+        }));
+        lines
+    }
+
+    /// Shows or hides the translation of the selected note, translating it the first time.
+    fn translate(&mut self) -> Option<Action> {
+        let note = self.tab().selected_note()?;
+        let (id, content) = (note.id, note.content.clone());
+        if self.shown_translations.remove(&id) {
+            return None;
+        }
+
+        self.shown_translations.insert(id);
+        if self.translations.contains_key(&id) {
+            return None;
+        }
+        Some(Action::TranslateNote(id, content))
+    }
+
+    /// Shows or hides the cards of the links in the selected note, fetching them the first time.
+    fn preview_links(&mut self) -> Option<Action> {
+        let note = self.tab().selected_note()?;
+        let (id, urls) = (note.id, link_preview::urls(&note.content));
+        if self.shown_previews.remove(&id) {
+            return None;
+        }
+        if urls.is_empty() {
+            return Some(Action::SystemMessage(i18n::tr("No links in the note")));
+        }
+
+        self.shown_previews.insert(id);
+        let missing: Vec<String> = urls
+            .into_iter()
+            .filter(|url| !self.link_previews.contains_key(url))
+            .collect();
+        for url in &missing {
+            self.link_previews
+                .insert(url.clone(), PreviewState::Loading);
+        }
+        (!missing.is_empty()).then_some(Action::FetchLinkPreviews(missing))
+    }
+
+    fn show_engagement(&mut self) -> Result<()> {
+        let Some(id) = self.tab().selected_note().map(|event| event.id) else {
+            return Ok(());
+        };
+
+        let engagement = self.engagement(&id);
+        let splits = self
+            .events
+            .get(&id)
+            .map(ZapSplit::parse)
+            .unwrap_or_default();
+        for pubkey in engagement
+            .pubkeys()
+            .chain(splits.iter().map(|split| split.pubkey))
+        {
+            self.queue_profile(pubkey, Priority::Visible);
+        }
+        self.engagement_note = Some(id);
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::ShowReport(
+                i18n::tr("Engagement"),
+                self.engagement_report(&id, &engagement),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows the engagement again if the profile of someone in it arrived.
+    fn refresh_engagement(&mut self, pubkey: PublicKey) -> Result<()> {
+        let Some(id) = self.engagement_note else {
+            return Ok(());
+        };
+
+        let engagement = self.engagement(&id);
+        let split = self
+            .events
+            .get(&id)
+            .is_some_and(|note| ZapSplit::parse(note).iter().any(|s| s.pubkey == pubkey));
+        if let (true, Some(tx)) = (
+            split || engagement.pubkeys().any(|p| p == pubkey),
+            &self.command_tx,
+        ) {
+            tx.send(Action::ShowReport(
+                i18n::tr("Engagement"),
+                self.engagement_report(&id, &engagement),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Queues the profile to be fetched, before others if I follow the author.
+    fn request_profile(&mut self, pubkey: PublicKey) -> Result<()> {
+        let priority =
+            if self.pubkey == Some(pubkey) || self.spam_filter.followings().contains(&pubkey) {
+                Priority::Followed
+            } else {
+                Priority::Deferred
+            };
+        self.queue_profile(pubkey, priority);
+        Ok(())
+    }
+
+    /// Queues the profile unless it is known or requested already. Authors shown on the screen
+    /// are queued as [`Priority::Visible`].
+    fn queue_profile(&mut self, pubkey: PublicKey, priority: Priority) {
+        if !self.profiles.contains_key(&pubkey)
+            && self.requested_profiles.is_due(&pubkey, Instant::now())
+        {
+            self.profile_queue.push(pubkey, priority);
+        }
+    }
+
+    /// Requests the most wanted profiles, raising the authors in view of the tab first.
+    fn fetch_profiles(&mut self) -> Result<()> {
+        let tab = self.tab();
+        let visible: Vec<PublicKey> = tab
+            .viewport
+            .visible()
+            .filter_map(|i| tab.notes.get(i))
+            .flat_map(|note| {
+                let reposters = note
+                    .0
+                    .is_reposted()
+                    .then(|| self.reposts.get(&note.0.event.id))
+                    .flatten()
+                    .into_iter()
+                    .flatten()
+                    .map(|repost| repost.pubkey);
+                std::iter::once(note.0.event.pubkey).chain(reposters)
+            })
+            .collect();
+        for pubkey in visible {
+            self.queue_profile(pubkey, Priority::Visible);
+        }
+
+        let Some(tx) = &self.command_tx else {
+            return Ok(());
+        };
+        for pubkey in self.profile_queue.pop(PROFILES_PER_TICK) {
+            self.requested_profiles.insert(pubkey, Instant::now());
+            tx.send(Action::FetchProfile(pubkey))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of the author replied to, or the note id until the parent is fetched.
+    fn reply_to_name(&self, event: &Event) -> Option<String> {
+        let parent_id = ThreadTags::parse(event).parent()?;
+        match self.events.get(&parent_id) {
+            Some(parent) => Some(self.author_name(parent)),
+            None => Some(shorten_hex(&parent_id.to_hex())),
+        }
+    }
+
+    fn is_reply_to_me(&self, event: &Event) -> bool {
+        match self.pubkey {
+            Some(pubkey) => {
+                event.pubkey != pubkey
+                    && event.public_keys().any(|pk| *pk == pubkey)
+                    && ThreadTags::parse(event).parent().is_some()
+            }
+            None => false,
+        }
+    }
+
+    fn update_followings(&mut self, contact_list: Event) -> Result<()> {
+        let created_at = self.contact_list.as_ref().map(|event| event.created_at);
+        if Some(contact_list.pubkey) != self.pubkey || created_at >= Some(contact_list.created_at) {
+            return Ok(());
+        }
+
+        let followings: Vec<PublicKey> = contact_list.public_keys().copied().collect();
+        // Follows of followings are trusted by the spam filter and suggested in the discover view
+        let new_followings: Vec<PublicKey> = followings
+            .iter()
+            .filter(|pubkey| !self.spam_filter.followings().contains(pubkey))
+            .copied()
+            .collect();
+        self.interaction_limiter
+            .set_followings(followings.iter().copied());
+        self.spam_filter.set_followings(followings);
+        self.contact_list = Some(contact_list);
+        if let (false, Some(tx)) = (new_followings.is_empty(), &self.command_tx) {
+            tx.send(Action::FetchContactLists(new_followings))?;
+        }
+        self.refresh_suggestions()?;
+
+        Ok(())
+    }
+
+    /// Counts the events kept for tabs, threads and engagement, including the copies in tabs.
+    fn note_store_stats(&self) -> NoteStoreStats {
+        let mut stats = NoteStoreStats::default();
+        let tabs = self
+            .tabs
+            .iter()
+            .flat_map(|tab| tab.notes.iter().map(|note| &note.0.event));
+        let engagement = [&self.reactions, &self.reposts, &self.zap_receipts]
+            .into_iter()
+            .flat_map(|events| events.values().flatten());
+        for event in self.events.values().chain(tabs).chain(engagement) {
+            stats.add(event);
+        }
+        stats
+    }
+
+    fn suggestion(&self, pubkey: PublicKey, followed_by: usize) -> Suggestion {
+        let metadata = self.profiles.get(&pubkey).map(|profile| &profile.metadata);
+        let non_empty = |value: Option<&String>| value.filter(|value| !value.is_empty()).cloned();
+        Suggestion {
+            name: self.profile_name(&pubkey),
+            about: non_empty(metadata.and_then(|metadata| metadata.about.as_ref())),
+            nip05: non_empty(metadata.and_then(|metadata| metadata.nip05.as_ref())),
+            followed: self.spam_filter.followings().contains(&pubkey),
+            ..Suggestion::new(pubkey, followed_by)
+        }
+    }
+
+    fn discover(&mut self) -> Result<Option<Action>> {
+        let Some(me) = self.pubkey else {
+            return Ok(Some(Action::SystemWarning(i18n::tr(
+                "Browse mode is read-only: set privatekey in the config to post",
+            ))));
+        };
+
+        let ranked = discover::rank(
+            &me,
+            self.spam_filter.followings(),
+            self.spam_filter.contact_lists(),
+            SUGGESTION_LIMIT,
+        );
+        for suggestion in &ranked {
+            self.queue_profile(suggestion.pubkey, Priority::Visible);
+        }
+        self.suggestions = Some(ranked);
+
+        Ok(Some(Action::OpenSuggestions(self.described_suggestions())))
+    }
+
+    /// Fills the suggestions with their profiles and whether they are followed now.
+    fn described_suggestions(&self) -> Vec<Suggestion> {
+        self.suggestions
+            .iter()
+            .flatten()
+            .map(|suggestion| self.suggestion(suggestion.pubkey, suggestion.followed_by))
+            .collect()
+    }
+
+    /// Shows the suggestions again if they are open, e.g. when a profile arrived.
+    fn refresh_suggestions(&self) -> Result<()> {
+        if let (Some(_), Some(tx)) = (&self.suggestions, &self.command_tx) {
+            tx.send(Action::OpenSuggestions(self.described_suggestions()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Publishes my contact list with an account added or removed.
+    fn follow(&self, pubkey: PublicKey, follow: bool) -> Result<Option<Action>> {
+        let Some(ref contact_list) = self.contact_list else {
+            return Ok(Some(Action::SystemWarning(i18n::tr(
+                "Your contact list has not been loaded yet",
+            ))));
+        };
+        let Some(tags) = discover::update_contact_list(&contact_list.tags, pubkey, follow) else {
+            return Ok(None);
+        };
+
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::SendContactList(tags, contact_list.content.clone()))?;
+            let message = if follow {
+                "[Followed] {name}"
+            } else {
+                "[Unfollowed] {name}"
+            };
+            tx.send(Action::SystemMessage(i18n::tr_args(
+                message,
+                &[("name", &self.profile_name(&pubkey))],
+            )))?;
+        }
+
+        Ok(None)
+    }
+
+    fn collapsed_authors(&self) -> Vec<CollapsedAuthor> {
+        self.interaction_limiter
+            .collapsed_authors(Timestamp::now())
+            .into_iter()
+            .map(|(pubkey, replies)| CollapsedAuthor {
+                pubkey,
+                name: self
+                    .profiles
+                    .get(&pubkey)
+                    .map(|profile| profile.name())
+                    .unwrap_or_else(|| shorten_hex(&pubkey.to_string())),
+                replies,
+                muted: self.interaction_limiter.is_muted(&pubkey),
+            })
+            .collect()
+    }
+
+    fn save_author_lists(&self) -> Result<()> {
+        if let Err(e) = self
+            .interaction_limiter
+            .lists()
+            .save(&self.config.config._data_dir)
+        {
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::Error(i18n::tr_args(
+                    "Failed to save author lists: {error}",
+                    &[("error", &e)],
+                )))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the selected note and its replies in the order they were posted.
+    fn selected_thread(&self) -> Vec<Event> {
+        let Some(selected) = self.tab().selected_note() else {
+            return vec![];
+        };
+
+        let root = ThreadTags::parse(selected).root.unwrap_or(selected.id);
+        let mut thread: Vec<Event> = self
+            .events
+            .values()
+            .filter(|event| event.id == root || ThreadTags::parse(event).root == Some(root))
+            .cloned()
+            .collect();
+        thread.sort_by_key(|event| (event.created_at, event.id));
+        thread
+    }
+
+    fn export(&self, kind: PromptKind, path: String) -> Result<()> {
+        let (title, events) = match kind {
+            PromptKind::ExportTimeline => (
+                self.tab_title(&self.tab().tab_type),
+                self.tab()
+                    .notes
+                    .iter()
+                    .map(|note| note.0.event.clone())
+                    .collect(),
+            ),
+            PromptKind::ExportThread => (i18n::tr("Thread"), self.selected_thread()),
+            PromptKind::JumpToDate | PromptKind::AttachMedia | PromptKind::Expiration => {
+                return Ok(())
+            }
+        };
+        let names = events
+            .iter()
+            .map(|event| (event.pubkey, self.author_name(event)))
+            .collect();
+
+        let path = std::path::PathBuf::from(path.trim());
+        let message = match export::write(&path, &title, &events, &names) {
+            Ok(()) => {
+                let notes = i18n::plural(
+                    "{count, plural, one {# note} other {# notes}}",
+                    events.len() as u64,
+                );
+                Action::SystemMessage(i18n::tr_args(
+                    "[Exported] {notes} to {path}",
+                    &[("notes", &notes), ("path", &path.display())],
+                ))
+            }
+            Err(e) => Action::Error(i18n::tr_args(
+                "Failed to export to {path}: {error}",
+                &[("path", &path.display()), ("error", &e)],
+            )),
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(message)?;
+        }
+
+        Ok(())
+    }
+
+    fn default_export_path() -> String {
+        format!("nostui-{}.json", Local::now().format("%Y%m%d-%H%M%S"))
+    }
+
+    fn add_profile(&mut self, event: Event) {
+        // Profiles parsed by the ingestion pipeline arrive before their events
+        if self
+            .profiles
+            .get(&event.pubkey)
+            .is_some_and(|profile| profile.created_at >= event.created_at)
+        {
+            return;
+        }
+
+        if let Ok(metadata) = Metadata::from_json(event.content.clone()) {
+            self.insert_profile(Profile::new(event.pubkey, event.created_at, metadata));
+        }
+    }
+
+    fn insert_profile(&mut self, profile: Profile) {
+        if let Some(existing_profile) = self.profiles.get(&profile.pubkey) {
+            if existing_profile.created_at > profile.created_at {
+                return;
+            }
+        }
+
+        self.requested_profiles.remove(&profile.pubkey);
+        self.profile_index.insert(&profile);
+        self.profiles.insert(profile.pubkey, profile);
+    }
+
+    /// Shows the new name of the account where it is listed.
+    fn refresh_profile(&mut self, pubkey: PublicKey) -> Result<()> {
+        self.refresh_engagement(pubkey)?;
+        if self.followers.is_some() {
+            // Listed again on the next tick rather than for each of the many profiles
+            self.followers_changed = true;
+        }
+        if self
+            .suggestions
+            .iter()
+            .flatten()
+            .any(|suggestion| suggestion.pubkey == pubkey)
+        {
+            self.refresh_suggestions()?;
+        }
+
+        Ok(())
+    }
+
+    fn append_reaction(&mut self, reaction: Event) {
+        // reactions grouped by event_id
+        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&reaction) {
+            match self.reactions.entry(event_id) {
+                Entry::Vacant(e) => {
+                    e.insert(HashSet::from([reaction]));
+                }
+                Entry::Occupied(mut e) => {
+                    e.get_mut().insert(reaction);
+                }
+            }
+        }
+    }
+
+    /// My reaction to the note, which can be deleted.
+    fn my_reaction(&self, id: &EventId) -> Option<&Event> {
+        let pubkey = self.pubkey?;
+        self.reactions
+            .get(id)?
+            .iter()
+            .find(|reaction| reaction.pubkey == pubkey)
+    }
+
+    /// Asks to send the selected note to my write relays, which the outbox reports on.
+    fn rebroadcast(&self) -> Option<Action> {
+        let note = self.tab().selected_note()?;
+        let count = self.config.write_relays().len();
+        if count == 0 {
+            return Some(Action::SystemWarning(i18n::tr("No relays to write to")));
+        }
+
+        let relays = i18n::plural(
+            "{count, plural, one {# relay} other {# relays}}",
+            count as u64,
+        );
+        let message = i18n::tr_args(
+            "Broadcast the note by {name} to {relays}?",
+            &[("name", &self.author_name(note)), ("relays", &relays)],
+        );
+        Some(Action::Confirm(
+            message,
+            vec![Action::BroadcastEvent(note.clone())],
+        ))
+    }
+
+    fn unreact(&self) -> Option<Action> {
+        let note = self.tab().selected_note()?;
+        match self.my_reaction(&note.id) {
+            Some(reaction) => Some(Action::DeleteReaction(reaction.clone())),
+            None => Some(Action::SystemWarning(i18n::tr(
+                "You have not reacted to the note",
+            ))),
+        }
+    }
+
+    /// Forgets notes and reactions deleted by their authors (NIP-09).
+    fn apply_deletion(&mut self, deletion: &Event) {
+        for reactions in self.reactions.values_mut() {
+            reactions.retain(|reaction| !nip09::is_deleted_by(reaction, deletion));
+        }
+        for reposts in self.reposts.values_mut() {
+            reposts.retain(|repost| !nip09::is_deleted_by(repost, deletion));
+        }
+
+        for id in nip09::deleted_ids(deletion) {
+            let deleted = self
+                .events
+                .get(&id)
+                .is_some_and(|event| nip09::is_deleted_by(event, deletion));
+            if deleted {
+                self.events.remove(&id);
+                for tab in &mut self.tabs {
+                    tab.remove_note(&id);
+                }
+            }
+        }
+    }
+
+    /// Lists the actions applicable to the selected note.
+    fn action_menu(&self) -> Option<Action> {
+        let note = self.tab().selected_note()?;
+        let author = note.pubkey;
+        let is_mine = self.pubkey == Some(author);
+        let mut items = vec![];
+        if self.config.has_identity() {
+            items.push(MenuItem::new(i18n::tr("Reply"), Action::ReplyTextNote));
+            if self.my_reaction(&note.id).is_some() {
+                items.push(MenuItem::new(
+                    i18n::tr("Remove my reaction"),
+                    Action::Unreact,
+                ));
+            } else {
+                items.push(MenuItem::new(i18n::tr("React"), Action::React));
+            }
+            items.push(MenuItem::new(i18n::tr("Repost"), Action::Repost));
+        }
+        if nip36::content_warning(note).is_some() {
+            items.push(MenuItem::new(
+                i18n::tr("Reveal or hide the content"),
+                Action::RevealContent,
+            ));
+        }
+        items.push(MenuItem::new(
+            i18n::tr("Show reactions, reposts and zaps"),
+            Action::ShowEngagement,
+        ));
+        if self.config.translation.url.is_some() {
+            let label = if self.shown_translations.contains(&note.id) {
+                i18n::tr("Hide the translation")
+            } else {
+                i18n::tr("Translate")
+            };
+            items.push(MenuItem::new(label, Action::Translate));
+        }
+        if !link_preview::urls(&note.content).is_empty() {
+            let label = if self.shown_previews.contains(&note.id) {
+                i18n::tr("Hide the link previews")
+            } else {
+                i18n::tr("Preview the links")
+            };
+            items.push(MenuItem::new(label, Action::PreviewLinks));
+        }
+        if let Some(file) = FileMetadata::from_event(note) {
+            items.push(MenuItem::new(
+                i18n::tr("Open the file"),
+                Action::OpenUrl(file.url.clone()),
+            ));
+            items.push(MenuItem::new(
+                i18n::tr("Copy the file URL"),
+                Action::CopyToClipboard(file.url),
+            ));
+        }
+        items.push(MenuItem::new(
+            i18n::tr("Open the author's timeline"),
+            Action::OpenAuthorTimeline,
+        ));
+        items.push(MenuItem::new(
+            i18n::tr("Show the author's followers"),
+            Action::ShowFollowers,
+        ));
+        if let Ok(id) = note.id.to_bech32() {
+            items.push(MenuItem::new(
+                i18n::tr("Copy the note ID"),
+                Action::CopyToClipboard(id),
+            ));
+        }
+        if let Ok(npub) = author.to_bech32() {
+            items.push(MenuItem::new(
+                i18n::tr("Copy the author's npub"),
+                Action::CopyToClipboard(npub),
+            ));
+        }
+        items.push(MenuItem::new(
+            i18n::tr("Export the thread"),
+            Action::ExportThread,
+        ));
+        items.push(MenuItem::new(
+            i18n::tr("Broadcast to my relays"),
+            Action::Rebroadcast,
+        ));
+        if self.config.has_identity() && !is_mine {
+            if self.spam_filter.followings().contains(&author) {
+                items.push(MenuItem::new(
+                    i18n::tr("Unfollow the author"),
+                    Action::Confirm(
+                        i18n::tr_args("Unfollow {name}?", &[("name", &self.profile_name(&author))]),
+                        vec![Action::Unfollow(author)],
+                    ),
+                ));
+            } else {
+                items.push(MenuItem::new(
+                    i18n::tr("Follow the author"),
+                    Action::Follow(author),
+                ));
+            }
+        }
+        if !is_mine {
+            items.extend(Self::mute_items(author));
+        }
+        if is_mine {
+            items.push(MenuItem::new(
+                i18n::tr("Delete the note"),
+                Action::Confirm(
+                    i18n::tr("Delete this note? Relays may keep it."),
+                    vec![Action::DeleteNote(note.clone())],
+                ),
+            ));
+        }
+
+        Some(Action::OpenActionMenu(items))
+    }
+
+    /// Lists the ways to mute the author, for now or permanently.
+    fn mute_items(author: PublicKey) -> Vec<MenuItem> {
+        vec![
+            MenuItem::new(
+                i18n::tr("Mute the author for an hour"),
+                Action::MuteAuthorFor(author, Some(HOUR_MUTE_SECS)),
+            ),
+            MenuItem::new(
+                i18n::tr("Mute the author for this session"),
+                Action::MuteAuthorFor(author, None),
+            ),
+            MenuItem::new(i18n::tr("Mute the author"), Action::MuteAuthor(author)),
+        ]
+    }
+
+    fn mute_menu(&self) -> Option<Action> {
+        let author = self.tab().selected_note()?.pubkey;
+        if self.pubkey == Some(author) {
+            return None;
+        }
+        Some(Action::OpenActionMenu(Self::mute_items(author)))
+    }
+
+    fn mute_temporarily(&mut self, author: PublicKey, secs: Option<u64>) -> Result<()> {
+        let until = secs.map(|secs| Timestamp::from(Timestamp::now().as_u64() + secs));
+        self.temporary_mutes.mute(author, until);
+
+        let name = self.profile_name(&author);
+        let message = match until {
+            Some(until) => {
+                let time = timestamp::format(until, TimestampMode::Absolute, Timestamp::now());
+                i18n::tr_args(
+                    "[Muted until {time}] {name}",
+                    &[("time", &time), ("name", &name)],
+                )
+            }
+            None => i18n::tr_args("[Muted for this session] {name}", &[("name", &name)]),
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::SystemMessage(message))?;
+        }
+        Ok(())
+    }
+
+    fn expire_mutes(&mut self) -> Result<()> {
+        for author in self.temporary_mutes.expire(Timestamp::now()) {
+            let name = self.profile_name(&author);
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::SystemMessage(i18n::tr_args(
+                    "[Unmuted] {name}",
+                    &[("name", &name)],
+                )))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts the followers of the author of the selected note, listing them as their contact
+    /// lists arrive.
+    fn show_followers(&mut self) -> Result<Option<Action>> {
+        let Some(pubkey) = self.tab().selected_note().map(|event| event.pubkey) else {
+            return Ok(None);
+        };
+
+        self.followers = Some(FollowerCount::new(pubkey, Instant::now()));
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::FetchFollowers(pubkey))?;
+        }
+        let list = self.follower_list();
+        self.followers_fetching = list.as_ref().is_some_and(|list| list.fetching);
+        Ok(list.map(Action::OpenFollowers))
+    }
+
+    fn follower_list(&self) -> Option<FollowerList> {
+        let count = self.followers.as_ref()?;
+        let followers = count
+            .followers()
+            .into_iter()
+            .map(|pubkey| Follower {
+                pubkey,
+                name: self.profile_name(&pubkey),
+            })
+            .collect();
+        Some(FollowerList {
+            name: self.profile_name(&count.target),
+            followers,
+            fetching: count.is_fetching(Instant::now()),
+        })
+    }
+
+    /// Lists the followers again if they changed or finished being fetched.
+    fn refresh_followers(&mut self) -> Result<()> {
+        let Some(list) = self.follower_list() else {
+            return Ok(());
+        };
+        if !self.followers_changed && list.fetching == self.followers_fetching {
+            return Ok(());
+        }
+
+        self.followers_changed = false;
+        self.followers_fetching = list.fetching;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::RefreshFollowers(list))?;
+        }
+        Ok(())
+    }
+
+    fn append_repost(&mut self, repost: Event) {
+        // reposts grouped by event_id
+        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&repost) {
+            match self.reposts.entry(event_id) {
+                Entry::Vacant(e) => {
+                    e.insert(HashSet::from([repost]));
+                }
+                Entry::Occupied(mut e) => {
+                    e.get_mut().insert(repost);
+                }
+            };
+        };
+    }
+
+    fn append_zap_receipt(&mut self, zap_receipt: Event) {
+        // Receipts which didn't go through the ingestion pipeline are decoded here
+        self.zaps
+            .entry(zap_receipt.id)
+            .or_insert_with(|| Zap::from_receipt(&zap_receipt));
+
+        // zap receipts grouped by event_id
+        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&zap_receipt) {
+            match self.zap_receipts.entry(event_id) {
+                Entry::Vacant(e) => {
+                    e.insert(HashSet::from([zap_receipt]));
+                }
+                Entry::Occupied(mut e) => {
+                    e.get_mut().insert(zap_receipt);
+                }
+            }
+        }
+    }
+
+    /// Returns the zaps to the note.
+    fn note_zaps(&self, id: &EventId) -> Vec<Zap> {
+        self.zap_receipts
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|receipt| self.zaps.get(&receipt.id).copied())
+            .collect()
+    }
+
+    fn text_note(&self, event: Event, area: Rect, padding: Padding) -> TextNote {
+        let profile = self.profiles.get(&event.pubkey);
+        let reactions = self.reactions.get(&event.id).map_or(0, HashSet::len);
+        let reposts = self.reposts.get(&event.id).map_or(0, HashSet::len);
+        let zaps = self.note_zaps(&event.id);
+        let goal = ZapGoal::linked(&event)
+            .and_then(|id| Some((self.goals.get(&id)?.clone(), self.note_zaps(&id))));
+        let reply_to = self.reply_to_name(&event);
+        let mut annotations = self.annotations.get(&event.id).cloned().unwrap_or_default();
+        if self.config.future_notes.policy == FuturePolicy::Annotate && self.is_future(&event) {
+            let date =
+                timestamp::format(event.created_at, TimestampMode::Absolute, Timestamp::now());
+            annotations.push(i18n::tr_args(
+                "Dated in the future: {date}",
+                &[("date", &date)],
+            ));
+        }
+        let collapsed = self.is_reply_to_me(&event)
+            && self
+                .interaction_limiter
+                .is_collapsed(&event.pubkey, Timestamp::now());
+        let muted = self
+            .temporary_mutes
+            .is_muted(&event.pubkey, Timestamp::now());
+        let filtered = self
+            .filtered_score(&event)
+            .filter(|_| !collapsed && !muted)
+            .map(|score| score.total());
+        let expanded = self.expanded.contains(&event.id);
+        let delivery = self.delivery(&event.id);
+        let translation = self
+            .shown_translations
+            .contains(&event.id)
+            .then(|| self.translations.get(&event.id).cloned());
+        let file = FileMetadata::from_event(&event);
+        let link_previews = if self.shown_previews.contains(&event.id) {
+            link_preview::urls(&event.content)
+                .into_iter()
+                .filter_map(|url| Some((url.clone(), self.link_previews.get(&url)?.clone())))
+                .collect()
+        } else {
+            vec![]
+        };
+        let content_warning =
+            if self.config.content_warning.always_show || self.revealed.contains(&event.id) {
+                None
+            } else {
+                nip36::content_warning(&event)
+            };
+        TextNote::new(
+            event,
+            profile.cloned(),
+            reactions,
+            reposts,
+            zaps,
+            area,
+            padding,
+        )
+        .layout_cache(&self.layout_cache)
+        .goal(goal)
+        .reply_to(reply_to)
+        .annotations(annotations)
+        .collapsed(collapsed)
+        .muted(muted)
+        .filtered(filtered)
+        .content_warning(content_warning)
+        .timestamp_mode(self.timestamp_mode)
+        .preview(self.config.preview.max_lines, expanded)
+        .translation(translation.clone().flatten(), translation == Some(None))
+        .link_previews(link_previews)
+        .file(file)
+        .author_style(self.config.authors.colors, self.config.authors.avatars)
+        .delivery(delivery)
+    }
+
+    fn get_note(&self, i: usize) -> Option<&Event> {
+        self.tab().get_note(i)
+    }
+
+    fn author_name(&self, event: &Event) -> String {
+        if let Some(profile) = self.profiles.get(&event.pubkey) {
+            profile.name()
+        } else {
+            shorten_hex(&event.pubkey.to_string())
+        }
+    }
+
+    fn open_input(&mut self) -> Option<Action> {
+        if !self.config.has_identity() {
+            self.reply_to = None;
+            return Some(Action::SystemWarning(i18n::tr(
+                "Browse mode is read-only: set privatekey in the config to post",
+            )));
+        }
+
+        if self.show_input {
+            return None;
+        }
+
+        self.show_input = true;
+        if let Some(vim) = &mut self.vim {
+            vim.reset();
+        }
+        Some(Action::EnterMode(Mode::Compose))
+    }
+
+    fn close_input(&mut self) -> Option<Action> {
+        if !self.show_input {
+            return None;
+        }
+
+        self.show_input = false;
+        self.reply_to = None;
+        self.completion = None;
+        self.recipient_cursor = None;
+        Some(Action::LeaveMode)
+    }
+
+    fn set_reply_to(&mut self, reply_to: Option<Event>) {
+        let tags = reply_to.clone().map(ReplyTagsBuilder::build);
+        self.recipients = ReplyTagsBuilder::recipients(&tags.unwrap_or_default())
+            .into_iter()
+            .filter(|pubkey| Some(*pubkey) != self.pubkey)
+            .collect();
+        self.removed_recipients.clear();
+        self.reply_to = reply_to;
+    }
+
+    /// Toggles whether the recipient under the cursor is notified of the reply.
+    fn toggle_recipient(&mut self) {
+        let Some(pubkey) = self.recipient_cursor.map(|i| self.recipients[i]) else {
+            return;
+        };
+        if let Some(i) = self.removed_recipients.iter().position(|p| *p == pubkey) {
+            self.removed_recipients.remove(i);
+        } else {
+            self.removed_recipients.push(pubkey);
+        }
+    }
+
+    fn reply_tags(&self, reply_to: &Event) -> Vec<Tag> {
+        let mut removed = self.removed_recipients.clone();
+        removed.extend(self.pubkey);
+        ReplyTagsBuilder::without_recipients(ReplyTagsBuilder::build(reply_to.clone()), &removed)
+    }
+
+    /// Lists the recipients of the reply, crossing out the removed ones.
+    fn recipients_line(&self) -> Line<'static> {
+        let mut spans = vec![Span::raw(i18n::tr("To: "))];
+        for (i, pubkey) in self.recipients.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(", "));
+            }
+            let name = self
+                .profiles
+                .get(pubkey)
+                .map(Profile::name)
+                .unwrap_or_else(|| shorten_hex(&pubkey.to_string()));
+            let mut style = Style::default();
+            if self.removed_recipients.contains(pubkey) {
+                style = style.fg(Color::Gray).crossed_out();
+            }
+            if self.recipient_cursor == Some(i) {
+                style = style.reversed();
+            }
+            spans.push(Span::styled(name, style));
+        }
+        let hint = if self.recipient_cursor.is_some() {
+            i18n::tr(" [space] remove or add [enter] done")
+        } else {
+            i18n::tr(" [Ctrl-t] edit")
+        };
+        spans.push(Span::styled(hint, Style::default().fg(Color::Gray)));
+        Line::from(spans)
+    }
+
+    /// Types a key into the composer, or picks a candidate while completing.
+    fn input_key(&mut self, key: KeyEvent) {
+        if let Some(cursor) = self.recipient_cursor {
+            match key.code {
+                KeyCode::Left | KeyCode::Up | KeyCode::Char('h') | KeyCode::Char('k') => {
+                    self.recipient_cursor = Some(cursor.saturating_sub(1));
+                }
+                KeyCode::Right | KeyCode::Down | KeyCode::Char('l') | KeyCode::Char('j') => {
+                    self.recipient_cursor = Some((cursor + 1).min(self.recipients.len() - 1));
+                }
+                KeyCode::Char(' ') | KeyCode::Char('x') => self.toggle_recipient(),
+                KeyCode::Enter => self.recipient_cursor = None,
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(completion) = &mut self.completion {
+            match key.code {
+                KeyCode::Down => return completion.select_next(),
+                KeyCode::Up => return completion.select_previous(),
+                KeyCode::Tab | KeyCode::Enter => return self.accept_completion(),
+                // Closes the popup via Unselect, keeping the composer open
+                KeyCode::Esc => return,
+                _ => {}
+            }
+        }
+
+        if let Some(vim) = &mut self.vim {
+            if vim.input(key, &mut self.input) {
+                self.completion = None;
+                return;
+            }
+        }
+
+        // Moving the cursor may leave or enter a word to complete too
+        self.input.input(key);
+        self.update_completion();
+    }
+
+    fn update_completion(&mut self) {
+        let (row, col) = self.input.cursor();
+        let line = &self.input.lines()[row];
+        let followings = self.spam_filter.followings();
+        let mentions = MentionProvider {
+            profiles: &self.profiles,
+            index: &self.profile_index,
+            followings,
+        };
+        let hashtags = HashtagProvider {
+            recent: &self.recent_hashtags,
+        };
+        let emojis = EmojiProvider {
+            custom: &self.custom_emojis,
+        };
+        let providers: [&dyn CompletionProvider; 3] = [&mentions, &hashtags, &emojis];
+        self.completion = Completion::new(line, col, &providers);
+    }
+
+    /// Replaces the word being completed with the selected candidate.
+    fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let (_, col) = self.input.cursor();
+        for _ in completion.trigger.start..col {
+            self.input.delete_char();
+        }
+        self.input
+            .insert_str(format!("{} ", completion.selected().replacement));
     }
 
-    fn find_last_event_tag(&self, ev: &Event) -> Option<Tag> {
-        ev.tags
+    fn draw_completion(&self, f: &mut Frame<'_>, input_area: Rect) {
+        let Some(completion) = &self.completion else {
+            return;
+        };
+
+        let (row, _) = self.input.cursor();
+        let height = completion.candidates.len() as u16 + 2;
+        let width = input_area.width.min(50);
+        // Below the cursor if it fits in the composer, otherwise above
+        let below = input_area.y + row as u16 + 2;
+        let y = if below + height <= input_area.bottom() {
+            below
+        } else {
+            (input_area.y + row as u16).saturating_sub(height - 1)
+        };
+        let x = (input_area.x + 1 + completion.trigger.start as u16)
+            .min(input_area.right().saturating_sub(width));
+        let area = Rect::new(x, y, width, height).intersection(f.size());
+
+        let items: Vec<ListItem> = completion
+            .candidates
             .iter()
-            .filter(|tag| matches!(tag, Tag::Event { .. }))
-            .last()
-            .cloned()
+            .map(|candidate| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(candidate.label.clone()),
+                    Span::styled(
+                        format!(" {}", candidate.detail),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = widgets::List::new(items)
+            .block(
+                widgets::Block::default()
+                    .borders(Borders::ALL)
+                    .title(i18n::tr("[tab] complete [esc] cancel")),
+            )
+            .highlight_style(Style::default().reversed());
+        let mut state = ListState::default().with_selected(Some(completion.selected));
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut state);
     }
 
-    fn add_note(&mut self, event: Event) {
-        let note = Reverse(SortableEvent::new(event));
-        self.notes.find_or_insert(note);
+    /// Draws the selected note with its thread, which follows the selection.
+    fn draw_thread_pane(&self, f: &mut Frame<'_>, area: Rect) {
+        let padding = Padding::new(1, 1, 1, 0);
+        let block = widgets::Block::default()
+            .borders(Borders::LEFT)
+            .title(if self.ui.zen {
+                String::new()
+            } else {
+                i18n::tr("Thread")
+            })
+            .padding(padding);
+        let Some(selected) = self.tab().selected_note() else {
+            let hint = Paragraph::new(i18n::tr("Select a note to show its thread"))
+                .style(Style::default().fg(Color::Gray).italic())
+                .block(block);
+            f.render_widget(hint, area);
+            return;
+        };
 
-        // Keep selected position
-        let selection = self.list_state.selected().map(|i| i + 1);
-        self.list_state.select(selection);
+        let thread = self.selected_thread();
+        let index = thread.iter().position(|event| event.id == selected.id);
+        // NOTE: 1 = the left border
+        let note_area = Rect {
+            width: area.width.saturating_sub(1),
+            ..area
+        };
+        let items: Vec<TextNote> = thread
+            .into_iter()
+            .map(|event| self.text_note(event, note_area, padding).preview(0, false))
+            .collect();
+        let mut state = tui_widget_list::ListState::default();
+        state.select(index);
+        let list = List::new(items)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .truncate(true);
+        f.render_stateful_widget(list, area, &mut state);
     }
 
-    fn add_profile(&mut self, event: Event) {
-        if let Ok(metadata) = Metadata::from_json(event.content.clone()) {
-            let profile = Profile::new(event.pubkey, event.created_at, metadata);
-            if let Some(existing_profile) = self.profiles.get(&event.pubkey) {
-                if existing_profile.created_at > profile.created_at {
-                    return;
-                }
-            }
+    /// Counter of the draft at the bottom right of the composer, red when over the soft limit.
+    fn input_stats_title(&self) -> block::Title<'static> {
+        let stats = InputStats::new(&self.input.lines().join("\n"));
+        let limit = self.config.composer.soft_limit;
+        let (counter, style) = if limit == 0 {
+            let counter = i18n::tr_args(
+                " chars: {chars} · bytes: {bytes} ",
+                &[("chars", &stats.chars), ("bytes", &stats.bytes)],
+            );
+            (counter, Style::default())
+        } else {
+            let counter = i18n::tr_args(
+                " chars: {chars} · bytes: {bytes}/{limit} ",
+                &[
+                    ("chars", &stats.chars),
+                    ("bytes", &stats.bytes),
+                    ("limit", &limit),
+                ],
+            );
+            let style = if stats.exceeds(limit) {
+                Style::default().fg(Color::Red).bold()
+            } else {
+                Style::default()
+            };
+            (counter, style)
+        };
+        block::Title::from(Span::styled(counter, style))
+            .position(block::Position::Bottom)
+            .alignment(Alignment::Right)
+    }
 
-            self.profiles.insert(event.pubkey, profile);
-        }
+    /// Shows when the note will expire, at the top right of the composer.
+    fn expiration_title(&self) -> Option<block::Title<'static>> {
+        let left = timestamp::duration(self.expiration?);
+        Some(
+            block::Title::from(Span::styled(
+                i18n::tr_args(" ⏳ expires after {time} ", &[("time", &left)]),
+                Style::default().fg(Color::Yellow),
+            ))
+            .alignment(Alignment::Right),
+        )
     }
 
-    fn append_reaction(&mut self, reaction: Event) {
-        // reactions grouped by event_id
-        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&reaction) {
-            match self.reactions.entry(event_id) {
-                Entry::Vacant(e) => {
-                    e.insert(HashSet::from([reaction]));
-                }
-                Entry::Occupied(mut e) => {
-                    e.get_mut().insert(reaction);
-                }
-            }
-        }
+    /// Shows the mode of vim-like editing, at the bottom left of the composer.
+    fn vim_title(&self) -> Option<block::Title<'static>> {
+        let mode = self.vim.as_ref()?.mode();
+        Some(
+            block::Title::from(Span::styled(
+                format!(" {} ", i18n::tr(mode.label())),
+                Style::default().bold(),
+            ))
+            .position(block::Position::Bottom)
+            .alignment(Alignment::Left),
+        )
     }
 
-    fn append_repost(&mut self, repost: Event) {
-        // reposts grouped by event_id
-        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&repost) {
-            match self.reposts.entry(event_id) {
-                Entry::Vacant(e) => {
-                    e.insert(HashSet::from([repost]));
-                }
-                Entry::Occupied(mut e) => {
-                    e.get_mut().insert(repost);
-                }
-            };
+    /// Offers to mention the NIP-19 ids pasted into the draft, at the bottom of the composer.
+    fn bare_ids_title(&self) -> Option<block::Title<'static>> {
+        let bare = nip27::find_bare(&self.input.lines().join("\n"));
+        if bare.is_empty() {
+            return None;
+        }
+
+        let unlinked = bare
+            .iter()
+            .filter(|id| !self.linked_ids.contains(*id))
+            .count();
+        let (hint, style) = if unlinked > 0 {
+            let hint = i18n::plural(
+                " {count, plural, one {[Alt-m] mention # pasted id} other {[Alt-m] mention # pasted ids}} ",
+                unlinked as u64,
+            );
+            (hint, Style::default().fg(Color::Yellow))
+        } else {
+            let hint = i18n::plural(
+                " {count, plural, one {✓ # pasted id is mentioned} other {✓ # pasted ids are mentioned}} ",
+                bare.len() as u64,
+            );
+            (hint, Style::default().fg(Color::Green))
         };
+        Some(
+            block::Title::from(Span::styled(hint, style))
+                .position(block::Position::Bottom)
+                .alignment(Alignment::Center),
+        )
     }
 
-    fn append_zap_receipt(&mut self, zap_receipt: Event) {
-        // zap receipts grouped by event_id
-        if let Some(Tag::Event { event_id, .. }) = self.find_last_event_tag(&zap_receipt) {
-            match self.zap_receipts.entry(event_id) {
-                Entry::Vacant(e) => {
-                    e.insert(HashSet::from([zap_receipt]));
-                }
-                Entry::Occupied(mut e) => {
-                    e.get_mut().insert(zap_receipt);
+    /// Sends the bare NIP-19 ids in the draft as `nostr:` mentions, tagging them when submitted.
+    fn link_bare_ids(&mut self) {
+        self.linked_ids
+            .extend(nip27::find_bare(&self.input.lines().join("\n")));
+    }
+
+    fn has_draft(&self) -> bool {
+        self.input.lines().iter().any(|line| !line.is_empty())
+    }
+
+    fn submit_text_note(&mut self) -> Result<Option<Action>> {
+        if let (true, Some(tx)) = (self.show_input, &self.command_tx) {
+            let content = nip27::link_bare(&self.input.lines().join("\n"), &self.linked_ids);
+            if !content.is_empty() {
+                let channel_id = match self.tab().tab_type {
+                    TimelineTabType::Channel { id } => Some(id),
+                    _ => None,
+                };
+                let mut tags = match (channel_id, &self.reply_to) {
+                    (Some(id), reply_to) => nip28::message_tags(id, reply_to.as_ref()),
+                    (None, Some(reply_to)) => self.reply_tags(reply_to),
+                    (None, None) => vec![],
+                };
+                tags.extend(completion::mention_tags(&content, &tags));
+                tags.extend(completion::quote_tags(&content, &tags));
+                tags.extend(completion::hashtag_tags(&content, &tags));
+                tags.extend(self.custom_emojis.tags(&content, &tags));
+                if let Some(expiration) = self.expiration {
+                    tags.push(Tag::Expiration(Timestamp::now() + expiration));
                 }
+                // Files whose URLs were removed are not described
+                tags.extend(
+                    self.attachments
+                        .iter()
+                        .filter(|(url, _)| content.contains(url.as_str()))
+                        .map(|(_, tag)| tag.clone()),
+                );
+                let action = match channel_id {
+                    Some(_) => Action::SendChannelMessage(content, tags),
+                    None => Action::SendTextNote(content, tags),
+                };
+                tx.send(action)?;
+                self.clear_input();
+                return Ok(self.close_input());
             }
         }
+        Ok(None)
     }
 
-    fn text_note(&self, event: Event, area: Rect, padding: Padding) -> TextNote {
-        let default_reactions = HashSet::new();
-        let default_reposts = HashSet::new();
-        let default_zap_receipts = HashSet::new();
-        let profile = self.profiles.get(&event.pubkey);
-        let reactions = self.reactions.get(&event.id).unwrap_or(&default_reactions);
-        let reposts = self.reposts.get(&event.id).unwrap_or(&default_reposts);
-        let zap_receipts = self
-            .zap_receipts
-            .get(&event.id)
-            .unwrap_or(&default_zap_receipts);
-        TextNote::new(
-            event,
-            profile.cloned(),
-            reactions.clone(),
-            reposts.clone(),
-            zap_receipts.clone(),
-            area,
-            padding,
-        )
-    }
-
-    fn get_note(&self, i: usize) -> Option<&Event> {
-        self.notes.get(i).map(|note| &note.0.event)
+    /// Replaces the draft with the text edited in the external editor, keeping the attachments.
+    fn load_draft(&mut self, text: &str) {
+        self.input.select_all();
+        self.input.delete_str(usize::MAX);
+        self.input.insert_str(text);
+        self.completion = None;
     }
 
     fn clear_input(&mut self) {
         self.input.select_all();
         self.input.delete_str(usize::MAX);
+        self.attachments.clear();
+        self.linked_ids.clear();
+        self.expiration = None;
+    }
+
+    /// Inserts the URL of an uploaded file at the cursor.
+    fn attach(&mut self, url: String, tag: Tag) -> Option<Action> {
+        let (row, col) = self.input.cursor();
+        let after_space = col
+            .checked_sub(1)
+            .and_then(|i| self.input.lines()[row].chars().nth(i))
+            .is_none_or(char::is_whitespace);
+        if !after_space {
+            self.input.insert_char(' ');
+        }
+        self.input.insert_str(&url);
+        let message = i18n::tr_args("[Uploaded] {url}", &[("url", &url)]);
+        self.attachments.push((url, tag));
+        Some(Action::SystemMessage(message))
     }
 }
 
@@ -151,141 +2320,555 @@ impl Component for Home<'_> {
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.layout_cache.clear();
+        let lists = AuthorLists::load(&config.config._data_dir).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Failed to load author lists");
+            AuthorLists::default()
+        });
+        self.interaction_limiter = InteractionLimiter::new(
+            config.interaction_limit.reply_threshold,
+            config.interaction_limit.window,
+            lists,
+        );
+        self.pubkey = config.keys().map(|keys| keys.public_key());
+        self.spam_filter = SpamFilter::new(config.spam_filter.threshold, self.pubkey);
+        self.timestamp_mode = config.timestamps;
+        self.vim = config.composer.vim.then(Vim::default);
+        self.layout = PaneLayout::new(config.layout.split_pane, config.layout.split_min_width);
+        self.ui = UiLayout::new(&config.layout);
+        let session = Session::load(&config.config._data_dir, config.workspace.as_deref())
+            .unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to load the session");
+                None
+            });
+        self.last_seen = session
+            .as_ref()
+            .and_then(|session| session.last_seen)
+            .filter(|_| config.last_visit.marker);
+        if !config.startup_tabs.is_empty() {
+            self.restore_session(Session::with_tabs(config.startup_tabs.clone()))?;
+        } else if let Some(session) = session {
+            self.restore_session(session)?;
+        }
+        if let Some(tab_type) = config.startup_uri.clone() {
+            self.open_tab(tab_type)?;
+        }
+        for tab in &mut self.tabs {
+            tab.follow = config.follow_new_notes;
+        }
         self.config = config;
         Ok(())
     }
 
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
-            Action::ReceiveEvent(ev) => match ev.kind {
-                Kind::Metadata => self.add_profile(ev),
-                Kind::TextNote => self.add_note(ev),
-                Kind::Reaction => self.append_reaction(ev),
-                Kind::Repost => self.append_repost(ev), // TODO: show reposts on feed
-                Kind::ZapReceipt => self.append_zap_receipt(ev),
-                _ => {}
-            },
-            Action::ScrollUp => {
-                if !self.show_input {
-                    self.scroll_up()
+            Action::ReceiveEvent(ev) => self.receive_event(&TimelineTabType::Home, ev)?,
+            Action::ReceiveTabEvent(tab_type, ev) => self.receive_event(&tab_type, ev)?,
+            Action::ReceiveFetchedEvent(ev) => self.add_fetched_event(ev)?,
+            Action::Resize(..) => self.layout_cache.clear(),
+            Action::FocusLost => self.away.focus_lost(Timestamp::now()),
+            Action::ResumeTicks => self.gap_detector.reset(),
+            Action::FocusGained => {
+                if let Some(since) = self.away.focus_gained() {
+                    if self.config.last_visit.marker {
+                        self.last_seen = Some(since);
+                    }
+                    return Ok(Some(Action::AwayUnreadChanged(0)));
+                }
+            }
+            Action::ReceiveProfile(profile) => {
+                let pubkey = profile.pubkey;
+                self.insert_profile(profile);
+                self.refresh_profile(pubkey)?;
+            }
+            Action::ReceiveZap(id, zap) => {
+                self.zaps.insert(id, zap);
+            }
+            Action::Tick => {
+                self.detect_gap();
+                let now = Instant::now();
+                for tab in &mut self.tabs {
+                    tab.settle_requests(now);
+                }
+                self.notify_unread()?;
+                self.expire_mutes()?;
+                self.refresh_followers()?;
+                self.fetch_profiles()?;
+                return Ok(self.load_older_notes());
+            }
+            Action::FillGap if !self.show_input => return Ok(self.fill_gap()),
+            // Notes taller than the view are scrolled through before the selection moves
+            Action::ScrollUp if !self.show_input => {
+                let selected = self.tab().selected();
+                if !self.tab_mut().viewport.scroll_line_up(selected) {
+                    self.scroll_up();
+                }
+            }
+            Action::ScrollDown if !self.show_input => {
+                let selected = self.tab().selected();
+                if !self.tab_mut().viewport.scroll_line_down(selected) {
+                    self.scroll_down();
+                }
+            }
+            Action::ScrollToTop if !self.show_input => self.scroll_to_top(),
+            Action::ScrollToBottom if !self.show_input => self.scroll_to_bottom(),
+            Action::ScrollHalfPageDown if !self.show_input => {
+                self.tab_mut().viewport.request(ViewportMove::HalfPageDown);
+            }
+            Action::ScrollHalfPageUp if !self.show_input => {
+                self.tab_mut().viewport.request(ViewportMove::HalfPageUp);
+            }
+            Action::ScrollViewDown if !self.show_input => {
+                self.tab_mut().viewport.request(ViewportMove::ScrollDown);
+            }
+            Action::ScrollViewUp if !self.show_input => {
+                self.tab_mut().viewport.request(ViewportMove::ScrollUp);
+            }
+            Action::PlaceSelection(placement) if !self.show_input => {
+                self.tab_mut()
+                    .viewport
+                    .request(ViewportMove::Place(placement));
+            }
+            Action::OpenAuthorTimeline if !self.show_input => {
+                if let Some(pubkey) = self.tab().selected_note().map(|event| event.pubkey) {
+                    self.open_tab(TimelineTabType::UserTimeline { pubkey })?;
+                }
+            }
+            Action::OpenTab(tab_type) => self.open_tab(tab_type)?,
+            Action::AnnotateEvent(id, annotations) => {
+                self.annotations.insert(id, annotations);
+            }
+            Action::ToggleFilteredNotes if !self.show_input => self.toggle_filtered_notes()?,
+            Action::ShowEngagement if !self.show_input => self.show_engagement()?,
+            Action::Translate if !self.show_input => return Ok(self.translate()),
+            Action::ReceiveTranslation(id, translation) => match translation {
+                Ok(translation) => {
+                    self.translations.insert(id, translation);
                 }
+                Err(e) => {
+                    self.shown_translations.remove(&id);
+                    return Ok(Some(Action::SystemWarning(i18n::tr_args(
+                        "Cannot translate the note: {error}",
+                        &[("error", &e)],
+                    ))));
+                }
+            },
+            Action::PreviewLinks if !self.show_input => return Ok(self.preview_links()),
+            Action::ReceiveLinkPreview(url, preview) => {
+                let state = match preview {
+                    Ok(preview) => PreviewState::Ready(preview),
+                    Err(e) => PreviewState::Failed(e),
+                };
+                self.link_previews.insert(url, state);
+            }
+            Action::ToggleTimestamps => self.timestamp_mode = self.timestamp_mode.toggle(),
+            Action::ToggleFollowMode if !self.show_input => self.toggle_follow_mode()?,
+            Action::MeasureNoteStore => {
+                return Ok(Some(Action::NoteStoreMeasured(self.note_store_stats())));
             }
-            Action::ScrollDown => {
-                if !self.show_input {
-                    self.scroll_down()
+            // The engagement popup, the discover view or my activity is closed
+            Action::LeaveMode => {
+                self.engagement_note = None;
+                self.suggestions = None;
+                self.activity_shown = false;
+                self.followers = None;
+            }
+            Action::ShowFollowers if !self.show_input => return self.show_followers(),
+            Action::ViewMyActivity if !self.show_input => {
+                if !self.config.has_identity() {
+                    return Ok(Some(Action::SystemWarning(i18n::tr(
+                        "Browse mode has no activity: set privatekey in the config",
+                    ))));
+                }
+                self.activity_shown = true;
+                if let Some(tx) = &self.command_tx {
+                    tx.send(Action::FetchMyActivity)?;
                 }
+                return Ok(Some(Action::OpenMyActivity(self.my_activity())));
+            }
+            Action::Discover if !self.show_input => return self.discover(),
+            Action::Follow(pubkey) => return self.follow(pubkey, true),
+            Action::Unfollow(pubkey) => return self.follow(pubkey, false),
+            Action::EventPublished(event, _) if event.kind == Kind::ContactList => {
+                self.update_followings(event)?;
+            }
+            // Counts are updated before my relays echo the events
+            Action::EventPublished(event, _) if event.kind == Kind::Reaction => {
+                self.append_reaction(event);
             }
-            Action::ScrollToTop => {
-                if !self.show_input {
-                    self.scroll_to_top()
+            Action::EventPublished(event, _) if event.kind == Kind::EventDeletion => {
+                self.apply_deletion(&event);
+            }
+            Action::EventPublished(event, relays)
+                if matches!(event.kind, Kind::TextNote | Kind::ChannelMessage) =>
+            {
+                self.echo_note(event, relays)?;
+            }
+            Action::ReceiveReceipt(event_id, relay, status, message) => {
+                self.published.record(event_id, relay, status, message);
+            }
+            Action::Unreact if !self.show_input => return Ok(self.unreact()),
+            Action::Rebroadcast if !self.show_input => return Ok(self.rebroadcast()),
+            Action::ShowActionMenu if !self.show_input => return Ok(self.action_menu()),
+            Action::ListChannels if !self.show_input => return Ok(Some(self.list_channels())),
+            Action::RevealContent if !self.show_input => {
+                if let Some(id) = self.tab().selected_note().map(|event| event.id) {
+                    if !self.revealed.remove(&id) {
+                        self.revealed.insert(id);
+                    }
                 }
             }
-            Action::ScrollToBottom => {
-                if !self.show_input {
-                    self.scroll_to_bottom()
+            Action::ToggleExpand if !self.show_input => {
+                if let Some(id) = self.tab().selected_note().map(|event| event.id) {
+                    if !self.expanded.remove(&id) {
+                        self.expanded.insert(id);
+                    }
                 }
             }
+            Action::ToggleSplitPane if !self.show_input => self.layout.toggle(),
+            Action::LayoutChanged(ui) => self.ui = ui,
+            Action::RunPluginCommand(name) if !self.show_input => {
+                let note = self.tab().selected_note().cloned();
+                return Ok(Some(Action::InvokePluginCommand(name, note)));
+            }
+            Action::CloseTab if !self.show_input => self.close_tab()?,
+            Action::NextTab if !self.show_input => self.next_tab(),
+            Action::PreviousTab if !self.show_input => self.previous_tab(),
+            Action::MoveTabLeft if !self.show_input => self.move_tab_left(),
+            Action::MoveTabRight if !self.show_input => self.move_tab_right(),
+            Action::SelectTab(number) if !self.show_input => self.select_tab(number),
+            Action::ListTabs if !self.show_input => {
+                return Ok(Some(Action::OpenTabList(
+                    self.tab_labels(),
+                    self.active_tab,
+                )));
+            }
             Action::React => {
-                if let (false, Some(i), Some(tx)) = (
-                    self.show_input,
-                    self.list_state.selected(),
-                    &self.command_tx,
-                ) {
+                if let (false, Some(i), Some(tx)) =
+                    (self.show_input, self.selected(), &self.command_tx)
+                {
                     let event = self.get_note(i).expect("failed to get target event");
                     tx.send(Action::SendReaction(event.clone()))?;
                 }
             }
             Action::Repost => {
-                if let (false, Some(i), Some(tx)) = (
-                    self.show_input,
-                    self.list_state.selected(),
-                    &self.command_tx,
-                ) {
+                if let (false, Some(i), Some(tx)) =
+                    (self.show_input, self.selected(), &self.command_tx)
+                {
                     let event = self.get_note(i).expect("failed to get target event");
-                    tx.send(Action::SendRepost(event.clone()))?;
+                    let message = i18n::tr_args(
+                        "Repost the note by {name}?",
+                        &[("name", &self.author_name(event))],
+                    );
+                    tx.send(Action::Confirm(
+                        message,
+                        vec![Action::SendRepost(event.clone())],
+                    ))?;
                 }
             }
+            Action::ReviewCollapsedAuthors if !self.show_input => {
+                return Ok(Some(Action::OpenCollapsedAuthors(self.collapsed_authors())));
+            }
+            Action::WhitelistAuthor(pubkey) => {
+                self.interaction_limiter.whitelist(pubkey);
+                self.save_author_lists()?;
+            }
+            Action::MuteAuthor(pubkey) => {
+                self.interaction_limiter.mute(pubkey);
+                self.save_author_lists()?;
+            }
+            Action::MuteSelectedAuthor if !self.show_input => return Ok(self.mute_menu()),
+            Action::MuteAuthorFor(pubkey, secs) => self.mute_temporarily(pubkey, secs)?,
+            Action::Unselect if self.completion.is_some() => self.completion = None,
+            Action::Unselect if self.recipient_cursor.is_some() => self.recipient_cursor = None,
+            // Esc goes back to normal mode first, and closes the composer in normal mode
+            Action::Unselect
+                if self.show_input
+                    && self
+                        .vim
+                        .as_mut()
+                        .is_some_and(|vim| vim.escape(&mut self.input)) => {}
+            Action::EditRecipients if self.show_input && !self.recipients.is_empty() => {
+                self.completion = None;
+                self.recipient_cursor = match self.recipient_cursor {
+                    Some(_) => None,
+                    None => Some(0),
+                };
+            }
             Action::Unselect => {
-                self.list_state.select(None);
-                self.show_input = false;
-                self.reply_to = None;
+                self.select(None);
+                return Ok(self.close_input());
             }
-            Action::NewTextNote => {
-                self.reply_to = None;
-                self.show_input = true;
+            Action::NewTextNote if !self.show_input => {
+                self.set_reply_to(None);
+                return Ok(self.open_input());
             }
-            Action::ReplyTextNote => {
+            Action::ReplyTextNote if !self.show_input => {
                 if let Some(i) = self.selected() {
-                    let selected = self.get_note(i).unwrap();
-                    self.reply_to = Some(selected.clone());
-                    self.show_input = true;
-                }
-            }
-            Action::SubmitTextNote => {
-                if let (true, Some(tx)) = (self.show_input, &self.command_tx) {
-                    let content = self.input.lines().join("\n");
-                    if !content.is_empty() {
-                        let tags = if let Some(ref reply_to) = self.reply_to {
-                            ReplyTagsBuilder::build(reply_to.clone())
-                        } else {
-                            vec![]
-                        };
-                        tx.send(Action::SendTextNote(content, tags))?;
-                        self.reply_to = None;
-                        self.show_input = false;
-                        self.clear_input();
+                    let selected = self.get_note(i).cloned();
+                    self.set_reply_to(selected);
+                    return Ok(self.open_input());
+                }
+            }
+            Action::Quit if self.show_input => {
+                if self.has_draft() {
+                    return Ok(Some(Action::Confirm(
+                        i18n::tr("Discard the unsent note and quit?"),
+                        vec![Action::LeaveMode, Action::Quit],
+                    )));
+                }
+
+                if let Some(tx) = &self.command_tx {
+                    tx.send(Action::LeaveMode)?;
+                    tx.send(Action::Quit)?;
+                }
+            }
+            Action::Quit => self.save_session(),
+            Action::ExportTimeline if !self.show_input => {
+                return Ok(Some(Action::OpenPrompt(
+                    PromptKind::ExportTimeline,
+                    i18n::tr("Export the tab to (.json or .md)"),
+                    Self::default_export_path(),
+                )));
+            }
+            Action::ExportThread if !self.show_input && self.selected().is_some() => {
+                return Ok(Some(Action::OpenPrompt(
+                    PromptKind::ExportThread,
+                    i18n::tr("Export the thread to (.json or .md)"),
+                    Self::default_export_path(),
+                )));
+            }
+            Action::SubmitPrompt(
+                kind @ (PromptKind::ExportTimeline | PromptKind::ExportThread),
+                path,
+            ) => self.export(kind, path)?,
+            Action::LinkBareIds if self.show_input => self.link_bare_ids(),
+            Action::Paste(text) if self.show_input && self.recipient_cursor.is_none() => {
+                self.input.insert_str(text::normalize_newlines(&text));
+                self.update_completion();
+            }
+            Action::EditInEditor if self.show_input => {
+                return Ok(Some(Action::RunEditor(self.input.lines().join("\n"))));
+            }
+            Action::LoadDraft(text) if self.show_input => self.load_draft(&text),
+            Action::AttachMedia if self.show_input => {
+                return Ok(Some(Action::OpenPrompt(
+                    PromptKind::AttachMedia,
+                    i18n::tr("Attach a file (path)"),
+                    String::new(),
+                )));
+            }
+            Action::SubmitPrompt(PromptKind::AttachMedia, path) if !path.trim().is_empty() => {
+                return Ok(Some(Action::UploadMedia(path.trim().to_owned())));
+            }
+            Action::MediaUploaded(url, tag) => return Ok(self.attach(url, tag)),
+            Action::SetExpiration if self.show_input => {
+                return Ok(Some(Action::OpenPrompt(
+                    PromptKind::Expiration,
+                    i18n::tr("Expire the note after (30m, 12h, 3d or 2w; empty for never)"),
+                    String::new(),
+                )));
+            }
+            Action::SubmitPrompt(PromptKind::Expiration, input) => {
+                if input.trim().is_empty() {
+                    self.expiration = None;
+                } else {
+                    match timestamp::parse_duration(&input) {
+                        Some(duration) if duration > 0 => self.expiration = Some(duration),
+                        _ => {
+                            return Ok(Some(Action::SystemWarning(i18n::tr_args(
+                                "Invalid duration: {input}",
+                                &[("input", &input)],
+                            ))));
+                        }
                     }
                 }
             }
-            Action::Key(key) => {
-                if self.show_input {
-                    self.input.input(key);
+            Action::JumpToDate if !self.show_input => {
+                return Ok(Some(Action::OpenPrompt(
+                    PromptKind::JumpToDate,
+                    i18n::tr("Go to date (2024-01-01, 2024-01-01 15:00 or 3d ago)"),
+                    String::new(),
+                )));
+            }
+            Action::SubmitPrompt(PromptKind::JumpToDate, input) => {
+                return self.jump_to_date(&input);
+            }
+            Action::JumpToLastVisit if !self.show_input => {
+                if let Some(last_seen) = self.last_seen {
+                    self.open_tab(TimelineTabType::Home)?;
+                    return self.jump_to(last_seen);
+                }
+            }
+            Action::SubmitTextNote if self.show_input => {
+                let stats = InputStats::new(&self.input.lines().join("\n"));
+                let limit = self.config.composer.soft_limit;
+                if stats.exceeds(limit) {
+                    return Ok(Some(Action::Confirm(
+                        i18n::tr_args(
+                            "The note is {bytes} bytes, over the limit of {limit}, and some relays may reject it. Send anyway?",
+                            &[("bytes", &stats.bytes), ("limit", &limit)],
+                        ),
+                        vec![Action::SubmitLongTextNote],
+                    )));
                 }
+                return self.submit_text_note();
             }
+            Action::SubmitLongTextNote => return self.submit_text_note(),
+            Action::Key(key) if self.show_input => self.input_key(key),
             _ => {}
         }
         Ok(None)
     }
 
-    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let padding = Padding::new(1, 1, 1, 3);
+    fn region(&self) -> Region {
+        Region::Main
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, main_area: Rect) -> Result<()> {
+        let (mut area, pane_area) = self.layout.areas(main_area);
+        if let Some(pane_area) = pane_area {
+            self.draw_thread_pane(f, pane_area);
+        }
+
+        let shows_tabs = self.tabs.len() > 1 && self.ui.shows_tab_bar();
+        if shows_tabs && self.ui.vertical_tabs {
+            let tabs = TabBar::new(self.tab_labels(), self.active_tab).vertical(true);
+            let width = tabs.width().min(area.width / 3);
+            f.render_widget(tabs, Rect { width, ..area });
+            area.x += width;
+            area.width -= width;
+        }
+
+        let padding = Padding::new(1, 1, 1, 1);
+        let last_visit = self
+            .last_seen
+            .filter(|_| self.tab().tab_type == TimelineTabType::Home)
+            .and_then(|last_seen| self.tab().index_below(last_seen));
         let items: Vec<TextNote> = self
+            .tab()
             .notes
             .iter()
-            .map(|ev| self.text_note(ev.0.event.clone(), area, padding))
+            .enumerate()
+            .map(|(i, ev)| {
+                let reposted_by = if ev.0.is_reposted() {
+                    self.reposted_by(&ev.0.event.id)
+                } else {
+                    vec![]
+                };
+                self.text_note(ev.0.event.clone(), area, padding)
+                    .reposted_by(reposted_by)
+                    .gap_above(self.tab().has_gap_above(i))
+                    .last_visit_above(last_visit == Some(i))
+                    .chat(matches!(
+                        self.tab().tab_type,
+                        TimelineTabType::Channel { .. }
+                    ))
+            })
             .collect();
 
-        let list = List::new(items)
-            .block(widgets::Block::default().title("Timeline").padding(padding))
-            .style(Style::default().fg(Color::White))
-            .truncate(true);
+        // The tab bar takes the place of the title once another tab is opened
+        let shows_tab_bar = shows_tabs && !self.ui.vertical_tabs;
+        let title = if shows_tab_bar || self.ui.zen {
+            String::new()
+        } else {
+            self.tab_title(&self.tab().tab_type)
+        };
+        let block = widgets::Block::default().title(title).padding(padding);
+        let heights: Vec<usize> = items.iter().map(Listable::height).collect();
+        let inner = block.inner(area);
+        let height = inner.height as usize;
+        let tab = self.tab_mut();
+        let selected = tab.viewport.layout(&heights, height, tab.selected());
+        tab.select(selected);
+
+        let (line, overflow) = (tab.viewport.line(), tab.viewport.overflow());
+
+        // Only the notes from the viewport are given, so that the list scrolls as the viewport does
+        let offset = tab.viewport.offset();
+        match selected.filter(|_| overflow > 0) {
+            // The selected note fills the view, from the line it is scrolled to
+            Some(i) => {
+                let note = items.into_iter().nth(i).map(Listable::highlight);
+                f.render_widget(block.style(Style::default().fg(Color::White)), area);
+                if let Some(note) = note {
+                    f.render_widget(Scrolled::new(note, heights[i], line), inner);
+                }
+                let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(None)
+                    .end_symbol(None);
+                let mut state = ScrollbarState::new(overflow + 1).position(line);
+                let track = Rect {
+                    x: inner.right(),
+                    width: 1,
+                    ..inner
+                };
+                f.render_stateful_widget(scrollbar, track, &mut state);
+            }
+            None => {
+                let mut state = tui_widget_list::ListState::default();
+                state.select(selected.and_then(|i| i.checked_sub(offset)));
+                let list = List::new(items.into_iter().skip(offset).collect())
+                    .block(block)
+                    .style(Style::default().fg(Color::White))
+                    .truncate(true);
+                f.render_stateful_widget(list, area, &mut state);
+            }
+        }
+        self.draw_new_notes_banner(f, inner);
 
-        f.render_stateful_widget(list, area, &mut self.list_state);
+        if shows_tab_bar {
+            let tabs = TabBar::new(self.tab_labels(), self.active_tab);
+            f.render_widget(tabs, Rect { height: 1, ..area });
+        }
 
         if self.show_input {
-            let mut input_area = f.size();
-            input_area.height /= 2;
-            input_area.y = input_area.height;
-            input_area.height -= 2;
+            let input_area = self.ui.composer_area(main_area);
             f.render_widget(Clear, input_area);
 
             let block = if let Some(ref reply_to) = self.reply_to {
-                let name = if let Some(profile) = self.profiles.get(&reply_to.pubkey) {
-                    profile.name()
-                } else {
-                    shorten_hex(&reply_to.pubkey.to_string())
-                };
+                let name = self.author_name(reply_to);
 
+                let block = widgets::Block::default()
+                    .borders(Borders::ALL)
+                    .title(i18n::tr_args(
+                        "Replying to {name}: Press ESC to close",
+                        &[("name", &name)],
+                    ));
+                if self.recipients.is_empty() {
+                    block
+                } else {
+                    block.title(
+                        block::Title::from(self.recipients_line())
+                            .position(block::Position::Bottom),
+                    )
+                }
+            } else if let TimelineTabType::Channel { .. } = self.tab().tab_type {
+                let channel = self.tab_title(&self.tab().tab_type);
                 widgets::Block::default()
                     .borders(Borders::ALL)
-                    .title(format!("Replying to {name}: Press ESC to close"))
+                    .title(i18n::tr_args(
+                        "Message to {channel}: Press ESC to close",
+                        &[("channel", &channel)],
+                    ))
             } else {
                 widgets::Block::default()
                     .borders(Borders::ALL)
-                    .title("New note: Press ESC to close")
+                    .title(i18n::tr("New note: Press ESC to close"))
             };
+            let mut block = block.title(self.input_stats_title());
+            if let Some(title) = self.bare_ids_title() {
+                block = block.title(title);
+            }
+            if let Some(title) = self.expiration_title() {
+                block = block.title(title);
+            }
+            if let Some(title) = self.vim_title() {
+                block = block.title(title);
+            }
             self.input.set_block(block);
             f.render_widget(self.input.widget(), input_area);
+            self.draw_completion(f, input_area);
         }
 
         Ok(())
@@ -294,18 +2877,412 @@ impl Component for Home<'_> {
 
 impl ScrollableList<Event> for Home<'_> {
     fn select(&mut self, index: Option<usize>) {
-        self.list_state.select(index);
+        self.tab_mut().select(index);
     }
 
     fn selected(&self) -> Option<usize> {
-        self.list_state.selected()
+        self.tab().selected()
     }
 
     fn len(&self) -> usize {
-        self.notes.len()
+        self.tab().len()
     }
 
     fn is_empty(&self) -> bool {
-        self.notes.is_empty()
+        self.tab().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+    use crate::{
+        components::testing::{browse_config, composing, config, note, render},
+        config::RelayConfig,
+    };
+
+    fn home(config: Config) -> Home<'static> {
+        let mut home = Home::new();
+        home.register_config_handler(config).unwrap();
+        home
+    }
+
+    fn global(max_notes: usize, threshold: u64) -> Home<'static> {
+        let mut config = config();
+        config.global.max_notes = max_notes;
+        config.spam_filter.threshold = threshold;
+        let mut home = home(config);
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        home
+    }
+
+    fn receive(home: &mut Home, tab_type: TimelineTabType, note: &Event) {
+        home.update(Action::ReceiveTabEvent(tab_type, note.clone()))
+            .unwrap();
+    }
+
+    #[rstest]
+    fn test_reactions_and_reposts() {
+        let gm = note("gm", 1_700_000_000);
+        let mut home = home(config());
+        receive(&mut home, TimelineTabType::Home, &gm);
+        let like = |keys: &Keys| EventBuilder::reaction(&gm, "+").to_event(keys).unwrap();
+        let (alice, bob) = (Keys::generate(), Keys::generate());
+        for event in [
+            like(&alice),
+            like(&bob),
+            EventBuilder::repost(&gm, None).to_event(&alice).unwrap(),
+        ] {
+            // Events arrive again from other relays
+            receive(&mut home, TimelineTabType::Home, &event);
+            receive(&mut home, TimelineTabType::Home, &event);
+        }
+
+        let note = home.text_note(gm, Rect::new(0, 0, 60, 10), Padding::default());
+        assert_eq!((note.reactions_count(), note.reposts_count()), (2, 1));
+        let lines = render(&mut home, 60, 10);
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.trim_start().starts_with("2Likes 1Repost")),
+            "{lines:?}"
+        );
+    }
+
+    #[rstest]
+    #[case(false, "1:⌂ Timeline|2:◎ Global")]
+    #[case(true, "1:⌂ Timeline│")]
+    fn test_tab_bar(#[case] vertical: bool, #[case] first_line: &str) {
+        let mut config = config();
+        config.layout.vertical_tabs = vertical;
+        let mut home = home(config);
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        home.update(Action::SelectTab(1)).unwrap();
+
+        let lines = render(&mut home, 60, 10);
+        assert!(lines[0].starts_with(first_line), "{lines:?}");
+        assert_eq!(lines[1].starts_with("2:◎ Global"), vertical);
+        assert_eq!(
+            home.update(Action::ListTabs).unwrap(),
+            Some(Action::OpenTabList(
+                vec![String::from("⌂ Timeline"), String::from("◎ Global")],
+                0
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case(false, true, true)]
+    #[case(false, false, false)]
+    #[case(true, true, false)]
+    fn test_layout_changed(#[case] zen: bool, #[case] tab_bar: bool, #[case] shown: bool) {
+        let mut home = home(config());
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        home.update(Action::LayoutChanged(UiLayout {
+            tab_bar,
+            zen,
+            ..UiLayout::default()
+        }))
+        .unwrap();
+
+        let lines = render(&mut home, 60, 10);
+        assert_eq!(lines[0].contains("2:◎ Global"), shown, "{lines:?}");
+    }
+
+    #[rstest]
+    fn test_unfollow_needs_confirmation() {
+        let config = config();
+        let me = config.keys().unwrap();
+        let gm = note("gm", 1_700_000_000);
+        let mut home = home(config);
+        let contact_list = EventBuilder::new(Kind::ContactList, "", [Tag::public_key(gm.pubkey)])
+            .to_event(&me)
+            .unwrap();
+        home.update(Action::ReceiveEvent(contact_list)).unwrap();
+        receive(&mut home, TimelineTabType::Home, &gm);
+        home.update(Action::ScrollToTop).unwrap();
+
+        let Some(Action::OpenActionMenu(items)) = home.action_menu() else {
+            panic!("no action menu");
+        };
+        let unfollow = items
+            .into_iter()
+            .find(|item| item.label == "Unfollow the author")
+            .unwrap();
+        assert!(
+            matches!(
+                unfollow.action,
+                Action::Confirm(ref message, ref actions)
+                    if message.starts_with("Unfollow ") && actions == &[Action::Unfollow(gm.pubkey)]
+            ),
+            "{:?}",
+            unfollow.action
+        );
+    }
+
+    #[rstest]
+    #[case(&[true, true], Some("Broadcast the note by"), Some("to 2 relays?"))]
+    #[case(&[true, false], Some("Broadcast the note by"), Some("to 1 relay?"))]
+    #[case(&[false, false], None, None)]
+    fn test_rebroadcast(
+        #[case] write: &[bool],
+        #[case] prefix: Option<&str>,
+        #[case] suffix: Option<&str>,
+    ) {
+        let gm = note("gm", 1_700_000_000);
+        let mut config = config();
+        config.relays = write
+            .iter()
+            .enumerate()
+            .map(|(i, write)| RelayConfig {
+                write: *write,
+                ..RelayConfig::new(format!("wss://relay{i}.example.com"))
+            })
+            .collect();
+        let mut home = home(config);
+        receive(&mut home, TimelineTabType::Home, &gm);
+        home.update(Action::ScrollDown).unwrap();
+
+        match home.rebroadcast() {
+            Some(Action::Confirm(message, actions)) => {
+                assert!(message.starts_with(prefix.unwrap()), "{message}");
+                assert!(message.ends_with(suffix.unwrap()), "{message}");
+                assert_eq!(actions, vec![Action::BroadcastEvent(gm)]);
+            }
+            Some(Action::SystemWarning(warning)) => {
+                assert_eq!(prefix, None);
+                assert_eq!(warning, "No relays to write to");
+            }
+            action => panic!("unexpected {action:?}"),
+        }
+    }
+
+    #[rstest]
+    #[case("", vec![Action::LeaveMode, Action::Quit], None)]
+    #[case(
+        "gm",
+        vec![],
+        Some(Action::Confirm(
+            String::from("Discard the unsent note and quit?"),
+            vec![Action::LeaveMode, Action::Quit],
+        )),
+    )]
+    fn test_quit_while_composing(
+        #[case] draft: &str,
+        #[case] sent: Vec<Action>,
+        #[case] returned: Option<Action>,
+    ) {
+        let mut home = composing(&[], draft);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        home.register_action_handler(tx).unwrap();
+
+        assert_eq!(home.update(Action::Quit).unwrap(), returned);
+        assert_eq!(
+            std::iter::from_fn(|| rx.try_recv().ok()).collect::<Vec<_>>(),
+            sent
+        );
+    }
+
+    #[rstest]
+    #[case(Action::NewTextNote)]
+    #[case(Action::ReplyTextNote)]
+    fn test_browse_mode_is_read_only(#[case] action: Action) {
+        let mut home = home(browse_config());
+        receive(&mut home, TimelineTabType::Home, &note("gm", 1_700_000_000));
+        home.update(Action::ScrollDown).unwrap();
+
+        assert_eq!(
+            home.update(action).unwrap(),
+            Some(Action::SystemWarning(String::from(
+                "Browse mode is read-only: set privatekey in the config to post"
+            )))
+        );
+        assert!(!home.show_input);
+        assert_eq!(home.reply_to, None);
+    }
+
+    fn tab_types(home: &Home) -> Vec<TimelineTabType> {
+        home.tabs.iter().map(|tab| tab.tab_type.clone()).collect()
+    }
+
+    #[rstest]
+    fn test_reorder_tabs() {
+        let hashtag = TimelineTabType::Hashtag {
+            tag: String::from("nostr"),
+        };
+        let mut home = home(config());
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        home.update(Action::OpenTab(hashtag.clone())).unwrap();
+
+        home.update(Action::MoveTabLeft).unwrap();
+        assert_eq!(
+            tab_types(&home),
+            [
+                TimelineTabType::Home,
+                hashtag.clone(),
+                TimelineTabType::Global
+            ]
+        );
+        assert_eq!(home.tab().tab_type, hashtag);
+
+        // The first tab stays
+        home.update(Action::MoveTabLeft).unwrap();
+        home.update(Action::MoveTabLeft).unwrap();
+        assert_eq!(
+            tab_types(&home),
+            [
+                hashtag.clone(),
+                TimelineTabType::Home,
+                TimelineTabType::Global
+            ]
+        );
+        assert_eq!(home.active_tab, 0);
+
+        home.update(Action::MoveTabRight).unwrap();
+        assert_eq!(
+            tab_types(&home),
+            [
+                TimelineTabType::Home,
+                hashtag.clone(),
+                TimelineTabType::Global
+            ]
+        );
+        assert_eq!(home.active_tab, 1);
+    }
+
+    #[rstest]
+    #[case(1, 0)]
+    #[case(3, 2)]
+    // Numbers without a tab keep the current one
+    #[case(0, 1)]
+    #[case(4, 1)]
+    fn test_select_tab(#[case] number: usize, #[case] expected: usize) {
+        let mut home = home(config());
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        home.update(Action::OpenTab(TimelineTabType::Hashtag {
+            tag: String::from("nostr"),
+        }))
+        .unwrap();
+        home.update(Action::SelectTab(2)).unwrap();
+
+        home.update(Action::SelectTab(number)).unwrap();
+        assert_eq!(home.active_tab, expected);
+    }
+
+    #[rstest]
+    fn test_close_tab() {
+        let mut home = home(config());
+        home.update(Action::OpenTab(TimelineTabType::Global))
+            .unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        home.register_action_handler(tx).unwrap();
+
+        home.update(Action::CloseTab).unwrap();
+        assert_eq!(tab_types(&home), [TimelineTabType::Home]);
+        assert_eq!(
+            rx.try_recv().ok(),
+            Some(Action::UnsubscribeTimeline(TimelineTabType::Global))
+        );
+
+        // The home tab can't be closed
+        home.update(Action::CloseTab).unwrap();
+        assert_eq!(tab_types(&home), [TimelineTabType::Home]);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[rstest]
+    fn test_thread_pane() {
+        let keys = Keys::generate();
+        let root = EventBuilder::text_note("gm", [])
+            .custom_created_at(Timestamp::from(1_700_000_000))
+            .to_event(&keys)
+            .unwrap();
+        let reply = EventBuilder::text_note("good morning", ReplyTagsBuilder::build(root.clone()))
+            .custom_created_at(Timestamp::from(1_700_000_060))
+            .to_event(&keys)
+            .unwrap();
+        let other = note("gn", 1_700_000_030);
+        let mut home = home(config());
+        for note in [&root, &other, &reply] {
+            receive(&mut home, TimelineTabType::Home, note);
+        }
+        // The side pane is right of 55% of the width
+        let pane = |home: &mut Home| -> Vec<String> {
+            render(home, 120, 20)
+                .iter()
+                .map(|line| line.chars().skip(66).collect())
+                .collect()
+        };
+        let contains = |lines: &[String], text: &str| lines.iter().any(|line| line.contains(text));
+
+        assert!(!contains(&pane(&mut home), "Thread"));
+        home.update(Action::ToggleSplitPane).unwrap();
+        assert!(contains(
+            &pane(&mut home),
+            "Select a note to show its thread"
+        ));
+
+        home.update(Action::ScrollToTop).unwrap();
+        assert_eq!(home.selected_thread(), [root, reply]);
+        let lines = pane(&mut home);
+        assert!(contains(&lines, "Thread"));
+        assert!(contains(&lines, "good morning"));
+        assert!(!contains(&lines, "gn"));
+    }
+
+    #[rstest]
+    fn test_global_forgets_dropped_notes() {
+        let mut home = global(2, 0);
+        let notes: Vec<Event> = (0..4).map(|i| note("gm", 1_700_000_000 + i)).collect();
+        receive(&mut home, TimelineTabType::Home, &notes[0]);
+        for note in &notes {
+            receive(&mut home, TimelineTabType::Global, note);
+        }
+
+        assert_eq!(home.tab().len(), 2);
+        // Still shown in the home tab
+        assert!(home.events.contains_key(&notes[0].id));
+        assert!(!home.events.contains_key(&notes[1].id));
+        assert!(home.events.contains_key(&notes[3].id));
+    }
+
+    #[rstest]
+    fn test_global_drops_spam() {
+        // Fresh notes by unknown authors score below the threshold, whatever their PoW
+        let mut home = global(2, 1000);
+        let spam = note("buy now", Timestamp::now().as_u64());
+        receive(&mut home, TimelineTabType::Global, &spam);
+        assert!(home.tab().is_empty());
+        assert!(home.hidden_notes.is_empty());
+        assert!(!home.events.contains_key(&spam.id));
+
+        home.update(Action::ToggleFilteredNotes).unwrap();
+        receive(&mut home, TimelineTabType::Global, &spam);
+        assert!(home.tab().is_empty());
+    }
+
+    #[rstest]
+    fn test_hidden_notes_are_bounded() {
+        let mut home = global(2, 1000);
+        home.config.spam_filter.action = FilterAction::Hide;
+        let now = Timestamp::now().as_u64();
+        let notes: Vec<Event> = (0..=MAX_HIDDEN_NOTES as u64)
+            .map(|i| note("buy now", now - i))
+            .collect();
+        for note in &notes {
+            receive(&mut home, TimelineTabType::Home, note);
+        }
+
+        assert_eq!(home.hidden_notes.len(), MAX_HIDDEN_NOTES);
+        assert!(!home.events.contains_key(&notes[0].id));
+        assert!(home.events.contains_key(&notes[MAX_HIDDEN_NOTES].id));
     }
 }
@@ -0,0 +1,170 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode, stats::RelayKinds};
+
+/// Interval to refresh the counts while the view is open.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Kinds shown for each relay, from the most frequent.
+const TOP_KINDS: usize = 6;
+
+/// Breaks down the events received from each relay by kind, pointing out relays which send
+/// mostly kinds that nostui doesn't use, so they can be removed from the relay list.
+pub struct KindStatsView {
+    command_tx: Option<UnboundedSender<Action>>,
+    relays: Vec<RelayKinds>,
+    open: bool,
+    refreshed_at: Instant,
+    scroll: u16,
+}
+
+impl Default for KindStatsView {
+    fn default() -> Self {
+        Self {
+            command_tx: None,
+            relays: vec![],
+            open: false,
+            refreshed_at: Instant::now(),
+            scroll: 0,
+        }
+    }
+}
+
+impl KindStatsView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn relay_lines(relay: &RelayKinds) -> Vec<Line<'static>> {
+        let share = (relay.unused_share() * 100.0).round();
+        let mut lines = vec![Line::from(vec![
+            Span::styled(relay.relay.clone(), Style::default().bold()),
+            Span::styled(
+                i18n::tr_args(
+                    "  {events} events, {share}% of unused kinds",
+                    &[("events", &relay.total), ("share", &share)],
+                ),
+                Style::default().fg(Color::Gray),
+            ),
+        ])];
+
+        let mut kinds: Vec<String> = relay
+            .kinds
+            .iter()
+            .take(TOP_KINDS)
+            .map(|(kind, count)| format!("{kind}: {count}"))
+            .collect();
+        if relay.kinds.len() > TOP_KINDS {
+            kinds.push(String::from("…"));
+        }
+        lines.push(Line::from(format!("  {}", kinds.join(", "))));
+
+        if relay.is_wasteful() {
+            lines.push(Line::styled(
+                i18n::tr("  Mostly kinds nostui doesn't use: consider removing the relay"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        lines
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 4 / 5;
+        let height = area.height * 3 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for KindStatsView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ReceiveKindStats(relays) => {
+                self.relays = relays;
+                self.refreshed_at = Instant::now();
+            }
+            Action::ViewKindStats if !self.open => {
+                self.open = true;
+                self.scroll = 0;
+                return Ok(Some(Action::EnterMode(Mode::Popup)));
+            }
+            Action::Tick if self.open && self.refreshed_at.elapsed() >= REFRESH_INTERVAL => {
+                self.refreshed_at = Instant::now();
+                return Ok(Some(Action::ViewKindStats));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Event kinds by relay: [esc] close"))
+            .padding(Padding::horizontal(1));
+
+        let lines: Vec<Line> = if self.relays.is_empty() {
+            vec![Line::from(i18n::tr("No events received yet"))]
+        } else {
+            self.relays
+                .iter()
+                .flat_map(|relay| {
+                    let mut lines = Self::relay_lines(relay);
+                    lines.push(Line::default());
+                    lines
+                })
+                .collect()
+        };
+        let paragraph = Paragraph::new(lines).block(block).scroll((self.scroll, 0));
+        f.render_widget(paragraph, popup);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::config::LayoutConfig;
+
+/// Share of the width taken by the timeline when the side pane is shown.
+const TIMELINE_PERCENTAGE: u16 = 55;
+
+/// Lines taken by the status line and the message line.
+const STATUS_BAR_HEIGHT: u16 = 2;
+
+/// Bounds and step of the composer height, in percent of the screen.
+const MIN_COMPOSER_HEIGHT: u16 = 20;
+const MAX_COMPOSER_HEIGHT: u16 = 90;
+const COMPOSER_HEIGHT_STEP: u16 = 10;
+
+/// Part of the screen which a component is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// The whole screen, e.g. for popups
+    Screen,
+    /// The screen above the status bar
+    Main,
+    /// The whole screen while the status bar is shown, so that it can show the message history
+    StatusBar,
+}
+
+/// Which regions of the UI are shown and how large they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiLayout {
+    pub status_bar: bool,
+    pub tab_bar: bool,
+    pub vertical_tabs: bool,
+    /// Hides the status bar, the tab bar and titles regardless of the others
+    pub zen: bool,
+    /// Height of the composer in percent of the screen
+    pub composer_height: u16,
+}
+
+impl Default for UiLayout {
+    fn default() -> Self {
+        Self::new(&LayoutConfig::default())
+    }
+}
+
+impl UiLayout {
+    pub fn new(config: &LayoutConfig) -> Self {
+        Self {
+            status_bar: config.status_bar,
+            tab_bar: config.tab_bar,
+            vertical_tabs: config.vertical_tabs,
+            zen: config.zen,
+            composer_height: config
+                .composer_height
+                .clamp(MIN_COMPOSER_HEIGHT, MAX_COMPOSER_HEIGHT),
+        }
+    }
+
+    pub fn shows_status_bar(&self) -> bool {
+        self.status_bar && !self.zen
+    }
+
+    pub fn shows_tab_bar(&self) -> bool {
+        self.tab_bar && !self.zen
+    }
+
+    pub fn grow_composer(&mut self) {
+        self.composer_height =
+            (self.composer_height + COMPOSER_HEIGHT_STEP).min(MAX_COMPOSER_HEIGHT);
+    }
+
+    pub fn shrink_composer(&mut self) {
+        self.composer_height = self
+            .composer_height
+            .saturating_sub(COMPOSER_HEIGHT_STEP)
+            .max(MIN_COMPOSER_HEIGHT);
+    }
+
+    /// Returns the area of a region, or `None` if it is hidden.
+    pub fn area(&self, region: Region, screen: Rect) -> Option<Rect> {
+        match region {
+            Region::Screen => Some(screen),
+            Region::Main if self.shows_status_bar() => Some(Rect {
+                height: screen.height.saturating_sub(STATUS_BAR_HEIGHT),
+                ..screen
+            }),
+            Region::Main => Some(screen),
+            Region::StatusBar => self.shows_status_bar().then_some(screen),
+        }
+    }
+
+    /// Returns the area of the composer at the bottom of `area`.
+    pub fn composer_area(&self, area: Rect) -> Rect {
+        let height = (u32::from(area.height) * u32::from(self.composer_height) / 100) as u16;
+        Rect {
+            y: area.bottom() - height,
+            height,
+            ..area
+        }
+    }
+}
+
+/// Splits the screen into the timeline and a side pane which shows the thread of the selected note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaneLayout {
+    /// Whether the side pane is shown
+    pub split: bool,
+    /// Width below which only the timeline is shown, even if the side pane is on
+    pub min_width: u16,
+}
+
+impl PaneLayout {
+    pub fn new(split: bool, min_width: u16) -> Self {
+        Self { split, min_width }
+    }
+
+    pub fn toggle(&mut self) {
+        self.split = !self.split;
+    }
+
+    /// Returns the area of the timeline and the one of the side pane, if it fits.
+    pub fn areas(&self, area: Rect) -> (Rect, Option<Rect>) {
+        if !self.split || area.width < self.min_width {
+            return (area, None);
+        }
+
+        let [timeline, pane] = Layout::horizontal([
+            Constraint::Percentage(TIMELINE_PERCENTAGE),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        (timeline, Some(pane))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn ui(status_bar: bool, tab_bar: bool, zen: bool) -> UiLayout {
+        UiLayout {
+            status_bar,
+            tab_bar,
+            zen,
+            ..UiLayout::default()
+        }
+    }
+
+    #[rstest]
+    #[case(ui(true, true, false), true, true)]
+    #[case(ui(false, true, false), false, true)]
+    #[case(ui(true, false, false), true, false)]
+    #[case(ui(true, true, true), false, false)]
+    fn test_shows(#[case] ui: UiLayout, #[case] status_bar: bool, #[case] tab_bar: bool) {
+        assert_eq!(ui.shows_status_bar(), status_bar);
+        assert_eq!(ui.shows_tab_bar(), tab_bar);
+    }
+
+    #[rstest]
+    #[case(ui(true, true, false), Some(18), Some(20))]
+    #[case(ui(false, true, false), Some(20), None)]
+    #[case(ui(true, true, true), Some(20), None)]
+    fn test_area(
+        #[case] ui: UiLayout,
+        #[case] main_height: Option<u16>,
+        #[case] status_bar_height: Option<u16>,
+    ) {
+        let screen = Rect::new(0, 0, 80, 20);
+        assert_eq!(ui.area(Region::Screen, screen), Some(screen));
+        let height = |region| ui.area(region, screen).map(|area| area.height);
+        assert_eq!(height(Region::Main), main_height);
+        assert_eq!(height(Region::StatusBar), status_bar_height);
+    }
+
+    #[rstest]
+    #[case(50, 50)]
+    #[case(0, 20)]
+    #[case(100, 90)]
+    fn test_composer_height_from_config(#[case] configured: u16, #[case] expected: u16) {
+        let config = LayoutConfig {
+            composer_height: configured,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(UiLayout::new(&config).composer_height, expected);
+    }
+
+    #[rstest]
+    fn test_resize_composer() {
+        let mut ui = UiLayout::default();
+        ui.grow_composer();
+        assert_eq!(ui.composer_height, 60);
+        for _ in 0..10 {
+            ui.grow_composer();
+        }
+        assert_eq!(ui.composer_height, 90);
+        for _ in 0..10 {
+            ui.shrink_composer();
+        }
+        assert_eq!(ui.composer_height, 20);
+
+        let area = Rect::new(0, 2, 80, 20);
+        assert_eq!(ui.composer_area(area), Rect::new(0, 18, 80, 4));
+    }
+
+    #[rstest]
+    #[case(false, 150, None)]
+    #[case(true, 119, None)]
+    #[case(true, 120, Some((66, 54)))]
+    fn test_pane_areas(
+        #[case] split: bool,
+        #[case] width: u16,
+        #[case] expected: Option<(u16, u16)>,
+    ) {
+        let area = Rect::new(0, 0, width, 10);
+        let (timeline, pane) = PaneLayout::new(split, 120).areas(area);
+        match expected {
+            Some((timeline_width, pane_width)) => {
+                assert_eq!(timeline.width, timeline_width);
+                let pane = pane.unwrap();
+                assert_eq!((pane.x, pane.width), (timeline_width, pane_width));
+            }
+            None => {
+                assert_eq!(timeline, area);
+                assert_eq!(pane, None);
+            }
+        }
+    }
+}
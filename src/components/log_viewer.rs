@@ -0,0 +1,152 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::Level;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    i18n,
+    log_buffer::{LogRecord, LOG_BUFFER},
+    mode::Mode,
+};
+
+/// Tails recent log records, so that relay issues can be debugged without the log file.
+pub struct LogViewer {
+    command_tx: Option<UnboundedSender<Action>>,
+    open: bool,
+    /// The least severe level to show
+    level: Level,
+    /// Lines scrolled up from the latest record
+    offset: usize,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            command_tx: None,
+            open: false,
+            level: Level::TRACE,
+            offset: 0,
+        }
+    }
+}
+
+impl LogViewer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows only more severe records each time, then all of them again.
+    fn cycle_level(&mut self) {
+        self.level = match self.level {
+            Level::TRACE => Level::DEBUG,
+            Level::DEBUG => Level::INFO,
+            Level::INFO => Level::WARN,
+            Level::WARN => Level::ERROR,
+            _ => Level::TRACE,
+        };
+        self.offset = 0;
+    }
+
+    fn scroll_up(&mut self) {
+        let len = LOG_BUFFER.records(self.level).len();
+        self.offset = (self.offset + 1).min(len.saturating_sub(1));
+    }
+
+    fn line(record: &LogRecord) -> Line<'static> {
+        let color = match record.level {
+            Level::ERROR => Color::LightRed,
+            Level::WARN => Color::LightYellow,
+            Level::INFO => Color::Reset,
+            _ => Color::Gray,
+        };
+        Line::styled(record.to_string(), Style::default().fg(color))
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 9 / 10;
+        let height = area.height * 4 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for LogViewer {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(),
+            KeyCode::Down | KeyCode::Char('j') => self.offset = self.offset.saturating_sub(1),
+            KeyCode::Char('l') => self.cycle_level(),
+            KeyCode::Char('c') => {
+                LOG_BUFFER.clear();
+                self.offset = 0;
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let (Action::ToggleLogs, false) = (action, self.open) {
+            self.open = true;
+            self.offset = 0;
+            return Ok(Some(Action::EnterMode(Mode::Popup)));
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr_args(
+                "Log ({level} and above): [l]evel [c]lear [esc] close",
+                &[("level", &self.level)],
+            ))
+            .padding(Padding::horizontal(1));
+
+        let records = LOG_BUFFER.records(self.level);
+        let height = block.inner(popup).height as usize;
+        let end = records.len().saturating_sub(self.offset);
+        let lines: Vec<Line> = records[end.saturating_sub(height)..end]
+            .iter()
+            .map(Self::line)
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+
+        Ok(())
+    }
+}
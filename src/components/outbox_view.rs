@@ -0,0 +1,244 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use nostr_sdk::prelude::*;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    i18n,
+    mode::Mode,
+    outbox::{Outbox, PublishedEvent, Receipt},
+};
+
+/// Lists events published in this session with the responses of each relay.
+#[derive(Default)]
+pub struct OutboxView {
+    command_tx: Option<UnboundedSender<Action>>,
+    outbox: Outbox,
+    open: bool,
+    list_state: ListState,
+}
+
+impl OutboxView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.outbox.is_empty() {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state.select(Some(
+            selected.clamp(0, self.outbox.len() as isize - 1) as usize
+        ));
+    }
+
+    fn retry_selected(&mut self) -> Result<()> {
+        let Some(published) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.outbox.events().get(i))
+        else {
+            return Ok(());
+        };
+
+        let relays = published.retry_targets();
+        let action = if relays.is_empty() {
+            Action::SystemWarning(i18n::tr("No relays to retry"))
+        } else {
+            Action::RetryPublish(published.event.clone(), relays, published.required_pow())
+        };
+        if let Some(tx) = &self.command_tx {
+            tx.send(action)?;
+        }
+
+        Ok(())
+    }
+
+    fn summary(published: &PublishedEvent) -> Line<'static> {
+        let content = published.event.content.lines().next().unwrap_or_default();
+        let mut spans = vec![
+            Span::styled(
+                format!("{}/{}", published.accepted(), published.receipts.len()),
+                Style::default().fg(Color::LightGreen),
+            ),
+            Span::raw(i18n::tr(" accepted")),
+        ];
+        if published.rejected() > 0 {
+            spans.push(Span::styled(
+                i18n::tr_args(", {count} rejected", &[("count", &published.rejected())]),
+                Style::default().fg(Color::LightRed),
+            ));
+        }
+        spans.push(Span::raw(format!(
+            " [{}] {content}",
+            published.event.kind.as_u64()
+        )));
+        Line::from(spans)
+    }
+
+    /// Reports how relays responded to a note once all of them have.
+    fn report(published: &PublishedEvent) -> Option<Action> {
+        if published.event.kind != Kind::TextNote || published.pending() > 0 {
+            return None;
+        }
+
+        let args: [(&str, &dyn std::fmt::Display); 3] = [
+            ("accepted", &published.accepted()),
+            ("total", &published.receipts.len()),
+            ("rejected", &published.rejected()),
+        ];
+        Some(if published.rejected() == 0 {
+            Action::SystemMessage(i18n::tr_args(
+                "[Sent] {accepted} of {total} relays accepted the note",
+                &args,
+            ))
+        } else {
+            Action::SystemWarning(i18n::tr_args(
+                "[Sent] {accepted} of {total} relays accepted the note, {rejected} rejected: press Shift-o for details",
+                &args,
+            ))
+        })
+    }
+
+    fn receipt_line(relay: &str, receipt: &Receipt) -> Line<'static> {
+        let (mark, color, detail) = match receipt {
+            Receipt::Pending => ("…", Color::Gray, i18n::tr("waiting")),
+            Receipt::Accepted => ("✓", Color::LightGreen, i18n::tr("accepted")),
+            Receipt::Rejected { message, .. } => ("✗", Color::LightRed, message.clone()),
+        };
+        Line::from(vec![
+            Span::styled(format!("  {mark} {relay}"), Style::default().fg(color)),
+            Span::raw(format!(" {detail}")),
+        ])
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 4 / 5;
+        let height = area.height * 3 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for OutboxView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Char('r') => self.retry_selected()?,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::EventPublished(event, relays) => {
+                let added = self.outbox.add(event, relays);
+                // Keep the selection on the same event
+                if let (true, Some(i)) = (added, self.list_state.selected()) {
+                    self.list_state.select(Some(i + 1));
+                }
+            }
+            Action::ReceiveReceipt(event_id, relay, status, message) => {
+                // Relays may respond to the same event more than once
+                let was_pending = self.outbox.get(&event_id).is_some_and(|published| {
+                    published.receipts.get(&relay) == Some(&Receipt::Pending)
+                });
+                self.outbox.record(event_id, relay, status, message);
+                if was_pending {
+                    return Ok(self.outbox.get(&event_id).and_then(Self::report));
+                }
+            }
+            Action::ViewOutbox if !self.open => {
+                self.open = true;
+                self.list_state.select(if self.outbox.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+                return Ok(Some(Action::EnterMode(Mode::Popup)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Outbox: [r]etry rejected relays [esc] close"))
+            .padding(Padding::horizontal(1));
+
+        if self.outbox.is_empty() {
+            f.render_widget(
+                Paragraph::new(i18n::tr("Nothing has been published yet")).block(block),
+                popup,
+            );
+            return Ok(());
+        }
+
+        let selected = self.list_state.selected();
+        let items: Vec<ListItem> = self
+            .outbox
+            .events()
+            .iter()
+            .enumerate()
+            .map(|(i, published)| {
+                let mut lines = vec![Self::summary(published)];
+                if Some(i) == selected {
+                    lines.extend(
+                        published
+                            .receipts
+                            .iter()
+                            .map(|(relay, receipt)| Self::receipt_line(relay, receipt)),
+                    );
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bold());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
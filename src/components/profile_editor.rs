@@ -0,0 +1,269 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use nostr_sdk::prelude::*;
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+use tui_textarea::TextArea;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode, nostr::ProfileField, text};
+
+const ABOUT_HEIGHT: u16 = 6;
+
+/// Edits my profile (kind 0), keeping the fields out of the form as they are.
+#[derive(Default)]
+pub struct ProfileEditor<'a> {
+    command_tx: Option<UnboundedSender<Action>>,
+    pubkey: Option<PublicKey>,
+    /// The latest metadata of mine and when it was created
+    profile: Option<(Timestamp, Metadata)>,
+    requested: bool,
+    inputs: Option<Vec<TextArea<'a>>>,
+    errors: Vec<Option<String>>,
+    focus: usize,
+}
+
+impl ProfileEditor<'_> {
+    pub fn new(pubkey: Option<PublicKey>) -> Self {
+        Self {
+            pubkey,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.inputs.is_some()
+    }
+
+    fn receive_metadata(&mut self, event: &Event) {
+        if event.kind != Kind::Metadata || Some(event.pubkey) != self.pubkey {
+            return;
+        }
+        if let Some((created_at, _)) = self.profile {
+            if created_at >= event.created_at {
+                return;
+            }
+        }
+        if let Ok(metadata) = Metadata::from_json(&event.content) {
+            self.profile = Some((event.created_at, metadata));
+        }
+    }
+
+    fn metadata(&self) -> Metadata {
+        self.profile
+            .as_ref()
+            .map(|(_, metadata)| metadata.clone())
+            .unwrap_or_default()
+    }
+
+    fn open(&mut self) -> Result<Option<Action>> {
+        let Some(pubkey) = self.pubkey else {
+            return Ok(Some(Action::SystemWarning(i18n::tr(
+                "Browse mode is read-only: set privatekey in the config to publish",
+            ))));
+        };
+
+        // Publishing before the current profile arrives would overwrite it with an empty one
+        if self.profile.is_none() && !self.requested {
+            self.requested = true;
+            if let Some(tx) = &self.command_tx {
+                tx.send(Action::FetchProfile(pubkey))?;
+            }
+            return Ok(Some(Action::SystemMessage(i18n::tr(
+                "Loading your profile: try again in a moment, or start a new one",
+            ))));
+        }
+
+        let metadata = self.metadata();
+        let inputs = ProfileField::ALL
+            .iter()
+            .map(|field| {
+                let value = field.get(&metadata);
+                let mut input = TextArea::new(value.lines().map(String::from).collect());
+                input.move_cursor(tui_textarea::CursorMove::Bottom);
+                input.move_cursor(tui_textarea::CursorMove::End);
+                input
+            })
+            .collect();
+        self.inputs = Some(inputs);
+        self.errors = vec![None; ProfileField::ALL.len()];
+        self.focus = 0;
+
+        Ok(Some(Action::EnterMode(Mode::Form)))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inputs = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn next(&mut self) {
+        self.focus = (self.focus + 1) % ProfileField::ALL.len();
+    }
+
+    fn previous(&mut self) {
+        self.focus = (self.focus + ProfileField::ALL.len() - 1) % ProfileField::ALL.len();
+    }
+
+    /// Publishes the profile if all fields are valid, or focuses the first invalid field.
+    fn save(&mut self) -> Result<()> {
+        let Some(ref inputs) = self.inputs else {
+            return Ok(());
+        };
+
+        let values: Vec<String> = inputs
+            .iter()
+            .map(|input| input.lines().join("\n"))
+            .collect();
+        self.errors = ProfileField::ALL
+            .iter()
+            .zip(&values)
+            .map(|(field, value)| field.validate(value).err())
+            .collect();
+        if let Some(i) = self.errors.iter().position(Option::is_some) {
+            self.focus = i;
+            return Ok(());
+        }
+
+        let current = self.metadata();
+        let mut metadata = current.clone();
+        for (field, value) in ProfileField::ALL.iter().zip(&values) {
+            field.set(&mut metadata, value);
+        }
+
+        self.close()?;
+        if let Some(tx) = &self.command_tx {
+            if metadata == current {
+                tx.send(Action::SystemMessage(i18n::tr("Nothing to update")))?;
+            } else {
+                tx.send(Action::SendMetadata(Box::new(metadata)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(80);
+        let height = area
+            .height
+            .min(3 * (ProfileField::ALL.len() as u16 - 1) + ABOUT_HEIGHT + 2);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for ProfileEditor<'_> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        // The cursor moves between lines in multi-line fields
+        let multiline = ProfileField::ALL[self.focus].is_multiline();
+        match key.code {
+            KeyCode::Esc => self.close()?,
+            KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => self.save()?,
+            KeyCode::Tab => self.next(),
+            KeyCode::BackTab => self.previous(),
+            KeyCode::Enter | KeyCode::Down if !multiline => self.next(),
+            KeyCode::Up if !multiline => self.previous(),
+            _ => {
+                if let Some(ref mut inputs) = self.inputs {
+                    inputs[self.focus].input(key);
+                }
+                self.errors[self.focus] = None;
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if let Some(ref mut inputs) = self.inputs {
+            let text = text::normalize_newlines(&text);
+            let text = if ProfileField::ALL[self.focus].is_multiline() {
+                text
+            } else {
+                text.replace('\n', " ")
+            };
+            inputs[self.focus].insert_str(text);
+            self.errors[self.focus] = None;
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ReceiveEvent(ref event) | Action::ReceiveFetchedEvent(ref event) => {
+                self.receive_metadata(event);
+            }
+            Action::EventPublished(ref event, _) => self.receive_metadata(event),
+            Action::EditProfile if !self.is_open() => return self.open(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some(ref mut inputs) = self.inputs else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+        let block = Block::default().borders(Borders::ALL).title(i18n::tr(
+            "Edit profile: [tab] next field [Ctrl-s] save [esc] cancel",
+        ));
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+
+        let constraints: Vec<Constraint> = ProfileField::ALL
+            .iter()
+            .map(|field| {
+                Constraint::Length(if field.is_multiline() {
+                    ABOUT_HEIGHT
+                } else {
+                    3
+                })
+            })
+            .collect();
+        let areas = Layout::vertical(constraints).split(inner);
+        for (i, (field, input)) in ProfileField::ALL.iter().zip(inputs.iter_mut()).enumerate() {
+            let (title, color) = match (&self.errors[i], i == self.focus) {
+                (Some(error), _) => (format!("{}: {error}", field.key()), Color::LightRed),
+                (None, true) => (field.key().to_owned(), Color::Yellow),
+                (None, false) => (field.key().to_owned(), Color::Gray),
+            };
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color))
+                    .title(title),
+            );
+            input.set_cursor_style(if i == self.focus {
+                Style::default().reversed()
+            } else {
+                Style::default()
+            });
+            f.render_widget(input.widget(), areas[i]);
+        }
+
+        Ok(())
+    }
+}
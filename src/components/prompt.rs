@@ -0,0 +1,129 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use tui_textarea::TextArea;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode, text};
+
+/// What the entered text is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromptKind {
+    ExportTimeline,
+    ExportThread,
+    JumpToDate,
+    AttachMedia,
+    Expiration,
+}
+
+/// Asks for a single line of text, e.g. a path.
+#[derive(Default)]
+pub struct Prompt<'a> {
+    command_tx: Option<UnboundedSender<Action>>,
+    kind: Option<PromptKind>,
+    title: String,
+    input: TextArea<'a>,
+}
+
+impl Prompt<'_> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.kind.is_some()
+    }
+
+    fn open(&mut self, kind: PromptKind, title: String, initial: String) {
+        self.kind = Some(kind);
+        self.title = title;
+        self.input = TextArea::new(vec![initial]);
+        self.input.move_cursor(tui_textarea::CursorMove::End);
+    }
+
+    fn close(&mut self, submitted: bool) -> Result<()> {
+        let kind = self.kind.take();
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+            if let (true, Some(kind)) = (submitted, kind) {
+                let text = self.input.lines().join("");
+                tx.send(Action::SubmitPrompt(kind, text))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width.min(80);
+        let height = area.height.min(3);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for Prompt<'_> {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Enter => self.close(true)?,
+            KeyCode::Esc => self.close(false)?,
+            _ => {
+                self.input.input(key);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if self.is_open() {
+            // Prompts take a single line
+            let text = text::normalize_newlines(&text).replace('\n', " ");
+            self.input.insert_str(text);
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenPrompt(kind, title, initial) if !self.is_open() => {
+                self.open(kind, title, initial);
+                Ok(Some(Action::EnterMode(Mode::Prompt)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.is_open() {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+        self.input
+            .set_block(Block::default().borders(Borders::ALL).title(i18n::tr_args(
+                "{title}: Press ESC to cancel",
+                &[("title", &self.title)],
+            )));
+        f.render_widget(self.input.widget(), popup);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    config::{Config, RelayConfig},
+    i18n,
+    mode::Mode,
+    nostr::nip11::{self, RelayInfo},
+};
+
+/// Lists the configured relays with their information documents (NIP-11).
+#[derive(Default)]
+pub struct RelayView {
+    command_tx: Option<UnboundedSender<Action>>,
+    relays: Vec<RelayConfig>,
+    infos: HashMap<String, RelayInfo>,
+    open: bool,
+    list_state: ListState,
+}
+
+impl RelayView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.relays.is_empty() {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state.select(Some(
+            selected.clamp(0, self.relays.len() as isize - 1) as usize
+        ));
+    }
+
+    fn relay_lines(relay: &RelayConfig, info: Option<&RelayInfo>) -> Vec<Line<'static>> {
+        let Some(info) = info else {
+            return vec![Line::from(vec![
+                Span::styled(format!("… {relay}"), Style::default().fg(Color::Gray)),
+                Span::raw(i18n::tr(" fetching the relay information")),
+            ])];
+        };
+        let info = match info {
+            Ok(info) => info,
+            Err(e) => {
+                return vec![Line::from(vec![
+                    Span::styled(format!("! {relay}"), Style::default().fg(Color::Yellow)),
+                    Span::raw(format!(" {e}")),
+                ])]
+            }
+        };
+
+        let missing = nip11::missing_nips(info, &nip11::REQUIRED_NIPS);
+        let restrictions = nip11::restrictions(info);
+        let (mark, color) = if missing.is_empty() && restrictions.is_empty() {
+            ("✓", Color::LightGreen)
+        } else {
+            ("!", Color::Yellow)
+        };
+        let mut lines = vec![Line::from(vec![
+            Span::styled(format!("{mark} {relay}"), Style::default().fg(color)),
+            Span::raw(format!(" {}", nip11::describe(info))),
+        ])];
+        if let Some(description) = info
+            .description
+            .as_deref()
+            .and_then(|description| description.lines().next())
+            .filter(|line| !line.trim().is_empty())
+        {
+            lines.push(Line::from(format!("  {}", description.trim())));
+        }
+        let limits = nip11::limits(info);
+        if !limits.is_empty() {
+            lines.push(Line::from(format!("  {}", limits.join(", "))));
+        }
+        if !restrictions.is_empty() {
+            lines.push(Line::styled(
+                format!("  {}", restrictions.join(", ")),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if !missing.is_empty() {
+            let nips: Vec<String> = missing.iter().map(|nip| nip.to_string()).collect();
+            lines.push(Line::styled(
+                i18n::tr_args(
+                    "  Doesn't support NIPs which nostui needs: {nips}",
+                    &[("nips", &nips.join(", "))],
+                ),
+                Style::default().fg(Color::LightRed),
+            ));
+        }
+        lines
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 4 / 5;
+        let height = area.height * 3 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for RelayView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.relays = config.relays;
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ReceiveRelayInfo(url, info) => {
+                self.infos.insert(url, info);
+            }
+            Action::ViewRelays if !self.open => {
+                self.open = true;
+                self.list_state.select(if self.relays.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+                return Ok(Some(Action::EnterMode(Mode::Popup)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Relays: [esc] close"))
+            .padding(Padding::horizontal(1));
+
+        let items: Vec<ListItem> = self
+            .relays
+            .iter()
+            .map(|relay| ListItem::new(Self::relay_lines(relay, self.infos.get(&relay.url))))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bold());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode};
+
+/// Shows a read-only report, e.g. the result of a check.
+#[derive(Default)]
+pub struct ReportPopup {
+    command_tx: Option<UnboundedSender<Action>>,
+    report: Option<(String, Vec<String>)>,
+    scroll: u16,
+}
+
+impl ReportPopup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.report.is_some()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.report = None;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn scroll_down(&mut self) {
+        let len = self.report.as_ref().map_or(0, |(_, lines)| lines.len());
+        self.scroll = (self.scroll + 1).min(len.saturating_sub(1) as u16);
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 4 / 5;
+        let height = area.height * 3 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for ReportPopup {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ShowReport(title, lines) => {
+                let was_open = self.is_open();
+                self.report = Some((title, lines));
+                self.scroll = 0;
+                if was_open {
+                    return Ok(None);
+                }
+
+                Ok(Some(Action::EnterMode(Mode::Popup)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let Some((ref title, ref lines)) = self.report else {
+            return Ok(());
+        };
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let text: Vec<Line> = lines.iter().map(|line| Line::from(line.as_str())).collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr_args(
+                "{title}: Press ESC to close",
+                &[("title", title)],
+            ))
+            .padding(Padding::horizontal(1));
+        f.render_widget(
+            Paragraph::new(text)
+                .block(block)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            popup,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Instant;
+
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    i18n,
+    stats::{format_bytes, NoteStoreStats, QueueDepth, RateCounter, TrafficStats},
+};
+
+const WIDTH: u16 = 60;
+
+/// Shows the performance of the app and the traffic of each relay, to find slow relays.
+#[derive(Default)]
+pub struct StatsOverlay {
+    open: bool,
+    app: RateCounter,
+    render: RateCounter,
+    traffic: TrafficStats,
+    depth: QueueDepth,
+    note_store: NoteStoreStats,
+}
+
+impl StatsOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let label = |text: String| Span::styled(text, Style::default().fg(Color::Gray));
+        let mut lines = vec![
+            Line::from(vec![
+                label(i18n::tr("Render ")),
+                Span::raw(format!(
+                    "{:.1} fps, {:.1} ticks/s",
+                    self.render.rate(),
+                    self.app.rate()
+                )),
+            ]),
+            Line::from(vec![
+                label(i18n::tr("Subscriptions ")),
+                Span::raw(self.traffic.subscriptions.to_string()),
+            ]),
+            Line::from(vec![
+                label(i18n::tr("Queue ")),
+                Span::raw(i18n::tr_args(
+                    "{actions} actions, {notifications} notifications",
+                    &[
+                        ("actions", &self.depth.actions),
+                        ("notifications", &self.depth.notifications),
+                    ],
+                )),
+            ]),
+            Line::from(vec![
+                label(i18n::tr("Note store ")),
+                Span::raw(i18n::tr_args(
+                    "{count} events, about {size}",
+                    &[
+                        ("count", &self.note_store.notes),
+                        ("size", &format_bytes(self.note_store.bytes)),
+                    ],
+                )),
+            ]),
+            Line::default(),
+            Line::styled(
+                format!("{:>9} {:>9}  {}", "events/s", "msgs/s", i18n::tr("Relay")),
+                Style::default().bold(),
+            ),
+        ];
+
+        for (relay, traffic) in self.traffic.busiest() {
+            // Relays which send nothing are likely to be slow or disconnected
+            let style = if traffic.messages == 0 {
+                Style::default().fg(Color::LightRed)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::styled(
+                format!(
+                    "{:>9.1} {:>9.1}  {relay}",
+                    self.traffic.per_sec(traffic.events),
+                    self.traffic.per_sec(traffic.messages),
+                ),
+                style,
+            ));
+        }
+        lines
+    }
+}
+
+impl Component for StatsOverlay {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Tick => self.app.tick(Instant::now()),
+            Action::Render => self.render.tick(Instant::now()),
+            Action::ToggleStats => {
+                self.open = !self.open;
+                if self.open {
+                    return Ok(Some(Action::MeasureNoteStore));
+                }
+            }
+            Action::ReceiveStats(traffic, depth) => {
+                self.traffic = traffic;
+                self.depth = depth;
+                if self.open {
+                    return Ok(Some(Action::MeasureNoteStore));
+                }
+            }
+            Action::NoteStoreMeasured(stats) => self.note_store = stats,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let lines = self.lines();
+        let width = area.width.min(WIDTH);
+        // Below the FPS counter in the first row
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(1));
+        let overlay = Rect::new(area.right() - width, area.y + 1, width, height);
+        f.render_widget(Clear, overlay);
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(i18n::tr("Stats: Press Shift-i to close")),
+            ),
+            overlay,
+        );
+
+        Ok(())
+    }
+}
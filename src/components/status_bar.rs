@@ -1,32 +1,61 @@
+use std::time::{Duration, Instant};
+
+use chrono::Local;
 use color_eyre::eyre::Result;
 use nostr_sdk::prelude::*;
 use ratatui::{prelude::*, widgets::*};
 
 use crate::action::Action;
-use crate::components::Component;
+use crate::components::{Component, Region};
+use crate::config::Config;
+use crate::i18n;
+use crate::message::{MessageLevel, MessageQueue, StatusMessage};
 use crate::nostr::Profile;
+use crate::status_format::{Field, StatusFormat};
 use crate::tui::Frame;
 use crate::widgets::PublicKey;
 
 pub struct StatusBar {
-    pubkey: nostr_sdk::PublicKey,
+    pubkey: Option<nostr_sdk::PublicKey>,
     profile: Option<Profile>,
-    message: Option<String>,
+    messages: MessageQueue,
+    show_history: bool,
     is_loading: bool,
+    do_not_disturb: bool,
+    /// Whether relays are connected through a proxy
+    proxied: bool,
+    format: StatusFormat,
+    connected_relays: usize,
+    total_relays: usize,
+    unread: usize,
 }
 
 impl StatusBar {
     pub fn new(
-        pubkey: nostr_sdk::PublicKey,
+        pubkey: Option<nostr_sdk::PublicKey>,
         profile: Option<Profile>,
         message: Option<String>,
         is_loading: bool,
     ) -> Self {
+        let mut messages = MessageQueue::new(Duration::from_secs(
+            Config::default().status_bar.message_timeout,
+        ));
+        if let Some(message) = message {
+            messages.push(MessageLevel::Info, message, Instant::now());
+        }
+
         Self {
             pubkey,
             profile,
-            message,
+            messages,
+            show_history: false,
             is_loading,
+            do_not_disturb: false,
+            proxied: false,
+            format: StatusFormat::default(),
+            connected_relays: 0,
+            total_relays: 0,
+            unread: 0,
         }
     }
 
@@ -35,21 +64,111 @@ impl StatusBar {
     }
 
     pub fn name(&self) -> String {
-        self.profile
-            .clone()
-            .map(|profile| profile.name())
-            .unwrap_or(PublicKey::new(self.pubkey).shortened())
+        match (&self.profile, self.pubkey) {
+            (Some(profile), _) => profile.name(),
+            (_, Some(pubkey)) => PublicKey::new(pubkey).shortened(),
+            (_, None) => i18n::tr("Browse mode (read-only)"),
+        }
+    }
+
+    fn field(&self, field: Field) -> String {
+        match field {
+            Field::Name => self.name(),
+            Field::RelaysConnected => self.connected_relays.to_string(),
+            Field::RelaysTotal => self.total_relays.to_string(),
+            Field::Unread if self.unread > 0 => {
+                i18n::tr_args("{count} new", &[("count", &self.unread)])
+            }
+            Field::Time => Local::now().format("%H:%M").to_string(),
+            Field::Proxy if self.proxied => String::from("[Proxy]"),
+            Field::Dnd if self.do_not_disturb => String::from("[DND]"),
+            Field::Unread | Field::Proxy | Field::Dnd => String::new(),
+        }
+    }
+
+    fn field_style(field: Option<Field>) -> Style {
+        match field {
+            Some(Field::Name) | None => Style::default().fg(Color::Gray).italic(),
+            Some(Field::Unread) => Style::default().fg(Color::Cyan),
+            Some(Field::Proxy) => Style::default().fg(Color::Magenta),
+            Some(Field::Dnd) => Style::default().fg(Color::Yellow),
+            Some(Field::RelaysConnected | Field::RelaysTotal | Field::Time) => {
+                Style::default().fg(Color::Gray)
+            }
+        }
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        let spans: Vec<Span> = self
+            .format
+            .render(|field| self.field(field))
+            .into_iter()
+            .map(|(field, text)| Span::styled(text, Self::field_style(field)))
+            .collect();
+        Line::from(spans)
+    }
+
+    fn message_style(level: MessageLevel) -> Style {
+        match level {
+            MessageLevel::Info => Style::default(),
+            MessageLevel::Warn => Style::default().fg(Color::Yellow),
+            MessageLevel::Error => Style::default().fg(Color::Red),
+        }
+    }
+
+    fn message_line(message: &StatusMessage) -> Line<'static> {
+        Line::styled(message.text.clone(), Self::message_style(message.level))
+    }
+
+    fn draw_history(&self, f: &mut Frame<'_>, area: Rect) {
+        let mut popup = area.inner(&Margin::new(area.width / 8, area.height / 6));
+        popup.height = popup.height.saturating_sub(2); // Keep the status bar visible
+        f.render_widget(Clear, popup);
+
+        let lines: Vec<Line> = self
+            .messages
+            .history()
+            .map(|message| {
+                let time = Span::styled(
+                    message.received_at.format("%T ").to_string(),
+                    Style::default().fg(Color::Gray),
+                );
+                let text = Span::styled(message.text.clone(), Self::message_style(message.level));
+                Line::from(vec![time, text])
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Messages: Press Shift-m to close"));
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            popup,
+        );
     }
 }
 
 impl Component for StatusBar {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.messages
+            .set_timeout(Duration::from_secs(config.status_bar.message_timeout));
+        self.proxied = config.proxy.is_some();
+        self.format = config.status_bar.format;
+        Ok(())
+    }
+
+    fn region(&self) -> Region {
+        Region::StatusBar
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::ReceiveEvent(ev) => {
                 self.is_loading = false;
 
                 match ev.kind {
-                    Kind::Metadata if ev.pubkey == self.pubkey => {
+                    Kind::Metadata if Some(ev.pubkey) == self.pubkey => {
                         if let Ok(metadata) = Metadata::from_json(ev.content.clone()) {
                             let profile = Profile::new(ev.pubkey, ev.created_at, metadata);
                             if let Some(existing_profile) = &self.profile {
@@ -64,7 +183,28 @@ impl Component for StatusBar {
                     _ => {}
                 };
             }
-            Action::SystemMessage(message) => self.message = Some(message),
+            Action::SystemMessage(message) => {
+                self.messages
+                    .push(MessageLevel::Info, message, Instant::now())
+            }
+            Action::SystemWarning(message) => {
+                self.messages
+                    .push(MessageLevel::Warn, message, Instant::now())
+            }
+            Action::Error(message) => {
+                self.messages
+                    .push(MessageLevel::Error, message, Instant::now())
+            }
+            Action::DismissMessage => self.messages.dismiss(Instant::now()),
+            Action::ReceiveStats(stats, _) => {
+                self.connected_relays = stats.connected_relays;
+                self.total_relays = stats.total_relays;
+            }
+            Action::UnreadChanged(unread) => self.unread = unread,
+            Action::ToggleMessageHistory => self.show_history = !self.show_history,
+            Action::ToggleDoNotDisturb if self.pubkey.is_some() => {
+                self.do_not_disturb = !self.do_not_disturb
+            }
             _ => {}
         };
 
@@ -84,17 +224,51 @@ impl Component for StatusBar {
         f.render_widget(Clear, layout[1]);
         f.render_widget(Clear, layout[2]);
 
-        let name = Span::styled(self.name(), Style::default().fg(Color::Gray).italic());
-        let status_line = Paragraph::new(name).style(Style::default().bg(Color::Black));
+        let status_line =
+            Paragraph::new(self.status_line()).style(Style::default().bg(Color::Black));
         f.render_widget(status_line, layout[1]);
 
         let message_line = if self.is_loading {
-            Paragraph::new("Loading...")
+            Paragraph::new(i18n::tr("Loading..."))
+        } else if let Some(message) = self.messages.current(Instant::now()) {
+            Paragraph::new(Self::message_line(message))
         } else {
-            Paragraph::new(self.message.clone().unwrap_or_default())
+            Paragraph::new("")
         };
         f.render_widget(message_line, layout[2]);
 
+        if self.show_history {
+            self.draw_history(f, area);
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_name() {
+        let keys = Keys::generate();
+        let status_bar = StatusBar::new(Some(keys.public_key()), None, None, true);
+        assert_eq!(
+            status_bar.name(),
+            PublicKey::new(keys.public_key()).shortened()
+        );
+
+        let mut status_bar = StatusBar::new(None, None, None, true);
+        assert_eq!(status_bar.name(), "Browse mode (read-only)");
+        let profile = Profile::new(
+            keys.public_key(),
+            Timestamp::from(0),
+            Metadata::new().name("alice"),
+        );
+        status_bar.set_profile(Some(profile));
+        assert_eq!(status_bar.name(), "@alice");
+    }
+}
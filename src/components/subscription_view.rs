@@ -0,0 +1,193 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, i18n, mode::Mode, nostr::subscriptions::SubscriptionInfo};
+
+/// Interval to refresh the counts of events while the inspector is open.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lists the subscriptions open on the relays to find duplicated or stale ones, which can be
+/// closed one by one.
+pub struct SubscriptionView {
+    command_tx: Option<UnboundedSender<Action>>,
+    subscriptions: Vec<SubscriptionInfo>,
+    open: bool,
+    refreshed_at: Instant,
+    list_state: ListState,
+}
+
+impl Default for SubscriptionView {
+    fn default() -> Self {
+        Self {
+            command_tx: None,
+            subscriptions: vec![],
+            open: false,
+            refreshed_at: Instant::now(),
+            list_state: ListState::default(),
+        }
+    }
+}
+
+impl SubscriptionView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        let selected = self.list_state.selected().unwrap_or(0) as isize + delta;
+        self.list_state.select(Some(
+            selected.clamp(0, self.subscriptions.len() as isize - 1) as usize,
+        ));
+    }
+
+    fn close_selected(&mut self) -> Result<()> {
+        let Some(subscription) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.subscriptions.get(i))
+        else {
+            return Ok(());
+        };
+
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::CloseSubscription(subscription.id.clone()))?;
+        }
+
+        Ok(())
+    }
+
+    fn subscription_lines(subscription: &SubscriptionInfo) -> Vec<Line<'static>> {
+        let owner = match &subscription.tab {
+            Some(tab) => Span::styled(tab.title(None), Style::default().bold()),
+            None => Span::styled(i18n::tr("No tab"), Style::default().fg(Color::Yellow)),
+        };
+        vec![
+            Line::from(vec![
+                owner,
+                Span::styled(
+                    format!(" {}", subscription.id),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]),
+            Line::from(format!("  {}", subscription.filters)),
+            Line::styled(
+                i18n::tr_args(
+                    "  {events} events, open on {relays} relays",
+                    &[
+                        ("events", &subscription.events),
+                        ("relays", &subscription.relays),
+                    ],
+                ),
+                Style::default().fg(Color::Gray),
+            ),
+        ]
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 4 / 5;
+        let height = area.height * 3 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for SubscriptionView {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll(1),
+            KeyCode::Char('d') | KeyCode::Delete => self.close_selected()?,
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ReceiveSubscriptions(subscriptions) => {
+                self.subscriptions = subscriptions;
+                self.refreshed_at = Instant::now();
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(if self.subscriptions.is_empty() {
+                    None
+                } else {
+                    Some(selected.min(self.subscriptions.len() - 1))
+                });
+            }
+            Action::ViewSubscriptions if !self.open => {
+                self.open = true;
+                self.list_state.select(None);
+                return Ok(Some(Action::EnterMode(Mode::Popup)));
+            }
+            Action::Tick if self.open && self.refreshed_at.elapsed() >= REFRESH_INTERVAL => {
+                self.refreshed_at = Instant::now();
+                return Ok(Some(Action::ViewSubscriptions));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr_args(
+                "Subscriptions ({count}): [d] close the subscription [esc] close",
+                &[("count", &self.subscriptions.len())],
+            ))
+            .padding(Padding::horizontal(1));
+
+        let items: Vec<ListItem> = self
+            .subscriptions
+            .iter()
+            .map(|subscription| ListItem::new(Self::subscription_lines(subscription)))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+        f.render_stateful_widget(list, popup, &mut self.list_state);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{action::Action, completion::fuzzy_score, i18n, mode::Mode};
+
+/// Lists the open tabs to switch to one by typing a part of its title, so that tabs which
+/// don't fit in the tab bar are found without cycling through them.
+#[derive(Default)]
+pub struct TabList {
+    command_tx: Option<UnboundedSender<Action>>,
+    titles: Vec<String>,
+    query: String,
+    /// Indices of the tabs matching the query, the best match first
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl TabList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_open(&self) -> bool {
+        !self.titles.is_empty()
+    }
+
+    fn search(&mut self) {
+        let mut matches: Vec<(usize, usize)> = self
+            .titles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, title)| fuzzy_score(&self.query, title).map(|score| (score, i)))
+            .collect();
+        matches.sort();
+        self.matches = matches.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn close(&mut self, chosen: bool) -> Result<()> {
+        let tab = self.matches.get(self.selected).copied().filter(|_| chosen);
+        self.titles.clear();
+        self.matches.clear();
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+            if let Some(i) = tab {
+                tx.send(Action::SelectTab(i + 1))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn popup_area(&self, area: Rect) -> Rect {
+        let width = area.width.min(60);
+        let height = area.height.min(self.titles.len() as u16 + 3);
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for TabList {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.is_open() {
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(false)?,
+            KeyCode::Enter => self.close(true)?,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.query.push(c);
+                self.search();
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::OpenTabList(titles, active) if !self.is_open() && !titles.is_empty() => {
+                self.titles = titles;
+                self.query.clear();
+                self.search();
+                self.selected = active.min(self.titles.len() - 1);
+                Ok(Some(Action::EnterMode(Mode::Popup)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.is_open() {
+            return Ok(());
+        }
+
+        let popup = self.popup_area(area);
+        f.render_widget(Clear, popup);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("Tabs: [enter] switch [esc] close"));
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+
+        let [input, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        f.render_widget(Paragraph::new(format!("> {}", self.query)), input);
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|i| ListItem::new(format!("{}:{}", i + 1, self.titles[*i])))
+            .collect();
+        let list_widget = List::new(items).highlight_style(Style::default().reversed());
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(list_widget, list, &mut state);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::components::testing::render;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    fn open(titles: &[&str], active: usize) -> (TabList, mpsc::UnboundedReceiver<Action>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut list = TabList::new();
+        list.register_action_handler(tx).unwrap();
+        let titles = titles.iter().map(|title| title.to_string()).collect();
+        assert_eq!(
+            list.update(Action::OpenTabList(titles, active)).unwrap(),
+            Some(Action::EnterMode(Mode::Popup))
+        );
+        (list, rx)
+    }
+
+    fn type_keys(list: &mut TabList, text: &str) {
+        for c in text.chars() {
+            list.handle_key_events(key(KeyCode::Char(c))).unwrap();
+        }
+    }
+
+    #[rstest]
+    fn test_search() {
+        let (mut list, mut rx) = open(&["⌂ Home", "#nostr", "@ alice", "#nostui"], 0);
+        type_keys(&mut list, "nsu");
+        let lines = render(&mut list, 60, 10);
+        assert!(lines.iter().any(|line| line.contains("> nsu")));
+        assert!(lines.iter().any(|line| line.contains("4:#nostui")));
+        assert!(!lines.iter().any(|line| line.contains("2:#nostr")));
+
+        list.handle_key_events(key(KeyCode::Enter)).unwrap();
+        assert_eq!(rx.try_recv().ok(), Some(Action::LeaveMode));
+        assert_eq!(rx.try_recv().ok(), Some(Action::SelectTab(4)));
+        assert!(!list.is_open());
+    }
+
+    #[rstest]
+    fn test_best_match_first() {
+        let (mut list, mut rx) = open(&["#nostr", "#nostui", "nostr"], 0);
+        type_keys(&mut list, "nostr");
+        list.handle_key_events(key(KeyCode::Enter)).unwrap();
+        assert_eq!(rx.try_recv().ok(), Some(Action::LeaveMode));
+        assert_eq!(rx.try_recv().ok(), Some(Action::SelectTab(3)));
+    }
+
+    #[rstest]
+    fn test_close() {
+        let (mut list, mut rx) = open(&["⌂ Home", "#nostr"], 1);
+        assert_eq!(list.selected, 1);
+        type_keys(&mut list, "x");
+        list.handle_key_events(key(KeyCode::Backspace)).unwrap();
+        assert_eq!(list.matches, [0, 1]);
+
+        list.handle_key_events(key(KeyCode::Esc)).unwrap();
+        assert_eq!(rx.try_recv().ok(), Some(Action::LeaveMode));
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Helpers to render components into a text grid, e.g. for snapshot tests of their layout.
+//!
+//! ```ignore
+//! let mut home = testing::timeline(&[testing::note("gm", 1_700_000_000)]);
+//! let lines = testing::render(&mut home, 80, 24);
+//! assert!(lines.iter().any(|line| line.contains("gm")));
+//! ```
+//!
+//! Strategies of [`proptest`] generate actions, e.g. to check [`Home::check_invariants`] after
+//! any sequence of them.
+//!
+//! Other crates use the module with the `testing` feature.
+
+use nostr_sdk::prelude::*;
+use proptest::{prelude::*, sample::select};
+use ratatui::{backend::TestBackend, Terminal};
+
+use super::{Component, Home, SubscriptionView};
+use crate::{
+    action::Action, config::Config, headless::lines, nostr::subscriptions::SubscriptionInfo,
+    timeline::TimelineTabType,
+};
+
+/// Draws the component over the whole screen of the size and returns its rows, trimmed at the
+/// end.
+pub fn render(component: &mut dyn Component, width: u16, height: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|f| component.draw(f, f.size()).unwrap())
+        .unwrap();
+    lines(terminal.backend().buffer())
+}
+
+/// A config which can post, with a fresh key and no saved session.
+pub fn config() -> Config {
+    browse_config().with_identity(Keys::generate())
+}
+
+/// A config without a key, i.e. browse mode, and no saved session.
+pub fn browse_config() -> Config {
+    let mut config = Config::default();
+    config.config._data_dir = std::env::temp_dir().join("nostui-testing");
+    config.startup_tabs = vec![TimelineTabType::Home];
+    config
+}
+
+/// A text note by a fresh key.
+pub fn note(content: &str, created_at: u64) -> Event {
+    EventBuilder::text_note(content, [])
+        .custom_created_at(Timestamp::from(created_at))
+        .to_event(&Keys::generate())
+        .unwrap()
+}
+
+/// The home timeline showing the notes.
+pub fn timeline(notes: &[Event]) -> Home<'static> {
+    let mut home = Home::new();
+    home.register_config_handler(config()).unwrap();
+    for note in notes {
+        home.update(Action::ReceiveTabEvent(TimelineTabType::Home, note.clone()))
+            .unwrap();
+    }
+    home
+}
+
+/// The home timeline with the composer open, holding the draft.
+pub fn composing(notes: &[Event], draft: &str) -> Home<'static> {
+    let mut home = timeline(notes);
+    home.update(Action::NewTextNote).unwrap();
+    home.update(Action::Paste(draft.to_string())).unwrap();
+    home
+}
+
+/// Notes of a timeline and deletions of some of them, which arbitrary actions deliver.
+#[derive(Debug, Clone)]
+pub struct Notes {
+    pub author: PublicKey,
+    pub notes: Vec<Event>,
+    pub deletions: Vec<Event>,
+}
+
+impl Notes {
+    /// Notes by a single author, a minute apart, with `#nostr` in every other one. Every third
+    /// note can be deleted.
+    pub fn new(len: usize) -> Self {
+        let keys = Keys::generate();
+        let notes: Vec<Event> = (0..len)
+            .map(|i| {
+                let tags = if i % 2 == 0 {
+                    vec![Tag::Hashtag(String::from("nostr"))]
+                } else {
+                    vec![]
+                };
+                EventBuilder::text_note(format!("note {i}"), tags)
+                    .custom_created_at(Timestamp::from(1_700_000_000 + 60 * i as u64))
+                    .to_event(&keys)
+                    .unwrap()
+            })
+            .collect();
+        let deletions = notes
+            .iter()
+            .step_by(3)
+            .map(|note| EventBuilder::delete([note.id]).to_event(&keys).unwrap())
+            .collect();
+        Self {
+            author: keys.public_key(),
+            notes,
+            deletions,
+        }
+    }
+}
+
+/// Tabs which arbitrary actions open and deliver notes to.
+pub fn tab_type(notes: &Notes) -> impl Strategy<Value = TimelineTabType> {
+    let thread =
+        notes
+            .notes
+            .first()
+            .map_or(TimelineTabType::Global, |note| TimelineTabType::Thread {
+                id: note.id,
+            });
+    select(vec![
+        TimelineTabType::Home,
+        TimelineTabType::Global,
+        TimelineTabType::Hashtag {
+            tag: String::from("nostr"),
+        },
+        TimelineTabType::UserTimeline {
+            pubkey: notes.author,
+        },
+        thread,
+    ])
+}
+
+/// Actions which deliver or delete the notes, scroll, or open, close, select and move tabs.
+pub fn action(notes: &Notes) -> impl Strategy<Value = Action> {
+    prop_oneof![
+        4 => (tab_type(notes), select(notes.notes.clone()))
+            .prop_map(|(tab_type, note)| Action::ReceiveTabEvent(tab_type, note)),
+        1 => select(notes.deletions.clone()).prop_map(Action::ReceiveEvent),
+        1 => tab_type(notes).prop_map(Action::OpenTab),
+        1 => (0usize..7).prop_map(Action::SelectTab),
+        4 => select(vec![
+            Action::ScrollUp,
+            Action::ScrollDown,
+            Action::ScrollToTop,
+            Action::ScrollToBottom,
+            Action::ScrollHalfPageUp,
+            Action::ScrollHalfPageDown,
+            Action::ScrollViewUp,
+            Action::ScrollViewDown,
+            Action::ToggleFollowMode,
+        ]),
+        2 => select(vec![
+            Action::CloseTab,
+            Action::NextTab,
+            Action::PreviousTab,
+            Action::MoveTabLeft,
+            Action::MoveTabRight,
+        ]),
+    ]
+}
+
+/// The subscription inspector open over the subscriptions.
+pub fn subscription_popup(subscriptions: Vec<SubscriptionInfo>) -> SubscriptionView {
+    let mut view = SubscriptionView::new();
+    view.update(Action::ViewSubscriptions).unwrap();
+    view.update(Action::ReceiveSubscriptions(subscriptions))
+        .unwrap();
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use pretty_assertions::assert_eq;
+    use ratatui::{buffer::Buffer, style::Style};
+    use rstest::*;
+
+    use super::*;
+
+    /// Notes shared by the cases, as signing them takes a while
+    static NOTES: LazyLock<Notes> = LazyLock::new(|| Notes::new(12));
+
+    fn contains(lines: &[String], text: &str) -> bool {
+        lines.iter().any(|line| line.contains(text))
+    }
+
+    #[rstest]
+    fn test_lines() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "gm", Style::default());
+        buffer.set_string(0, 1, "おはよ", Style::default());
+        assert_eq!(lines(&buffer), ["gm", "おはよ"]);
+    }
+
+    #[rstest]
+    fn test_render_timeline() {
+        let mut home = timeline(&[note("gm nostr", 1_700_000_000), note("gn", 1_700_000_060)]);
+        let lines = render(&mut home, 80, 24);
+        assert_eq!(lines.len(), 24);
+        assert!(contains(&lines, "gm nostr"));
+        assert!(contains(&lines, "gn"));
+    }
+
+    #[rstest]
+    fn test_render_composer() {
+        let mut home = composing(&[], "hello\nnostr");
+        let lines = render(&mut home, 80, 24);
+        assert!(contains(&lines, "New note: Press ESC to close"));
+        assert!(contains(&lines, "hello"));
+        assert!(contains(&lines, "nostr"));
+    }
+
+    #[rstest]
+    fn test_render_popup() {
+        let mut view = subscription_popup(vec![SubscriptionInfo {
+            id: SubscriptionId::new("home"),
+            tab: Some(TimelineTabType::Home),
+            filters: String::from("kinds:1 authors:2"),
+            events: 3,
+            relays: 2,
+        }]);
+        let lines = render(&mut view, 80, 24);
+        assert!(contains(&lines, "Subscriptions (1)"));
+        assert!(contains(&lines, "kinds:1 authors:2"));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_invariants(actions in proptest::collection::vec(action(&NOTES), 1..50)) {
+            let mut home = timeline(&[]);
+            for action in actions {
+                home.update(action).unwrap();
+                // Layout moves the selection with the viewport
+                render(&mut home, 60, 20);
+                prop_assert_eq!(home.check_invariants(), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{Component, Frame};
+use crate::{
+    action::Action,
+    i18n,
+    mode::Mode,
+    nostr::trace::{Direction, TraceEntry},
+};
+
+const MAX_ENTRIES: usize = 1000;
+
+/// Shows relay messages recorded in trace mode as they arrive.
+#[derive(Default)]
+pub struct TraceOverlay {
+    command_tx: Option<UnboundedSender<Action>>,
+    enabled: bool,
+    open: bool,
+    entries: VecDeque<TraceEntry>,
+    filter: String,
+    editing_filter: bool,
+    /// Lines scrolled up from the latest entry
+    offset: usize,
+}
+
+impl TraceOverlay {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        self.editing_filter = false;
+        if let Some(tx) = &self.command_tx {
+            tx.send(Action::LeaveMode)?;
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, entry: TraceEntry) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        // Keep the scrolled position while new entries arrive
+        if self.offset > 0 && entry.matches(&self.filter) {
+            self.offset += 1;
+        }
+        self.entries.push_back(entry);
+    }
+
+    fn filtered(&self) -> Vec<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.matches(&self.filter))
+            .collect()
+    }
+
+    fn scroll_up(&mut self) {
+        let len = self.filtered().len();
+        self.offset = (self.offset + 1).min(len.saturating_sub(1));
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => {}
+        }
+        self.offset = 0;
+    }
+
+    fn popup_area(area: Rect) -> Rect {
+        let width = area.width * 9 / 10;
+        let height = area.height * 4 / 5;
+        Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl Component for TraceOverlay {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.command_tx = Some(tx);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.open {
+            return Ok(None);
+        }
+
+        if self.editing_filter {
+            self.handle_filter_key(key);
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.close()?,
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(),
+            KeyCode::Down | KeyCode::Char('j') => self.offset = self.offset.saturating_sub(1),
+            KeyCode::Char('c') => {
+                self.entries.clear();
+                self.offset = 0;
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ReceiveTrace(entry) => self.push(entry),
+            Action::ToggleTrace if !self.enabled => {
+                return Ok(Some(Action::SystemWarning(i18n::tr(
+                    "Tracing is off: start nostui with --trace",
+                ))));
+            }
+            Action::ToggleTrace if !self.open => {
+                self.open = true;
+                self.offset = 0;
+                return Ok(Some(Action::EnterMode(Mode::Trace)));
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+
+        let popup = Self::popup_area(area);
+        f.render_widget(Clear, popup);
+
+        let filter = if self.editing_filter {
+            format!(" /{}_", self.filter)
+        } else if !self.filter.is_empty() {
+            format!(" /{}", self.filter)
+        } else {
+            String::new()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr_args(
+                "Trace{filter}: [/] filter [c]lear [esc] close",
+                &[("filter", &filter)],
+            ))
+            .padding(Padding::horizontal(1));
+
+        let entries = self.filtered();
+        let height = block.inner(popup).height as usize;
+        let end = entries.len().saturating_sub(self.offset);
+        let lines: Vec<Line> = entries[end.saturating_sub(height)..end]
+            .iter()
+            .map(|entry| {
+                let color = match entry.direction {
+                    Direction::Sent => Color::LightCyan,
+                    Direction::Received => Color::LightGreen,
+                };
+                Line::from(Span::styled(entry.to_string(), Style::default().fg(color)))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+
+        Ok(())
+    }
+}
@@ -1,15 +1,36 @@
-mod keybindings;
+pub mod contrast;
+pub mod keybindings;
+pub mod migration;
+pub mod palette;
 mod styles;
 
+use std::fs;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use config::ConfigError;
-use serde::Deserialize;
+use nostr_sdk::{Keys, Url};
+use serde::{Deserialize, Serialize};
 
-use crate::utils;
+use crate::{
+    clock_skew::FuturePolicy,
+    i18n::{self, Locale},
+    keys::{self, KeySource, PassphrasePrompt},
+    link_preview::PreviewResolver,
+    media::MediaProtocol,
+    nostr::proxy,
+    notify::{AlertKind, NotificationMethod},
+    pagination::DEFAULT_TIMELINE_LIMIT,
+    spam_filter::FilterAction,
+    status_format::StatusFormat,
+    timeline::TimelineTabType,
+    timestamp::TimestampMode,
+    translate::TranslationBackend,
+    utils,
+};
 
-const CONFIG: &str = include_str!("../.config/config.json5");
+pub(crate) const CONFIG: &str = include_str!("../.config/config.json5");
 
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
@@ -19,18 +40,743 @@ pub struct AppConfig {
     pub _config_dir: PathBuf,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct StatusBarConfig {
+    /// Seconds until info and warning messages disappear
+    #[serde(default = "StatusBarConfig::default_message_timeout")]
+    pub message_timeout: u64,
+    /// Contents of the status line, e.g. `{name} {relays_connected}/{relays_total} {unread} {time}`
+    #[serde(default)]
+    pub format: StatusFormat,
+}
+
+impl StatusBarConfig {
+    fn default_message_timeout() -> u64 {
+        5
+    }
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            message_timeout: Self::default_message_timeout(),
+            format: StatusFormat::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InteractionLimitConfig {
+    /// Replies to me within `window` which collapse an author I don't follow (0 disables)
+    #[serde(default = "InteractionLimitConfig::default_reply_threshold")]
+    pub reply_threshold: usize,
+    /// Seconds to count replies in
+    #[serde(default = "InteractionLimitConfig::default_window")]
+    pub window: u64,
+}
+
+impl InteractionLimitConfig {
+    fn default_reply_threshold() -> usize {
+        5
+    }
+
+    fn default_window() -> u64 {
+        60 * 60
+    }
+}
+
+impl Default for InteractionLimitConfig {
+    fn default() -> Self {
+        Self {
+            reply_threshold: Self::default_reply_threshold(),
+            window: Self::default_window(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpamFilterConfig {
+    /// Score below which notes from accounts I don't follow are filtered (0 disables)
+    #[serde(default = "SpamFilterConfig::default_threshold")]
+    pub threshold: u64,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+impl SpamFilterConfig {
+    fn default_threshold() -> u64 {
+        10
+    }
+}
+
+impl Default for SpamFilterConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Self::default_threshold(),
+            action: FilterAction::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThemeConfig {
+    /// WCAG contrast ratio required between foreground and background colors drawn together
+    #[serde(default = "ThemeConfig::default_min_contrast_ratio")]
+    pub min_contrast_ratio: f64,
+    #[serde(default)]
+    pub contrast_policy: contrast::ContrastPolicy,
+    /// Colors of the UI, which `NO_COLOR` turns off
+    #[serde(default)]
+    pub color_mode: palette::ColorMode,
+}
+
+impl ThemeConfig {
+    fn default_min_contrast_ratio() -> f64 {
+        4.5
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            min_contrast_ratio: Self::default_min_contrast_ratio(),
+            contrast_policy: contrast::ContrastPolicy::default(),
+            color_mode: palette::ColorMode::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PluginConfig {
+    /// Whether to load Lua plugins from the `plugins` directory of the config directory
+    #[serde(default = "PluginConfig::default_enabled")]
+    pub enabled: bool,
+}
+
+impl PluginConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ContentWarningConfig {
+    /// Show the content of notes with a content warning without revealing them
+    #[serde(default)]
+    pub always_show: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReactionConfig {
+    /// Content of reactions, e.g. `+` (like) or an emoji
+    #[serde(default = "ReactionConfig::default_content")]
+    pub content: String,
+}
+
+impl ReactionConfig {
+    fn default_content() -> String {
+        String::from("+")
+    }
+}
+
+impl Default for ReactionConfig {
+    fn default() -> Self {
+        Self {
+            content: Self::default_content(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PreviewConfig {
+    /// Lines of the content shown until a note is expanded, or 0 to show all of them
+    #[serde(default = "PreviewConfig::default_max_lines")]
+    pub max_lines: usize,
+}
+
+impl PreviewConfig {
+    fn default_max_lines() -> usize {
+        10
+    }
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: Self::default_max_lines(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuthorConfig {
+    /// Whether names and avatars are colored by author
+    #[serde(default = "AuthorConfig::default_enabled")]
+    pub colors: bool,
+    /// Whether the initial of the author is shown before their name
+    #[serde(default = "AuthorConfig::default_enabled")]
+    pub avatars: bool,
+}
+
+impl AuthorConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl Default for AuthorConfig {
+    fn default() -> Self {
+        Self {
+            colors: Self::default_enabled(),
+            avatars: Self::default_enabled(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LayoutConfig {
+    /// Whether the thread of the selected note is shown beside the timeline at startup
+    #[serde(default)]
+    pub split_pane: bool,
+    /// Columns below which the side pane is hidden
+    #[serde(default = "LayoutConfig::default_split_min_width")]
+    pub split_min_width: u16,
+    #[serde(default = "LayoutConfig::default_shown")]
+    pub status_bar: bool,
+    /// Whether tabs are listed at the top once another tab is opened
+    #[serde(default = "LayoutConfig::default_shown")]
+    pub tab_bar: bool,
+    /// Whether tabs are listed down the left side instead of at the top
+    #[serde(default)]
+    pub vertical_tabs: bool,
+    /// Whether to start with all of the above hidden
+    #[serde(default)]
+    pub zen: bool,
+    /// Height of the composer in percent of the screen, from 20 to 90
+    #[serde(default = "LayoutConfig::default_composer_height")]
+    pub composer_height: u16,
+}
+
+impl LayoutConfig {
+    fn default_split_min_width() -> u16 {
+        120
+    }
+
+    fn default_shown() -> bool {
+        true
+    }
+
+    fn default_composer_height() -> u16 {
+        50
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            split_pane: false,
+            split_min_width: Self::default_split_min_width(),
+            status_bar: Self::default_shown(),
+            tab_bar: Self::default_shown(),
+            vertical_tabs: false,
+            zen: false,
+            composer_height: Self::default_composer_height(),
+        }
+    }
+}
+
+/// Limits of the global tab, which shows notes of everyone on the relays.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GlobalConfig {
+    /// Notes let into the tab per second, beyond which notes are dropped
+    #[serde(default = "GlobalConfig::default_max_rate")]
+    pub max_rate: u32,
+    /// Notes kept in the tab, dropping the oldest ones
+    #[serde(default = "GlobalConfig::default_max_notes")]
+    pub max_notes: usize,
+}
+
+impl GlobalConfig {
+    fn default_max_rate() -> u32 {
+        5
+    }
+
+    fn default_max_notes() -> usize {
+        500
+    }
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            max_rate: Self::default_max_rate(),
+            max_notes: Self::default_max_notes(),
+        }
+    }
+}
+
+/// Handling of notes dated in the future, which would stay at the top of timelines.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FutureNotesConfig {
+    #[serde(default)]
+    pub policy: FuturePolicy,
+    /// Seconds a note may be dated ahead of the local clock before it is handled
+    #[serde(default = "FutureNotesConfig::default_tolerance")]
+    pub tolerance: u64,
+}
+
+impl FutureNotesConfig {
+    fn default_tolerance() -> u64 {
+        300
+    }
+}
+
+impl Default for FutureNotesConfig {
+    fn default() -> Self {
+        Self {
+            policy: FuturePolicy::default(),
+            tolerance: Self::default_tolerance(),
+        }
+    }
+}
+
+/// The marker between the notes received since the app quit last time and the older ones.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LastVisitConfig {
+    /// Whether the Home tab shows the marker
+    #[serde(default = "LastVisitConfig::default_marker")]
+    pub marker: bool,
+}
+
+impl LastVisitConfig {
+    fn default_marker() -> bool {
+        true
+    }
+}
+
+impl Default for LastVisitConfig {
+    fn default() -> Self {
+        Self {
+            marker: Self::default_marker(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LinkPreviewConfig {
+    /// Sources of the metadata of pages, tried in order
+    #[serde(default = "LinkPreviewConfig::default_resolvers")]
+    pub resolvers: Vec<PreviewResolver>,
+    /// Seconds to wait for each page
+    #[serde(default = "LinkPreviewConfig::default_timeout")]
+    pub timeout: u64,
+}
+
+impl LinkPreviewConfig {
+    fn default_resolvers() -> Vec<PreviewResolver> {
+        vec![PreviewResolver::OEmbed, PreviewResolver::OpenGraph]
+    }
+
+    fn default_timeout() -> u64 {
+        10
+    }
+}
+
+impl Default for LinkPreviewConfig {
+    fn default() -> Self {
+        Self {
+            resolvers: Self::default_resolvers(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PowerConfig {
+    /// Whether timers and renders stop while the terminal is unfocused, if the terminal reports
+    /// focus changes
+    #[serde(default)]
+    pub pause_when_unfocused: bool,
+    /// Seconds the terminal is unfocused before pausing
+    #[serde(default = "PowerConfig::default_unfocused_delay")]
+    pub unfocused_delay: u64,
+    /// Whether subscriptions of tabs are closed while paused or suspended, and opened again on
+    /// resume
+    #[serde(default)]
+    pub pause_subscriptions: bool,
+}
+
+impl PowerConfig {
+    fn default_unfocused_delay() -> u64 {
+        30
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            pause_when_unfocused: false,
+            unfocused_delay: Self::default_unfocused_delay(),
+            pause_subscriptions: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MediaConfig {
+    /// Server which attached files are uploaded to, e.g. `https://blossom.example.com`
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub protocol: MediaProtocol,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TranslationConfig {
+    /// Endpoint of the translation API, e.g. `https://libretranslate.com/translate`
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub backend: TranslationBackend,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Language which notes are translated into, e.g. `de`, defaulting to the one of the UI
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// A relay to connect to, given as a URL or as `{ "url": "wss://...", "write": false }`
+/// to only read from it (or `"read": false` to only send events to it).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(from = "RelayEntry")]
+pub struct RelayConfig {
+    pub url: String,
+    /// Whether timelines and other events are requested from the relay
+    pub read: bool,
+    /// Whether my events are sent to the relay
+    pub write: bool,
+}
+
+impl RelayConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            read: true,
+            write: true,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        Url::parse(&self.url).map_err(|e| eyre!("Invalid relay {}: {e}", self.url))?;
+        if !self.read && !self.write {
+            return Err(eyre!("The relay {} is neither read nor written", self.url));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for RelayConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.read, self.write) {
+            (true, false) => write!(f, "{} (read)", self.url),
+            (false, true) => write!(f, "{} (write)", self.url),
+            _ => write!(f, "{}", self.url),
+        }
+    }
+}
+
+impl AsRef<str> for RelayConfig {
+    fn as_ref(&self) -> &str {
+        &self.url
+    }
+}
+
+impl From<String> for RelayConfig {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+/// How relays are written in the config file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum RelayEntry {
+    Url(String),
+    Policy {
+        url: String,
+        #[serde(default = "RelayEntry::default_enabled")]
+        read: bool,
+        #[serde(default = "RelayEntry::default_enabled")]
+        write: bool,
+    },
+}
+
+impl RelayEntry {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl From<RelayEntry> for RelayConfig {
+    fn from(entry: RelayEntry) -> Self {
+        match entry {
+            RelayEntry::Url(url) => Self::new(url),
+            RelayEntry::Policy { url, read, write } => Self { url, read, write },
+        }
+    }
+}
+
+impl From<&RelayConfig> for RelayEntry {
+    fn from(relay: &RelayConfig) -> Self {
+        if relay.read && relay.write {
+            Self::Url(relay.url.clone())
+        } else {
+            Self::Policy {
+                url: relay.url.clone(),
+                read: relay.read,
+                write: relay.write,
+            }
+        }
+    }
+}
+
+/// Checks the relays in the config, which have to be valid URLs listed once and read or written.
+pub fn validate_relays(relays: &[RelayConfig]) -> Result<()> {
+    for (i, relay) in relays.iter().enumerate() {
+        relay.validate()?;
+        if relays[..i].iter().any(|other| other.url == relay.url) {
+            return Err(eyre!("The relay {} is listed twice", relay.url));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ComposerConfig {
+    /// Bytes over which sending a note asks for confirmation, as some relays reject large events
+    /// (0 disables)
+    #[serde(default = "ComposerConfig::default_soft_limit")]
+    pub soft_limit: usize,
+    /// Whether to edit notes with vim-like normal, insert and visual modes
+    #[serde(default)]
+    pub vim: bool,
+}
+
+impl ComposerConfig {
+    fn default_soft_limit() -> usize {
+        8000
+    }
+}
+
+impl Default for ComposerConfig {
+    fn default() -> Self {
+        Self {
+            soft_limit: Self::default_soft_limit(),
+            vim: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PaginationConfig {
+    /// Notes requested at first and by each page of older notes
+    #[serde(default = "PaginationConfig::default_limit")]
+    pub limit: usize,
+    /// Largest page, which pages grow to while scrolling to the bottom repeatedly
+    #[serde(default = "PaginationConfig::default_max_limit")]
+    pub max_limit: usize,
+    /// Number of notes below the selection at which older notes are loaded
+    #[serde(default = "PaginationConfig::default_prefetch")]
+    pub prefetch: usize,
+}
+
+impl PaginationConfig {
+    fn default_limit() -> usize {
+        DEFAULT_TIMELINE_LIMIT
+    }
+
+    fn default_max_limit() -> usize {
+        500
+    }
+
+    fn default_prefetch() -> usize {
+        5
+    }
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            limit: Self::default_limit(),
+            max_limit: Self::default_max_limit(),
+            prefetch: Self::default_prefetch(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SoundConfig {
+    /// Kinds of events which play a sound
+    #[serde(default)]
+    pub events: Vec<AlertKind>,
+    /// Command to play a sound with instead of the terminal bell, e.g. `["paplay", "bell.oga"]`
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(default = "NotificationConfig::default_enabled")]
+    pub mention: bool,
+    /// Replies to my notes
+    #[serde(default = "NotificationConfig::default_enabled")]
+    pub reply: bool,
+    #[serde(default = "NotificationConfig::default_enabled")]
+    pub direct_message: bool,
+    #[serde(default)]
+    pub zap: bool,
+    #[serde(default)]
+    pub method: NotificationMethod,
+    /// Notify only while the terminal is not focused, if the terminal reports focus changes
+    #[serde(default = "NotificationConfig::default_enabled")]
+    pub only_when_unfocused: bool,
+    /// Show the number of notes which arrived while the terminal is unfocused in its title
+    #[serde(default = "NotificationConfig::default_enabled")]
+    pub unread_in_title: bool,
+    #[serde(default)]
+    pub sound: SoundConfig,
+}
+
+impl NotificationConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    pub fn is_enabled(&self, kind: AlertKind) -> bool {
+        match kind {
+            AlertKind::Mention => self.mention,
+            AlertKind::Reply => self.reply,
+            AlertKind::DirectMessage => self.direct_message,
+            AlertKind::Zap => self.zap,
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            mention: Self::default_enabled(),
+            reply: Self::default_enabled(),
+            direct_message: Self::default_enabled(),
+            zap: false,
+            method: NotificationMethod::default(),
+            only_when_unfocused: Self::default_enabled(),
+            unread_in_title: Self::default_enabled(),
+            sound: SoundConfig::default(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct Config {
     #[serde(default, flatten)]
     pub config: AppConfig,
     #[serde(default)]
     pub keybindings: keybindings::KeyBindings,
+    /// Set of default keybindings which the ones in `keybindings` override
+    #[serde(default)]
+    pub keybinding_profile: keybindings::KeybindingProfile,
     #[serde(default)]
     pub styles: styles::Styles,
+    /// nsec, hex or ncryptsec (NIP-49)
     #[serde(default)]
     pub privatekey: String,
+    /// File which contains the private key instead of `privatekey`, relative to the config directory
+    #[serde(default)]
+    pub privatekey_file: Option<PathBuf>,
+    /// Whether to read the private key from the OS keyring instead of `privatekey`
+    #[serde(default)]
+    pub keyring: bool,
+    /// Whether to read the private key from stdin, given by `--key-stdin`
+    #[serde(skip)]
+    pub key_stdin: bool,
+    #[serde(default)]
+    pub relays: Vec<RelayConfig>,
+    /// SOCKS5 proxy which relays are connected through, e.g. `socks5://127.0.0.1:9050` for Tor
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub status_bar: StatusBarConfig,
+    #[serde(default)]
+    pub interaction_limit: InteractionLimitConfig,
+    #[serde(default)]
+    pub spam_filter: SpamFilterConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub plugins: PluginConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub content_warning: ContentWarningConfig,
+    #[serde(default)]
+    pub preview: PreviewConfig,
     #[serde(default)]
-    pub relays: Vec<String>,
+    pub reaction: ReactionConfig,
+    #[serde(default)]
+    pub authors: AuthorConfig,
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    #[serde(default)]
+    pub composer: ComposerConfig,
+    #[serde(default)]
+    pub pagination: PaginationConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub global: GlobalConfig,
+    #[serde(default)]
+    pub future_notes: FutureNotesConfig,
+    #[serde(default)]
+    pub last_visit: LastVisitConfig,
+    #[serde(default)]
+    pub translation: TranslationConfig,
+    #[serde(default)]
+    pub link_preview: LinkPreviewConfig,
+    #[serde(default)]
+    pub power: PowerConfig,
+    /// How timestamps of notes are shown at startup
+    #[serde(default)]
+    pub timestamps: TimestampMode,
+    /// Whether tabs follow new notes at startup
+    #[serde(default)]
+    pub follow_new_notes: bool,
+    /// Language of the UI, which defaults to the one of `LANG`
+    #[serde(default)]
+    pub locale: Option<Locale>,
+    /// Config files from older versions, which were upgraded on load but not saved yet
+    #[serde(skip)]
+    pub migrations: Vec<migration::Migration>,
+    /// Tabs given on the command line, which replace the saved session
+    #[serde(skip)]
+    pub startup_tabs: Vec<TimelineTabType>,
+    /// Tab of a `nostr:` URI given on the command line, which is opened and focused
+    #[serde(skip)]
+    pub startup_uri: Option<TimelineTabType>,
+    /// Workspace given on the command line, which has its own session
+    #[serde(skip)]
+    pub workspace: Option<String>,
+    /// Keys loaded by [`Config::unlock`]
+    #[serde(skip)]
+    identity: Option<Keys>,
 }
 
 impl Config {
@@ -50,26 +796,51 @@ impl Config {
             ("config.ini", config::FileFormat::Ini),
         ];
         let mut found_config = false;
+        let mut migrations = vec![];
         for (file, format) in &config_files {
-            builder = builder.add_source(
-                config::File::from(config_dir.join(file))
-                    .format(*format)
-                    .required(false),
-            );
-            if config_dir.join(file).exists() {
+            let path = config_dir.join(file);
+            let migration = match format {
+                config::FileFormat::Json5 | config::FileFormat::Json if path.exists() => {
+                    migration::Migration::load(&path)
+                        .map_err(|e| ConfigError::Message(format!("{}: {e}", path.display())))?
+                }
+                _ => None,
+            };
+            builder = match migration {
+                // The upgraded file is loaded until it is saved
+                Some(ref migration) => builder.add_source(config::File::from_str(
+                    &migration.content,
+                    config::FileFormat::Json,
+                )),
+                None => builder.add_source(
+                    config::File::from(path.clone())
+                        .format(*format)
+                        .required(false),
+                ),
+            };
+            migrations.extend(migration);
+            if path.exists() {
                 found_config = true
             }
         }
         if !found_config {
-            log::error!("No configuration file found");
+            tracing::error!("No configuration file found");
             return Err(ConfigError::Message(String::from(
                 "No configuration file found",
             )));
         }
 
         let mut cfg: Self = builder.build()?.try_deserialize()?;
+        for migration in &migrations {
+            tracing::warn!(path = %migration.path.display(), from = migration.from, "Upgraded an old config file");
+        }
+        cfg.migrations = migrations;
 
-        for (mode, default_bindings) in default_config.keybindings.iter() {
+        let mut default_keybindings = default_config.keybindings;
+        cfg.keybinding_profile
+            .apply(&mut default_keybindings)
+            .map_err(ConfigError::Message)?;
+        for (mode, default_bindings) in default_keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
             for (key, cmd) in default_bindings.iter() {
                 user_bindings
@@ -86,23 +857,193 @@ impl Config {
             }
         }
 
-        if cfg.privatekey.is_empty() {
-            return Err(ConfigError::NotFound(String::from("privatekey")));
-        }
-
         if cfg.relays.is_empty() {
             cfg.relays.clone_from(&default_config.relays);
         }
+        validate_relays(&cfg.relays).map_err(|e| ConfigError::Message(e.to_string()))?;
+
+        cfg.theme.color_mode = cfg
+            .theme
+            .color_mode
+            .resolve(std::env::var("NO_COLOR").ok().as_deref());
 
         Ok(cfg)
     }
+
+    /// Describes the result of the contrast check of the colors drawn so far.
+    pub fn theme_report(&self, issues: &[contrast::ContrastIssue]) -> Vec<String> {
+        let min_ratio = self.theme.min_contrast_ratio;
+        if self.theme.contrast_policy == contrast::ContrastPolicy::Off {
+            return vec![i18n::tr("The contrast check is off")];
+        }
+
+        if issues.is_empty() {
+            return vec![i18n::tr_args(
+                "All colors drawn so far meet the minimum contrast ratio of {ratio}:1",
+                &[("ratio", &min_ratio)],
+            )];
+        }
+
+        let mut lines = vec![
+            i18n::tr_args(
+                "Colors drawn below the minimum contrast ratio of {ratio}:1",
+                &[("ratio", &min_ratio)],
+            ),
+            String::new(),
+        ];
+        lines.extend(issues.iter().map(|issue| issue.describe()));
+        lines
+    }
+
+    /// Address of the proxy, if configured.
+    pub fn proxy_addr(&self) -> Result<Option<SocketAddr>> {
+        self.proxy.as_deref().map(proxy::parse).transpose()
+    }
+
+    /// Uses the relays given by `--relay` instead of the configured ones, reading and writing
+    /// all of them. Nothing changes without any.
+    pub fn override_relays(&mut self, urls: &[String]) {
+        if !urls.is_empty() {
+            self.relays = urls.iter().cloned().map(RelayConfig::from).collect();
+        }
+    }
+
+    /// URLs of the relays my events are sent to, normalized in the same way as the relay URLs of
+    /// receipts. Read-only relays and onion relays skipped without a proxy are left out.
+    pub fn write_relays(&self) -> Vec<String> {
+        let (relays, _) = proxy::reachable_relays(&self.relays, self.proxy.is_some());
+        relays
+            .iter()
+            .filter(|relay| relay.write)
+            .filter_map(|relay| Url::parse(&relay.url).ok())
+            .map(|url| url.to_string())
+            .collect()
+    }
+
+    /// Returns `false` in browse mode, i.e. when no private key is configured.
+    pub fn has_identity(&self) -> bool {
+        self.identity.is_some()
+    }
+
+    /// Where the private key is stored, in order of precedence: stdin, `NOSTUI_NSEC`,
+    /// the keyring, the key file and `privatekey`.
+    pub fn key_source(&self) -> KeySource {
+        if self.key_stdin {
+            KeySource::Stdin
+        } else if keys::has_env_key() {
+            KeySource::Env
+        } else if self.keyring {
+            KeySource::Keyring
+        } else if let Some(ref path) = self.privatekey_file {
+            KeySource::File(self.config._config_dir.join(path))
+        } else if !self.privatekey.is_empty() {
+            KeySource::Config(self.privatekey.clone())
+        } else {
+            KeySource::None
+        }
+    }
+
+    /// Loads the private key, asking the passphrase if it is encrypted.
+    pub fn unlock(&mut self, prompt: &mut dyn PassphrasePrompt) -> Result<()> {
+        let source = self.key_source();
+        if matches!(source, KeySource::Config(ref key) if !key.starts_with("ncryptsec1")) {
+            tracing::warn!("The private key is stored as plaintext in the config");
+        }
+        self.identity = keys::load(&source, prompt)?;
+        Ok(())
+    }
+
+    pub fn plugins_dir(&self) -> PathBuf {
+        self.config._config_dir.join("plugins")
+    }
+
+    /// Returns `None` in browse mode or before [`Config::unlock`].
+    pub fn keys(&self) -> Option<Keys> {
+        self.identity.clone()
+    }
+
+    /// Uses the keys without reading them from a key source.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_identity(mut self, keys: Keys) -> Self {
+        self.identity = Some(keys);
+        self
+    }
+}
+
+/// Relays of the JSON or JSON5 config file, edited by `nostui relays` apart from the other config
+/// files and `--relay`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayFile {
+    pub path: PathBuf,
+    content: String,
+    /// Relays of the file, or the default ones if it has none
+    pub relays: Vec<RelayConfig>,
+}
+
+impl RelayFile {
+    pub fn load() -> Result<Self> {
+        let config_dir = utils::get_config_dir();
+        // config.json takes precedence over config.json5
+        let path = ["config.json", "config.json5"]
+            .iter()
+            .map(|file| config_dir.join(file))
+            .find(|path| path.exists())
+            .ok_or_else(|| eyre!("Relays can only be saved to config.json5 or config.json"))?;
+        let content = fs::read_to_string(&path)?;
+        Self::parse(path, content)
+    }
+
+    fn parse(path: PathBuf, content: String) -> Result<Self> {
+        let mut value: serde_json::Value = json5::from_str(&content)?;
+        // Relays of older versions are written differently
+        migration::upgrade(&mut value)?;
+        let relays = match value.get("relays") {
+            Some(relays) => serde_json::from_value(relays.clone())?,
+            None => json5::from_str::<Config>(CONFIG)?.relays,
+        };
+        Ok(Self {
+            path,
+            content,
+            relays,
+        })
+    }
+
+    /// Path the original file is kept at when the relays are saved.
+    pub fn backup_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".bak");
+        PathBuf::from(path)
+    }
+
+    /// Replaces `relays` in the file, keeping the original at [`Self::backup_path`].
+    ///
+    /// The file is rewritten as plain JSON, so comments are not kept.
+    pub fn save(&self) -> Result<PathBuf> {
+        let content = set_relays(&self.content, &self.relays)?;
+        let backup = self.backup_path();
+        fs::copy(&self.path, &backup)?;
+        fs::write(&self.path, content)?;
+        Ok(backup)
+    }
+}
+
+fn set_relays(content: &str, relays: &[RelayConfig]) -> Result<String> {
+    let mut value: serde_json::Value = json5::from_str(content)?;
+    let config = value
+        .as_object_mut()
+        .ok_or_else(|| eyre!("The config file is not an object"))?;
+    let relays: Vec<RelayEntry> = relays.iter().map(RelayEntry::from).collect();
+    config.insert(String::from("relays"), serde_json::to_value(relays)?);
+    Ok(serde_json::to_string_pretty(&value)?)
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use rstest::*;
 
     use super::*;
+    use crate::{action::Action, mode::Mode};
 
     #[test]
     fn test_config() {
@@ -119,4 +1060,180 @@ mod tests {
         // );
         // Ok(())
     }
+
+    #[rstest]
+    fn test_default_config_version() {
+        let mut config: serde_json::Value = json5::from_str(CONFIG).unwrap();
+        assert_eq!(
+            config["version"],
+            serde_json::json!(migration::CURRENT_VERSION)
+        );
+        assert_eq!(migration::upgrade(&mut config).unwrap(), None);
+    }
+
+    #[rstest]
+    #[case(keybindings::KeybindingProfile::Default)]
+    #[case(keybindings::KeybindingProfile::Vim)]
+    #[case(keybindings::KeybindingProfile::Emacs)]
+    fn test_keybinding_profiles(#[case] profile: keybindings::KeybindingProfile) {
+        let mut keybindings = json5::from_str::<Config>(CONFIG).unwrap().keybindings;
+        assert_eq!(profile.apply(&mut keybindings), Ok(()));
+        assert_eq!(
+            keybindings
+                .key_for(Mode::Compose, &Action::Unselect)
+                .is_some(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_set_relays() {
+        let content = r#"{
+            // my key
+            "privatekey": "nsec1...",
+            "relays": ["wss://nos.lol"],
+        }"#;
+        let relays = vec![
+            RelayConfig::new("wss://nos.lol"),
+            RelayConfig::new("wss://yabu.me"),
+            RelayConfig {
+                write: false,
+                ..RelayConfig::new("wss://relay.nostr.band")
+            },
+        ];
+        let value: serde_json::Value =
+            serde_json::from_str(&set_relays(content, &relays).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "privatekey": "nsec1...",
+                "relays": [
+                    "wss://nos.lol",
+                    "wss://yabu.me",
+                    { "url": "wss://relay.nostr.band", "read": true, "write": false },
+                ],
+            })
+        );
+        assert_eq!(set_relays("[]", &relays).is_err(), true);
+    }
+
+    #[rstest]
+    #[case(r#"{"relays": ["wss://nos.lol"]}"#, vec![RelayConfig::new("wss://nos.lol")])]
+    #[case(
+        r#"{"version": 2, "relays": {"wss://nos.lol": {"write": false}}}"#,
+        vec![RelayConfig { write: false, ..RelayConfig::new("wss://nos.lol") }]
+    )]
+    fn test_relay_file(#[case] content: &str, #[case] expected: Vec<RelayConfig>) {
+        let file = RelayFile::parse(PathBuf::from("config.json5"), content.to_string()).unwrap();
+        assert_eq!(file.relays, expected);
+        assert_eq!(file.backup_path(), PathBuf::from("config.json5.bak"));
+    }
+
+    #[rstest]
+    fn test_relay_file_defaults() {
+        let file = RelayFile::parse(PathBuf::from("config.json"), String::from("{}")).unwrap();
+        let default_config: Config = json5::from_str(CONFIG).unwrap();
+        assert_eq!(file.relays, default_config.relays);
+    }
+
+    #[test]
+    fn test_relay_config() {
+        let relays: Vec<RelayConfig> = json5::from_str(
+            r#"[
+                "wss://nos.lol",
+                { url: "wss://relay.nostr.band", write: false },
+                { url: "wss://sendit.nosflare.com", read: false },
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            relays,
+            vec![
+                RelayConfig::new("wss://nos.lol"),
+                RelayConfig {
+                    url: String::from("wss://relay.nostr.band"),
+                    read: true,
+                    write: false,
+                },
+                RelayConfig {
+                    url: String::from("wss://sendit.nosflare.com"),
+                    read: false,
+                    write: true,
+                },
+            ]
+        );
+        assert_eq!(validate_relays(&relays).is_ok(), true);
+    }
+
+    #[test]
+    fn test_validate_relays() {
+        let relay = RelayConfig::new("wss://nos.lol");
+        let muted = RelayConfig {
+            read: false,
+            write: false,
+            ..relay.clone()
+        };
+        assert_eq!(
+            validate_relays(&[relay.clone(), relay.clone()]).is_err(),
+            true
+        );
+        assert_eq!(validate_relays(&[muted]).is_err(), true);
+        assert_eq!(
+            validate_relays(&[RelayConfig::new("nos.lol")]).is_err(),
+            true
+        );
+    }
+
+    #[rstest]
+    #[case(None, vec!["wss://nos.lol/", "wss://sendit.nosflare.com/"])]
+    #[case(
+        Some("socks5://127.0.0.1:9050"),
+        vec!["wss://nos.lol/", "wss://sendit.nosflare.com/", "ws://abc.onion/"]
+    )]
+    fn test_write_relays(#[case] proxy: Option<&str>, #[case] expected: Vec<&str>) {
+        let config = Config {
+            relays: vec![
+                RelayConfig::new("wss://nos.lol"),
+                RelayConfig {
+                    write: false,
+                    ..RelayConfig::new("wss://relay.damus.io")
+                },
+                RelayConfig {
+                    read: false,
+                    ..RelayConfig::new("wss://sendit.nosflare.com")
+                },
+                RelayConfig::new("ws://abc.onion"),
+            ],
+            proxy: proxy.map(String::from),
+            ..Config::default()
+        };
+        assert_eq!(config.write_relays(), expected);
+    }
+
+    #[rstest]
+    #[case(&[], vec!["wss://nos.lol/"])]
+    #[case(&["ws://localhost:7777"], vec!["ws://localhost:7777/"])]
+    fn test_override_relays(#[case] urls: &[&str], #[case] expected: Vec<&str>) {
+        let mut config = Config {
+            relays: vec![RelayConfig {
+                read: false,
+                ..RelayConfig::new("wss://nos.lol")
+            }],
+            ..Config::default()
+        };
+        let urls: Vec<String> = urls.iter().map(|url| url.to_string()).collect();
+        config.override_relays(&urls);
+        assert_eq!(config.write_relays(), expected);
+        assert_eq!(config.relays.len(), 1);
+        assert_eq!(config.relays[0].read, !urls.is_empty());
+    }
+
+    #[rstest]
+    fn test_browse_mode() {
+        // Without a private key, the app browses without an identity
+        let config = Config::default();
+        assert!(config.privatekey.is_empty());
+        assert!(!config.has_identity());
+        assert!(config.keys().is_none());
+    }
 }
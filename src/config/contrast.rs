@@ -0,0 +1,279 @@
+use ratatui::{buffer::Buffer, style::Color};
+use serde::Deserialize;
+
+const BLACK: (u8, u8, u8) = (0, 0, 0);
+const WHITE: (u8, u8, u8) = (255, 255, 255);
+
+/// What to do with drawn color pairs below the minimum contrast ratio.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContrastPolicy {
+    /// Replace the foreground with black or white, whichever is more readable
+    #[default]
+    Adjust,
+    /// Keep the colors and only report them
+    Warn,
+    Off,
+}
+
+/// A foreground and background color drawn together, which are hard to tell apart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContrastIssue {
+    pub fg: Color,
+    pub bg: Color,
+    pub ratio: f64,
+    pub adjusted: Option<(Color, f64)>,
+}
+
+impl ContrastIssue {
+    pub fn describe(&self) -> String {
+        let mut line = format!("{} on {}: {:.2}:1", self.fg, self.bg, self.ratio);
+        if let Some((color, ratio)) = self.adjusted {
+            line.push_str(&format!(" (foreground adjusted to {color}, {ratio:.2}:1)"));
+        }
+        line
+    }
+}
+
+/// Converts a terminal color to sRGB, assuming the xterm palette for named and indexed colors.
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let rgb = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => ansi_rgb(0),
+        Color::Red => ansi_rgb(1),
+        Color::Green => ansi_rgb(2),
+        Color::Yellow => ansi_rgb(3),
+        Color::Blue => ansi_rgb(4),
+        Color::Magenta => ansi_rgb(5),
+        Color::Cyan => ansi_rgb(6),
+        Color::Gray => ansi_rgb(7),
+        Color::DarkGray => ansi_rgb(8),
+        Color::LightRed => ansi_rgb(9),
+        Color::LightGreen => ansi_rgb(10),
+        Color::LightYellow => ansi_rgb(11),
+        Color::LightBlue => ansi_rgb(12),
+        Color::LightMagenta => ansi_rgb(13),
+        Color::LightCyan => ansi_rgb(14),
+        Color::White => ansi_rgb(15),
+        Color::Indexed(i) => ansi_rgb(i),
+    };
+    Some(rgb)
+}
+
+fn ansi_rgb(index: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    match index {
+        0..=15 => BASE[index as usize],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+fn rgb_contrast_ratio(fg: (u8, u8, u8), bg: (u8, u8, u8)) -> f64 {
+    let (l1, l2) = (relative_luminance(fg), relative_luminance(bg));
+    (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+}
+
+/// Returns the WCAG contrast ratio, from 1 to 21.
+pub fn contrast_ratio(fg: Color, bg: Color) -> Option<f64> {
+    Some(rgb_contrast_ratio(to_rgb(fg)?, to_rgb(bg)?))
+}
+
+pub(super) fn readable_foreground(bg: Color) -> Option<(Color, f64)> {
+    let bg = to_rgb(bg)?;
+    [BLACK, WHITE]
+        .into_iter()
+        .map(|(r, g, b)| (Color::Rgb(r, g, b), rgb_contrast_ratio((r, g, b), bg)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+fn check_pair(
+    fg: Color,
+    bg: Color,
+    min_ratio: f64,
+    policy: ContrastPolicy,
+) -> Option<ContrastIssue> {
+    let ratio = contrast_ratio(fg, bg)?;
+    if ratio >= min_ratio {
+        return None;
+    }
+
+    let adjusted = match policy {
+        ContrastPolicy::Adjust => readable_foreground(bg),
+        _ => None,
+    };
+    Some(ContrastIssue {
+        fg,
+        bg,
+        ratio,
+        adjusted,
+    })
+}
+
+/// Checks every drawn cell with both colors set, adjusting failing ones depending on `policy`.
+///
+/// Returns each failing pair of colors once, as the colors are drawn in many cells.
+pub fn enforce(buffer: &mut Buffer, min_ratio: f64, policy: ContrastPolicy) -> Vec<ContrastIssue> {
+    if policy == ContrastPolicy::Off {
+        return vec![];
+    }
+
+    let mut issues: Vec<ContrastIssue> = vec![];
+    for cell in buffer.content.iter_mut() {
+        let known = issues
+            .iter()
+            .find(|issue| (issue.fg, issue.bg) == (cell.fg, cell.bg));
+        let adjusted = match known {
+            Some(issue) => issue.adjusted,
+            None => match check_pair(cell.fg, cell.bg, min_ratio, policy) {
+                Some(issue) => {
+                    let adjusted = issue.adjusted;
+                    issues.push(issue);
+                    adjusted
+                }
+                None => continue,
+            },
+        };
+        if let Some((color, _)) = adjusted {
+            cell.fg = color;
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::{layout::Rect, style::Style};
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Color::Black, Color::White, Some(21.0))]
+    #[case(Color::White, Color::White, Some(1.0))]
+    #[case(Color::Rgb(118, 118, 118), Color::Rgb(255, 255, 255), Some(4.54))]
+    #[case(Color::Reset, Color::White, None)]
+    fn test_contrast_ratio(#[case] fg: Color, #[case] bg: Color, #[case] expected: Option<f64>) {
+        let ratio = contrast_ratio(fg, bg).map(|ratio| (ratio * 100.0).round() / 100.0);
+        assert_eq!(ratio, expected);
+    }
+
+    #[rstest]
+    #[case(16, (0, 0, 0))]
+    #[case(21, (0, 0, 255))]
+    #[case(196, (255, 0, 0))]
+    #[case(232, (8, 8, 8))]
+    #[case(255, (238, 238, 238))]
+    fn test_to_rgb_indexed(#[case] index: u8, #[case] expected: (u8, u8, u8)) {
+        assert_eq!(to_rgb(Color::Indexed(index)), Some(expected));
+    }
+
+    /// Draws "x" in the colors, returning the issues and the foreground of the drawn cell.
+    fn draw(fg: Color, bg: Color, policy: ContrastPolicy) -> (Vec<ContrastIssue>, Color) {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "xxx", Style::default().fg(fg).bg(bg));
+        let issues = enforce(&mut buffer, 4.5, policy);
+        (issues, buffer.get(0, 0).fg)
+    }
+
+    /// Expected adjustment of each case is `None` if the colors pass the check.
+    #[rstest]
+    #[case(Color::White, Color::Black, ContrastPolicy::Adjust, None, Color::White)]
+    #[case(
+        Color::Yellow,
+        Color::Reset,
+        ContrastPolicy::Adjust,
+        None,
+        Color::Yellow
+    )]
+    #[case(
+        Color::Yellow,
+        Color::White,
+        ContrastPolicy::Adjust,
+        Some(Some(Color::Rgb(0, 0, 0))),
+        Color::Rgb(0, 0, 0)
+    )]
+    #[case(
+        Color::Yellow,
+        Color::White,
+        ContrastPolicy::Warn,
+        Some(None),
+        Color::Yellow
+    )]
+    fn test_enforce(
+        #[case] fg: Color,
+        #[case] bg: Color,
+        #[case] policy: ContrastPolicy,
+        #[case] adjusted: Option<Option<Color>>,
+        #[case] drawn: Color,
+    ) {
+        let (issues, color) = draw(fg, bg, policy);
+        assert_eq!(color, drawn);
+        // Reported once for all the cells
+        let issues: Vec<_> = issues
+            .into_iter()
+            .map(|issue| (issue.fg, issue.bg, issue.adjusted.map(|(color, _)| color)))
+            .collect();
+        let expected: Vec<_> = adjusted
+            .into_iter()
+            .map(|adjusted| (fg, bg, adjusted))
+            .collect();
+        assert_eq!(issues, expected);
+    }
+
+    #[rstest]
+    fn test_enforce_off() {
+        assert_eq!(
+            draw(Color::Yellow, Color::White, ContrastPolicy::Off),
+            (vec![], Color::Yellow)
+        );
+    }
+
+    #[rstest]
+    fn test_describe() {
+        let (issues, _) = draw(Color::Red, Color::Black, ContrastPolicy::Adjust);
+        assert_eq!(
+            issues[0].describe(),
+            "Red on Black: 3.60:1 (foreground adjusted to #FFFFFF, 21.00:1)"
+        );
+    }
+}
@@ -32,12 +32,90 @@ impl<'de> Deserialize<'de> for KeyBindings {
     }
 }
 
+/// Alternative sets of default keybindings, which are applied before the ones in the config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeybindingProfile {
+    #[default]
+    Default,
+    Vim,
+    Emacs,
+}
+
+impl KeybindingProfile {
+    fn overrides(self) -> Option<&'static str> {
+        match self {
+            KeybindingProfile::Default => None,
+            KeybindingProfile::Vim => Some(include_str!("../../.config/keybindings/vim.json5")),
+            KeybindingProfile::Emacs => Some(include_str!("../../.config/keybindings/emacs.json5")),
+        }
+    }
+
+    /// Replaces the bindings with the ones of the profile, where `null` unbinds the keys.
+    pub fn apply(self, keybindings: &mut KeyBindings) -> Result<(), String> {
+        let Some(overrides) = self.overrides() else {
+            return Ok(());
+        };
+        let overrides: HashMap<Mode, HashMap<String, Option<Action>>> =
+            json5::from_str(overrides).map_err(|e| e.to_string())?;
+
+        for (mode, bindings) in overrides {
+            let keymap = keybindings.entry(mode).or_default();
+            for (key_str, cmd) in bindings {
+                let keys = parse_key_sequence(&key_str)?;
+                match cmd {
+                    Some(cmd) => keymap.insert(keys, cmd),
+                    None => keymap.remove(&keys),
+                };
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
     parse_key_code_with_modifiers(remaining, modifiers)
 }
 
+impl KeyBindings {
+    /// Returns the shortest keys bound to the action in the mode, e.g. `shift-u` or `zz`.
+    pub fn key_for(&self, mode: Mode, action: &Action) -> Option<String> {
+        self.get(&mode)?
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(keys, _)| keys.iter().map(key_event_to_string).collect::<String>())
+            .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+    }
+
+    /// Returns the action bound to the key, or to the keys typed so far in `pending` followed by
+    /// it. Keys which can't start or continue any binding are dropped from `pending`.
+    pub fn lookup(&self, mode: Mode, pending: &mut Vec<KeyEvent>, key: KeyEvent) -> Option<Action> {
+        let keymap = self.get(&mode)?;
+        if let Some(action) = keymap.get(&vec![key]) {
+            pending.clear();
+            return Some(action.clone());
+        }
+
+        pending.push(key);
+        if let Some(action) = keymap.get(pending) {
+            pending.clear();
+            return Some(action.clone());
+        }
+        let is_prefix = |keys: &[KeyEvent]| keymap.keys().any(|bound| bound.starts_with(keys));
+        if !is_prefix(pending) {
+            // The key may still start another binding, e.g. `g` after `zg` for `gg`
+            pending.clear();
+            if is_prefix(&[key]) {
+                pending.push(key);
+            }
+        }
+        None
+    }
+}
+
 fn extract_modifiers(raw: &str) -> (&str, KeyModifiers) {
     let mut modifiers = KeyModifiers::empty();
     let mut current = raw;
@@ -272,6 +350,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_for() {
+        let keybindings: KeyBindings = json5::from_str(
+            r#"{
+                "Home": {
+                    "<up>": "ScrollUp",
+                    "<k>": "ScrollUp",
+                    "<z><z>": {"PlaceSelection": "Center"},
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            keybindings.key_for(Mode::Home, &Action::ScrollUp),
+            Some(String::from("k"))
+        );
+        assert_eq!(
+            keybindings.key_for(
+                Mode::Home,
+                &Action::PlaceSelection(crate::viewport::Placement::Center)
+            ),
+            Some(String::from("zz"))
+        );
+        assert_eq!(keybindings.key_for(Mode::Home, &Action::Quit), None);
+        assert_eq!(keybindings.key_for(Mode::Compose, &Action::ScrollUp), None);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let keybindings: KeyBindings = json5::from_str(
+            r#"{
+                "Home": {
+                    "<j>": "ScrollDown",
+                    "<z><z>": {"PlaceSelection": "Center"},
+                    "<g><g>": "ScrollToTop",
+                }
+            }"#,
+        )
+        .unwrap();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::empty());
+        let lookup = |keys: &str| {
+            let mut pending = vec![];
+            let actions: Vec<Option<Action>> = keys
+                .chars()
+                .map(|c| keybindings.lookup(Mode::Home, &mut pending, key(c)))
+                .collect();
+            (actions, pending)
+        };
+
+        assert_eq!(lookup("j"), (vec![Some(Action::ScrollDown)], vec![]));
+        assert_eq!(
+            lookup("zgg"),
+            (vec![None, None, Some(Action::ScrollToTop)], vec![])
+        );
+        // Keys bound to nothing are not kept waiting for the timeout
+        assert_eq!(lookup("xzx"), (vec![None, None, None], vec![]));
+        assert_eq!(
+            lookup("zjz"),
+            (vec![None, Some(Action::ScrollDown), None], vec![key('z')])
+        );
+        assert_eq!(lookup("g"), (vec![None], vec![key('g')]));
+        assert_eq!(
+            keybindings.lookup(Mode::Compose, &mut vec![], key('j')),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keybinding_profile() {
+        let mut keybindings: KeyBindings = json5::from_str(
+            r#"{
+                "Home": {
+                    "<j>": "ScrollDown",
+                    "<q>": "Quit",
+                },
+                "Compose": {
+                    "<Ctrl-p>": "SubmitTextNote",
+                }
+            }"#,
+        )
+        .unwrap();
+
+        KeybindingProfile::Default.apply(&mut keybindings).unwrap();
+        assert_eq!(
+            keybindings.key_for(Mode::Home, &Action::ScrollDown),
+            Some(String::from("j"))
+        );
+
+        KeybindingProfile::Emacs.apply(&mut keybindings).unwrap();
+        assert_eq!(
+            keybindings.key_for(Mode::Home, &Action::ScrollDown),
+            Some(String::from("ctrl-n"))
+        );
+        assert_eq!(
+            keybindings.key_for(Mode::Home, &Action::Quit),
+            Some(String::from("q"))
+        );
+        assert_eq!(
+            keybindings.key_for(Mode::Compose, &Action::SubmitTextNote),
+            Some(String::from("ctrl-cctrl-c"))
+        );
+
+        KeybindingProfile::Vim.apply(&mut keybindings).unwrap();
+        assert_eq!(
+            keybindings.key_for(Mode::Home, &Action::ScrollToTop),
+            Some(String::from("gg"))
+        );
+    }
+
+    #[test]
+    fn test_profile_chord_after_unbound_key() {
+        let unbound = KeyEvent::new(KeyCode::F(12), KeyModifiers::empty());
+        let lookup = |profile: KeybindingProfile, mode: Mode, keys: &str| {
+            let mut keybindings = KeyBindings::default();
+            profile.apply(&mut keybindings).unwrap();
+            let mut pending = vec![];
+            assert_eq!(keybindings.lookup(mode, &mut pending, unbound), None);
+            parse_key_sequence(keys)
+                .unwrap()
+                .into_iter()
+                .map(|key| keybindings.lookup(mode, &mut pending, key))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            lookup(KeybindingProfile::Emacs, Mode::Compose, "<Ctrl-c><Ctrl-c>"),
+            vec![None, Some(Action::SubmitTextNote)]
+        );
+        assert_eq!(
+            lookup(KeybindingProfile::Vim, Mode::Home, "<g><g>"),
+            vec![None, Some(Action::ScrollToTop)]
+        );
+    }
+
     #[test]
     fn test_invalid_keys() {
         assert!(parse_key_event("invalid-key").is_err());
@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{eyre, Result};
+use serde_json::{Map, Value};
+
+/// Version of the config files written by this version of nostui.
+pub const CURRENT_VERSION: u64 = 3;
+
+/// Version of the files written before `version` was added.
+const UNVERSIONED: u64 = 1;
+
+type Transform = fn(&mut Map<String, Value>);
+
+/// Transforms from each version to the next, starting at [`UNVERSIONED`].
+const TRANSFORMS: [Transform; (CURRENT_VERSION - UNVERSIONED) as usize] =
+    [nest_keybindings, list_relays];
+
+/// A config file written by an older version of nostui, upgraded to be saved after confirmation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Migration {
+    pub path: PathBuf,
+    pub from: u64,
+    /// The upgraded file as plain JSON, which is loaded instead of the file until it is saved
+    pub content: String,
+}
+
+impl Migration {
+    /// Reads the JSON or JSON5 config file, returning the migration if it is from an older version.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let mut value: Value = json5::from_str(&fs::read_to_string(path)?)?;
+        let Some(from) = upgrade(&mut value)? else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            from,
+            content: serde_json::to_string_pretty(&value)?,
+        }))
+    }
+
+    /// Path the original file is kept at when the upgraded one is saved.
+    pub fn backup_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".v{}.bak", self.from));
+        PathBuf::from(path)
+    }
+
+    /// Replaces the file with the upgraded one, keeping the original at [`Self::backup_path`].
+    ///
+    /// The file is rewritten as plain JSON, so comments are not kept.
+    pub fn save(&self) -> Result<PathBuf> {
+        let backup = self.backup_path();
+        fs::copy(&self.path, &backup)?;
+        fs::write(&self.path, &self.content)?;
+        Ok(backup)
+    }
+}
+
+/// Upgrades the config to [`CURRENT_VERSION`], returning the version it was written for if
+/// anything but `version` changed.
+pub fn upgrade(value: &mut Value) -> Result<Option<u64>> {
+    let config = value
+        .as_object_mut()
+        .ok_or_else(|| eyre!("The config file is not an object"))?;
+    let from = match config.get("version") {
+        None => UNVERSIONED,
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version >= UNVERSIONED)
+            .ok_or_else(|| eyre!("Unknown config version {version}"))?,
+    };
+    if from > CURRENT_VERSION {
+        return Err(eyre!(
+            "The config file is for a newer version of nostui (version {from})"
+        ));
+    }
+
+    let original = config.clone();
+    for transform in &TRANSFORMS[(from - UNVERSIONED) as usize..] {
+        transform(config);
+    }
+    let changed = *config != original;
+    config.insert(String::from("version"), Value::from(CURRENT_VERSION));
+    Ok(changed.then_some(from))
+}
+
+/// Version 1 to 2: keybindings given without a mode, e.g. `{"<q>": "Quit"}`, are bound in `Home`.
+fn nest_keybindings(config: &mut Map<String, Value>) {
+    let Some(Value::Object(keybindings)) = config.get_mut("keybindings") else {
+        return;
+    };
+
+    let keys: Vec<String> = keybindings
+        .keys()
+        .filter(|key| key.starts_with('<'))
+        .cloned()
+        .collect();
+    if keys.is_empty() {
+        return;
+    }
+
+    let mut home = match keybindings.remove("Home") {
+        Some(Value::Object(home)) => home,
+        _ => Map::new(),
+    };
+    for key in keys {
+        if let Some(action) = keybindings.remove(&key) {
+            // Bindings given within the mode win over the flat ones
+            home.entry(key).or_insert(action);
+        }
+    }
+    keybindings.insert(String::from("Home"), Value::Object(home));
+}
+
+/// Version 2 to 3: relays given as an object of URLs to policies, e.g.
+/// `{"wss://nos.lol": {"read": true, "write": false}}`, become a list.
+fn list_relays(config: &mut Map<String, Value>) {
+    let Some(Value::Object(relays)) = config.get("relays") else {
+        return;
+    };
+
+    let list = relays
+        .iter()
+        .map(|(url, policy)| match policy {
+            Value::Object(policy) => {
+                let mut entry = policy.clone();
+                entry.insert(String::from("url"), Value::from(url.clone()));
+                Value::Object(entry)
+            }
+            _ => Value::from(url.clone()),
+        })
+        .collect();
+    config.insert(String::from("relays"), Value::Array(list));
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+    use serde_json::json;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        json!({"keybindings": {"<q>": "Quit", "Home": {"<j>": "ScrollDown"}}}),
+        json!({"keybindings": {"Home": {"<q>": "Quit", "<j>": "ScrollDown"}}}),
+    )]
+    #[case(
+        json!({"keybindings": {"<q>": "Quit", "Home": {"<q>": "Suspend"}}}),
+        json!({"keybindings": {"Home": {"<q>": "Suspend"}}}),
+    )]
+    #[case(
+        json!({"keybindings": {"Compose": {"<esc>": "Unselect"}}}),
+        json!({"keybindings": {"Compose": {"<esc>": "Unselect"}}}),
+    )]
+    fn test_nest_keybindings(#[case] config: Value, #[case] expected: Value) {
+        let (Value::Object(mut config), Value::Object(expected)) = (config, expected) else {
+            unreachable!();
+        };
+        nest_keybindings(&mut config);
+        assert_eq!(config, expected);
+    }
+
+    #[rstest]
+    #[case(
+        json!({"relays": {"wss://nos.lol": {}, "wss://relay.damus.io": {"write": false}}}),
+        json!({"relays": [
+            {"url": "wss://nos.lol"},
+            {"url": "wss://relay.damus.io", "write": false},
+        ]}),
+    )]
+    #[case(
+        json!({"relays": {"wss://nos.lol": true}}),
+        json!({"relays": ["wss://nos.lol"]}),
+    )]
+    #[case(json!({"relays": ["wss://nos.lol"]}), json!({"relays": ["wss://nos.lol"]}))]
+    fn test_list_relays(#[case] config: Value, #[case] expected: Value) {
+        let (Value::Object(mut config), Value::Object(expected)) = (config, expected) else {
+            unreachable!();
+        };
+        list_relays(&mut config);
+        assert_eq!(config, expected);
+    }
+
+    #[rstest]
+    #[case(json!({"keybindings": {"<q>": "Quit"}}), Some(1))]
+    #[case(json!({"version": 2, "relays": {"wss://nos.lol": {}}}), Some(2))]
+    // Keybindings of version 2 have modes already, so they are not nested again
+    #[case(json!({"version": 2, "keybindings": {"<q>": "Quit"}}), None)]
+    #[case(json!({"relays": ["wss://nos.lol"]}), None)]
+    #[case(json!({"version": 3}), None)]
+    fn test_upgrade(#[case] mut config: Value, #[case] expected: Option<u64>) {
+        assert_eq!(upgrade(&mut config).unwrap(), expected);
+        assert_eq!(config["version"], json!(CURRENT_VERSION));
+    }
+
+    #[rstest]
+    #[case(json!({"version": 4}))]
+    #[case(json!({"version": 0}))]
+    #[case(json!({"version": "3"}))]
+    #[case(json!([]))]
+    fn test_upgrade_unknown(#[case] mut config: Value) {
+        assert_eq!(upgrade(&mut config).is_err(), true);
+    }
+}
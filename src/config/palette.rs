@@ -0,0 +1,148 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::Deserialize;
+
+use super::contrast;
+
+/// How colors of the UI are rendered, applied to every cell after components are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// Colors as components draw them
+    #[default]
+    Default,
+    /// Bright colors, no dimmed grays, and black or white behind highlighted text
+    HighContrast,
+    /// No colors, only modifiers such as bold and reversed
+    None,
+}
+
+impl ColorMode {
+    /// Returns the mode to render with, which is `None` if `NO_COLOR` is set to a non-empty value.
+    pub fn resolve(self, no_color: Option<&str>) -> Self {
+        match no_color {
+            Some(value) if !value.is_empty() => ColorMode::None,
+            _ => self,
+        }
+    }
+}
+
+fn high_contrast_foreground(color: Color) -> Color {
+    match color {
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        Color::Reset
+        | Color::LightRed
+        | Color::LightGreen
+        | Color::LightYellow
+        | Color::LightBlue
+        | Color::LightMagenta
+        | Color::LightCyan => color,
+        // Grays and other colors are left to the default color of the terminal
+        _ => Color::Reset,
+    }
+}
+
+/// Replaces a background with black or white, whichever it is closer to, and the foreground
+/// with the other one.
+fn high_contrast_pair(bg: Color) -> Option<(Color, Color)> {
+    match contrast::readable_foreground(bg)? {
+        (Color::Rgb(0, 0, 0), _) => Some((Color::Black, Color::White)),
+        _ => Some((Color::White, Color::Black)),
+    }
+}
+
+/// Renders the colors of the buffer in the mode.
+pub fn apply(buffer: &mut Buffer, mode: ColorMode) {
+    if mode == ColorMode::Default {
+        return;
+    }
+
+    for cell in buffer.content.iter_mut() {
+        match mode {
+            ColorMode::Default => {}
+            ColorMode::HighContrast => {
+                cell.modifier.remove(Modifier::DIM);
+                match high_contrast_pair(cell.bg) {
+                    Some((fg, bg)) => {
+                        cell.fg = fg;
+                        cell.bg = bg;
+                    }
+                    None => cell.fg = high_contrast_foreground(cell.fg),
+                }
+            }
+            ColorMode::None => {
+                // Highlighted text stays distinguishable
+                if cell.bg != Color::Reset {
+                    cell.modifier.toggle(Modifier::REVERSED);
+                }
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+                cell.underline_color = Color::Reset;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::{layout::Rect, style::Style};
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(ColorMode::Default, None, ColorMode::Default)]
+    #[case(ColorMode::HighContrast, None, ColorMode::HighContrast)]
+    #[case(ColorMode::HighContrast, Some(""), ColorMode::HighContrast)]
+    #[case(ColorMode::Default, Some("1"), ColorMode::None)]
+    fn test_resolve(
+        #[case] mode: ColorMode,
+        #[case] no_color: Option<&str>,
+        #[case] expected: ColorMode,
+    ) {
+        assert_eq!(mode.resolve(no_color), expected);
+    }
+
+    /// Renders "x" with the colors and the modifier, returning the style of the rendered cell.
+    fn render(
+        fg: Color,
+        bg: Color,
+        modifier: Modifier,
+        mode: ColorMode,
+    ) -> (Color, Color, Modifier) {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 1, 1));
+        let style = Style::new().fg(fg).bg(bg).add_modifier(modifier);
+        buffer.set_string(0, 0, "x", style);
+        apply(&mut buffer, mode);
+        let cell = buffer.get(0, 0);
+        (cell.fg, cell.bg, cell.modifier)
+    }
+
+    #[rstest]
+    #[case(ColorMode::Default, Color::Yellow, Color::Reset, Modifier::empty(), (Color::Yellow, Color::Reset, Modifier::empty()))]
+    #[case(ColorMode::Default, Color::Yellow, Color::Blue, Modifier::DIM, (Color::Yellow, Color::Blue, Modifier::DIM))]
+    #[case(ColorMode::HighContrast, Color::Yellow, Color::Reset, Modifier::empty(), (Color::LightYellow, Color::Reset, Modifier::empty()))]
+    #[case(ColorMode::HighContrast, Color::DarkGray, Color::Reset, Modifier::DIM, (Color::Reset, Color::Reset, Modifier::empty()))]
+    #[case(ColorMode::HighContrast, Color::Yellow, Color::Blue, Modifier::BOLD, (Color::White, Color::Black, Modifier::BOLD))]
+    #[case(ColorMode::HighContrast, Color::Black, Color::LightYellow, Modifier::empty(), (Color::Black, Color::White, Modifier::empty()))]
+    #[case(ColorMode::None, Color::Yellow, Color::Reset, Modifier::BOLD, (Color::Reset, Color::Reset, Modifier::BOLD))]
+    #[case(ColorMode::None, Color::Black, Color::Yellow, Modifier::empty(), (Color::Reset, Color::Reset, Modifier::REVERSED))]
+    #[case(ColorMode::None, Color::Reset, Color::Yellow, Modifier::REVERSED, (Color::Reset, Color::Reset, Modifier::empty()))]
+    fn test_apply(
+        #[case] mode: ColorMode,
+        #[case] fg: Color,
+        #[case] bg: Color,
+        #[case] modifier: Modifier,
+        #[case] expected: (Color, Color, Modifier),
+    ) {
+        assert_eq!(render(fg, bg, modifier, mode), expected);
+    }
+}
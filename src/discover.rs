@@ -0,0 +1,141 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Number of authors in a filter to fetch contact lists, to stay within the limits of relays.
+pub const CONTACT_LIST_BATCH_SIZE: usize = 100;
+
+/// An account followed by my followings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub pubkey: PublicKey,
+    /// Number of my followings who follow the account
+    pub followed_by: usize,
+    pub name: String,
+    pub about: Option<String>,
+    pub nip05: Option<String>,
+    /// Whether I followed the account from the suggestions
+    pub followed: bool,
+}
+
+impl Suggestion {
+    pub fn new(pubkey: PublicKey, followed_by: usize) -> Self {
+        Self {
+            pubkey,
+            followed_by,
+            name: String::new(),
+            about: None,
+            nip05: None,
+            followed: false,
+        }
+    }
+}
+
+/// Ranks accounts by the number of my followings who follow them, excluding me and my followings.
+/// Ties are broken by the public key for the order to be stable.
+pub fn rank<'a>(
+    me: &PublicKey,
+    followings: &HashSet<PublicKey>,
+    contact_lists: impl IntoIterator<Item = &'a HashSet<PublicKey>>,
+    limit: usize,
+) -> Vec<Suggestion> {
+    let mut counts: HashMap<PublicKey, usize> = HashMap::new();
+    for contact_list in contact_lists {
+        for pubkey in contact_list {
+            if pubkey != me && !followings.contains(pubkey) {
+                *counts.entry(*pubkey).or_default() += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(PublicKey, usize)> = counts.into_iter().collect();
+    ranked.sort_by_key(|(pubkey, count)| (Reverse(*count), *pubkey));
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(pubkey, count)| Suggestion::new(pubkey, count))
+        .collect()
+}
+
+/// Adds or removes a `p` tag of a contact list, keeping the other tags as they are.
+/// Returns `None` if nothing changes.
+pub fn update_contact_list(tags: &[Tag], pubkey: PublicKey, follow: bool) -> Option<Vec<Tag>> {
+    let is_followed = |tag: &Tag| matches!(tag, Tag::PublicKey { public_key, uppercase: false, .. } if *public_key == pubkey);
+    let followed = tags.iter().any(is_followed);
+    match (follow, followed) {
+        (true, false) => {
+            let mut tags = tags.to_vec();
+            tags.push(Tag::public_key(pubkey));
+            Some(tags)
+        }
+        (false, true) => Some(
+            tags.iter()
+                .filter(|tag| !is_followed(tag))
+                .cloned()
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_rank() {
+        let [me, a, b, c, d] = [(); 5].map(|_| Keys::generate().public_key());
+        let followings = HashSet::from([a, b]);
+        let contact_lists = [
+            HashSet::from([me, b, c, d]),
+            HashSet::from([a, c]),
+            HashSet::from([c]),
+        ];
+
+        let suggestions = rank(&me, &followings, &contact_lists, 10);
+        let ranked: Vec<(PublicKey, usize)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.pubkey, suggestion.followed_by))
+            .collect();
+        assert_eq!(ranked, vec![(c, 3), (d, 1)]);
+
+        assert_eq!(rank(&me, &followings, &contact_lists, 1).len(), 1);
+    }
+
+    #[rstest]
+    fn test_rank_tie() {
+        let me = Keys::generate().public_key();
+        let [a, b] = [(); 2].map(|_| Keys::generate().public_key());
+        let contact_lists = [HashSet::from([a, b])];
+
+        let ranked: Vec<PublicKey> = rank(&me, &HashSet::new(), &contact_lists, 10)
+            .into_iter()
+            .map(|suggestion| suggestion.pubkey)
+            .collect();
+        assert_eq!(ranked, vec![a.min(b), a.max(b)]);
+    }
+
+    #[rstest]
+    fn test_update_contact_list() {
+        let [a, b] = [(); 2].map(|_| Keys::generate().public_key());
+        let tags = vec![
+            Tag::public_key(a),
+            Tag::Generic(TagKind::Custom(String::from("client")), vec![]),
+        ];
+
+        let followed = update_contact_list(&tags, b, true).unwrap();
+        assert_eq!(followed.len(), 3);
+        assert_eq!(followed[2], Tag::public_key(b));
+        assert_eq!(update_contact_list(&followed, b, true), None);
+
+        assert_eq!(update_contact_list(&followed, b, false), Some(tags.clone()));
+        assert_eq!(update_contact_list(&tags, b, false), None);
+    }
+}
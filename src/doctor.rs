@@ -0,0 +1,223 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+
+use crate::{
+    cli::Cli,
+    config::{Config, RelayConfig},
+    i18n,
+    keys::{KeySource, TerminalPrompt},
+    nostr::{nip11, proxy},
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Result of a check, e.g. of the connection to a relay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: Status,
+    pub subject: String,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(status: Status, subject: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            subject: subject.into(),
+            detail: detail.into(),
+        }
+    }
+
+    /// Formats the check as a line of the report, e.g. `✓ wss://nos.lol: connected in 120 ms`.
+    pub fn line(&self) -> String {
+        let mark = match self.status {
+            Status::Ok => "✓",
+            Status::Warning => "!",
+            Status::Error => "✗",
+        };
+        format!("{mark} {}: {}", self.subject, self.detail)
+    }
+}
+
+/// Checks the config, the key and the relays, which is used at startup by `--doctor` and
+/// in the app. The config has to be unlocked to check the key.
+pub async fn diagnose(config: &Config) -> Vec<Check> {
+    let mut checks = vec![check_config(config), check_key(config)];
+
+    let proxy = match config.proxy_addr() {
+        Ok(proxy) => {
+            if let Some(proxy) = proxy {
+                checks.push(Check::new(Status::Ok, "Proxy", proxy.to_string()));
+            }
+            proxy
+        }
+        Err(e) => {
+            checks.push(Check::new(Status::Error, "Proxy", e.to_string()));
+            return checks;
+        }
+    };
+
+    let (relays, onion_relays) = proxy::reachable_relays(&config.relays, proxy.is_some());
+    for relay in onion_relays {
+        checks.push(Check::new(
+            Status::Warning,
+            relay.url,
+            i18n::tr("skipped, as onion relays need a proxy"),
+        ));
+    }
+    let relay_checks =
+        futures::future::join_all(relays.iter().map(|relay| check_relay(relay, proxy))).await;
+    checks.extend(relay_checks.into_iter().flatten());
+    checks
+}
+
+fn check_config(config: &Config) -> Check {
+    let dir = config.config._config_dir.display().to_string();
+    Check::new(
+        Status::Ok,
+        "Config",
+        i18n::tr_args("loaded from {dir}", &[("dir", &dir)]),
+    )
+}
+
+fn check_key(config: &Config) -> Check {
+    match (config.keys(), config.key_source()) {
+        (Some(keys), source) => {
+            let npub = keys.public_key().to_bech32().unwrap_or_default();
+            if matches!(source, KeySource::Config(ref key) if !key.starts_with("ncryptsec1")) {
+                let detail = i18n::tr_args(
+                    "{npub}, stored as plaintext in the config",
+                    &[("npub", &npub)],
+                );
+                Check::new(Status::Warning, "Key", detail)
+            } else {
+                Check::new(Status::Ok, "Key", npub)
+            }
+        }
+        (None, KeySource::None) => Check::new(
+            Status::Warning,
+            "Key",
+            i18n::tr("no private key, so nostui starts in browse mode"),
+        ),
+        (None, _) => Check::new(
+            Status::Error,
+            "Key",
+            i18n::tr("the private key is not loaded"),
+        ),
+    }
+}
+
+/// Connects to the relay and fetches its information document (NIP-11).
+async fn check_relay(relay: &RelayConfig, proxy: Option<SocketAddr>) -> Vec<Check> {
+    let Ok(url) = Url::parse(&relay.url) else {
+        return vec![Check::new(
+            Status::Error,
+            &relay.url,
+            i18n::tr("not a relay URL"),
+        )];
+    };
+
+    let (connection, info) = tokio::join!(connect(url.clone(), proxy), nip11::fetch(url, proxy));
+    let subject = relay.to_string();
+    let connection = match connection {
+        Some(latency) => Check::new(
+            Status::Ok,
+            &subject,
+            i18n::tr_args("connected in {ms} ms", &[("ms", &latency.as_millis())]),
+        ),
+        None => Check::new(Status::Error, &subject, i18n::tr("cannot connect")),
+    };
+    let info = match info {
+        Ok(info) => {
+            let restrictions = nip11::restrictions(&info);
+            let description = nip11::describe(&info);
+            if restrictions.is_empty() {
+                Check::new(Status::Ok, &subject, description)
+            } else {
+                let description = format!("{description} [{}]", restrictions.join(", "));
+                Check::new(Status::Warning, &subject, description)
+            }
+        }
+        Err(e) => Check::new(
+            Status::Warning,
+            &subject,
+            i18n::tr_args("no relay information (NIP-11): {error}", &[("error", &e)]),
+        ),
+    };
+    vec![connection, info]
+}
+
+/// Returns how long it took to connect to the relay, or `None` if it couldn't.
+async fn connect(url: Url, proxy: Option<SocketAddr>) -> Option<Duration> {
+    let relay = Relay::with_opts(url, RelayOptions::new().proxy(proxy));
+    let started_at = Instant::now();
+    relay.connect(Some(CONNECT_TIMEOUT)).await;
+    let latency = started_at.elapsed();
+    let connected = relay.is_connected().await;
+    let _ = relay.terminate().await;
+    connected.then_some(latency)
+}
+
+/// Runs the checks without the TUI and prints the report, failing if any check failed.
+pub async fn run(args: &Cli) -> Result<()> {
+    let mut config = match Config::new() {
+        Ok(config) => config,
+        Err(e) => {
+            println!(
+                "{}",
+                Check::new(Status::Error, "Config", e.to_string()).line()
+            );
+            return Err(eyre!("The config cannot be loaded"));
+        }
+    };
+    config.override_relays(&args.relays);
+    config.key_stdin = args.key_stdin;
+
+    let unlocked = config.unlock(&mut TerminalPrompt);
+    let mut checks = diagnose(&config).await;
+    // Tells why the key is not loaded
+    let key = checks.iter_mut().find(|check| check.subject == "Key");
+    if let (Err(e), Some(check)) = (unlocked, key) {
+        check.detail = e.to_string();
+    }
+    for check in &checks {
+        println!("{}", check.line());
+    }
+
+    let errors = checks
+        .iter()
+        .filter(|check| check.status == Status::Error)
+        .count();
+    if errors > 0 {
+        return Err(eyre!("{errors} of {} checks failed", checks.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Status::Ok, "✓ Key: npub1")]
+    #[case(Status::Warning, "! Key: npub1")]
+    #[case(Status::Error, "✗ Key: npub1")]
+    fn test_line(#[case] status: Status, #[case] expected: &str) {
+        assert_eq!(Check::new(status, "Key", "npub1").line(), expected);
+    }
+}
@@ -0,0 +1,71 @@
+use std::{fs, process::Command};
+
+use color_eyre::eyre::{eyre, Result};
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Returns the program and arguments to edit a file with, from `VISUAL` or `EDITOR` such as
+/// `code --wait`, falling back to `vi`.
+fn command(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            value
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .find(|command| !command.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_EDITOR.to_string()])
+}
+
+/// Opens the text in the external editor and returns the edited text, without the newline
+/// which editors add at the end. The terminal must be left before calling this.
+pub fn edit(text: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("nostui-draft-{}.txt", std::process::id()));
+    fs::write(&path, text)?;
+
+    let command = command(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(&path)
+        .status();
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.map_err(|e| eyre!("Failed to run {}: {e}", command[0]))?;
+    if !status.success() {
+        return Err(eyre!("{} exited with {status}", command[0]));
+    }
+    let edited = edited?;
+    Ok(edited
+        .strip_suffix('\n')
+        .map(|s| s.strip_suffix('\r').unwrap_or(s))
+        .unwrap_or(&edited)
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Some("nvim"), Some("nano"), &["nvim"])]
+    #[case(None, Some("code --wait"), &["code", "--wait"])]
+    #[case(Some(" "), Some("nano"), &["nano"])]
+    #[case(None, None, &["vi"])]
+    fn test_command(
+        #[case] visual: Option<&str>,
+        #[case] editor: Option<&str>,
+        #[case] expected: &[&str],
+    ) {
+        assert_eq!(
+            command(visual.map(String::from), editor.map(String::from)),
+            expected
+        );
+    }
+}
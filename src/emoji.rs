@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use nostr_sdk::prelude::*;
+use regex::{Captures, Regex};
+
+/// Shortcodes of common emojis, completed after `:` and shown as emojis in notes.
+pub const EMOJIS: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("clap", "👏"),
+    ("cry", "😢"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("grin", "😁"),
+    ("heart", "❤️"),
+    ("joy", "😂"),
+    ("laughing", "😆"),
+    ("ok_hand", "👌"),
+    ("pray", "🙏"),
+    ("purple_heart", "💜"),
+    ("rocket", "🚀"),
+    ("see_no_evil", "🙈"),
+    ("smile", "😄"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("sunglasses", "😎"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("wave", "👋"),
+    ("wink", "😉"),
+    ("zap", "⚡"),
+];
+
+lazy_static! {
+    static ref SHORTCODE_PATTERN: Regex = Regex::new(r":([\w+-]+):").unwrap();
+}
+
+/// Replaces `:shortcode:` with the emoji, or with `[shortcode]` for custom emojis (NIP-30),
+/// whose images can't be shown in the terminal. Unknown shortcodes are kept as they are.
+pub fn emojify(content: &str, tags: &[Tag]) -> String {
+    let custom: HashMap<&str, &UncheckedUrl> = tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Emoji { shortcode, url } => Some((shortcode.as_str(), url)),
+            _ => None,
+        })
+        .collect();
+
+    SHORTCODE_PATTERN
+        .replace_all(content, |captures: &Captures| {
+            let shortcode = &captures[1];
+            if custom.contains_key(shortcode) {
+                return format!("[{shortcode}]");
+            }
+
+            EMOJIS
+                .iter()
+                .find(|(code, _)| *code == shortcode)
+                .map_or_else(|| captures[0].to_owned(), |(_, emoji)| (*emoji).to_owned())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("gm :wave: :+1:", "gm 👋 👍")]
+    #[case("soon:tm: :pepe:", "soon:tm: [pepe]")]
+    #[case("at 12:30:45", "at 12:30:45")]
+    #[case(":smile::fire:", "😄🔥")]
+    #[case("no shortcodes", "no shortcodes")]
+    fn test_emojify(#[case] content: &str, #[case] expected: &str) {
+        let tags = vec![Tag::Emoji {
+            shortcode: String::from("pepe"),
+            url: UncheckedUrl::from("https://example.com/pepe.png"),
+        }];
+        assert_eq!(emojify(content, &tags), expected);
+    }
+}
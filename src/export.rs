@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+
+use crate::timestamp;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Guesses the format from the file extension, defaulting to JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown") => {
+                Self::Markdown
+            }
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Renders events as a JSON array of raw nostr events, which can be re-published as they are.
+pub fn to_json(events: &[Event]) -> Result<String> {
+    let events: Vec<serde_json::Value> = events
+        .iter()
+        .map(|event| serde_json::from_str(&event.as_json()))
+        .collect::<Result<_, _>>()?;
+    Ok(serde_json::to_string_pretty(&events)?)
+}
+
+/// Renders events as a Markdown document. `names` maps authors to their display names.
+pub fn to_markdown(title: &str, events: &[Event], names: &HashMap<PublicKey, String>) -> String {
+    let mut markdown = format!("# {title}\n");
+    for event in events {
+        let name = names
+            .get(&event.pubkey)
+            .cloned()
+            .or_else(|| event.pubkey.to_bech32().ok())
+            .unwrap_or_else(|| event.pubkey.to_string());
+        let created_at = timestamp::utc(event.created_at);
+        let note1 = event.id.to_bech32().unwrap_or_else(|_| event.id.to_hex());
+
+        markdown.push_str(&format!(
+            "\n## {name}\n\n{created_at} `{note1}`\n\n{}\n",
+            event.content.trim_end()
+        ));
+    }
+    markdown
+}
+
+/// Writes events to `path` in the format given by its extension.
+pub fn write(
+    path: &Path,
+    title: &str,
+    events: &[Event],
+    names: &HashMap<PublicKey, String>,
+) -> Result<()> {
+    if path.as_os_str().is_empty() {
+        return Err(eyre!("No path is given"));
+    }
+
+    let content = match ExportFormat::from_path(path) {
+        ExportFormat::Json => to_json(events)?,
+        ExportFormat::Markdown => to_markdown(title, events, names),
+    };
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[fixture]
+    fn event() -> Event {
+        Event::from_json(
+            r#"{
+                "kind":1,
+                "sig":"a8d944e323439d16f867d59f0fb5c4b6f9c1302c887ab45c546b1fe38d58bf20263c79b1ffa86258a7607578a29c46f2613b286fb81efb45e2b2524a350a4f51",
+                "id":"fcd6707cf1943d6f3ffa3c382bddb966027f98ddca15511a897a51ccfe160cd6",
+                "pubkey":"4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25",
+                "created_at":1704091367,
+                "tags":[],
+                "content":"hello, world\n"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("notes.json", ExportFormat::Json)]
+    #[case("notes", ExportFormat::Json)]
+    #[case("notes.md", ExportFormat::Markdown)]
+    #[case("notes.MARKDOWN", ExportFormat::Markdown)]
+    fn test_export_format_from_path(#[case] path: &str, #[case] expected: ExportFormat) {
+        assert_eq!(ExportFormat::from_path(Path::new(path)), expected);
+    }
+
+    #[rstest]
+    fn test_to_json(event: Event) {
+        let json = to_json(std::slice::from_ref(&event)).unwrap();
+        let events: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(Event::from_value(events[0].clone()).unwrap(), event);
+    }
+
+    #[rstest]
+    fn test_to_markdown(event: Event) {
+        let names = HashMap::from([(event.pubkey, String::from("foo"))]);
+        assert_eq!(
+            to_markdown("Timeline", &[event], &names),
+            "# Timeline\n\n## foo\n\n2024-01-01 06:42:47 UTC `note1lnt8ql83js7k70l68suzhhdevcp8lxxaeg24zx5f0fguelskpntqy0r809`\n\nhello, world\n"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Contact lists fetched to count followers, as relays return a share of them anyway and each
+/// one lists hundreds of accounts.
+pub const LIMIT: usize = 2_000;
+
+/// Time after the last follower arrived until the count is done, as fetches don't report their
+/// end. Fetches time out after 10 seconds.
+const SETTLE_TIME: Duration = Duration::from_secs(12);
+
+/// A follower listed in the followers view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Follower {
+    pub pubkey: PublicKey,
+    pub name: String,
+}
+
+/// Followers of an account as listed in the followers view, while they are being fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowerList {
+    /// Name of the account followed
+    pub name: String,
+    pub followers: Vec<Follower>,
+    pub fetching: bool,
+}
+
+/// Counts the followers of an account from the contact lists which tag it, keeping the latest
+/// list of each author so that unfollows are not counted.
+#[derive(Debug, Clone)]
+pub struct FollowerCount {
+    pub target: PublicKey,
+    /// Whether the latest contact list of each author follows the target, and when it was made
+    lists: HashMap<PublicKey, (Timestamp, bool)>,
+    last_change: Instant,
+}
+
+impl FollowerCount {
+    pub fn new(target: PublicKey, now: Instant) -> Self {
+        Self {
+            target,
+            lists: HashMap::new(),
+            last_change: now,
+        }
+    }
+
+    /// Counts the contact list, returning `true` if the followers changed.
+    pub fn add(&mut self, contact_list: &Event, now: Instant) -> bool {
+        if contact_list.kind != Kind::ContactList {
+            return false;
+        }
+        if let Some((created_at, _)) = self.lists.get(&contact_list.pubkey) {
+            if *created_at >= contact_list.created_at {
+                return false;
+            }
+        }
+
+        let follows = contact_list
+            .public_keys()
+            .any(|pubkey| *pubkey == self.target);
+        let previous = self
+            .lists
+            .insert(contact_list.pubkey, (contact_list.created_at, follows));
+        let changed = previous.map_or(follows, |(_, followed)| followed != follows);
+        if changed {
+            self.last_change = now;
+        }
+        changed
+    }
+
+    /// Followers found so far, ordered by public key for the list to be stable.
+    pub fn followers(&self) -> Vec<PublicKey> {
+        let mut followers: Vec<PublicKey> = self
+            .lists
+            .iter()
+            .filter(|(_, (_, follows))| *follows)
+            .map(|(pubkey, _)| *pubkey)
+            .collect();
+        followers.sort();
+        followers
+    }
+
+    pub fn is_fetching(&self, now: Instant) -> bool {
+        now.duration_since(self.last_change) < SETTLE_TIME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn contact_list(keys: &Keys, follows: &[PublicKey], created_at: u64) -> Event {
+        let contacts = follows
+            .iter()
+            .map(|pubkey| Contact::new::<String>(*pubkey, None, None));
+        EventBuilder::contact_list(contacts)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_add() {
+        let (target, alice, bob) = (Keys::generate(), Keys::generate(), Keys::generate());
+        let start = Instant::now();
+        let mut count = FollowerCount::new(target.public_key(), start);
+
+        assert!(count.add(&contact_list(&alice, &[target.public_key()], 2), start));
+        assert!(!count.add(&contact_list(&bob, &[alice.public_key()], 2), start));
+        // Older lists are ignored
+        assert!(!count.add(&contact_list(&alice, &[], 1), start));
+        assert_eq!(count.followers(), vec![alice.public_key()]);
+
+        let later = start + SETTLE_TIME;
+        assert!(!count.is_fetching(later));
+        // Unfollowed since
+        assert!(count.add(&contact_list(&alice, &[], 3), later));
+        assert_eq!(count.followers(), vec![]);
+        assert!(count.is_fetching(later));
+    }
+}
@@ -0,0 +1,244 @@
+//! Drives the app without a terminal or relays, e.g. for bots and end-to-end tests.
+//!
+//! Keys and actions go through the same pipeline as in [`App::run`], i.e. keybindings, the
+//! mode and layout of the app and then every component, and frames are drawn into a
+//! [`TestBackend`]. Requests to the relays, e.g. [`Action::SendTextNote`], are returned by
+//! [`Headless::settle`] instead of being sent, and events from relays are fed with
+//! [`Headless::send`], e.g. as [`Action::ReceiveEvent`].
+//!
+//! ```ignore
+//! let mut headless = Headless::new(App::with_config(config, &args)?, 80, 24)?;
+//! headless.send(Action::ReceiveEvent(note))?;
+//! headless.settle()?;
+//! assert!(headless.screen()?.iter().any(|line| line.contains("gm")));
+//! ```
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use tokio::sync::mpsc;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{action::Action, app::App, tui};
+
+pub struct Headless {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    action_tx: mpsc::UnboundedSender<Action>,
+    action_rx: mpsc::UnboundedReceiver<Action>,
+}
+
+impl Headless {
+    /// Starts the components of the app in a screen of the size.
+    pub fn new(mut app: App, width: u16, height: u16) -> Result<Self> {
+        let terminal = Terminal::new(TestBackend::new(width, height))?;
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        app.init_components(&action_tx, terminal.size()?)?;
+        Ok(Self {
+            app,
+            terminal,
+            action_tx,
+            action_rx,
+        })
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Queues an action as if it came from the runtime, which [`Headless::settle`] processes.
+    pub fn send(&self, action: Action) -> Result<()> {
+        self.action_tx.send(action)?;
+        Ok(())
+    }
+
+    /// Presses a key and processes the actions which it leads to.
+    pub fn key(&mut self, key: KeyEvent) -> Result<Vec<Action>> {
+        self.app.handle_key(key, &self.action_tx)?;
+        self.app
+            .handle_event(tui::Event::Key(key), &self.action_tx)?;
+        self.settle()
+    }
+
+    /// Processes the queued actions and the ones which they lead to, returning all of them.
+    pub fn settle(&mut self) -> Result<Vec<Action>> {
+        let mut processed = Vec::new();
+        while let Ok(action) = self.action_rx.try_recv() {
+            self.app.update_state(&action, &self.action_tx)?;
+            self.app.dispatch(&action, &self.action_tx)?;
+            processed.push(action);
+        }
+        Ok(processed)
+    }
+
+    /// Draws a frame and returns its rows, trimmed at the end.
+    pub fn screen(&mut self) -> Result<Vec<String>> {
+        let Self {
+            app,
+            terminal,
+            action_tx,
+            ..
+        } = self;
+        terminal.draw(|f| app.draw(f, action_tx))?;
+        Ok(lines(self.buffer()))
+    }
+
+    /// Returns the last frame, e.g. to check the colors of its cells.
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+}
+
+/// Returns the rows of the buffer, skipping the cells hidden by wide characters.
+pub fn lines(buffer: &Buffer) -> Vec<String> {
+    let width = buffer.area.width as usize;
+    buffer
+        .content
+        .chunks(width)
+        .map(|row| {
+            let mut line = String::new();
+            let mut hidden = 0;
+            for cell in row {
+                if hidden > 0 {
+                    hidden -= 1;
+                    continue;
+                }
+                line.push_str(cell.symbol());
+                hidden = cell.symbol().width().saturating_sub(1);
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use pretty_assertions::assert_eq;
+    use ratatui::style::Color;
+    use rstest::*;
+
+    use super::*;
+    use crate::{
+        cli::Cli,
+        components::testing::{config, note},
+        config::{Config, CONFIG},
+        mode::Mode,
+    };
+
+    fn headless() -> Headless {
+        with_config(config())
+    }
+
+    fn with_config(mut config: Config) -> Headless {
+        config.keybindings = json5::from_str::<Config>(CONFIG).unwrap().keybindings;
+        let app = App::with_config(config, &Cli::parse_from(["nostui"])).unwrap();
+        Headless::new(app, 80, 24).unwrap()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn contains(lines: &[String], text: &str) -> bool {
+        lines.iter().any(|line| line.contains(text))
+    }
+
+    #[rstest]
+    fn test_receive_note() {
+        let mut headless = headless();
+        headless
+            .send(Action::ReceiveEvent(note("gm nostr", 1_700_000_000)))
+            .unwrap();
+        headless.settle().unwrap();
+        assert!(contains(&headless.screen().unwrap(), "gm nostr"));
+    }
+
+    #[rstest]
+    fn test_post_note() {
+        let mut headless = headless();
+        headless
+            .key(key(KeyCode::Char('n'), KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(headless.app().mode, Mode::Compose);
+        for c in "hello".chars() {
+            headless
+                .key(key(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        }
+        assert!(contains(&headless.screen().unwrap(), "hello"));
+
+        let actions = headless
+            .key(key(KeyCode::Char('p'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(
+            actions.iter().any(
+                |action| matches!(action, Action::SendTextNote(content, _) if content == "hello")
+            ),
+            "{actions:?}"
+        );
+        assert_eq!(headless.app().mode, Mode::Home);
+    }
+
+    #[rstest]
+    fn test_zen_mode() {
+        let mut headless = headless();
+        let status_bar = headless.screen().unwrap()[22].clone();
+        assert!(!status_bar.is_empty());
+
+        headless
+            .key(key(KeyCode::Char('z'), KeyModifiers::ALT))
+            .unwrap();
+        assert!(headless.app().layout.zen);
+        assert!(!contains(&headless.screen().unwrap(), &status_bar));
+    }
+
+    #[rstest]
+    fn test_contrast() {
+        let mut config = config();
+        config.proxy = Some(String::from("socks5://127.0.0.1:9050"));
+        let mut headless = with_config(config);
+        let lines = headless.screen().unwrap();
+
+        // The status bar draws the proxy in magenta on black, which is slightly below 4.5:1
+        let row = lines
+            .iter()
+            .position(|line| line.contains("[Proxy]"))
+            .unwrap();
+        let column = lines[row].find("[Proxy]").unwrap() as u16;
+        let cell = headless.buffer().get(column, row as u16);
+        assert_eq!(
+            (cell.fg, cell.bg),
+            (Color::Rgb(255, 255, 255), Color::Black)
+        );
+        let actions = headless.settle().unwrap();
+        assert!(
+            actions
+                .iter()
+                .any(|action| matches!(action, Action::SystemWarning(_))),
+            "{actions:?}"
+        );
+
+        let actions = headless
+            .key(key(KeyCode::Char('T'), KeyModifiers::SHIFT))
+            .unwrap();
+        let report = actions.iter().find_map(|action| match action {
+            Action::ShowReport(_, lines) => Some(lines),
+            _ => None,
+        });
+        assert!(
+            report.is_some_and(|lines| contains(lines, "Magenta on Black")),
+            "{report:?}"
+        );
+    }
+
+    #[rstest]
+    fn test_quit() {
+        let mut headless = headless();
+        headless
+            .key(key(KeyCode::Char('q'), KeyModifiers::NONE))
+            .unwrap();
+        assert!(headless.app().should_quit);
+    }
+}
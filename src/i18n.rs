@@ -0,0 +1,155 @@
+mod catalog;
+mod plural;
+
+use std::{fmt::Display, sync::RwLock};
+
+use lazy_static::lazy_static;
+pub use plural::{format_plural, PluralCategory, PluralPattern};
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    // Tests expect English messages regardless of the environment
+    static ref LOCALE: RwLock<Locale> = RwLock::new(if cfg!(test) {
+        Locale::En
+    } else {
+        Locale::from_env()
+    });
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+impl Locale {
+    /// Parses POSIX locale names such as `ja_JP.UTF-8` or BCP 47 tags such as `en-US`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let language = s
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Self::En),
+            "ja" => Some(Self::Ja),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+pub fn locale() -> Locale {
+    *LOCALE.read().expect("failed to read locale")
+}
+
+pub fn set_locale(locale: Locale) {
+    *LOCALE.write().expect("failed to write locale") = locale;
+}
+
+/// Translates a message into the current locale, falling back to the message itself.
+pub fn tr(message: &str) -> String {
+    translate(locale(), message).to_owned()
+}
+
+/// Translates a message and fills its `{name}` placeholders with `args`.
+pub fn tr_args(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    interpolate(translate(locale(), message), args)
+}
+
+/// Translates a plural pattern and formats it with the current locale.
+///
+/// Patterns in the catalog are parsed once, and the others each time.
+pub fn plural(pattern: &str, count: u64) -> String {
+    let locale = locale();
+    match catalog::lookup_plural(locale, pattern) {
+        Some(parsed) => parsed.format(locale, count),
+        None => format_plural(locale, translate(locale, pattern), count),
+    }
+}
+
+fn translate(locale: Locale, message: &str) -> &str {
+    catalog::lookup(locale, message).unwrap_or(message)
+}
+
+/// Replaces `{name}` placeholders in a single pass, so that arguments containing braces are kept as is.
+/// Unknown placeholders are left untouched.
+fn interpolate(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = String::new();
+    let mut rest = message;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + end];
+        match args.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => result.push_str(&value.to_string()),
+            None => result.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("en", Some(Locale::En))]
+    #[case("en_US.UTF-8", Some(Locale::En))]
+    #[case("ja_JP.UTF-8", Some(Locale::Ja))]
+    #[case("ja-JP", Some(Locale::Ja))]
+    #[case("C", None)]
+    #[case("", None)]
+    fn test_locale_parse(#[case] s: &str, #[case] expected: Option<Locale>) {
+        assert_eq!(Locale::parse(s), expected);
+    }
+
+    #[rstest]
+    #[case(Locale::En, "Thread {id}", "Thread abc")]
+    #[case(Locale::Ja, "Thread {id}", "スレッド abc")]
+    #[case(Locale::Ja, "Unknown {id}", "Unknown abc")]
+    fn test_translate(#[case] locale: Locale, #[case] message: &str, #[case] expected: &str) {
+        assert_eq!(
+            interpolate(translate(locale, message), &[("id", &"abc")]),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_interpolate() {
+        // Arguments are not interpolated again
+        assert_eq!(
+            interpolate("[Posted] {content}", &[("content", &"{note}")]),
+            "[Posted] {note}"
+        );
+        assert_eq!(interpolate("{a}/{b}", &[("a", &1), ("b", &2)]), "1/2");
+        assert_eq!(interpolate("{missing} {", &[]), "{missing} {");
+    }
+
+    #[rstest]
+    fn test_locale_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Locale>("\"ja\"").unwrap(),
+            Locale::Ja
+        );
+    }
+}
@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use super::{Locale, PluralPattern};
+
+/// Japanese translations keyed by the English messages.
+///
+/// Messages may contain `{name}` placeholders, which are filled by [`super::tr_args`],
+/// and plural arguments, which are formatted by [`super::plural`].
+const JA: &[(&str, &str)] = &[
+    // App
+    ("Failed to draw: {error}", "描画に失敗しました: {error}"),
+    ("Theme check", "テーマのチェック"),
+    (
+        "Cannot open the clipboard: {error}",
+        "クリップボードを開けません: {error}",
+    ),
+    (
+        "{count, plural, one {# plugin} other {# plugins}} failed to load: see the log",
+        "{count, plural, other {#個のプラグイン}}の読み込みに失敗しました: ログを確認してください",
+    ),
+    (
+        "[Plugins] {count, plural, one {# plugin} other {# plugins}} loaded",
+        "[プラグイン] {count, plural, other {#個のプラグイン}}を読み込みました",
+    ),
+    (
+        "{count, plural, one {# color pair is} other {# color pairs are}} below the minimum contrast ratio: see the theme check",
+        "{count, plural, other {#組の色}}のコントラスト比が最小値を下回っています: テーマのチェックを確認してください",
+    ),
+    (
+        "The contrast check is off",
+        "コントラストのチェックはオフです",
+    ),
+    (
+        "All colors drawn so far meet the minimum contrast ratio of {ratio}:1",
+        "これまでに描画したすべての色が最小コントラスト比 {ratio}:1 を満たしています",
+    ),
+    (
+        "Colors drawn below the minimum contrast ratio of {ratio}:1",
+        "最小コントラスト比 {ratio}:1 を下回って描画された色",
+    ),
+    (
+        "[Do not disturb] Notifications and sounds are off",
+        "[おやすみモード] 通知と音をオフにしました",
+    ),
+    (
+        "[Do not disturb] Notifications and sounds are on",
+        "[おやすみモード] 通知と音をオンにしました",
+    ),
+    (
+        "Notifications need privatekey in the config",
+        "通知には設定の privatekey が必要です",
+    ),
+    (
+        "Browse mode is read-only: set privatekey in the config to publish",
+        "閲覧モードは読み取り専用です: 投稿するには設定に privatekey を追加してください",
+    ),
+    (
+        "Browse mode is read-only: set privatekey in the config to post",
+        "閲覧モードは読み取り専用です: 投稿するには設定に privatekey を追加してください",
+    ),
+    ("[Liked] {note}", "[いいね] {note}"),
+    ("[Reaction removed]", "[リアクションを取り消しました]"),
+    ("[Note deleted]", "[投稿を削除しました]"),
+    ("[Copied] {text}", "[コピー] {text}"),
+    ("Cannot edit the note: {error}", "投稿を編集できません: {error}"),
+    ("[Diagnostics] Checking the relays...", "[診断] リレーを確認しています..."),
+    ("Diagnostics", "診断"),
+    ("skipped, as onion relays need a proxy", "onion リレーにはプロキシが必要なためスキップしました"),
+    ("loaded from {dir}", "{dir} から読み込みました"),
+    (
+        "{npub}, stored as plaintext in the config",
+        "{npub} (設定ファイルに平文で保存されています)",
+    ),
+    (
+        "no private key, so nostui starts in browse mode",
+        "秘密鍵がないため閲覧モードで起動します",
+    ),
+    ("the private key is not loaded", "秘密鍵が読み込まれていません"),
+    ("not a relay URL", "リレーの URL ではありません"),
+    ("connected in {ms} ms", "{ms} ms で接続しました"),
+    ("cannot connect", "接続できません"),
+    (
+        "no relay information (NIP-11): {error}",
+        "リレー情報 (NIP-11) がありません: {error}",
+    ),
+    (
+        " {count, plural, one {[Alt-m] mention # pasted id} other {[Alt-m] mention # pasted ids}} ",
+        " {count, plural, other {[Alt-m] 貼り付けた#個のIDをメンションにする}} ",
+    ),
+    (
+        " {count, plural, one {✓ # pasted id is mentioned} other {✓ # pasted ids are mentioned}} ",
+        " {count, plural, other {✓ 貼り付けた#個のIDをメンションします}} ",
+    ),
+    (
+        "Cannot copy to the clipboard: {error}",
+        "クリップボードにコピーできません: {error}",
+    ),
+    ("Actions: [enter] run [esc] close", "操作: [enter] 実行 [esc] 閉じる"),
+    (
+        "The config file is from an older version of nostui. Save the upgraded file? Comments are not kept",
+        "設定ファイルは古いバージョンのnostuiのものです。更新したファイルを保存しますか？コメントは保持されません",
+    ),
+    (
+        "[Config] Upgraded {path}, the original is kept as {backup}",
+        "[設定] {path} を更新しました。元のファイルは {backup} に保存されています",
+    ),
+    (
+        "Cannot save the config file: {error}",
+        "設定ファイルを保存できません: {error}",
+    ),
+    ("Reply", "返信"),
+    ("React", "リアクション"),
+    ("Remove my reaction", "リアクションを取り消す"),
+    ("Repost", "リポスト"),
+    ("Reveal or hide the content", "内容を表示・非表示"),
+    (
+        "Show reactions, reposts and zaps",
+        "リアクション・リポスト・Zapを表示",
+    ),
+    ("Translate", "翻訳"),
+    ("Hide the translation", "翻訳を非表示"),
+    ("Preview the links", "リンクをプレビュー"),
+    ("Open the file", "ファイルを開く"),
+    ("Copy the file URL", "ファイルのURLをコピー"),
+    ("Cannot open {url}: {error}", "{url} を開けません: {error}"),
+    ("Hide the link previews", "リンクのプレビューを非表示"),
+    ("No links in the note", "ノートにリンクがありません"),
+    ("loading…", "読み込み中…"),
+    ("Open the author's timeline", "投稿者のタイムラインを開く"),
+    ("Show the author's followers", "投稿者のフォロワーを表示"),
+    (
+        "Followers of {name}: {count} so far, fetching…",
+        "{name} のフォロワー: これまでに{count}人、取得中…",
+    ),
+    (
+        "Followers of {name}: about {count} [enter] open [esc] close",
+        "{name} のフォロワー: 約{count}人 [enter] 開く [esc] 閉じる",
+    ),
+    (
+        "Asking relays for the contact lists which follow the account…",
+        "このアカウントをフォローしているコンタクトリストをリレーに問い合わせています…",
+    ),
+    ("No followers found on the relays", "リレーにフォロワーが見つかりません"),
+    ("Copy the note ID", "投稿のIDをコピー"),
+    ("Copy the author's npub", "投稿者のnpubをコピー"),
+    ("Export the thread", "スレッドをエクスポート"),
+    ("Broadcast to my relays", "自分のリレーにブロードキャスト"),
+    ("No relays to write to", "書き込み先のリレーがありません"),
+    (
+        "{count, plural, one {# relay} other {# relays}}",
+        "{count, plural, other {#個のリレー}}",
+    ),
+    (
+        "Broadcast the note by {name} to {relays}?",
+        "{name} のノートを{relays}にブロードキャストしますか？",
+    ),
+    (
+        "[Broadcasting] to {count, plural, one {# relay} other {# relays}}: press Shift-o for the results",
+        "[ブロードキャスト中] {count, plural, other {#個のリレー}}へ: Shift-oで結果を表示",
+    ),
+    ("Follow the author", "投稿者をフォロー"),
+    ("Unfollow the author", "投稿者のフォローを解除"),
+    ("Unfollow {name}?", "{name} のフォローを解除しますか?"),
+    ("Mute the author", "投稿者をミュート"),
+    ("Mute the author for an hour", "投稿者を1時間ミュート"),
+    ("Mute the author for this session", "投稿者をこのセッションの間ミュート"),
+    ("[Muted until {time}] {name}", "[{time}までミュート] {name}"),
+    ("[Muted for this session] {name}", "[このセッションの間ミュート] {name}"),
+    ("[Unmuted] {name}", "[ミュート解除] {name}"),
+    ("▸ Muted a note from {name} for now", "▸ 一時的にミュート中の{name}の投稿"),
+    ("Delete the note", "投稿を削除"),
+    (
+        "Delete this note? Relays may keep it.",
+        "この投稿を削除しますか？リレーには残る場合があります。",
+    ),
+    ("You have not reacted to the note", "この投稿にはリアクションしていません"),
+    ("[Reposted] {note}", "[リポスト] {note}"),
+    ("[Posted] {content}", "[投稿] {content}"),
+    ("[Sent to the channel] {content}", "[チャンネルに送信] {content}"),
+    (
+        "[Mining] PoW difficulty {difficulty}",
+        "[マイニング中] PoW 難易度 {difficulty}",
+    ),
+    (
+        "Failed to mine PoW: {error}",
+        "PoW のマイニングに失敗しました: {error}",
+    ),
+    (
+        "[Retried] {count, plural, one {# relay} other {# relays}}",
+        "[再送信] {count, plural, other {#個のリレー}}",
+    ),
+    ("[Profile updated]", "[プロフィール更新]"),
+    // Notifications
+    ("Mentioned", "メンション"),
+    ("Replied", "返信"),
+    ("Direct message", "ダイレクトメッセージ"),
+    ("Zapped", "Zap"),
+    ("From {author}", "{author} から"),
+    ("Zap received", "Zap を受け取りました"),
+    // Author review
+    ("[Whitelisted] {name}", "[ホワイトリスト] {name}"),
+    ("[Muted] {name}", "[ミュート] {name}"),
+    (
+        "Collapsed authors: [w]hitelist [m]ute [esc] close",
+        "折りたたまれたユーザー: [w]ホワイトリスト [m]ミュート [esc]閉じる",
+    ),
+    ("No collapsed authors", "折りたたまれたユーザーはいません"),
+    (
+        "{count, plural, one {# reply} other {# replies}}",
+        "{count, plural, other {#件の返信}}",
+    ),
+    (" (muted)", " (ミュート中)"),
+    // Confirm dialog
+    ("Confirm", "確認"),
+    (" [Y]es ", " [Y]はい "),
+    (" [N]o ", " [N]いいえ "),
+    // Home
+    (
+        "[Spam filter] Showing filtered notes, including {notes}",
+        "[スパムフィルター] {notes}を含め、フィルターされた投稿を表示しています",
+    ),
+    (
+        "{count, plural, one {# hidden note} other {# hidden notes}}",
+        "{count, plural, other {#件の非表示の投稿}}",
+    ),
+    (
+        "[Spam filter] Filtering notes",
+        "[スパムフィルター] 投稿をフィルターしています",
+    ),
+    (
+        "No reactions, reposts or zaps yet",
+        "まだリアクション、リポスト、Zap はありません",
+    ),
+    (
+        "{count, plural, one {# Like} other {# Likes}}",
+        "{count, plural, other {#件のいいね}}",
+    ),
+    (
+        "{count, plural, one {# Repost} other {# Reposts}}",
+        "{count, plural, other {#件のリポスト}}",
+    ),
+    ("Anonymous", "匿名"),
+    ("Engagement", "反応"),
+    (
+        "Failed to save author lists: {error}",
+        "ユーザーリストの保存に失敗しました: {error}",
+    ),
+    ("Thread", "スレッド"),
+    ("{count} new", "新着{count}件"),
+    (
+        "Select a note to show its thread",
+        "ノートを選択するとスレッドを表示します",
+    ),
+    (
+        "[Exported] {notes} to {path}",
+        "[エクスポート] {notes}を {path} に書き出しました",
+    ),
+    (
+        "{count, plural, one {# note} other {# notes}}",
+        "{count, plural, other {#件の投稿}}",
+    ),
+    (
+        "Failed to export to {path}: {error}",
+        "{path} へのエクスポートに失敗しました: {error}",
+    ),
+    (
+        "Repost the note by {name}?",
+        "{name} の投稿をリポストしますか?",
+    ),
+    (
+        "Discard the unsent note and quit?",
+        "未送信の投稿を破棄して終了しますか?",
+    ),
+    (
+        "Export the tab to (.json or .md)",
+        "タブのエクスポート先 (.json または .md)",
+    ),
+    (
+        "Export the thread to (.json or .md)",
+        "スレッドのエクスポート先 (.json または .md)",
+    ),
+    (
+        "Go to date (2024-01-01, 2024-01-01 15:00 or 3d ago)",
+        "移動先の日時 (2024-01-01、2024-01-01 15:00 または 3d で3日前)",
+    ),
+    ("Invalid date: {input}", "日時が正しくありません: {input}"),
+    ("Attach a file (path)", "添付するファイルのパス"),
+    (
+        "Set media.server in the config to attach files",
+        "ファイルを添付するには設定で media.server を指定してください",
+    ),
+    ("[Uploading] {file} to {server}", "[アップロード中] {file} を {server} へ"),
+    ("[Uploaded] {url}", "[アップロード完了] {url}"),
+    (
+        "Failed to upload {file}: {error}",
+        "{file} のアップロードに失敗しました: {error}",
+    ),
+    (
+        "Publish hook failed, so its tags are not used: {error}",
+        "公開フックが失敗したため、そのタグは使いません: {error}",
+    ),
+    (
+        "Skipped onion relays, which need a proxy: {relays}",
+        "プロキシが必要な onion リレーには接続しません: {relays}",
+    ),
+    ("── New since last visit ──", "── 前回の終了以降の投稿 ──"),
+    ("{raised}/{goal} sats", "{raised}/{goal} sats"),
+    ("(closed)", "(終了)"),
+    ("Zaps are split among", "Zapの分配先"),
+    ("[Repost removed]", "[リポストを削除]"),
+    (
+        "Browse mode has no activity: set privatekey in the config",
+        "閲覧モードにはアクティビティがありません: 設定で privatekey を指定してください",
+    ),
+    (
+        "Delete your reaction {reaction} to {target}?",
+        "{target} への {reaction} のリアクションを削除しますか?",
+    ),
+    ("Delete your repost of {target}?", "{target} のリポストを削除しますか?"),
+    ("Delete your reply to {target}?", "{target} への返信を削除しますか?"),
+    ("My activity: [d] undo [esc] close", "アクティビティ: [d]取り消し [esc]閉じる"),
+    (
+        "No reactions, reposts or replies yet",
+        "まだリアクション、リポスト、返信はありません",
+    ),
+    (
+        "┄ Notes may be missing here: press R to fill the gap ┄",
+        "┄ ここに未取得のノートがあるかもしれません: R で取得 ┄",
+    ),
+    (
+        "Replying to {name}: Press ESC to close",
+        "{name} に返信: ESC で閉じる",
+    ),
+    ("New note: Press ESC to close", "新規投稿: ESC で閉じる"),
+    (
+        "Message to {channel}: Press ESC to close",
+        "{channel}へのメッセージ: ESC で閉じる",
+    ),
+    (" chars: {chars} · bytes: {bytes} ", " 文字数: {chars} · バイト数: {bytes} "),
+    (
+        " chars: {chars} · bytes: {bytes}/{limit} ",
+        " 文字数: {chars} · バイト数: {bytes}/{limit} ",
+    ),
+    (
+        "The note is {bytes} bytes, over the limit of {limit}, and some relays may reject it. Send anyway?",
+        "投稿が {bytes} バイトで上限の {limit} を超えており、リレーに拒否される可能性があります。送信しますか？",
+    ),
+    ("To: ", "宛先: "),
+    (" [space] remove or add [enter] done", " [space] 除外/追加 [enter] 完了"),
+    (" [Ctrl-t] edit", " [Ctrl-t] 編集"),
+    ("[tab] complete [esc] cancel", "[tab] 補完 [esc] キャンセル"),
+    ("custom", "カスタム"),
+    // Profile editor
+    (
+        "Loading your profile: try again in a moment, or start a new one",
+        "プロフィールを読み込んでいます: 少し待ってからもう一度試すか、新しく作成してください",
+    ),
+    ("Nothing to update", "変更はありません"),
+    (
+        "Edit profile: [tab] next field [Ctrl-s] save [esc] cancel",
+        "プロフィールの編集: [tab]次の項目 [Ctrl-s]保存 [esc]キャンセル",
+    ),
+    (
+        "Picture must be an http or https URL",
+        "picture には http または https の URL を入力してください",
+    ),
+    (
+        "NIP-05 must be like name@example.com",
+        "NIP-05 は name@example.com の形式で入力してください",
+    ),
+    (
+        "Lightning address must be like name@example.com",
+        "ライトニングアドレスは name@example.com の形式で入力してください",
+    ),
+    // Discover view
+    (
+        "Your contact list has not been loaded yet",
+        "フォローリストがまだ読み込まれていません",
+    ),
+    ("[Followed] {name}", "[フォロー] {name}"),
+    ("[Unfollowed] {name}", "[フォロー解除] {name}"),
+    (
+        "Discover: [f]ollow or unfollow [enter] open timeline [esc] close",
+        "おすすめ: [f]フォロー/解除 [enter]タイムラインを開く [esc]閉じる",
+    ),
+    (
+        "No suggestions yet: contact lists of your followings are being fetched",
+        "おすすめはまだありません: フォロー中のユーザーのフォローリストを取得しています",
+    ),
+    (
+        " followed by {count} you follow",
+        " フォロー中の{count}人がフォロー",
+    ),
+    (" (following)", " (フォロー中)"),
+    // Translation
+    ("⇄ translating…", "⇄ 翻訳中…"),
+    ("⇄ translated from {language}", "⇄ {language}から翻訳"),
+    ("⇄ translated", "⇄ 翻訳"),
+    ("Cannot translate the note: {error}", "投稿を翻訳できません: {error}"),
+    (
+        "set translation.url in the config",
+        "設定ファイルにtranslation.urlを設定してください",
+    ),
+    // Relay information
+    ("Relays: [esc] close", "リレー: [esc]閉じる"),
+    (" fetching the relay information", " リレー情報を取得中"),
+    ("sending…", "送信中…"),
+    ("Dated in the future: {date}", "未来の日時: {date}"),
+    (" ⏳ expires in {time}", " ⏳ あと {time} で期限切れ"),
+    (" ⌛ expired", " ⌛ 期限切れ"),
+    (" ⏳ expires after {time} ", " ⏳ {time} 後に期限切れ "),
+    (
+        "Expire the note after (30m, 12h, 3d or 2w; empty for never)",
+        "投稿の有効期限 (30m, 12h, 3d, 2w、空欄で無期限)",
+    ),
+    ("Invalid duration: {input}", "無効な期間です: {input}"),
+    ("Event kinds by relay: [esc] close", "リレー別のイベント種別: [esc]閉じる"),
+    (
+        "  {events} events, {share}% of unused kinds",
+        "  {events} 件、未使用の種別が {share}%",
+    ),
+    (
+        "  Mostly kinds nostui doesn't use: consider removing the relay",
+        "  ほとんどが nostui で使わない種別です: リレーの削除を検討してください",
+    ),
+    ("No events received yet", "まだイベントを受信していません"),
+    ("-- NORMAL --", "-- ノーマル --"),
+    ("-- INSERT --", "-- 挿入 --"),
+    ("-- VISUAL --", "-- ビジュアル --"),
+    ("✓ sent", "✓ 送信済み"),
+    ("✗ failed: see the outbox", "✗ 送信失敗: 送信履歴を確認"),
+    (
+        "Subscriptions ({count}): [d] close the subscription [esc] close",
+        "購読 ({count}): [d]購読を閉じる [esc]閉じる",
+    ),
+    ("No tab", "タブなし"),
+    (
+        "  {events} events, open on {relays} relays",
+        "  {events} 件のイベント、{relays} 個のリレーで購読中",
+    ),
+    (
+        "  Doesn't support NIPs which nostui needs: {nips}",
+        "  nostuiに必要なNIPに対応していません: {nips}",
+    ),
+    // Outbox
+    ("No relays to retry", "再送信するリレーはありません"),
+    (
+        "Outbox: [r]etry rejected relays [esc] close",
+        "送信履歴: [r]拒否したリレーに再送信 [esc]閉じる",
+    ),
+    ("Nothing has been published yet", "まだ何も投稿していません"),
+    (" accepted", " 件受理"),
+    (", {count} rejected", "、{count} 件拒否"),
+    ("waiting", "待機中"),
+    ("accepted", "受理"),
+    (
+        "[Sent] {accepted} of {total} relays accepted the note",
+        "[送信] {total} 個中 {accepted} 個のリレーが投稿を受理しました",
+    ),
+    (
+        "[Sent] {accepted} of {total} relays accepted the note, {rejected} rejected: press Shift-o for details",
+        "[送信] {total} 個中 {accepted} 個のリレーが投稿を受理し、{rejected} 個が拒否しました: Shift-o で詳細を表示",
+    ),
+    // Status bar
+    ("Browse mode (read-only)", "閲覧モード (読み取り専用)"),
+    (
+        "Messages: Press Shift-m to close",
+        "メッセージ: Shift-m で閉じる",
+    ),
+    ("Loading...", "読み込み中..."),
+    // Stats overlay
+    ("Render ", "描画 "),
+    ("Subscriptions ", "購読 "),
+    ("Queue ", "キュー "),
+    (
+        "{actions} actions, {notifications} notifications",
+        "アクション {actions} 件、通知 {notifications} 件",
+    ),
+    ("Note store ", "イベント "),
+    ("{count} events, about {size}", "{count} 件、約 {size}"),
+    ("Relay", "リレー"),
+    (
+        "Stats: Press Shift-i to close",
+        "統計: Shift-i で閉じる",
+    ),
+    // Log viewer
+    (
+        "Log ({level} and above): [l]evel [c]lear [esc] close",
+        "ログ ({level} 以上): [l]レベル [c]クリア [esc]閉じる",
+    ),
+    // Popups
+    ("{title}: Press ESC to close", "{title}: ESC で閉じる"),
+    ("{title}: Press ESC to cancel", "{title}: ESC でキャンセル"),
+    (
+        "Tracing is off: start nostui with --trace",
+        "トレースはオフです: --trace を付けて nostui を起動してください",
+    ),
+    (
+        "Trace{filter}: [/] filter [c]lear [esc] close",
+        "トレース{filter}: [/]絞り込み [c]クリア [esc]閉じる",
+    ),
+    // Text notes
+    ("CW", "注意"),
+    ("CW: {reason}", "注意: {reason}"),
+    ("{warning} — press x to reveal", "{warning} — x で表示"),
+    (
+        "… {count, plural, one {# more line} other {# more lines}}: press m to expand",
+        "… 残り{count, plural, other {#行}}: m で展開",
+    ),
+    ("▴ press m to collapse", "▴ m で折りたたむ"),
+    (
+        "▸ Filtered a note from {name} (score {score})",
+        "▸ {name} の投稿をフィルターしました (スコア {score})",
+    ),
+    (
+        "▸ Collapsed a reply from {name}",
+        "▸ {name} からの返信を折りたたみました",
+    ),
+    ("↳ replying to {name}", "↳ {name} への返信"),
+    (
+        "[Follow] The newest note is kept selected as new notes arrive",
+        "[追従] 新しいノートが届くと最新のノートを選択し続けます",
+    ),
+    ("[Follow] Stopped following new notes", "[追従] 新しいノートへの追従を止めました"),
+    (
+        "{count, plural, one {↑ # new note} other {↑ # new notes}}",
+        "{count, plural, other {↑ 新着#件}}",
+    ),
+    ("⟳ reposted by {names}", "⟳ {names} がリポスト"),
+    (
+        "⟳ reposted by {names} and {others}",
+        "⟳ {names} ほか{others}がリポスト",
+    ),
+    (
+        "{count, plural, one {# other} other {# others}}",
+        "{count, plural, other {#人}}",
+    ),
+    (
+        "{count, plural, one {#Like} other {#Likes}}",
+        "{count, plural, other {#いいね}}",
+    ),
+    (
+        "{count, plural, one {#Repost} other {#Reposts}}",
+        "{count, plural, other {#リポスト}}",
+    ),
+    // Tabs
+    ("Timeline", "タイムライン"),
+    ("Global", "グローバル"),
+    ("Thread {id}", "スレッド {id}"),
+    ("Channel {id}", "チャンネル {id}"),
+    (
+        "No joined channels: open one with --tab channel:<note or nevent>",
+        "参加中のチャンネルはありません: --tab channel:<note または nevent> で開けます",
+    ),
+    (
+        "Tabs: [enter] switch [esc] close",
+        "タブ: [enter] 切り替え [esc] 閉じる",
+    ),
+    // Timestamps
+    ("just now", "たった今"),
+    ("{minutes}m ago", "{minutes}分前"),
+    ("{hours}h ago", "{hours}時間前"),
+    ("{days}d ago", "{days}日前"),
+];
+
+lazy_static! {
+    static ref JA_CATALOG: HashMap<&'static str, &'static str> = JA.iter().copied().collect();
+    /// Plural patterns in every locale keyed by the English ones, parsed once as they are
+    /// formatted for each note on each frame
+    static ref PLURAL_PATTERNS: HashMap<Locale, HashMap<&'static str, PluralPattern>> = {
+        let plurals = || JA.iter().filter(|(en, _)| en.contains("plural"));
+        HashMap::from([
+            (Locale::En, plurals().map(|(en, _)| (*en, PluralPattern::parse(en))).collect()),
+            (Locale::Ja, plurals().map(|(en, ja)| (*en, PluralPattern::parse(ja))).collect()),
+        ])
+    };
+}
+
+/// Looks up the translation of a message. English messages are the keys themselves.
+pub fn lookup(locale: Locale, message: &str) -> Option<&'static str> {
+    match locale {
+        Locale::En => None,
+        Locale::Ja => JA_CATALOG.get(message).copied(),
+    }
+}
+
+/// Looks up the parsed translation of a plural pattern.
+pub fn lookup_plural(locale: Locale, pattern: &str) -> Option<&'static PluralPattern> {
+    PLURAL_PATTERNS.get(&locale)?.get(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    /// Names of `{name}` placeholders, ignoring plural arguments.
+    fn placeholders(message: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = message
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+            .filter(|name| !name.contains([',', ' ', '#']))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[rstest]
+    fn test_ja_placeholders() {
+        for (en, ja) in JA {
+            assert_eq!(placeholders(en), placeholders(ja), "{en}");
+            assert_eq!(en.contains("plural"), ja.contains("plural"), "{en}");
+        }
+    }
+
+    #[rstest]
+    fn test_ja_unique() {
+        assert_eq!(JA_CATALOG.len(), JA.len());
+    }
+
+    #[rstest]
+    #[case(Locale::En, "Timeline", None)]
+    #[case(Locale::Ja, "Timeline", Some("タイムライン"))]
+    #[case(Locale::Ja, "Unknown message", None)]
+    fn test_lookup(#[case] locale: Locale, #[case] message: &str, #[case] expected: Option<&str>) {
+        assert_eq!(lookup(locale, message), expected);
+    }
+
+    #[rstest]
+    #[case(
+        Locale::En,
+        "{count, plural, one {#Like} other {#Likes}}",
+        Some("1Like")
+    )]
+    #[case(
+        Locale::Ja,
+        "{count, plural, one {#Like} other {#Likes}}",
+        Some("1いいね")
+    )]
+    #[case(Locale::Ja, "Timeline", None)]
+    fn test_lookup_plural(
+        #[case] locale: Locale,
+        #[case] pattern: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let formatted = lookup_plural(locale, pattern).map(|pattern| pattern.format(locale, 1));
+        assert_eq!(formatted.as_deref(), expected);
+    }
+}
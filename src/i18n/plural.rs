@@ -0,0 +1,230 @@
+use thousands::Separable;
+
+use super::Locale;
+
+/// CLDR plural categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn of(locale: Locale, n: u64) -> Self {
+        match locale {
+            Locale::En if n == 1 => Self::One,
+            Locale::En => Self::Other,
+            // Japanese has no grammatical number
+            Locale::Ja => Self::Other,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Formats an ICU MessageFormat-style pattern containing plural arguments,
+/// e.g. `{count, plural, =0 {No likes} one {# Like} other {# Likes}}`.
+///
+/// `#` inside the selected message is replaced with the count.
+/// Exact matches (`=N`) take precedence over plural categories, and `other` is used as a fallback.
+pub fn format_plural(locale: Locale, pattern: &str, count: u64) -> String {
+    PluralPattern::parse(pattern).format(locale, count)
+}
+
+/// A pattern with plural arguments, parsed once so that it is formatted for each count without
+/// scanning it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluralPattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Text kept as is, including braces which are not plural arguments
+    Text(String),
+    /// Selectors and messages of the cases in the order of the pattern, and the argument itself
+    /// which is kept if no case matches
+    Plural {
+        cases: Vec<(String, String)>,
+        raw: String,
+    },
+}
+
+impl PluralPattern {
+    pub fn parse(pattern: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = find_closing_brace(&rest[start..]) else {
+                break;
+            };
+            segments.push(Segment::Text(rest[..start].to_string()));
+            let block = &rest[start + 1..start + end];
+            let raw = rest[start..=start + end].to_string();
+            segments.push(match parse_plural_cases(block) {
+                Some(cases) => Segment::Plural { cases, raw },
+                None => Segment::Text(raw),
+            });
+            rest = &rest[start + end + 1..];
+        }
+        segments.push(Segment::Text(rest.to_string()));
+
+        Self { segments }
+    }
+
+    pub fn format(&self, locale: Locale, count: u64) -> String {
+        let category = PluralCategory::of(locale, count);
+        let exact = format!("={count}");
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Plural { cases, raw } => {
+                    match select_plural_message(cases, &exact, category) {
+                        Some(message) => message.replace('#', &count.separate_with_commas()),
+                        None => raw.clone(),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn find_closing_brace(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_plural_cases(block: &str) -> Option<Vec<(String, String)>> {
+    let mut parts = block.splitn(3, ',');
+    let (_, kind, cases) = (parts.next()?, parts.next()?, parts.next()?);
+    if kind.trim() != "plural" {
+        return None;
+    }
+
+    let mut parsed = Vec::new();
+    let mut rest = cases;
+    while let Some(start) = rest.find('{') {
+        let selector = rest[..start].trim();
+        let end = find_closing_brace(&rest[start..])?;
+        parsed.push((
+            selector.to_string(),
+            rest[start + 1..start + end].to_string(),
+        ));
+        rest = &rest[start + end + 1..];
+    }
+
+    Some(parsed)
+}
+
+fn select_plural_message<'a>(
+    cases: &'a [(String, String)],
+    exact: &str,
+    category: PluralCategory,
+) -> Option<&'a str> {
+    let mut fallback = None;
+    for (selector, message) in cases {
+        if selector == exact {
+            return Some(message);
+        }
+        // The matching category wins over `other` regardless of the order of cases
+        if selector == category.keyword() || (selector == "other" && fallback.is_none()) {
+            fallback = Some(message.as_str());
+        }
+    }
+
+    fallback
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Locale::En, 0, PluralCategory::Other)]
+    #[case(Locale::En, 1, PluralCategory::One)]
+    #[case(Locale::En, 2, PluralCategory::Other)]
+    #[case(Locale::Ja, 1, PluralCategory::Other)]
+    fn test_plural_category(
+        #[case] locale: Locale,
+        #[case] n: u64,
+        #[case] expected: PluralCategory,
+    ) {
+        assert_eq!(PluralCategory::of(locale, n), expected);
+    }
+
+    #[rstest]
+    #[case(Locale::En, 1, "1 Like")]
+    #[case(Locale::En, 2, "2 Likes")]
+    #[case(Locale::En, 0, "No likes")]
+    #[case(Locale::En, 1234, "1,234 Likes")]
+    #[case(Locale::Ja, 1, "1 Likes")]
+    fn test_format_plural(#[case] locale: Locale, #[case] count: u64, #[case] expected: &str) {
+        let pattern = "{count, plural, =0 {No likes} one {# Like} other {# Likes}}";
+        assert_eq!(format_plural(locale, pattern, count), expected);
+    }
+
+    #[rstest]
+    fn test_format_plural_with_surrounding_text() {
+        let pattern =
+            "Unsubscribing from {count, plural, one {# subscription} other {# subscriptions}}...";
+        assert_eq!(
+            format_plural(Locale::En, pattern, 3),
+            "Unsubscribing from 3 subscriptions..."
+        );
+    }
+
+    #[rstest]
+    fn test_plural_pattern() {
+        let pattern = PluralPattern::parse(
+            "{n} and {count, plural, =0 {none} other {# {unit}}}, {count, plural, few {#}}",
+        );
+        assert_eq!(
+            pattern.format(Locale::En, 0),
+            "{n} and none, {count, plural, few {#}}"
+        );
+        assert_eq!(
+            pattern.format(Locale::En, 3),
+            "{n} and 3 {unit}, {count, plural, few {#}}"
+        );
+    }
+
+    #[rstest]
+    #[case("no placeholders")]
+    #[case("{name} is not a plural argument")]
+    #[case("{broken, plural, one {#}")]
+    #[case("text before {broken")]
+    fn test_format_plural_passthrough(#[case] pattern: &str) {
+        assert_eq!(format_plural(Locale::En, pattern, 1), pattern);
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const AUTHOR_LISTS_FILE: &str = "interaction_limits.json";
+
+/// Authors reviewed by the user, which overrule the heuristic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorLists {
+    #[serde(default)]
+    pub whitelist: HashSet<PublicKey>,
+    #[serde(default)]
+    pub muted: HashSet<PublicKey>,
+}
+
+impl AuthorLists {
+    /// Loads the lists from the data directory, or returns empty lists if they were never saved.
+    pub fn load(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(AUTHOR_LISTS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        fs::create_dir_all(data_dir)?;
+        fs::write(
+            data_dir.join(AUTHOR_LISTS_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// An author whose replies are collapsed, as listed on the review screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollapsedAuthor {
+    pub pubkey: PublicKey,
+    pub name: String,
+    pub replies: usize,
+    pub muted: bool,
+}
+
+/// Collapses replies from accounts that reply to me very frequently but whom I don't follow.
+#[derive(Debug, Clone, Default)]
+pub struct InteractionLimiter {
+    /// Number of replies within `window` to collapse an author. `0` disables the heuristic.
+    threshold: usize,
+    /// Seconds to count replies in
+    window: u64,
+    followings: HashSet<PublicKey>,
+    replies: HashMap<PublicKey, HashSet<(Timestamp, EventId)>>,
+    lists: AuthorLists,
+}
+
+impl InteractionLimiter {
+    pub fn new(threshold: usize, window: u64, lists: AuthorLists) -> Self {
+        Self {
+            threshold,
+            window,
+            lists,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_followings(&mut self, followings: impl IntoIterator<Item = PublicKey>) {
+        self.followings = followings.into_iter().collect();
+    }
+
+    pub fn record_reply(&mut self, reply: &Event) {
+        self.replies
+            .entry(reply.pubkey)
+            .or_default()
+            .insert((reply.created_at, reply.id));
+    }
+
+    /// Returns the number of replies from the author within the window.
+    pub fn reply_count(&self, author: &PublicKey, now: Timestamp) -> usize {
+        let since = now.as_u64().saturating_sub(self.window);
+        self.replies.get(author).map_or(0, |replies| {
+            replies
+                .iter()
+                .filter(|(created_at, _)| created_at.as_u64() >= since)
+                .count()
+        })
+    }
+
+    pub fn is_collapsed(&self, author: &PublicKey, now: Timestamp) -> bool {
+        if self.lists.muted.contains(author) {
+            return true;
+        }
+
+        if self.threshold == 0
+            || self.lists.whitelist.contains(author)
+            || self.followings.contains(author)
+        {
+            return false;
+        }
+
+        self.reply_count(author, now) >= self.threshold
+    }
+
+    /// Returns collapsed authors with their reply counts, the noisiest first.
+    pub fn collapsed_authors(&self, now: Timestamp) -> Vec<(PublicKey, usize)> {
+        let mut authors: Vec<(PublicKey, usize)> = self
+            .replies
+            .keys()
+            .chain(self.lists.muted.iter())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|author| self.is_collapsed(author, now))
+            .map(|author| (*author, self.reply_count(author, now)))
+            .collect();
+        authors.sort_by(|(a_author, a_count), (b_author, b_count)| {
+            b_count.cmp(a_count).then(a_author.cmp(b_author))
+        });
+        authors
+    }
+
+    pub fn whitelist(&mut self, author: PublicKey) {
+        self.lists.muted.remove(&author);
+        self.lists.whitelist.insert(author);
+    }
+
+    pub fn mute(&mut self, author: PublicKey) {
+        self.lists.whitelist.remove(&author);
+        self.lists.muted.insert(author);
+    }
+
+    pub fn is_muted(&self, author: &PublicKey) -> bool {
+        self.lists.muted.contains(author)
+    }
+
+    pub fn lists(&self) -> &AuthorLists {
+        &self.lists
+    }
+}
+
+/// Authors muted for a while or until the app quits, which are only kept in memory and never
+/// published.
+#[derive(Debug, Clone, Default)]
+pub struct TemporaryMutes {
+    /// When each mute expires, or `None` to keep it for the session
+    until: HashMap<PublicKey, Option<Timestamp>>,
+}
+
+impl TemporaryMutes {
+    pub fn mute(&mut self, author: PublicKey, until: Option<Timestamp>) {
+        self.until.insert(author, until);
+    }
+
+    pub fn is_muted(&self, author: &PublicKey, now: Timestamp) -> bool {
+        self.until
+            .get(author)
+            .is_some_and(|until| until.is_none_or(|until| now < until))
+    }
+
+    /// Forgets the mutes which have expired by `now`, returning their authors.
+    pub fn expire(&mut self, now: Timestamp) -> Vec<PublicKey> {
+        let expired: Vec<PublicKey> = self
+            .until
+            .iter()
+            .filter(|(_, until)| until.is_some_and(|until| now >= until))
+            .map(|(author, _)| *author)
+            .collect();
+        for author in &expired {
+            self.until.remove(author);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn reply(keys: &Keys, created_at: u64) -> Event {
+        EventBuilder::text_note("gm", [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    fn limiter(keys: &Keys, replies: u64) -> InteractionLimiter {
+        let mut limiter = InteractionLimiter::new(3, 3600, AuthorLists::default());
+        for i in 0..replies {
+            limiter.record_reply(&reply(keys, NOW - i));
+        }
+        limiter
+    }
+
+    #[rstest]
+    #[case(2, false)]
+    #[case(3, true)]
+    #[case(10, true)]
+    fn test_is_collapsed(#[case] replies: u64, #[case] expected: bool) {
+        let keys = Keys::generate();
+        let limiter = limiter(&keys, replies);
+        assert_eq!(
+            limiter.is_collapsed(&keys.public_key(), Timestamp::from(NOW)),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_is_collapsed_outside_window() {
+        let keys = Keys::generate();
+        let limiter = limiter(&keys, 3);
+        let later = Timestamp::from(NOW + 3601);
+        assert_eq!(limiter.reply_count(&keys.public_key(), later), 0);
+        assert_eq!(limiter.is_collapsed(&keys.public_key(), later), false);
+    }
+
+    #[rstest]
+    fn test_is_collapsed_disabled() {
+        let keys = Keys::generate();
+        let mut limiter = InteractionLimiter::new(0, 3600, AuthorLists::default());
+        limiter.record_reply(&reply(&keys, NOW));
+        assert_eq!(
+            limiter.is_collapsed(&keys.public_key(), Timestamp::from(NOW)),
+            false
+        );
+    }
+
+    #[rstest]
+    fn test_is_collapsed_followings() {
+        let keys = Keys::generate();
+        let mut limiter = limiter(&keys, 5);
+        limiter.set_followings([keys.public_key()]);
+        assert_eq!(
+            limiter.is_collapsed(&keys.public_key(), Timestamp::from(NOW)),
+            false
+        );
+    }
+
+    #[rstest]
+    fn test_record_reply_ignores_duplicates() {
+        let keys = Keys::generate();
+        let mut limiter = InteractionLimiter::new(3, 3600, AuthorLists::default());
+        let event = reply(&keys, NOW);
+        limiter.record_reply(&event);
+        limiter.record_reply(&event);
+        assert_eq!(
+            limiter.reply_count(&keys.public_key(), Timestamp::from(NOW)),
+            1
+        );
+    }
+
+    #[rstest]
+    fn test_whitelist_and_mute() {
+        let keys = Keys::generate();
+        let pubkey = keys.public_key();
+        let now = Timestamp::from(NOW);
+        let mut limiter = limiter(&keys, 5);
+
+        limiter.whitelist(pubkey);
+        assert_eq!(limiter.is_collapsed(&pubkey, now), false);
+        assert_eq!(limiter.collapsed_authors(now), vec![]);
+
+        limiter.mute(pubkey);
+        assert_eq!(limiter.is_collapsed(&pubkey, now), true);
+        assert_eq!(limiter.lists().whitelist.contains(&pubkey), false);
+        assert_eq!(limiter.collapsed_authors(now), vec![(pubkey, 5)]);
+    }
+
+    #[rstest]
+    fn test_collapsed_authors() {
+        let noisy = Keys::generate();
+        let noisier = Keys::generate();
+        let quiet = Keys::generate();
+        let mut limiter = limiter(&noisy, 3);
+        for i in 0..4 {
+            limiter.record_reply(&reply(&noisier, NOW - i));
+        }
+        limiter.record_reply(&reply(&quiet, NOW));
+
+        assert_eq!(
+            limiter.collapsed_authors(Timestamp::from(NOW)),
+            vec![(noisier.public_key(), 4), (noisy.public_key(), 3)]
+        );
+    }
+
+    #[rstest]
+    fn test_temporary_mutes() {
+        let (hour, session) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let now = Timestamp::from(NOW);
+        let mut mutes = TemporaryMutes::default();
+        mutes.mute(hour, Some(Timestamp::from(NOW + 3600)));
+        mutes.mute(session, None);
+        assert_eq!(mutes.is_muted(&hour, now), true);
+        assert_eq!(mutes.is_muted(&session, now), true);
+        assert_eq!(mutes.expire(now), vec![]);
+
+        let later = Timestamp::from(NOW + 3600);
+        assert_eq!(mutes.is_muted(&hour, later), false);
+        assert_eq!(mutes.expire(later), vec![hour]);
+        assert_eq!(mutes.expire(later), vec![]);
+        assert_eq!(mutes.is_muted(&session, later), true);
+    }
+
+    #[rstest]
+    fn test_author_lists_save_and_load() {
+        let dir =
+            std::env::temp_dir().join(format!("nostui-test-{}", Keys::generate().public_key()));
+        assert_eq!(AuthorLists::load(&dir).unwrap(), AuthorLists::default());
+
+        let mut lists = AuthorLists::default();
+        lists.muted.insert(Keys::generate().public_key());
+        lists.save(&dir).unwrap();
+        assert_eq!(AuthorLists::load(&dir).unwrap(), lists);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
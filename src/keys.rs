@@ -0,0 +1,324 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use color_eyre::eyre::{eyre, Result};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal,
+};
+use lazy_static::lazy_static;
+use nostr_sdk::prelude::*;
+use zeroize::Zeroizing;
+
+/// Environment variable which contains the private key.
+pub const KEY_ENV: &str = "NOSTUI_NSEC";
+
+lazy_static! {
+    /// The key taken out of `NOSTUI_NSEC` by [`take_env_key`], until it is read
+    static ref ENV_KEY: Mutex<Option<Zeroizing<String>>> = Mutex::new(None);
+}
+
+/// Capacity of buffers which keys and passphrases are read into, so that they are not
+/// reallocated and copied before being zeroized.
+const SECRET_CAPACITY: usize = 256;
+
+/// Service and account names of the private key in the OS keyring.
+pub const KEYRING_SERVICE: &str = "nostui";
+pub const KEYRING_ACCOUNT: &str = "privatekey";
+
+/// Times to ask the passphrase of an encrypted key before giving up.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Strength of the key derivation for newly encrypted keys, as recommended by NIP-49.
+const ENCRYPTION_LOG_N: u8 = 16;
+
+/// Commands to read a password from the OS keyring.
+#[cfg(target_os = "macos")]
+const KEYRING_COMMAND: Option<&[&str]> = Some(&[
+    "security",
+    "find-generic-password",
+    "-s",
+    KEYRING_SERVICE,
+    "-a",
+    KEYRING_ACCOUNT,
+    "-w",
+]);
+#[cfg(target_os = "windows")]
+const KEYRING_COMMAND: Option<&[&str]> = None;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const KEYRING_COMMAND: Option<&[&str]> = Some(&[
+    "secret-tool",
+    "lookup",
+    "service",
+    KEYRING_SERVICE,
+    "account",
+    KEYRING_ACCOUNT,
+]);
+
+/// Where the private key is stored. The key may be an nsec, hex or a NIP-49 ncryptsec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// No key, i.e. browse mode
+    None,
+    /// `privatekey` in the config
+    Config(String),
+    /// A file which only contains the key
+    File(PathBuf),
+    /// The OS keyring, via `secret-tool` or `security`
+    Keyring,
+    /// `NOSTUI_NSEC`, which is removed at startup so that child processes don't inherit it
+    Env,
+    /// The first line of the standard input
+    Stdin,
+}
+
+impl KeySource {
+    /// Reads the stored key. Returns `None` if nothing is stored.
+    /// The key is zeroized once dropped.
+    pub fn read(&self) -> Result<Option<Zeroizing<String>>> {
+        let secret = Zeroizing::new(match self {
+            Self::None => return Ok(None),
+            Self::Config(secret) => secret.clone(),
+            Self::File(path) => fs::read_to_string(path)
+                .map_err(|e| eyre!("Failed to read the key file {}: {e}", path.display()))?,
+            Self::Keyring => read_keyring()?,
+            Self::Env => {
+                let mut secret = ENV_KEY
+                    .lock()
+                    .map_err(|_| eyre!("Failed to read {KEY_ENV}"))?
+                    .take()
+                    .ok_or_else(|| eyre!("{KEY_ENV} was already read"))?;
+                std::mem::take(&mut *secret)
+            }
+            Self::Stdin => {
+                let mut secret = String::with_capacity(SECRET_CAPACITY);
+                io::stdin()
+                    .lock()
+                    .read_line(&mut secret)
+                    .map_err(|e| eyre!("Failed to read the key from stdin: {e}"))?;
+                secret
+            }
+        });
+
+        let trimmed = secret.trim();
+        Ok((!trimmed.is_empty()).then(|| Zeroizing::new(trimmed.to_owned())))
+    }
+}
+
+/// Moves `NOSTUI_NSEC` out of the environment. As changing the environment is not thread-safe,
+/// this must be called before other threads start, e.g. the async runtime.
+pub fn take_env_key() {
+    let Some(secret) = env::var_os(KEY_ENV) else {
+        return;
+    };
+    env::remove_var(KEY_ENV);
+    if let Ok(mut key) = ENV_KEY.lock() {
+        *key = Some(Zeroizing::new(secret.to_string_lossy().into_owned()));
+    }
+}
+
+/// Returns whether `NOSTUI_NSEC` was set at startup and its key is not read yet.
+pub fn has_env_key() -> bool {
+    ENV_KEY.lock().is_ok_and(|key| key.is_some())
+}
+
+fn read_keyring() -> Result<String> {
+    let command =
+        KEYRING_COMMAND.ok_or_else(|| eyre!("The OS keyring is not supported on this platform"))?;
+    let output = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| eyre!("Failed to run {}: {e}", command[0]))?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "No private key is stored in the OS keyring for service {KEYRING_SERVICE} and account {KEYRING_ACCOUNT}"
+        ));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Asks the passphrase of an encrypted key.
+pub trait PassphrasePrompt {
+    fn ask(&mut self, message: &str) -> Result<String>;
+}
+
+/// Loads the keys from the source, asking the passphrase if the key is encrypted.
+/// Returns `None` in browse mode.
+pub fn load(source: &KeySource, prompt: &mut dyn PassphrasePrompt) -> Result<Option<Keys>> {
+    let Some(secret) = source.read()? else {
+        return Ok(None);
+    };
+    if !secret.starts_with("ncryptsec1") {
+        return Ok(Some(Keys::parse(secret.as_str())?));
+    }
+
+    let encrypted = EncryptedSecretKey::from_bech32(secret.as_str())?;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let message = if attempt == 1 {
+            "Passphrase of the private key: "
+        } else {
+            "Wrong passphrase, try again: "
+        };
+        let passphrase = Zeroizing::new(prompt.ask(message)?);
+        if let Ok(secret_key) = encrypted.to_secret_key(passphrase.as_str()) {
+            return Ok(Some(Keys::new(secret_key)));
+        }
+    }
+
+    Err(eyre!("Failed to decrypt the private key: wrong passphrase"))
+}
+
+/// Encrypts the keys with a passphrase into an ncryptsec (NIP-49).
+pub fn encrypt(keys: &Keys, passphrase: &str) -> Result<String> {
+    let encrypted = EncryptedSecretKey::new(
+        keys.secret_key()?,
+        passphrase,
+        ENCRYPTION_LOG_N,
+        KeySecurity::Medium,
+    )?;
+    Ok(encrypted.to_bech32()?)
+}
+
+/// Reads a passphrase from the terminal without echoing it.
+pub struct TerminalPrompt;
+
+impl PassphrasePrompt for TerminalPrompt {
+    fn ask(&mut self, message: &str) -> Result<String> {
+        let mut stderr = io::stderr();
+        write!(stderr, "{message}")?;
+        stderr.flush()?;
+
+        terminal::enable_raw_mode()?;
+        let passphrase = read_hidden();
+        terminal::disable_raw_mode()?;
+        writeln!(stderr)?;
+        passphrase
+    }
+}
+
+fn read_hidden() -> Result<String> {
+    let mut passphrase = String::with_capacity(SECRET_CAPACITY);
+    loop {
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        match code {
+            KeyCode::Enter => return Ok(passphrase),
+            KeyCode::Esc => return Err(eyre!("Cancelled")),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Err(eyre!("Cancelled"));
+            }
+            KeyCode::Backspace => {
+                passphrase.pop();
+            }
+            KeyCode::Char(c) => passphrase.push(c),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    /// Answers the passphrases in order.
+    struct Answers(Vec<&'static str>);
+
+    impl PassphrasePrompt for Answers {
+        fn ask(&mut self, _message: &str) -> Result<String> {
+            if self.0.is_empty() {
+                return Err(eyre!("No more answers"));
+            }
+            Ok(self.0.remove(0).to_owned())
+        }
+    }
+
+    /// Encrypts with a weak derivation, which is fast enough for tests.
+    fn ncryptsec(keys: &Keys, passphrase: &str) -> String {
+        EncryptedSecretKey::new(
+            keys.secret_key().unwrap(),
+            passphrase,
+            4,
+            KeySecurity::Medium,
+        )
+        .unwrap()
+        .to_bech32()
+        .unwrap()
+    }
+
+    #[rstest]
+    fn test_load_plain() {
+        let keys = Keys::generate();
+        let nsec = keys.secret_key().unwrap().to_bech32().unwrap();
+        let source = KeySource::Config(format!("{nsec}\n"));
+        let loaded = load(&source, &mut Answers(vec![])).unwrap().unwrap();
+        assert_eq!(loaded.public_key(), keys.public_key());
+
+        assert_eq!(load(&KeySource::None, &mut Answers(vec![])).unwrap(), None);
+        let empty = KeySource::Config(String::new());
+        assert_eq!(load(&empty, &mut Answers(vec![])).unwrap(), None);
+    }
+
+    #[rstest]
+    fn test_load_env() {
+        let keys = Keys::generate();
+        env::set_var(KEY_ENV, keys.secret_key().unwrap().to_bech32().unwrap());
+        take_env_key();
+        assert!(has_env_key());
+        let loaded = load(&KeySource::Env, &mut Answers(vec![]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.public_key(), keys.public_key());
+
+        // The key is not inherited by child processes
+        assert!(env::var_os(KEY_ENV).is_none());
+        assert!(!has_env_key());
+        assert!(load(&KeySource::Env, &mut Answers(vec![])).is_err());
+    }
+
+    #[rstest]
+    fn test_load_encrypted() {
+        let keys = Keys::generate();
+        let source = KeySource::Config(ncryptsec(&keys, "correct horse"));
+
+        let mut answers = Answers(vec!["wrong", "correct horse"]);
+        let loaded = load(&source, &mut answers).unwrap().unwrap();
+        assert_eq!(loaded.public_key(), keys.public_key());
+
+        let mut answers = Answers(vec!["a", "b", "c", "correct horse"]);
+        assert!(load(&source, &mut answers).is_err());
+        assert_eq!(answers.0, vec!["correct horse"]);
+    }
+
+    #[rstest]
+    fn test_load_file() {
+        let keys = Keys::generate();
+        let path =
+            std::env::temp_dir().join(format!("nostui-test-key-{}", Keys::generate().public_key()));
+        fs::write(&path, ncryptsec(&keys, "passphrase")).unwrap();
+
+        let source = KeySource::File(path.clone());
+        let loaded = load(&source, &mut Answers(vec!["passphrase"]));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().unwrap().public_key(), keys.public_key());
+
+        assert!(KeySource::File(path).read().is_err());
+    }
+}
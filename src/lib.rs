@@ -0,0 +1,52 @@
+#![deny(warnings)]
+#![allow(dead_code)]
+
+pub mod action;
+pub mod activity;
+pub mod app;
+pub mod avatar;
+pub mod away;
+pub mod browser;
+pub mod cli;
+pub mod clipboard;
+pub mod clock_skew;
+pub mod completion;
+pub mod components;
+pub mod config;
+pub mod discover;
+pub mod doctor;
+pub mod editor;
+pub mod emoji;
+pub mod export;
+pub mod followers;
+pub mod headless;
+pub mod i18n;
+pub mod interaction_limit;
+pub mod keys;
+pub mod link_preview;
+pub mod log_buffer;
+pub mod media;
+pub mod message;
+pub mod mode;
+pub mod nostr;
+pub mod notify;
+pub mod outbox;
+pub mod pagination;
+pub mod plugin;
+pub mod power;
+pub mod profile_queue;
+pub mod session;
+pub mod spam_filter;
+pub mod stats;
+pub mod status_format;
+pub mod subcommand;
+pub mod text;
+pub mod throttle;
+pub mod timeline;
+pub mod timestamp;
+pub mod translate;
+pub mod tui;
+pub mod utils;
+pub mod viewport;
+pub mod vim;
+pub mod widgets;
@@ -0,0 +1,324 @@
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use color_eyre::eyre::{eyre, Result};
+use lazy_static::lazy_static;
+use nostr_sdk::Url;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::LinkPreviewConfig;
+
+/// Links previewed per note, as each one takes a few lines under it.
+pub const MAX_PREVIEWS: usize = 3;
+
+/// Bytes of a page read for its metadata, which is in the head of the page.
+const MAX_PAGE_BYTES: usize = 256 * 1024;
+
+lazy_static! {
+    static ref URL_PATTERN: Regex = Regex::new(r#"https?://[^\s<>"'`]+"#).unwrap();
+    static ref TAG_PATTERN: Regex = Regex::new(r"(?is)<(meta|link)\s[^>]*>").unwrap();
+    static ref ATTRIBUTE_PATTERN: Regex =
+        Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    static ref TITLE_PATTERN: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+}
+
+/// Sources of the metadata of pages, tried in the configured order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewResolver {
+    /// The oEmbed endpoint which the page links to, e.g. of videos
+    OEmbed,
+    /// OpenGraph `og:` meta tags of the page, or its `<title>`
+    OpenGraph,
+}
+
+/// Metadata of a linked page, shown as a card under the note.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+    /// Host of the page without `www.`, e.g. `example.com`
+    pub domain: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Preview of a link, which is fetched once in a session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreviewState {
+    Loading,
+    Ready(LinkPreview),
+    Failed(String),
+}
+
+/// Returns the first [`MAX_PREVIEWS`] web links in the content, each once.
+pub fn urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    for m in URL_PATTERN.find_iter(content) {
+        let url = m
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        if Url::parse(url).is_ok() && !urls.iter().any(|other| other == url) {
+            urls.push(url.to_string());
+        }
+        if urls.len() == MAX_PREVIEWS {
+            break;
+        }
+    }
+    urls
+}
+
+/// Returns the host of the URL without `www.`, e.g. `example.com`.
+pub fn domain(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .map(|host| host.trim_start_matches("www.").to_string())
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Decodes the entities which are common in titles and collapses whitespace.
+fn decode(s: &str) -> String {
+    let decoded = s
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Attributes of the `<meta>` or `<link>` tags of the page, with lowercase names.
+fn tags<'a>(html: &'a str, name: &str) -> Vec<HashMap<String, &'a str>> {
+    TAG_PATTERN
+        .captures_iter(html)
+        .filter(|captures| captures[1].eq_ignore_ascii_case(name))
+        .map(|captures| {
+            ATTRIBUTE_PATTERN
+                .captures_iter(captures.get(0).map_or("", |m| m.as_str()))
+                .filter_map(|attribute| {
+                    let value = attribute.get(2).or(attribute.get(3))?.as_str();
+                    Some((attribute[1].to_lowercase(), value))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses the OpenGraph meta tags of the page, falling back to its `<title>` and description.
+fn open_graph(url: &str, html: &str) -> Option<LinkPreview> {
+    let metas = tags(html, "meta");
+    let meta = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            metas.iter().find_map(|attributes| {
+                let key = attributes.get("property").or(attributes.get("name"))?;
+                key.eq_ignore_ascii_case(name)
+                    .then(|| attributes.get("content").map(|content| decode(content)))
+                    .flatten()
+                    .filter(|content| !content.is_empty())
+            })
+        })
+    };
+
+    let title = meta(&["og:title", "twitter:title"]).or_else(|| {
+        TITLE_PATTERN
+            .captures(html)
+            .map(|captures| decode(&captures[1]))
+            .filter(|title| !title.is_empty())
+    })?;
+    Some(LinkPreview {
+        url: url.to_string(),
+        domain: domain(url),
+        title,
+        description: meta(&["og:description", "twitter:description", "description"]),
+    })
+}
+
+/// Returns the JSON oEmbed endpoint which the page links to.
+fn oembed_endpoint(url: &str, html: &str) -> Option<String> {
+    let href = tags(html, "link").into_iter().find_map(|attributes| {
+        let is_oembed = attributes
+            .get("type")
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("application/json+oembed"));
+        is_oembed.then(|| attributes.get("href").copied()).flatten()
+    })?;
+    Url::parse(url)
+        .ok()?
+        .join(&decode(href))
+        .ok()
+        .map(String::from)
+}
+
+/// Parses the oEmbed response, which describes the page by its author rather than a description.
+fn parse_oembed(url: &str, body: &str) -> Option<LinkPreview> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let title = value["title"]
+        .as_str()
+        .map(decode)
+        .filter(|t| !t.is_empty())?;
+    let description = value["author_name"]
+        .as_str()
+        .map(|author| format!("by {}", decode(author)));
+    Some(LinkPreview {
+        url: url.to_string(),
+        domain: domain(url),
+        title,
+        description,
+    })
+}
+
+/// Reads the head of the page, which has to be HTML.
+async fn fetch_page(client: &reqwest::Client, url: &str) -> Result<String> {
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|kind| kind.to_str().ok())
+        .is_some_and(|kind| kind.contains("html"));
+    if !is_html {
+        return Err(eyre!("not a web page"));
+    }
+
+    let mut page = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        page.extend_from_slice(&chunk);
+        if page.len() >= MAX_PAGE_BYTES {
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&page).into_owned())
+}
+
+/// Fetches the metadata of the page with the configured resolvers, through the proxy if any.
+pub async fn resolve(
+    config: &LinkPreviewConfig,
+    proxy: Option<SocketAddr>,
+    url: &str,
+) -> Result<LinkPreview> {
+    let mut client = reqwest::Client::builder().timeout(Duration::from_secs(config.timeout));
+    if let Some(proxy) = proxy {
+        client = client.proxy(reqwest::Proxy::all(format!("socks5h://{proxy}"))?);
+    }
+    let client = client.build()?;
+
+    let page = fetch_page(&client, url).await.map_err(|e| {
+        if e.downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout)
+        {
+            eyre!("timed out")
+        } else {
+            e
+        }
+    })?;
+    for resolver in &config.resolvers {
+        let preview = match resolver {
+            PreviewResolver::OEmbed => match oembed_endpoint(url, &page) {
+                Some(endpoint) => match client.get(endpoint).send().await {
+                    Ok(response) => parse_oembed(url, &response.text().await.unwrap_or_default()),
+                    Err(e) => {
+                        tracing::warn!(error = %e, url, "Failed to fetch oEmbed");
+                        None
+                    }
+                },
+                None => None,
+            },
+            PreviewResolver::OpenGraph => open_graph(url, &page),
+        };
+        if let Some(preview) = preview {
+            return Ok(preview);
+        }
+    }
+    Err(eyre!("no title in the page"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("gm", vec![])]
+    #[case(
+        "see https://example.com/a?b=c, and (https://nos.lol).",
+        vec!["https://example.com/a?b=c", "https://nos.lol"]
+    )]
+    #[case("http://a.com http://a.com", vec!["http://a.com"])]
+    #[case(
+        "https://a.com https://b.com https://c.com https://d.com",
+        vec!["https://a.com", "https://b.com", "https://c.com"]
+    )]
+    #[case("wss://nos.lol nostr:npub1", vec![])]
+    fn test_urls(#[case] content: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(urls(content), expected);
+    }
+
+    #[rstest]
+    #[case("https://www.example.com/a", "example.com")]
+    #[case("https://nos.lol", "nos.lol")]
+    fn test_domain(#[case] url: &str, #[case] expected: &str) {
+        assert_eq!(domain(url), expected);
+    }
+
+    #[rstest]
+    #[case(
+        r#"<head><meta property="og:title" content="Nostr &amp; you">
+        <META name='description' content='A protocol'>
+        <title>ignored</title></head>"#,
+        Some(("Nostr & you", Some("A protocol")))
+    )]
+    #[case(
+        "<title>\n  Plain  page\n</title>",
+        Some(("Plain page", None))
+    )]
+    #[case(
+        r#"<meta property="og:title" content=""><meta property="twitter:title" content="Card">"#,
+        Some(("Card", None))
+    )]
+    #[case("<p>no title</p>", None)]
+    fn test_open_graph(#[case] html: &str, #[case] expected: Option<(&str, Option<&str>)>) {
+        let url = "https://www.example.com/a";
+        let expected = expected.map(|(title, description)| LinkPreview {
+            url: url.to_string(),
+            domain: String::from("example.com"),
+            title: title.to_string(),
+            description: description.map(String::from),
+        });
+        assert_eq!(open_graph(url, html), expected);
+    }
+
+    #[rstest]
+    #[case(
+        r#"<link rel="alternate" type="application/json+oembed" href="/oembed?url=a&amp;format=json">"#,
+        Some("https://example.com/oembed?url=a&format=json")
+    )]
+    #[case(
+        r#"<link type="text/xml+oembed" href="https://example.com/oembed.xml">"#,
+        None
+    )]
+    fn test_oembed_endpoint(#[case] html: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            oembed_endpoint("https://example.com/v/1", html).as_deref(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_parse_oembed() {
+        let url = "https://example.com/v/1";
+        assert_eq!(
+            parse_oembed(url, r#"{"title": "A talk", "author_name": "alice"}"#),
+            Some(LinkPreview {
+                url: url.to_string(),
+                domain: String::from("example.com"),
+                title: String::from("A talk"),
+                description: Some(String::from("by alice")),
+            })
+        );
+        assert_eq!(parse_oembed(url, r#"{"type": "photo"}"#), None);
+    }
+}
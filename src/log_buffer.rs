@@ -0,0 +1,255 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+use lazy_static::lazy_static;
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// Number of records kept for the log viewer.
+pub const MAX_RECORDS: usize = 1000;
+
+lazy_static! {
+    /// Recent records of the app, shown in the log viewer.
+    pub static ref LOG_BUFFER: Arc<LogBuffer> = Arc::new(LogBuffer::new(MAX_RECORDS));
+}
+
+/// A log record with the spans it was recorded in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    /// Spans from the root, e.g. `update{action=Render}`
+    pub spans: Vec<String>,
+    /// The message followed by the other fields
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:>5} ", self.time.format("%H:%M:%S%.3f"), self.level)?;
+        if !self.spans.is_empty() {
+            write!(f, "{}: ", self.spans.join(":"))?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Records kept in memory, dropping the oldest ones.
+#[derive(Debug)]
+pub struct LogBuffer {
+    capacity: usize,
+    records: Mutex<VecDeque<LogRecord>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        let mut records = self
+            .records
+            .lock()
+            .expect("log buffer should not be poisoned");
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Records at `level` or more severe, the oldest first.
+    pub fn records(&self, level: Level) -> Vec<LogRecord> {
+        let records = self
+            .records
+            .lock()
+            .expect("log buffer should not be poisoned");
+        records
+            .iter()
+            .filter(|record| record.level <= level)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        let mut records = self
+            .records
+            .lock()
+            .expect("log buffer should not be poisoned");
+        records.clear();
+    }
+}
+
+/// Formats the `message` field first and the others as `name=value`.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+
+impl FieldVisitor {
+    fn finish(self) -> String {
+        match (self.message.is_empty(), self.fields.is_empty()) {
+            (_, true) => self.message,
+            (true, false) => self.fields,
+            (false, false) => format!("{} {}", self.message, self.fields),
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+            return;
+        }
+
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{}={value:?}", field.name());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
+
+/// Fields of a span, formatted when it is created.
+struct SpanFields(String);
+
+/// Copies records into a [`LogBuffer`].
+pub struct BufferLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl BufferLayer {
+    pub fn new(buffer: Arc<LogBuffer>) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S> Layer<S> for BufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.finish()));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| {
+                        let extensions = span.extensions();
+                        match extensions.get::<SpanFields>() {
+                            Some(SpanFields(fields)) if !fields.is_empty() => {
+                                format!("{}{{{fields}}}", span.name())
+                            }
+                            _ => span.name().to_owned(),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        self.buffer.push(LogRecord {
+            time: Local::now(),
+            level: *event.metadata().level(),
+            spans,
+            message: visitor.finish(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    fn record(level: Level, message: &str) -> LogRecord {
+        LogRecord {
+            time: Local::now(),
+            level,
+            spans: vec![],
+            message: String::from(message),
+        }
+    }
+
+    #[rstest]
+    fn test_log_buffer() {
+        let buffer = LogBuffer::new(3);
+        buffer.push(record(Level::DEBUG, "a"));
+        buffer.push(record(Level::INFO, "b"));
+        buffer.push(record(Level::ERROR, "c"));
+        buffer.push(record(Level::WARN, "d"));
+
+        let messages = |level| -> Vec<String> {
+            buffer
+                .records(level)
+                .into_iter()
+                .map(|record| record.message)
+                .collect()
+        };
+        assert_eq!(messages(Level::TRACE), vec!["b", "c", "d"]);
+        assert_eq!(messages(Level::WARN), vec!["c", "d"]);
+
+        buffer.clear();
+        assert_eq!(messages(Level::TRACE), Vec::<String>::new());
+    }
+
+    #[rstest]
+    fn test_buffer_layer() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let subscriber = tracing_subscriber::registry().with(BufferLayer::new(buffer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let _update = tracing::info_span!("update", action = "Render").entered();
+            let _command = tracing::info_span!("command").entered();
+            tracing::warn!(relay = "wss://a/", count = 2, "Failed to send");
+        });
+
+        let records = buffer.records(Level::TRACE);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, Level::WARN);
+        assert_eq!(
+            records[0].spans,
+            vec![
+                String::from("update{action=\"Render\"}"),
+                String::from("command")
+            ]
+        );
+        assert_eq!(
+            records[0].message,
+            "Failed to send relay=\"wss://a/\" count=2"
+        );
+        assert!(records[0].to_string().ends_with(
+            " WARN update{action=\"Render\"}:command: Failed to send relay=\"wss://a/\" count=2"
+        ));
+    }
+}
@@ -1,24 +1,11 @@
 #![deny(warnings)]
-#![allow(dead_code)]
-
-pub mod action;
-pub mod app;
-pub mod cli;
-pub mod components;
-pub mod config;
-pub mod mode;
-pub mod nostr;
-pub mod text;
-pub mod tui;
-pub mod utils;
-pub mod widgets;
 
 use clap::Parser;
-use cli::Cli;
 use color_eyre::eyre::Result;
-
-use crate::{
+use nostui::{
     app::App,
+    cli::Cli,
+    doctor, keys, subcommand,
     utils::{initialize_logging, initialize_panic_handler},
 };
 
@@ -27,16 +14,28 @@ async fn tokio_main() -> Result<()> {
 
     initialize_panic_handler()?;
 
-    let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    let mut args = Cli::parse();
+    if let Some(command) = args.command.take() {
+        return subcommand::run(command, &args).await;
+    }
+    if args.doctor {
+        return doctor::run(&args).await;
+    }
+
+    let mut app = App::new(&args)?;
     app.run().await?;
 
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    if let Err(e) = tokio_main().await {
+fn main() -> Result<()> {
+    // Before the runtime starts its threads, as the environment is changed
+    keys::take_env_key();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    if let Err(e) = runtime.block_on(tokio_main()) {
         eprintln!("{} error: Something went wrong", env!("CARGO_PKG_NAME"));
         Err(e)
     } else {
@@ -0,0 +1,312 @@
+use std::{net::SocketAddr, path::Path, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::{
+    hashes::{sha256::Hash as Sha256Hash, Hash},
+    prelude::*,
+};
+use serde::Deserialize;
+
+/// Kind of the authorization events of Blossom servers (BUD-01).
+const BLOSSOM_AUTH_KIND: u64 = 24242;
+
+/// Time for which an upload is authorized.
+const AUTH_EXPIRATION: u64 = 5 * 60;
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// API of media servers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaProtocol {
+    /// Blossom (BUD-02), which takes the file as is
+    #[default]
+    Blossom,
+    /// NIP-96, which takes the file as a form
+    Nip96,
+}
+
+/// A file uploaded to a media server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upload {
+    pub url: String,
+    pub mime_type: String,
+    /// SHA-256 of the file in hex
+    pub sha256: String,
+    pub size: usize,
+}
+
+impl Upload {
+    /// Describes the file for clients which show media without fetching it first (NIP-92).
+    pub fn imeta_tag(&self) -> Result<Tag> {
+        Ok(Tag::parse(vec![
+            String::from("imeta"),
+            format!("url {}", self.url),
+            format!("m {}", self.mime_type),
+            format!("x {}", self.sha256),
+            format!("size {}", self.size),
+        ])?)
+    }
+}
+
+/// Guesses the MIME type from the extension of the file.
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("svg") => "image/svg+xml",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        Some("mp3") => "audio/mpeg",
+        Some("ogg") => "audio/ogg",
+        Some("wav") => "audio/wav",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Formats the event as the value of the `Authorization` header.
+fn authorization(event: &Event) -> String {
+    format!("Nostr {}", STANDARD.encode(event.as_json()))
+}
+
+fn blossom_auth(keys: &Keys, name: &str, sha256: &Sha256Hash, now: Timestamp) -> Result<Event> {
+    let tags = [
+        Tag::Hashtag(String::from("upload")),
+        Tag::parse(vec![String::from("x"), sha256.to_string()])?,
+        Tag::Expiration(now + AUTH_EXPIRATION),
+    ];
+    Ok(EventBuilder::new(
+        Kind::from(BLOSSOM_AUTH_KIND),
+        format!("Upload {name}"),
+        tags,
+    )
+    .custom_created_at(now)
+    .to_event(keys)?)
+}
+
+/// Encodes the file as the `file` field of a form.
+fn multipart(boundary: &str, name: &str, mime_type: &str, bytes: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\nContent-Type: {mime_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+#[derive(Deserialize)]
+struct BlobDescriptor {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Nip96Info {
+    api_url: String,
+}
+
+#[derive(Deserialize)]
+struct Nip96Response {
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    nip94_event: Option<Nip96Event>,
+}
+
+#[derive(Deserialize)]
+struct Nip96Event {
+    tags: Vec<Vec<String>>,
+}
+
+/// Returns the URL of the file uploaded to a NIP-96 server.
+fn nip96_url(response: &str) -> Result<String> {
+    let response: Nip96Response = serde_json::from_str(response)?;
+    if response.status != "success" {
+        return Err(eyre!("{}", response.message.unwrap_or(response.status)));
+    }
+
+    response
+        .nip94_event
+        .into_iter()
+        .flat_map(|event| event.tags)
+        .find_map(|tag| match tag.as_slice() {
+            [name, url, ..] if name == "url" => Some(url.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("No URL in the response"))
+}
+
+/// Uploads the file to the media server, through the proxy if any.
+pub async fn upload(
+    keys: &Keys,
+    server: &str,
+    protocol: MediaProtocol,
+    proxy: Option<SocketAddr>,
+    path: &Path,
+) -> Result<Upload> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| eyre!("Failed to read {}: {e}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mime_type = mime_type(path);
+    let sha256 = Sha256Hash::hash(&bytes);
+
+    let mut client = reqwest::Client::builder().timeout(UPLOAD_TIMEOUT);
+    if let Some(proxy) = proxy {
+        client = client.proxy(reqwest::Proxy::all(format!("socks5h://{proxy}"))?);
+    }
+    let client = client.build()?;
+    let server = server.trim_end_matches('/');
+
+    let url = match protocol {
+        MediaProtocol::Blossom => {
+            let auth = blossom_auth(keys, &name, &sha256, Timestamp::now())?;
+            let response = client
+                .put(format!("{server}/upload"))
+                .header("Authorization", authorization(&auth))
+                .header("Content-Type", mime_type)
+                .body(bytes.clone())
+                .send()
+                .await?
+                .error_for_status()?;
+            response.json::<BlobDescriptor>().await?.url
+        }
+        MediaProtocol::Nip96 => {
+            let info: Nip96Info = client
+                .get(format!("{server}/.well-known/nostr/nip96.json"))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let api_url = Url::parse(&info.api_url)?;
+            let data = HttpData::new(UncheckedUrl::from(api_url.clone()), HttpMethod::POST)
+                .payload(sha256);
+            let auth = EventBuilder::http_auth(data).to_event(keys)?;
+            let boundary = format!("nostui{}", &sha256.to_string()[..16]);
+            let response = client
+                .post(api_url)
+                .header("Authorization", authorization(&auth))
+                .header(
+                    "Content-Type",
+                    format!("multipart/form-data; boundary={boundary}"),
+                )
+                .body(multipart(&boundary, &name, mime_type, &bytes))
+                .send()
+                .await?;
+            nip96_url(&response.text().await?)?
+        }
+    };
+
+    Ok(Upload {
+        url,
+        mime_type: String::from(mime_type),
+        sha256: sha256.to_string(),
+        size: bytes.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("photo.JPG", "image/jpeg")]
+    #[case("/tmp/clip.webm", "video/webm")]
+    #[case("notes.txt", "application/octet-stream")]
+    #[case("noextension", "application/octet-stream")]
+    fn test_mime_type(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(mime_type(&PathBuf::from(path)), expected);
+    }
+
+    #[rstest]
+    fn test_imeta_tag() {
+        let upload = Upload {
+            url: String::from("https://example.com/a.png"),
+            mime_type: String::from("image/png"),
+            sha256: String::from("abc"),
+            size: 3,
+        };
+        assert_eq!(
+            upload.imeta_tag().unwrap().as_vec(),
+            vec![
+                "imeta",
+                "url https://example.com/a.png",
+                "m image/png",
+                "x abc",
+                "size 3"
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_blossom_auth() {
+        let keys = Keys::generate();
+        let sha256 = Sha256Hash::hash(b"gm");
+        let now = Timestamp::from(1704091367);
+        let event = blossom_auth(&keys, "gm.txt", &sha256, now).unwrap();
+        assert_eq!(event.kind, Kind::from(BLOSSOM_AUTH_KIND));
+        assert!(event.verify().is_ok());
+        assert_eq!(
+            event.tags.iter().map(Tag::as_vec).collect::<Vec<_>>(),
+            vec![
+                vec![String::from("t"), String::from("upload")],
+                vec![String::from("x"), sha256.to_string()],
+                vec![String::from("expiration"), String::from("1704091667")],
+            ]
+        );
+
+        let header = authorization(&event);
+        let json = STANDARD
+            .decode(header.strip_prefix("Nostr ").unwrap())
+            .unwrap();
+        assert_eq!(Event::from_json(json).unwrap(), event);
+    }
+
+    #[rstest]
+    fn test_multipart() {
+        let body = multipart("b", "a.png", "image/png", b"png");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.png\"\r\nContent-Type: image/png\r\n\r\npng\r\n--b--\r\n"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"status":"success","nip94_event":{"tags":[["ox","abc"],["url","https://example.com/a.png"]]}}"#,
+        Ok("https://example.com/a.png")
+    )]
+    #[case(
+        r#"{"status":"error","message":"File too large"}"#,
+        Err("File too large")
+    )]
+    #[case(
+        r#"{"status":"success","nip94_event":{"tags":[]}}"#,
+        Err("No URL in the response")
+    )]
+    fn test_nip96_url(#[case] response: &str, #[case] expected: Result<&str, &str>) {
+        assert_eq!(
+            nip96_url(response).map_err(|e| e.to_string()),
+            expected.map(String::from).map_err(String::from)
+        );
+    }
+}
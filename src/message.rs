@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+const HISTORY_CAPACITY: usize = 100;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    pub level: MessageLevel,
+    pub text: String,
+    pub created_at: Instant,
+    pub received_at: DateTime<Local>,
+    pub dismissed: bool,
+}
+
+impl StatusMessage {
+    pub fn new(level: MessageLevel, text: String, created_at: Instant) -> Self {
+        Self {
+            level,
+            text,
+            created_at,
+            received_at: Local::now(),
+            dismissed: false,
+        }
+    }
+
+    /// Errors stay until they are dismissed, other messages fade out after `timeout`.
+    pub fn is_active(&self, now: Instant, timeout: Duration) -> bool {
+        match self.level {
+            _ if self.dismissed => false,
+            MessageLevel::Error => true,
+            _ => now.saturating_duration_since(self.created_at) < timeout,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageQueue {
+    messages: VecDeque<StatusMessage>,
+    timeout: Duration,
+}
+
+impl MessageQueue {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            timeout,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn push(&mut self, level: MessageLevel, text: String, now: Instant) {
+        self.messages
+            .push_front(StatusMessage::new(level, text, now));
+        self.messages.truncate(HISTORY_CAPACITY);
+    }
+
+    /// Returns the newest message that is still visible.
+    pub fn current(&self, now: Instant) -> Option<&StatusMessage> {
+        self.messages
+            .iter()
+            .find(|message| message.is_active(now, self.timeout))
+    }
+
+    /// Dismisses the message currently shown.
+    pub fn dismiss(&mut self, now: Instant) {
+        let timeout = self.timeout;
+        if let Some(message) = self
+            .messages
+            .iter_mut()
+            .find(|message| message.is_active(now, timeout))
+        {
+            message.dismissed = true;
+        }
+    }
+
+    /// Returns all messages including expired ones, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &StatusMessage> {
+        self.messages.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_current_empty() {
+        let queue = MessageQueue::new(TIMEOUT);
+        assert_eq!(queue.current(Instant::now()), None);
+    }
+
+    #[test]
+    fn test_current_info_expires() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new(TIMEOUT);
+        queue.push(MessageLevel::Info, String::from("Reacted"), now);
+        assert_eq!(
+            queue
+                .current(now + Duration::from_secs(1))
+                .map(|m| m.text.as_str()),
+            Some("Reacted")
+        );
+        assert_eq!(queue.current(now + TIMEOUT), None);
+    }
+
+    #[test]
+    fn test_current_error_persists() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new(TIMEOUT);
+        queue.push(MessageLevel::Error, String::from("Failed"), now);
+        let later = now + TIMEOUT * 10;
+        assert_eq!(
+            queue.current(later).map(|m| m.text.as_str()),
+            Some("Failed")
+        );
+
+        queue.dismiss(later);
+        assert_eq!(queue.current(later), None);
+    }
+
+    #[test]
+    fn test_current_falls_back_to_error() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new(TIMEOUT);
+        queue.push(MessageLevel::Error, String::from("Failed"), now);
+        queue.push(MessageLevel::Info, String::from("Reposted"), now);
+        assert_eq!(
+            queue.current(now).map(|m| m.text.as_str()),
+            Some("Reposted")
+        );
+        assert_eq!(
+            queue.current(now + TIMEOUT).map(|m| m.text.as_str()),
+            Some("Failed")
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let now = Instant::now();
+        let mut queue = MessageQueue::new(TIMEOUT);
+        for i in 0..(HISTORY_CAPACITY + 10) {
+            queue.push(MessageLevel::Info, i.to_string(), now);
+        }
+        assert_eq!(queue.len(), HISTORY_CAPACITY);
+        assert_eq!(
+            queue.history().next().map(|m| m.text.clone()),
+            Some((HISTORY_CAPACITY + 9).to_string())
+        );
+    }
+}
@@ -1,7 +1,26 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(
+    Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub enum Mode {
     #[default]
     Home,
+    Compose,
+    Confirm,
+    Review,
+    Popup,
+    Prompt,
+    Trace,
+    Form,
+}
+
+impl Mode {
+    /// Whether the mode is a popup which takes over all key inputs.
+    pub fn is_modal(&self) -> bool {
+        matches!(
+            self,
+            Mode::Confirm | Mode::Review | Mode::Popup | Mode::Prompt | Mode::Trace | Mode::Form
+        )
+    }
 }
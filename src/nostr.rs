@@ -1,11 +1,32 @@
+mod command;
 mod connection;
 mod connection_process;
+mod engagement;
 mod event;
+pub mod ingestion;
+pub mod nip09;
 pub mod nip10;
+pub mod nip11;
+pub mod nip18;
+pub mod nip21;
 pub mod nip27;
+pub mod nip28;
+pub mod nip36;
+pub mod nip57;
+pub mod nip65;
+pub mod nip75;
+pub mod nip94;
 mod profile;
+pub mod proxy;
+pub mod send_queue;
+#[cfg(any(test, feature = "simulation"))]
+pub mod simulation;
+pub mod subscriptions;
+pub mod trace;
 
+pub use command::NostrCommand;
 pub use connection::Connection;
-pub use connection_process::ConnectionProcess;
+pub use connection_process::{ConnectionProcess, Notification};
+pub use engagement::Engagement;
 pub use event::SortableEvent;
-pub use profile::Profile;
+pub use profile::{Profile, ProfileField};
@@ -0,0 +1,32 @@
+use nostr_sdk::prelude::*;
+use strum::Display;
+
+use crate::timeline::TimelineTabType;
+
+/// Requests from the app to the relay connection.
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum NostrCommand {
+    SendEvent(Box<Event>),
+    /// Sends the event only to the relays, e.g. to retry after rejections
+    SendEventTo(Vec<String>, Box<Event>),
+    /// Fetches events once, apart from the timeline subscription
+    Fetch(Vec<Filter>),
+    /// Starts the subscription which feeds the tab
+    SubscribeTimeline(TimelineTabType),
+    /// Closes the subscription of the tab
+    Unsubscribe(TimelineTabType),
+    /// Loads notes of the tab since the first timestamp, if any, until the second one,
+    /// up to the number
+    LoadPage(TimelineTabType, Option<Timestamp>, Timestamp, usize),
+    /// Reports the open subscriptions to the inspector
+    InspectSubscriptions,
+    /// Closes a single subscription, e.g. a stale one found in the inspector
+    CloseSubscription(SubscriptionId),
+    /// Reports the kinds of events received from each relay
+    InspectKinds,
+    /// Closes the subscriptions of all tabs until [`NostrCommand::ResumeTimelines`], e.g. while
+    /// suspended
+    PauseTimelines,
+    /// Subscribes the tabs again which were open when paused
+    ResumeTimelines,
+}
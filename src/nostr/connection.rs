@@ -1,49 +1,368 @@
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::Duration,
+};
 
 use color_eyre::eyre::Result;
 use nostr_sdk::prelude::*;
 
+use crate::{
+    config::RelayConfig,
+    nostr::{
+        nip28, nip94,
+        trace::{Direction, TraceEntry, Tracer},
+    },
+    pagination::DEFAULT_TIMELINE_LIMIT,
+    timeline::TimelineTabType,
+};
+
+const BROWSE_MODE_PROFILE_LIMIT: usize = 500;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct Connection {
-    keys: Keys,
+    keys: Option<Keys>,
     client: Client,
+    tracer: Option<Tracer>,
+    /// Relays out of the config which events are delivered to, e.g. inboxes of other users
+    inbox_relays: HashSet<Url>,
+    /// Notes requested by the subscription of a tab
+    timeline_limit: usize,
+    /// Followings when the home timeline was subscribed, which older notes are loaded from
+    followings: Vec<PublicKey>,
 }
 
 impl Connection {
-    pub async fn new(keys: Keys, relays: Vec<String>) -> Result<Self> {
-        let client = Client::new(&keys);
+    /// Connects to relays, through the SOCKS5 proxy if any.
+    /// Subscriptions are sent only to read relays and events only to write relays.
+    /// Without keys the connection is read-only (browse mode).
+    pub async fn new(
+        keys: Option<Keys>,
+        relays: Vec<RelayConfig>,
+        proxy: Option<SocketAddr>,
+    ) -> Result<Self> {
+        let opts = Options::new().proxy(proxy);
+        let client = match keys {
+            Some(ref keys) => Client::with_opts(keys, opts),
+            None => ClientBuilder::new().opts(opts).build(),
+        };
 
-        client.add_relays(relays).await?;
+        for relay in relays {
+            let opts = RelayOptions::new()
+                .proxy(proxy)
+                .read(relay.read)
+                .write(relay.write);
+            client.add_relay_with_opts(relay.url, opts).await?;
+        }
         client.connect().await;
 
-        Ok(Self { keys, client })
+        Ok(Self {
+            keys,
+            client,
+            tracer: None,
+            inbox_relays: HashSet::new(),
+            timeline_limit: DEFAULT_TIMELINE_LIMIT,
+            followings: vec![],
+        })
+    }
+
+    /// Records raw relay messages from now on.
+    pub fn trace(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    pub fn timeline_limit(mut self, limit: usize) -> Self {
+        self.timeline_limit = limit;
+        self
+    }
+
+    pub fn notifications(&self) -> tokio::sync::broadcast::Receiver<RelayPoolNotification> {
+        self.client.notifications()
+    }
+
+    pub async fn subscribe_timeline(&mut self, tab: &TimelineTabType) -> Result<SubscriptionId> {
+        match tab {
+            TimelineTabType::Home => self.subscribe_home_timeline().await,
+            TimelineTabType::UserTimeline { pubkey } => {
+                let filters = vec![
+                    Filter::new()
+                        .author(*pubkey)
+                        .kinds([Kind::TextNote, Kind::Repost])
+                        .kinds(nip94::kinds())
+                        .limit(self.timeline_limit),
+                    Filter::new().author(*pubkey).kind(Kind::Metadata).limit(1),
+                ];
+                Ok(self.subscribe(filters, None).await)
+            }
+            TimelineTabType::Hashtag { tag } => {
+                let filter = Filter::new()
+                    .kinds([Kind::TextNote, Kind::Repost])
+                    .hashtag(tag)
+                    .limit(self.timeline_limit);
+                Ok(self.subscribe(vec![filter], None).await)
+            }
+            TimelineTabType::Global => {
+                let filter = Filter::new()
+                    .kind(Kind::TextNote)
+                    .limit(self.timeline_limit);
+                Ok(self.subscribe(vec![filter], None).await)
+            }
+            TimelineTabType::Channel { id } => {
+                let filters = vec![
+                    Filter::new().id(*id).kind(Kind::ChannelCreation),
+                    Filter::new().event(*id).kind(Kind::ChannelMetadata),
+                    Filter::new()
+                        .event(*id)
+                        .kind(Kind::ChannelMessage)
+                        .limit(self.timeline_limit),
+                ];
+                Ok(self.subscribe(filters, None).await)
+            }
+            TimelineTabType::Thread { id } => {
+                let filters = vec![
+                    Filter::new().id(*id),
+                    Filter::new().event(*id).kind(Kind::TextNote),
+                ];
+                Ok(self.subscribe(filters, None).await)
+            }
+        }
+    }
+
+    /// Requests notes of the tab between `since` and `until`, closing the subscription on EOSE.
+    /// Returns `None` for tabs without older notes to load, i.e. threads.
+    pub async fn load_page(
+        &mut self,
+        tab: &TimelineTabType,
+        since: Option<Timestamp>,
+        until: Timestamp,
+        limit: usize,
+    ) -> Option<SubscriptionId> {
+        let mut filter = Filter::new().until(until).limit(limit);
+        if let Some(since) = since {
+            filter = filter.since(since);
+        }
+        let notes = [Kind::TextNote, Kind::Repost];
+        let filter = match tab {
+            TimelineTabType::Home if self.keys.is_some() => filter
+                .kinds(notes)
+                .kinds(nip94::kinds())
+                .authors(self.followings.clone()),
+            TimelineTabType::Home => filter.kinds(notes),
+            TimelineTabType::UserTimeline { pubkey } => {
+                filter.kinds(notes).kinds(nip94::kinds()).author(*pubkey)
+            }
+            TimelineTabType::Hashtag { tag } => filter.kinds(notes).hashtag(tag),
+            TimelineTabType::Channel { id } => filter.kind(Kind::ChannelMessage).event(*id),
+            // Only the newest notes are kept in the global tab
+            TimelineTabType::Thread { .. } | TimelineTabType::Global => return None,
+        };
+        let opts = SubscribeAutoCloseOptions::default()
+            .filter(FilterOptions::ExitOnEOSE)
+            .timeout(Some(FETCH_TIMEOUT));
+        Some(self.subscribe(vec![filter], Some(opts)).await)
+    }
+
+    pub async fn unsubscribe(&mut self, id: SubscriptionId) {
+        tracing::debug!(%id, "Unsubscribe");
+        self.trace_sent(None, ClientMessage::close(id.clone()));
+        self.client.unsubscribe(id).await;
     }
 
-    pub async fn subscribe_timeline(
-        &self,
-    ) -> Result<tokio::sync::broadcast::Receiver<RelayPoolNotification>> {
-        let followings = self.client.get_contact_list_public_keys(None).await?;
+    async fn subscribe(
+        &mut self,
+        filters: Vec<Filter>,
+        opts: Option<SubscribeAutoCloseOptions>,
+    ) -> SubscriptionId {
+        let id = SubscriptionId::generate();
+        tracing::debug!(%id, filters = filters.len(), auto_close = opts.is_some(), "Subscribe");
+        self.trace_sent(None, ClientMessage::req(id.clone(), filters.clone()));
+        self.client
+            .subscribe_with_id(id.clone(), filters, opts)
+            .await;
+        id
+    }
+
+    async fn subscribe_home_timeline(&mut self) -> Result<SubscriptionId> {
+        let since = Timestamp::now() - Duration::new(60 * 5, 0); // 5min
         let timeline_filter = Filter::new()
-            .authors(followings.clone())
             .kinds([
                 Kind::TextNote,
                 Kind::Repost,
                 Kind::Reaction,
                 Kind::ZapReceipt,
             ])
-            .since(Timestamp::now() - Duration::new(60 * 5, 0)); // 5min
-        let profile_filter = Filter::new().authors(followings).kinds([Kind::Metadata]);
-        self.client
-            .subscribe(vec![timeline_filter, profile_filter], None)
-            .await;
+            .kinds(nip94::kinds())
+            .since(since);
+        let profile_filter = Filter::new().kinds([Kind::Metadata]);
+        let filters = if let Some(ref keys) = self.keys {
+            let followings = self.client.get_contact_list_public_keys(None).await?;
+            self.followings.clone_from(&followings);
+            vec![
+                timeline_filter.authors(followings.clone()),
+                profile_filter.authors(followings),
+                // Replies, mentions, DMs and zaps from accounts not followed
+                Filter::new()
+                    .kinds([
+                        Kind::TextNote,
+                        Kind::EncryptedDirectMessage,
+                        Kind::ZapReceipt,
+                    ])
+                    .pubkey(keys.public_key())
+                    .since(since),
+                Filter::new()
+                    .author(keys.public_key())
+                    .kind(Kind::ContactList)
+                    .limit(1),
+                Filter::new()
+                    .author(keys.public_key())
+                    .kind(Kind::from(nip28::PUBLIC_CHATS_KIND))
+                    .limit(1),
+            ]
+        } else {
+            // Browse the firehose of relays
+            vec![
+                timeline_filter,
+                profile_filter.limit(BROWSE_MODE_PROFILE_LIMIT),
+            ]
+        };
+        Ok(self.subscribe(filters, None).await)
+    }
 
-        Ok(self.client.notifications())
+    /// Requests events once. They are delivered as notifications of a subscription closed on EOSE.
+    pub async fn fetch(&mut self, filters: Vec<Filter>) {
+        let opts = SubscribeAutoCloseOptions::default()
+            .filter(FilterOptions::ExitOnEOSE)
+            .timeout(Some(FETCH_TIMEOUT));
+        self.subscribe(filters, Some(opts)).await;
     }
 
     pub async fn send(&mut self, event: Event) -> Result<()> {
+        tracing::debug!(id = %event.id, "Send an event");
+        self.trace_sent(None, ClientMessage::event(event.clone()));
         self.client.send_event(event).await?;
         Ok(())
     }
 
+    /// Sends the event only to the relays.
+    ///
+    /// Relays which are not connected yet are added to the pool without reading or writing,
+    /// and are writable only while sending, so that the other events are not broadcast to them.
+    pub async fn send_to(&mut self, relays: Vec<String>, event: Event) -> Result<()> {
+        let urls: Vec<Url> = relays
+            .iter()
+            .map(|relay| Url::parse(relay))
+            .collect::<Result<_, _>>()?;
+        self.add_inbox_relays(&urls).await?;
+
+        let mut inboxes: Vec<Relay> = Vec::new();
+        for url in urls.iter().filter(|url| self.inbox_relays.contains(url)) {
+            let relay = self.client.relay(url.clone()).await?;
+            relay.flags().add(RelayServiceFlags::WRITE);
+            inboxes.push(relay);
+        }
+
+        for relay in &relays {
+            self.trace_sent(Some(relay.clone()), ClientMessage::event(event.clone()));
+        }
+        tracing::debug!(id = %event.id, ?relays, inboxes = inboxes.len(), "Send an event to relays");
+        let result = self.client.send_event_to(urls, event).await;
+        for relay in inboxes {
+            relay.flags().remove(RelayServiceFlags::WRITE);
+        }
+        result?;
+        Ok(())
+    }
+
+    /// Returns the relays which events are sent to, i.e. write relays except inboxes.
+    pub async fn write_relays(&self) -> Vec<Url> {
+        self.client
+            .relays()
+            .await
+            .into_iter()
+            .filter(|(url, relay)| !self.inbox_relays.contains(url) && relay.flags().has_write())
+            .map(|(url, _)| url)
+            .collect()
+    }
+
+    /// Returns how many relays are connected and how many there are, except inboxes.
+    pub async fn relay_counts(&self) -> (usize, usize) {
+        let relays = self.client.relays().await;
+        let mut connected = 0;
+        let mut total = 0;
+        for (url, relay) in &relays {
+            if !self.inbox_relays.contains(url) {
+                total += 1;
+                connected += usize::from(relay.is_connected().await);
+            }
+        }
+        (connected, total)
+    }
+
+    /// Returns the long-lived subscriptions with their filters and how many relays each is open
+    /// on. Subscriptions closed on EOSE aren't included.
+    pub async fn subscriptions(&self) -> HashMap<SubscriptionId, (Vec<Filter>, usize)> {
+        let mut subscriptions: HashMap<SubscriptionId, (Vec<Filter>, usize)> = self
+            .client
+            .subscriptions()
+            .await
+            .into_iter()
+            .map(|(id, filters)| (id, (filters, 0)))
+            .collect();
+        for relay in self.client.relays().await.values() {
+            for id in relay.subscriptions().await.keys() {
+                if let Some((_, relays)) = subscriptions.get_mut(id) {
+                    *relays += 1;
+                }
+            }
+        }
+        subscriptions
+    }
+
+    async fn add_inbox_relays(&mut self, urls: &[Url]) -> Result<()> {
+        let known = self.client.relays().await;
+        let mut added: Vec<Relay> = Vec::new();
+        for url in urls.iter().filter(|url| !known.contains_key(url)) {
+            let opts = RelayOptions::new().read(false).write(false);
+            tracing::info!(%url, "Add an inbox relay");
+            self.client.add_relay_with_opts(url.clone(), opts).await?;
+            added.push(self.client.relay(url.clone()).await?);
+            self.inbox_relays.insert(url.clone());
+        }
+
+        // Events are not sent to relays which are not connected
+        futures::future::join_all(
+            added
+                .iter()
+                .map(|relay| relay.connect(Some(CONNECT_TIMEOUT))),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Traces a message sent to `relay`, or to all relays if `None`.
+    fn trace_sent(&mut self, relay: Option<String>, message: ClientMessage) {
+        if let Some(tracer) = &mut self.tracer {
+            tracer.record(Direction::Sent, relay, message.as_json());
+        }
+    }
+
+    pub fn trace_received(&mut self, relay_url: &Url, message: &RelayMessage) {
+        if let Some(tracer) = &mut self.tracer {
+            tracer.record(
+                Direction::Received,
+                Some(relay_url.to_string()),
+                message.as_json(),
+            );
+        }
+    }
+
+    /// Takes relay messages traced since the last call.
+    pub fn take_traces(&mut self) -> Vec<TraceEntry> {
+        self.tracer.as_mut().map(Tracer::take).unwrap_or_default()
+    }
+
     pub async fn close(self) -> Result<(), nostr_sdk::client::Error> {
         self.client.shutdown().await
     }
@@ -1,18 +1,49 @@
+use std::time::Instant;
+
 use color_eyre::eyre::{ErrReport, Result};
 use nostr_sdk::prelude::*;
+use tracing::Instrument;
+
+use crate::{
+    nostr::{
+        send_queue::SendQueue,
+        subscriptions::{FetchBatcher, SubscriptionInfo, TimelineSubscriptions},
+        trace::TraceEntry,
+        Connection, NostrCommand,
+    },
+    stats::{KindCounter, RelayKinds, TrafficMeter, TrafficStats},
+    timeline::TimelineTabType,
+};
 
-use crate::nostr::Connection;
+/// Events from the relay connection to the app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// An event from the subscription of a timeline tab
+    Timeline(TimelineTabType, Event),
+    /// An event requested by [`NostrCommand::Fetch`]
+    Fetched(Event),
+    /// An OK message for a published event: relay, whether it was accepted and the reason
+    Receipt(EventId, Url, bool, String),
+    /// A relay message recorded in trace mode
+    Trace(TraceEntry),
+    /// Rates of relay messages, sent every second
+    Stats(TrafficStats),
+    /// Open subscriptions requested by [`NostrCommand::InspectSubscriptions`]
+    Subscriptions(Vec<SubscriptionInfo>),
+    /// Kinds of events by relay requested by [`NostrCommand::InspectKinds`]
+    Kinds(Vec<RelayKinds>),
+}
 
 pub struct ConnectionProcess {
     conn: Connection,
-    req_tx: tokio::sync::mpsc::UnboundedSender<Event>,
-    event_rx: tokio::sync::mpsc::UnboundedReceiver<Event>,
+    req_tx: tokio::sync::mpsc::UnboundedSender<Notification>,
+    command_rx: tokio::sync::mpsc::UnboundedReceiver<NostrCommand>,
     terminate_rx: tokio::sync::mpsc::UnboundedReceiver<()>,
 }
 
 type NewConnectionProcess = (
-    tokio::sync::mpsc::UnboundedReceiver<Event>,
-    tokio::sync::mpsc::UnboundedSender<Event>,
+    tokio::sync::mpsc::UnboundedReceiver<Notification>,
+    tokio::sync::mpsc::UnboundedSender<NostrCommand>,
     tokio::sync::mpsc::UnboundedSender<()>,
     ConnectionProcess,
 );
@@ -20,17 +51,17 @@ type NewConnectionProcess = (
 impl ConnectionProcess {
     pub fn new(conn: Connection) -> Result<NewConnectionProcess> {
         let (req_tx, req_rx) = tokio::sync::mpsc::unbounded_channel();
-        let (event_tx, event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (command_tx, command_rx) = tokio::sync::mpsc::unbounded_channel();
         let (terminate_tx, terminate_rx) = tokio::sync::mpsc::unbounded_channel();
 
         Ok((
             req_rx,
-            event_tx,
+            command_tx,
             terminate_tx,
             Self {
                 conn,
                 req_tx,
-                event_rx,
+                command_rx,
                 terminate_rx,
             },
         ))
@@ -38,17 +69,186 @@ impl ConnectionProcess {
 
     pub fn run(mut self) {
         tokio::spawn(async move {
-            let mut timeline = self.conn.subscribe_timeline().await?;
+            let mut notifications = self.conn.notifications();
+            let mut timelines = TimelineSubscriptions::default();
+            let mut fetches = FetchBatcher::default();
+            let mut send_queue = SendQueue::default();
+            let home_id = self.conn.subscribe_timeline(&TimelineTabType::Home).await?;
+            timelines.insert(home_id, TimelineTabType::Home);
+            let mut meter = TrafficMeter::new(Instant::now());
+            let mut kinds = KindCounter::default();
 
             loop {
-                while let Ok(notification) = timeline.try_recv() {
-                    if let RelayPoolNotification::Event { event, .. } = notification {
-                        self.req_tx.send(*event)?;
-                    };
+                while let Ok(notification) = notifications.try_recv() {
+                    match notification {
+                        RelayPoolNotification::Event {
+                            subscription_id,
+                            event,
+                            ..
+                        } => {
+                            timelines.record(&subscription_id);
+                            if let Some(tab) = timelines.tab(&subscription_id) {
+                                self.req_tx
+                                    .send(Notification::Timeline(tab.clone(), *event))?;
+                            } else {
+                                self.req_tx.send(Notification::Fetched(*event))?;
+                            }
+                        }
+                        RelayPoolNotification::Message {
+                            relay_url, message, ..
+                        } => {
+                            self.conn.trace_received(&relay_url, &message);
+                            meter.record(
+                                relay_url.to_string(),
+                                matches!(message, RelayMessage::Event { .. }),
+                            );
+                            match message {
+                                RelayMessage::Event { event, .. } => {
+                                    kinds.record(relay_url.to_string(), event.kind);
+                                }
+                                RelayMessage::Ok {
+                                    event_id,
+                                    status,
+                                    message,
+                                } => {
+                                    // Rejections are reported once no retry is left
+                                    let retrying = send_queue.receipt(
+                                        event_id,
+                                        &relay_url,
+                                        status,
+                                        &message,
+                                        Instant::now(),
+                                    );
+                                    if retrying {
+                                        tracing::info!(%event_id, %relay_url, %message, "Retry a rejected event");
+                                    } else {
+                                        self.req_tx.send(Notification::Receipt(
+                                            event_id, relay_url, status, message,
+                                        ))?;
+                                    }
+                                }
+                                RelayMessage::Notice { message } => {
+                                    send_queue.notice(&relay_url, &message, Instant::now());
+                                }
+                                _ => {}
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                while let Ok(command) = self.command_rx.try_recv() {
+                    let span = tracing::info_span!("command", command = %command);
+                    match command {
+                        // Rejections are reported by receipts, so they don't stop the connection
+                        NostrCommand::SendEvent(event) => {
+                            let id = event.id;
+                            let relays = self.conn.write_relays().await;
+                            send_queue.track(&event, relays, Instant::now());
+                            if let Err(e) = self.conn.send(*event).instrument(span).await {
+                                tracing::error!(%id, error = %e, "Failed to send an event");
+                            }
+                        }
+                        NostrCommand::SendEventTo(relays, event) => {
+                            let id = event.id;
+                            let urls = relays.iter().filter_map(|relay| Url::parse(relay).ok());
+                            send_queue.track(&event, urls, Instant::now());
+                            let sent = self.conn.send_to(relays, *event).instrument(span).await;
+                            if let Err(e) = sent {
+                                tracing::error!(%id, error = %e, "Failed to send an event");
+                            }
+                        }
+                        NostrCommand::Fetch(filters) => fetches.push(filters, Instant::now()),
+                        NostrCommand::SubscribeTimeline(tab) if timelines.is_paused() => {
+                            timelines.defer(tab);
+                        }
+                        NostrCommand::SubscribeTimeline(tab) => {
+                            if !timelines.contains(&tab) {
+                                let id = self
+                                    .conn
+                                    .subscribe_timeline(&tab)
+                                    .instrument(
+                                        tracing::info_span!(parent: &span, "subscribe", ?tab),
+                                    )
+                                    .await?;
+                                timelines.insert(id, tab);
+                            }
+                        }
+                        NostrCommand::LoadPage(tab, since, until, limit) => {
+                            let page = self.conn.load_page(&tab, since, until, limit);
+                            let page = page.instrument(span);
+                            if let Some(id) = page.await {
+                                timelines.insert_page(id, tab);
+                            }
+                        }
+                        NostrCommand::Unsubscribe(tab) => {
+                            for id in timelines.remove(&tab) {
+                                self.conn.unsubscribe(id).instrument(span.clone()).await;
+                            }
+                        }
+                        NostrCommand::InspectSubscriptions => {
+                            let open = self.conn.subscriptions().await;
+                            self.req_tx
+                                .send(Notification::Subscriptions(timelines.inspect(open)))?;
+                        }
+                        NostrCommand::CloseSubscription(id) => {
+                            let tab = timelines.close(&id);
+                            tracing::info!(%id, ?tab, "Close a subscription");
+                            self.conn.unsubscribe(id).instrument(span).await;
+                        }
+                        NostrCommand::InspectKinds => {
+                            self.req_tx.send(Notification::Kinds(kinds.breakdown()))?;
+                        }
+                        NostrCommand::PauseTimelines => {
+                            for id in timelines.pause() {
+                                self.conn.unsubscribe(id).instrument(span.clone()).await;
+                            }
+                        }
+                        NostrCommand::ResumeTimelines => {
+                            for tab in timelines.resume() {
+                                let id = self
+                                    .conn
+                                    .subscribe_timeline(&tab)
+                                    .instrument(
+                                        tracing::info_span!(parent: &span, "subscribe", ?tab),
+                                    )
+                                    .await?;
+                                timelines.insert(id, tab);
+                            }
+                        }
+                    }
+                }
+
+                let due = send_queue.poll(Instant::now());
+                for (event, relays) in due.retries {
+                    let id = event.id;
+                    let relays: Vec<String> = relays.iter().map(Url::to_string).collect();
+                    let span = tracing::info_span!("retry", %id, ?relays);
+                    if let Err(e) = self.conn.send_to(relays, event).instrument(span).await {
+                        tracing::error!(%id, error = %e, "Failed to send an event again");
+                    }
+                }
+                for (event_id, relay_url) in due.expired {
+                    self.req_tx.send(Notification::Receipt(
+                        event_id,
+                        relay_url,
+                        false,
+                        String::from("error: no response from the relay"),
+                    ))?;
+                }
+
+                if let Some(filters) = fetches.flush(Instant::now()) {
+                    let span = tracing::info_span!("fetch", filters = filters.len());
+                    self.conn.fetch(filters).instrument(span).await;
+                }
+
+                for entry in self.conn.take_traces() {
+                    self.req_tx.send(Notification::Trace(entry))?;
                 }
 
-                while let Ok(event) = self.event_rx.try_recv() {
-                    self.conn.send(event).await?;
+                if let Some(mut stats) = meter.sample(Instant::now(), timelines.len()) {
+                    (stats.connected_relays, stats.total_relays) = self.conn.relay_counts().await;
+                    self.req_tx.send(Notification::Stats(stats))?;
                 }
 
                 if self.terminate_rx.try_recv().is_ok() {
@@ -0,0 +1,139 @@
+use std::{cmp::Reverse, collections::HashSet};
+
+use nostr_sdk::prelude::*;
+
+use super::nip57::Zap;
+use crate::emoji;
+
+/// Who reacted to, reposted and zapped a note.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Engagement {
+    /// Authors and their reactions, the oldest first
+    pub reactions: Vec<(PublicKey, String)>,
+    /// Authors of reposts, the oldest first
+    pub reposts: Vec<PublicKey>,
+    /// Senders if known and the zapped millisats, the largest first
+    pub zaps: Vec<(Option<PublicKey>, u64)>,
+}
+
+impl Engagement {
+    pub fn new(reactions: &HashSet<Event>, reposts: &HashSet<Event>, zaps: &[Zap]) -> Self {
+        let mut reactions: Vec<&Event> = reactions.iter().collect();
+        reactions.sort_by_key(|event| event.created_at);
+        let mut reposts: Vec<&Event> = reposts.iter().collect();
+        reposts.sort_by_key(|event| event.created_at);
+        let mut zaps: Vec<(Option<PublicKey>, u64)> =
+            zaps.iter().map(|zap| (zap.sender, zap.millisats)).collect();
+        zaps.sort_by_key(|(_, millisats)| Reverse(*millisats));
+
+        Self {
+            reactions: reactions
+                .into_iter()
+                .map(|event| (event.pubkey, Self::emoji(event)))
+                .collect(),
+            reposts: reposts.into_iter().map(|event| event.pubkey).collect(),
+            zaps,
+        }
+    }
+
+    /// Shows a like (`+` or empty, NIP-25) as a heart and other reactions as emojis,
+    /// including custom emojis (NIP-30).
+    fn emoji(reaction: &Event) -> String {
+        match reaction.content.as_str() {
+            "+" | "" => String::from("♥"),
+            content => emoji::emojify(content, &reaction.tags),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reactions.is_empty() && self.reposts.is_empty() && self.zaps.is_empty()
+    }
+
+    pub fn zapped_millisats(&self) -> u64 {
+        self.zaps.iter().map(|(_, millisats)| millisats).sum()
+    }
+
+    /// Everyone who engaged, e.g. to fetch their profiles.
+    pub fn pubkeys(&self) -> impl Iterator<Item = PublicKey> + '_ {
+        self.reactions
+            .iter()
+            .map(|(pubkey, _)| *pubkey)
+            .chain(self.reposts.iter().copied())
+            .chain(self.zaps.iter().filter_map(|(pubkey, _)| *pubkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn event(keys: &Keys, kind: Kind, content: &str, tags: Vec<Tag>, created_at: u64) -> Event {
+        EventBuilder::new(kind, content, tags)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_engagement() {
+        let [alice, bob, carol, wallet] = [(); 4].map(|_| Keys::generate());
+        let pepe = Tag::Emoji {
+            shortcode: String::from("pepe"),
+            url: UncheckedUrl::from("https://example.com/pepe.png"),
+        };
+        let reactions = HashSet::from([
+            event(&bob, Kind::Reaction, "🤙", vec![], 2),
+            event(&alice, Kind::Reaction, "+", vec![], 1),
+            event(&carol, Kind::Reaction, ":pepe:", vec![pepe], 3),
+        ]);
+        let reposts = HashSet::from([event(&bob, Kind::Repost, "", vec![], 3)]);
+        let request = event(&alice, Kind::ZapRequest, "", vec![], 4);
+        let amount = |millisats| Tag::Amount {
+            millisats,
+            bolt11: None,
+        };
+        let zaps: Vec<Zap> = [
+            event(&wallet, Kind::ZapReceipt, "", vec![amount(1_000)], 5),
+            event(
+                &wallet,
+                Kind::ZapReceipt,
+                "",
+                vec![amount(21_000), Tag::Description(request.as_json())],
+                6,
+            ),
+        ]
+        .iter()
+        .map(Zap::from_receipt)
+        .collect();
+
+        let engagement = Engagement::new(&reactions, &reposts, &zaps);
+        assert_eq!(
+            engagement.reactions,
+            vec![
+                (alice.public_key(), String::from("♥")),
+                (bob.public_key(), String::from("🤙")),
+                (carol.public_key(), String::from("[pepe]"))
+            ]
+        );
+        assert_eq!(engagement.reposts, vec![bob.public_key()]);
+        assert_eq!(
+            engagement.zaps,
+            vec![(Some(alice.public_key()), 21_000), (None, 1_000)]
+        );
+        assert_eq!(engagement.zapped_millisats(), 22_000);
+        assert_eq!(
+            engagement.pubkeys().collect::<Vec<_>>(),
+            vec![
+                alice.public_key(),
+                bob.public_key(),
+                carol.public_key(),
+                bob.public_key(),
+                alice.public_key()
+            ]
+        );
+        assert!(!engagement.is_empty());
+    }
+}
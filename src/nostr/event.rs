@@ -3,11 +3,33 @@ use nostr_sdk::prelude::*;
 #[derive(PartialEq, Eq)]
 pub struct SortableEvent {
     pub event: Event,
+    /// Time the event is sorted by, which is the time of the latest repost for reposted notes
+    pub sorted_at: Timestamp,
 }
 
 impl SortableEvent {
     pub fn new(event: Event) -> Self {
-        Self { event }
+        let sorted_at = event.created_at;
+        Self { event, sorted_at }
+    }
+
+    /// Sorts a note by the time it was reposted, unless it is older than the note.
+    pub fn reposted(event: Event, reposted_at: Timestamp) -> Self {
+        let sorted_at = reposted_at.max(event.created_at);
+        Self { event, sorted_at }
+    }
+
+    /// Sorts a note by the time it was received, e.g. one dated in the future.
+    pub fn received(event: Event, received_at: Timestamp) -> Self {
+        Self {
+            event,
+            sorted_at: received_at,
+        }
+    }
+
+    /// Returns whether the note is sorted by a repost.
+    pub fn is_reposted(&self) -> bool {
+        self.sorted_at > self.event.created_at
     }
 }
 
@@ -19,10 +41,10 @@ impl PartialOrd for SortableEvent {
 
 impl Ord for SortableEvent {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.event.created_at == other.event.created_at {
+        if self.sorted_at == other.sorted_at {
             self.event.id.cmp(&other.event.id)
         } else {
-            self.event.created_at.cmp(&other.event.created_at)
+            self.sorted_at.cmp(&other.sorted_at)
         }
     }
 }
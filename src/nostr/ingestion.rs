@@ -0,0 +1,190 @@
+//! Parses events received from relays on a pool of blocking workers, between the connection and
+//! the app, so that heavy traffic doesn't hold up the UI. The relay pool has already verified
+//! their signatures and dropped the invalid ones.
+//!
+//! Events keep the order they were received in. Profiles and zaps parsed from them are delivered
+//! right before their events.
+
+use std::thread;
+
+use futures::{
+    future::{self, Either},
+    stream::FuturesOrdered,
+    StreamExt,
+};
+use nostr_sdk::prelude::*;
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver},
+    task::{self, JoinError},
+};
+
+use super::{nip57::Zap, Notification, Profile};
+
+/// Upper bound of the events being processed at once.
+const MAX_WORKERS: usize = 4;
+
+/// What the app receives from the pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ingested {
+    Notification(Notification),
+    Profile(Profile),
+    /// Zap decoded from the receipt with the id
+    Zap(EventId, Zap),
+}
+
+/// Parses profiles and zaps from the events.
+pub fn ingest(notification: Notification) -> Vec<Ingested> {
+    let event = match &notification {
+        Notification::Timeline(_, event) | Notification::Fetched(event) => event,
+        _ => return vec![Ingested::Notification(notification)],
+    };
+
+    let mut ingested = vec![];
+    match event.kind {
+        Kind::Metadata => {
+            if let Ok(metadata) = Metadata::from_json(&event.content) {
+                ingested.push(Ingested::Profile(Profile::new(
+                    event.pubkey,
+                    event.created_at,
+                    metadata,
+                )));
+            }
+        }
+        Kind::ZapReceipt => ingested.push(Ingested::Zap(event.id, Zap::from_receipt(event))),
+        _ => {}
+    }
+    ingested.push(Ingested::Notification(notification));
+    ingested
+}
+
+/// Runs [`ingest`] on the notifications until the connection closes.
+pub fn spawn(mut rx: UnboundedReceiver<Notification>) -> UnboundedReceiver<Ingested> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_WORKERS);
+    let (tx, ingested_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut pending = FuturesOrdered::new();
+        let mut open = true;
+        loop {
+            tokio::select! {
+                notification = rx.recv(), if open && pending.len() < workers => {
+                    match notification {
+                        Some(notification @ (Notification::Timeline(..) | Notification::Fetched(_))) => {
+                            pending.push_back(Either::Left(task::spawn_blocking(move || {
+                                ingest(notification)
+                            })));
+                        }
+                        // Answers to commands have nothing to parse, but wait for events before them
+                        Some(notification) => pending.push_back(Either::Right(future::ready(
+                            Ok::<_, JoinError>(vec![Ingested::Notification(notification)]),
+                        ))),
+                        None => open = false,
+                    }
+                }
+                Some(result) = pending.next() => {
+                    match result {
+                        Ok(ingested) => {
+                            for ingested in ingested {
+                                if tx.send(ingested).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => tracing::error!(error = %e, "Failed to ingest an event"),
+                    }
+                }
+                else => break,
+            }
+        }
+    });
+
+    ingested_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+    use crate::timeline::TimelineTabType;
+
+    #[rstest]
+    fn test_ingest() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("gm", []).to_event(&keys).unwrap();
+        let notification = Notification::Timeline(TimelineTabType::Home, note);
+        assert_eq!(
+            ingest(notification.clone()),
+            vec![Ingested::Notification(notification)]
+        );
+
+        let metadata = Metadata::new().name("alice");
+        let event = EventBuilder::metadata(&metadata).to_event(&keys).unwrap();
+        assert_eq!(
+            ingest(Notification::Fetched(event.clone())),
+            vec![
+                Ingested::Profile(Profile::new(keys.public_key(), event.created_at, metadata)),
+                Ingested::Notification(Notification::Fetched(event)),
+            ]
+        );
+
+        let receipt = EventBuilder::new(
+            Kind::ZapReceipt,
+            "",
+            [Tag::Amount {
+                millisats: 21_000,
+                bolt11: None,
+            }],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert_eq!(
+            ingest(Notification::Fetched(receipt.clone())),
+            vec![
+                Ingested::Zap(
+                    receipt.id,
+                    Zap {
+                        sender: None,
+                        millisats: 21_000
+                    }
+                ),
+                Ingested::Notification(Notification::Fetched(receipt)),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_spawn_keeps_order() {
+        let keys = Keys::generate();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut ingested_rx = spawn(rx);
+
+        let notes: Vec<Event> = (0..20)
+            .map(|i| {
+                EventBuilder::text_note(format!("note {i}"), [])
+                    .to_event(&keys)
+                    .unwrap()
+            })
+            .collect();
+        for note in &notes {
+            tx.send(Notification::Fetched(note.clone())).unwrap();
+        }
+        tx.send(Notification::Subscriptions(vec![])).unwrap();
+        drop(tx);
+
+        let mut received = vec![];
+        while let Some(ingested) = ingested_rx.recv().await {
+            received.push(ingested);
+        }
+        let expected: Vec<Ingested> = notes
+            .into_iter()
+            .map(|note| Ingested::Notification(Notification::Fetched(note)))
+            .chain([Ingested::Notification(Notification::Subscriptions(vec![]))])
+            .collect();
+        assert_eq!(received, expected);
+    }
+}
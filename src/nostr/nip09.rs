@@ -0,0 +1,49 @@
+use nostr_sdk::prelude::*;
+
+/// Ids of the events requested to be deleted by a deletion event (NIP-09).
+pub fn deleted_ids(deletion: &Event) -> Vec<EventId> {
+    if deletion.kind != Kind::EventDeletion {
+        return vec![];
+    }
+
+    deletion
+        .tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Event { event_id, .. } => Some(*event_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the deletion removes the event, which only its author can do.
+pub fn is_deleted_by(event: &Event, deletion: &Event) -> bool {
+    event.pubkey == deletion.pubkey && deleted_ids(deletion).contains(&event.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_is_deleted_by() {
+        let (alice, bob) = (Keys::generate(), Keys::generate());
+        let note = EventBuilder::text_note("gm", []).to_event(&bob).unwrap();
+        let reaction = EventBuilder::reaction(&note, "+").to_event(&alice).unwrap();
+
+        let deletion = EventBuilder::delete([reaction.id])
+            .to_event(&alice)
+            .unwrap();
+        assert_eq!(deleted_ids(&deletion), vec![reaction.id]);
+        assert!(is_deleted_by(&reaction, &deletion));
+        assert!(!is_deleted_by(&note, &deletion));
+
+        // Only the author can delete the event
+        let forged = EventBuilder::delete([reaction.id]).to_event(&bob).unwrap();
+        assert!(!is_deleted_by(&reaction, &forged));
+        assert_eq!(deleted_ids(&reaction), vec![]);
+    }
+}
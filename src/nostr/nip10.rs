@@ -1,61 +1,121 @@
 use nostr_sdk::prelude::*;
 
-pub struct ReplyTagsBuilder {}
+/// Thread references of an event, following both the marked and the deprecated positional e tags.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ThreadTags {
+    pub root: Option<EventId>,
+    pub reply: Option<EventId>,
+    pub mentions: Vec<EventId>,
+}
 
-impl ReplyTagsBuilder {
-    pub fn build(reply_to: Event) -> Vec<Tag> {
-        let (mut etags, mut ptags, rest_tags): (Vec<Tag>, Vec<Tag>, Vec<Tag>) = reply_to
+impl ThreadTags {
+    pub fn parse(event: &Event) -> Self {
+        let etags: Vec<(EventId, Option<&Marker>)> = event
             .tags
             .iter()
-            .fold((vec![], vec![], vec![]), |mut acc, tag| {
-                match tag {
-                    Tag::Event {
-                        event_id,
-                        relay_url,
-                        marker,
-                    } => {
-                        if let Some(Marker::Reply) = marker {
-                            acc.0.push(Tag::Event {
-                                event_id: *event_id,
-                                relay_url: relay_url.clone(),
-                                marker: None,
-                            })
-                        } else {
-                            acc.0.push(tag.clone())
-                        }
+            .filter_map(|tag| match tag {
+                Tag::Event {
+                    event_id, marker, ..
+                } => Some((*event_id, marker.as_ref())),
+                _ => None,
+            })
+            .collect();
+
+        if etags.iter().any(|(_, marker)| marker.is_some()) {
+            etags
+                .into_iter()
+                .fold(Self::default(), |mut acc, (event_id, marker)| {
+                    match marker {
+                        Some(Marker::Root) => acc.root = Some(event_id),
+                        Some(Marker::Reply) => acc.reply = Some(event_id),
+                        _ => acc.mentions.push(event_id),
                     }
-                    Tag::PublicKey { .. } => acc.1.push(tag.clone()),
-                    _ => acc.2.push(tag.clone()),
-                }
+                    acc
+                })
+        } else {
+            // Positional: the first is the root and the last is the reply
+            let root = etags.first().map(|(event_id, _)| *event_id);
+            let reply = etags.last().map(|(event_id, _)| *event_id);
+            let mentions = etags
+                .iter()
+                .skip(1)
+                .take(etags.len().saturating_sub(2))
+                .map(|(event_id, _)| *event_id)
+                .collect();
+            Self {
+                root,
+                reply: if etags.len() > 1 { reply } else { None },
+                mentions,
+            }
+        }
+    }
 
-                acc
-            });
+    /// Returns the event directly replied to.
+    pub fn parent(&self) -> Option<EventId> {
+        self.reply.or(self.root)
+    }
+}
 
-        let marker = if etags.is_empty() {
-            Some(Marker::Root)
-        } else {
-            Some(Marker::Reply)
-        };
+pub struct ReplyTagsBuilder {}
 
-        etags.push(Tag::Event {
-            event_id: reply_to.id,
+impl ReplyTagsBuilder {
+    /// Tags of a reply: the root and the parent with NIP-10 markers, and everyone in the
+    /// conversation, i.e. the p-tags of the parent and its author.
+    pub fn build(reply_to: Event) -> Vec<Tag> {
+        let event_tag = |event_id, marker| Tag::Event {
+            event_id,
             relay_url: None,
-            marker,
-        });
+            marker: Some(marker),
+        };
+        let etags = match ThreadTags::parse(&reply_to).root {
+            Some(root) => vec![
+                event_tag(root, Marker::Root),
+                event_tag(reply_to.id, Marker::Reply),
+            ],
+            None => vec![event_tag(reply_to.id, Marker::Root)],
+        };
 
-        if !ptags
+        let mut recipients: Vec<PublicKey> = vec![];
+        let pubkeys = reply_to
+            .tags
             .iter()
-            .any(|tag| matches!(tag, Tag::PublicKey { public_key, .. } if *public_key == reply_to.pubkey))
-        {
-            ptags.push(Tag::PublicKey {
-                public_key: reply_to.pubkey,
-                relay_url: None,
-                alias: None,
-                uppercase: false,
-            });
+            .filter_map(|tag| match tag {
+                Tag::PublicKey { public_key, .. } => Some(*public_key),
+                _ => None,
+            })
+            .chain([reply_to.pubkey]);
+        for pubkey in pubkeys {
+            if !recipients.contains(&pubkey) {
+                recipients.push(pubkey);
+            }
         }
+        let ptags = recipients.into_iter().map(|public_key| Tag::PublicKey {
+            public_key,
+            relay_url: None,
+            alias: None,
+            uppercase: false,
+        });
+
+        etags.into_iter().chain(ptags).collect()
+    }
 
-        [etags, ptags, rest_tags].concat()
+    /// Accounts tagged in the tags, in order.
+    pub fn recipients(tags: &[Tag]) -> Vec<PublicKey> {
+        tags.iter()
+            .filter_map(|tag| match tag {
+                Tag::PublicKey { public_key, .. } => Some(*public_key),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Removes the p-tags of the accounts which should not be notified.
+    pub fn without_recipients(tags: Vec<Tag>, removed: &[PublicKey]) -> Vec<Tag> {
+        tags.into_iter()
+            .filter(|tag| {
+                !matches!(tag, Tag::PublicKey { public_key, .. } if removed.contains(public_key))
+            })
+            .collect()
     }
 }
 
@@ -146,6 +206,60 @@ mod tests {
         .unwrap()
     }
 
+    #[rstest]
+    fn test_thread_tags_parse_root(root_event: Event) {
+        let tags = ThreadTags::parse(&root_event);
+        assert_eq!(tags, ThreadTags::default());
+        assert_eq!(tags.parent(), None);
+    }
+
+    #[rstest]
+    fn test_thread_tags_parse_reply(reply_event: Event) {
+        let root =
+            EventId::from_hex("03aafbdec84e4cbbbe3cd1811d45f16a0b55214b0b72097851c3618f73638cf0")
+                .unwrap();
+        let tags = ThreadTags::parse(&reply_event);
+        assert_eq!(tags.root, Some(root));
+        assert_eq!(tags.reply, None);
+        assert_eq!(tags.parent(), Some(root));
+    }
+
+    #[rstest]
+    fn test_thread_tags_parse_nested_reply(tag_event: Event) {
+        let reply =
+            EventId::from_hex("d444f485b5d401ee64564e4cc2bca7d9a50ad5ec628191470c009490ed1d43c3")
+                .unwrap();
+        let tags = ThreadTags::parse(&tag_event);
+        assert_eq!(tags.reply, Some(reply));
+        assert_eq!(tags.parent(), Some(reply));
+    }
+
+    #[rstest]
+    fn test_thread_tags_parse_positional() {
+        let ids: Vec<EventId> = (0..3)
+            .map(|i| EventId::from_slice(&[i; 32]).unwrap())
+            .collect();
+        let event = EventBuilder::text_note(
+            "positional",
+            ids.iter().map(|id| Tag::Event {
+                event_id: *id,
+                relay_url: None,
+                marker: None,
+            }),
+        )
+        .to_event(&Keys::generate())
+        .unwrap();
+        let tags = ThreadTags::parse(&event);
+        assert_eq!(
+            tags,
+            ThreadTags {
+                root: Some(ids[0]),
+                reply: Some(ids[2]),
+                mentions: vec![ids[1]],
+            }
+        );
+    }
+
     #[rstest]
     fn test_reply_tags_builder_build_root(root_event: Event) {
         let expected = vec![
@@ -213,14 +327,6 @@ mod tests {
                 relay_url: None,
                 marker: Some(Marker::Root),
             },
-            Tag::Event {
-                event_id: EventId::from_hex(
-                    "d444f485b5d401ee64564e4cc2bca7d9a50ad5ec628191470c009490ed1d43c3",
-                )
-                .unwrap(),
-                relay_url: None,
-                marker: None,
-            },
             Tag::Event {
                 event_id: EventId::from_hex(
                     "5d6468d901f4b933b3b71c1ad9761226121de929ba3351a28973a3ba1cab05f2",
@@ -238,8 +344,53 @@ mod tests {
                 alias: None,
                 uppercase: false,
             },
-            Tag::Hashtag(String::from("nostr")),
         ];
         assert_eq!(ReplyTagsBuilder::build(tag_event), expected);
     }
+
+    #[rstest]
+    fn test_reply_tags_builder_build_reply_all() {
+        let (alice, bob, carol) = (Keys::generate(), Keys::generate(), Keys::generate());
+        let ids: Vec<EventId> = (0..2)
+            .map(|i| EventId::from_slice(&[i; 32]).unwrap())
+            .collect();
+        // A positional reply by bob to alice, mentioning carol
+        let parent = EventBuilder::text_note(
+            "positional",
+            [
+                Tag::event(ids[0]),
+                Tag::event(ids[1]),
+                Tag::public_key(alice.public_key()),
+                Tag::public_key(carol.public_key()),
+                Tag::public_key(alice.public_key()),
+            ],
+        )
+        .to_event(&bob)
+        .unwrap();
+
+        let tags = ReplyTagsBuilder::build(parent.clone());
+        assert_eq!(
+            ThreadTags::parse(
+                &EventBuilder::text_note("", tags.clone())
+                    .to_event(&alice)
+                    .unwrap()
+            ),
+            ThreadTags {
+                root: Some(ids[0]),
+                reply: Some(parent.id),
+                mentions: vec![],
+            }
+        );
+        assert_eq!(
+            ReplyTagsBuilder::recipients(&tags),
+            vec![alice.public_key(), carol.public_key(), bob.public_key()]
+        );
+
+        let tags = ReplyTagsBuilder::without_recipients(tags, &[carol.public_key()]);
+        assert_eq!(
+            ReplyTagsBuilder::recipients(&tags),
+            vec![alice.public_key(), bob.public_key()]
+        );
+        assert_eq!(tags.len(), 4);
+    }
 }
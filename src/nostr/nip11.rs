@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// Failures are kept for a shorter time so that they are retried when the relay is back
+const FAILURE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// NIPs which nostui relies on, e.g. filters by kind and author (NIP-01).
+pub const REQUIRED_NIPS: [u16; 1] = [1];
+
+/// The information document of a relay, or why it could not be fetched.
+pub type RelayInfo = Result<Box<RelayInformationDocument>, String>;
+
+/// Keeps fetched information documents for a while, keyed by relay URL.
+#[derive(Debug, Default)]
+pub struct RelayInfoCache {
+    entries: HashMap<String, (Instant, RelayInfo)>,
+}
+
+impl RelayInfoCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the information of the relay unless it has expired.
+    pub fn get(&self, url: &str, now: Instant) -> Option<&RelayInfo> {
+        let (fetched_at, info) = self.entries.get(url)?;
+        let ttl = if info.is_ok() {
+            CACHE_TTL
+        } else {
+            FAILURE_CACHE_TTL
+        };
+        (now.saturating_duration_since(*fetched_at) < ttl).then_some(info)
+    }
+
+    pub fn insert(&mut self, url: String, info: RelayInfo, now: Instant) {
+        self.entries.insert(url, (now, info));
+    }
+}
+
+/// Fetches the relay information document (NIP-11) of the relay, through the proxy if any.
+pub async fn fetch(url: Url, proxy: Option<SocketAddr>) -> Result<RelayInformationDocument> {
+    tokio::time::timeout(FETCH_TIMEOUT, RelayInformationDocument::get(url, proxy))
+        .await
+        .map_err(|_| eyre!("timed out"))?
+        .map_err(|e| eyre!("{e}"))
+}
+
+/// Describes the relay in a line, e.g. `nos.lol (strfry 1.0.0), NIPs 1, 2, 4`.
+pub fn describe(info: &RelayInformationDocument) -> String {
+    let mut description = info.name.clone().unwrap_or_default();
+    let software = [info.software.as_deref(), info.version.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(|s| s.rsplit('/').next().unwrap_or(s))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !software.is_empty() {
+        description = format!("{description} ({software})").trim().to_string();
+    }
+    if let Some(ref nips) = info.supported_nips {
+        let nips: Vec<String> = nips.iter().map(|nip| nip.to_string()).collect();
+        description = format!("{description}, NIPs {}", nips.join(", "));
+    }
+    description
+}
+
+/// Returns the required NIPs which the relay doesn't list as supported. Relays which don't
+/// list NIPs at all are assumed to support them.
+pub fn missing_nips(info: &RelayInformationDocument, required: &[u16]) -> Vec<u16> {
+    let Some(ref nips) = info.supported_nips else {
+        return vec![];
+    };
+    required
+        .iter()
+        .filter(|nip| !nips.contains(nip))
+        .copied()
+        .collect()
+}
+
+/// Returns the limits of the relay on messages and subscriptions, e.g. `max limit 500`.
+pub fn limits(info: &RelayInformationDocument) -> Vec<String> {
+    let Some(ref limitation) = info.limitation else {
+        return vec![];
+    };
+
+    [
+        ("max message length", limitation.max_message_length),
+        ("max content length", limitation.max_content_length),
+        ("max subscriptions", limitation.max_subscriptions),
+        ("max filters", limitation.max_filters),
+        ("max limit", limitation.max_limit),
+        ("max event tags", limitation.max_event_tags),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{name} {value}")))
+    .collect()
+}
+
+/// Returns the limits of the relay which may keep my events out, e.g. required payment.
+pub fn restrictions(info: &RelayInformationDocument) -> Vec<String> {
+    let Some(ref limitation) = info.limitation else {
+        return vec![];
+    };
+
+    let mut restrictions = vec![];
+    if limitation.auth_required == Some(true) {
+        restrictions.push(String::from("auth required"));
+    }
+    if limitation.payment_required == Some(true) {
+        restrictions.push(String::from("payment required"));
+    }
+    if let Some(difficulty) = limitation.min_pow_difficulty.filter(|d| *d > 0) {
+        restrictions.push(format!("PoW difficulty {difficulty}"));
+    }
+    restrictions
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn info(json: &str) -> RelayInformationDocument {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[rstest]
+    #[case(r#"{}"#, "")]
+    #[case(
+        r#"{"name": "nos.lol", "software": "git+https://github.com/hoytech/strfry.git", "version": "1.0.0", "supported_nips": [1, 11]}"#,
+        "nos.lol (strfry.git 1.0.0), NIPs 1, 11"
+    )]
+    #[case(r#"{"software": "nostream"}"#, "(nostream)")]
+    fn test_describe(#[case] json: &str, #[case] expected: &str) {
+        assert_eq!(describe(&info(json)), expected);
+    }
+
+    #[rstest]
+    fn test_restrictions() {
+        assert_eq!(restrictions(&info("{}")), Vec::<String>::new());
+        let info = info(
+            r#"{"limitation": {"auth_required": true, "payment_required": false, "min_pow_difficulty": 20}}"#,
+        );
+        assert_eq!(
+            restrictions(&info),
+            vec!["auth required", "PoW difficulty 20"]
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{}"#, vec![])]
+    #[case(r#"{"supported_nips": [1, 11]}"#, vec![])]
+    #[case(r#"{"supported_nips": [11, 42]}"#, vec![1])]
+    fn test_missing_nips(#[case] json: &str, #[case] expected: Vec<u16>) {
+        assert_eq!(missing_nips(&info(json), &REQUIRED_NIPS), expected);
+    }
+
+    #[rstest]
+    fn test_limits() {
+        assert_eq!(limits(&info("{}")), Vec::<String>::new());
+        let info = info(
+            r#"{"limitation": {"max_message_length": 16384, "max_limit": 500, "auth_required": false}}"#,
+        );
+        assert_eq!(
+            limits(&info),
+            vec!["max message length 16384", "max limit 500"]
+        );
+    }
+
+    #[rstest]
+    fn test_cache() {
+        let now = Instant::now();
+        let mut cache = RelayInfoCache::new();
+        cache.insert(String::from("wss://a"), Ok(Box::default()), now);
+        cache.insert(String::from("wss://b"), Err(String::from("timed out")), now);
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(cache.get("wss://a", later), Some(&Ok(Box::default())));
+        assert_eq!(
+            cache.get("wss://b", now),
+            Some(&Err(String::from("timed out")))
+        );
+        assert_eq!(cache.get("wss://b", later), None);
+        assert_eq!(cache.get("wss://a", later + CACHE_TTL), None);
+        assert_eq!(cache.get("wss://c", now), None);
+    }
+}
@@ -0,0 +1,81 @@
+use nostr_sdk::prelude::*;
+
+/// The note reposted by a repost (NIP-18).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reposted {
+    /// The note embedded in the content of the repost
+    Note(Box<Event>),
+    /// The id of the note from the `e` tag, if the content is empty or invalid
+    Id(EventId),
+}
+
+impl Reposted {
+    pub fn id(&self) -> EventId {
+        match self {
+            Self::Note(note) => note.id,
+            Self::Id(id) => *id,
+        }
+    }
+}
+
+/// Returns the reposted note. An embedded note is only trusted if its signature is valid
+/// and it is the one tagged.
+pub fn reposted(repost: &Event) -> Option<Reposted> {
+    if repost.kind != Kind::Repost {
+        return None;
+    }
+
+    let tagged = repost.tags.iter().find_map(|tag| match tag {
+        Tag::Event { event_id, .. } => Some(*event_id),
+        _ => None,
+    });
+    let embedded = Event::from_json(&repost.content).ok().filter(|note| {
+        note.kind == Kind::TextNote
+            && tagged.is_none_or(|id| id == note.id)
+            && note.verify().is_ok()
+    });
+    match (embedded, tagged) {
+        (Some(note), _) => Some(Reposted::Note(Box::new(note))),
+        (None, Some(id)) => Some(Reposted::Id(id)),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_reposted() {
+        let [alice, bob] = [(); 2].map(|_| Keys::generate());
+        let note = EventBuilder::text_note("gm", []).to_event(&alice).unwrap();
+        let other = EventBuilder::text_note("gn", []).to_event(&alice).unwrap();
+
+        let repost = EventBuilder::repost(&note, None).to_event(&bob).unwrap();
+        assert_eq!(
+            reposted(&repost),
+            Some(Reposted::Note(Box::new(note.clone())))
+        );
+
+        // A note which is not the tagged one is ignored
+        let tags = [Tag::event(note.id)];
+        let repost = EventBuilder::new(Kind::Repost, other.as_json(), tags.clone())
+            .to_event(&bob)
+            .unwrap();
+        assert_eq!(reposted(&repost), Some(Reposted::Id(note.id)));
+
+        let repost = EventBuilder::new(Kind::Repost, "", tags)
+            .to_event(&bob)
+            .unwrap();
+        assert_eq!(reposted(&repost).map(|note| note.id()), Some(note.id));
+
+        let repost = EventBuilder::new(Kind::Repost, "", [])
+            .to_event(&bob)
+            .unwrap();
+        assert_eq!(reposted(&repost), None);
+        assert_eq!(reposted(&note), None);
+    }
+}
@@ -0,0 +1,93 @@
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+
+const NJUMP_PREFIXES: [&str; 2] = ["https://njump.me/", "http://njump.me/"];
+// An nevent/nprofile with a handful of relay hints fits well within this
+const MAX_URI_LENGTH: usize = 1000;
+
+/// An entity which can be opened from a nostr URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NostrTarget {
+    Profile(PublicKey),
+    Event(EventId),
+}
+
+impl NostrTarget {
+    /// Parses `nostr:` URIs, njump URLs and bare bech32 entities (e.g. pasted from the clipboard).
+    ///
+    /// Secret keys are rejected so that they are never handled as something to open.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(eyre!("Nothing to open"));
+        }
+        if text.len() > MAX_URI_LENGTH {
+            return Err(eyre!("Too long to be a nostr URI"));
+        }
+
+        let bech32 = NJUMP_PREFIXES
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix))
+            .or_else(|| text.strip_prefix("nostr:"))
+            .unwrap_or(text);
+        let bech32 = bech32.trim_end_matches('/');
+
+        let nip19 = Nip19::from_bech32(bech32).map_err(|_| eyre!("Not a nostr URI"))?;
+        match Nip21::try_from(nip19).map_err(|e| eyre!("Unsupported nostr URI: {e}"))? {
+            Nip21::Pubkey(pubkey) => Ok(Self::Profile(pubkey)),
+            Nip21::Profile(profile) => Ok(Self::Profile(profile.public_key)),
+            Nip21::EventId(id) => Ok(Self::Event(id)),
+            Nip21::Event(event) => Ok(Self::Event(event.event_id)),
+            Nip21::Coordinate(_) => Err(eyre!("Addressable events are not supported yet")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    const NPUB: &str = "npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmug";
+    const NOTE: &str = "note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv";
+
+    fn profile() -> NostrTarget {
+        NostrTarget::Profile(PublicKey::from_bech32(NPUB).unwrap())
+    }
+
+    fn event() -> NostrTarget {
+        NostrTarget::Event(EventId::from_bech32(NOTE).unwrap())
+    }
+
+    #[rstest]
+    #[case(format!("nostr:{NPUB}"), profile())]
+    #[case(format!("  nostr:{NPUB}\n"), profile())]
+    #[case(NPUB.to_string(), profile())]
+    #[case(format!("https://njump.me/{NPUB}"), profile())]
+    #[case(format!("nostr:{NOTE}"), event())]
+    #[case(format!("https://njump.me/{NOTE}/"), event())]
+    #[case(
+        Nip19Event::new(EventId::from_bech32(NOTE).unwrap(), ["wss://nos.lol"]).to_bech32().unwrap(),
+        event()
+    )]
+    fn test_parse(#[case] text: String, #[case] expected: NostrTarget) {
+        assert_eq!(NostrTarget::parse(&text).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("hello")]
+    #[case("https://example.com/npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmug")]
+    #[case("nostr:npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmuh")]
+    fn test_parse_invalid(#[case] text: &str) {
+        assert!(NostrTarget::parse(text).is_err());
+    }
+
+    #[rstest]
+    fn test_parse_secret_key() {
+        let nsec = Keys::generate().secret_key().unwrap().to_bech32().unwrap();
+        assert!(NostrTarget::parse(&nsec).is_err());
+    }
+}
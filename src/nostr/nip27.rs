@@ -1,6 +1,11 @@
+use std::collections::HashSet;
+
 use nostr_sdk::prelude::*;
 use regex::Regex;
 
+/// NIP-19 ids which can be written as `nostr:` URIs in notes.
+const BARE_PATTERN: &str = r"(^|[^\w:/])((?:npub|nprofile|note|nevent)1[a-z0-9]+)";
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Reference {
     // TODO: Add search index
@@ -29,6 +34,33 @@ impl Reference {
     }
 }
 
+/// Returns the NIP-19 ids in the text which are not written as `nostr:` URIs, e.g. pasted
+/// `npub1...`, without duplicates.
+pub fn find_bare(text: &str) -> Vec<String> {
+    let pattern = Regex::new(BARE_PATTERN).unwrap();
+    let mut found = HashSet::new();
+    pattern
+        .captures_iter(text)
+        .map(|capture| capture[2].to_string())
+        .filter(|id| Nip19::from_bech32(id).is_ok())
+        .filter(|id| found.insert(id.clone()))
+        .collect()
+}
+
+/// Writes the bare `ids` in the text as `nostr:` URIs.
+pub fn link_bare(text: &str, ids: &HashSet<String>) -> String {
+    let pattern = Regex::new(BARE_PATTERN).unwrap();
+    pattern
+        .replace_all(text, |capture: &regex::Captures| {
+            if ids.contains(&capture[2]) {
+                format!("{}nostr:{}", &capture[1], &capture[2])
+            } else {
+                capture[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -86,4 +118,27 @@ mod tests {
     fn test_parse(#[case] content: &str, #[case] expected: Vec<Reference>) {
         assert_eq!(Reference::find(content), expected);
     }
+
+    const NPUB: &str = "npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmug";
+    const NOTE: &str = "note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv";
+
+    #[rstest]
+    #[case("", vec![])]
+    #[case(&format!("see {NOTE} by {NPUB}\n{NOTE}"), vec![NOTE, NPUB])]
+    #[case(NPUB, vec![NPUB])]
+    #[case(&format!("nostr:{NPUB} https://njump.me/{NOTE}"), vec![])]
+    #[case(&format!("see {NOTE}foo"), vec![])]
+    #[case("npub1broken", vec![])]
+    fn test_find_bare(#[case] text: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(find_bare(text), expected);
+    }
+
+    #[rstest]
+    fn test_link_bare() {
+        let ids = HashSet::from([NOTE.to_string()]);
+        assert_eq!(
+            link_bare(&format!("{NOTE} by {NPUB}, nostr:{NOTE}"), &ids),
+            format!("nostr:{NOTE} by {NPUB}, nostr:{NOTE}")
+        );
+    }
 }
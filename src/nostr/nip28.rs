@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use nostr_sdk::prelude::*;
+
+use crate::nostr::nip10::ThreadTags;
+
+/// Kind of the list of public chats which I joined (NIP-51).
+pub const PUBLIC_CHATS_KIND: u64 = 10005;
+
+/// Returns the channel which a channel event belongs to: the channel itself for its creation,
+/// and the root of metadata and messages.
+pub fn channel_id(event: &Event) -> Option<EventId> {
+    match event.kind {
+        Kind::ChannelCreation => Some(event.id),
+        Kind::ChannelMetadata | Kind::ChannelMessage => ThreadTags::parse(event).root,
+        _ => None,
+    }
+}
+
+/// Returns the channels in the list of public chats, in the order of the list.
+pub fn joined_channels(list: &Event) -> Vec<EventId> {
+    list.tags
+        .iter()
+        .filter_map(|tag| match tag {
+            Tag::Event { event_id, .. } => Some(*event_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Tags of a message to the channel, replying to `reply_to` in it if any.
+pub fn message_tags(channel_id: EventId, reply_to: Option<&Event>) -> Vec<Tag> {
+    let mut tags = vec![Tag::Event {
+        event_id: channel_id,
+        relay_url: None,
+        marker: Some(Marker::Root),
+    }];
+    if let Some(reply_to) = reply_to {
+        tags.push(Tag::Event {
+            event_id: reply_to.id,
+            relay_url: None,
+            marker: Some(Marker::Reply),
+        });
+        tags.push(Tag::PublicKey {
+            public_key: reply_to.pubkey,
+            relay_url: None,
+            alias: None,
+            uppercase: false,
+        });
+    }
+    tags
+}
+
+/// Names and descriptions of channels, which only their creators can update.
+#[derive(Debug, Default)]
+pub struct Channels {
+    created: HashMap<EventId, (PublicKey, Metadata)>,
+    /// The latest metadata of each channel by author, kept until the creator is known
+    updated: HashMap<(EventId, PublicKey), (Timestamp, Metadata)>,
+}
+
+impl Channels {
+    pub fn add(&mut self, event: &Event) {
+        let Some(id) = channel_id(event) else {
+            return;
+        };
+        let Ok(metadata) = Metadata::from_json(&event.content) else {
+            return;
+        };
+
+        match event.kind {
+            Kind::ChannelCreation => {
+                self.created.insert(id, (event.pubkey, metadata));
+            }
+            Kind::ChannelMetadata => {
+                let key = (id, event.pubkey);
+                if self
+                    .updated
+                    .get(&key)
+                    .is_none_or(|(created_at, _)| *created_at < event.created_at)
+                {
+                    self.updated.insert(key, (event.created_at, metadata));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn metadata(&self, id: &EventId) -> Option<&Metadata> {
+        let (creator, metadata) = self.created.get(id)?;
+        Some(
+            self.updated
+                .get(&(*id, *creator))
+                .map_or(metadata, |(_, metadata)| metadata),
+        )
+    }
+
+    pub fn name(&self, id: &EventId) -> Option<String> {
+        self.metadata(id)
+            .and_then(|metadata| metadata.name.clone())
+            .filter(|name| !name.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn metadata(name: &str) -> Metadata {
+        Metadata::new().name(name)
+    }
+
+    fn update(keys: &Keys, channel: &Event, name: &str, created_at: u64) -> Event {
+        EventBuilder::channel_metadata(channel.id, None, &metadata(name))
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_channel_id() {
+        let keys = Keys::generate();
+        let channel = EventBuilder::channel(&metadata("nostr"))
+            .to_event(&keys)
+            .unwrap();
+        let message = EventBuilder::new(Kind::ChannelMessage, "gm", message_tags(channel.id, None))
+            .to_event(&keys)
+            .unwrap();
+        let note = EventBuilder::text_note("gm", []).to_event(&keys).unwrap();
+
+        assert_eq!(channel_id(&channel), Some(channel.id));
+        assert_eq!(channel_id(&message), Some(channel.id));
+        assert_eq!(
+            channel_id(&update(&keys, &channel, "x", 1)),
+            Some(channel.id)
+        );
+        assert_eq!(channel_id(&note), None);
+    }
+
+    #[rstest]
+    fn test_message_tags() {
+        let keys = Keys::generate();
+        let channel_id = EventId::all_zeros();
+        let reply_to =
+            EventBuilder::new(Kind::ChannelMessage, "gm", message_tags(channel_id, None))
+                .to_event(&keys)
+                .unwrap();
+        let message = EventBuilder::new(
+            Kind::ChannelMessage,
+            "gm!",
+            message_tags(channel_id, Some(&reply_to)),
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let thread = ThreadTags::parse(&message);
+        assert_eq!(thread.root, Some(channel_id));
+        assert_eq!(thread.reply, Some(reply_to.id));
+        assert_eq!(
+            message.public_keys().copied().collect::<Vec<_>>(),
+            vec![keys.public_key()]
+        );
+    }
+
+    #[rstest]
+    fn test_joined_channels() {
+        let ids = [EventId::all_zeros(), EventId::from_slice(&[1; 32]).unwrap()];
+        let list = EventBuilder::new(
+            Kind::from(PUBLIC_CHATS_KIND),
+            "",
+            ids.iter().map(|id| Tag::event(*id)),
+        )
+        .to_event(&Keys::generate())
+        .unwrap();
+        assert_eq!(joined_channels(&list), ids);
+    }
+
+    #[rstest]
+    fn test_channels() {
+        let (creator, other) = (Keys::generate(), Keys::generate());
+        let channel = EventBuilder::channel(&metadata("nostr"))
+            .to_event(&creator)
+            .unwrap();
+        let mut channels = Channels::default();
+
+        // Updates are applied once the creator is known
+        channels.add(&update(&creator, &channel, "nostr-ja", 2));
+        assert_eq!(channels.name(&channel.id), None);
+        channels.add(&channel);
+        assert_eq!(channels.name(&channel.id), Some(String::from("nostr-ja")));
+
+        // Older updates and updates by others are ignored
+        channels.add(&update(&creator, &channel, "old", 1));
+        channels.add(&update(&other, &channel, "spam", 3));
+        assert_eq!(channels.name(&channel.id), Some(String::from("nostr-ja")));
+    }
+}
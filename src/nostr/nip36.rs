@@ -0,0 +1,33 @@
+use nostr_sdk::prelude::*;
+
+/// Returns the reason of the content warning of an event, which is empty if not given.
+pub fn content_warning(event: &Event) -> Option<String> {
+    event.tags.iter().find_map(|tag| match tag {
+        Tag::ContentWarning { reason } => Some(reason.clone().unwrap_or_default()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(vec![], None)]
+    #[case(vec![vec!["content-warning"]], Some(""))]
+    #[case(vec![vec!["content-warning", "spoiler"]], Some("spoiler"))]
+    #[case(vec![vec!["t", "nostr"], vec!["content-warning", "nsfw"]], Some("nsfw"))]
+    fn test_content_warning(#[case] tags: Vec<Vec<&str>>, #[case] expected: Option<&str>) {
+        let tags: Vec<Tag> = tags
+            .into_iter()
+            .map(|tag| Tag::parse(tag).unwrap())
+            .collect();
+        let event = EventBuilder::text_note("hello", tags)
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(content_warning(&event), expected.map(String::from));
+    }
+}
@@ -0,0 +1,331 @@
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const MILLISATS_PER_BTC: u64 = 100_000_000_000;
+
+/// Who zapped and how much, decoded from a zap receipt once it is received.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Zap {
+    pub sender: Option<PublicKey>,
+    pub millisats: u64,
+}
+
+impl Zap {
+    /// Decodes the receipt, which counts as zero millisats if no amount is found.
+    pub fn from_receipt(receipt: &Event) -> Self {
+        Self {
+            sender: sender(receipt),
+            millisats: millisats(receipt).unwrap_or_default(),
+        }
+    }
+}
+
+/// A recipient of the zaps to a note, which shares them with the others by weight (zap splits).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZapSplit {
+    pub pubkey: PublicKey,
+    pub relay: Option<String>,
+    pub weight: Option<u64>,
+}
+
+impl ZapSplit {
+    /// Parses the `zap` tags of the note, which are empty when zaps go to its author.
+    pub fn parse(event: &Event) -> Vec<Self> {
+        event
+            .tags
+            .iter()
+            .filter_map(|tag| match tag.as_vec().as_slice() {
+                [kind, pubkey, rest @ ..] if kind == "zap" => Some(Self {
+                    pubkey: PublicKey::from_hex(pubkey).ok()?,
+                    relay: rest.first().filter(|relay| !relay.is_empty()).cloned(),
+                    weight: rest.get(1).and_then(|weight| weight.parse().ok()),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the percentage of zaps which each recipient receives.
+    ///
+    /// Zaps are split equally without weights, and only among the weighted recipients if some
+    /// of them have one.
+    pub fn shares(splits: &[Self]) -> Vec<(PublicKey, f64)> {
+        let weighted = splits.iter().any(|split| split.weight.is_some());
+        let weights: Vec<(PublicKey, u64)> = splits
+            .iter()
+            .filter_map(|split| match split.weight {
+                Some(weight) => Some((split.pubkey, weight)),
+                None if !weighted => Some((split.pubkey, 1)),
+                None => None,
+            })
+            .collect();
+        let total: u64 = weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return vec![];
+        }
+        weights
+            .into_iter()
+            .map(|(pubkey, weight)| (pubkey, weight as f64 * 100.0 / total as f64))
+            .collect()
+    }
+}
+
+/// Returns the amount zapped by the receipt in millisats.
+///
+/// The bolt11 invoice is the source of truth, so the `amount` tags on the receipt
+/// and on the embedded zap request are only used as a fallback.
+pub fn millisats(receipt: &Event) -> Option<u64> {
+    bolt11_millisats(receipt)
+        .or_else(|| amount_tag(&receipt.tags))
+        .or_else(|| zap_request_millisats(receipt))
+}
+
+/// Returns the author of the zap request embedded in the receipt, i.e. who zapped.
+///
+/// The receipt itself is signed by the lightning wallet of the recipient.
+pub fn sender(receipt: &Event) -> Option<PublicKey> {
+    zap_request(receipt).map(|request| request.pubkey)
+}
+
+fn zap_request(receipt: &Event) -> Option<Event> {
+    receipt.tags.iter().find_map(|tag| match tag {
+        Tag::Description(description) => Event::from_json(description).ok(),
+        _ => None,
+    })
+}
+
+fn bolt11_millisats(receipt: &Event) -> Option<u64> {
+    receipt.tags.iter().find_map(|tag| match tag {
+        Tag::Bolt11(invoice) => parse_bolt11_millisats(invoice),
+        _ => None,
+    })
+}
+
+fn zap_request_millisats(receipt: &Event) -> Option<u64> {
+    zap_request(receipt).and_then(|request| amount_tag(&request.tags))
+}
+
+fn amount_tag(tags: &[Tag]) -> Option<u64> {
+    tags.iter().find_map(|tag| match tag {
+        Tag::Amount { millisats, .. } => Some(*millisats),
+        _ => None,
+    })
+}
+
+/// Parses the amount in the human-readable part of a bolt11 invoice (e.g. `lnbc2500u1...`).
+pub fn parse_bolt11_millisats(invoice: &str) -> Option<u64> {
+    let invoice = invoice.trim().to_ascii_lowercase();
+    let invoice = invoice.strip_prefix("lightning:").unwrap_or(&invoice);
+    let separator = invoice.rfind('1')?;
+    let hrp = invoice[..separator].strip_prefix("ln")?;
+
+    // Skip the currency prefix (bc, tb, bcrt, ...)
+    let amount_start = hrp.find(|c: char| c.is_ascii_digit())?;
+    let amount = &hrp[amount_start..];
+    let (digits, multiplier) = match amount.chars().last()? {
+        c if c.is_ascii_digit() => (amount, None),
+        c => (&amount[..amount.len() - 1], Some(c)),
+    };
+    let value: u64 = digits.parse().ok()?;
+
+    match multiplier {
+        None => value.checked_mul(MILLISATS_PER_BTC),
+        Some('m') => value.checked_mul(MILLISATS_PER_BTC / 1_000),
+        Some('u') => value.checked_mul(MILLISATS_PER_BTC / 1_000_000),
+        Some('n') => value.checked_mul(MILLISATS_PER_BTC / 1_000_000_000),
+        // 1 pico-BTC = 0.1 millisats, so only multiples of 10 are valid
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("", None)]
+    #[case("hello", None)]
+    #[case(
+        "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        None
+    )]
+    #[case(
+        "lnbc2500u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        Some(250_000_000)
+    )]
+    #[case(
+        "LNBC2500U1PVJLUEZPP5QQQSYQCYQ5RQWZQFQQQSYQCYQ5RQWZQFQQQSYQCYQ5RQWZQFQYPQ",
+        Some(250_000_000)
+    )]
+    #[case(
+        "lightning:lnbc20m1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzq",
+        Some(2_000_000_000)
+    )]
+    #[case(
+        "lnbc21n1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        Some(2_100)
+    )]
+    #[case(
+        "lnbc10p1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        Some(1)
+    )]
+    #[case(
+        "lnbc15p1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        None
+    )]
+    #[case(
+        "lntb1m1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        Some(100_000_000)
+    )]
+    #[case(
+        "lnbcrt5u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+        Some(500_000)
+    )]
+    fn test_parse_bolt11_millisats(#[case] invoice: &str, #[case] expected: Option<u64>) {
+        assert_eq!(parse_bolt11_millisats(invoice), expected);
+    }
+
+    #[rstest]
+    #[case(vec![], None)]
+    #[case(vec![Tag::Bolt11(String::from("lnbc21n1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq"))], Some(2_100))]
+    #[case(vec![Tag::Amount { millisats: 21_000, bolt11: None }], Some(21_000))]
+    #[case(
+        vec![
+            Tag::Amount { millisats: 21_000, bolt11: None },
+            Tag::Bolt11(String::from("lnbc21n1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq")),
+        ],
+        Some(2_100)
+    )]
+    fn test_zap_receipt_millisats(#[case] tags: Vec<Tag>, #[case] expected: Option<u64>) {
+        let receipt = EventBuilder::new(Kind::ZapReceipt, "", tags)
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(millisats(&receipt), expected);
+    }
+
+    #[rstest]
+    fn test_zap_receipt_millisats_from_zap_request() {
+        let keys = Keys::generate();
+        let request = EventBuilder::new(
+            Kind::ZapRequest,
+            "",
+            [Tag::Amount {
+                millisats: 42_000,
+                bolt11: None,
+            }],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let receipt =
+            EventBuilder::new(Kind::ZapReceipt, "", [Tag::Description(request.as_json())])
+                .to_event(&keys)
+                .unwrap();
+        assert_eq!(millisats(&receipt), Some(42_000));
+    }
+
+    #[rstest]
+    #[case(vec![None, None], vec![50.0, 50.0])]
+    #[case(vec![Some(1), Some(3)], vec![25.0, 75.0])]
+    #[case(vec![Some(1), None], vec![100.0])]
+    #[case(vec![Some(0)], vec![])]
+    fn test_zap_split_shares(#[case] weights: Vec<Option<u64>>, #[case] expected: Vec<f64>) {
+        let splits: Vec<ZapSplit> = weights
+            .into_iter()
+            .map(|weight| ZapSplit {
+                pubkey: Keys::generate().public_key(),
+                relay: None,
+                weight,
+            })
+            .collect();
+        let shares: Vec<f64> = ZapSplit::shares(&splits)
+            .into_iter()
+            .map(|(_, share)| share)
+            .collect();
+        assert_eq!(shares, expected);
+    }
+
+    #[rstest]
+    fn test_zap_split_parse() {
+        let (alice, bob) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let tags = [
+            vec!["zap", &alice.to_hex(), "wss://nos.lol", "2"],
+            vec!["zap", &bob.to_hex()],
+            vec!["zap", "invalid", "", "1"],
+        ]
+        .map(|tag| Tag::parse(tag).unwrap());
+        let note = EventBuilder::text_note("gm", tags)
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(
+            ZapSplit::parse(&note),
+            vec![
+                ZapSplit {
+                    pubkey: alice,
+                    relay: Some(String::from("wss://nos.lol")),
+                    weight: Some(2),
+                },
+                ZapSplit {
+                    pubkey: bob,
+                    relay: None,
+                    weight: None,
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_zap_receipt_sender() {
+        let zapper = Keys::generate();
+        let wallet = Keys::generate();
+        let request = EventBuilder::new(Kind::ZapRequest, "", [])
+            .to_event(&zapper)
+            .unwrap();
+        let receipt =
+            EventBuilder::new(Kind::ZapReceipt, "", [Tag::Description(request.as_json())])
+                .to_event(&wallet)
+                .unwrap();
+        assert_eq!(sender(&receipt), Some(zapper.public_key()));
+
+        let receipt = EventBuilder::new(Kind::ZapReceipt, "", [])
+            .to_event(&wallet)
+            .unwrap();
+        assert_eq!(sender(&receipt), None);
+    }
+
+    #[rstest]
+    fn test_zap_from_receipt() {
+        let sender = Keys::generate();
+        let wallet = Keys::generate();
+        let request = EventBuilder::new(Kind::ZapRequest, "", [])
+            .to_event(&sender)
+            .unwrap();
+        let receipt = EventBuilder::new(
+            Kind::ZapReceipt,
+            "",
+            [
+                Tag::Description(request.as_json()),
+                Tag::Bolt11(String::from(
+                    "lnbc21n1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+                )),
+            ],
+        )
+        .to_event(&wallet)
+        .unwrap();
+        assert_eq!(
+            Zap::from_receipt(&receipt),
+            Zap {
+                sender: Some(sender.public_key()),
+                millisats: 2_100
+            }
+        );
+
+        let receipt = EventBuilder::new(Kind::ZapReceipt, "", [])
+            .to_event(&wallet)
+            .unwrap();
+        assert_eq!(Zap::from_receipt(&receipt), Zap::default());
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use nostr_sdk::prelude::*;
+
+/// Number of read relays of a user to deliver to, as relay lists may be long.
+pub const MAX_INBOX_RELAYS: usize = 4;
+
+/// Returns the relays which a user reads from (i.e. inboxes) in a relay list (NIP-65).
+///
+/// Relays without a marker are both read and written. URLs are normalized as the ones of receipts.
+pub fn read_relays(relay_list: &Event) -> Vec<String> {
+    let mut relays: Vec<String> = Vec::new();
+    for (url, metadata) in nostr_sdk::nips::nip65::extract_relay_list(relay_list) {
+        if metadata == Some(RelayMetadata::Write) {
+            continue;
+        }
+        let Ok(url) = Url::parse(&url.to_string()) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "ws" | "wss") {
+            continue;
+        }
+        let url = url.to_string();
+        if !relays.contains(&url) {
+            relays.push(url);
+        }
+    }
+    relays
+}
+
+/// Users mentioned by an event, except its author.
+fn recipients(event: &Event) -> Vec<PublicKey> {
+    let mut pubkeys: Vec<PublicKey> = Vec::new();
+    for tag in event.tags.iter() {
+        if let Tag::PublicKey {
+            public_key,
+            uppercase: false,
+            ..
+        } = tag
+        {
+            if *public_key != event.pubkey && !pubkeys.contains(public_key) {
+                pubkeys.push(*public_key);
+            }
+        }
+    }
+    pubkeys
+}
+
+/// Routes my events to the read relays of the users they mention, so that they see them
+/// even if we share no relays.
+#[derive(Debug, Default)]
+pub struct Inboxes {
+    /// Read relays by user and when their relay list was created
+    relays: HashMap<PublicKey, (Timestamp, Vec<String>)>,
+    /// Events waiting for the relay list of a user
+    pending: HashMap<PublicKey, Vec<Event>>,
+}
+
+impl Inboxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn inboxes(&self, pubkey: &PublicKey, exclude: &[String]) -> Vec<String> {
+        self.relays
+            .get(pubkey)
+            .map(|(_, relays)| {
+                relays
+                    .iter()
+                    .filter(|relay| !exclude.contains(relay))
+                    .take(MAX_INBOX_RELAYS)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the inbox relays of the users mentioned by the event except `exclude`,
+    /// and the users whose relay lists have to be fetched.
+    ///
+    /// The event waits for the missing relay lists, to be delivered by [`Inboxes::add`].
+    pub fn route(&mut self, event: &Event, exclude: &[String]) -> (Vec<String>, Vec<PublicKey>) {
+        let mut relays: Vec<String> = Vec::new();
+        let mut missing: Vec<PublicKey> = Vec::new();
+        for pubkey in recipients(event) {
+            if self.relays.contains_key(&pubkey) {
+                for relay in self.inboxes(&pubkey, exclude) {
+                    if !relays.contains(&relay) {
+                        relays.push(relay);
+                    }
+                }
+                continue;
+            }
+
+            // The relay list has been requested already if some events are waiting for it
+            let waiting = self.pending.entry(pubkey).or_default();
+            if waiting.is_empty() {
+                missing.push(pubkey);
+            }
+            waiting.push(event.clone());
+        }
+        (relays, missing)
+    }
+
+    /// Adds a relay list (kind 10002), returning the events which were waiting for it
+    /// with the inbox relays to deliver them to.
+    pub fn add(&mut self, relay_list: &Event, exclude: &[String]) -> Vec<(Event, Vec<String>)> {
+        if relay_list.kind != Kind::RelayList {
+            return vec![];
+        }
+        if let Some((created_at, _)) = self.relays.get(&relay_list.pubkey) {
+            if *created_at >= relay_list.created_at {
+                return vec![];
+            }
+        }
+
+        self.relays.insert(
+            relay_list.pubkey,
+            (relay_list.created_at, read_relays(relay_list)),
+        );
+        let relays = self.inboxes(&relay_list.pubkey, exclude);
+        let waiting = self.pending.remove(&relay_list.pubkey).unwrap_or_default();
+        if relays.is_empty() {
+            return vec![];
+        }
+        waiting
+            .into_iter()
+            .map(|event| (event, relays.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn relay_list(keys: &Keys, relays: &[(&str, Option<RelayMetadata>)]) -> Event {
+        let relays = relays
+            .iter()
+            .map(|(url, metadata)| (UncheckedUrl::from(*url), metadata.clone()));
+        EventBuilder::relay_list(relays).to_event(keys).unwrap()
+    }
+
+    fn mention(keys: &Keys, pubkeys: &[PublicKey]) -> Event {
+        let tags = pubkeys.iter().map(|pubkey| Tag::public_key(*pubkey));
+        EventBuilder::text_note("hello", tags)
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_read_relays() {
+        let event = relay_list(
+            &Keys::generate(),
+            &[
+                ("wss://both.example.com", None),
+                ("wss://read.example.com", Some(RelayMetadata::Read)),
+                ("wss://write.example.com", Some(RelayMetadata::Write)),
+                ("wss://both.example.com/", None),
+                ("https://example.com", None),
+                ("not a url", None),
+            ],
+        );
+        assert_eq!(
+            read_relays(&event),
+            vec![
+                String::from("wss://both.example.com/"),
+                String::from("wss://read.example.com/"),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_inboxes() {
+        let me = Keys::generate();
+        let (alice, bob) = (Keys::generate(), Keys::generate());
+        let exclude = vec![String::from("wss://mine.example.com/")];
+        let mut inboxes = Inboxes::new();
+
+        // Relay lists are fetched once and events wait for them
+        let first = mention(&me, &[alice.public_key(), me.public_key()]);
+        let second = mention(&me, &[alice.public_key(), bob.public_key()]);
+        assert_eq!(
+            inboxes.route(&first, &exclude),
+            (vec![], vec![alice.public_key()])
+        );
+        assert_eq!(
+            inboxes.route(&second, &exclude),
+            (vec![], vec![bob.public_key()])
+        );
+
+        let alice_list = relay_list(
+            &alice,
+            &[
+                ("wss://mine.example.com", None),
+                ("wss://alice.example.com", Some(RelayMetadata::Read)),
+            ],
+        );
+        let alice_inboxes = vec![String::from("wss://alice.example.com/")];
+        assert_eq!(
+            inboxes.add(&alice_list, &exclude),
+            vec![
+                (first.clone(), alice_inboxes.clone()),
+                (second.clone(), alice_inboxes.clone())
+            ]
+        );
+        assert_eq!(inboxes.add(&alice_list, &exclude), vec![]);
+
+        // Users without inboxes to deliver to are known as well
+        let bob_list = relay_list(&bob, &[("wss://mine.example.com", None)]);
+        assert_eq!(inboxes.add(&bob_list, &exclude), vec![]);
+        assert_eq!(inboxes.route(&second, &exclude), (alice_inboxes, vec![]));
+    }
+}
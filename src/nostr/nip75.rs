@@ -0,0 +1,134 @@
+use nostr_sdk::prelude::*;
+
+use super::nip57::Zap;
+
+/// Kind of zap goals (NIP-75).
+pub const ZAP_GOAL_KIND: u64 = 9041;
+
+/// A fundraising goal, which the zaps to the goal event count towards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZapGoal {
+    pub description: String,
+    pub millisats: u64,
+    pub closed_at: Option<Timestamp>,
+}
+
+impl ZapGoal {
+    /// Parses the goal event, which needs an amount to raise.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.kind != Kind::from(ZAP_GOAL_KIND) {
+            return None;
+        }
+
+        let millisats = event.tags.iter().find_map(|tag| match tag {
+            Tag::Amount { millisats, .. } => Some(*millisats),
+            _ => None,
+        })?;
+        let closed_at = event
+            .tags
+            .iter()
+            .find_map(|tag| match tag.as_vec().as_slice() {
+                [kind, closed_at, ..] if kind == "closed_at" => closed_at.parse::<u64>().ok(),
+                _ => None,
+            })
+            .map(Timestamp::from);
+        Some(Self {
+            description: event.content.clone(),
+            millisats,
+            closed_at,
+        })
+    }
+
+    /// Returns the goal which the note raises funds for with a `goal` tag.
+    pub fn linked(event: &Event) -> Option<EventId> {
+        event
+            .tags
+            .iter()
+            .find_map(|tag| match tag.as_vec().as_slice() {
+                [kind, id, ..] if kind == "goal" => EventId::from_hex(id).ok(),
+                _ => None,
+            })
+    }
+
+    /// Returns the millisats raised by the zaps and how much of the goal they reach, from 0 to 1.
+    pub fn progress(&self, zaps: &[Zap]) -> (u64, f64) {
+        let raised: u64 = zaps.iter().map(|zap| zap.millisats).sum();
+        if self.millisats == 0 {
+            return (raised, 1.0);
+        }
+        (raised, (raised as f64 / self.millisats as f64).min(1.0))
+    }
+
+    pub fn is_closed(&self, now: Timestamp) -> bool {
+        self.closed_at.is_some_and(|closed_at| closed_at <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn goal(tags: Vec<Tag>) -> Event {
+        EventBuilder::new(Kind::from(ZAP_GOAL_KIND), "New relay", tags)
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_from_event() {
+        let amount = Tag::Amount {
+            millisats: 100_000,
+            bolt11: None,
+        };
+        let closed_at = Tag::parse(vec!["closed_at", "1700000000"]).unwrap();
+        assert_eq!(
+            ZapGoal::from_event(&goal(vec![amount.clone(), closed_at])),
+            Some(ZapGoal {
+                description: String::from("New relay"),
+                millisats: 100_000,
+                closed_at: Some(Timestamp::from(1_700_000_000)),
+            })
+        );
+        assert_eq!(ZapGoal::from_event(&goal(vec![])), None);
+
+        let note = EventBuilder::text_note("New relay", [amount])
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(ZapGoal::from_event(&note), None);
+    }
+
+    #[rstest]
+    fn test_linked() {
+        let id = goal(vec![]).id;
+        let note = EventBuilder::text_note(
+            "Help us",
+            [Tag::parse(vec!["goal", &id.to_hex(), "wss://nos.lol"]).unwrap()],
+        )
+        .to_event(&Keys::generate())
+        .unwrap();
+        assert_eq!(ZapGoal::linked(&note), Some(id));
+    }
+
+    #[rstest]
+    #[case(vec![], (0, 0.0))]
+    #[case(vec![21_000, 4_000], (25_000, 0.25))]
+    #[case(vec![200_000], (200_000, 1.0))]
+    fn test_progress(#[case] zaps: Vec<u64>, #[case] expected: (u64, f64)) {
+        let goal = ZapGoal {
+            description: String::new(),
+            millisats: 100_000,
+            closed_at: None,
+        };
+        let zaps: Vec<Zap> = zaps
+            .into_iter()
+            .map(|millisats| Zap {
+                sender: None,
+                millisats,
+            })
+            .collect();
+        assert_eq!(goal.progress(&zaps), expected);
+    }
+}
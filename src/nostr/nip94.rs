@@ -0,0 +1,130 @@
+use nostr_sdk::prelude::*;
+
+/// Kinds of video events (NIP-71): normal and short videos, and their former addressable kinds.
+pub const VIDEO_KINDS: [u64; 4] = [21, 22, 34235, 34236];
+
+/// Kinds of the file metadata (NIP-94) and video events shown as files in timelines.
+pub fn kinds() -> Vec<Kind> {
+    let videos = VIDEO_KINDS.into_iter().map(Kind::from);
+    [Kind::FileMetadata].into_iter().chain(videos).collect()
+}
+
+pub fn is_file(kind: Kind) -> bool {
+    kinds().contains(&kind)
+}
+
+/// A shared file, described by a file metadata event (NIP-94) or a video event (NIP-71).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub url: String,
+    /// Title of the file, or the last segment of its URL, e.g. `report.pdf`
+    pub name: String,
+    pub mime_type: Option<String>,
+    /// Bytes of the file
+    pub size: Option<u64>,
+    pub video: bool,
+}
+
+impl FileMetadata {
+    /// Parses the file of the event, which needs a URL. Video events may give it in an `imeta`
+    /// tag instead of a `url` tag.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !is_file(event.kind) {
+            return None;
+        }
+
+        let tags: Vec<Vec<String>> = event.tags.iter().map(|tag| tag.as_vec()).collect();
+        let value = |name: &str| {
+            let tag = tags.iter().find_map(|tag| match tag.as_slice() {
+                [kind, value, ..] if kind == name => Some(value.clone()),
+                _ => None,
+            });
+            tag.or_else(|| {
+                tags.iter()
+                    .filter(|tag| tag.first().is_some_and(|kind| kind == "imeta"))
+                    .flat_map(|tag| tag.iter().skip(1))
+                    .find_map(|entry| entry.strip_prefix(&format!("{name} ")).map(String::from))
+            })
+        };
+
+        let url = value("url")?;
+        let name = value("title")
+            .filter(|title| !title.is_empty())
+            .or_else(|| {
+                Url::parse(&url)
+                    .ok()?
+                    .path_segments()?
+                    .next_back()
+                    .filter(|segment| !segment.is_empty())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| url.clone());
+        Some(Self {
+            name,
+            mime_type: value("m"),
+            size: value("size").and_then(|size| size.parse().ok()),
+            video: event.kind != Kind::FileMetadata,
+            url,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn event(kind: u64, tags: Vec<Vec<&str>>) -> Event {
+        let tags = tags.into_iter().map(|tag| Tag::parse(tag).unwrap());
+        EventBuilder::new(Kind::from(kind), "", tags)
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    fn file(url: &str, name: &str, mime_type: Option<&str>, size: Option<u64>) -> FileMetadata {
+        FileMetadata {
+            url: url.to_string(),
+            name: name.to_string(),
+            mime_type: mime_type.map(String::from),
+            size,
+            video: false,
+        }
+    }
+
+    #[rstest]
+    #[case(
+        1063,
+        vec![vec!["url", "https://example.com/a/report.pdf"], vec!["m", "application/pdf"], vec!["size", "1024"]],
+        Some(file("https://example.com/a/report.pdf", "report.pdf", Some("application/pdf"), Some(1024)))
+    )]
+    #[case(
+        21,
+        vec![
+            vec!["title", "A talk"],
+            vec!["imeta", "url https://example.com/talk.mp4", "m video/mp4"],
+        ],
+        Some(FileMetadata {
+            video: true,
+            ..file("https://example.com/talk.mp4", "A talk", Some("video/mp4"), None)
+        })
+    )]
+    #[case(
+        34235,
+        vec![vec!["url", "https://example.com/"], vec!["size", "big"]],
+        Some(FileMetadata {
+            video: true,
+            ..file("https://example.com/", "https://example.com/", None, None)
+        })
+    )]
+    #[case(1063, vec![vec!["m", "image/png"]], None)]
+    #[case(1, vec![vec!["url", "https://example.com/a.png"]], None)]
+    fn test_from_event(
+        #[case] kind: u64,
+        #[case] tags: Vec<Vec<&str>>,
+        #[case] expected: Option<FileMetadata>,
+    ) {
+        assert_eq!(FileMetadata::from_event(&event(kind, tags)), expected);
+    }
+}
@@ -1,8 +1,9 @@
 use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::text::shorten_hex;
+use crate::{i18n, text::shorten_hex};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Profile {
     pub pubkey: PublicKey,
     pub created_at: Timestamp,
@@ -31,3 +32,162 @@ impl Profile {
         }
     }
 }
+
+/// Fields of a profile editable in the profile form.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProfileField {
+    Name,
+    DisplayName,
+    About,
+    Picture,
+    Nip05,
+    Lud16,
+}
+
+impl ProfileField {
+    pub const ALL: [Self; 6] = [
+        Self::Name,
+        Self::DisplayName,
+        Self::About,
+        Self::Picture,
+        Self::Nip05,
+        Self::Lud16,
+    ];
+
+    /// The key of the field in the metadata JSON.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::DisplayName => "display_name",
+            Self::About => "about",
+            Self::Picture => "picture",
+            Self::Nip05 => "nip05",
+            Self::Lud16 => "lud16",
+        }
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Self::About)
+    }
+
+    fn field_mut<'a>(&self, metadata: &'a mut Metadata) -> &'a mut Option<String> {
+        match self {
+            Self::Name => &mut metadata.name,
+            Self::DisplayName => &mut metadata.display_name,
+            Self::About => &mut metadata.about,
+            Self::Picture => &mut metadata.picture,
+            Self::Nip05 => &mut metadata.nip05,
+            Self::Lud16 => &mut metadata.lud16,
+        }
+    }
+
+    pub fn get(&self, metadata: &Metadata) -> String {
+        let value = match self {
+            Self::Name => &metadata.name,
+            Self::DisplayName => &metadata.display_name,
+            Self::About => &metadata.about,
+            Self::Picture => &metadata.picture,
+            Self::Nip05 => &metadata.nip05,
+            Self::Lud16 => &metadata.lud16,
+        };
+        value.clone().unwrap_or_default()
+    }
+
+    /// Sets the field, removing it if the value is blank.
+    pub fn set(&self, metadata: &mut Metadata, value: &str) {
+        let value = if self.is_multiline() {
+            value.trim_end()
+        } else {
+            value.trim()
+        };
+        *self.field_mut(metadata) = (!value.is_empty()).then(|| value.to_owned());
+    }
+
+    /// Checks the value of the field. Blank values are valid as they remove the field.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            Self::Picture => match Url::parse(value) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+                _ => Err(i18n::tr("Picture must be an http or https URL")),
+            },
+            // NIP-05 restricts the local part to `a-z0-9-_.`
+            Self::Nip05
+                if !is_internet_identifier(value, |c| {
+                    c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c)
+                }) =>
+            {
+                Err(i18n::tr("NIP-05 must be like name@example.com"))
+            }
+            Self::Lud16 if !is_internet_identifier(value, |c| !c.is_whitespace()) => {
+                Err(i18n::tr("Lightning address must be like name@example.com"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether a value is like `local@example.com`.
+fn is_internet_identifier(value: &str, is_local_char: impl Fn(char) -> bool) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    let labels: Vec<&str> = domain.split('.').collect();
+    !local.is_empty()
+        && local.chars().all(is_local_char)
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_profile_field_set() {
+        let mut metadata = Metadata::new()
+            .name("alice")
+            .website(Url::parse("https://example.com").unwrap());
+        ProfileField::Name.set(&mut metadata, "  bob ");
+        ProfileField::About.set(&mut metadata, "  hello\nworld\n");
+        ProfileField::Nip05.set(&mut metadata, "bob@example.com");
+        ProfileField::Nip05.set(&mut metadata, " ");
+
+        assert_eq!(ProfileField::Name.get(&metadata), "bob");
+        assert_eq!(ProfileField::About.get(&metadata), "  hello\nworld");
+        assert_eq!(metadata.nip05, None);
+        // Fields out of the form are kept
+        assert_eq!(metadata.website, Some(String::from("https://example.com/")));
+    }
+
+    #[rstest]
+    #[case(ProfileField::Name, "any name", true)]
+    #[case(ProfileField::Picture, "", true)]
+    #[case(ProfileField::Picture, "https://example.com/a.png", true)]
+    #[case(ProfileField::Picture, "ftp://example.com/a.png", false)]
+    #[case(ProfileField::Picture, "a.png", false)]
+    #[case(ProfileField::Nip05, "_@example.com", true)]
+    #[case(ProfileField::Nip05, "bob.b-c@sub.example.com", true)]
+    #[case(ProfileField::Nip05, "Bob@example.com", false)]
+    #[case(ProfileField::Nip05, "bob@localhost", false)]
+    #[case(ProfileField::Nip05, "example.com", false)]
+    #[case(ProfileField::Lud16, "Bob@getalby.com", true)]
+    #[case(ProfileField::Lud16, "bob@example..com", false)]
+    #[case(ProfileField::Lud16, "b ob@example.com", false)]
+    fn test_profile_field_validate(
+        #[case] field: ProfileField,
+        #[case] value: &str,
+        #[case] valid: bool,
+    ) {
+        assert_eq!(field.validate(value).is_ok(), valid);
+    }
+}
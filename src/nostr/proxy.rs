@@ -0,0 +1,78 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::Url;
+
+/// Parses the address of a SOCKS5 proxy, e.g. `socks5://127.0.0.1:9050` or `localhost:9050`.
+pub fn parse(proxy: &str) -> Result<SocketAddr> {
+    let address = ["socks5://", "socks5h://"]
+        .iter()
+        .find_map(|scheme| proxy.strip_prefix(scheme))
+        .unwrap_or(proxy)
+        .trim_end_matches('/');
+    if address.contains("://") {
+        return Err(eyre!("Only SOCKS5 proxies are supported: {proxy}"));
+    }
+
+    address
+        .to_socket_addrs()
+        .map_err(|e| eyre!("Invalid proxy {proxy}: {e}"))?
+        .next()
+        .ok_or_else(|| eyre!("Invalid proxy {proxy}: no address"))
+}
+
+/// Returns whether the relay is a Tor onion service, which is reachable only through the proxy.
+pub fn is_onion(relay: &str) -> bool {
+    Url::parse(relay)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.ends_with(".onion")))
+        .unwrap_or(false)
+}
+
+/// Splits relays into the ones to connect to and onion services skipped without a proxy.
+pub fn reachable_relays<T>(relays: &[T], proxied: bool) -> (Vec<T>, Vec<T>)
+where
+    T: AsRef<str> + Clone,
+{
+    relays
+        .iter()
+        .cloned()
+        .partition(|relay| proxied || !is_onion(relay.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("socks5://127.0.0.1:9050", Some("127.0.0.1:9050"))]
+    #[case("socks5h://127.0.0.1:9150/", Some("127.0.0.1:9150"))]
+    #[case("127.0.0.1:9050", Some("127.0.0.1:9050"))]
+    #[case("[::1]:9050", Some("[::1]:9050"))]
+    #[case("http://127.0.0.1:8080", None)]
+    #[case("127.0.0.1", None)]
+    fn test_parse(#[case] proxy: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            parse(proxy).ok(),
+            expected.map(|addr| addr.parse().unwrap())
+        );
+    }
+
+    #[rstest]
+    fn test_reachable_relays() {
+        let onion =
+            String::from("ws://abcdefghijklmnopqrstuvwxyz234567abcdefghijklmnopqrstuv.onion");
+        let relays = vec![String::from("wss://nos.lol"), onion.clone()];
+        assert!(is_onion(&onion));
+        assert!(!is_onion("wss://nos.lol"));
+
+        assert_eq!(
+            reachable_relays(&relays, false),
+            (vec![String::from("wss://nos.lol")], vec![onion])
+        );
+        assert_eq!(reachable_relays(&relays, true), (relays, vec![]));
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use nostr_sdk::prelude::*;
+
+use crate::outbox::RejectReason;
+
+/// Time to wait for the OK message of a relay before sending the event again.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before the first retry, which doubles with each of the next ones.
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(120);
+
+/// Attempts to send an event to a relay, including the first one.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Words of NOTICE messages which ask to send less often, as relays don't agree on a prefix.
+const RATE_LIMIT_WORDS: [&str; 5] = [
+    "rate-limit",
+    "rate limit",
+    "slow down",
+    "too many",
+    "too fast",
+];
+
+#[derive(Debug, Clone)]
+struct Delivery {
+    event: Event,
+    attempts: u32,
+    sent_at: Instant,
+    /// Time to send the event again, or `None` while waiting for the OK message
+    retry_at: Option<Instant>,
+}
+
+/// A relay which asked to slow down.
+#[derive(Debug, Clone, Copy)]
+struct Hold {
+    until: Instant,
+    /// Times the relay asked since it last accepted an event
+    count: u32,
+}
+
+/// Events to send again and relays given up on, returned by [`SendQueue::poll`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Due {
+    pub retries: Vec<(Event, Vec<Url>)>,
+    /// Relays which didn't respond to the last attempt
+    pub expired: Vec<(EventId, Url)>,
+}
+
+/// Sends events again to relays which failed to accept them, backing off exponentially
+/// for each relay and while a relay asks to slow down.
+#[derive(Debug, Default)]
+pub struct SendQueue {
+    deliveries: HashMap<(EventId, Url), Delivery>,
+    holds: HashMap<Url, Hold>,
+}
+
+impl SendQueue {
+    /// Records an event sent to the relays, replacing earlier attempts, e.g. for a manual retry.
+    pub fn track(&mut self, event: &Event, relays: impl IntoIterator<Item = Url>, now: Instant) {
+        for relay in relays {
+            let delivery = Delivery {
+                event: event.clone(),
+                attempts: 1,
+                sent_at: now,
+                retry_at: None,
+            };
+            self.deliveries.insert((event.id, relay), delivery);
+        }
+    }
+
+    /// Handles an OK message. Returns `true` if the event is sent again, so that the rejection
+    /// is not final yet.
+    pub fn receipt(
+        &mut self,
+        event_id: EventId,
+        relay: &Url,
+        accepted: bool,
+        message: &str,
+        now: Instant,
+    ) -> bool {
+        let key = (event_id, relay.clone());
+        if !self.deliveries.contains_key(&key) {
+            return false;
+        }
+        if accepted {
+            self.deliveries.remove(&key);
+            self.holds.remove(relay);
+            return false;
+        }
+
+        let reason = RejectReason::parse(message);
+        if reason == Some(RejectReason::RateLimited) {
+            self.hold(relay, now);
+        }
+        // PoW has to be mined again, which is left to a manual retry from the outbox
+        let retryable = matches!(
+            reason,
+            None | Some(RejectReason::RateLimited | RejectReason::Error)
+        );
+        match self.deliveries.get_mut(&key) {
+            Some(delivery) if retryable && delivery.attempts < MAX_ATTEMPTS => {
+                delivery.retry_at = Some(now + backoff(delivery.attempts));
+                true
+            }
+            _ => {
+                self.deliveries.remove(&key);
+                false
+            }
+        }
+    }
+
+    /// Handles a NOTICE message, holding the relay if it asks to slow down.
+    pub fn notice(&mut self, relay: &Url, message: &str, now: Instant) {
+        let message = message.to_lowercase();
+        if RATE_LIMIT_WORDS.iter().any(|word| message.contains(word)) {
+            self.hold(relay, now);
+        }
+    }
+
+    fn hold(&mut self, relay: &Url, now: Instant) {
+        let count = self.holds.get(relay).map_or(0, |hold| hold.count) + 1;
+        let until = now + backoff(count);
+        self.holds.insert(relay.clone(), Hold { until, count });
+    }
+
+    /// Returns the events to send again now, and the relays which never responded.
+    pub fn poll(&mut self, now: Instant) -> Due {
+        let mut retries: BTreeMap<EventId, (Event, Vec<Url>)> = BTreeMap::new();
+        let mut expired = vec![];
+        let holds = &self.holds;
+        self.deliveries.retain(|(id, relay), delivery| {
+            if delivery.retry_at.is_none() && now >= delivery.sent_at + RESPONSE_TIMEOUT {
+                if delivery.attempts >= MAX_ATTEMPTS {
+                    expired.push((*id, relay.clone()));
+                    return false;
+                }
+                delivery.retry_at = Some(now + backoff(delivery.attempts));
+            }
+
+            let held = holds.get(relay).is_some_and(|hold| now < hold.until);
+            if delivery.retry_at.is_some_and(|at| now >= at) && !held {
+                delivery.attempts += 1;
+                delivery.sent_at = now;
+                delivery.retry_at = None;
+                retries
+                    .entry(*id)
+                    .or_insert_with(|| (delivery.event.clone(), vec![]))
+                    .1
+                    .push(relay.clone());
+            }
+            true
+        });
+
+        let mut retries: Vec<(Event, Vec<Url>)> = retries.into_values().collect();
+        for (_, relays) in &mut retries {
+            relays.sort();
+        }
+        expired.sort();
+        Due { retries, expired }
+    }
+}
+
+/// Delay after the attempt, e.g. 2s after the first one and 4s after the second one.
+fn backoff(attempts: u32) -> Duration {
+    let factor = 2_u32.saturating_pow(attempts.saturating_sub(1));
+    BASE_DELAY.saturating_mul(factor).min(MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn event() -> Event {
+        EventBuilder::text_note("hello", [])
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    fn relay(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[rstest]
+    #[case(1, 2)]
+    #[case(2, 4)]
+    #[case(4, 16)]
+    #[case(10, 120)]
+    #[case(100, 120)]
+    fn test_backoff(#[case] attempts: u32, #[case] expected: u64) {
+        assert_eq!(backoff(attempts), Duration::from_secs(expected));
+    }
+
+    #[rstest]
+    fn test_retry_with_backoff() {
+        let now = Instant::now();
+        let event = event();
+        let (a, b) = (relay("wss://a/"), relay("wss://b/"));
+        let mut queue = SendQueue::default();
+        queue.track(&event, [a.clone(), b.clone()], now);
+
+        assert!(!queue.receipt(event.id, &a, true, "", now));
+        assert!(queue.receipt(event.id, &b, false, "error: try again", now));
+        assert_eq!(queue.poll(now + Duration::from_secs(1)), Due::default());
+        assert_eq!(
+            queue.poll(now + Duration::from_secs(2)).retries,
+            vec![(event.clone(), vec![b.clone()])]
+        );
+
+        // The delay doubles
+        let now = now + Duration::from_secs(2);
+        assert!(queue.receipt(event.id, &b, false, "", now));
+        assert_eq!(queue.poll(now + Duration::from_secs(3)), Due::default());
+        assert_eq!(queue.poll(now + Duration::from_secs(4)).retries.len(), 1);
+
+        // Events are not sent again for final rejections, nor after they are accepted
+        assert!(!queue.receipt(event.id, &b, false, "blocked: banned", now));
+        assert!(!queue.receipt(event.id, &a, false, "error: again", now));
+        assert_eq!(queue.poll(now + MAX_DELAY), Due::default());
+    }
+
+    #[rstest]
+    fn test_give_up() {
+        let now = Instant::now();
+        let event = event();
+        let a = relay("wss://a/");
+        let mut queue = SendQueue::default();
+        queue.track(&event, [a.clone()], now);
+
+        let mut now = now;
+        for _ in 1..MAX_ATTEMPTS {
+            assert!(queue.receipt(event.id, &a, false, "error: again", now));
+            now += MAX_DELAY;
+            assert_eq!(queue.poll(now).retries.len(), 1);
+        }
+        assert!(!queue.receipt(event.id, &a, false, "error: again", now));
+        assert_eq!(queue.poll(now + MAX_DELAY), Due::default());
+    }
+
+    #[rstest]
+    fn test_response_timeout() {
+        let now = Instant::now();
+        let event = event();
+        let a = relay("wss://a/");
+        let mut queue = SendQueue::default();
+        queue.track(&event, [a.clone()], now);
+
+        // Relays which don't respond are tried again
+        let mut now = now;
+        for _ in 1..MAX_ATTEMPTS {
+            now += RESPONSE_TIMEOUT;
+            assert_eq!(queue.poll(now), Due::default());
+            now += MAX_DELAY;
+            assert_eq!(queue.poll(now).retries.len(), 1);
+        }
+        assert_eq!(
+            queue.poll(now + RESPONSE_TIMEOUT).expired,
+            vec![(event.id, a)]
+        );
+    }
+
+    #[rstest]
+    #[case("rate-limited: slow down", true)]
+    #[case("You are sending too many events", true)]
+    #[case("Rate limit exceeded", true)]
+    #[case("welcome!", false)]
+    fn test_notice(#[case] message: &str, #[case] holds: bool) {
+        let now = Instant::now();
+        let event = event();
+        let a = relay("wss://a/");
+        let mut queue = SendQueue::default();
+        queue.track(&event, [a.clone()], now);
+        assert!(queue.receipt(event.id, &a, false, "", now));
+
+        // The relay is held for longer than the retry, as it asked more than once
+        queue.notice(&a, message, now);
+        queue.notice(&a, message, now);
+        let retried = !queue.poll(now + BASE_DELAY).retries.is_empty();
+        assert_eq!(retried, !holds);
+        assert_eq!(
+            queue.poll(now + BASE_DELAY * 2).retries.len(),
+            usize::from(holds)
+        );
+    }
+}
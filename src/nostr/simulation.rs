@@ -0,0 +1,276 @@
+//! Replays relay notifications from a script instead of connecting to relays, for reproducible
+//! demos and tests of the whole app without network access.
+//!
+//! A script has a JSON object per line, delivered `at` milliseconds after the start:
+//!
+//! ```text
+//! {"at": 0, "tab": "home", "event": {"id": "...", "kind": 1, ...}}
+//! {"at": 1500, "event": {"id": "...", "kind": 0, ...}}
+//! ```
+//!
+//! Events with a `tab` (as given to `--tab`) feed the tab, and the others are delivered as
+//! fetched events. Empty lines and lines starting with `//` are skipped.
+
+use std::{collections::VecDeque, fs, path::Path, time::Duration};
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    nostr::{NostrCommand, Notification},
+    timeline::TimelineTabType,
+};
+
+/// Relay which published events are accepted by.
+const SIMULATED_RELAY: &str = "wss://simulation.invalid";
+
+/// Interval at which the virtual clock advances.
+const STEP_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Deserialize)]
+struct Line {
+    at: u64,
+    #[serde(default)]
+    tab: Option<String>,
+    event: Event,
+}
+
+/// A notification of the script and when it is delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub at: Duration,
+    pub notification: Notification,
+}
+
+/// Steps of a script in the order of delivery. Steps at the same time keep their order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut steps = vec![];
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let line: Line =
+                serde_json::from_str(line).map_err(|e| eyre!("Line {}: {e}", i + 1))?;
+            let notification = match line.tab {
+                Some(tab) => {
+                    let tab: TimelineTabType =
+                        tab.parse().map_err(|e| eyre!("Line {}: {e}", i + 1))?;
+                    Notification::Timeline(tab, line.event)
+                }
+                None => Notification::Fetched(line.event),
+            };
+            steps.push(Step {
+                at: Duration::from_millis(line.at),
+                notification,
+            });
+        }
+        steps.sort_by_key(|step| step.at);
+
+        Ok(Self { steps })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+/// Delivers the steps of a script as a virtual clock advances.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    steps: VecDeque<Step>,
+    clock: Duration,
+}
+
+impl Simulation {
+    pub fn new(script: Script) -> Self {
+        Self {
+            steps: script.steps.into(),
+            clock: Duration::ZERO,
+        }
+    }
+
+    /// Advances the virtual clock, returning the notifications due by then.
+    pub fn advance(&mut self, elapsed: Duration) -> Vec<Notification> {
+        self.clock += elapsed;
+        let due = self
+            .steps
+            .iter()
+            .take_while(|step| step.at <= self.clock)
+            .count();
+        self.steps
+            .drain(..due)
+            .map(|step| step.notification)
+            .collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Answers a command as relays would, if it needs an answer. Published events are accepted.
+    fn respond(command: NostrCommand) -> Option<Notification> {
+        match command {
+            NostrCommand::SendEvent(event) | NostrCommand::SendEventTo(_, event) => {
+                Some(Notification::Receipt(
+                    event.id,
+                    Url::parse(SIMULATED_RELAY).ok()?,
+                    true,
+                    String::new(),
+                ))
+            }
+            NostrCommand::InspectSubscriptions => Some(Notification::Subscriptions(vec![])),
+            NostrCommand::InspectKinds => Some(Notification::Kinds(vec![])),
+            command => {
+                tracing::debug!(%command, "Ignore a command in the simulation");
+                None
+            }
+        }
+    }
+
+    /// Replays the script in real time, with the same channels as
+    /// [`ConnectionProcess`](crate::nostr::ConnectionProcess).
+    pub fn spawn(
+        mut self,
+    ) -> (
+        tokio::sync::mpsc::UnboundedReceiver<Notification>,
+        tokio::sync::mpsc::UnboundedSender<NostrCommand>,
+        tokio::sync::mpsc::UnboundedSender<()>,
+    ) {
+        let (req_tx, req_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (command_tx, mut command_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (terminate_tx, mut terminate_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STEP_INTERVAL);
+            let mut last = tokio::time::Instant::now();
+            loop {
+                tokio::select! {
+                    now = interval.tick() => {
+                        for notification in self.advance(now - last) {
+                            req_tx.send(notification)?;
+                        }
+                        last = now;
+                    }
+                    Some(command) = command_rx.recv() => {
+                        if let Some(notification) = Self::respond(command) {
+                            req_tx.send(notification)?;
+                        }
+                    }
+                    _ = terminate_rx.recv() => break,
+                }
+            }
+
+            Ok::<(), tokio::sync::mpsc::error::SendError<Notification>>(())
+        });
+
+        (req_rx, command_tx, terminate_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn note(content: &str) -> Event {
+        EventBuilder::text_note(content, [])
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_parse() {
+        let (gm, gn, profile) = (note("gm"), note("gn"), note("profile"));
+        let content = format!(
+            "// A quiet morning\n\
+             {{\"at\": 2000, \"tab\": \"hashtag:nostr\", \"event\": {}}}\n\
+             \n\
+             {{\"at\": 0, \"tab\": \"home\", \"event\": {}}}\n\
+             {{\"at\": 2000, \"event\": {}}}\n",
+            gn.as_json(),
+            gm.as_json(),
+            profile.as_json()
+        );
+        let script = Script::parse(&content).unwrap();
+        assert_eq!(
+            script.steps,
+            vec![
+                Step {
+                    at: Duration::ZERO,
+                    notification: Notification::Timeline(TimelineTabType::Home, gm),
+                },
+                Step {
+                    at: Duration::from_secs(2),
+                    notification: Notification::Timeline(
+                        TimelineTabType::Hashtag {
+                            tag: String::from("nostr")
+                        },
+                        gn
+                    ),
+                },
+                Step {
+                    at: Duration::from_secs(2),
+                    notification: Notification::Fetched(profile),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("{\"at\": 0}", "Line 1: missing field `event`")]
+    #[case("\n{\"at\": 0, \"tab\": \"nowhere\", \"event\": {}}", "Line 2: ")]
+    fn test_parse_error(#[case] content: &str, #[case] expected: &str) {
+        let content = content.replace("{}", &note("gm").as_json());
+        let error = Script::parse(&content).unwrap_err().to_string();
+        assert!(error.starts_with(expected), "{error}");
+    }
+
+    #[rstest]
+    fn test_advance() {
+        let (gm, gn) = (note("gm"), note("gn"));
+        let content = format!(
+            "{{\"at\": 0, \"event\": {}}}\n{{\"at\": 1000, \"event\": {}}}",
+            gm.as_json(),
+            gn.as_json()
+        );
+        let mut simulation = Simulation::new(Script::parse(&content).unwrap());
+
+        assert_eq!(
+            simulation.advance(Duration::ZERO),
+            vec![Notification::Fetched(gm)]
+        );
+        assert_eq!(simulation.advance(Duration::from_millis(999)), vec![]);
+        assert_eq!(simulation.is_finished(), false);
+        assert_eq!(
+            simulation.advance(Duration::from_millis(1)),
+            vec![Notification::Fetched(gn)]
+        );
+        assert_eq!(simulation.is_finished(), true);
+    }
+
+    #[rstest]
+    fn test_respond() {
+        let event = note("gm");
+        assert_eq!(
+            Simulation::respond(NostrCommand::SendEvent(Box::new(event.clone()))),
+            Some(Notification::Receipt(
+                event.id,
+                Url::parse(SIMULATED_RELAY).unwrap(),
+                true,
+                String::new()
+            ))
+        );
+        assert_eq!(Simulation::respond(NostrCommand::Fetch(vec![])), None);
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::timeline::TimelineTabType;
+
+/// Time to collect fetch requests before sending them together.
+pub const FETCH_BATCH_WINDOW: Duration = Duration::from_millis(200);
+
+/// Number of ids or authors in a merged filter, to stay within the limits of relays.
+const MAX_BATCH_VALUES: usize = 250;
+
+/// Open subscriptions of timeline tabs, and pages of older notes loaded into them.
+#[derive(Debug, Default)]
+pub struct TimelineSubscriptions {
+    tabs: HashMap<SubscriptionId, TimelineTabType>,
+    pages: HashMap<SubscriptionId, TimelineTabType>,
+    /// Events received by the subscriptions of tabs
+    events: HashMap<SubscriptionId, u64>,
+    /// Tabs to subscribe again on resume, while the subscriptions are paused
+    paused: Option<Vec<TimelineTabType>>,
+}
+
+/// A subscription open on the relays, as listed in the subscription inspector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub id: SubscriptionId,
+    /// Tab fed by the subscription, or `None` if no tab owns it, e.g. a stale one
+    pub tab: Option<TimelineTabType>,
+    /// Summary of the filters, e.g. `kinds:1,6 authors:120 limit:50`
+    pub filters: String,
+    pub events: u64,
+    /// Relays which the subscription is open on, sharing one connection per relay
+    pub relays: usize,
+}
+
+impl TimelineSubscriptions {
+    pub fn insert(&mut self, id: SubscriptionId, tab: TimelineTabType) {
+        self.tabs.insert(id, tab);
+    }
+
+    /// Pages are closed on EOSE, so they are forgotten only with the tab.
+    pub fn insert_page(&mut self, id: SubscriptionId, tab: TimelineTabType) {
+        self.pages.insert(id, tab);
+    }
+
+    pub fn tab(&self, id: &SubscriptionId) -> Option<&TimelineTabType> {
+        self.tabs.get(id).or_else(|| self.pages.get(id))
+    }
+
+    pub fn contains(&self, tab: &TimelineTabType) -> bool {
+        self.tabs.values().any(|t| t == tab)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Forgets the subscriptions of all tabs and returns them to be closed, keeping the tabs to
+    /// subscribe again on [`Self::resume`].
+    pub fn pause(&mut self) -> Vec<SubscriptionId> {
+        let paused = self.paused.get_or_insert_with(Vec::new);
+        for tab in self.tabs.values() {
+            if !paused.contains(tab) {
+                paused.push(tab.clone());
+            }
+        }
+        self.pages.clear();
+        self.events.clear();
+        self.tabs.drain().map(|(id, _)| id).collect()
+    }
+
+    /// Keeps a tab opened while paused, to subscribe on resume.
+    pub fn defer(&mut self, tab: TimelineTabType) {
+        if let Some(paused) = &mut self.paused {
+            if !paused.contains(&tab) {
+                paused.push(tab);
+            }
+        }
+    }
+
+    /// Returns the tabs to subscribe again, which were open when paused.
+    pub fn resume(&mut self) -> Vec<TimelineTabType> {
+        self.paused.take().unwrap_or_default()
+    }
+
+    /// Counts an event received by the subscription of a tab.
+    pub fn record(&mut self, id: &SubscriptionId) {
+        if self.tabs.contains_key(id) {
+            *self.events.entry(id.clone()).or_default() += 1;
+        }
+    }
+
+    /// Describes the subscriptions open on the relays, given their filters and the number of
+    /// relays each is open on, with tabs first in the order of their ids.
+    pub fn inspect(
+        &self,
+        open: HashMap<SubscriptionId, (Vec<Filter>, usize)>,
+    ) -> Vec<SubscriptionInfo> {
+        let mut infos: Vec<SubscriptionInfo> = open
+            .into_iter()
+            .map(|(id, (filters, relays))| SubscriptionInfo {
+                tab: self.tabs.get(&id).cloned(),
+                filters: describe_filters(&filters),
+                events: self.events.get(&id).copied().unwrap_or_default(),
+                relays,
+                id,
+            })
+            .collect();
+        infos.sort_by(|a, b| (a.tab.is_none(), &a.id).cmp(&(b.tab.is_none(), &b.id)));
+        infos
+    }
+
+    /// Forgets a single subscription, e.g. closed in the inspector, returning its tab.
+    pub fn close(&mut self, id: &SubscriptionId) -> Option<TimelineTabType> {
+        self.events.remove(id);
+        self.tabs.remove(id)
+    }
+
+    /// Forgets the subscriptions of the tab and returns them to be closed.
+    pub fn remove(&mut self, tab: &TimelineTabType) -> Vec<SubscriptionId> {
+        self.pages.retain(|_, t| t != tab);
+        if let Some(paused) = &mut self.paused {
+            paused.retain(|t| t != tab);
+        }
+        let ids: Vec<SubscriptionId> = self
+            .tabs
+            .iter()
+            .filter(|(_, t)| *t == tab)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &ids {
+            self.tabs.remove(id);
+            self.events.remove(id);
+        }
+        ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+}
+
+/// Summarizes filters for the subscription inspector, counting ids, authors and tag values
+/// instead of listing them.
+pub fn describe_filters(filters: &[Filter]) -> String {
+    let describe = |filter: &Filter| {
+        let mut parts = vec![];
+        if let Some(kinds) = &filter.kinds {
+            let mut kinds: Vec<u64> = kinds.iter().map(|kind| kind.as_u64()).collect();
+            kinds.sort_unstable();
+            let kinds: Vec<String> = kinds.iter().map(u64::to_string).collect();
+            parts.push(format!("kinds:{}", kinds.join(",")));
+        }
+        if let Some(ids) = &filter.ids {
+            parts.push(format!("ids:{}", ids.len()));
+        }
+        if let Some(authors) = &filter.authors {
+            parts.push(format!("authors:{}", authors.len()));
+        }
+        let mut tags: Vec<String> = filter
+            .generic_tags
+            .iter()
+            .map(|(tag, values)| format!("#{tag}:{}", values.len()))
+            .collect();
+        tags.sort_unstable();
+        parts.extend(tags);
+        if filter.search.is_some() {
+            parts.push(String::from("search"));
+        }
+        if filter.since.is_some() {
+            parts.push(String::from("since"));
+        }
+        if filter.until.is_some() {
+            parts.push(String::from("until"));
+        }
+        if let Some(limit) = filter.limit {
+            parts.push(format!("limit:{limit}"));
+        }
+        if parts.is_empty() {
+            String::from("*")
+        } else {
+            parts.join(" ")
+        }
+    };
+    filters.iter().map(describe).collect::<Vec<_>>().join(" | ")
+}
+
+/// Collects filters of fetch requests, e.g. of profiles and parent notes, to send them
+/// as a single subscription instead of one for each.
+#[derive(Debug, Default)]
+pub struct FetchBatcher {
+    filters: Vec<Filter>,
+    started_at: Option<Instant>,
+}
+
+impl FetchBatcher {
+    pub fn push(&mut self, filters: Vec<Filter>, now: Instant) {
+        self.started_at.get_or_insert(now);
+        self.filters.extend(filters);
+    }
+
+    /// Returns the merged filters once the batch window has passed.
+    pub fn flush(&mut self, now: Instant) -> Option<Vec<Filter>> {
+        let started_at = self.started_at?;
+        if now.saturating_duration_since(started_at) < FETCH_BATCH_WINDOW {
+            return None;
+        }
+
+        self.started_at = None;
+        Some(merge_filters(std::mem::take(&mut self.filters)))
+    }
+}
+
+/// Merges filters which differ only in their ids or only in their authors.
+/// Filters with a limit are kept as they are, as the limit applies to the whole filter.
+pub fn merge_filters(filters: Vec<Filter>) -> Vec<Filter> {
+    let mut merged: Vec<Filter> = vec![];
+    // Filters without ids and authors, keyed by their JSON and whether they had ids
+    let mut groups: BTreeMap<(String, bool), (Filter, Vec<Filter>)> = BTreeMap::new();
+    for filter in filters {
+        let mergeable = filter.limit.is_none() && filter.ids.is_some() != filter.authors.is_some();
+        if !mergeable {
+            merged.push(filter);
+            continue;
+        }
+
+        let rest = Filter {
+            ids: None,
+            authors: None,
+            ..filter.clone()
+        };
+        groups
+            .entry((rest.as_json(), filter.ids.is_some()))
+            .or_insert_with(|| (rest, vec![]))
+            .1
+            .push(filter);
+    }
+
+    for ((_, by_ids), (rest, filters)) in groups {
+        if by_ids {
+            let mut ids: Vec<EventId> = filters.into_iter().flat_map(|f| f.ids.unwrap()).collect();
+            ids.sort();
+            ids.dedup();
+            merged.extend(
+                ids.chunks(MAX_BATCH_VALUES)
+                    .map(|chunk| rest.clone().ids(chunk.to_vec())),
+            );
+        } else {
+            let mut authors: Vec<PublicKey> = filters
+                .into_iter()
+                .flat_map(|f| f.authors.unwrap())
+                .collect();
+            authors.sort();
+            authors.dedup();
+            merged.extend(
+                authors
+                    .chunks(MAX_BATCH_VALUES)
+                    .map(|chunk| rest.clone().authors(chunk.to_vec())),
+            );
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn id(i: u8) -> EventId {
+        EventId::from_slice(&[i; 32]).unwrap()
+    }
+
+    #[rstest]
+    fn test_timeline_subscriptions() {
+        let mut subscriptions = TimelineSubscriptions::default();
+        let hashtag = TimelineTabType::Hashtag {
+            tag: String::from("nostr"),
+        };
+        subscriptions.insert(SubscriptionId::new("a"), TimelineTabType::Home);
+        subscriptions.insert(SubscriptionId::new("b"), hashtag.clone());
+        subscriptions.insert_page(SubscriptionId::new("c"), hashtag.clone());
+        assert!(subscriptions.contains(&hashtag));
+        assert_eq!(subscriptions.tab(&SubscriptionId::new("c")), Some(&hashtag));
+        assert_eq!(
+            subscriptions.tab(&SubscriptionId::new("a")),
+            Some(&TimelineTabType::Home)
+        );
+
+        assert_eq!(
+            subscriptions.remove(&hashtag),
+            vec![SubscriptionId::new("b")]
+        );
+        assert!(!subscriptions.contains(&hashtag));
+        assert_eq!(subscriptions.tab(&SubscriptionId::new("c")), None);
+        assert_eq!(subscriptions.len(), 1);
+    }
+
+    #[rstest]
+    fn test_pause() {
+        let mut subscriptions = TimelineSubscriptions::default();
+        let hashtag = TimelineTabType::Hashtag {
+            tag: String::from("nostr"),
+        };
+        let global = TimelineTabType::Global;
+        subscriptions.insert(SubscriptionId::new("a"), TimelineTabType::Home);
+        subscriptions.insert_page(SubscriptionId::new("b"), TimelineTabType::Home);
+        subscriptions.insert(SubscriptionId::new("c"), hashtag.clone());
+        // Tabs opened before pausing are subscribed as usual
+        subscriptions.defer(global.clone());
+        assert!(!subscriptions.is_paused());
+
+        let mut ids = subscriptions.pause();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![SubscriptionId::new("a"), SubscriptionId::new("c")]
+        );
+        assert!(subscriptions.is_paused());
+        assert!(subscriptions.is_empty());
+        assert_eq!(subscriptions.tab(&SubscriptionId::new("b")), None);
+
+        subscriptions.defer(global.clone());
+        subscriptions.defer(global.clone());
+        assert_eq!(subscriptions.remove(&hashtag), vec![]);
+        assert_eq!(subscriptions.resume(), vec![TimelineTabType::Home, global]);
+        assert!(!subscriptions.is_paused());
+        assert_eq!(subscriptions.resume(), vec![]);
+    }
+
+    #[rstest]
+    fn test_inspect() {
+        let mut subscriptions = TimelineSubscriptions::default();
+        subscriptions.insert(SubscriptionId::new("b"), TimelineTabType::Home);
+        subscriptions.insert_page(SubscriptionId::new("c"), TimelineTabType::Home);
+        subscriptions.record(&SubscriptionId::new("b"));
+        subscriptions.record(&SubscriptionId::new("b"));
+        subscriptions.record(&SubscriptionId::new("c"));
+
+        let filter = Filter::new().kind(Kind::TextNote).limit(10);
+        let open = HashMap::from([
+            (SubscriptionId::new("a"), (vec![filter.clone()], 1)),
+            (SubscriptionId::new("b"), (vec![filter], 2)),
+        ]);
+        let infos = subscriptions.inspect(open);
+        assert_eq!(
+            infos,
+            vec![
+                SubscriptionInfo {
+                    id: SubscriptionId::new("b"),
+                    tab: Some(TimelineTabType::Home),
+                    filters: String::from("kinds:1 limit:10"),
+                    events: 2,
+                    relays: 2,
+                },
+                SubscriptionInfo {
+                    id: SubscriptionId::new("a"),
+                    tab: None,
+                    filters: String::from("kinds:1 limit:10"),
+                    events: 0,
+                    relays: 1,
+                },
+            ]
+        );
+
+        assert_eq!(
+            subscriptions.close(&SubscriptionId::new("b")),
+            Some(TimelineTabType::Home)
+        );
+        assert!(!subscriptions.contains(&TimelineTabType::Home));
+        assert_eq!(subscriptions.close(&SubscriptionId::new("b")), None);
+    }
+
+    #[rstest]
+    #[case(vec![], "")]
+    #[case(vec![Filter::new()], "*")]
+    #[case(
+        vec![Filter::new().kinds([Kind::TextNote, Kind::Repost]).authors([Keys::generate().public_key()]).since(Timestamp::from(1))],
+        "kinds:1,6 authors:1 since"
+    )]
+    #[case(
+        vec![Filter::new().ids([id(1), id(2)]), Filter::new().hashtag("nostr").limit(50)],
+        "ids:2 | #t:1 limit:50"
+    )]
+    fn test_describe_filters(#[case] filters: Vec<Filter>, #[case] expected: &str) {
+        assert_eq!(describe_filters(&filters), expected);
+    }
+
+    #[rstest]
+    fn test_merge_filters() {
+        let (alice, bob) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let profile = |pubkey| Filter::new().author(pubkey).kind(Kind::Metadata);
+        let filters = vec![
+            Filter::new().id(id(1)),
+            profile(alice),
+            Filter::new().id(id(2)),
+            profile(bob),
+            profile(alice),
+            Filter::new().author(alice).kind(Kind::TextNote).limit(10),
+            Filter::new().id(id(1)),
+        ];
+
+        let merged = merge_filters(filters);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(
+            merged[0],
+            Filter::new().author(alice).kind(Kind::TextNote).limit(10)
+        );
+        assert!(merged.contains(&Filter::new().ids([id(1), id(2)])));
+        assert!(merged.contains(&Filter::new().authors([alice, bob]).kind(Kind::Metadata)));
+    }
+
+    #[rstest]
+    fn test_merge_filters_in_chunks() {
+        let filters = (0..=MAX_BATCH_VALUES)
+            .map(|_| Filter::new().author(Keys::generate().public_key()))
+            .collect();
+        let merged = merge_filters(filters);
+        let sizes: Vec<usize> = merged
+            .iter()
+            .map(|filter| filter.authors.as_ref().unwrap().len())
+            .collect();
+        assert_eq!(sizes, vec![MAX_BATCH_VALUES, 1]);
+    }
+
+    #[rstest]
+    fn test_fetch_batcher() {
+        let start = Instant::now();
+        let mut batcher = FetchBatcher::default();
+        assert_eq!(batcher.flush(start), None);
+
+        batcher.push(vec![Filter::new().id(id(1))], start);
+        batcher.push(
+            vec![Filter::new().id(id(2))],
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(batcher.flush(start + Duration::from_millis(100)), None);
+        assert_eq!(
+            batcher.flush(start + FETCH_BATCH_WINDOW),
+            Some(vec![Filter::new().ids([id(1), id(2)])])
+        );
+        assert_eq!(batcher.flush(start + FETCH_BATCH_WINDOW * 2), None);
+    }
+}
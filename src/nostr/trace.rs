@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+const TRACE_FILE: &str = "trace.log";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sent => write!(f, "->"),
+            Self::Received => write!(f, "<-"),
+        }
+    }
+}
+
+/// A raw relay message, either sent to all relays or received from one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Milliseconds since tracing started
+    pub elapsed_ms: u64,
+    pub direction: Direction,
+    /// `None` for messages sent to all relays
+    pub relay: Option<String>,
+    /// Message type, e.g. `REQ` or `EOSE`
+    pub kind: String,
+    /// Milliseconds since the matching REQ or EVENT, for EOSE, CLOSED and OK
+    pub latency_ms: Option<u64>,
+    pub message: String,
+}
+
+impl TraceEntry {
+    /// Whether the kind, relay or message contains `filter`, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        filter.is_empty()
+            || self.kind.to_lowercase().contains(&filter)
+            || self
+                .relay
+                .as_ref()
+                .is_some_and(|relay| relay.to_lowercase().contains(&filter))
+            || self.message.to_lowercase().contains(&filter)
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10.3}s {} {} {}",
+            self.elapsed_ms as f64 / 1000.0,
+            self.direction,
+            self.relay.as_deref().unwrap_or("*"),
+            self.kind
+        )?;
+        if let Some(latency) = self.latency_ms {
+            write!(f, " ({latency}ms)")?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// Returns the message type and the key which pairs a request with its response.
+fn classify(direction: Direction, message: &str) -> (String, Option<String>) {
+    let Ok(serde_json::Value::Array(values)) = serde_json::from_str(message) else {
+        return (String::from("?"), None);
+    };
+
+    let kind = values
+        .first()
+        .and_then(|v| v.as_str())
+        .unwrap_or("?")
+        .to_string();
+    let key = match (direction, kind.as_str()) {
+        (Direction::Sent, "REQ" | "CLOSE") | (Direction::Received, "EOSE" | "CLOSED") => values
+            .get(1)
+            .and_then(|v| v.as_str())
+            .map(|id| format!("sub:{id}")),
+        (Direction::Sent, "EVENT") => values
+            .get(1)
+            .and_then(|v| v.get("id"))
+            .and_then(|v| v.as_str())
+            .map(|id| format!("event:{id}")),
+        (Direction::Received, "OK") => values
+            .get(1)
+            .and_then(|v| v.as_str())
+            .map(|id| format!("event:{id}")),
+        _ => None,
+    };
+    (kind, key)
+}
+
+/// Records relay messages to `trace.log` in the data directory and keeps them until taken.
+pub struct Tracer {
+    started: Instant,
+    file: Option<File>,
+    /// Requests waiting for their first response
+    pending: HashMap<String, Instant>,
+    entries: Vec<TraceEntry>,
+}
+
+impl Tracer {
+    pub fn new(data_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(data_dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(data_dir.join(TRACE_FILE))?;
+        Ok(Self {
+            file: Some(file),
+            ..Self::in_memory()
+        })
+    }
+
+    /// A tracer which does not write to a file.
+    pub fn in_memory() -> Self {
+        Self {
+            started: Instant::now(),
+            file: None,
+            pending: HashMap::new(),
+            entries: vec![],
+        }
+    }
+
+    pub fn record(&mut self, direction: Direction, relay: Option<String>, message: String) {
+        self.record_at(Instant::now(), direction, relay, message);
+    }
+
+    fn record_at(
+        &mut self,
+        now: Instant,
+        direction: Direction,
+        relay: Option<String>,
+        message: String,
+    ) {
+        let (kind, key) = classify(direction, &message);
+        let latency_ms = key.and_then(|key| match direction {
+            Direction::Sent => {
+                self.pending.insert(key, now);
+                None
+            }
+            // The first relay to respond wins
+            Direction::Received => self
+                .pending
+                .remove(&key)
+                .map(|sent| now.duration_since(sent).as_millis() as u64),
+        });
+        let entry = TraceEntry {
+            elapsed_ms: now.duration_since(self.started).as_millis() as u64,
+            direction,
+            relay,
+            kind,
+            latency_ms,
+            message,
+        };
+
+        if let Some(file) = &mut self.file {
+            if let Err(e) = writeln!(file, "{entry}") {
+                tracing::error!(error = %e, "Failed to write the trace");
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    /// Takes the entries recorded since the last call.
+    pub fn take(&mut self) -> Vec<TraceEntry> {
+        std::mem::take(&mut self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        Direction::Sent,
+        r#"["REQ","abc",{"kinds":[1]}]"#,
+        "REQ",
+        Some("sub:abc")
+    )]
+    #[case(Direction::Received, r#"["EOSE","abc"]"#, "EOSE", Some("sub:abc"))]
+    #[case(Direction::Sent, r#"["EVENT",{"id":"01"}]"#, "EVENT", Some("event:01"))]
+    #[case(Direction::Received, r#"["OK","01",true,""]"#, "OK", Some("event:01"))]
+    #[case(Direction::Received, r#"["EVENT","abc",{"id":"01"}]"#, "EVENT", None)]
+    #[case(Direction::Received, r#"["NOTICE","rate limited"]"#, "NOTICE", None)]
+    #[case(Direction::Received, "not json", "?", None)]
+    fn test_classify(
+        #[case] direction: Direction,
+        #[case] message: &str,
+        #[case] kind: &str,
+        #[case] key: Option<&str>,
+    ) {
+        assert_eq!(
+            classify(direction, message),
+            (kind.to_string(), key.map(String::from))
+        );
+    }
+
+    #[rstest]
+    fn test_record_latency() {
+        let mut tracer = Tracer::in_memory();
+        let sent = tracer.started + Duration::from_millis(100);
+        let relay = Some(String::from("wss://relay.example.com"));
+        tracer.record_at(
+            sent,
+            Direction::Sent,
+            None,
+            String::from(r#"["REQ","abc",{}]"#),
+        );
+        tracer.record_at(
+            sent + Duration::from_millis(250),
+            Direction::Received,
+            relay.clone(),
+            String::from(r#"["EOSE","abc"]"#),
+        );
+        tracer.record_at(
+            sent + Duration::from_millis(300),
+            Direction::Received,
+            relay,
+            String::from(r#"["EOSE","abc"]"#),
+        );
+
+        let entries = tracer.take();
+        let latencies: Vec<Option<u64>> = entries.iter().map(|e| e.latency_ms).collect();
+        assert_eq!(latencies, vec![None, Some(250), None]);
+        assert_eq!(
+            entries[1].to_string(),
+            "     0.350s <- wss://relay.example.com EOSE (250ms) [\"EOSE\",\"abc\"]"
+        );
+        assert_eq!(tracer.take(), vec![]);
+    }
+
+    #[rstest]
+    #[case("", true)]
+    #[case("eose", true)]
+    #[case("EXAMPLE", true)]
+    #[case("abc", true)]
+    #[case("notice", false)]
+    fn test_matches(#[case] filter: &str, #[case] expected: bool) {
+        let entry = TraceEntry {
+            elapsed_ms: 0,
+            direction: Direction::Received,
+            relay: Some(String::from("wss://relay.example.com")),
+            kind: String::from("EOSE"),
+            latency_ms: None,
+            message: String::from(r#"["EOSE","abc"]"#),
+        };
+        assert_eq!(entry.matches(filter), expected);
+    }
+}
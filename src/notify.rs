@@ -0,0 +1,345 @@
+use std::{
+    collections::HashSet,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use color_eyre::eyre::Result;
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    config::NotificationConfig,
+    i18n,
+    nostr::{nip10::ThreadTags, nip57},
+    text::shorten_hex,
+};
+
+const BODY_MAX_CHARS: usize = 100;
+
+/// Events addressed to me which are worth a notification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Mention,
+    Reply,
+    DirectMessage,
+    Zap,
+}
+
+impl AlertKind {
+    /// Classifies an event tagging `me`. Replies tag the author of the parent note (NIP-10).
+    pub fn classify(event: &Event, me: &PublicKey) -> Option<Self> {
+        if event.pubkey == *me || !event.public_keys().any(|pubkey| pubkey == me) {
+            return None;
+        }
+
+        match event.kind {
+            Kind::EncryptedDirectMessage => Some(Self::DirectMessage),
+            Kind::TextNote if ThreadTags::parse(event).parent().is_some() => Some(Self::Reply),
+            Kind::TextNote => Some(Self::Mention),
+            Kind::ZapReceipt => Some(Self::Zap),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        i18n::tr(match self {
+            Self::Mention => "Mentioned",
+            Self::Reply => "Replied",
+            Self::DirectMessage => "Direct message",
+            Self::Zap => "Zapped",
+        })
+    }
+}
+
+/// How desktop notifications are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationMethod {
+    /// The notification system of the OS, falling back to OSC 777 if it is unavailable
+    #[default]
+    System,
+    /// `OSC 777` escape sequence, supported by e.g. urxvt, foot and WezTerm
+    Osc777,
+    /// `OSC 9` escape sequence, supported by e.g. iTerm2, Windows Terminal and kitty
+    Osc9,
+}
+
+/// Decides which incoming events to notify of, and how.
+pub struct Notifier {
+    me: PublicKey,
+    config: NotificationConfig,
+    since: Timestamp,
+    seen: HashSet<EventId>,
+    do_not_disturb: bool,
+}
+
+impl Notifier {
+    pub fn new(me: PublicKey, config: NotificationConfig) -> Self {
+        Self {
+            me,
+            config,
+            since: Timestamp::now(),
+            seen: HashSet::new(),
+            do_not_disturb: false,
+        }
+    }
+
+    /// Silences notifications and sounds, or brings them back. Returns the new state.
+    pub fn toggle_do_not_disturb(&mut self) -> bool {
+        self.do_not_disturb = !self.do_not_disturb;
+        self.do_not_disturb
+    }
+
+    /// Returns the kind of notification for an event, if a notification or a sound is enabled for it.
+    /// Events older than the notifier and events already seen from other relays are skipped.
+    pub fn check(&mut self, event: &Event) -> Option<AlertKind> {
+        if event.created_at < self.since {
+            return None;
+        }
+
+        let kind = AlertKind::classify(event, &self.me)?;
+        let enabled = self.config.is_enabled(kind) || self.config.sound.events.contains(&kind);
+        if !enabled || !self.seen.insert(event.id) {
+            return None;
+        }
+
+        Some(kind)
+    }
+
+    /// Shows a desktop notification and plays a sound as configured, unless in do-not-disturb mode.
+    pub fn alert(&self, kind: AlertKind, event: &Event, focused: bool) {
+        if self.do_not_disturb {
+            return;
+        }
+
+        let unfocused = !focused || !self.config.only_when_unfocused;
+        if self.config.is_enabled(kind) && unfocused {
+            self.notify(kind, event);
+        }
+        if self.config.sound.events.contains(&kind) {
+            self.ring();
+        }
+    }
+
+    fn notify(&self, kind: AlertKind, event: &Event) {
+        let title = format!("nostui: {}", kind.title());
+        let body = Self::body(kind, event);
+        let method = self.config.method;
+        // Talking to the notification daemon may block
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = show(method, &title, &body) {
+                tracing::warn!(error = %e, "Failed to show a notification");
+            }
+        });
+    }
+
+    /// Rings the terminal bell, or runs the sound command if configured.
+    fn ring(&self) {
+        let Some((program, args)) = self.config.sound.command.split_first() else {
+            if let Err(e) = write_escape("\x07") {
+                tracing::warn!(error = %e, "Failed to ring the bell");
+            }
+            return;
+        };
+
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = command.status() {
+                tracing::warn!(error = %e, "Failed to play a sound");
+            }
+        });
+    }
+
+    fn body(kind: AlertKind, event: &Event) -> String {
+        let author = shorten_hex(&event.pubkey.to_string());
+        match kind {
+            // The content is encrypted
+            AlertKind::DirectMessage => i18n::tr_args("From {author}", &[("author", &author)]),
+            AlertKind::Zap => match nip57::millisats(event) {
+                Some(millisats) => format!("{} sats", millisats / 1000),
+                None => i18n::tr("Zap received"),
+            },
+            AlertKind::Mention | AlertKind::Reply => {
+                let content: String = event.content.chars().take(BODY_MAX_CHARS).collect();
+                format!(
+                    "{author}: {}",
+                    content.split_whitespace().collect::<Vec<_>>().join(" ")
+                )
+            }
+        }
+    }
+}
+
+fn show(method: NotificationMethod, title: &str, body: &str) -> Result<()> {
+    match method {
+        NotificationMethod::System => {
+            let shown = notify_rust::Notification::new()
+                .appname("nostui")
+                .summary(title)
+                .body(body)
+                .show();
+            if let Err(e) = shown {
+                tracing::info!(error = %e, "No notification system, falling back to OSC 777");
+                write_escape(&osc777(title, body))?;
+            }
+        }
+        NotificationMethod::Osc777 => write_escape(&osc777(title, body))?,
+        NotificationMethod::Osc9 => write_escape(&osc9(title, body))?,
+    }
+
+    Ok(())
+}
+
+fn write_escape(sequence: &str) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Removes characters which would end the escape sequence or split its fields.
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if c == ';' { ',' } else { c })
+        .collect()
+}
+
+fn osc777(title: &str, body: &str) -> String {
+    format!("\x1b]777;notify;{};{}\x07", sanitize(title), sanitize(body))
+}
+
+fn osc9(title: &str, body: &str) -> String {
+    format!("\x1b]9;{}: {}\x07", sanitize(title), sanitize(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+    use crate::config::SoundConfig;
+
+    fn note(keys: &Keys, tags: Vec<Tag>, created_at: Timestamp) -> Event {
+        EventBuilder::text_note("hello", tags)
+            .custom_created_at(created_at)
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_classify() {
+        let me = Keys::generate().public_key();
+        let other = Keys::generate();
+        let p = Tag::public_key(me);
+        let e = Tag::event(EventId::all_zeros());
+        let now = Timestamp::now();
+
+        assert_eq!(
+            AlertKind::classify(&note(&other, vec![p.clone()], now), &me),
+            Some(AlertKind::Mention)
+        );
+        assert_eq!(
+            AlertKind::classify(&note(&other, vec![e.clone(), p.clone()], now), &me),
+            Some(AlertKind::Reply)
+        );
+        assert_eq!(AlertKind::classify(&note(&other, vec![e], now), &me), None);
+
+        let dm = EventBuilder::new(Kind::EncryptedDirectMessage, "?iv=", [p])
+            .to_event(&other)
+            .unwrap();
+        assert_eq!(
+            AlertKind::classify(&dm, &me),
+            Some(AlertKind::DirectMessage)
+        );
+    }
+
+    #[rstest]
+    fn test_classify_own_event() {
+        let keys = Keys::generate();
+        let event = note(
+            &keys,
+            vec![Tag::public_key(keys.public_key())],
+            Timestamp::now(),
+        );
+        assert_eq!(AlertKind::classify(&event, &keys.public_key()), None);
+    }
+
+    #[rstest]
+    fn test_notifier_check() {
+        let me = Keys::generate().public_key();
+        let other = Keys::generate();
+        let config = NotificationConfig {
+            reply: false,
+            ..NotificationConfig::default()
+        };
+        let mut notifier = Notifier::new(me, config);
+        let now = Timestamp::now();
+        let p = Tag::public_key(me);
+
+        let mention = note(&other, vec![p.clone()], now);
+        assert_eq!(notifier.check(&mention), Some(AlertKind::Mention));
+        // Delivered by another relay
+        assert_eq!(notifier.check(&mention), None);
+
+        let old = note(
+            &other,
+            vec![p.clone()],
+            now - std::time::Duration::from_secs(60),
+        );
+        assert_eq!(notifier.check(&old), None);
+
+        let reply = note(&other, vec![Tag::event(EventId::all_zeros()), p], now);
+        assert_eq!(notifier.check(&reply), None);
+    }
+
+    #[rstest]
+    fn test_notifier_check_sound() {
+        let me = Keys::generate().public_key();
+        let config = NotificationConfig {
+            reply: false,
+            sound: SoundConfig {
+                events: vec![AlertKind::Reply, AlertKind::Zap],
+                command: vec![],
+            },
+            ..NotificationConfig::default()
+        };
+        let mut notifier = Notifier::new(me, config);
+        let tags = vec![Tag::event(EventId::all_zeros()), Tag::public_key(me)];
+
+        let reply = note(&Keys::generate(), tags.clone(), Timestamp::now());
+        assert_eq!(notifier.check(&reply), Some(AlertKind::Reply));
+
+        let zap = EventBuilder::new(Kind::ZapReceipt, "", tags)
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(notifier.check(&zap), Some(AlertKind::Zap));
+    }
+
+    #[rstest]
+    fn test_toggle_do_not_disturb() {
+        let mut notifier = Notifier::new(Keys::generate().public_key(), Default::default());
+        assert!(notifier.toggle_do_not_disturb());
+        assert!(!notifier.toggle_do_not_disturb());
+    }
+
+    #[rstest]
+    #[case(NotificationMethod::Osc777, "\x1b]777;notify;a, b;c\x07")]
+    #[case(NotificationMethod::Osc9, "\x1b]9;a, b: c\x07")]
+    fn test_escape_sequence(#[case] method: NotificationMethod, #[case] expected: &str) {
+        let (title, body) = ("a; b", "c\x07");
+        let sequence = match method {
+            NotificationMethod::Osc9 => osc9(title, body),
+            _ => osc777(title, body),
+        };
+        assert_eq!(sequence, expected);
+    }
+}
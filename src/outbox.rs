@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use nostr_sdk::prelude::*;
+
+/// Machine-readable prefix of OK and CLOSED messages (NIP-01).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    Duplicate,
+    Pow,
+    Blocked,
+    RateLimited,
+    Invalid,
+    Restricted,
+    AuthRequired,
+    Error,
+}
+
+impl RejectReason {
+    pub fn parse(message: &str) -> Option<Self> {
+        let (prefix, _) = message.split_once(':')?;
+        let reason = match prefix.trim() {
+            "duplicate" => Self::Duplicate,
+            "pow" => Self::Pow,
+            "blocked" => Self::Blocked,
+            "rate-limited" => Self::RateLimited,
+            "invalid" => Self::Invalid,
+            "restricted" => Self::Restricted,
+            "auth-required" => Self::AuthRequired,
+            "error" => Self::Error,
+            _ => return None,
+        };
+        Some(reason)
+    }
+
+    /// Whether sending the same event again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Pow | Self::RateLimited | Self::Error)
+    }
+}
+
+/// Returns the PoW difficulty demanded by a `pow:` message, e.g. `pow: difficulty 26 is less than 28`.
+///
+/// Relays word it differently, so the largest number in the message is taken as the requirement.
+pub fn required_pow(message: &str) -> Option<u8> {
+    if RejectReason::parse(message) != Some(RejectReason::Pow) {
+        return None;
+    }
+
+    message
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse::<u8>().ok())
+        .max()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Receipt {
+    Pending,
+    Accepted,
+    Rejected {
+        reason: Option<RejectReason>,
+        message: String,
+    },
+}
+
+/// State of a published note shown in the timeline before relays respond.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    Sending,
+    /// Accepted by a relay at least
+    Sent,
+    /// Rejected by every relay
+    Failed,
+}
+
+/// An event published in this session and how each relay responded to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedEvent {
+    pub event: Event,
+    /// Responses keyed by relay URL
+    pub receipts: BTreeMap<String, Receipt>,
+}
+
+impl PublishedEvent {
+    pub fn accepted(&self) -> usize {
+        self.receipts
+            .values()
+            .filter(|receipt| **receipt == Receipt::Accepted)
+            .count()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.receipts
+            .values()
+            .filter(|receipt| **receipt == Receipt::Pending)
+            .count()
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.receipts
+            .values()
+            .filter(|receipt| matches!(receipt, Receipt::Rejected { .. }))
+            .count()
+    }
+
+    pub fn delivery(&self) -> Delivery {
+        if self.accepted() > 0 {
+            Delivery::Sent
+        } else if self.pending() > 0 {
+            Delivery::Sending
+        } else {
+            Delivery::Failed
+        }
+    }
+
+    /// Relays which rejected the event for a reason that a retry may fix.
+    pub fn retry_targets(&self) -> Vec<String> {
+        self.receipts
+            .iter()
+            .filter(|(_, receipt)| match receipt {
+                Receipt::Rejected { reason, .. } => reason.is_none_or(|r| r.is_retryable()),
+                _ => false,
+            })
+            .map(|(relay, _)| relay.clone())
+            .collect()
+    }
+
+    /// The highest PoW difficulty demanded by the relays.
+    pub fn required_pow(&self) -> Option<u8> {
+        self.receipts
+            .values()
+            .filter_map(|receipt| match receipt {
+                Receipt::Rejected { message, .. } => required_pow(message),
+                _ => None,
+            })
+            .max()
+    }
+}
+
+/// Events published in this session, the latest first.
+#[derive(Debug, Clone, Default)]
+pub struct Outbox {
+    events: Vec<PublishedEvent>,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> &[PublishedEvent] {
+        &self.events
+    }
+
+    pub fn get(&self, event_id: &EventId) -> Option<&PublishedEvent> {
+        self.events.iter().find(|p| p.event.id == *event_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Adds a published event, or marks the relays as pending again if it is sent once more.
+    /// Returns `true` if the event is new.
+    pub fn add(&mut self, event: Event, relays: impl IntoIterator<Item = String>) -> bool {
+        let receipts = relays.into_iter().map(|relay| (relay, Receipt::Pending));
+        if let Some(published) = self.events.iter_mut().find(|p| p.event.id == event.id) {
+            published.receipts.extend(receipts);
+            return false;
+        }
+
+        self.events.insert(
+            0,
+            PublishedEvent {
+                event,
+                receipts: receipts.collect(),
+            },
+        );
+        true
+    }
+
+    /// Records an OK message. Returns `false` if the event was not published in this session.
+    pub fn record(
+        &mut self,
+        event_id: EventId,
+        relay: String,
+        status: bool,
+        message: String,
+    ) -> bool {
+        let Some(published) = self.events.iter_mut().find(|p| p.event.id == event_id) else {
+            return false;
+        };
+
+        let receipt = if status {
+            Receipt::Accepted
+        } else {
+            Receipt::Rejected {
+                reason: RejectReason::parse(&message),
+                message,
+            }
+        };
+        published.receipts.insert(relay, receipt);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("pow: difficulty 26 is less than 28", Some(RejectReason::Pow))]
+    #[case("rate-limited: slow down", Some(RejectReason::RateLimited))]
+    #[case(
+        "auth-required: we only accept events from registered users",
+        Some(RejectReason::AuthRequired)
+    )]
+    #[case("blocked: you are banned", Some(RejectReason::Blocked))]
+    #[case("something went wrong", None)]
+    #[case("", None)]
+    fn test_reject_reason_parse(#[case] message: &str, #[case] expected: Option<RejectReason>) {
+        assert_eq!(RejectReason::parse(message), expected);
+    }
+
+    #[rstest]
+    #[case("pow: difficulty 26 is less than 28", Some(28))]
+    #[case("pow: required 20", Some(20))]
+    #[case("pow: difficulty too low", None)]
+    #[case("rate-limited: 10 events per minute", None)]
+    fn test_required_pow(#[case] message: &str, #[case] expected: Option<u8>) {
+        assert_eq!(required_pow(message), expected);
+    }
+
+    #[rstest]
+    fn test_outbox_record() {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("hello", [])
+            .to_event(&keys)
+            .unwrap();
+        let relays = ["wss://a/", "wss://b/", "wss://c/", "wss://d/"].map(String::from);
+        let mut outbox = Outbox::new();
+        assert!(outbox.add(event.clone(), relays));
+
+        assert!(outbox.record(event.id, String::from("wss://a/"), true, String::new()));
+        assert!(outbox.record(
+            event.id,
+            String::from("wss://b/"),
+            false,
+            String::from("pow: difficulty 0 is less than 16")
+        ));
+        assert!(outbox.record(
+            event.id,
+            String::from("wss://c/"),
+            false,
+            String::from("blocked: not on the whitelist")
+        ));
+        assert!(!outbox.record(
+            EventId::all_zeros(),
+            String::from("wss://a/"),
+            true,
+            String::new()
+        ));
+
+        let published = &outbox.events()[0];
+        assert_eq!(published.accepted(), 1);
+        assert_eq!(published.rejected(), 2);
+        assert_eq!(published.pending(), 1);
+        assert_eq!(published.receipts["wss://d/"], Receipt::Pending);
+        assert_eq!(published.retry_targets(), vec![String::from("wss://b/")]);
+        assert_eq!(published.required_pow(), Some(16));
+
+        // Sending it again only resets the receipts of the targeted relays
+        assert!(!outbox.add(event.clone(), [String::from("wss://b/")]));
+        assert_eq!(outbox.len(), 1);
+        assert_eq!(outbox.events()[0].receipts["wss://b/"], Receipt::Pending);
+        assert_eq!(outbox.events()[0].accepted(), 1);
+
+        // Inboxes of mentioned users are added to the receipts
+        assert!(!outbox.add(event.clone(), [String::from("wss://e/")]));
+        let published = outbox.get(&event.id).unwrap();
+        assert_eq!(published.receipts.len(), 5);
+        assert_eq!(published.pending(), 3);
+        assert!(outbox.get(&EventId::all_zeros()).is_none());
+    }
+
+    #[rstest]
+    fn test_delivery() {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("hello", [])
+            .to_event(&keys)
+            .unwrap();
+        let mut outbox = Outbox::new();
+        outbox.add(event.clone(), ["wss://a/", "wss://b/"].map(String::from));
+        assert_eq!(outbox.get(&event.id).unwrap().delivery(), Delivery::Sending);
+
+        let rejected = String::from("blocked: not on the whitelist");
+        outbox.record(event.id, String::from("wss://a/"), false, rejected.clone());
+        assert_eq!(outbox.get(&event.id).unwrap().delivery(), Delivery::Sending);
+        outbox.record(event.id, String::from("wss://b/"), false, rejected);
+        assert_eq!(outbox.get(&event.id).unwrap().delivery(), Delivery::Failed);
+
+        // A retry may still be accepted
+        outbox.add(event.clone(), [String::from("wss://b/")]);
+        outbox.record(event.id, String::from("wss://b/"), true, String::new());
+        assert_eq!(outbox.get(&event.id).unwrap().delivery(), Delivery::Sent);
+    }
+}
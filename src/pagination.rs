@@ -0,0 +1,481 @@
+use std::time::{Duration, Instant};
+
+use nostr_sdk::Timestamp;
+
+use crate::config::PaginationConfig;
+
+/// Notes requested by the subscription of a tab, and by each page unless it grows.
+pub const DEFAULT_TIMELINE_LIMIT: usize = 100;
+
+/// Time for the notes of a page to arrive before the next page can be requested.
+const PAGE_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Time after which a page without notes means that there are no older notes.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pages requested within this time of the previous one are made larger.
+const FAST_SCROLL_WINDOW: Duration = Duration::from_secs(10);
+
+/// Seconds without ticks after which notes may be missing, e.g. after the machine slept.
+const GAP_THRESHOLD: u64 = 60;
+
+/// Time in which notes of a tab may be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// The newest note before the interruption
+    pub since: Timestamp,
+    /// When the app was back
+    pub until: Timestamp,
+}
+
+/// Detects interruptions of the app, e.g. suspends and sleeps, from the wall clock of ticks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GapDetector {
+    last_tick: Option<Timestamp>,
+}
+
+impl GapDetector {
+    /// Returns whether the previous tick was long enough ago for notes to be missing.
+    pub fn tick(&mut self, now: Timestamp) -> bool {
+        self.last_tick.replace(now).is_some_and(|last_tick| {
+            now.as_u64().saturating_sub(last_tick.as_u64()) >= GAP_THRESHOLD
+        })
+    }
+
+    /// Forgets the previous tick, e.g. when ticks were paused on purpose.
+    pub fn reset(&mut self) {
+        self.last_tick = None;
+    }
+}
+
+/// Times of which notes were requested apart from the newest notes of a tab, e.g. around a date
+/// jumped to or in a gap, so that pages of older notes don't ask relays for them again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Disjoint times, the newest first
+    ranges: Vec<Gap>,
+    /// Windows whose notes are still arriving
+    pending: Vec<Request>,
+}
+
+/// A window of notes requested up to a limit, which covers only the newest notes of the window
+/// if relays have more than the limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Request {
+    window: Gap,
+    limit: usize,
+    requested_at: Instant,
+    /// Times of the notes received in the window
+    received: Vec<Timestamp>,
+}
+
+impl Request {
+    /// Returns the time down to which the window is covered. Each relay returns up to `limit`
+    /// notes, so with a full page only the times of the newest `limit` notes received are covered
+    /// by all of them.
+    fn covered_since(&self) -> Timestamp {
+        let mut received = self.received.clone();
+        received.sort_unstable_by(|a, b| b.cmp(a));
+        match self.limit.checked_sub(1).and_then(|i| received.get(i)) {
+            Some(oldest) => (*oldest).max(self.window.since),
+            None => self.window.since,
+        }
+    }
+}
+
+impl Coverage {
+    /// Records that the notes between the times were requested.
+    pub fn add(&mut self, since: Timestamp, until: Timestamp) {
+        if since > until {
+            return;
+        }
+
+        let mut merged = Gap { since, until };
+        self.ranges.retain(|range| {
+            let overlaps = range.since <= merged.until && merged.since <= range.until;
+            if overlaps {
+                merged.since = merged.since.min(range.since);
+                merged.until = merged.until.max(range.until);
+            }
+            !overlaps
+        });
+        let index = self
+            .ranges
+            .iter()
+            .position(|range| range.until < merged.since)
+            .unwrap_or(self.ranges.len());
+        self.ranges.insert(index, merged);
+    }
+
+    /// Records a window requested up to `limit` notes, whose coverage is known once its notes
+    /// arrived. Until then, the window is skipped like covered times.
+    pub fn request(&mut self, since: Timestamp, until: Timestamp, limit: usize, now: Instant) {
+        if since <= until {
+            self.pending.push(Request {
+                window: Gap { since, until },
+                limit,
+                requested_at: now,
+                received: vec![],
+            });
+        }
+    }
+
+    /// Counts a note which arrived in the tab, sorted at the time.
+    pub fn receive(&mut self, sorted_at: Timestamp) {
+        for request in &mut self.pending {
+            if request.window.since <= sorted_at && sorted_at <= request.window.until {
+                request.received.push(sorted_at);
+            }
+        }
+    }
+
+    /// Records the times covered by the windows whose notes had time to arrive, returning whether
+    /// any did.
+    pub fn settle(&mut self, now: Instant) -> bool {
+        let (settled, pending): (Vec<Request>, Vec<Request>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|request| {
+                now.saturating_duration_since(request.requested_at) >= PAGE_TIMEOUT
+            });
+        self.pending = pending;
+        for request in &settled {
+            self.add(request.covered_since(), request.window.until);
+        }
+        !settled.is_empty()
+    }
+
+    /// Narrows the window to its newest part which wasn't requested yet, returning `None` if all
+    /// of it was. The bounds stay inclusive, so a note at a bound may be delivered again.
+    pub fn narrow(
+        &self,
+        since: Option<Timestamp>,
+        until: Timestamp,
+    ) -> Option<(Option<Timestamp>, Timestamp)> {
+        let mut requested = Coverage {
+            ranges: self.ranges.clone(),
+            pending: vec![],
+        };
+        for request in &self.pending {
+            requested.add(request.window.since, request.window.until);
+        }
+        requested.missing(since, until)
+    }
+
+    /// Narrows the window like [`Self::narrow`], but only to the times whose notes arrived.
+    pub fn missing(
+        &self,
+        since: Option<Timestamp>,
+        until: Timestamp,
+    ) -> Option<(Option<Timestamp>, Timestamp)> {
+        let until = self
+            .ranges
+            .iter()
+            .find(|range| range.since <= until && until <= range.until)
+            .map_or(until, |range| range.since);
+        let covered_below = self
+            .ranges
+            .iter()
+            .find(|range| range.until < until)
+            .map(|range| range.until);
+        let since = since.max(covered_below);
+        match since {
+            Some(since) if since >= until => None,
+            _ => Some((since, until)),
+        }
+    }
+}
+
+/// A page of older notes being loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingPage {
+    requested_at: Instant,
+    /// Number of notes in the tab when the page was requested
+    len: usize,
+    /// Whether the page stops at notes requested before, so that it may be empty while relays
+    /// still have older notes
+    bounded: bool,
+}
+
+/// Requests pages of older notes as the selection gets near the bottom of a tab.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Paginator {
+    page_size: usize,
+    pending: Option<PendingPage>,
+    last_requested_at: Option<Instant>,
+    /// Whether a page without a lower bound came back empty, i.e. relays have no older notes
+    exhausted: bool,
+}
+
+impl Paginator {
+    /// Returns the size of the next page if it should be requested now. A `bounded` page stops at
+    /// notes requested before.
+    pub fn poll(
+        &mut self,
+        config: &PaginationConfig,
+        selected: Option<usize>,
+        len: usize,
+        bounded: bool,
+        now: Instant,
+    ) -> Option<usize> {
+        if self.exhausted {
+            return None;
+        }
+
+        if let Some(page) = self.pending {
+            let elapsed = now.saturating_duration_since(page.requested_at);
+            if len > page.len && elapsed >= PAGE_SETTLE_TIME {
+                self.pending = None;
+            } else if len == page.len && elapsed >= PAGE_TIMEOUT {
+                self.pending = None;
+                if !page.bounded {
+                    self.exhausted = true;
+                    return None;
+                }
+            } else {
+                return None;
+            }
+        }
+
+        let remaining = len.checked_sub(selected? + 1)?;
+        if remaining > config.prefetch {
+            return None;
+        }
+
+        let fast = self
+            .last_requested_at
+            .is_some_and(|at| now.saturating_duration_since(at) < FAST_SCROLL_WINDOW);
+        self.page_size = if fast {
+            (self.page_size * 2).clamp(config.limit, config.max_limit.max(config.limit))
+        } else {
+            config.limit
+        };
+        self.pending = Some(PendingPage {
+            requested_at: now,
+            len,
+            bounded,
+        });
+        self.last_requested_at = Some(now);
+        Some(self.page_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    const CONFIG: PaginationConfig = PaginationConfig {
+        limit: 10,
+        max_limit: 30,
+        prefetch: 2,
+    };
+
+    #[rstest]
+    #[case(None, 20, None)]
+    #[case(Some(10), 20, None)]
+    #[case(Some(17), 20, Some(10))]
+    #[case(Some(19), 20, Some(10))]
+    #[case(Some(0), 0, None)]
+    fn test_poll_near_bottom(
+        #[case] selected: Option<usize>,
+        #[case] len: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        let mut paginator = Paginator::default();
+        assert_eq!(
+            paginator.poll(&CONFIG, selected, len, false, Instant::now()),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_poll_waits_for_page() {
+        let start = Instant::now();
+        let mut paginator = Paginator::default();
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start),
+            Some(10)
+        );
+
+        // Notes of the page are still arriving
+        let soon = start + Duration::from_millis(500);
+        assert_eq!(paginator.poll(&CONFIG, Some(19), 25, false, soon), None);
+
+        // The page has arrived, but the selection is not near the bottom anymore
+        let later = start + FAST_SCROLL_WINDOW * 2;
+        assert_eq!(paginator.poll(&CONFIG, Some(19), 30, false, later), None);
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(29), 30, false, later),
+            Some(10)
+        );
+    }
+
+    #[rstest]
+    fn test_poll_grows_page_when_scrolling_fast() {
+        let mut now = Instant::now();
+        let mut paginator = Paginator::default();
+        let mut sizes = vec![];
+        let mut len = 20;
+        for _ in 0..4 {
+            sizes.push(
+                paginator
+                    .poll(&CONFIG, Some(len - 1), len, false, now)
+                    .unwrap(),
+            );
+            len += 10;
+            now += PAGE_SETTLE_TIME;
+        }
+        assert_eq!(sizes, vec![10, 20, 30, 30]);
+
+        // The page is back to the configured size after a pause
+        now += FAST_SCROLL_WINDOW;
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(len - 1), len, false, now),
+            Some(10)
+        );
+    }
+
+    #[rstest]
+    fn test_gap_detector() {
+        let mut detector = GapDetector::default();
+        assert!(!detector.tick(Timestamp::from(100)));
+        assert!(!detector.tick(Timestamp::from(101)));
+        assert!(detector.tick(Timestamp::from(101 + GAP_THRESHOLD)));
+        assert!(!detector.tick(Timestamp::from(102 + GAP_THRESHOLD)));
+
+        detector.reset();
+        assert!(!detector.tick(Timestamp::from(103 + 2 * GAP_THRESHOLD)));
+    }
+
+    fn coverage(ranges: &[(u64, u64)]) -> Coverage {
+        let mut coverage = Coverage::default();
+        for (since, until) in ranges {
+            coverage.add(Timestamp::from(*since), Timestamp::from(*until));
+        }
+        coverage
+    }
+
+    #[rstest]
+    #[case(&[(10, 20)], &[(10, 20)])]
+    #[case(&[(10, 20), (30, 40)], &[(30, 40), (10, 20)])]
+    #[case(&[(10, 20), (15, 30)], &[(10, 30)])]
+    #[case(&[(10, 20), (30, 40), (20, 30)], &[(10, 40)])]
+    #[case(&[(30, 40), (10, 50)], &[(10, 50)])]
+    #[case(&[(20, 10)], &[])]
+    fn test_coverage_add(#[case] ranges: &[(u64, u64)], #[case] expected: &[(u64, u64)]) {
+        let expected: Vec<Gap> = expected
+            .iter()
+            .map(|(since, until)| Gap {
+                since: Timestamp::from(*since),
+                until: Timestamp::from(*until),
+            })
+            .collect();
+        assert_eq!(coverage(ranges).ranges, expected);
+    }
+
+    #[rstest]
+    // Nothing was requested
+    #[case(&[], None, 100, Some((None, 100)))]
+    // Older notes stop at the notes requested before
+    #[case(&[(10, 20)], None, 100, Some((Some(20), 100)))]
+    // Notes requested before are skipped
+    #[case(&[(10, 20)], None, 15, Some((None, 10)))]
+    #[case(&[(10, 20), (30, 40)], None, 35, Some((Some(20), 30)))]
+    #[case(&[(10, 20)], Some(5), 100, Some((Some(20), 100)))]
+    #[case(&[(10, 20)], Some(25), 100, Some((Some(25), 100)))]
+    // All of the window was requested
+    #[case(&[(10, 20)], Some(12), 18, None)]
+    #[case(&[(10, 20), (20, 30)], None, 25, Some((None, 10)))]
+    fn test_coverage_narrow(
+        #[case] ranges: &[(u64, u64)],
+        #[case] since: Option<u64>,
+        #[case] until: u64,
+        #[case] expected: Option<(Option<u64>, u64)>,
+    ) {
+        let expected =
+            expected.map(|(since, until)| (since.map(Timestamp::from), Timestamp::from(until)));
+        assert_eq!(
+            coverage(ranges).narrow(since.map(Timestamp::from), Timestamp::from(until)),
+            expected
+        );
+    }
+
+    #[rstest]
+    // Fewer notes than the limit cover the whole window
+    #[case(3, &[50, 60], &[(10, 100)], None)]
+    // A full page covers down to its oldest note, leaving the older part of the window
+    #[case(2, &[50, 60, 90], &[(60, 100)], Some((Some(10), 60)))]
+    // Notes out of the window are not counted
+    #[case(2, &[5, 50, 200], &[(10, 100)], None)]
+    fn test_coverage_request(
+        #[case] limit: usize,
+        #[case] received: &[u64],
+        #[case] expected: &[(u64, u64)],
+        #[case] missing: Option<(Option<u64>, u64)>,
+    ) {
+        let start = Instant::now();
+        let mut coverage = Coverage::default();
+        coverage.request(Timestamp::from(10), Timestamp::from(100), limit, start);
+        for sorted_at in received {
+            coverage.receive(Timestamp::from(*sorted_at));
+        }
+        // Pending windows are not requested again, but not covered yet
+        assert_eq!(
+            coverage.narrow(Some(Timestamp::from(10)), Timestamp::from(100)),
+            None
+        );
+        assert_eq!(coverage.settle(start + PAGE_SETTLE_TIME), false);
+        assert_eq!(coverage.ranges, vec![]);
+
+        assert_eq!(coverage.settle(start + PAGE_TIMEOUT), true);
+        let expected: Vec<Gap> = expected
+            .iter()
+            .map(|(since, until)| Gap {
+                since: Timestamp::from(*since),
+                until: Timestamp::from(*until),
+            })
+            .collect();
+        assert_eq!(coverage.ranges, expected);
+        let missing =
+            missing.map(|(since, until)| (since.map(Timestamp::from), Timestamp::from(until)));
+        assert_eq!(
+            coverage.missing(Some(Timestamp::from(10)), Timestamp::from(100)),
+            missing
+        );
+    }
+
+    #[rstest]
+    fn test_poll_continues_after_empty_bounded_page() {
+        let start = Instant::now();
+        let mut paginator = Paginator::default();
+        assert_eq!(paginator.poll(&CONFIG, Some(19), 20, true, start), Some(10));
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start + PAGE_TIMEOUT),
+            Some(10)
+        );
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start + PAGE_TIMEOUT * 2),
+            None
+        );
+    }
+
+    #[rstest]
+    fn test_poll_stops_when_exhausted() {
+        let start = Instant::now();
+        let mut paginator = Paginator::default();
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start),
+            Some(10)
+        );
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start + PAGE_TIMEOUT),
+            None
+        );
+        assert_eq!(
+            paginator.poll(&CONFIG, Some(19), 20, false, start + PAGE_TIMEOUT * 2),
+            None
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use color_eyre::eyre::{eyre, Result};
+use mlua::{Function, HookTriggers, Lua, LuaOptions, StdLib, Table, Value};
+use nostr_sdk::prelude::*;
+
+const HOOKS: &str = "nostui_hooks";
+const MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+/// Instructions between checks of the budget
+const INSTRUCTION_STEP: u32 = 1000;
+/// Steps a single hook call may take, i.e. about ten million instructions
+const INSTRUCTION_BUDGET: u32 = 10_000;
+
+/// The API given to plugins. Hooks are kept out of reach of plugins in the registry.
+const PRELUDE: &str = r#"
+local hooks = { event = {}, publish = {}, commands = {} }
+nostui = {}
+
+function nostui.on_event(f)
+  table.insert(hooks.event, { plugin = PLUGIN, fn = f })
+end
+
+function nostui.on_publish(f)
+  table.insert(hooks.publish, { plugin = PLUGIN, fn = f })
+end
+
+function nostui.command(name, f)
+  hooks.commands[name] = { plugin = PLUGIN, fn = f }
+end
+
+return hooks
+"#;
+
+/// What event hooks decided for an incoming event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub hidden: bool,
+    pub annotations: Vec<String>,
+}
+
+/// Lua plugins loaded from the `plugins` directory of the config directory.
+///
+/// Plugins run in a sandbox without `io`, `os`, `package` and `debug`, and with limits on memory
+/// and on instructions per hook call.
+pub struct Plugins {
+    lua: Lua,
+    names: Vec<String>,
+    steps: Arc<AtomicU32>,
+}
+
+impl Plugins {
+    pub fn new() -> Result<Self> {
+        let libs = StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8;
+        let lua = Lua::new_with(libs, LuaOptions::default()).map_err(lua_error)?;
+        lua.set_memory_limit(MEMORY_LIMIT).map_err(lua_error)?;
+
+        Self::prepare(&lua).map_err(lua_error)?;
+
+        let steps = Arc::new(AtomicU32::new(0));
+        let counter = steps.clone();
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(INSTRUCTION_STEP),
+            move |_, _| {
+                if counter.fetch_add(1, Ordering::Relaxed) >= INSTRUCTION_BUDGET {
+                    return Err(mlua::Error::runtime("too many instructions"));
+                }
+                Ok(())
+            },
+        );
+
+        Ok(Self {
+            lua,
+            names: vec![],
+            steps,
+        })
+    }
+
+    fn prepare(lua: &Lua) -> mlua::Result<()> {
+        let globals = lua.globals();
+        for name in ["dofile", "loadfile", "load"] {
+            globals.raw_remove(name)?;
+        }
+        let print = lua.create_function(|_, message: String| {
+            tracing::info!("[plugin] {message}");
+            Ok(())
+        })?;
+        globals.set("print", print)?;
+
+        let hooks: Table = lua.load(PRELUDE).eval()?;
+        lua.set_named_registry_value(HOOKS, hooks)
+    }
+
+    /// Loads `*.lua` files in `dir` in the order of their names. Returns errors of the plugins which
+    /// failed to load, while the others are kept.
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+            .collect();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                let result = fs::read_to_string(&path)
+                    .map_err(|e| eyre!(e))
+                    .and_then(|source| self.load(&name, &source));
+                result.err().map(|e| format!("{name}: {e}"))
+            })
+            .collect()
+    }
+
+    pub fn load(&mut self, name: &str, source: &str) -> Result<()> {
+        self.lua.globals().set("PLUGIN", name).map_err(lua_error)?;
+        self.steps.store(0, Ordering::Relaxed);
+        self.lua
+            .load(source)
+            .set_name(name)
+            .exec()
+            .map_err(lua_error)?;
+        self.names.push(name.to_string());
+        Ok(())
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn hooks(&self, kind: &str) -> Result<Table<'_>> {
+        let hooks: Table = self.lua.named_registry_value(HOOKS).map_err(lua_error)?;
+        hooks.get(kind).map_err(lua_error)
+    }
+
+    /// Registered command names, sorted.
+    pub fn commands(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .hooks("commands")
+            .map(|commands| {
+                commands
+                    .pairs::<String, Value>()
+                    .filter_map(|pair| pair.ok().map(|(name, _)| name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    fn call<'lua, R: mlua::FromLuaMulti<'lua>>(
+        &'lua self,
+        hook: &Table<'lua>,
+        args: impl mlua::IntoLuaMulti<'lua>,
+    ) -> Result<R> {
+        let plugin: String = hook.get("plugin").map_err(lua_error)?;
+        let f: Function = hook.get("fn").map_err(lua_error)?;
+        self.steps.store(0, Ordering::Relaxed);
+        f.call(args).map_err(|e| eyre!("{plugin}: {e}"))
+    }
+
+    /// Runs event hooks. A hook hides the event by returning `false`, or annotates it by returning
+    /// a string. Failing hooks are logged and ignored.
+    pub fn check_event(&self, event: &Event) -> Verdict {
+        let mut verdict = Verdict::default();
+        let Ok(hooks) = self.hooks("event") else {
+            return verdict;
+        };
+
+        for hook in hooks
+            .sequence_values::<Table>()
+            .filter_map(|hook| hook.ok())
+        {
+            let result = event_table(&self.lua, event)
+                .map_err(lua_error)
+                .and_then(|table| self.call::<Value>(&hook, table));
+            match result {
+                Ok(Value::Boolean(false)) => verdict.hidden = true,
+                Ok(Value::String(annotation)) => verdict
+                    .annotations
+                    .push(annotation.to_string_lossy().to_string()),
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Event hook failed"),
+            }
+        }
+
+        verdict
+    }
+
+    /// Signs the event built by `builder` after publish hooks changed its tags. A hook receives the
+    /// event without `id`, `pubkey` and `sig`, and returns new tags, or `nil` to keep them.
+    ///
+    /// Failing hooks, e.g. returning invalid tags, are logged and ignored, keeping the tags they
+    /// were given. Their errors are returned with the event.
+    pub fn sign(&self, builder: EventBuilder, keys: &Keys) -> Result<(Event, Vec<String>)> {
+        let unsigned = builder.to_unsigned_event(keys.public_key());
+        let mut tags = unsigned.tags.clone();
+        let mut failures = vec![];
+        if let Ok(hooks) = self.hooks("publish") {
+            for hook in hooks
+                .sequence_values::<Table>()
+                .filter_map(|hook| hook.ok())
+            {
+                match self.publish_hook(&hook, &unsigned, &tags) {
+                    Ok(Some(new_tags)) => tags = new_tags,
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Publish hook failed");
+                        failures.push(e.to_string());
+                    }
+                }
+            }
+        }
+
+        let event = EventBuilder::new(unsigned.kind, unsigned.content, tags)
+            .custom_created_at(unsigned.created_at)
+            .to_event(keys)?;
+        Ok((event, failures))
+    }
+
+    /// Runs a publish hook, returning the new tags if it changed them.
+    fn publish_hook(
+        &self,
+        hook: &Table<'_>,
+        unsigned: &UnsignedEvent,
+        tags: &[Tag],
+    ) -> Result<Option<Vec<Tag>>> {
+        let table = self.lua.create_table().map_err(lua_error)?;
+        table
+            .set("kind", unsigned.kind.as_u64())
+            .map_err(lua_error)?;
+        table
+            .set("content", unsigned.content.clone())
+            .map_err(lua_error)?;
+        table
+            .set("tags", tags_table(&self.lua, tags).map_err(lua_error)?)
+            .map_err(lua_error)?;
+
+        let Some(new_tags) = self.call::<Option<Vec<Vec<String>>>>(hook, table)? else {
+            return Ok(None);
+        };
+        let plugin: String = hook.get("plugin").map_err(lua_error)?;
+        let new_tags = new_tags
+            .into_iter()
+            .map(Tag::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|e| eyre!("{plugin}: {e}"))?;
+        Ok(Some(new_tags))
+    }
+
+    /// Runs the command with the selected note. Returns the message the command returned, if any.
+    pub fn run_command(&self, name: &str, note: Option<&Event>) -> Result<Option<String>> {
+        let command: Option<Table> = self.hooks("commands")?.get(name).map_err(lua_error)?;
+        let command = command.ok_or_else(|| eyre!("No such plugin command: {name}"))?;
+        let note = note
+            .map(|event| event_table(&self.lua, event))
+            .transpose()
+            .map_err(lua_error)?;
+        self.call(&command, note)
+    }
+}
+
+fn lua_error(e: mlua::Error) -> color_eyre::Report {
+    eyre!("{e}")
+}
+
+fn tags_table<'lua>(lua: &'lua Lua, tags: &[Tag]) -> mlua::Result<Table<'lua>> {
+    lua.create_sequence_from(tags.iter().map(|tag| tag.as_vec()))
+}
+
+fn event_table<'lua>(lua: &'lua Lua, event: &Event) -> mlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    table.set("id", event.id.to_hex())?;
+    table.set("pubkey", event.pubkey.to_hex())?;
+    table.set("kind", event.kind.as_u64())?;
+    table.set("created_at", event.created_at.as_u64())?;
+    table.set("content", event.content.clone())?;
+    table.set("tags", tags_table(lua, &event.tags)?)?;
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn note(content: &str) -> Event {
+        EventBuilder::text_note(content, [Tag::Hashtag(String::from("nostr"))])
+            .to_event(&Keys::generate())
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_check_event() {
+        let mut plugins = Plugins::new().unwrap();
+        plugins
+            .load(
+                "filter",
+                r##"
+                nostui.on_event(function(event)
+                  if event.content:find("spam") then return false end
+                end)
+                nostui.on_event(function(event)
+                  if event.tags[1][2] == "nostr" then return "#nostr" end
+                end)
+                "##,
+            )
+            .unwrap();
+
+        assert_eq!(
+            plugins.check_event(&note("gm")),
+            Verdict {
+                hidden: false,
+                annotations: vec![String::from("#nostr")]
+            }
+        );
+        assert!(plugins.check_event(&note("buy spam")).hidden);
+        assert_eq!(plugins.names(), [String::from("filter")]);
+    }
+
+    #[rstest]
+    fn test_sign() {
+        let mut plugins = Plugins::new().unwrap();
+        plugins
+            .load(
+                "client_tag",
+                r#"
+                nostui.on_publish(function(event)
+                  local tags = event.tags
+                  table.insert(tags, { "client", "nostui" })
+                  return tags
+                end)
+                "#,
+            )
+            .unwrap();
+
+        let keys = Keys::generate();
+        let (event, failures) = plugins
+            .sign(
+                EventBuilder::text_note("gm", [Tag::Hashtag(String::from("nostr"))]),
+                &keys,
+            )
+            .unwrap();
+        assert_eq!(failures, [] as [String; 0]);
+        assert_eq!(event.verify(), Ok(()));
+        assert_eq!(
+            event
+                .tags
+                .iter()
+                .map(|tag| tag.as_vec())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![String::from("t"), String::from("nostr")],
+                vec![String::from("client"), String::from("nostui")]
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_run_command() {
+        let mut plugins = Plugins::new().unwrap();
+        plugins
+            .load(
+                "length",
+                r#"
+                nostui.command("length", function(note)
+                  if note == nil then return nil end
+                  return "length: " .. utf8.len(note.content)
+                end)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(plugins.commands(), vec![String::from("length")]);
+        assert_eq!(
+            plugins
+                .run_command("length", Some(&note("こんにちは")))
+                .unwrap(),
+            Some(String::from("length: 5"))
+        );
+        assert_eq!(plugins.run_command("length", None).unwrap(), None);
+        assert!(plugins.run_command("unknown", None).is_err());
+    }
+
+    #[rstest]
+    #[case("return io.open('/etc/passwd')")]
+    #[case("return os.execute('ls')")]
+    #[case("return require('os')")]
+    #[case("return loadfile('/etc/passwd')")]
+    #[case("while true do end")]
+    fn test_sandbox(#[case] source: &str) {
+        let mut plugins = Plugins::new().unwrap();
+        assert!(plugins.load("evil", source).is_err());
+        assert_eq!(plugins.names(), [] as [String; 0]);
+    }
+
+    #[rstest]
+    fn test_failing_hook_is_ignored() {
+        let mut plugins = Plugins::new().unwrap();
+        plugins
+            .load(
+                "broken",
+                "nostui.on_event(function(event) error('oops') end)",
+            )
+            .unwrap();
+        assert_eq!(plugins.check_event(&note("gm")), Verdict::default());
+    }
+
+    #[rstest]
+    #[case("nostui.on_publish(function(event) error('oops') end)")]
+    #[case("nostui.on_publish(function(event) return { {} } end)")]
+    #[case("nostui.on_publish(function(event) while true do end end)")]
+    fn test_failing_publish_hook_is_ignored(#[case] source: &str) {
+        let mut plugins = Plugins::new().unwrap();
+        plugins.load("broken", source).unwrap();
+        plugins
+            .load(
+                "client_tag",
+                r#"
+                nostui.on_publish(function(event)
+                  local tags = event.tags
+                  table.insert(tags, { "client", "nostui" })
+                  return tags
+                end)
+                "#,
+            )
+            .unwrap();
+
+        let (event, failures) = plugins
+            .sign(EventBuilder::text_note("gm", []), &Keys::generate())
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].starts_with("broken: "));
+        assert_eq!(
+            event
+                .tags
+                .iter()
+                .map(|tag| tag.as_vec())
+                .collect::<Vec<_>>(),
+            vec![vec![String::from("client"), String::from("nostui")]]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use crate::config::PowerConfig;
+
+/// Whether the app is paused to save battery and bandwidth while the terminal is unfocused.
+///
+/// Timers and renders stop once the terminal has been unfocused for the configured delay, so that
+/// switching windows briefly doesn't pause anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PowerState {
+    unfocused_since: Option<Instant>,
+    paused: bool,
+}
+
+impl PowerState {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn focus_lost(&mut self, now: Instant) {
+        self.unfocused_since.get_or_insert(now);
+    }
+
+    /// Resumes on focus, returning `true` if the app was paused.
+    pub fn focus_gained(&mut self) -> bool {
+        self.unfocused_since = None;
+        std::mem::take(&mut self.paused)
+    }
+
+    /// Pauses once the terminal has been unfocused for the delay, returning `true` when it pauses.
+    pub fn tick(&mut self, config: &PowerConfig, now: Instant) -> bool {
+        let Some(unfocused_since) = self.unfocused_since else {
+            return false;
+        };
+        let delay = Duration::from_secs(config.unfocused_delay);
+        if self.paused
+            || !config.pause_when_unfocused
+            || now.saturating_duration_since(unfocused_since) < delay
+        {
+            return false;
+        }
+
+        self.paused = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(true, 30, true)]
+    #[case(true, 31, true)]
+    #[case(true, 29, false)]
+    #[case(false, 30, false)]
+    fn test_tick(#[case] pause_when_unfocused: bool, #[case] secs: u64, #[case] expected: bool) {
+        let config = PowerConfig {
+            pause_when_unfocused,
+            unfocused_delay: 30,
+            ..PowerConfig::default()
+        };
+        let start = Instant::now();
+        let mut power = PowerState::default();
+        assert_eq!(
+            power.tick(&config, start + Duration::from_secs(secs)),
+            false
+        );
+
+        power.focus_lost(start);
+        // Repeated focus events don't restart the delay
+        power.focus_lost(start + Duration::from_secs(10));
+        assert_eq!(
+            power.tick(&config, start + Duration::from_secs(secs)),
+            expected
+        );
+        assert_eq!(power.is_paused(), expected);
+        // Paused only once
+        assert_eq!(
+            power.tick(&config, start + Duration::from_secs(secs)),
+            false
+        );
+
+        assert_eq!(power.focus_gained(), expected);
+        assert_eq!(power.is_paused(), false);
+        assert_eq!(power.focus_gained(), false);
+    }
+}
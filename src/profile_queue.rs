@@ -0,0 +1,165 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::{Duration, Instant};
+
+use nostr_sdk::PublicKey;
+
+/// Time to wait for a requested profile before requesting it again, doubled on each attempt.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Requests of a profile, after which the author is left without a name.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// How soon the profile of an author is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Authors seen in notes, e.g. of hashtag tabs or replies from strangers
+    Deferred,
+    /// Me and my followings
+    Followed,
+    /// Authors shown on the screen
+    Visible,
+}
+
+/// Profiles waiting to be fetched, the most wanted first and then in the order they were asked.
+///
+/// Priorities are raised by pushing an author again. Entries outranked this way are left in the
+/// heap and skipped when popped.
+#[derive(Debug, Default)]
+pub struct ProfileQueue {
+    heap: BinaryHeap<(Priority, Reverse<u64>, PublicKey)>,
+    queued: HashMap<PublicKey, Priority>,
+    seq: u64,
+}
+
+impl ProfileQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Queues the author, or raises the priority if the author is queued with a lower one.
+    pub fn push(&mut self, pubkey: PublicKey, priority: Priority) {
+        if self
+            .queued
+            .get(&pubkey)
+            .is_some_and(|queued| *queued >= priority)
+        {
+            return;
+        }
+
+        self.queued.insert(pubkey, priority);
+        self.heap.push((priority, Reverse(self.seq), pubkey));
+        self.seq += 1;
+    }
+
+    /// Takes up to `limit` authors to fetch, the most wanted first.
+    pub fn pop(&mut self, limit: usize) -> Vec<PublicKey> {
+        let mut popped = vec![];
+        while popped.len() < limit {
+            let Some((priority, _, pubkey)) = self.heap.pop() else {
+                break;
+            };
+            if self.queued.get(&pubkey) == Some(&priority) {
+                self.queued.remove(&pubkey);
+                popped.push(pubkey);
+            }
+        }
+        popped
+    }
+}
+
+/// Profiles requested from relays, which are requested again while they don't arrive, e.g. as
+/// the relays which were asked first don't have them.
+#[derive(Debug, Default)]
+pub struct ProfileRequests {
+    /// Time of the last request and the number of requests
+    requests: HashMap<PublicKey, (Instant, u32)>,
+}
+
+impl ProfileRequests {
+    pub fn insert(&mut self, pubkey: PublicKey, now: Instant) {
+        let attempts = self
+            .requests
+            .get(&pubkey)
+            .map_or(0, |(_, attempts)| *attempts);
+        self.requests.insert(pubkey, (now, attempts + 1));
+    }
+
+    /// Forgets the requests once the profile arrives.
+    pub fn remove(&mut self, pubkey: &PublicKey) {
+        self.requests.remove(pubkey);
+    }
+
+    /// Returns whether the profile should be requested: it never was, or the last request has
+    /// been waited for long enough and attempts are left.
+    pub fn is_due(&self, pubkey: &PublicKey, now: Instant) -> bool {
+        let Some((requested_at, attempts)) = self.requests.get(pubkey) else {
+            return true;
+        };
+        *attempts < MAX_ATTEMPTS
+            && now.saturating_duration_since(*requested_at)
+                >= RETRY_INTERVAL * 2u32.pow(attempts - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::Keys;
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_pop() {
+        let [alice, bob, carol, dave] = [(); 4].map(|_| Keys::generate().public_key());
+        let mut queue = ProfileQueue::new();
+        queue.push(alice, Priority::Deferred);
+        queue.push(bob, Priority::Deferred);
+        queue.push(carol, Priority::Followed);
+        queue.push(dave, Priority::Deferred);
+
+        // Bob comes into view
+        queue.push(bob, Priority::Visible);
+        // Lowering a priority does nothing
+        queue.push(carol, Priority::Deferred);
+        assert_eq!(queue.len(), 4);
+
+        assert_eq!(queue.pop(2), vec![bob, carol]);
+        assert_eq!(queue.pop(5), vec![alice, dave]);
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(5), vec![]);
+    }
+
+    #[rstest]
+    fn test_profile_requests() {
+        let [alice, bob] = [(); 2].map(|_| Keys::generate().public_key());
+        let start = Instant::now();
+        let mut requests = ProfileRequests::default();
+        assert!(requests.is_due(&alice, start));
+
+        // Requested again after 30 and 60 seconds, and then never
+        requests.insert(alice, start);
+        assert!(!requests.is_due(&alice, start + Duration::from_secs(29)));
+        assert!(requests.is_due(&alice, start + RETRY_INTERVAL));
+        let retried_at = start + RETRY_INTERVAL;
+        requests.insert(alice, retried_at);
+        assert!(!requests.is_due(&alice, retried_at + RETRY_INTERVAL));
+        assert!(requests.is_due(&alice, retried_at + RETRY_INTERVAL * 2));
+        let retried_at = retried_at + RETRY_INTERVAL * 2;
+        requests.insert(alice, retried_at);
+        assert!(!requests.is_due(&alice, retried_at + Duration::from_secs(3600)));
+
+        requests.insert(bob, start);
+        requests.remove(&bob);
+        assert!(requests.is_due(&bob, start));
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::timeline::TimelineTabType;
+
+const SESSION_FILE: &str = "session.json";
+
+/// Returns whether `name` can be used as a workspace name, which becomes a part of the file name.
+pub fn is_valid_workspace(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn session_file(workspace: Option<&str>) -> String {
+    match workspace {
+        Some(name) => format!("session-{name}.json"),
+        None => String::from(SESSION_FILE),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TabSession {
+    pub tab_type: TimelineTabType,
+    /// The selected note, restored once it is received again
+    #[serde(default)]
+    pub selected: Option<EventId>,
+    /// Notes shown above the selected one, to restore where it was in the view
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// Open tabs saved on quit and restored on the next launch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<TabSession>,
+    #[serde(default)]
+    pub active_tab: usize,
+    /// When the app quit, which marks the notes received since then on the next launch
+    #[serde(default)]
+    pub last_seen: Option<Timestamp>,
+}
+
+impl Session {
+    /// Opens `tabs` with the first one active. Duplicates are opened once.
+    pub fn with_tabs(tabs: impl IntoIterator<Item = TimelineTabType>) -> Self {
+        let mut session = Self::default();
+        for tab_type in tabs {
+            if !session.tabs.iter().any(|tab| tab.tab_type == tab_type) {
+                session.tabs.push(TabSession {
+                    tab_type,
+                    selected: None,
+                    offset: 0,
+                });
+            }
+        }
+        session
+    }
+
+    /// Loads the session of the workspace from the data directory, or returns `None` if there is no
+    /// saved session. Each workspace has its own session, and `None` is the default one.
+    pub fn load(data_dir: &Path, workspace: Option<&str>) -> Result<Option<Self>> {
+        let path = data_dir.join(session_file(workspace));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, data_dir: &Path, workspace: Option<&str>) -> Result<()> {
+        fs::create_dir_all(data_dir)?;
+        fs::write(
+            data_dir.join(session_file(workspace)),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_session_save_and_load() {
+        let dir = std::env::temp_dir().join(format!(
+            "nostui-session-test-{}",
+            Keys::generate().public_key()
+        ));
+        assert_eq!(Session::load(&dir, None).unwrap(), None);
+
+        let session = Session {
+            tabs: vec![
+                TabSession {
+                    tab_type: TimelineTabType::Home,
+                    selected: None,
+                    offset: 0,
+                },
+                TabSession {
+                    tab_type: TimelineTabType::UserTimeline {
+                        pubkey: Keys::generate().public_key(),
+                    },
+                    selected: Some(EventId::all_zeros()),
+                    offset: 3,
+                },
+            ],
+            active_tab: 1,
+            last_seen: Some(Timestamp::from(1_700_000_000)),
+        };
+        session.save(&dir, None).unwrap();
+        assert_eq!(Session::load(&dir, None).unwrap(), Some(session.clone()));
+        // Workspaces don't share sessions
+        assert_eq!(Session::load(&dir, Some("art")).unwrap(), None);
+        session.save(&dir, Some("art")).unwrap();
+        assert_eq!(Session::load(&dir, Some("art")).unwrap(), Some(session));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid_workspace() {
+        assert!(is_valid_workspace("art_2-b"));
+        assert!(!is_valid_workspace(""));
+        assert!(!is_valid_workspace("../art"));
+    }
+}
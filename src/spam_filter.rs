@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+
+use nostr_sdk::{nips::nip13, prelude::*};
+use serde::Deserialize;
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Points given for each factor of a score.
+const FOLLOWING_POINTS: u64 = 100;
+const FOLLOW_OF_FOLLOWING_POINTS: u64 = 50;
+const POW_POINTS_PER_BIT: u64 = 2;
+const MAX_AGE_POINTS: u64 = 30;
+
+/// What to do with notes scored below the threshold.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Fold them into a single line
+    #[default]
+    Collapse,
+    /// Keep them out of timelines
+    Hide,
+}
+
+/// Distance from me in the follow graph.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrustDistance {
+    Me,
+    Following,
+    FollowOfFollowing,
+    Unknown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub distance: TrustDistance,
+    /// Leading zero bits of the event ID (NIP-13)
+    pub pow: u8,
+    /// Days since the oldest event seen from the author
+    pub age_days: u64,
+}
+
+impl Score {
+    pub fn total(&self) -> u64 {
+        let trust = match self.distance {
+            TrustDistance::Me | TrustDistance::Following => FOLLOWING_POINTS,
+            TrustDistance::FollowOfFollowing => FOLLOW_OF_FOLLOWING_POINTS,
+            TrustDistance::Unknown => 0,
+        };
+        trust + u64::from(self.pow) * POW_POINTS_PER_BIT + self.age_days.min(MAX_AGE_POINTS)
+    }
+}
+
+/// Scores notes by web-of-trust distance, PoW and account age to filter out spam.
+///
+/// The age of an account is estimated from the oldest event seen from it, e.g. its profile.
+#[derive(Debug, Clone, Default)]
+pub struct SpamFilter {
+    /// Score below which notes are filtered. `0` disables the filter.
+    threshold: u64,
+    me: Option<PublicKey>,
+    followings: HashSet<PublicKey>,
+    /// Contact lists of followings and when they were created
+    contact_lists: HashMap<PublicKey, (Timestamp, HashSet<PublicKey>)>,
+    first_seen: HashMap<PublicKey, Timestamp>,
+}
+
+impl SpamFilter {
+    pub fn new(threshold: u64, me: Option<PublicKey>) -> Self {
+        Self {
+            threshold,
+            me,
+            ..Self::default()
+        }
+    }
+
+    /// Whether notes are filtered. Without an identity there is no web of trust to score with.
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0 && self.me.is_some()
+    }
+
+    pub fn set_followings(&mut self, followings: impl IntoIterator<Item = PublicKey>) {
+        self.followings = followings.into_iter().collect();
+        self.contact_lists
+            .retain(|pubkey, _| self.followings.contains(pubkey));
+    }
+
+    pub fn followings(&self) -> &HashSet<PublicKey> {
+        &self.followings
+    }
+
+    /// Followings of my followings, from their contact lists.
+    pub fn contact_lists(&self) -> impl Iterator<Item = &HashSet<PublicKey>> {
+        self.contact_lists
+            .values()
+            .map(|(_, followings)| followings)
+    }
+
+    /// Adds the contact list of a following. Returns `false` if it is ignored.
+    pub fn add_contact_list(&mut self, contact_list: &Event) -> bool {
+        if !self.followings.contains(&contact_list.pubkey) {
+            return false;
+        }
+        if let Some((created_at, _)) = self.contact_lists.get(&contact_list.pubkey) {
+            if *created_at >= contact_list.created_at {
+                return false;
+            }
+        }
+
+        let followings = contact_list.public_keys().copied().collect();
+        self.contact_lists
+            .insert(contact_list.pubkey, (contact_list.created_at, followings));
+        true
+    }
+
+    /// Records an event of any kind to estimate the age of its author.
+    pub fn observe(&mut self, event: &Event) {
+        self.first_seen
+            .entry(event.pubkey)
+            .and_modify(|seen| *seen = (*seen).min(event.created_at))
+            .or_insert(event.created_at);
+    }
+
+    pub fn distance(&self, pubkey: &PublicKey) -> TrustDistance {
+        if self.me.as_ref() == Some(pubkey) {
+            TrustDistance::Me
+        } else if self.followings.contains(pubkey) {
+            TrustDistance::Following
+        } else if self
+            .contact_lists
+            .values()
+            .any(|(_, followings)| followings.contains(pubkey))
+        {
+            TrustDistance::FollowOfFollowing
+        } else {
+            TrustDistance::Unknown
+        }
+    }
+
+    pub fn score(&self, event: &Event, now: Timestamp) -> Score {
+        let first_seen = self
+            .first_seen
+            .get(&event.pubkey)
+            .copied()
+            .unwrap_or(event.created_at)
+            .min(event.created_at);
+        Score {
+            distance: self.distance(&event.pubkey),
+            pow: nip13::get_leading_zero_bits(event.id),
+            age_days: now.as_u64().saturating_sub(first_seen.as_u64()) / SECONDS_PER_DAY,
+        }
+    }
+
+    /// Returns the score of a note if it is below the threshold.
+    pub fn filter(&self, event: &Event, now: Timestamp) -> Option<Score> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let score = self.score(event, now);
+        (score.total() < self.threshold).then_some(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    const DAY: u64 = SECONDS_PER_DAY;
+
+    fn event(keys: &Keys, kind: Kind, tags: Vec<Tag>, created_at: u64) -> Event {
+        EventBuilder::new(kind, "", tags)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_distance() {
+        let [me, following, fof, stranger] = [(); 4].map(|_| Keys::generate());
+        let mut filter = SpamFilter::new(10, Some(me.public_key()));
+        filter.set_followings([following.public_key()]);
+        let contact_list = event(
+            &following,
+            Kind::ContactList,
+            vec![Tag::public_key(fof.public_key())],
+            0,
+        );
+        assert!(filter.add_contact_list(&contact_list));
+        // Contact lists of strangers don't count
+        let contact_list = event(
+            &stranger,
+            Kind::ContactList,
+            vec![Tag::public_key(stranger.public_key())],
+            0,
+        );
+        assert!(!filter.add_contact_list(&contact_list));
+
+        assert_eq!(filter.distance(&me.public_key()), TrustDistance::Me);
+        assert_eq!(
+            filter.distance(&following.public_key()),
+            TrustDistance::Following
+        );
+        assert_eq!(
+            filter.distance(&fof.public_key()),
+            TrustDistance::FollowOfFollowing
+        );
+        assert_eq!(
+            filter.distance(&stranger.public_key()),
+            TrustDistance::Unknown
+        );
+
+        // Unfollowing drops the contact list
+        filter.set_followings([]);
+        assert_eq!(filter.distance(&fof.public_key()), TrustDistance::Unknown);
+    }
+
+    #[rstest]
+    fn test_filter_by_age() {
+        let me = Keys::generate();
+        // Fixed for the IDs of its events to have the same PoW every time
+        let stranger =
+            Keys::parse("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let mut filter = SpamFilter::new(10, Some(me.public_key()));
+        let now = Timestamp::from(100 * DAY);
+
+        let note = event(&stranger, Kind::TextNote, vec![], 100 * DAY);
+        assert_eq!(
+            filter.filter(&note, now).map(|score| score.age_days),
+            Some(0)
+        );
+
+        // A profile created long ago
+        filter.observe(&event(&stranger, Kind::Metadata, vec![], 80 * DAY));
+        let score = filter.score(&note, now);
+        assert_eq!(score.age_days, 20);
+        assert_eq!(score.pow, 3);
+        assert_eq!(score.total(), 20 + 3 * POW_POINTS_PER_BIT);
+        assert_eq!(filter.filter(&note, now), None);
+    }
+
+    #[rstest]
+    fn test_filter_pow() {
+        let me = Keys::generate();
+        let stranger = Keys::generate();
+        let filter = SpamFilter::new(10, Some(me.public_key()));
+        let mined = EventBuilder::text_note("gm", [])
+            .to_pow_event(&stranger, 5)
+            .unwrap();
+
+        assert!(filter.score(&mined, mined.created_at).pow >= 5);
+        assert_eq!(filter.filter(&mined, mined.created_at), None);
+    }
+
+    #[rstest]
+    #[case(0, Some(Keys::generate().public_key()))]
+    #[case(10, None)]
+    fn test_disabled(#[case] threshold: u64, #[case] me: Option<PublicKey>) {
+        let filter = SpamFilter::new(threshold, me);
+        let note = event(&Keys::generate(), Kind::TextNote, vec![], 0);
+        assert!(!filter.is_enabled());
+        assert_eq!(filter.filter(&note, Timestamp::from(0)), None);
+    }
+}
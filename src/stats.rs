@@ -0,0 +1,418 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    mem,
+    time::{Duration, Instant},
+};
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::nostr::nip28::PUBLIC_CHATS_KIND;
+
+/// Period over which rates are averaged.
+const PERIOD: Duration = Duration::from_secs(1);
+
+/// Counts occurrences, e.g. ticks or frames, and averages them per second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateCounter {
+    start: Instant,
+    count: u64,
+    rate: f64,
+}
+
+impl Default for RateCounter {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl RateCounter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            count: 0,
+            rate: 0.0,
+        }
+    }
+
+    /// Counts an occurrence, updating the rate once a period passes.
+    pub fn tick(&mut self, now: Instant) {
+        self.count += 1;
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= PERIOD {
+            self.rate = self.count as f64 / elapsed.as_secs_f64();
+            self.start = now;
+            self.count = 0;
+        }
+    }
+
+    /// Occurrences per second in the last period.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+/// Messages received from a relay in a period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayTraffic {
+    /// EVENT messages
+    pub events: u64,
+    /// All messages, including EVENT
+    pub messages: u64,
+}
+
+/// Traffic of the relay connection in a period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficStats {
+    /// Traffic keyed by relay URL
+    pub relays: BTreeMap<String, RelayTraffic>,
+    pub period: Duration,
+    /// Open subscriptions of timeline tabs
+    pub subscriptions: usize,
+    /// Connected relays and all of them, except inboxes of other users
+    pub connected_relays: usize,
+    pub total_relays: usize,
+}
+
+impl TrafficStats {
+    pub fn per_sec(&self, count: u64) -> f64 {
+        if self.period.is_zero() {
+            return 0.0;
+        }
+        count as f64 / self.period.as_secs_f64()
+    }
+
+    /// Relays from the busiest, as slow relays stand out at the bottom.
+    pub fn busiest(&self) -> Vec<(&String, &RelayTraffic)> {
+        let mut relays: Vec<(&String, &RelayTraffic)> = self.relays.iter().collect();
+        relays.sort_by_key(|(_, traffic)| Reverse(traffic.events));
+        relays
+    }
+}
+
+/// Counts messages from relays to sample their traffic.
+#[derive(Debug, Clone)]
+pub struct TrafficMeter {
+    start: Instant,
+    relays: BTreeMap<String, RelayTraffic>,
+}
+
+impl TrafficMeter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            relays: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, relay: String, is_event: bool) {
+        let traffic = self.relays.entry(relay).or_default();
+        traffic.events += u64::from(is_event);
+        traffic.messages += 1;
+    }
+
+    /// Returns the traffic once a period passes and starts a new period.
+    /// Relays which sent nothing in the period are kept with zero counts.
+    pub fn sample(&mut self, now: Instant, subscriptions: usize) -> Option<TrafficStats> {
+        let period = now.saturating_duration_since(self.start);
+        if period < PERIOD {
+            return None;
+        }
+
+        self.start = now;
+        let relays = self
+            .relays
+            .iter_mut()
+            .map(|(relay, traffic)| (relay.clone(), mem::take(traffic)))
+            .collect();
+        Some(TrafficStats {
+            relays,
+            period,
+            subscriptions,
+            ..TrafficStats::default()
+        })
+    }
+}
+
+/// Events a relay needs to send before it is judged by the kinds of its events.
+const MIN_KIND_SAMPLE: u64 = 100;
+
+/// Share of events of unused kinds over which a relay is suggested to be removed.
+const UNUSED_KIND_SHARE: f64 = 0.5;
+
+/// Whether nostui shows or otherwise uses events of the kind.
+pub fn is_used_kind(kind: Kind) -> bool {
+    matches!(
+        kind,
+        Kind::Metadata
+            | Kind::TextNote
+            | Kind::ContactList
+            | Kind::EncryptedDirectMessage
+            | Kind::EventDeletion
+            | Kind::Repost
+            | Kind::Reaction
+            | Kind::ChannelCreation
+            | Kind::ChannelMetadata
+            | Kind::ChannelMessage
+            | Kind::ZapReceipt
+            | Kind::RelayList
+    ) || kind == Kind::from(PUBLIC_CHATS_KIND)
+}
+
+/// Events received from a relay by kind since startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayKinds {
+    pub relay: String,
+    /// Kinds and their counts, from the most frequent
+    pub kinds: Vec<(u64, u64)>,
+    pub total: u64,
+    /// Events of kinds which nostui doesn't use
+    pub unused: u64,
+}
+
+impl RelayKinds {
+    pub fn unused_share(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.unused as f64 / self.total as f64
+    }
+
+    /// Whether the relay sends mostly events nostui doesn't use, so it's worth pruning.
+    pub fn is_wasteful(&self) -> bool {
+        self.total >= MIN_KIND_SAMPLE && self.unused_share() >= UNUSED_KIND_SHARE
+    }
+}
+
+/// Counts events from relays by kind, including duplicates sent by several relays.
+#[derive(Debug, Clone, Default)]
+pub struct KindCounter {
+    relays: BTreeMap<String, BTreeMap<u64, u64>>,
+}
+
+impl KindCounter {
+    pub fn record(&mut self, relay: String, kind: Kind) {
+        *self
+            .relays
+            .entry(relay)
+            .or_default()
+            .entry(kind.as_u64())
+            .or_default() += 1;
+    }
+
+    /// Relays from the one sending the largest share of unused kinds.
+    pub fn breakdown(&self) -> Vec<RelayKinds> {
+        let mut relays: Vec<RelayKinds> = self
+            .relays
+            .iter()
+            .map(|(relay, counts)| {
+                let mut kinds: Vec<(u64, u64)> = counts.iter().map(|(k, n)| (*k, *n)).collect();
+                kinds.sort_by_key(|(kind, count)| (Reverse(*count), *kind));
+                let unused = counts
+                    .iter()
+                    .filter(|(kind, _)| !is_used_kind(Kind::from(**kind)))
+                    .map(|(_, count)| count)
+                    .sum();
+                RelayKinds {
+                    relay: relay.clone(),
+                    kinds,
+                    total: counts.values().sum(),
+                    unused,
+                }
+            })
+            .collect();
+        relays.sort_by(|a, b| {
+            b.is_wasteful()
+                .cmp(&a.is_wasteful())
+                .then_with(|| b.unused_share().total_cmp(&a.unused_share()))
+                .then_with(|| a.relay.cmp(&b.relay))
+        });
+        relays
+    }
+}
+
+/// Actions and notifications waiting to be handled by the app.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueDepth {
+    pub actions: usize,
+    pub notifications: usize,
+}
+
+/// Notes kept in memory and roughly how much memory they take.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteStoreStats {
+    pub notes: usize,
+    pub bytes: usize,
+}
+
+impl NoteStoreStats {
+    pub fn add(&mut self, event: &Event) {
+        self.notes += 1;
+        self.bytes += estimated_size(event);
+    }
+}
+
+/// Estimates the memory taken by an event, counting the strings of its content and tags.
+pub fn estimated_size(event: &Event) -> usize {
+    let tags: usize = event
+        .tags
+        .iter()
+        .map(|tag| {
+            tag.as_vec()
+                .iter()
+                .map(|value| mem::size_of::<String>() + value.len())
+                .sum::<usize>()
+        })
+        .sum();
+    mem::size_of::<Event>() + event.content.len() + tags
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_rate_counter() {
+        let start = Instant::now();
+        let mut counter = RateCounter::new(start);
+        for i in 1..=4 {
+            counter.tick(start + Duration::from_millis(i * 100));
+        }
+        assert_eq!(counter.rate(), 0.0);
+
+        counter.tick(start + Duration::from_secs(2));
+        assert_eq!(counter.rate(), 2.5);
+    }
+
+    #[rstest]
+    fn test_traffic_meter() {
+        let start = Instant::now();
+        let mut meter = TrafficMeter::new(start);
+        meter.record(String::from("wss://a/"), true);
+        meter.record(String::from("wss://a/"), true);
+        meter.record(String::from("wss://a/"), false);
+        meter.record(String::from("wss://b/"), false);
+        assert_eq!(meter.sample(start + Duration::from_millis(500), 1), None);
+
+        let stats = meter.sample(start + Duration::from_secs(2), 1).unwrap();
+        let a = stats.relays["wss://a/"];
+        assert_eq!(
+            a,
+            RelayTraffic {
+                events: 2,
+                messages: 3
+            }
+        );
+        assert_eq!(stats.per_sec(a.events), 1.0);
+        assert_eq!(stats.per_sec(a.messages), 1.5);
+        assert_eq!(stats.relays["wss://b/"].messages, 1);
+
+        // Silent relays are kept
+        let stats = meter.sample(start + Duration::from_secs(3), 1).unwrap();
+        assert_eq!(stats.relays["wss://a/"], RelayTraffic::default());
+        assert_eq!(stats.period, Duration::from_secs(1));
+    }
+
+    #[rstest]
+    fn test_kind_counter() {
+        let mut counter = KindCounter::default();
+        for _ in 0..60 {
+            counter.record(String::from("wss://a/"), Kind::Custom(30078));
+        }
+        for _ in 0..40 {
+            counter.record(String::from("wss://a/"), Kind::TextNote);
+        }
+        for _ in 0..3 {
+            counter.record(String::from("wss://b/"), Kind::TextNote);
+        }
+        counter.record(String::from("wss://b/"), Kind::Custom(30078));
+        counter.record(String::from("wss://c/"), Kind::from(PUBLIC_CHATS_KIND));
+
+        let breakdown = counter.breakdown();
+        assert_eq!(
+            breakdown[0],
+            RelayKinds {
+                relay: String::from("wss://a/"),
+                kinds: vec![(30078, 60), (1, 40)],
+                total: 100,
+                unused: 60,
+            }
+        );
+        assert_eq!(breakdown[0].is_wasteful(), true);
+        assert_eq!(breakdown[1].relay, "wss://b/");
+        assert_eq!(breakdown[1].unused_share(), 0.25);
+        // Too few events to judge
+        assert_eq!(breakdown[1].is_wasteful(), false);
+        assert_eq!(breakdown[2].unused, 0);
+    }
+
+    #[rstest]
+    fn test_busiest() {
+        let traffic = |events| RelayTraffic {
+            events,
+            messages: events,
+        };
+        let stats = TrafficStats {
+            relays: BTreeMap::from([
+                (String::from("wss://a/"), traffic(1)),
+                (String::from("wss://b/"), traffic(3)),
+                (String::from("wss://c/"), traffic(2)),
+            ]),
+            period: PERIOD,
+            subscriptions: 1,
+            ..TrafficStats::default()
+        };
+        let relays: Vec<&str> = stats
+            .busiest()
+            .into_iter()
+            .map(|(relay, _)| relay.as_str())
+            .collect();
+        assert_eq!(relays, vec!["wss://b/", "wss://c/", "wss://a/"]);
+    }
+
+    #[rstest]
+    fn test_estimated_size() {
+        let keys = Keys::generate();
+        let plain = EventBuilder::text_note("hello", [])
+            .to_event(&keys)
+            .unwrap();
+        let tagged = EventBuilder::text_note("hello", [Tag::Hashtag(String::from("nostr"))])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(estimated_size(&plain), mem::size_of::<Event>() + 5);
+        assert_eq!(
+            estimated_size(&tagged),
+            estimated_size(&plain) + 2 * mem::size_of::<String>() + 6
+        );
+    }
+
+    #[rstest]
+    #[case(0, "0 B")]
+    #[case(1023, "1023 B")]
+    #[case(1536, "1.5 KiB")]
+    #[case(3 * 1024 * 1024, "3.0 MiB")]
+    fn test_format_bytes(#[case] bytes: usize, #[case] expected: &str) {
+        assert_eq!(format_bytes(bytes), expected);
+    }
+}
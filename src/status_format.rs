@@ -0,0 +1,199 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// Format of the status bar shown by default.
+pub const DEFAULT_FORMAT: &str = "{name} {proxy} {dnd}";
+
+/// A value which a status bar format can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// My name, or the shortened public key
+    Name,
+    RelaysConnected,
+    RelaysTotal,
+    /// Notes which arrived above the view of the current tab
+    Unread,
+    /// The local time, e.g. `12:34`
+    Time,
+    /// `[Proxy]` while relays are connected through a proxy
+    Proxy,
+    /// `[DND]` while notifications are silenced
+    Dnd,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Self::Name),
+            "relays_connected" => Some(Self::RelaysConnected),
+            "relays_total" => Some(Self::RelaysTotal),
+            "unread" => Some(Self::Unread),
+            "time" => Some(Self::Time),
+            "proxy" => Some(Self::Proxy),
+            "dnd" => Some(Self::Dnd),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Text(String),
+    Field(Field),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    UnknownField(String),
+    Unclosed,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => write!(f, "unknown field {{{name}}} in the status bar"),
+            Self::Unclosed => write!(f, "unclosed {{ in the status bar, write {{{{ for a brace"),
+        }
+    }
+}
+
+/// Contents of the status bar, e.g. `{name} {relays_connected}/{relays_total} {unread} {time}`.
+/// `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct StatusFormat(Vec<Segment>);
+
+impl Default for StatusFormat {
+    fn default() -> Self {
+        DEFAULT_FORMAT
+            .parse()
+            .expect("the default format should be valid")
+    }
+}
+
+impl std::str::FromStr for StatusFormat {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    text.push(c);
+                }
+                ('{', _) => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(FormatError::Unclosed),
+                        }
+                    }
+                    let Some(field) = Field::parse(&name) else {
+                        return Err(FormatError::UnknownField(name));
+                    };
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Field(field));
+                }
+                (c, _) => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self(segments))
+    }
+}
+
+impl TryFrom<String> for StatusFormat {
+    type Error = FormatError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl StatusFormat {
+    /// Fills the fields with `value`, pairing each piece of text with the field it came from.
+    /// Whitespace after an empty field is dropped, so that `{proxy} {dnd}` doesn't leave a gap.
+    pub fn render(&self, value: impl Fn(Field) -> String) -> Vec<(Option<Field>, String)> {
+        let mut pieces: Vec<(Option<Field>, String)> = vec![];
+        let mut after_empty = false;
+        for segment in &self.0 {
+            match segment {
+                Segment::Text(text) => {
+                    let text = if after_empty { text.trim_start() } else { text };
+                    if !text.is_empty() {
+                        pieces.push((None, text.to_owned()));
+                    }
+                    after_empty = false;
+                }
+                Segment::Field(field) => {
+                    let text = value(*field);
+                    after_empty = text.is_empty();
+                    if !after_empty {
+                        pieces.push((Some(*field), text));
+                    }
+                }
+            }
+        }
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case("{name}", Ok(vec![Segment::Field(Field::Name)]))]
+    #[case(
+        "{name} {relays_connected}/{relays_total}",
+        Ok(vec![
+            Segment::Field(Field::Name),
+            Segment::Text(String::from(" ")),
+            Segment::Field(Field::RelaysConnected),
+            Segment::Text(String::from("/")),
+            Segment::Field(Field::RelaysTotal),
+        ])
+    )]
+    #[case("{{unread}} {unread}", Ok(vec![
+        Segment::Text(String::from("{unread} ")),
+        Segment::Field(Field::Unread),
+    ]))]
+    #[case("", Ok(vec![]))]
+    #[case("{foo}", Err(FormatError::UnknownField(String::from("foo"))))]
+    #[case("{name", Err(FormatError::Unclosed))]
+    fn test_parse(#[case] format: &str, #[case] expected: Result<Vec<Segment>, FormatError>) {
+        assert_eq!(
+            format.parse::<StatusFormat>().map(|format| format.0),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("{name} {proxy} {dnd}", "", "alice")]
+    #[case("{name} {proxy} {dnd}", "[DND]", "alice [DND]")]
+    #[case("{dnd} | {name}", "", "| alice")]
+    fn test_render(#[case] format: &str, #[case] dnd: &str, #[case] expected: &str) {
+        let format: StatusFormat = format.parse().unwrap();
+        let pieces = format.render(|field| match field {
+            Field::Name => String::from("alice"),
+            Field::Dnd => String::from(dnd),
+            _ => String::new(),
+        });
+        let line: String = pieces.into_iter().map(|(_, text)| text).collect();
+        assert_eq!(line.trim_end(), expected);
+    }
+}
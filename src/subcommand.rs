@@ -0,0 +1,146 @@
+use std::{fs, path::PathBuf};
+
+use color_eyre::eyre::{eyre, Result};
+use nostr_sdk::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::{
+    cli::{Cli, Command, KeyCommand, RelaysCommand},
+    config::{Config, RelayConfig, RelayFile},
+    keys::{self, PassphrasePrompt, TerminalPrompt},
+    nostr::{proxy, Connection},
+};
+
+const KEY_FILE: &str = "key.ncryptsec";
+
+/// Runs a command without the TUI. Relays and the key are given by `args` like in the TUI.
+pub async fn run(command: Command, args: &Cli) -> Result<()> {
+    let mut config = Config::new()?;
+    config.override_relays(&args.relays);
+    config.key_stdin = args.key_stdin;
+
+    match command {
+        Command::Post { text } => post(&mut config, text).await,
+        Command::Whoami => whoami(&mut config),
+        Command::Relays { command } => manage_relays(&config, command),
+        Command::Key { command } => manage_key(&mut config, command),
+    }
+}
+
+fn identity(config: &mut Config) -> Result<Keys> {
+    config.unlock(&mut TerminalPrompt)?;
+    config
+        .keys()
+        .ok_or_else(|| eyre!("No privatekey is configured"))
+}
+
+async fn post(config: &mut Config, text: String) -> Result<()> {
+    let keys = identity(config)?;
+    let event = EventBuilder::text_note(text, []).to_event(&keys)?;
+    let id = event.id;
+
+    let proxy = config.proxy_addr()?;
+    let (relays, _) = proxy::reachable_relays(&config.relays, proxy.is_some());
+    let mut conn = Connection::new(Some(keys), relays, proxy).await?;
+    let sent = conn.send(event).await;
+    conn.close().await?;
+    sent?;
+
+    println!("{}", id.to_bech32()?);
+    Ok(())
+}
+
+fn whoami(config: &mut Config) -> Result<()> {
+    let pubkey = identity(config)?.public_key();
+    println!("{}", pubkey.to_bech32()?);
+    Ok(())
+}
+
+/// Lists the relays in use, or edits the ones of the config file regardless of `--relay`.
+fn manage_relays(config: &Config, command: RelaysCommand) -> Result<()> {
+    let file = match command {
+        RelaysCommand::List => {
+            for relay in &config.relays {
+                println!("{relay}");
+            }
+            return Ok(());
+        }
+        RelaysCommand::Add { url } => {
+            Url::parse(&url)?;
+            let mut file = RelayFile::load()?;
+            if file.relays.iter().any(|relay| relay.url == url) {
+                return Err(eyre!("{url} is already added"));
+            }
+            file.relays.push(RelayConfig::new(url));
+            file
+        }
+        RelaysCommand::Remove { url } => {
+            let mut file = RelayFile::load()?;
+            let len = file.relays.len();
+            file.relays.retain(|relay| relay.url != url);
+            if file.relays.len() == len {
+                return Err(eyre!("{url} is not in the relays"));
+            }
+            file
+        }
+    };
+
+    let backup = file.save()?;
+    println!("Saved to {}", file.path.display());
+    println!(
+        "Comments are not kept, so the original file is saved to {}",
+        backup.display()
+    );
+    Ok(())
+}
+
+fn manage_key(config: &mut Config, command: KeyCommand) -> Result<()> {
+    match command {
+        KeyCommand::Encrypt { output } => encrypt_key(config, output),
+    }
+}
+
+/// Writes the private key encrypted with a new passphrase, without overwriting a file.
+fn encrypt_key(config: &mut Config, output: Option<PathBuf>) -> Result<()> {
+    let keys = identity(config)?;
+    let path = output.unwrap_or_else(|| config.config._config_dir.join(KEY_FILE));
+    if path.exists() {
+        return Err(eyre!("{} already exists", path.display()));
+    }
+
+    let mut prompt = TerminalPrompt;
+    let passphrase = Zeroizing::new(prompt.ask("New passphrase: ")?);
+    if passphrase.is_empty() {
+        return Err(eyre!("The passphrase is empty"));
+    }
+    if *Zeroizing::new(prompt.ask("Repeat the passphrase: ")?) != *passphrase {
+        return Err(eyre!("The passphrases do not match"));
+    }
+
+    write_private(&path, &keys::encrypt(&keys, &passphrase)?)?;
+    println!("Saved to {}", path.display());
+    println!(
+        "Set \"privatekey_file\": \"{}\" in the config and remove \"privatekey\"",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Writes a file which only the user can read.
+fn write_private(path: &PathBuf, content: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(content.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    fs::write(path, content)?;
+
+    Ok(())
+}
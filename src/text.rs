@@ -32,6 +32,39 @@ pub fn truncate_text(s: &str, height: usize) -> String {
     }
 }
 
+/// Keeps the first `max_lines` lines and returns the number of the others.
+/// `max_lines` of 0 keeps all of them.
+pub fn preview_text(s: &str, max_lines: usize) -> (String, usize) {
+    let lines: Vec<&str> = s.lines().collect();
+    if max_lines == 0 || lines.len() <= max_lines {
+        return (s.to_string(), 0);
+    }
+
+    (lines[..max_lines].join("\n"), lines.len() - max_lines)
+}
+
+/// Cuts the text to fit in `width` cells, ending with `…` if it is cut.
+pub fn truncate_width(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.to_string().width();
+        if used + w + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += w;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
 pub fn shorten_hex(hex: &str) -> String {
     let pubkey = hex.to_string();
     let len = pubkey.len();
@@ -40,6 +73,50 @@ pub fn shorten_hex(hex: &str) -> String {
     format!("{}:{}", heading, trail)
 }
 
+pub fn compact_number(n: u64) -> String {
+    let units = [(1_000_000_000, "G"), (1_000_000, "M"), (1_000, "k")];
+    for (base, suffix) in units {
+        if n >= base {
+            // Truncate to a single decimal place, e.g. 2_150 => 2.1k
+            let tenths = n / (base / 10);
+            return if tenths.is_multiple_of(10) {
+                format!("{}{suffix}", tenths / 10)
+            } else {
+                format!("{}.{}{suffix}", tenths / 10, tenths % 10)
+            };
+        }
+    }
+
+    n.to_string()
+}
+
+/// Turns the `\r\n` and `\r` of pasted text into `\n`, as terminals send either of them.
+pub fn normalize_newlines(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Size of a draft, shown in the composer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputStats {
+    pub chars: usize,
+    /// Bytes in UTF-8, which relays limit events by
+    pub bytes: usize,
+}
+
+impl InputStats {
+    pub fn new(content: &str) -> Self {
+        Self {
+            chars: content.chars().count(),
+            bytes: content.len(),
+        }
+    }
+
+    /// Returns whether the draft is larger than `limit` bytes, where 0 means no limit.
+    pub fn exceeds(&self, limit: usize) -> bool {
+        limit > 0 && self.bytes > limit
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -123,6 +200,52 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_preview_text() {
+        assert_eq!(
+            preview_text("foo\nbar\nbaz", 2),
+            (String::from("foo\nbar"), 1)
+        );
+        assert_eq!(
+            preview_text("foo\nbar\nbaz", 3),
+            (String::from("foo\nbar\nbaz"), 0)
+        );
+        assert_eq!(
+            preview_text("foo\nbar\nbaz", 0),
+            (String::from("foo\nbar\nbaz"), 0)
+        );
+    }
+
+    #[test]
+    fn test_truncate_width() {
+        assert_eq!(truncate_width("nostr", 5), "nostr");
+        assert_eq!(truncate_width("nostrich", 5), "nost…");
+        assert_eq!(truncate_width("のすとら", 5), "のす…");
+        assert_eq!(truncate_width("のすとら", 4), "の…");
+        assert_eq!(truncate_width("nostr", 1), "…");
+        assert_eq!(truncate_width("nostr", 0), "");
+    }
+
+    #[test]
+    fn test_normalize_newlines() {
+        assert_eq!(normalize_newlines("a\r\nb\rc\nd"), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn test_input_stats() {
+        let stats = InputStats::new("gm\nおはよう");
+        assert_eq!(
+            stats,
+            InputStats {
+                chars: 7,
+                bytes: 15
+            }
+        );
+        assert!(stats.exceeds(14));
+        assert!(!stats.exceeds(15));
+        assert!(!stats.exceeds(0));
+    }
+
     #[test]
     fn test_shortened() {
         assert_eq!(
@@ -130,4 +253,15 @@ mod tests {
             "4d39c:aae25"
         );
     }
+
+    #[test]
+    fn test_compact_number() {
+        assert_eq!(compact_number(0), "0");
+        assert_eq!(compact_number(999), "999");
+        assert_eq!(compact_number(1_000), "1k");
+        assert_eq!(compact_number(2_150), "2.1k");
+        assert_eq!(compact_number(999_999), "999.9k");
+        assert_eq!(compact_number(1_500_000), "1.5M");
+        assert_eq!(compact_number(21_000_000_000), "21G");
+    }
 }
@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+/// Lets through `rate` items per second on average and bursts of up to `burst` items, dropping
+/// the rest, e.g. to sample the firehose of relays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throttle {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated_at: Instant,
+    dropped: u64,
+}
+
+impl Throttle {
+    pub fn new(rate: u32, burst: usize, now: Instant) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            rate: f64::from(rate),
+            burst,
+            tokens: burst,
+            updated_at: now,
+            dropped: 0,
+        }
+    }
+
+    /// Returns whether the item may pass, counting it as dropped otherwise.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+
+    /// Items dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_allow() {
+        let now = Instant::now();
+        let mut throttle = Throttle::new(2, 3, now);
+
+        // A burst passes at once
+        let allowed = (0..5).filter(|_| throttle.allow(now)).count();
+        assert_eq!(allowed, 3);
+        assert_eq!(throttle.dropped(), 2);
+
+        // and then `rate` items per second
+        let later = now + Duration::from_secs(1);
+        let allowed = (0..5).filter(|_| throttle.allow(later)).count();
+        assert_eq!(allowed, 2);
+
+        // Tokens don't pile up beyond the burst
+        let much_later = later + Duration::from_secs(60);
+        let allowed = (0..5).filter(|_| throttle.allow(much_later)).count();
+        assert_eq!(allowed, 3);
+        assert_eq!(throttle.dropped(), 7);
+    }
+
+    #[rstest]
+    fn test_zero_rate() {
+        let now = Instant::now();
+        let mut throttle = Throttle::new(0, 0, now);
+        assert!(throttle.allow(now));
+        assert!(!throttle.allow(now + Duration::from_secs(10)));
+    }
+}
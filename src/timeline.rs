@@ -0,0 +1,947 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Instant;
+
+use color_eyre::eyre::{eyre, Report, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use sorted_vec::{FindOrInsert, ReverseSortedSet};
+
+use crate::{
+    i18n,
+    nostr::{nip21::NostrTarget, nip28, nip94, SortableEvent},
+    pagination::{Coverage, Gap, Paginator},
+    text::shorten_hex,
+    viewport::Viewport,
+    widgets::ScrollableList,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimelineTabType {
+    Home,
+    UserTimeline {
+        pubkey: PublicKey,
+    },
+    Thread {
+        id: EventId,
+    },
+    Hashtag {
+        tag: String,
+    },
+    /// Notes of everyone on the relays, sampled to protect the UI
+    Global,
+    /// Messages of a public chat channel (NIP-28), identified by its creation event
+    Channel {
+        id: EventId,
+    },
+}
+
+impl TimelineTabType {
+    /// Returns the tab title. `name` is the display name of the user or the name of the channel,
+    /// if known.
+    pub fn title(&self, name: Option<String>) -> String {
+        match self {
+            Self::Home => i18n::tr("Timeline"),
+            Self::UserTimeline { pubkey } => {
+                name.unwrap_or_else(|| shorten_hex(&pubkey.to_string()))
+            }
+            Self::Thread { id } => {
+                i18n::tr_args("Thread {id}", &[("id", &shorten_hex(&id.to_hex()))])
+            }
+            Self::Hashtag { tag } => format!("#{tag}"),
+            Self::Global => i18n::tr("Global"),
+            Self::Channel { id } => name.unwrap_or_else(|| {
+                i18n::tr_args("Channel {id}", &[("id", &shorten_hex(&id.to_hex()))])
+            }),
+        }
+    }
+
+    /// Returns the icon shown before the title in the tab bar. Hashtags have none, as their
+    /// titles start with `#`.
+    pub fn icon(&self) -> Option<&'static str> {
+        match self {
+            Self::Home => Some("⌂"),
+            Self::UserTimeline { .. } => Some("@"),
+            Self::Thread { .. } => Some("↳"),
+            Self::Hashtag { .. } => None,
+            Self::Global => Some("◎"),
+            Self::Channel { .. } => Some("≡"),
+        }
+    }
+
+    pub fn is_closable(&self) -> bool {
+        !matches!(self, Self::Home)
+    }
+
+    /// Returns whether the subscription of the tab would deliver the note, e.g. to show my note
+    /// in the tab before relays echo it.
+    pub fn includes(&self, event: &Event) -> bool {
+        let is_note = event.kind == Kind::TextNote;
+        // Files are shared by follows and users, rather than in threads and hashtags
+        let is_post = is_note || nip94::is_file(event.kind);
+        match self {
+            Self::Home => is_post,
+            Self::Global => is_note,
+            Self::UserTimeline { pubkey } => is_post && event.pubkey == *pubkey,
+            Self::Thread { id } => {
+                is_note && (event.id == *id || event.event_ids().any(|e| e == id))
+            }
+            Self::Hashtag { tag } => {
+                is_note
+                    && event.tags.iter().any(
+                        |t| matches!(t, Tag::Hashtag(hashtag) if hashtag.eq_ignore_ascii_case(tag)),
+                    )
+            }
+            Self::Channel { id } => {
+                event.kind == Kind::ChannelMessage && nip28::channel_id(event) == Some(*id)
+            }
+        }
+    }
+}
+
+impl From<NostrTarget> for TimelineTabType {
+    fn from(target: NostrTarget) -> Self {
+        match target {
+            NostrTarget::Profile(pubkey) => Self::UserTimeline { pubkey },
+            NostrTarget::Event(id) => Self::Thread { id },
+        }
+    }
+}
+
+/// Parses tab specs given on the command line: `home`, `global`, `user:<npub or hex>`,
+/// `thread:<note, nevent or hex>`, `channel:<note, nevent or hex>` and `hashtag:<tag>`. Entities can
+/// also be given as `nostr:` URIs.
+impl FromStr for TimelineTabType {
+    type Err = Report;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        match spec {
+            "home" => return Ok(Self::Home),
+            "global" => return Ok(Self::Global),
+            _ => {}
+        }
+
+        let (kind, value) = spec
+            .split_once(':')
+            .ok_or_else(|| eyre!("Invalid tab: {spec}"))?;
+        match kind {
+            "user" => match NostrTarget::parse(value) {
+                Ok(NostrTarget::Profile(pubkey)) => Ok(Self::UserTimeline { pubkey }),
+                _ => Ok(Self::UserTimeline {
+                    pubkey: PublicKey::from_hex(value)
+                        .map_err(|_| eyre!("Invalid user: {value}"))?,
+                }),
+            },
+            "thread" => match NostrTarget::parse(value) {
+                Ok(NostrTarget::Event(id)) => Ok(Self::Thread { id }),
+                _ => Ok(Self::Thread {
+                    id: EventId::from_hex(value).map_err(|_| eyre!("Invalid note: {value}"))?,
+                }),
+            },
+            "channel" => match NostrTarget::parse(value) {
+                Ok(NostrTarget::Event(id)) => Ok(Self::Channel { id }),
+                _ => Ok(Self::Channel {
+                    id: EventId::from_hex(value).map_err(|_| eyre!("Invalid channel: {value}"))?,
+                }),
+            },
+            "hashtag" => {
+                // Hashtags are matched by lowercase `t` tags (NIP-24)
+                let tag = value.trim_start_matches('#').to_lowercase();
+                if tag.is_empty() || tag.contains(char::is_whitespace) {
+                    return Err(eyre!("Invalid hashtag: {value}"));
+                }
+                Ok(Self::Hashtag { tag })
+            }
+            _ => Err(eyre!("Unknown tab type: {kind}")),
+        }
+    }
+}
+
+/// Notes and the selection of a tab.
+pub struct TimelineTab {
+    pub tab_type: TimelineTabType,
+    pub notes: ReverseSortedSet<SortableEvent>,
+    pub list_state: tui_widget_list::ListState,
+    pub viewport: Viewport,
+    pub paginator: Paginator,
+    /// Times requested apart from the notes loaded from the newest one, which pages skip
+    pub coverage: Coverage,
+    /// Note to select once it arrives, e.g. after restoring a session
+    pub pending_selection: Option<EventId>,
+    /// Notes shown above the pending selection, to restore where it was in the view
+    pub pending_offset: usize,
+    /// Date jumped to, whose closest note is selected again as notes around it arrive
+    jump: Option<Jump>,
+    /// Times in which notes may be missing, the newest first
+    pub gaps: Vec<Gap>,
+    /// Whether new notes are selected while the newest one is, and kept above the view otherwise
+    pub follow: bool,
+    /// Time each note is sorted by, so that a note delivered again or reposted takes a single row
+    sorted_at: HashMap<EventId, Timestamp>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Jump {
+    target: Timestamp,
+    /// Note selected by the jump, which stops following the date once the selection moves
+    selected: Option<EventId>,
+}
+
+impl TimelineTab {
+    pub fn new(tab_type: TimelineTabType) -> Self {
+        Self {
+            tab_type,
+            notes: ReverseSortedSet::new(),
+            list_state: tui_widget_list::ListState::default(),
+            viewport: Viewport::default(),
+            paginator: Paginator::default(),
+            coverage: Coverage::default(),
+            pending_selection: None,
+            pending_offset: 0,
+            jump: None,
+            gaps: vec![],
+            follow: false,
+            sorted_at: HashMap::new(),
+        }
+    }
+
+    pub fn add_note(&mut self, event: Event) {
+        if !self.sorted_at.contains_key(&event.id) {
+            self.insert(SortableEvent::new(event));
+        }
+    }
+
+    /// Adds a note at the time it was received instead of its date, e.g. a date in the future.
+    pub fn add_received_note(&mut self, event: Event, received_at: Timestamp) {
+        if !self.sorted_at.contains_key(&event.id) {
+            self.insert(SortableEvent::received(event, received_at));
+        }
+    }
+
+    /// Adds a reposted note at the time of the repost. A note which is already shown is moved up
+    /// to its latest repost rather than shown again.
+    pub fn add_repost(&mut self, event: Event, reposted_at: Timestamp) {
+        let id = event.id;
+        let note = SortableEvent::reposted(event, reposted_at);
+        match self.sorted_at.get(&id) {
+            Some(sorted_at) if *sorted_at >= note.sorted_at => {}
+            Some(_) => {
+                let selected = self.selected_note().is_some_and(|event| event.id == id);
+                self.remove_note(&id);
+                let index = self.insert(note);
+                if selected {
+                    self.list_state.select(index);
+                }
+            }
+            None => {
+                self.insert(note);
+            }
+        }
+    }
+
+    /// Inserts a note, returning its index.
+    fn insert(&mut self, note: SortableEvent) -> Option<usize> {
+        let id = note.event.id;
+        let sorted_at = note.sorted_at;
+        let at_newest = self.is_at_newest();
+        let FindOrInsert::Inserted(index) = self.notes.find_or_insert(Reverse(note)) else {
+            return None;
+        };
+        self.sorted_at.insert(id, sorted_at);
+        self.coverage.receive(sorted_at);
+        if self.follow && !at_newest {
+            // Auto-scroll pauses while reading older notes
+            self.viewport.pin(index);
+        } else {
+            self.viewport.insert(index);
+        }
+
+        if self.pending_selection == Some(id) {
+            self.pending_selection = None;
+            self.list_state.select(Some(index));
+            self.viewport
+                .set_offset(index.saturating_sub(self.pending_offset));
+            return Some(index);
+        }
+
+        // Keep selected position, unless following the newest note
+        match self.list_state.selected() {
+            Some(0) if self.follow && at_newest && index == 0 => {}
+            Some(i) if i >= index => self.list_state.select(Some(i + 1)),
+            _ => {}
+        }
+
+        if let Some(jump) = self.jump {
+            if self.selected_note().map(|event| event.id) == jump.selected {
+                self.jump_to(jump.target);
+            } else {
+                self.jump = None;
+            }
+        }
+        Some(index)
+    }
+
+    /// Returns whether the newest note is in view and selected, if any note is.
+    pub fn is_at_newest(&self) -> bool {
+        self.viewport.offset() == 0 && self.list_state.selected().is_none_or(|i| i == 0)
+    }
+
+    /// Removes the oldest notes beyond `len`, e.g. to cap a busy tab, returning their ids.
+    pub fn truncate(&mut self, len: usize) -> Vec<EventId> {
+        let mut removed = vec![];
+        while self.notes.len() > len {
+            let Some(id) = self.notes.last().map(|note| note.0.event.id) else {
+                break;
+            };
+            self.remove_note(&id);
+            removed.push(id);
+        }
+        removed
+    }
+
+    pub fn contains(&self, id: &EventId) -> bool {
+        self.sorted_at.contains_key(id)
+    }
+
+    /// Removes a note, e.g. a deleted one, keeping the selection on the same note or the next one.
+    pub fn remove_note(&mut self, id: &EventId) -> bool {
+        if self.sorted_at.remove(id).is_none() {
+            return false;
+        }
+        let Some(index) = self.notes.iter().position(|note| note.0.event.id == *id) else {
+            return false;
+        };
+        self.notes.remove_index(index);
+
+        let selected = match self.list_state.selected() {
+            Some(i) if i > index => Some(i - 1),
+            Some(i) => Some(i)
+                .filter(|i| *i < self.notes.len())
+                .or(i.checked_sub(1)),
+            None => None,
+        };
+        self.list_state.select(selected);
+        true
+    }
+
+    /// Selects the note closest to the timestamp.
+    pub fn jump_to(&mut self, target: Timestamp) {
+        self.list_state.select(self.index_at(target));
+        self.jump = Some(Jump {
+            target,
+            selected: self.selected_note().map(|event| event.id),
+        });
+    }
+
+    /// Records that notes since the newest one until `until` may be missing.
+    pub fn open_gap(&mut self, until: Timestamp) {
+        let Some(since) = self.sorted_at(0) else {
+            return;
+        };
+        if since < until {
+            self.gaps.insert(0, Gap { since, until });
+        }
+    }
+
+    /// Records the times covered by requested windows whose notes had time to arrive, and narrows
+    /// the gaps to the times still missing.
+    pub fn settle_requests(&mut self, now: Instant) {
+        if !self.coverage.settle(now) {
+            return;
+        }
+
+        let coverage = &self.coverage;
+        self.gaps
+            .retain_mut(|gap| match coverage.missing(Some(gap.since), gap.until) {
+                Some((_, until)) => {
+                    gap.until = until;
+                    true
+                }
+                None => false,
+            });
+    }
+
+    /// Returns whether the note at the index is the first one below a gap.
+    pub fn has_gap_above(&self, index: usize) -> bool {
+        let Some(sorted_at) = self.sorted_at(index) else {
+            return false;
+        };
+        let newer = index.checked_sub(1).and_then(|i| self.sorted_at(i));
+        self.gaps
+            .iter()
+            .any(|gap| sorted_at <= gap.since && newer.is_none_or(|newer| newer > gap.since))
+    }
+
+    /// Returns the index of the newest note from the time or before, if newer notes are above it.
+    pub fn index_below(&self, at: Timestamp) -> Option<usize> {
+        let i = self.notes.partition_point(|note| note.0.sorted_at > at);
+        (i > 0 && i < self.len()).then_some(i)
+    }
+
+    /// Returns the index of the note closest to the timestamp.
+    pub fn index_at(&self, target: Timestamp) -> Option<usize> {
+        // Notes are sorted from the newest
+        let older = self.notes.partition_point(|note| note.0.sorted_at > target);
+        let distance = |i: usize| {
+            self.sorted_at(i)
+                .map(|sorted_at| sorted_at.as_u64().abs_diff(target.as_u64()))
+        };
+        match (older.checked_sub(1), distance(older)) {
+            (Some(newer), Some(d)) if distance(newer) < Some(d) => Some(newer),
+            (_, Some(_)) => Some(older),
+            (newer, None) => newer,
+        }
+    }
+
+    pub fn get_note(&self, i: usize) -> Option<&Event> {
+        self.notes.get(i).map(|note| &note.0.event)
+    }
+
+    /// Returns the time the note at the index is sorted by.
+    pub fn sorted_at(&self, i: usize) -> Option<Timestamp> {
+        self.notes.get(i).map(|note| note.0.sorted_at)
+    }
+
+    pub fn selected_note(&self) -> Option<&Event> {
+        self.list_state.selected().and_then(|i| self.get_note(i))
+    }
+
+    /// Returns the time of the oldest note, which older notes are loaded until.
+    pub fn oldest_sorted_at(&self) -> Option<Timestamp> {
+        self.notes.last().map(|note| note.0.sorted_at)
+    }
+
+    /// Checks that the selection is within the notes and each note takes a single row,
+    /// describing the first broken invariant.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if let Some(i) = self.list_state.selected() {
+            if i >= self.notes.len() {
+                return Err(format!(
+                    "{:?}: note {i} is selected out of {}",
+                    self.tab_type,
+                    self.notes.len()
+                ));
+            }
+        }
+        if self.sorted_at.len() != self.notes.len() {
+            return Err(format!(
+                "{:?}: {} notes are sorted but {} are shown",
+                self.tab_type,
+                self.sorted_at.len(),
+                self.notes.len()
+            ));
+        }
+        if let Some(note) = self
+            .notes
+            .iter()
+            .find(|note| self.sorted_at.get(&note.0.event.id) != Some(&note.0.sorted_at))
+        {
+            return Err(format!(
+                "{:?}: note {} is shown at another time than it is sorted by",
+                self.tab_type, note.0.event.id
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds the notes by their dates, e.g. a batch loaded at once.
+impl Extend<Event> for TimelineTab {
+    fn extend<I: IntoIterator<Item = Event>>(&mut self, events: I) {
+        for event in events {
+            self.add_note(event);
+        }
+    }
+}
+
+impl ScrollableList<Event> for TimelineTab {
+    fn select(&mut self, index: Option<usize>) {
+        self.list_state.select(index);
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list_state.selected()
+    }
+
+    fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn note(keys: &Keys, created_at: u64) -> Event {
+        EventBuilder::text_note("gm", [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_title() {
+        let pubkey =
+            PublicKey::from_hex("4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25")
+                .unwrap();
+        assert_eq!(TimelineTabType::Home.title(None), "Timeline");
+        assert_eq!(
+            TimelineTabType::UserTimeline { pubkey }.title(None),
+            "4d39c:aae25"
+        );
+        assert_eq!(
+            TimelineTabType::UserTimeline { pubkey }.title(Some(String::from("foo"))),
+            "foo"
+        );
+        let id =
+            EventId::from_hex("fcd6707cf1943d6f3ffa3c382bddb966027f98ddca15511a897a51ccfe160cd6")
+                .unwrap();
+        assert_eq!(
+            TimelineTabType::Thread { id }.title(None),
+            "Thread fcd67:60cd6"
+        );
+        assert_eq!(
+            TimelineTabType::Hashtag {
+                tag: String::from("nostr")
+            }
+            .title(None),
+            "#nostr"
+        );
+        assert_eq!(TimelineTabType::Global.title(None), "Global");
+        assert_eq!(
+            TimelineTabType::Channel { id }.title(None),
+            "Channel fcd67:60cd6"
+        );
+        assert_eq!(
+            TimelineTabType::Channel { id }.title(Some(String::from("nostr"))),
+            "nostr"
+        );
+    }
+
+    #[rstest]
+    #[case("home", Some(TimelineTabType::Home))]
+    #[case("global", Some(TimelineTabType::Global))]
+    #[case(
+        "channel:fcd6707cf1943d6f3ffa3c382bddb966027f98ddca15511a897a51ccfe160cd6",
+        Some(TimelineTabType::Channel {
+            id: EventId::from_hex("fcd6707cf1943d6f3ffa3c382bddb966027f98ddca15511a897a51ccfe160cd6").unwrap()
+        })
+    )]
+    #[case("channel:npub1", None)]
+    #[case(
+        "user:npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmug",
+        Some(TimelineTabType::UserTimeline {
+            pubkey: PublicKey::from_hex("4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25").unwrap()
+        })
+    )]
+    #[case(
+        "user:4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25",
+        Some(TimelineTabType::UserTimeline {
+            pubkey: PublicKey::from_hex("4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25").unwrap()
+        })
+    )]
+    #[case(
+        "thread:nostr:note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv",
+        Some(TimelineTabType::Thread {
+            id: EventId::from_bech32("note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv").unwrap()
+        })
+    )]
+    #[case("hashtag:#Nostr", Some(TimelineTabType::Hashtag { tag: String::from("nostr") }))]
+    #[case("hashtag:", None)]
+    #[case(
+        "user:note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv",
+        None
+    )]
+    #[case("list:friends", None)]
+    #[case("nostr", None)]
+    fn test_from_str(#[case] spec: &str, #[case] expected: Option<TimelineTabType>) {
+        assert_eq!(spec.parse::<TimelineTabType>().ok(), expected);
+    }
+
+    #[rstest]
+    fn test_includes() {
+        let (keys, other) = (Keys::generate(), Keys::generate());
+        let root = note(&other, 1);
+        let reply = EventBuilder::text_note(
+            "gm #Nostr",
+            [Tag::event(root.id), Tag::Hashtag(String::from("Nostr"))],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let message = EventBuilder::new(
+            Kind::ChannelMessage,
+            "gm",
+            nip28::message_tags(root.id, None),
+        )
+        .to_event(&keys)
+        .unwrap();
+        let hashtag = |tag: &str| TimelineTabType::Hashtag {
+            tag: String::from(tag),
+        };
+
+        assert!(TimelineTabType::Home.includes(&reply));
+        assert!(!TimelineTabType::Home.includes(&message));
+        assert!(TimelineTabType::UserTimeline {
+            pubkey: keys.public_key()
+        }
+        .includes(&reply));
+        assert!(!TimelineTabType::UserTimeline {
+            pubkey: other.public_key()
+        }
+        .includes(&reply));
+        assert!(TimelineTabType::Thread { id: root.id }.includes(&reply));
+        assert!(!TimelineTabType::Thread { id: reply.id }.includes(&root));
+        assert!(hashtag("nostr").includes(&reply));
+        assert!(!hashtag("bitcoin").includes(&reply));
+        assert!(TimelineTabType::Channel { id: root.id }.includes(&message));
+        assert!(!TimelineTabType::Channel { id: root.id }.includes(&reply));
+
+        let file = EventBuilder::new(Kind::FileMetadata, "", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(TimelineTabType::Home.includes(&file));
+        assert!(!TimelineTabType::Global.includes(&file));
+        assert!(!TimelineTabType::Thread { id: root.id }.includes(&file));
+    }
+
+    #[rstest]
+    fn test_from_target() {
+        let uri = "nostr:npub1f5uuywemqwlejj2d7he6zjw8jz9wr0r5z6q8lhttxj333ph24cjsymjmug";
+        let pubkey =
+            PublicKey::from_hex("4d39c23b3b03bf99494df5f3a149c7908ae1bc7416807fdd6b34a31886eaae25")
+                .unwrap();
+        assert_eq!(
+            TimelineTabType::from(NostrTarget::parse(uri).unwrap()),
+            TimelineTabType::UserTimeline { pubkey }
+        );
+
+        let uri = "nostr:note1jnnkqfzn70k6z94nwljdnaw5s5pd8jlf0eyjfmc2pvsytvsa7unsex9dyv";
+        let id = EventId::from_bech32(uri.trim_start_matches("nostr:")).unwrap();
+        assert_eq!(
+            TimelineTabType::from(NostrTarget::parse(uri).unwrap()),
+            TimelineTabType::Thread { id }
+        );
+    }
+
+    #[rstest]
+    fn test_add_note_keeps_selection() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        let first = note(&keys, 1);
+        tab.add_note(first.clone());
+        tab.select(Some(0));
+
+        tab.add_note(note(&keys, 2));
+        assert_eq!(tab.selected_note(), Some(&first));
+
+        // Duplicates don't move the selection
+        tab.add_note(first.clone());
+        assert_eq!(tab.len(), 2);
+        assert_eq!(tab.selected_note(), Some(&first));
+
+        // Neither do older notes
+        tab.add_note(note(&keys, 0));
+        assert_eq!(tab.selected_note(), Some(&first));
+    }
+
+    #[rstest]
+    #[case(0, Some(3))]
+    #[case(12, Some(2))]
+    #[case(14, Some(1))]
+    #[case(20, Some(1))]
+    #[case(100, Some(0))]
+    fn test_index_at(#[case] target: u64, #[case] expected: Option<usize>) {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        assert_eq!(tab.index_at(Timestamp::from(target)), None);
+
+        for created_at in [1, 10, 15, 30] {
+            tab.add_note(note(&keys, created_at));
+        }
+        assert_eq!(tab.index_at(Timestamp::from(target)), expected);
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(1, Some(3))]
+    #[case(12, Some(2))]
+    #[case(15, Some(1))]
+    #[case(30, None)]
+    fn test_index_below(#[case] at: u64, #[case] expected: Option<usize>) {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        for created_at in [1, 10, 15, 30] {
+            tab.add_note(note(&keys, created_at));
+        }
+        assert_eq!(tab.index_below(Timestamp::from(at)), expected);
+    }
+
+    #[rstest]
+    fn test_jump_to() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        tab.add_note(note(&keys, 100));
+        tab.add_note(note(&keys, 1));
+        tab.jump_to(Timestamp::from(50));
+        assert_eq!(tab.selected(), Some(1));
+
+        // Notes around the date arrive
+        let closest = note(&keys, 49);
+        tab.add_note(closest.clone());
+        tab.add_note(note(&keys, 40));
+        assert_eq!(tab.selected_note(), Some(&closest));
+
+        // The selection is kept once it is moved
+        tab.select(Some(0));
+        tab.add_note(note(&keys, 50));
+        assert_eq!(tab.selected(), Some(0));
+    }
+
+    #[rstest]
+    fn test_gaps() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        tab.open_gap(Timestamp::from(100));
+        assert_eq!(tab.gaps, vec![]);
+
+        tab.add_note(note(&keys, 10));
+        tab.add_note(note(&keys, 20));
+        tab.open_gap(Timestamp::from(100));
+        assert_eq!(
+            tab.gaps,
+            vec![Gap {
+                since: Timestamp::from(20),
+                until: Timestamp::from(100)
+            }]
+        );
+        assert!(tab.has_gap_above(0));
+        assert!(!tab.has_gap_above(1));
+
+        // Notes after the gap are shown above it
+        tab.add_note(note(&keys, 110));
+        assert!(!tab.has_gap_above(0));
+        assert!(tab.has_gap_above(1));
+        assert!(!tab.has_gap_above(2));
+    }
+
+    #[rstest]
+    fn test_settle_requests() {
+        let keys = Keys::generate();
+        let start = Instant::now();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        tab.add_note(note(&keys, 20));
+        tab.open_gap(Timestamp::from(100));
+        tab.coverage
+            .request(Timestamp::from(20), Timestamp::from(100), 2, start);
+        for created_at in [90, 80, 70] {
+            tab.add_note(note(&keys, created_at));
+        }
+
+        // The relays had more notes than the limit, so the older part is still missing
+        tab.settle_requests(start + Duration::from_secs(60));
+        assert_eq!(
+            tab.gaps,
+            vec![Gap {
+                since: Timestamp::from(20),
+                until: Timestamp::from(80)
+            }]
+        );
+
+        tab.coverage
+            .request(Timestamp::from(20), Timestamp::from(80), 2, start);
+        tab.settle_requests(start + Duration::from_secs(60));
+        assert_eq!(tab.gaps, vec![]);
+    }
+
+    #[rstest]
+    fn test_extend() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Global);
+        let notes: Vec<Event> = (0..3).map(|i| note(&keys, i)).collect();
+        tab.extend(notes.iter().cloned().chain([notes[1].clone()]));
+        assert_eq!(tab.len(), 3);
+        assert_eq!(tab.get_note(0), Some(&notes[2]));
+        assert_eq!(tab.get_note(2), Some(&notes[0]));
+    }
+
+    #[rstest]
+    fn test_check_invariants() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Global);
+        tab.extend((0..2).map(|i| note(&keys, i)));
+        tab.select(Some(1));
+        assert_eq!(tab.check_invariants(), Ok(()));
+
+        tab.list_state.select(Some(2));
+        assert_eq!(
+            tab.check_invariants(),
+            Err(String::from("Global: note 2 is selected out of 2"))
+        );
+    }
+
+    #[rstest]
+    fn test_truncate() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Global);
+        let notes: Vec<Event> = (0..5).map(|i| note(&keys, i)).collect();
+        for note in &notes {
+            tab.add_note(note.clone());
+        }
+        tab.select(Some(4));
+
+        // The oldest notes are removed, keeping the selection within the tab
+        let removed = tab.truncate(3);
+        assert_eq!(removed, vec![notes[0].id, notes[1].id]);
+        assert!(!tab.contains(&notes[0].id));
+        assert!(tab.contains(&notes[2].id));
+        assert_eq!(tab.len(), 3);
+        assert_eq!(tab.get_note(2), Some(&notes[2]));
+        assert_eq!(tab.selected(), Some(2));
+        assert_eq!(tab.oldest_sorted_at(), Some(Timestamp::from(2)));
+    }
+
+    #[rstest]
+    fn test_remove_note() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        let notes: Vec<Event> = (0..3).map(|i| note(&keys, i)).collect();
+        for note in &notes {
+            tab.add_note(note.clone());
+        }
+
+        tab.select(Some(2));
+        assert!(tab.remove_note(&notes[2].id));
+        assert_eq!(tab.selected_note(), Some(&notes[0]));
+        assert!(!tab.remove_note(&notes[2].id));
+
+        assert!(tab.remove_note(&notes[0].id));
+        assert_eq!(tab.selected_note(), Some(&notes[1]));
+        assert!(tab.remove_note(&notes[1].id));
+        assert_eq!(tab.selected(), None);
+    }
+
+    #[rstest]
+    fn test_add_repost() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        let original = note(&keys, 1);
+        let newer = note(&keys, 5);
+        tab.add_note(original.clone());
+        tab.add_note(newer.clone());
+        tab.select(Some(1));
+
+        // The note is moved up to the repost, keeping the selection on it
+        tab.add_repost(original.clone(), Timestamp::from(10));
+        assert_eq!(tab.len(), 2);
+        assert_eq!(tab.get_note(0), Some(&original));
+        assert_eq!(tab.sorted_at(0), Some(Timestamp::from(10)));
+        assert_eq!(tab.selected_note(), Some(&original));
+
+        // Neither older reposts nor the note itself add a row
+        tab.add_repost(original.clone(), Timestamp::from(8));
+        tab.add_note(original.clone());
+        assert_eq!(tab.len(), 2);
+        assert_eq!(tab.sorted_at(0), Some(Timestamp::from(10)));
+
+        // A note reposted before it arrives is shown once
+        let reposted = note(&keys, 2);
+        tab.add_repost(reposted.clone(), Timestamp::from(3));
+        tab.add_note(reposted.clone());
+        assert_eq!(tab.len(), 3);
+        assert_eq!(tab.get_note(2), Some(&reposted));
+        assert_eq!(tab.oldest_sorted_at(), Some(Timestamp::from(3)));
+
+        assert!(tab.remove_note(&original.id));
+        tab.add_note(original.clone());
+        assert_eq!(tab.get_note(2), Some(&original));
+    }
+
+    #[rstest]
+    fn test_add_received_note() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        tab.add_note(note(&keys, 5));
+        let future = note(&keys, 1000);
+
+        // A note dated in the future doesn't stay at the top
+        tab.add_received_note(future.clone(), Timestamp::from(3));
+        tab.add_note(note(&keys, 10));
+        assert_eq!(tab.get_note(2), Some(&future));
+        assert_eq!(tab.oldest_sorted_at(), Some(Timestamp::from(3)));
+
+        tab.add_note(future.clone());
+        assert_eq!(tab.len(), 3);
+    }
+
+    #[rstest]
+    fn test_follow() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        tab.follow = true;
+        tab.add_note(note(&keys, 1));
+        tab.select(Some(0));
+
+        // The selection follows new notes from the newest one
+        let newest = note(&keys, 2);
+        tab.add_note(newest.clone());
+        assert_eq!(tab.selected_note(), Some(&newest));
+        assert_eq!(tab.viewport.unread(), 0);
+
+        // It pauses while an older note is selected, keeping new notes above the view
+        tab.select(Some(1));
+        tab.add_note(note(&keys, 3));
+        tab.add_note(note(&keys, 4));
+        assert_eq!(tab.selected(), Some(3));
+        assert_eq!((tab.viewport.offset(), tab.viewport.unread()), (2, 2));
+        assert!(!tab.is_at_newest());
+    }
+
+    #[rstest]
+    fn test_add_note_pending_selection() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        let target = note(&keys, 1);
+        tab.pending_selection = Some(target.id);
+
+        tab.add_note(note(&keys, 2));
+        assert_eq!(tab.selected(), None);
+
+        tab.add_note(target.clone());
+        assert_eq!(tab.selected_note(), Some(&target));
+        assert_eq!(tab.pending_selection, None);
+    }
+
+    #[rstest]
+    fn test_add_note_pending_offset() {
+        let keys = Keys::generate();
+        let mut tab = TimelineTab::new(TimelineTabType::Home);
+        for created_at in 3..=6 {
+            tab.add_note(note(&keys, created_at));
+        }
+        let target = note(&keys, 1);
+        tab.pending_selection = Some(target.id);
+        tab.pending_offset = 2;
+
+        // The selection is shown at the same row as before
+        tab.add_note(target.clone());
+        assert_eq!(tab.selected(), Some(4));
+        assert_eq!(tab.viewport.offset(), 2);
+
+        // and stays there as notes arrive above the view
+        tab.add_note(note(&keys, 2));
+        tab.add_note(note(&keys, 7));
+        assert_eq!(tab.selected(), Some(6));
+        assert_eq!(tab.viewport.offset(), 3);
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use nostr_sdk::Timestamp;
+use serde::Deserialize;
+
+use crate::i18n;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// How timestamps of notes are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampMode {
+    /// Local time, e.g. `15:42:47`
+    #[default]
+    Absolute,
+    /// Time elapsed, e.g. `5m ago`
+    Relative,
+}
+
+impl TimestampMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Absolute => Self::Relative,
+            Self::Relative => Self::Absolute,
+        }
+    }
+}
+
+pub fn format(timestamp: Timestamp, mode: TimestampMode, now: Timestamp) -> String {
+    format_in(timestamp, mode, now, &Local)
+}
+
+fn format_in<Tz>(timestamp: Timestamp, mode: TimestampMode, now: Timestamp, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    match mode {
+        TimestampMode::Absolute => absolute_in(timestamp, tz),
+        TimestampMode::Relative => relative_in(timestamp, now, tz),
+    }
+}
+
+/// Formats a timestamp as the local time of day.
+pub fn absolute(timestamp: Timestamp) -> String {
+    absolute_in(timestamp, &Local)
+}
+
+fn absolute_in<Tz>(timestamp: Timestamp, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    DateTime::from_timestamp(timestamp.as_i64(), 0)
+        .expect("Invalid timestamp")
+        .with_timezone(tz)
+        .format("%T")
+        .to_string()
+}
+
+/// Formats the time elapsed since a timestamp, falling back to the local date after a week.
+pub fn relative(timestamp: Timestamp, now: Timestamp) -> String {
+    relative_in(timestamp, now, &Local)
+}
+
+fn relative_in<Tz>(timestamp: Timestamp, now: Timestamp, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    // Clocks of other clients may be a little ahead
+    let elapsed = now.as_u64().saturating_sub(timestamp.as_u64());
+    match elapsed {
+        0..MINUTE => i18n::tr("just now"),
+        MINUTE..HOUR => i18n::tr_args("{minutes}m ago", &[("minutes", &(elapsed / MINUTE))]),
+        HOUR..DAY => i18n::tr_args("{hours}h ago", &[("hours", &(elapsed / HOUR))]),
+        DAY..WEEK => i18n::tr_args("{days}d ago", &[("days", &(elapsed / DAY))]),
+        _ => DateTime::from_timestamp(timestamp.as_i64(), 0)
+            .expect("Invalid timestamp")
+            .with_timezone(tz)
+            .format("%Y-%m-%d")
+            .to_string(),
+    }
+}
+
+/// Parses a local date, e.g. `2024-01-01` or `2024-01-01 15:42`, or the time elapsed,
+/// e.g. `3d` (days), `12h` (hours), `30m` (minutes) or `2w` (weeks).
+pub fn parse(input: &str, now: Timestamp) -> Option<Timestamp> {
+    parse_in(input, now, &Local)
+}
+
+fn parse_in<Tz: TimeZone>(input: &str, now: Timestamp, tz: &Tz) -> Option<Timestamp> {
+    let input = input.trim();
+    if let Some(duration) = parse_duration(input) {
+        return Some(Timestamp::from(now.as_u64().saturating_sub(duration)));
+    }
+
+    let datetime = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight should be valid"))
+        })
+        .ok()?;
+    let local = tz.from_local_datetime(&datetime).earliest()?;
+    u64::try_from(local.timestamp()).ok().map(Timestamp::from)
+}
+
+/// Parses a duration in seconds, e.g. `3d` (days), `12h` (hours), `30m` (minutes) or `2w` (weeks).
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let unit = match input.chars().last()? {
+        'm' => MINUTE,
+        'h' => HOUR,
+        'd' => DAY,
+        'w' => WEEK,
+        _ => return None,
+    };
+    input[..input.len() - 1]
+        .parse::<u64>()
+        .ok()
+        .and_then(|count| count.checked_mul(unit))
+}
+
+/// Formats a duration in seconds in its largest unit, e.g. `3h`.
+pub fn duration(seconds: u64) -> String {
+    match seconds {
+        0..MINUTE => format!("{seconds}s"),
+        MINUTE..HOUR => format!("{}m", seconds / MINUTE),
+        HOUR..DAY => format!("{}h", seconds / HOUR),
+        _ => format!("{}d", seconds / DAY),
+    }
+}
+
+/// Formats the time left until a timestamp, or returns `None` once it has passed.
+pub fn countdown(until: Timestamp, now: Timestamp) -> Option<String> {
+    let left = until.as_u64().checked_sub(now.as_u64())?;
+    (left > 0).then(|| duration(left))
+}
+
+/// Formats a timestamp in UTC with the date, e.g. for exported files.
+pub fn utc(timestamp: Timestamp) -> String {
+    DateTime::from_timestamp(timestamp.as_i64(), 0)
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use chrono::{FixedOffset, Utc};
+
+    use super::*;
+
+    const NOW: u64 = 1704091367; // 2024-01-01T15:42:47+09:00
+
+    /// Offset of the cases, so that they don't depend on the time zone of the machine
+    fn jst() -> FixedOffset {
+        FixedOffset::east_opt(9 * 60 * 60).unwrap()
+    }
+
+    #[rstest]
+    #[case(NOW, "just now")]
+    #[case(NOW + 30, "just now")]
+    #[case(NOW - 59, "just now")]
+    #[case(NOW - 60, "1m ago")]
+    #[case(NOW - 59 * 60, "59m ago")]
+    #[case(NOW - 2 * 60 * 60, "2h ago")]
+    #[case(NOW - 6 * 24 * 60 * 60, "6d ago")]
+    #[case(NOW - 7 * 24 * 60 * 60, "2023-12-25")]
+    fn test_relative(#[case] timestamp: u64, #[case] expected: &str) {
+        assert_eq!(
+            relative_in(Timestamp::from(timestamp), Timestamp::from(NOW), &jst()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(TimestampMode::Absolute, "15:42:47")]
+    #[case(TimestampMode::Relative, "1h ago")]
+    fn test_format(#[case] mode: TimestampMode, #[case] expected: &str) {
+        let now = Timestamp::from(NOW + 60 * 60);
+        assert_eq!(format_in(Timestamp::from(NOW), mode, now, &jst()), expected);
+        assert_eq!(
+            format_in(Timestamp::from(NOW), TimestampMode::Absolute, now, &Utc),
+            "06:42:47"
+        );
+    }
+
+    #[rstest]
+    #[case("2024-01-01", Some(NOW - (15 * 60 + 42) * 60 - 47))]
+    #[case("2024-01-01 15:42", Some(NOW - 47))]
+    #[case(" 3d ", Some(NOW - 3 * DAY))]
+    #[case("12h", Some(NOW - 12 * HOUR))]
+    #[case("30m", Some(NOW - 30 * MINUTE))]
+    #[case("2w", Some(NOW - 2 * WEEK))]
+    #[case("yesterday", None)]
+    #[case("2024-13-01", None)]
+    #[case("", None)]
+    fn test_parse(#[case] input: &str, #[case] expected: Option<u64>) {
+        assert_eq!(
+            parse_in(input, Timestamp::from(NOW), &jst()),
+            expected.map(Timestamp::from)
+        );
+    }
+
+    #[rstest]
+    fn test_parse_utc() {
+        assert_eq!(
+            parse_in("2024-01-01 06:42", Timestamp::from(NOW), &Utc),
+            Some(Timestamp::from(NOW - 47))
+        );
+    }
+
+    #[rstest]
+    #[case("30m", Some(30 * MINUTE))]
+    #[case(" 1w", Some(WEEK))]
+    #[case("3", None)]
+    #[case("d", None)]
+    #[case("-1d", None)]
+    fn test_parse_duration(#[case] input: &str, #[case] expected: Option<u64>) {
+        assert_eq!(parse_duration(input), expected);
+    }
+
+    #[rstest]
+    #[case(NOW + 45, Some("45s"))]
+    #[case(NOW + 90, Some("1m"))]
+    #[case(NOW + 3 * HOUR + 59 * MINUTE, Some("3h"))]
+    #[case(NOW + 2 * DAY, Some("2d"))]
+    #[case(NOW, None)]
+    #[case(NOW - 1, None)]
+    fn test_countdown(#[case] until: u64, #[case] expected: Option<&str>) {
+        assert_eq!(
+            countdown(Timestamp::from(until), Timestamp::from(NOW)),
+            expected.map(String::from)
+        );
+    }
+
+    #[rstest]
+    fn test_utc() {
+        assert_eq!(utc(Timestamp::from(NOW)), "2024-01-01 06:42:47 UTC");
+    }
+}
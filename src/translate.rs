@@ -0,0 +1,199 @@
+use std::{net::SocketAddr, time::Duration};
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{config::TranslationConfig, i18n::Locale};
+
+const TRANSLATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// API of translation services.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationBackend {
+    /// LibreTranslate, e.g. `https://libretranslate.com/translate`
+    #[default]
+    LibreTranslate,
+    /// DeepL, e.g. `https://api-free.deepl.com/v2/translate`
+    DeepL,
+}
+
+/// A note translated into the target language.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Translation {
+    pub text: String,
+    /// Language of the note detected by the service, e.g. `en`
+    pub source: Option<String>,
+}
+
+/// Returns the language to translate into, which defaults to the one of the UI.
+pub fn target_language(config: &TranslationConfig, locale: Locale) -> String {
+    config.target.clone().unwrap_or_else(|| {
+        match locale {
+            Locale::En => "en",
+            Locale::Ja => "ja",
+        }
+        .to_string()
+    })
+}
+
+/// Builds the body of the request, leaving the source language to be detected.
+fn request_body(
+    backend: TranslationBackend,
+    text: &str,
+    target: &str,
+    api_key: Option<&str>,
+) -> Value {
+    match backend {
+        TranslationBackend::LibreTranslate => {
+            let mut body = json!({
+                "q": text,
+                "source": "auto",
+                "target": target,
+                "format": "text",
+            });
+            if let Some(api_key) = api_key {
+                body["api_key"] = json!(api_key);
+            }
+            body
+        }
+        TranslationBackend::DeepL => json!({
+            "text": [text],
+            "target_lang": target.to_uppercase(),
+        }),
+    }
+}
+
+fn parse_response(backend: TranslationBackend, body: &str) -> Result<Translation> {
+    let value: Value = serde_json::from_str(body)?;
+    let (text, source) = match backend {
+        TranslationBackend::LibreTranslate => (
+            &value["translatedText"],
+            &value["detectedLanguage"]["language"],
+        ),
+        TranslationBackend::DeepL => (
+            &value["translations"][0]["text"],
+            &value["translations"][0]["detected_source_language"],
+        ),
+    };
+    let text = text.as_str().ok_or_else(|| {
+        let error = value["error"].as_str().or(value["message"].as_str());
+        eyre!("{}", error.unwrap_or("No translation in the response"))
+    })?;
+    Ok(Translation {
+        text: text.to_string(),
+        source: source.as_str().map(str::to_lowercase),
+    })
+}
+
+/// Translates the text with the configured service, through the proxy if any.
+pub async fn translate(
+    config: &TranslationConfig,
+    proxy: Option<SocketAddr>,
+    text: &str,
+    target: &str,
+) -> Result<Translation> {
+    let url = config
+        .url
+        .as_deref()
+        .ok_or_else(|| eyre!("Set translation.url in the config"))?;
+
+    let mut client = reqwest::Client::builder().timeout(TRANSLATE_TIMEOUT);
+    if let Some(proxy) = proxy {
+        client = client.proxy(reqwest::Proxy::all(format!("socks5h://{proxy}"))?);
+    }
+    let client = client.build()?;
+
+    let api_key = config.api_key.as_deref();
+    let mut request = client
+        .post(url)
+        .json(&request_body(config.backend, text, target, api_key));
+    if let (TranslationBackend::DeepL, Some(api_key)) = (config.backend, api_key) {
+        request = request.header("Authorization", format!("DeepL-Auth-Key {api_key}"));
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    parse_response(config.backend, &body).map_err(|e| {
+        if status.is_success() {
+            e
+        } else {
+            eyre!("{status}: {e}")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(None, Locale::Ja, "ja")]
+    #[case(Some("de"), Locale::Ja, "de")]
+    #[case(None, Locale::En, "en")]
+    fn test_target_language(
+        #[case] target: Option<&str>,
+        #[case] locale: Locale,
+        #[case] expected: &str,
+    ) {
+        let config = TranslationConfig {
+            target: target.map(String::from),
+            ..Default::default()
+        };
+        assert_eq!(target_language(&config, locale), expected);
+    }
+
+    #[rstest]
+    fn test_request_body() {
+        assert_eq!(
+            request_body(TranslationBackend::LibreTranslate, "hi", "ja", Some("key")),
+            json!({"q": "hi", "source": "auto", "target": "ja", "format": "text", "api_key": "key"})
+        );
+        assert_eq!(
+            request_body(TranslationBackend::DeepL, "hi", "ja", Some("key")),
+            json!({"text": ["hi"], "target_lang": "JA"})
+        );
+    }
+
+    #[rstest]
+    #[case(
+        TranslationBackend::LibreTranslate,
+        r#"{"translatedText": "こんにちは", "detectedLanguage": {"confidence": 90, "language": "en"}}"#,
+        Some("こんにちは"),
+        Some("en")
+    )]
+    #[case(
+        TranslationBackend::DeepL,
+        r#"{"translations": [{"detected_source_language": "EN", "text": "こんにちは"}]}"#,
+        Some("こんにちは"),
+        Some("en")
+    )]
+    #[case(
+        TranslationBackend::LibreTranslate,
+        r#"{"translatedText": "hi"}"#,
+        Some("hi"),
+        None
+    )]
+    #[case(
+        TranslationBackend::LibreTranslate,
+        r#"{"error": "Invalid API key"}"#,
+        None,
+        None
+    )]
+    fn test_parse_response(
+        #[case] backend: TranslationBackend,
+        #[case] body: &str,
+        #[case] text: Option<&str>,
+        #[case] source: Option<&str>,
+    ) {
+        let expected = text.map(|text| Translation {
+            text: text.to_string(),
+            source: source.map(String::from),
+        });
+        assert_eq!(parse_response(backend, body).ok(), expected);
+    }
+}
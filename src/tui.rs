@@ -7,10 +7,11 @@ use color_eyre::eyre::Result;
 use crossterm::{
     cursor,
     event::{
-        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
-        Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent,
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event as CrosstermEvent, KeyEvent, KeyEventKind,
+        MouseEvent,
     },
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, SetTitle},
 };
 use futures::{FutureExt, StreamExt};
 use ratatui::backend::CrosstermBackend as Backend;
@@ -53,6 +54,7 @@ pub struct Tui {
     pub tick_rate: f64,
     pub mouse: bool,
     pub paste: bool,
+    pub focus: bool,
 }
 
 impl Tui {
@@ -65,6 +67,7 @@ impl Tui {
         let task = tokio::spawn(async {});
         let mouse = false;
         let paste = false;
+        let focus = false;
         Ok(Self {
             terminal,
             task,
@@ -75,6 +78,7 @@ impl Tui {
             tick_rate,
             mouse,
             paste,
+            focus,
         })
     }
 
@@ -98,6 +102,11 @@ impl Tui {
         self
     }
 
+    pub fn focus(mut self, focus: bool) -> Self {
+        self.focus = focus;
+        self
+    }
+
     pub fn start(&mut self) {
         let tick_delay = std::time::Duration::from_secs_f64(1.0 / self.tick_rate);
         let render_delay = std::time::Duration::from_secs_f64(1.0 / self.frame_rate);
@@ -171,7 +180,7 @@ impl Tui {
                 self.task.abort();
             }
             if counter > 100 {
-                log::error!("Failed to abort task in 100 milliseconds for unknown reason");
+                tracing::error!("Failed to abort task in 100 milliseconds for unknown reason");
                 break;
             }
         }
@@ -187,6 +196,9 @@ impl Tui {
         if self.paste {
             crossterm::execute!(io(), EnableBracketedPaste)?;
         }
+        if self.focus {
+            crossterm::execute!(io(), EnableFocusChange)?;
+        }
         self.start();
         Ok(())
     }
@@ -195,6 +207,9 @@ impl Tui {
         self.stop()?;
         if crossterm::terminal::is_raw_mode_enabled()? {
             self.flush()?;
+            if self.focus {
+                crossterm::execute!(io(), DisableFocusChange)?;
+            }
             if self.paste {
                 crossterm::execute!(io(), DisableBracketedPaste)?;
             }
@@ -223,6 +238,21 @@ impl Tui {
         Ok(())
     }
 
+    pub fn set_title(&self, title: &str) -> Result<()> {
+        crossterm::execute!(io(), SetTitle(title))?;
+        Ok(())
+    }
+
+    /// Leaves the terminal to run a program which takes it over, e.g. an editor, and redraws
+    /// everything after coming back.
+    pub fn exec<T>(&mut self, f: impl FnOnce() -> T) -> Result<T> {
+        self.exit()?;
+        let result = f();
+        self.enter()?;
+        self.terminal.clear()?;
+        Ok(result)
+    }
+
     pub async fn next(&mut self) -> Option<Event> {
         self.event_rx.recv().await
     }
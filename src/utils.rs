@@ -9,7 +9,10 @@ use tracing_subscriber::{
     self, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, Layer,
 };
 
-use crate::tui::Tui;
+use crate::{
+    log_buffer::{BufferLayer, LOG_BUFFER},
+    tui::Tui,
+};
 
 lazy_static! {
     pub static ref PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
@@ -50,12 +53,9 @@ pub fn initialize_panic_handler() -> Result<()> {
         #[cfg(not(debug_assertions))]
         {
             use human_panic::{handle_dump, print_msg, Metadata};
-            let meta = Metadata {
-                version: env!("CARGO_PKG_VERSION").into(),
-                name: env!("CARGO_PKG_NAME").into(),
-                authors: env!("CARGO_PKG_AUTHORS").replace(':', ", ").into(),
-                homepage: env!("CARGO_PKG_HOMEPAGE").into(),
-            };
+            let meta = Metadata::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+                .authors(env!("CARGO_PKG_AUTHORS").replace(':', ", "))
+                .homepage(env!("CARGO_PKG_HOMEPAGE"));
 
             let file_path = handle_dump(&meta, panic_info);
             // prints human-panic message
@@ -64,7 +64,7 @@ pub fn initialize_panic_handler() -> Result<()> {
             eprintln!("{}", panic_hook.panic_report(panic_info)); // prints color-eyre stack trace to stderr
         }
         let msg = format!("{}", panic_hook.panic_report(panic_info));
-        log::error!("Error: {}", strip_ansi_escapes::strip_str(msg));
+        error!("Error: {}", strip_ansi_escapes::strip_str(msg));
 
         #[cfg(debug_assertions)]
         {
@@ -121,8 +121,12 @@ pub fn initialize_logging() -> Result<()> {
         .with_target(false)
         .with_ansi(false)
         .with_filter(tracing_subscriber::filter::EnvFilter::from_default_env());
+    // Shown in the log viewer
+    let buffer_subscriber = BufferLayer::new(LOG_BUFFER.clone())
+        .with_filter(tracing_subscriber::filter::EnvFilter::from_default_env());
     tracing_subscriber::registry()
         .with(file_subscriber)
+        .with(buffer_subscriber)
         .with(ErrorLayer::default())
         .init();
     Ok(())
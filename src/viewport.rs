@@ -0,0 +1,401 @@
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+/// Where to put the selected item in the viewport, like `zt`, `zz` and `zb` of Vim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Placement {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// A move of the viewport, applied when the heights of the items are known on render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportMove {
+    /// Scrolls the viewport by an item, keeping the selection unless it goes out of view
+    ScrollDown,
+    ScrollUp,
+    /// Scrolls the viewport and the selection by half of the viewport
+    HalfPageDown,
+    HalfPageUp,
+    Place(Placement),
+}
+
+/// The first item shown in a list, kept apart from the selection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Viewport {
+    offset: usize,
+    pending: Vec<ViewportMove>,
+    /// Items inserted above the viewport which haven't been scrolled to yet
+    unread: usize,
+    /// Items shown by the last layout
+    visible: usize,
+    /// Lines of the selected item scrolled past, when it is taller than the viewport
+    line: usize,
+    /// Item whose lines are scrolled, which starts from its top once another one is selected
+    line_item: Option<usize>,
+    /// Lines of the selected item which don't fit in the viewport by the last layout
+    overflow: usize,
+}
+
+impl Viewport {
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Shows the list from the item, e.g. to restore the view of a session.
+    pub fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    pub fn request(&mut self, movement: ViewportMove) {
+        self.pending.push(movement);
+    }
+
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    /// Returns the lines of the selected item scrolled past.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the lines of the selected item which don't fit in the viewport.
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+
+    /// Scrolls the selected item by a line while it has lines below the viewport, returning
+    /// whether it did.
+    pub fn scroll_line_down(&mut self, selected: Option<usize>) -> bool {
+        if selected.is_none() || selected != self.line_item || self.line >= self.overflow {
+            return false;
+        }
+        self.line += 1;
+        true
+    }
+
+    /// Scrolls the selected item back by a line while it has lines above the viewport, returning
+    /// whether it did.
+    pub fn scroll_line_up(&mut self, selected: Option<usize>) -> bool {
+        if selected.is_none() || selected != self.line_item || self.line == 0 {
+            return false;
+        }
+        self.line -= 1;
+        true
+    }
+
+    /// Returns the indices of the items shown by the last layout.
+    pub fn visible(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.visible
+    }
+
+    /// Keeps the items in view when an item is inserted above them.
+    pub fn insert(&mut self, index: usize) {
+        if self.offset > 0 && index <= self.offset {
+            self.offset += 1;
+            self.unread += 1;
+        }
+        self.shift_line_item(index);
+    }
+
+    /// Keeps the items in view when an item is inserted above them, even at the top of the list.
+    pub fn pin(&mut self, index: usize) {
+        if index <= self.offset {
+            self.offset += 1;
+            self.unread += 1;
+        }
+        self.shift_line_item(index);
+    }
+
+    fn shift_line_item(&mut self, index: usize) {
+        if let Some(item) = self.line_item.as_mut().filter(|item| index <= **item) {
+            *item += 1;
+        }
+    }
+
+    /// Applies the requested moves and returns the selection, which may be moved to stay in view.
+    /// Without moves of the viewport, the viewport follows the selection instead.
+    pub fn layout(
+        &mut self,
+        heights: &[usize],
+        height: usize,
+        selected: Option<usize>,
+    ) -> Option<usize> {
+        let moved = !self.pending.is_empty();
+        let selected = self.layout_selection(heights, height, selected);
+        // Only an item taller than the viewport is scrolled by lines, which is shown from the top
+        self.overflow = match selected {
+            Some(i) if i == self.offset => heights[i].saturating_sub(height),
+            _ => 0,
+        };
+        if moved || selected != self.line_item {
+            self.line = 0;
+        }
+        self.line = self.line.min(self.overflow);
+        self.line_item = selected;
+        // Items are read once scrolled into view
+        self.unread = self.unread.min(self.offset);
+        self.visible = if heights.is_empty() {
+            0
+        } else {
+            last_visible(heights, self.offset, height) + 1 - self.offset
+        };
+        selected
+    }
+
+    fn layout_selection(
+        &mut self,
+        heights: &[usize],
+        height: usize,
+        selected: Option<usize>,
+    ) -> Option<usize> {
+        let pending = mem::take(&mut self.pending);
+        let Some(last) = heights.len().checked_sub(1) else {
+            self.offset = 0;
+            return None;
+        };
+
+        let mut selected = selected.map(|i| i.min(last));
+        let mut follow_viewport = false;
+        for movement in pending {
+            follow_viewport = true;
+            match movement {
+                ViewportMove::ScrollDown => self.offset += 1,
+                ViewportMove::ScrollUp => self.offset = self.offset.saturating_sub(1),
+                ViewportMove::HalfPageDown => {
+                    let n =
+                        count_within(heights[self.offset.min(last)..].iter(), height / 2).max(1);
+                    self.offset += n;
+                    selected = Some(selected.unwrap_or(self.offset) + n);
+                    follow_viewport = false;
+                }
+                ViewportMove::HalfPageUp => {
+                    let n = count_within(heights[..self.offset.min(last)].iter().rev(), height / 2)
+                        .max(1);
+                    self.offset = self.offset.saturating_sub(n);
+                    selected = Some(selected.unwrap_or(self.offset).saturating_sub(n));
+                    follow_viewport = false;
+                }
+                ViewportMove::Place(placement) => {
+                    if let Some(i) = selected {
+                        self.offset = match placement {
+                            Placement::Top => i,
+                            Placement::Center => {
+                                let above = height.saturating_sub(heights[i]) / 2;
+                                i - count_within(heights[..i].iter().rev(), above)
+                            }
+                            Placement::Bottom => bottom_offset(heights, i, height),
+                        };
+                    }
+                }
+            }
+            self.offset = self.offset.min(last);
+            selected = selected.map(|i| i.min(last));
+        }
+
+        let i = selected?;
+        let last_visible = last_visible(heights, self.offset, height);
+        if follow_viewport {
+            Some(i.clamp(self.offset, last_visible))
+        } else {
+            if i < self.offset {
+                self.offset = i;
+            } else if i > last_visible {
+                self.offset = bottom_offset(heights, i, height);
+            }
+            Some(i)
+        }
+    }
+}
+
+/// Counts the items which fit in `height`.
+fn count_within<'a>(heights: impl Iterator<Item = &'a usize>, height: usize) -> usize {
+    let mut total = 0;
+    heights
+        .take_while(|h| {
+            total += *h;
+            total <= height
+        })
+        .count()
+}
+
+/// The last item fully shown from `offset`, or `offset` if it is taller than the viewport.
+fn last_visible(heights: &[usize], offset: usize, height: usize) -> usize {
+    let count = count_within(heights[offset..].iter(), height).max(1);
+    offset + count - 1
+}
+
+/// The offset which shows the item at the bottom of the viewport.
+fn bottom_offset(heights: &[usize], index: usize, height: usize) -> usize {
+    index + 1 - count_within(heights[..=index].iter().rev(), height).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    /// Ten items of 3 lines, five of which fit in the viewport.
+    const HEIGHTS: [usize; 10] = [3; 10];
+    const HEIGHT: usize = 15;
+
+    fn at(offset: usize) -> Viewport {
+        Viewport {
+            offset,
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(0, Some(2), 0, Some(2))]
+    #[case(0, Some(7), 3, Some(7))]
+    #[case(5, Some(2), 2, Some(2))]
+    #[case(3, None, 3, None)]
+    fn test_layout_follows_selection(
+        #[case] offset: usize,
+        #[case] selected: Option<usize>,
+        #[case] expected_offset: usize,
+        #[case] expected_selected: Option<usize>,
+    ) {
+        let mut viewport = at(offset);
+        let selected = viewport.layout(&HEIGHTS, HEIGHT, selected);
+        assert_eq!(
+            (viewport.offset(), selected),
+            (expected_offset, expected_selected)
+        );
+    }
+
+    #[rstest]
+    #[case(0, Some(2), ViewportMove::ScrollDown, 1, Some(2))]
+    #[case(2, Some(2), ViewportMove::ScrollDown, 3, Some(3))]
+    #[case(2, Some(6), ViewportMove::ScrollUp, 1, Some(5))]
+    #[case(9, Some(9), ViewportMove::ScrollDown, 9, Some(9))]
+    #[case(0, Some(1), ViewportMove::HalfPageDown, 2, Some(3))]
+    #[case(4, Some(6), ViewportMove::HalfPageUp, 2, Some(4))]
+    #[case(0, Some(0), ViewportMove::HalfPageUp, 0, Some(0))]
+    #[case(0, Some(6), ViewportMove::Place(Placement::Top), 6, Some(6))]
+    #[case(0, Some(6), ViewportMove::Place(Placement::Center), 4, Some(6))]
+    #[case(0, Some(6), ViewportMove::Place(Placement::Bottom), 2, Some(6))]
+    #[case(0, Some(1), ViewportMove::Place(Placement::Center), 0, Some(1))]
+    #[case(0, Some(0), ViewportMove::Place(Placement::Center), 0, Some(0))]
+    fn test_layout_moves(
+        #[case] offset: usize,
+        #[case] selected: Option<usize>,
+        #[case] movement: ViewportMove,
+        #[case] expected_offset: usize,
+        #[case] expected_selected: Option<usize>,
+    ) {
+        let mut viewport = at(offset);
+        viewport.request(movement);
+        let selected = viewport.layout(&HEIGHTS, HEIGHT, selected);
+        assert_eq!(
+            (viewport.offset(), selected),
+            (expected_offset, expected_selected)
+        );
+
+        // The viewport follows the selection again after the move
+        assert_eq!(viewport.layout(&HEIGHTS, HEIGHT, selected), selected);
+        assert_eq!(viewport.offset(), expected_offset);
+    }
+
+    #[rstest]
+    fn test_layout_tall_item() {
+        let mut viewport = at(0);
+        let heights = [3, 20, 3];
+        assert_eq!(viewport.layout(&heights, HEIGHT, Some(1)), Some(1));
+        assert_eq!(viewport.offset(), 1);
+        assert_eq!(viewport.layout(&[], HEIGHT, Some(1)), None);
+        assert_eq!(viewport.offset(), 0);
+    }
+
+    #[rstest]
+    fn test_scroll_lines() {
+        let mut viewport = at(0);
+        let heights = [3, 17, 3];
+        // Items which fit in the viewport aren't scrolled by lines
+        viewport.layout(&heights, HEIGHT, Some(0));
+        assert!(!viewport.scroll_line_down(Some(0)));
+
+        viewport.layout(&heights, HEIGHT, Some(1));
+        assert_eq!(viewport.overflow(), 2);
+        assert!(!viewport.scroll_line_up(Some(1)));
+        assert!(viewport.scroll_line_down(Some(1)));
+        assert!(viewport.scroll_line_down(Some(1)));
+        assert!(!viewport.scroll_line_down(Some(1)));
+        viewport.layout(&heights, HEIGHT, Some(1));
+        assert_eq!(viewport.line(), 2);
+        assert!(viewport.scroll_line_up(Some(1)));
+
+        // Items inserted above keep the lines of the item
+        viewport.insert(0);
+        viewport.layout(&[3, 3, 17, 3], HEIGHT, Some(2));
+        assert_eq!(viewport.line(), 1);
+
+        // Another item is shown from its top
+        viewport.layout(&[3, 3, 17, 3], HEIGHT, Some(3));
+        viewport.layout(&[3, 3, 17, 3], HEIGHT, Some(2));
+        assert_eq!(viewport.line(), 0);
+    }
+
+    #[rstest]
+    fn test_insert() {
+        let mut viewport = at(0);
+        viewport.insert(0);
+        assert_eq!(viewport.offset(), 0);
+
+        let mut viewport = at(3);
+        viewport.insert(5);
+        assert_eq!(viewport.offset(), 3);
+        viewport.insert(1);
+        assert_eq!(viewport.offset(), 4);
+    }
+
+    #[rstest]
+    fn test_visible() {
+        let mut viewport = at(2);
+        assert_eq!(viewport.visible(), 2..2);
+        viewport.layout(&HEIGHTS, HEIGHT, Some(3));
+        assert_eq!(viewport.visible(), 2..7);
+        viewport.layout(&HEIGHTS, HEIGHT, Some(9));
+        assert_eq!(viewport.visible(), 5..10);
+        viewport.layout(&[], HEIGHT, None);
+        assert_eq!(viewport.visible(), 0..0);
+    }
+
+    #[rstest]
+    fn test_pin() {
+        let mut viewport = at(0);
+        viewport.pin(0);
+        assert_eq!((viewport.offset(), viewport.unread()), (1, 1));
+        viewport.pin(3);
+        assert_eq!((viewport.offset(), viewport.unread()), (1, 1));
+    }
+
+    #[rstest]
+    fn test_unread() {
+        let mut viewport = at(0);
+        viewport.insert(0);
+        assert_eq!(viewport.unread(), 0);
+
+        let mut viewport = at(3);
+        viewport.insert(0);
+        viewport.insert(2);
+        viewport.insert(9);
+        assert_eq!((viewport.offset(), viewport.unread()), (5, 2));
+
+        // Scrolling up reads the items above
+        viewport.request(ViewportMove::ScrollUp);
+        viewport.layout(&HEIGHTS, HEIGHT, Some(5));
+        assert_eq!(viewport.unread(), 2);
+        viewport.request(ViewportMove::Place(Placement::Top));
+        viewport.layout(&HEIGHTS, HEIGHT, Some(1));
+        assert_eq!(viewport.unread(), 1);
+        viewport.layout(&HEIGHTS, HEIGHT, Some(0));
+        assert_eq!(viewport.unread(), 0);
+    }
+}
@@ -0,0 +1,329 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui_textarea::{CursorMove, TextArea};
+
+/// Modes of the vim-like editing in the composer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VimMode {
+    Normal,
+    /// Keys are typed into the textarea as usual
+    #[default]
+    Insert,
+    /// Motions extend the selection
+    Visual,
+}
+
+impl VimMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VimMode::Normal => "-- NORMAL --",
+            VimMode::Insert => "-- INSERT --",
+            VimMode::Visual => "-- VISUAL --",
+        }
+    }
+}
+
+/// A small subset of vim on top of the textarea: motions, `dd`/`yy`/`p` and a visual mode.
+#[derive(Clone, Debug, Default)]
+pub struct Vim {
+    mode: VimMode,
+    /// First key of a two-key command, e.g. `g` of `gg`
+    pending: Option<char>,
+    /// Where the selection of visual mode started
+    anchor: (usize, usize),
+}
+
+impl Vim {
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Starts over in insert mode, e.g. when the composer is opened.
+    pub fn reset(&mut self) {
+        self.mode = VimMode::Insert;
+        self.pending = None;
+    }
+
+    /// Goes back to normal mode, returning false if it is already in normal mode.
+    pub fn escape(&mut self, textarea: &mut TextArea<'_>) -> bool {
+        self.pending = None;
+        match self.mode {
+            VimMode::Normal => false,
+            VimMode::Insert => {
+                self.mode = VimMode::Normal;
+                // The cursor steps back onto the last typed character as in vim
+                if textarea.cursor().1 > 0 {
+                    textarea.move_cursor(CursorMove::Back);
+                }
+                true
+            }
+            VimMode::Visual => {
+                textarea.cancel_selection();
+                self.mode = VimMode::Normal;
+                true
+            }
+        }
+    }
+
+    /// Handles the key in normal and visual modes, returning false to leave it to the textarea.
+    pub fn input(&mut self, key: KeyEvent, textarea: &mut TextArea<'_>) -> bool {
+        if self.mode == VimMode::Insert {
+            return false;
+        }
+
+        let pending = self.pending.take();
+        let c = match key.code {
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if key.code == KeyCode::Char('r') {
+                    textarea.redo();
+                }
+                return true;
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::ALT) => c,
+            KeyCode::Esc => return true,
+            // Arrow keys and the like work as in the textarea
+            _ => return false,
+        };
+
+        if let Some(motion) = motion(c, pending) {
+            textarea.move_cursor(motion);
+            return true;
+        }
+
+        match (self.mode, pending, c) {
+            (_, None, 'g') => self.pending = Some('g'),
+            (VimMode::Normal, None, 'd' | 'y') => self.pending = Some(c),
+            (VimMode::Normal, Some('y'), 'y') => yank_line(textarea),
+            (VimMode::Normal, Some('d'), 'd') => delete_line(textarea),
+            (VimMode::Normal, _, 'x') => {
+                textarea.delete_next_char();
+            }
+            (VimMode::Normal, _, 'p') => put(textarea, false),
+            (VimMode::Normal, _, 'P') => put(textarea, true),
+            (VimMode::Normal, _, 'u') => {
+                textarea.undo();
+            }
+            (VimMode::Normal, _, 'i') => self.mode = VimMode::Insert,
+            (VimMode::Normal, _, 'a') => {
+                if !textarea.lines()[textarea.cursor().0].is_empty() {
+                    textarea.move_cursor(CursorMove::Forward);
+                }
+                self.mode = VimMode::Insert;
+            }
+            (VimMode::Normal, _, 'A') => {
+                textarea.move_cursor(CursorMove::End);
+                self.mode = VimMode::Insert;
+            }
+            (VimMode::Normal, _, 'I') => {
+                textarea.move_cursor(CursorMove::Head);
+                self.mode = VimMode::Insert;
+            }
+            (VimMode::Normal, _, 'o') => {
+                textarea.move_cursor(CursorMove::End);
+                textarea.insert_newline();
+                self.mode = VimMode::Insert;
+            }
+            (VimMode::Normal, _, 'O') => {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.insert_newline();
+                textarea.move_cursor(CursorMove::Up);
+                self.mode = VimMode::Insert;
+            }
+            (VimMode::Normal, _, 'v') => {
+                self.anchor = textarea.cursor();
+                textarea.start_selection();
+                self.mode = VimMode::Visual;
+            }
+            (VimMode::Visual, _, 'y') => {
+                let start = self.select(textarea);
+                textarea.copy();
+                jump(textarea, start);
+                self.mode = VimMode::Normal;
+            }
+            (VimMode::Visual, _, 'd' | 'x') => {
+                self.select(textarea);
+                textarea.cut();
+                self.mode = VimMode::Normal;
+            }
+            (VimMode::Visual, _, 'v') => {
+                textarea.cancel_selection();
+                self.mode = VimMode::Normal;
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Selects from the anchor to the cursor including both ends as in vim, returning the start.
+    fn select(&self, textarea: &mut TextArea<'_>) -> (usize, usize) {
+        let cursor = textarea.cursor();
+        let (start, end) = (self.anchor.min(cursor), self.anchor.max(cursor));
+        textarea.cancel_selection();
+        jump(textarea, start);
+        textarea.start_selection();
+        jump(textarea, end);
+        textarea.move_cursor(CursorMove::Forward);
+        start
+    }
+}
+
+fn jump(textarea: &mut TextArea<'_>, (row, col): (usize, usize)) {
+    textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+}
+
+fn motion(c: char, pending: Option<char>) -> Option<CursorMove> {
+    let motion = match (pending, c) {
+        (Some('g'), 'g') => CursorMove::Top,
+        (Some(_), _) => return None,
+        (None, 'h') => CursorMove::Back,
+        (None, 'j') => CursorMove::Down,
+        (None, 'k') => CursorMove::Up,
+        (None, 'l') => CursorMove::Forward,
+        (None, 'w') => CursorMove::WordForward,
+        (None, 'b') => CursorMove::WordBack,
+        (None, '0' | '^') => CursorMove::Head,
+        (None, '$') => CursorMove::End,
+        (None, 'G') => CursorMove::Bottom,
+        _ => return None,
+    };
+    Some(motion)
+}
+
+/// Yanks the line under the cursor, with a newline to put it as a line.
+fn yank_line(textarea: &mut TextArea<'_>) {
+    let line = &textarea.lines()[textarea.cursor().0];
+    textarea.set_yank_text(format!("{line}\n"));
+}
+
+fn delete_line(textarea: &mut TextArea<'_>) {
+    yank_line(textarea);
+    let yanked = textarea.yank_text();
+
+    let (row, _) = textarea.cursor();
+    textarea.move_cursor(CursorMove::Head);
+    textarea.start_selection();
+    textarea.move_cursor(CursorMove::Down);
+    if textarea.cursor().0 == row {
+        // The last line takes the newline before it instead
+        textarea.cancel_selection();
+        if row > 0 {
+            textarea.move_cursor(CursorMove::Up);
+            textarea.move_cursor(CursorMove::End);
+        }
+        textarea.start_selection();
+        textarea.move_cursor(CursorMove::Bottom);
+        textarea.move_cursor(CursorMove::End);
+    }
+    textarea.cut();
+    textarea.move_cursor(CursorMove::Head);
+    textarea.set_yank_text(yanked);
+}
+
+/// Puts the yanked text after the cursor, or below the line if a line was yanked, or before
+/// and above them respectively.
+fn put(textarea: &mut TextArea<'_>, before: bool) {
+    let yanked = textarea.yank_text();
+    let (row, _) = textarea.cursor();
+    match (yanked.strip_suffix('\n'), before) {
+        (Some(lines), false) => {
+            textarea.move_cursor(CursorMove::End);
+            textarea.insert_newline();
+            textarea.insert_str(lines);
+            jump(textarea, (row + 1, 0));
+        }
+        (Some(lines), true) => {
+            textarea.move_cursor(CursorMove::Head);
+            textarea.insert_str(lines);
+            textarea.insert_newline();
+            jump(textarea, (row, 0));
+        }
+        (None, false) => {
+            if !textarea.lines()[row].is_empty() {
+                textarea.move_cursor(CursorMove::Forward);
+            }
+            textarea.paste();
+        }
+        (None, true) => {
+            textarea.paste();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    fn type_keys(vim: &mut Vim, textarea: &mut TextArea<'_>, keys: &str) {
+        for c in keys.chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            if !vim.input(key, textarea) {
+                textarea.input(key);
+            }
+        }
+    }
+
+    fn normal(lines: &[&str]) -> (Vim, TextArea<'static>) {
+        let mut vim = Vim::default();
+        let mut textarea = TextArea::from(lines.iter().map(|line| line.to_string()));
+        vim.escape(&mut textarea);
+        (vim, textarea)
+    }
+
+    #[rstest]
+    #[case(&["gm", "nostr"], "jl", (1, 1))]
+    #[case(&["gm", "nostr"], "j$", (1, 5))]
+    #[case(&["gm", "nostr"], "Ggg", (0, 0))]
+    #[case(&["gm nostr"], "w", (0, 3))]
+    #[case(&["gm nostr"], "w0", (0, 0))]
+    fn test_motions(#[case] lines: &[&str], #[case] keys: &str, #[case] expected: (usize, usize)) {
+        let (mut vim, mut textarea) = normal(lines);
+        type_keys(&mut vim, &mut textarea, keys);
+        assert_eq!(textarea.cursor(), expected);
+        assert_eq!(textarea.lines(), lines);
+    }
+
+    #[rstest]
+    #[case(&["a", "b", "c"], "ddp", &["b", "a", "c"])]
+    #[case(&["a", "b", "c"], "jyyP", &["a", "b", "b", "c"])]
+    #[case(&["a", "b", "c"], "Gdd", &["a", "b"])]
+    #[case(&["a"], "dd", &[""])]
+    #[case(&["gm nostr"], "x", &["m nostr"])]
+    #[case(&["gm nostr"], "vlyp", &["ggmm nostr"])]
+    #[case(&["gm nostr"], "wvlld", &["gm tr"])]
+    #[case(&["gm"], "A!", &["gm!"])]
+    #[case(&["gm"], "onostr", &["gm", "nostr"])]
+    #[case(&["gm"], "xu", &["gm"])]
+    fn test_edits(#[case] lines: &[&str], #[case] keys: &str, #[case] expected: &[&str]) {
+        let (mut vim, mut textarea) = normal(lines);
+        type_keys(&mut vim, &mut textarea, keys);
+        assert_eq!(textarea.lines(), expected);
+    }
+
+    #[rstest]
+    fn test_modes() {
+        let mut vim = Vim::default();
+        let mut textarea = TextArea::default();
+        assert_eq!(vim.mode(), VimMode::Insert);
+
+        type_keys(&mut vim, &mut textarea, "gm");
+        assert_eq!(textarea.lines(), ["gm"]);
+
+        assert!(vim.escape(&mut textarea));
+        assert_eq!(vim.mode(), VimMode::Normal);
+        assert_eq!(textarea.cursor(), (0, 1));
+
+        type_keys(&mut vim, &mut textarea, "v");
+        assert_eq!(vim.mode(), VimMode::Visual);
+        assert!(vim.escape(&mut textarea));
+        assert_eq!(vim.mode(), VimMode::Normal);
+
+        // Esc in normal mode is left to the composer, which closes
+        assert!(!vim.escape(&mut textarea));
+
+        vim.reset();
+        assert_eq!(vim.mode(), VimMode::Insert);
+    }
+}
@@ -1,9 +1,15 @@
+mod layout_cache;
 mod public_key;
 mod scrollable_list;
+mod scrolled;
 mod shrink_text;
+mod tab_bar;
 mod text_note;
 
+pub use layout_cache::LayoutCache;
 pub use public_key::PublicKey;
 pub use scrollable_list::ScrollableList;
+pub use scrolled::Scrolled;
 pub use shrink_text::ShrinkText;
+pub use tab_bar::TabBar;
 pub use text_note::TextNote;
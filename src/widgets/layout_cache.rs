@@ -0,0 +1,84 @@
+use std::{cell::RefCell, num::NonZeroUsize, sync::Arc};
+
+use lru::LruCache;
+use nostr_sdk::prelude::*;
+
+/// Layouts kept, which covers the notes of busy tabs as heights of all notes are measured on
+/// each frame.
+const CAPACITY: NonZeroUsize = match NonZeroUsize::new(16_384) {
+    Some(capacity) => capacity,
+    None => unreachable!(),
+};
+
+/// Wrapped lines of the content of notes by the width they were wrapped at, so that scrolling
+/// doesn't wrap every note again on each frame.
+///
+/// Wrapping doesn't depend on colors, so the cache is cleared when the theme changes rather than
+/// keyed by it.
+#[derive(Debug)]
+pub struct LayoutCache {
+    entries: RefCell<LruCache<(EventId, u16), Arc<str>>>,
+}
+
+impl Default for LayoutCache {
+    fn default() -> Self {
+        Self::new(CAPACITY)
+    }
+}
+
+impl LayoutCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: RefCell::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the wrapped content of the note, wrapping it with `wrap` unless it is cached.
+    pub fn wrapped(&self, id: EventId, width: u16, wrap: impl FnOnce() -> String) -> Arc<str> {
+        self.entries
+            .borrow_mut()
+            .get_or_insert((id, width), || Arc::from(wrap()))
+            .clone()
+    }
+
+    /// Forgets the layouts, e.g. when the terminal is resized.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    fn test_wrapped() {
+        let cache = LayoutCache::new(NonZeroUsize::new(2).unwrap());
+        let (gm, gn) = (EventId::all_zeros(), EventId::from_slice(&[1; 32]).unwrap());
+
+        assert_eq!(&*cache.wrapped(gm, 10, || String::from("gm")), "gm");
+        // Cached layouts are used as they are
+        assert_eq!(&*cache.wrapped(gm, 10, || String::from("g\nm")), "gm");
+        // Another width is wrapped again
+        assert_eq!(&*cache.wrapped(gm, 1, || String::from("g\nm")), "g\nm");
+        assert_eq!(cache.len(), 2);
+
+        // The least recently used layout is dropped
+        cache.wrapped(gn, 10, || String::from("gn"));
+        assert_eq!(&*cache.wrapped(gm, 10, || String::from("g m")), "g m");
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+use ratatui::prelude::*;
+
+/// Shows a widget taller than the area from one of its lines, e.g. a long note scrolled within.
+#[derive(Clone, Debug)]
+pub struct Scrolled<W> {
+    widget: W,
+    height: u16,
+    line: u16,
+}
+
+impl<W: Widget> Scrolled<W> {
+    /// Wraps the widget of `height` lines, which is shown from `line`.
+    pub fn new(widget: W, height: usize, line: usize) -> Self {
+        Self {
+            widget,
+            height: u16::try_from(height).unwrap_or(u16::MAX),
+            line: u16::try_from(line).unwrap_or(u16::MAX),
+        }
+    }
+}
+
+impl<W: Widget> Widget for Scrolled<W> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // The widget is rendered whole aside, as widgets can't be rendered from a line
+        let mut hidden = Buffer::empty(Rect {
+            height: self.height.max(area.height),
+            ..area
+        });
+        self.widget.render(hidden.area, &mut hidden);
+
+        let line = self.line.min(hidden.area.height - area.height);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                *buf.get_mut(x, y) = hidden.get(x, y + line).clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::widgets::Paragraph;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, "ab")]
+    #[case(1, "bc")]
+    #[case(2, "cd")]
+    #[case(5, "cd")]
+    fn test_render(#[case] line: usize, #[case] expected: &str) {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 2));
+        Scrolled::new(Paragraph::new("a\nb\nc\nd"), 4, line).render(buf.area, &mut buf);
+        assert_eq!(
+            buf.content.iter().map(|c| c.symbol()).collect::<String>(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthStr;
+
+use crate::text::truncate_width;
+
+/// Widest a tab title can be, so that long names don't push the other tabs out of view.
+const MAX_TITLE_WIDTH: usize = 20;
+
+const DIVIDER: &str = "|";
+const LEFT_MARKER: &str = "‹ ";
+const RIGHT_MARKER: &str = " ›";
+const BORDER: &str = "│";
+
+/// A tab bar which scrolls to keep the selected tab in view when the tabs don't fit.
+#[derive(Clone, Debug, Default)]
+pub struct TabBar {
+    labels: Vec<String>,
+    selected: usize,
+    /// Whether the tabs are listed one per line, with a border on the right
+    vertical: bool,
+}
+
+impl TabBar {
+    /// Numbers the titles from 1 and truncates the long ones.
+    pub fn new(titles: impl IntoIterator<Item = String>, selected: usize) -> Self {
+        let labels = titles
+            .into_iter()
+            .enumerate()
+            .map(|(i, title)| format!("{}:{}", i + 1, truncate_width(&title, MAX_TITLE_WIDTH)))
+            .collect();
+        Self {
+            labels,
+            selected,
+            vertical: false,
+        }
+    }
+
+    pub fn vertical(mut self, vertical: bool) -> Self {
+        self.vertical = vertical;
+        self
+    }
+
+    /// Returns the width which fits all of the labels of a vertical tab bar.
+    pub fn width(&self) -> u16 {
+        let widest = self.labels.iter().map(|label| label.width()).max();
+        (widest.unwrap_or(0) + BORDER.width()) as u16
+    }
+
+    fn render_vertical(self, area: Rect, buf: &mut Buffer) {
+        let height = area.height as usize;
+        let start = (self.selected + 1).saturating_sub(height);
+        let label_width = (area.width as usize).saturating_sub(BORDER.width());
+        for (i, label) in self.labels.iter().enumerate().skip(start).take(height) {
+            let style = if i == self.selected {
+                Style::default().bold().reversed()
+            } else {
+                Style::default()
+            };
+            let y = area.y + (i - start) as u16;
+            buf.set_stringn(area.x, y, label, label_width, style);
+        }
+        let x = area.right() - BORDER.width() as u16;
+        for y in area.top()..area.bottom() {
+            buf.set_string(x, y, BORDER, Style::default().dim());
+        }
+    }
+}
+
+impl Widget for TabBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() || self.labels.is_empty() {
+            return;
+        }
+        if self.vertical {
+            return self.render_vertical(area, buf);
+        }
+
+        let widths: Vec<usize> = self.labels.iter().map(|label| label.width()).collect();
+        let range = visible_range(&widths, self.selected, area.width as usize);
+        let marker_style = Style::default().dim();
+        let right = area.right() as usize;
+
+        let mut x = area.x;
+        if range.start > 0 {
+            (x, _) = buf.set_stringn(x, area.y, LEFT_MARKER, right - x as usize, marker_style);
+        }
+        for i in range.clone() {
+            if i > range.start {
+                (x, _) = buf.set_stringn(x, area.y, DIVIDER, right - x as usize, Style::default());
+            }
+            let style = if i == self.selected {
+                Style::default().bold().reversed()
+            } else {
+                Style::default()
+            };
+            (x, _) = buf.set_stringn(x, area.y, &self.labels[i], right - x as usize, style);
+        }
+        if range.end < self.labels.len() && area.width as usize >= RIGHT_MARKER.width() {
+            let x = area.right() - RIGHT_MARKER.width() as u16;
+            buf.set_string(x, area.y, RIGHT_MARKER, marker_style);
+        }
+    }
+}
+
+/// Returns the tabs to show in `width` cells, which include the selected one and as many as
+/// fit after the first tab or, if it is far, before the selected one.
+fn visible_range(widths: &[usize], selected: usize, width: usize) -> Range<usize> {
+    let len = widths.len();
+    if len == 0 {
+        return 0..0;
+    }
+    let selected = selected.min(len - 1);
+
+    let fits = |start: usize, end: usize| {
+        let tabs: usize = widths[start..end].iter().sum::<usize>() + (end - start - 1);
+        let left = if start > 0 { LEFT_MARKER.width() } else { 0 };
+        let right = if end < len { RIGHT_MARKER.width() } else { 0 };
+        tabs + left + right <= width
+    };
+
+    let mut start = 0;
+    let mut end = selected + 1;
+    while start < selected && !fits(start, end) {
+        start += 1;
+    }
+    while end < len && fits(start, end + 1) {
+        end += 1;
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(&[], 0, 10, 0..0)]
+    #[case(&[4, 4, 4], 0, 14, 0..3)]
+    #[case(&[4, 4, 4], 2, 14, 0..3)]
+    #[case(&[4, 4, 4], 0, 13, 0..2)]
+    #[case(&[4, 4, 4], 2, 13, 1..3)]
+    #[case(&[4, 4, 4, 4, 4], 2, 13, 1..3)]
+    #[case(&[4, 4, 4, 4, 4], 4, 13, 3..5)]
+    #[case(&[30, 4], 0, 10, 0..1)]
+    fn test_visible_range(
+        #[case] widths: &[usize],
+        #[case] selected: usize,
+        #[case] width: usize,
+        #[case] expected: Range<usize>,
+    ) {
+        assert_eq!(visible_range(widths, selected, width), expected);
+    }
+
+    #[rstest]
+    fn test_render() {
+        let titles = ["⌂ Home", "#nostr", "@ alice"].map(String::from);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 1));
+        TabBar::new(titles, 2).render(buf.area, &mut buf);
+        assert_eq!(
+            buf.content.iter().map(|c| c.symbol()).collect::<String>(),
+            "‹ 2:#nostr|3:@ alice"
+        );
+    }
+
+    #[rstest]
+    fn test_render_vertical() {
+        let titles = ["⌂ Home", "#nostr", "@ alice"].map(String::from);
+        let tabs = TabBar::new(titles, 2).vertical(true);
+        assert_eq!(tabs.width(), 10);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        tabs.render(buf.area, &mut buf);
+        let lines: Vec<String> = buf
+            .content
+            .chunks(8)
+            .map(|line| line.iter().map(|c| c.symbol()).collect())
+            .collect();
+        assert_eq!(lines, ["2:#nost│", "3:@ ali│"]);
+    }
+}
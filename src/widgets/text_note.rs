@@ -1,34 +1,81 @@
-use std::collections::HashSet;
+use std::sync::Arc;
 
-use chrono::{DateTime, Local};
 use nostr_sdk::prelude::*;
 use ratatui::{prelude::*, widgets::*};
-use thousands::Separable;
 use tui_widget_list::Listable;
 
-use crate::nostr::Profile;
-use crate::widgets::{PublicKey, ShrinkText};
+use crate::avatar;
+use crate::emoji;
+use crate::i18n;
+use crate::link_preview::{self, PreviewState};
+use crate::nostr::{nip57::Zap, nip75::ZapGoal, nip94::FileMetadata, Profile};
+use crate::outbox::Delivery;
+use crate::stats;
+use crate::text::{self, compact_number};
+use crate::timestamp::{self, TimestampMode};
+use crate::translate::Translation;
+use crate::widgets::{LayoutCache, PublicKey};
 
 #[derive(Clone, Debug)]
 pub struct TextNote {
     pub event: Event,
     pub profile: Option<Profile>,
-    pub reactions: HashSet<Event>,
-    pub reposts: HashSet<Event>,
-    pub zap_receipts: HashSet<Event>,
+    /// Numbers of reactions and reposts, counted rather than copied on each frame
+    pub reactions: usize,
+    pub reposts: usize,
+    pub zaps: Vec<Zap>,
     pub area: Rect,
     pub padding: Padding, // Only use to calc width/height
     pub highlight: bool,
     pub top_truncated_height: Option<usize>,
+    pub reply_to: Option<String>,
+    /// Names of the accounts which reposted the note, the latest first
+    pub reposted_by: Vec<String>,
+    pub collapsed: bool,
+    /// Whether the author is muted for now, which collapses any of their notes
+    pub muted: bool,
+    /// Score of a note folded by the spam filter
+    pub filtered: Option<u64>,
+    pub annotations: Vec<String>,
+    pub content_warning: Option<String>,
+    /// Translation shown under the content
+    pub translation: Option<Translation>,
+    pub translating: bool,
+    /// Cards of the links in the content, shown under it
+    pub link_previews: Vec<(String, PreviewState)>,
+    pub timestamp_mode: TimestampMode,
+    /// Lines of the content shown until expanded, or 0 for all of them
+    pub max_lines: usize,
+    pub expanded: bool,
+    /// Whether notes may be missing above the note
+    pub gap_above: bool,
+    /// Whether the note is the newest one from before the app quit last time
+    pub last_visit_above: bool,
+    /// Whether the name and the avatar are colored by author
+    pub author_colors: bool,
+    pub avatar: bool,
+    /// Whether to render the note as a chat message, without the stats
+    pub chat: bool,
+    /// State of my note published in this session
+    pub delivery: Option<Delivery>,
+    /// Content wrapped at the width of the note, if it was laid out before
+    pub wrapped: Option<Arc<str>>,
+    /// Zap goal which the note raises funds for, with the zaps to the goal
+    pub goal: Option<(ZapGoal, Vec<Zap>)>,
+    /// File shared by the note, shown as a card above its description
+    pub file: Option<FileMetadata>,
 }
 
+/// Cells of the progress bar of a zap goal.
+const GOAL_BAR_WIDTH: usize = 10;
+
 impl TextNote {
     pub fn new(
         event: Event,
         profile: Option<Profile>,
-        reactions: HashSet<Event>,
-        reposts: HashSet<Event>,
-        zap_receipts: HashSet<Event>,
+        reactions: usize,
+        reposts: usize,
+        zaps: Vec<Zap>,
         area: Rect,
         padding: Padding,
     ) -> Self {
@@ -37,12 +84,293 @@ impl TextNote {
             profile,
             reactions,
             reposts,
-            zap_receipts,
+            zaps,
             area,
             padding,
             highlight: false,
             top_truncated_height: None,
+            reply_to: None,
+            reposted_by: vec![],
+            collapsed: false,
+            muted: false,
+            filtered: None,
+            annotations: vec![],
+            content_warning: None,
+            translation: None,
+            translating: false,
+            link_previews: vec![],
+            timestamp_mode: TimestampMode::default(),
+            max_lines: 0,
+            expanded: false,
+            gap_above: false,
+            last_visit_above: false,
+            author_colors: false,
+            avatar: false,
+            chat: false,
+            delivery: None,
+            wrapped: None,
+            goal: None,
+            file: None,
+        }
+    }
+
+    /// Takes the wrapped content from the cache, wrapping it only if the note is new or the width
+    /// changed.
+    pub fn layout_cache(mut self, cache: &LayoutCache) -> Self {
+        self.wrapped =
+            Some(cache.wrapped(self.event.id, self.content_width(), || self.wrap_content()));
+        self
+    }
+
+    fn wrap_content(&self) -> String {
+        let content = emoji::emojify(&self.event.content, &self.event.tags);
+        text::wrap_text(&content, self.content_width() as usize)
+    }
+
+    /// Sets the name of the author replied to.
+    pub fn reply_to(mut self, name: Option<String>) -> Self {
+        self.reply_to = name;
+        self
+    }
+
+    /// Sets the names of the accounts which reposted the note, shown above it.
+    pub fn reposted_by(mut self, names: Vec<String>) -> Self {
+        self.reposted_by = names;
+        self
+    }
+
+    /// Returns e.g. `⟳ reposted by alice, bob and 2 others`.
+    fn reposted_by_line(&self) -> Option<String> {
+        const SHOWN_NAMES: usize = 2;
+
+        if self.reposted_by.is_empty() {
+            return None;
+        }
+
+        let names = self
+            .reposted_by
+            .iter()
+            .take(SHOWN_NAMES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        let others = self.reposted_by.len().saturating_sub(SHOWN_NAMES);
+        if others == 0 {
+            return Some(i18n::tr_args("⟳ reposted by {names}", &[("names", &names)]));
+        }
+
+        let others = i18n::plural(
+            "{count, plural, one {# other} other {# others}}",
+            others as u64,
+        );
+        Some(i18n::tr_args(
+            "⟳ reposted by {names} and {others}",
+            &[("names", &names), ("others", &others)],
+        ))
+    }
+
+    /// Folds the note into a single line, e.g. for replies from noisy accounts.
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Folds the note into a single line as its author is muted for now.
+    pub fn muted(mut self, muted: bool) -> Self {
+        self.muted = muted;
+        self
+    }
+
+    /// Folds the note into a single line showing the score given by the spam filter.
+    pub fn filtered(mut self, score: Option<u64>) -> Self {
+        self.filtered = score;
+        self
+    }
+
+    fn is_collapsed(&self) -> bool {
+        self.collapsed || self.muted || self.filtered.is_some()
+    }
+
+    /// Sets notes added by plugins, shown above the author.
+    pub fn annotations(mut self, annotations: Vec<String>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Hides the content behind the reason of its content warning (NIP-36).
+    pub fn content_warning(mut self, reason: Option<String>) -> Self {
+        self.content_warning = reason;
+        self
+    }
+
+    /// Shows the translation under the content, or that it is being translated.
+    pub fn translation(mut self, translation: Option<Translation>, translating: bool) -> Self {
+        self.translation = translation;
+        self.translating = translating;
+        self
+    }
+
+    pub fn file(mut self, file: Option<FileMetadata>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Shows cards of the links under the content, by their URLs.
+    pub fn link_previews(mut self, previews: Vec<(String, PreviewState)>) -> Self {
+        self.link_previews = previews;
+        self
+    }
+
+    pub fn timestamp_mode(mut self, mode: TimestampMode) -> Self {
+        self.timestamp_mode = mode;
+        self
+    }
+
+    /// Shows only the first lines of a long content, unless expanded.
+    pub fn preview(mut self, max_lines: usize, expanded: bool) -> Self {
+        self.max_lines = max_lines;
+        self.expanded = expanded;
+        self
+    }
+
+    /// Shows a row above the note telling that newer notes may be missing.
+    pub fn gap_above(mut self, gap_above: bool) -> Self {
+        self.gap_above = gap_above;
+        self
+    }
+
+    /// Shows the progress of the zap goal which the note links to.
+    pub fn goal(mut self, goal: Option<(ZapGoal, Vec<Zap>)>) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    /// Shows a row above the note telling that the notes above arrived since the last visit.
+    pub fn last_visit_above(mut self, last_visit_above: bool) -> Self {
+        self.last_visit_above = last_visit_above;
+        self
+    }
+
+    /// Colors the name by author and shows their initial before it.
+    pub fn author_style(mut self, colors: bool, avatar: bool) -> Self {
+        self.author_colors = colors;
+        self.avatar = avatar;
+        self
+    }
+
+    /// Renders the note as a chat message, e.g. in a channel.
+    pub fn chat(mut self, chat: bool) -> Self {
+        self.chat = chat;
+        self
+    }
+
+    /// Marks my note as being sent, sent or failed after the time.
+    pub fn delivery(mut self, delivery: Option<Delivery>) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    fn delivery_span(&self) -> Option<Span<'static>> {
+        let (label, color) = match self.delivery? {
+            Delivery::Sending => (i18n::tr("sending…"), Color::Gray),
+            Delivery::Sent => (i18n::tr("✓ sent"), Color::LightGreen),
+            Delivery::Failed => (i18n::tr("✗ failed: see the outbox"), Color::LightRed),
+        };
+        Some(Span::styled(
+            format!(" {label}"),
+            Style::default().fg(color),
+        ))
+    }
+
+    /// Draws how much of the zap goal is raised, e.g. `◎ ██░░░░░░░░ 25% 25/100 sats`.
+    fn goal_line(&self) -> Option<Line<'static>> {
+        let (goal, zaps) = self.goal.as_ref()?;
+        let (raised, ratio) = goal.progress(zaps);
+        let filled = (ratio * GOAL_BAR_WIDTH as f64).round() as usize;
+        let mut spans = vec![
+            Span::styled("◎ ", Style::default().fg(Color::LightYellow)),
+            Span::styled("█".repeat(filled), Style::default().fg(Color::LightYellow)),
+            Span::styled(
+                "░".repeat(GOAL_BAR_WIDTH - filled),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!(
+                " {}% {}",
+                (ratio * 100.0).floor(),
+                i18n::tr_args(
+                    "{raised}/{goal} sats",
+                    &[
+                        ("raised", &compact_number(raised / 1000)),
+                        ("goal", &compact_number(goal.millisats / 1000)),
+                    ],
+                )
+            )),
+        ];
+        if goal.is_closed(Timestamp::now()) {
+            spans.push(Span::styled(
+                format!(" {}", i18n::tr("(closed)")),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        if !goal.description.is_empty() {
+            spans.push(Span::styled(
+                format!(" {}", goal.description.replace('\n', " ")),
+                Style::default().italic(),
+            ));
+        }
+        Some(Line::from(spans))
+    }
+
+    /// Counts down to the expiration of the note (NIP-40), if any.
+    fn expiration_span(&self) -> Option<Span<'static>> {
+        let expiration = *self.event.expiration()?;
+        let span = match timestamp::countdown(expiration, Timestamp::now()) {
+            Some(left) => Span::styled(
+                i18n::tr_args(" ⏳ expires in {time}", &[("time", &left)]),
+                Style::default().fg(Color::Yellow),
+            ),
+            None => Span::styled(
+                i18n::tr(" ⌛ expired"),
+                Style::default().fg(Color::DarkGray),
+            ),
+        };
+        Some(span)
+    }
+
+    fn avatar(&self, name: &str) -> Option<Span<'static>> {
+        if !self.avatar {
+            return None;
         }
+
+        let initial = avatar::initial(name).unwrap_or_else(|| String::from("?"));
+        let style = if self.author_colors {
+            Style::default().fg(Color::Black).bg(self.author_color())
+        } else {
+            Style::default().reversed()
+        };
+        Some(Span::styled(format!(" {initial} "), style.bold()))
+    }
+
+    fn author_color(&self) -> Color {
+        avatar::color(&self.event.pubkey)
+    }
+
+    /// Marks the notes above as new since the last visit, then as possibly missing some notes.
+    fn gap(&self) -> Text<'static> {
+        let mut text = Text::default();
+        if self.last_visit_above {
+            text.extend(Text::styled(
+                i18n::tr("── New since last visit ──"),
+                Style::default().fg(Color::LightBlue).bold(),
+            ));
+        }
+        if self.gap_above {
+            text.extend(Text::styled(
+                i18n::tr("┄ Notes may be missing here: press R to fill the gap ┄"),
+                Style::default().fg(Color::LightYellow).italic(),
+            ));
+        }
+        text
     }
 
     pub fn display_name(&self) -> Option<String> {
@@ -73,46 +401,19 @@ impl TextNote {
     }
 
     pub fn created_at(&self) -> String {
-        DateTime::from_timestamp(self.event.created_at.as_i64(), 0)
-            .expect("Invalid created_at")
-            .with_timezone(&Local)
-            .format("%T")
-            .to_string()
+        timestamp::format(self.event.created_at, self.timestamp_mode, Timestamp::now())
     }
 
     pub fn reactions_count(&self) -> usize {
-        self.reactions.len()
+        self.reactions
     }
 
     pub fn reposts_count(&self) -> usize {
-        self.reposts.len()
-    }
-
-    fn find_amount(&self, ev: &Event) -> Option<Tag> {
-        ev.tags
-            .iter()
-            .filter(|tag| matches!(tag, Tag::Amount { .. }))
-            .last()
-            .cloned()
-    }
-
-    fn find_reply_tag(&self) -> Option<Tag> {
-        self.event
-            .tags
-            .iter()
-            .filter(|tag| matches!(tag, Tag::Event { .. }))
-            .last()
-            .cloned()
+        self.reposts
     }
 
     pub fn zap_amount(&self) -> u64 {
-        self.zap_receipts.iter().fold(0, |acc, ev| {
-            if let Some(Tag::Amount { millisats, .. }) = self.find_amount(ev) {
-                acc + millisats
-            } else {
-                acc
-            }
-        })
+        self.zaps.iter().map(|zap| zap.millisats).sum()
     }
 
     fn content_width(&self) -> u16 {
@@ -127,28 +428,249 @@ impl TextNote {
             .height
             .saturating_sub(self.padding.top + self.padding.bottom + 5)
     }
+
+    fn content(&self) -> Text<'static> {
+        if let Some(ref reason) = self.content_warning {
+            let reason = if reason.is_empty() {
+                i18n::tr("CW")
+            } else {
+                i18n::tr_args("CW: {reason}", &[("reason", reason)])
+            };
+            return Text::styled(
+                i18n::tr_args("{warning} — press x to reveal", &[("warning", &reason)]),
+                Style::default().fg(Color::Yellow).italic(),
+            );
+        }
+
+        let wrapped = match self.wrapped {
+            Some(ref wrapped) => wrapped.to_string(),
+            None => self.wrap_content(),
+        };
+        let (preview, hidden) = text::preview_text(&wrapped, self.max_lines);
+        let hint_style = Style::default().fg(Color::Gray).italic();
+        let hint = match (hidden, self.expanded) {
+            (0, _) => None,
+            (_, true) => Some(Text::styled(i18n::tr("▴ press m to collapse"), hint_style)),
+            (hidden, false) => Some(Text::styled(
+                i18n::plural(
+                    "… {count, plural, one {# more line} other {# more lines}}: press m to expand",
+                    hidden as u64,
+                ),
+                hint_style,
+            )),
+        };
+
+        let content_text = if self.expanded { &wrapped } else { &preview };
+        let height = (self.content_height() as usize).saturating_sub(usize::from(hint.is_some()));
+        let mut content = self.file_text();
+        content.extend(Text::from(text::truncate_text(content_text, height)));
+        content.extend(hint.unwrap_or_default());
+        content.extend(self.translation_text());
+        for (url, preview) in &self.link_previews {
+            content.extend(self.link_preview_text(url, preview));
+        }
+        content
+    }
+
+    /// Renders the card of the shared file: its name, its type and size, and its URL.
+    fn file_text(&self) -> Text<'static> {
+        let Some(ref file) = self.file else {
+            return Text::default();
+        };
+
+        let width = self.content_width() as usize;
+        let icon = if file.video { "🎞" } else { "📄" };
+        let mut text = Text::styled(
+            text::truncate_width(&format!("{icon} {}", file.name), width),
+            Style::default().bold(),
+        );
+        let details: Vec<String> = [
+            file.mime_type.clone(),
+            file.size.map(|size| stats::format_bytes(size as usize)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !details.is_empty() {
+            text.extend(Text::styled(
+                text::truncate_width(&details.join(" · "), width),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        text.extend(Text::styled(
+            text::truncate_width(&file.url, width),
+            Style::default().fg(Color::LightBlue).underlined(),
+        ));
+        text
+    }
+
+    /// Renders the card of a link in up to three lines: the domain, the title and the description.
+    fn link_preview_text(&self, url: &str, preview: &PreviewState) -> Text<'static> {
+        let width = self.content_width() as usize;
+        let header_style = Style::default().fg(Color::Gray).italic();
+        let header = |suffix: String| {
+            let header = format!("🔗 {} {suffix}", link_preview::domain(url));
+            Text::styled(text::truncate_width(header.trim_end(), width), header_style)
+        };
+        let preview = match preview {
+            PreviewState::Loading => return header(i18n::tr("loading…")),
+            PreviewState::Failed(error) => return header(format!("({error})")),
+            PreviewState::Ready(preview) => preview,
+        };
+
+        let mut text = header(String::new());
+        text.extend(Text::styled(
+            text::truncate_width(&format!("▏{}", preview.title), width),
+            Style::default().fg(Color::LightCyan).bold(),
+        ));
+        if let Some(ref description) = preview.description {
+            text.extend(Text::styled(
+                text::truncate_width(&format!("▏{description}"), width),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        text
+    }
+
+    fn translation_text(&self) -> Text<'static> {
+        let header_style = Style::default().fg(Color::Gray).italic();
+        let Some(ref translation) = self.translation else {
+            return if self.translating {
+                Text::styled(i18n::tr("⇄ translating…"), header_style)
+            } else {
+                Text::default()
+            };
+        };
+
+        let header = match translation.source {
+            Some(ref language) => {
+                i18n::tr_args("⇄ translated from {language}", &[("language", language)])
+            }
+            None => i18n::tr("⇄ translated"),
+        };
+        let mut text = Text::styled(header, header_style);
+        text.extend(Text::styled(
+            text::wrap_text(&translation.text, self.content_width() as usize),
+            Style::default().fg(Color::LightCyan),
+        ));
+        text
+    }
+
+    fn separator(&self) -> Text<'static> {
+        Text::styled(
+            "─".repeat(self.content_width() as usize),
+            Style::default().fg(Color::Gray),
+        )
+    }
+
+    fn render_text(&self, text: Text, area: Rect, buf: &mut Buffer) {
+        if let Some(height) = self.top_truncated_height {
+            let len = text.lines.len();
+            let index = len.saturating_sub(height);
+            let lines: Vec<Line> = Vec::from(&text.lines[index..]);
+            Paragraph::new(lines).render(area, buf);
+            return;
+        }
+
+        Paragraph::new(text).render(area, buf);
+    }
+
+    fn render_collapsed(self, area: Rect, buf: &mut Buffer) {
+        let name = self
+            .display_name()
+            .or(self.name())
+            .unwrap_or_else(|| PublicKey::new(self.event.pubkey).shortened());
+        let style = if self.highlight {
+            Style::default().italic().fg(Color::Gray).reversed()
+        } else {
+            Style::default().italic().fg(Color::Gray)
+        };
+        let summary = match self.filtered {
+            Some(score) => i18n::tr_args(
+                "▸ Filtered a note from {name} (score {score})",
+                &[("name", &name), ("score", &score)],
+            ),
+            None if self.muted => {
+                i18n::tr_args("▸ Muted a note from {name} for now", &[("name", &name)])
+            }
+            None => i18n::tr_args("▸ Collapsed a reply from {name}", &[("name", &name)]),
+        };
+        let mut text = self.gap();
+        text.extend(Text::styled(summary, style));
+        text.extend(self.separator());
+        self.render_text(text, area, buf);
+    }
+
+    /// Renders the author and the time on a line and the content below, without the stats.
+    fn render_chat(self, area: Rect, buf: &mut Buffer) {
+        let name = self
+            .display_name()
+            .or(self.name())
+            .unwrap_or_else(|| PublicKey::new(self.event.pubkey).shortened());
+        let name_style = match (self.highlight, self.author_colors) {
+            (true, _) => Style::default().bold().reversed(),
+            (false, true) => Style::default().bold().fg(self.author_color()),
+            (false, false) => Style::default().bold(),
+        };
+        let mut header = vec![
+            Span::styled(name.clone(), name_style),
+            Span::styled(
+                format!(" {}", self.created_at()),
+                Style::default().fg(Color::Gray),
+            ),
+        ];
+        header.extend(self.delivery_span());
+        header.extend(self.expiration_span());
+        if let Some(avatar) = self.avatar(&name) {
+            header.splice(0..0, [avatar, Span::raw(" ")]);
+        }
+
+        let mut text = self.gap();
+        text.extend::<Text>(Line::from(header).into());
+        text.extend(self.content());
+        self.render_text(text, area, buf);
+    }
 }
 
 impl Widget for TextNote {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut text = Text::default();
+        if self.is_collapsed() {
+            return self.render_collapsed(area, buf);
+        }
+        if self.chat {
+            return self.render_chat(area, buf);
+        }
 
-        if let Some(Tag::Event { event_id, .. }) = self.find_reply_tag() {
-            if let Ok(note1) = event_id.to_bech32() {
-                text.extend(Text::styled(
-                    format!("Reply to {}", note1),
-                    Style::default().fg(Color::Cyan),
-                ));
-            }
+        let mut text = self.gap();
+
+        if let Some(reposted_by) = self.reposted_by_line() {
+            text.extend(Text::styled(
+                reposted_by,
+                Style::default().fg(Color::LightGreen),
+            ));
+        }
+
+        if let Some(ref name) = self.reply_to {
+            text.extend(Text::styled(
+                i18n::tr_args("↳ replying to {name}", &[("name", name)]),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+
+        for annotation in &self.annotations {
+            text.extend(Text::styled(
+                format!("◆ {annotation}"),
+                Style::default().fg(Color::LightMagenta),
+            ));
         }
 
         let display_name = self.display_name();
         let name = self.name();
 
-        let display_name_style = if self.highlight {
-            Style::default().bold().reversed()
-        } else {
-            Style::default().bold()
+        let display_name_style = match (self.highlight, self.author_colors) {
+            (true, _) => Style::default().bold().reversed(),
+            (false, true) => Style::default().bold().fg(self.author_color()),
+            (false, false) => Style::default().bold(),
         };
 
         let name_style = if display_name.is_none() && self.highlight {
@@ -157,85 +679,88 @@ impl Widget for TextNote {
             Style::default().italic().fg(Color::Gray)
         };
 
-        let name_line: Text = match (display_name, name) {
-            (Some(display_name), Some(name)) => Line::from(vec![
+        let mut name_line = match (display_name, name) {
+            (Some(display_name), Some(name)) => vec![
                 Span::styled(display_name, display_name_style),
                 Span::raw(" "),
                 Span::styled(name, name_style),
-            ])
-            .into(),
-            (Some(display_name), _) => Span::styled(display_name, display_name_style).into(),
-            (_, Some(name)) => Span::styled(name, name_style).into(),
-            (_, _) => Text::styled(
+            ],
+            (Some(display_name), _) => vec![Span::styled(display_name, display_name_style)],
+            (_, Some(name)) => vec![Span::styled(name, name_style)],
+            (_, _) => vec![Span::styled(
                 PublicKey::new(self.event.pubkey).shortened(),
                 display_name_style,
-            ),
+            )],
         };
-        text.extend::<Text>(name_line);
-
-        let content: Text = ShrinkText::new(
-            self.event.content.clone(),
-            self.content_width() as usize,
-            self.content_height() as usize,
-        )
-        .into();
-        text.extend(content);
+        if let Some(avatar) = self.avatar(&name_line[0].content) {
+            name_line.splice(0..0, [avatar, Span::raw(" ")]);
+        }
+        text.extend::<Text>(Line::from(name_line).into());
+        text.extend(self.content());
+        if let Some(goal) = self.goal_line() {
+            text.extend::<Text>(goal.into());
+        }
 
-        text.extend(Text::styled(
+        let mut created_at = vec![Span::styled(
             self.created_at(),
             Style::default().fg(Color::Gray),
-        ));
+        )];
+        created_at.extend(self.delivery_span());
+        created_at.extend(self.expiration_span());
+        text.extend::<Text>(Line::from(created_at).into());
         let line = Line::from(vec![
             Span::styled(
-                format!("{}Likes", self.reactions_count().separate_with_commas()),
+                i18n::plural(
+                    "{count, plural, one {#Like} other {#Likes}}",
+                    self.reactions_count() as u64,
+                ),
                 Style::default().fg(Color::LightRed),
             ),
             Span::raw(" "),
             Span::styled(
-                format!("{}Reposts", self.reposts_count().separate_with_commas()),
+                i18n::plural(
+                    "{count, plural, one {#Repost} other {#Reposts}}",
+                    self.reposts_count() as u64,
+                ),
                 Style::default().fg(Color::LightGreen),
             ),
             Span::raw(" "),
             Span::styled(
-                format!("{}Sats", (self.zap_amount() / 1000).separate_with_commas()),
+                format!("⚡ {}", compact_number(self.zap_amount() / 1000)),
                 Style::default().fg(Color::LightYellow),
             ),
         ]);
         text.extend::<Text>(line.into());
 
-        text.extend(Text::styled(
-            "─".repeat(self.content_width() as usize),
-            Style::default().fg(Color::Gray),
-        ));
-
-        if let Some(height) = self.top_truncated_height {
-            let len = text.lines.len();
-            let index = len.saturating_sub(height);
-            let lines: Vec<Line> = Vec::from(&text.lines[index..]);
-            Paragraph::new(lines).render(area, buf);
-            return;
-        }
-
-        Paragraph::new(text).render(area, buf);
+        text.extend(self.separator());
+        self.render_text(text, area, buf);
     }
 }
 
 impl Listable for TextNote {
     fn height(&self) -> usize {
-        let content: Text = ShrinkText::new(
-            self.event.content.clone(),
-            self.content_width() as usize,
-            self.content_height() as usize,
-        )
-        .into();
+        let gap = usize::from(self.gap_above) + usize::from(self.last_visit_above);
+        if self.is_collapsed() {
+            // NOTE: 2 = annotation + separator
+            return gap + 2;
+        }
+
+        let content = self.content();
+        if self.chat {
+            // NOTE: 1 = name and created_at
+            return gap + 1 + content.height();
+        }
 
-        if self.find_reply_tag().is_some() {
+        let annotations = self.annotations.len()
+            + usize::from(!self.reposted_by.is_empty())
+            + usize::from(self.goal.is_some());
+        if self.reply_to.is_some() {
             // NOTE: 5 = annotation + name + created_at + stats + separator
-            return 5 + content.height();
+            return gap + 5 + annotations + content.height();
         }
 
         // NOTE: 4 = name + created_at + stats + separator
-        4 + content.height()
+        gap + 4 + annotations + content.height()
     }
 
     fn highlight(self) -> Self {
@@ -250,12 +775,13 @@ impl Listable for TextNote {
 mod tests {
     use std::str::FromStr;
 
+    use chrono::{Local, TimeZone};
     use nostr_sdk::JsonUtil;
     use pretty_assertions::assert_eq;
     use rstest::*;
 
     use super::*;
-    use crate::nostr::Profile;
+    use crate::{link_preview::LinkPreview, nostr::Profile};
 
     #[fixture]
     fn event() -> Event {
@@ -308,15 +834,7 @@ mod tests {
             )
         });
 
-        let note = TextNote::new(
-            event,
-            profile,
-            HashSet::new(),
-            HashSet::new(),
-            HashSet::new(),
-            area,
-            padding,
-        );
+        let note = TextNote::new(event, profile, 0, 0, vec![], area, padding);
         assert_eq!(note.display_name(), expected);
     }
 
@@ -345,29 +863,203 @@ mod tests {
             )
         });
 
-        let note = TextNote::new(
-            event,
-            profile,
-            HashSet::new(),
-            HashSet::new(),
-            HashSet::new(),
-            area,
-            padding,
-        );
+        let note = TextNote::new(event, profile, 0, 0, vec![], area, padding);
         assert_eq!(note.name(), expected);
     }
 
+    #[rstest]
+    fn test_avatar(event: Event, area: Rect, padding: Padding) {
+        let note = TextNote::new(event, None, 0, 0, vec![], area, padding);
+        assert_eq!(note.clone().avatar("@foo"), None);
+
+        let color = avatar::color(&note.event.pubkey);
+        let note = note.author_style(true, true);
+        assert_eq!(
+            note.avatar("@foo"),
+            Some(Span::styled(
+                " F ",
+                Style::default().fg(Color::Black).bg(color).bold()
+            ))
+        );
+
+        let note = note.author_style(false, true);
+        assert_eq!(
+            note.avatar("🤙"),
+            Some(Span::styled(" ? ", Style::default().reversed().bold()))
+        );
+    }
+
     #[rstest]
     fn test_created_at(event: Event) {
+        // 2024-01-01T15:42:47+09:00, shown in the local time zone of the machine
+        let expected = Local
+            .timestamp_opt(event.created_at.as_i64(), 0)
+            .unwrap()
+            .format("%T")
+            .to_string();
         let note = TextNote::new(
             event,
             None,
-            HashSet::new(),
-            HashSet::new(),
-            HashSet::new(),
+            0,
+            0,
+            vec![],
             Rect::new(0, 0, 0, 0),
             Padding::new(0, 0, 0, 0),
         );
-        assert_eq!(note.created_at(), "15:42:47");
+        assert_eq!(note.created_at(), expected);
+    }
+
+    #[rstest]
+    fn test_zap_amount(event: Event, area: Rect, padding: Padding) {
+        let keys = Keys::generate();
+        let zaps = [
+            vec![Tag::Bolt11(String::from(
+                "lnbc21n1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq",
+            ))],
+            vec![Tag::Amount {
+                millisats: 1_000,
+                bolt11: None,
+            }],
+            vec![],
+        ]
+        .into_iter()
+        .map(|tags| {
+            Zap::from_receipt(
+                &EventBuilder::new(Kind::ZapReceipt, "", tags)
+                    .to_event(&keys)
+                    .unwrap(),
+            )
+        })
+        .collect();
+
+        let note = TextNote::new(event, None, 0, 0, zaps, area, padding);
+        assert_eq!(note.zap_amount(), 3_100);
+    }
+
+    #[rstest]
+    fn test_height_with_content_warning(event: Event, padding: Padding) {
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding);
+        // NOTE: 5 = name + warning + created_at + stats + separator
+        assert_eq!(
+            note.content_warning(Some(String::from("spoiler"))).height(),
+            5
+        );
+    }
+
+    #[rstest]
+    #[case(0, false, 8)]
+    #[case(3, false, 4)]
+    #[case(3, true, 9)]
+    #[case(10, false, 8)]
+    fn test_height_with_preview(
+        padding: Padding,
+        #[case] max_lines: usize,
+        #[case] expanded: bool,
+        #[case] content_height: usize,
+    ) {
+        let event = EventBuilder::text_note("1\n2\n3\n4\n5\n6\n7\n8", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding);
+        // NOTE: 4 = name + created_at + stats + separator
+        assert_eq!(
+            note.preview(max_lines, expanded).height(),
+            4 + content_height
+        );
+    }
+
+    #[rstest]
+    fn test_height_with_layout_cache(event: Event, padding: Padding) {
+        let note = || {
+            TextNote::new(
+                event.clone(),
+                None,
+                0,
+                0,
+                vec![],
+                Rect::new(0, 0, 10, 20),
+                padding,
+            )
+        };
+        let cache = LayoutCache::default();
+        // NOTE: 4 = name + created_at + stats + separator
+        assert_eq!(note().layout_cache(&cache).height(), 4 + 2);
+        assert_eq!(cache.len(), 1);
+
+        // The layout is taken from the cache instead of wrapping the content again
+        cache.clear();
+        cache.wrapped(event.id, 10, || String::from("初\nforce\npush"));
+        assert_eq!(note().layout_cache(&cache).height(), 4 + 3);
+        assert_eq!(note().height(), 4 + 2);
+    }
+
+    #[rstest]
+    fn test_height_with_gap(event: Event, padding: Padding) {
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding);
+        // NOTE: 6 = gap + name + content + created_at + stats + separator
+        assert_eq!(note.clone().gap_above(true).height(), 6);
+        assert_eq!(note.clone().collapsed(true).gap_above(true).height(), 3);
+        // NOTE: 7 = last visit + gap + name + content + created_at + stats + separator
+        assert_eq!(note.last_visit_above(true).gap_above(true).height(), 7);
+    }
+
+    #[rstest]
+    fn test_height_with_goal(event: Event, padding: Padding) {
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding);
+        let goal = ZapGoal {
+            description: String::from("New relay"),
+            millisats: 100_000,
+            closed_at: None,
+        };
+        // NOTE: 6 = name + content + goal + created_at + stats + separator
+        assert_eq!(note.goal(Some((goal, vec![]))).height(), 6);
+    }
+
+    #[rstest]
+    #[case(PreviewState::Loading, 1)]
+    #[case(PreviewState::Failed(String::from("timed out")), 1)]
+    #[case(PreviewState::Ready(LinkPreview { description: None, ..preview() }), 2)]
+    #[case(PreviewState::Ready(preview()), 3)]
+    fn test_height_with_link_preview(
+        event: Event,
+        padding: Padding,
+        #[case] preview: PreviewState,
+        #[case] expected: usize,
+    ) {
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding)
+            .link_previews(vec![(String::from("https://example.com"), preview)]);
+        // NOTE: 5 = name + content + created_at + stats + separator, and the card
+        assert_eq!(note.height(), 5 + expected);
+    }
+
+    fn preview() -> LinkPreview {
+        LinkPreview {
+            url: String::from("https://example.com"),
+            domain: String::from("example.com"),
+            title: String::from("Example"),
+            description: Some(String::from("An example page")),
+        }
+    }
+
+    #[rstest]
+    #[case(vec![], None)]
+    #[case(vec!["alice"], Some("⟳ reposted by alice"))]
+    #[case(vec!["alice", "bob"], Some("⟳ reposted by alice, bob"))]
+    #[case(vec!["alice", "bob", "carol"], Some("⟳ reposted by alice, bob and 1 other"))]
+    #[case(
+        vec!["alice", "bob", "carol", "dave"],
+        Some("⟳ reposted by alice, bob and 2 others")
+    )]
+    fn test_reposted_by(
+        event: Event,
+        padding: Padding,
+        #[case] names: Vec<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let note = TextNote::new(event, None, 0, 0, vec![], Rect::new(0, 0, 40, 20), padding)
+            .reposted_by(names.into_iter().map(String::from).collect());
+        assert_eq!(note.reposted_by_line().as_deref(), expected);
+        // NOTE: 5 = name + content + created_at + stats + separator, and the reposters
+        assert_eq!(note.height(), 5 + usize::from(expected.is_some()));
     }
 }
@@ -0,0 +1,40 @@
+//! Tests of the helpers of `components::testing` as other crates use them.
+//!
+//! ```sh
+//! cargo test --features testing --test testing
+//! ```
+
+use nostui::components::{testing, Component};
+use proptest::prelude::*;
+
+fn contains(lines: &[String], text: &str) -> bool {
+    lines.iter().any(|line| line.contains(text))
+}
+
+#[test]
+fn test_render_timeline() {
+    let mut home = testing::timeline(&[testing::note("gm nostr", 1_700_000_000)]);
+    assert!(contains(&testing::render(&mut home, 80, 24), "gm nostr"));
+}
+
+#[test]
+fn test_render_composer() {
+    let mut home = testing::composing(&[], "hello");
+    assert!(contains(&testing::render(&mut home, 80, 24), "hello"));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn test_invariants(
+        actions in proptest::collection::vec(testing::action(&testing::Notes::new(6)), 1..20)
+    ) {
+        let mut home = testing::timeline(&[]);
+        for action in actions {
+            home.update(action).unwrap();
+            testing::render(&mut home, 60, 20);
+            prop_assert_eq!(home.check_invariants(), Ok(()));
+        }
+    }
+}